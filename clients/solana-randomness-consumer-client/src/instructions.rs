@@ -0,0 +1,312 @@
+use crate::*;
+use solana_program::instruction::AccountMeta;
+use solana_program::{system_program, sysvar};
+use solana_randomness_service_lite::{
+    anchor_sighash, RANDOMNESS_SERVICE_REWARD_MINT, RANDOMNESS_SERVICE_STATE,
+};
+
+/// `request_randomness`: requests 8 bytes of randomness, settled through
+/// `consume_randomness`. `randomness_request` must be a fresh keypair that signs the
+/// transaction.
+pub fn request_randomness(payer: &Pubkey, randomness_request: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: randomness_accounts(payer, randomness_request),
+        data: anchor_sighash("request_randomness").to_vec(),
+    }
+}
+
+/// `consume_randomness`: the callback for [`request_randomness`]. Only the randomness
+/// service can sign it; built off-chain for tests.
+pub fn consume_randomness(randomness_request: &Pubkey, result: &[u8]) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+            AccountMeta::new_readonly(*randomness_request, false),
+        ],
+        data: callback_data("consume_randomness", result),
+    }
+}
+
+/// `initialize_lootbox`: creates the lootbox for a new key mint and prize mint. Both mints
+/// must be fresh keypairs that sign the transaction; the payer becomes the lootbox authority.
+pub fn initialize_lootbox(payer: &Pubkey, key_mint: &Pubkey, prize_mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(lootbox_address(key_mint).0, false),
+            AccountMeta::new(*key_mint, true),
+            AccountMeta::new(*prize_mint, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        data: anchor_sighash("initialize_lootbox").to_vec(),
+    }
+}
+
+/// `mint_lootbox_keys`: mints `amount` keys to a key mint token account. Signed by the
+/// lootbox authority.
+pub fn mint_lootbox_keys(
+    authority: &Pubkey,
+    key_mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut data = anchor_sighash("mint_lootbox_keys").to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(lootbox_address(key_mint).0, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*key_mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// The accounts of an `open_box` instruction other than the payer and the randomness
+/// request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenBoxAccounts {
+    pub key_mint: Pubkey,
+    pub prize_mint: Pubkey,
+    /// The payer's key mint token account. One key is burned from it.
+    pub user_key_account: Pubkey,
+    /// The payer's prize mint token account. The callback mints the prize into it.
+    pub user_prize_account: Pubkey,
+}
+
+/// `open_box`: burns one key and requests the randomness that decides the prize.
+/// `randomness_request` must be a fresh keypair that signs the transaction.
+pub fn open_box(
+    payer: &Pubkey,
+    randomness_request: &Pubkey,
+    accounts: &OpenBoxAccounts,
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(lootbox_address(&accounts.key_mint).0, false),
+        AccountMeta::new(accounts.key_mint, false),
+        AccountMeta::new(accounts.user_key_account, false),
+        AccountMeta::new_readonly(accounts.prize_mint, false),
+        AccountMeta::new_readonly(accounts.user_prize_account, false),
+        AccountMeta::new(opening_address(randomness_request).0, false),
+    ];
+    metas.extend(randomness_accounts(payer, randomness_request));
+
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: anchor_sighash("open_box").to_vec(),
+    }
+}
+
+/// `consume_lootbox`: the callback for [`open_box`]. Only the randomness service can sign
+/// it; built off-chain for tests.
+pub fn consume_lootbox(
+    randomness_request: &Pubkey,
+    accounts: &OpenBoxAccounts,
+    result: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+            AccountMeta::new_readonly(*randomness_request, false),
+            AccountMeta::new_readonly(lootbox_address(&accounts.key_mint).0, false),
+            AccountMeta::new(opening_address(randomness_request).0, false),
+            AccountMeta::new(accounts.prize_mint, false),
+            AccountMeta::new(accounts.user_prize_account, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: callback_data("consume_lootbox", result),
+    }
+}
+
+/// `initialize_result_feed`: creates the payer's result feed.
+pub fn initialize_result_feed(payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(result_feed_address(payer).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: anchor_sighash("initialize_result_feed").to_vec(),
+    }
+}
+
+/// `request_feed_randomness`: requests `num_bytes` of randomness for the payer's result feed.
+/// `randomness_request` must be a fresh keypair that signs the transaction.
+pub fn request_feed_randomness(
+    payer: &Pubkey,
+    randomness_request: &Pubkey,
+    num_bytes: u8,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(
+        result_feed_address(payer).0,
+        false,
+    )];
+    accounts.extend(randomness_accounts(payer, randomness_request));
+    let mut data = anchor_sighash("request_feed_randomness").to_vec();
+    data.push(num_bytes);
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
+
+/// `consume_feed_randomness`: the callback for [`request_feed_randomness`]. Only the
+/// randomness service can sign it; built off-chain for tests.
+pub fn consume_feed_randomness(
+    randomness_request: &Pubkey,
+    user: &Pubkey,
+    result: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+            AccountMeta::new_readonly(*randomness_request, false),
+            AccountMeta::new(result_feed_address(user).0, false),
+        ],
+        data: callback_data("consume_feed_randomness", result),
+    }
+}
+
+/// `initialize_cached_randomness`: creates the payer's randomness cache, refreshed with
+/// `num_bytes` of randomness.
+pub fn initialize_cached_randomness(payer: &Pubkey, num_bytes: u8) -> Instruction {
+    let mut data = anchor_sighash("initialize_cached_randomness").to_vec();
+    data.push(num_bytes);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(cached_randomness_address(payer).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// `get_or_request`: serves the authority's cached randomness if it is at most
+/// `max_age_slots` old, otherwise requests a refresh paid by `payer`. `randomness_request`
+/// must be a fresh keypair that signs the transaction either way.
+pub fn get_or_request(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    randomness_request: &Pubkey,
+    max_age_slots: u64,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(
+        cached_randomness_address(authority).0,
+        false,
+    )];
+    accounts.extend(randomness_accounts(payer, randomness_request));
+    let mut data = anchor_sighash("get_or_request").to_vec();
+    data.extend_from_slice(&max_age_slots.to_le_bytes());
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
+
+/// `consume_cached_randomness`: the callback for the refresh requested by
+/// [`get_or_request`]. Only the randomness service can sign it; built off-chain for tests.
+pub fn consume_cached_randomness(
+    randomness_request: &Pubkey,
+    authority: &Pubkey,
+    result: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+            AccountMeta::new_readonly(*randomness_request, false),
+            AccountMeta::new(cached_randomness_address(authority).0, false),
+        ],
+        data: callback_data("consume_cached_randomness", result),
+    }
+}
+
+/// `initialize_self_test`: creates the program's self-test result. Signed by the program's
+/// upgrade authority, which pays for it.
+pub fn initialize_self_test(admin: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(self_test_result_address().0, false),
+            AccountMeta::new_readonly(program_data_address(), false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: anchor_sighash("initialize_self_test").to_vec(),
+    }
+}
+
+/// `self_test`: requests 1 byte of randomness, settled through `consume_self_test`. Signed
+/// by the program's upgrade authority, which pays for the request. `randomness_request` must
+/// be a fresh keypair that signs the transaction.
+pub fn self_test(admin: &Pubkey, randomness_request: &Pubkey) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(self_test_result_address().0, false),
+        AccountMeta::new_readonly(program_data_address(), false),
+    ];
+    accounts.extend(randomness_accounts(admin, randomness_request));
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: anchor_sighash("self_test").to_vec(),
+    }
+}
+
+/// `consume_self_test`: the callback for [`self_test`]. Only the randomness service can sign
+/// it; built off-chain for tests.
+pub fn consume_self_test(randomness_request: &Pubkey, result: &[u8]) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+            AccountMeta::new_readonly(*randomness_request, false),
+            AccountMeta::new(self_test_result_address().0, false),
+        ],
+        data: callback_data("consume_self_test", result),
+    }
+}
+
+/// The accounts the example program forwards to the randomness service's
+/// `simple_randomness_v1`, in the order of its accounts structs.
+fn randomness_accounts(payer: &Pubkey, randomness_request: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(solana_randomness_service_lite::ID, false),
+        AccountMeta::new(*randomness_request, true),
+        AccountMeta::new(randomness_escrow_address(randomness_request), false),
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, false),
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_REWARD_MINT, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+    ]
+}
+
+/// A callback's discriminator followed by its borsh `Vec<u8>` argument.
+fn callback_data(name: &str, result: &[u8]) -> Vec<u8> {
+    let mut data = anchor_sighash(name).to_vec();
+    data.extend_from_slice(&(result.len() as u32).to_le_bytes());
+    data.extend_from_slice(result);
+    data
+}
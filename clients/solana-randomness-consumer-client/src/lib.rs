@@ -0,0 +1,44 @@
+//! Instruction builders and PDA derivations for the `solana-randomness-consumer` example
+//! program, for bots and off-chain tests that should not depend on the Anchor program crate.
+//!
+//! Every builder takes pubkeys only and returns a ready to sign [`Instruction`]. The account
+//! order and instruction data are checked against the Anchor generated client in
+//! `tests/anchor_parity.rs`, so a consumer exposing its own lite client can copy the pattern.
+//!
+//! With the `rpc` feature, [`run_self_test`] runs the program's post-deploy self-test against
+//! a cluster and waits for its outcome.
+//!
+//! ```
+//! use solana_program::pubkey::Pubkey;
+//! use solana_randomness_consumer_client::*;
+//!
+//! let payer = Pubkey::new_unique();
+//! let request = Pubkey::new_unique(); // a fresh keypair, signs the transaction
+//! let ix = request_randomness(&payer, &request);
+//! assert_eq!(ix.program_id, ID);
+//! ```
+
+pub use solana_program::instruction::Instruction;
+use solana_program::pubkey;
+pub use solana_program::pubkey::Pubkey;
+
+mod pda;
+pub use pda::*;
+
+mod instructions;
+pub use instructions::*;
+
+mod self_test;
+pub use self_test::*;
+
+#[cfg(feature = "rpc")]
+mod rpc;
+#[cfg(feature = "rpc")]
+pub use rpc::*;
+
+/// The example program ID.
+pub const ID: Pubkey = pubkey!("39hMZgeiesFXMRFt8svuKVsdCW5geiYueSRx7dxhXN4f");
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
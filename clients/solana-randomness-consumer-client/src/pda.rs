@@ -0,0 +1,63 @@
+use crate::*;
+use solana_program::bpf_loader_upgradeable;
+use solana_randomness_service_lite::{
+    define_pda, derive_escrow_pubkey, RANDOMNESS_SERVICE_REWARD_MINT,
+};
+
+/// The seed prefix of the lootbox PDA, `[LOOTBOX_SEED, key_mint]`.
+pub const LOOTBOX_SEED: &[u8] = b"LOOTBOX";
+
+/// The seed prefix of the lootbox opening PDA, `[OPENING_SEED, randomness_request]`.
+pub const OPENING_SEED: &[u8] = b"OPENING";
+
+/// The seed prefix of the result feed PDA, `[RESULT_FEED_SEED, user]`.
+pub const RESULT_FEED_SEED: &[u8] = b"RESULT_FEED";
+
+/// The seed prefix of the randomness cache PDA, `[CACHED_RANDOMNESS_SEED, authority]`.
+pub const CACHED_RANDOMNESS_SEED: &[u8] = b"CACHED_RANDOMNESS";
+
+/// The seed of the program's self-test result PDA, `[SELF_TEST_SEED]`.
+pub const SELF_TEST_SEED: &[u8] = b"SELF_TEST";
+
+define_pda!(LootboxPda, [LOOTBOX_SEED, key_mint: Pubkey]);
+define_pda!(OpeningPda, [OPENING_SEED, randomness_request: Pubkey]);
+define_pda!(ResultFeedPda, [RESULT_FEED_SEED, user: Pubkey]);
+define_pda!(CachedRandomnessPda, [CACHED_RANDOMNESS_SEED, authority: Pubkey]);
+
+/// The lootbox for a key mint and its bump.
+pub fn lootbox_address(key_mint: &Pubkey) -> (Pubkey, u8) {
+    LootboxPda::derive(&ID, *key_mint)
+}
+
+/// The opening recorded for a randomness request and its bump.
+pub fn opening_address(randomness_request: &Pubkey) -> (Pubkey, u8) {
+    OpeningPda::derive(&ID, *randomness_request)
+}
+
+/// The user's result feed and its bump. Read it with the lite crate's `ResultFeed`.
+pub fn result_feed_address(user: &Pubkey) -> (Pubkey, u8) {
+    ResultFeedPda::derive(&ID, *user)
+}
+
+/// The authority's randomness cache and its bump. Read it with the lite crate's
+/// `CachedRandomness`.
+pub fn cached_randomness_address(authority: &Pubkey) -> (Pubkey, u8) {
+    CachedRandomnessPda::derive(&ID, *authority)
+}
+
+/// The program's self-test result and its bump. Read it with [`SelfTestResult`].
+pub fn self_test_result_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SELF_TEST_SEED], &ID)
+}
+
+/// The program's ProgramData account, kept by the upgradeable loader. Its upgrade authority
+/// is the admin of the self-test.
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// The escrow token account the randomness service creates for a request: the request's
+/// associated token account for wrapped SOL.
+pub fn randomness_escrow_address(randomness_request: &Pubkey) -> Pubkey {
+    derive_escrow_pubkey(randomness_request, &RANDOMNESS_SERVICE_REWARD_MINT)
+}
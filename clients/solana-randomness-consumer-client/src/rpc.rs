@@ -0,0 +1,84 @@
+use crate::*;
+use solana_randomness_service_lite::client::{LiteClientError, RandomnessRpc, RequestStatus};
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::time::Duration;
+
+/// How often [`run_self_test`] reads the self-test result while it waits.
+pub const SELF_TEST_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How a self-test started by [`run_self_test`] ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelfTestOutcome {
+    /// The oracle invoked the callback, which recorded the byte it received.
+    Passed { settled_slot: u64, result: u8 },
+    /// The oracle completed the request with an error instead of invoking the callback.
+    Failed { error_message: String },
+    /// Neither happened before the timeout.
+    TimedOut,
+}
+
+/// The self-test sent by [`run_self_test`] and how it ended.
+#[derive(Clone, Debug)]
+pub struct SelfTestReport {
+    pub request: Pubkey,
+    /// The `self_test` transaction.
+    pub signature: Signature,
+    pub outcome: SelfTestOutcome,
+}
+
+/// Sends `self_test` signed by the program's upgrade authority and waits up to `timeout` for
+/// the outcome, reading the self-test result and the request every
+/// [`SELF_TEST_POLL_INTERVAL`].
+///
+/// The self-test result must have been created with [`initialize_self_test`]. Fails if the
+/// transaction is rejected, e.g. because `admin` is not the upgrade authority or the previous
+/// self-test is still pending.
+pub async fn run_self_test<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    admin: &dyn Signer,
+    timeout: Duration,
+) -> Result<SelfTestReport, LiteClientError> {
+    let request = Keypair::new();
+    let ix = self_test(&admin.pubkey(), &request.pubkey());
+    let mut transaction = Transaction::new_with_payer(&[ix], Some(&admin.pubkey()));
+    transaction.try_sign(
+        &[admin, &request as &dyn Signer],
+        rpc.get_latest_blockhash().await?,
+    )?;
+    let signature = rpc.send_and_confirm_transaction(&transaction).await?;
+
+    let request = request.pubkey();
+    let outcome = tokio::time::timeout(timeout, await_outcome(rpc, &request)).await;
+    Ok(SelfTestReport {
+        request,
+        signature,
+        outcome: outcome.unwrap_or(Ok(SelfTestOutcome::TimedOut))?,
+    })
+}
+
+async fn await_outcome<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+) -> Result<SelfTestOutcome, LiteClientError> {
+    let (address, _) = self_test_result_address();
+    let mut interval = tokio::time::interval(SELF_TEST_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Some(account) = rpc.get_account(&address).await? {
+            let result = SelfTestResult::try_deserialize(&mut &account.data[..])?;
+            if result.passed(request) {
+                return Ok(SelfTestOutcome::Passed {
+                    settled_slot: result.settled_slot,
+                    result: result.result,
+                });
+            }
+        }
+        let account = rpc.get_account(request).await?;
+        if let RequestStatus::Failed(failed) = RequestStatus::from_account(account.as_ref())? {
+            return Ok(SelfTestOutcome::Failed {
+                error_message: failed.error_message,
+            });
+        }
+    }
+}
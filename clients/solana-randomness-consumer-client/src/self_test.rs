@@ -0,0 +1,47 @@
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+
+/// The program's `SelfTestResult` account: the outcome of the latest self-test, polled by
+/// operators after a deploy. Lives at [`self_test_result_address`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct SelfTestResult {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The upgrade authority that started the latest self-test.
+    pub admin: Pubkey,
+    /// The latest self-test's request.
+    pub request: Pubkey,
+    /// The slot the latest self-test was requested at.
+    pub request_slot: u64,
+    /// Whether the latest self-test is waiting for its callback.
+    pub pending: bool,
+    /// The slot the latest self-test's callback ran at. Zero until the first one passes.
+    pub settled_slot: u64,
+    /// The byte the latest passing self-test received.
+    pub result: u8,
+    /// The number of self-tests started.
+    pub runs: u64,
+    /// The number of self-tests whose callback ran.
+    pub passes: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl SelfTestResult {
+    /// The Anchor account discriminator, `sha256("account:SelfTestResult")[..8]`.
+    pub const DISCRIMINATOR: [u8; 8] = [85, 102, 126, 254, 168, 234, 32, 1];
+
+    /// Whether the self-test for `request` is the latest one and its callback ran.
+    pub fn passed(&self, request: &Pubkey) -> bool {
+        self.request == *request && !self.pending
+    }
+
+    pub fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        if buf.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        *buf = &buf[8..];
+        Self::deserialize(buf).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
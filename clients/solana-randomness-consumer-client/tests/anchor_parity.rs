@@ -0,0 +1,413 @@
+//! Checks every builder against the client Anchor generates for the example program.
+
+use anchor_lang::{AccountSerialize, InstructionData, ToAccountMetas};
+use solana_program::{system_program, sysvar};
+use solana_randomness_consumer::{
+    accounts, instruction, CachedRandomness, Lootbox, LootboxOpening, ResultFeed,
+};
+use solana_randomness_consumer_client::*;
+use solana_randomness_service_lite::{RANDOMNESS_SERVICE_REWARD_MINT, RANDOMNESS_SERVICE_STATE};
+
+fn assert_matches_anchor(
+    ix: Instruction,
+    anchor_accounts: impl ToAccountMetas,
+    anchor_data: impl InstructionData,
+) {
+    assert_eq!(ix.program_id, solana_randomness_consumer::ID);
+    assert_eq!(ix.accounts, anchor_accounts.to_account_metas(None));
+    assert_eq!(ix.data, anchor_data.data());
+}
+
+fn open_box_accounts() -> OpenBoxAccounts {
+    OpenBoxAccounts {
+        key_mint: Pubkey::new_unique(),
+        prize_mint: Pubkey::new_unique(),
+        user_key_account: Pubkey::new_unique(),
+        user_prize_account: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn test_pdas_match_program_seeds() {
+    let key_mint = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+
+    assert_eq!(ID, solana_randomness_consumer::ID);
+    assert_eq!(LOOTBOX_SEED, Lootbox::SEED);
+    assert_eq!(OPENING_SEED, LootboxOpening::SEED);
+    assert_eq!(
+        lootbox_address(&key_mint),
+        Pubkey::find_program_address(&[Lootbox::SEED, key_mint.as_ref()], &ID)
+    );
+    assert_eq!(RESULT_FEED_SEED, ResultFeed::SEED);
+    assert_eq!(
+        result_feed_address(&key_mint),
+        Pubkey::find_program_address(&[ResultFeed::SEED, key_mint.as_ref()], &ID)
+    );
+    assert_eq!(CACHED_RANDOMNESS_SEED, CachedRandomness::SEED);
+    assert_eq!(
+        cached_randomness_address(&key_mint),
+        Pubkey::find_program_address(&[CachedRandomness::SEED, key_mint.as_ref()], &ID)
+    );
+    assert_eq!(
+        opening_address(&request),
+        Pubkey::find_program_address(&[LootboxOpening::SEED, request.as_ref()], &ID)
+    );
+}
+
+#[test]
+fn test_lootbox_pda_matches_program() {
+    let key_mint = Pubkey::new_unique();
+
+    let (address, pda) = LootboxPda::find(&ID, key_mint);
+    let (program_address, program_pda) =
+        solana_randomness_consumer::LootboxPda::find(&ID, key_mint);
+
+    assert_eq!(address, program_address);
+    assert_eq!(pda.seeds_with_bump(), program_pda.seeds_with_bump());
+    assert_eq!(LootboxPda::SEEDS, "[LOOTBOX_SEED, key_mint.as_ref()]");
+    assert_eq!(
+        solana_randomness_consumer::LootboxPda::SEEDS,
+        "[Lootbox::SEED, key_mint.as_ref()]"
+    );
+}
+
+#[test]
+fn test_request_randomness() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        request_randomness(&payer, &request),
+        accounts::RequestRandomness {
+            randomness_service: solana_randomness_service_lite::ID,
+            randomness_request: request,
+            randomness_escrow: randomness_escrow_address(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            payer,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+        },
+        instruction::RequestRandomness {},
+    );
+}
+
+#[test]
+fn test_consume_randomness() {
+    let request = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        consume_randomness(&request, &[1, 2, 3, 4, 5, 6, 7, 8]),
+        accounts::ConsumeRandomness {
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            request,
+        },
+        instruction::ConsumeRandomness {
+            result: vec![1, 2, 3, 4, 5, 6, 7, 8],
+        },
+    );
+}
+
+#[test]
+fn test_initialize_lootbox() {
+    let payer = Pubkey::new_unique();
+    let key_mint = Pubkey::new_unique();
+    let prize_mint = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        initialize_lootbox(&payer, &key_mint, &prize_mint),
+        accounts::InitializeLootbox {
+            lootbox: lootbox_address(&key_mint).0,
+            key_mint,
+            prize_mint,
+            payer,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::InitializeLootbox {},
+    );
+}
+
+#[test]
+fn test_mint_lootbox_keys() {
+    let authority = Pubkey::new_unique();
+    let key_mint = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        mint_lootbox_keys(&authority, &key_mint, &destination, 1_000),
+        accounts::MintLootboxKeys {
+            lootbox: lootbox_address(&key_mint).0,
+            authority,
+            key_mint,
+            destination,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::MintLootboxKeys { amount: 1_000 },
+    );
+}
+
+#[test]
+fn test_open_box() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let open = open_box_accounts();
+
+    assert_matches_anchor(
+        open_box(&payer, &request, &open),
+        accounts::OpenBox {
+            lootbox: lootbox_address(&open.key_mint).0,
+            key_mint: open.key_mint,
+            user_key_account: open.user_key_account,
+            prize_mint: open.prize_mint,
+            user_prize_account: open.user_prize_account,
+            opening: opening_address(&request).0,
+            randomness_service: solana_randomness_service_lite::ID,
+            randomness_request: request,
+            randomness_escrow: randomness_escrow_address(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            payer,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+        },
+        instruction::OpenBox {},
+    );
+}
+
+#[test]
+fn test_consume_lootbox() {
+    let request = Pubkey::new_unique();
+    let open = open_box_accounts();
+
+    assert_matches_anchor(
+        consume_lootbox(&request, &open, &[9, 8, 7, 6]),
+        accounts::ConsumeLootbox {
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            request,
+            lootbox: lootbox_address(&open.key_mint).0,
+            opening: opening_address(&request).0,
+            prize_mint: open.prize_mint,
+            user_prize_account: open.user_prize_account,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::ConsumeLootbox {
+            result: vec![9, 8, 7, 6],
+        },
+    );
+}
+
+#[test]
+fn test_initialize_result_feed() {
+    let payer = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        initialize_result_feed(&payer),
+        accounts::InitializeResultFeed {
+            result_feed: result_feed_address(&payer).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeResultFeed {},
+    );
+}
+
+#[test]
+fn test_request_feed_randomness() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        request_feed_randomness(&payer, &request, 16),
+        accounts::RequestFeedRandomness {
+            result_feed: result_feed_address(&payer).0,
+            randomness_service: solana_randomness_service_lite::ID,
+            randomness_request: request,
+            randomness_escrow: randomness_escrow_address(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            payer,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+        },
+        instruction::RequestFeedRandomness { num_bytes: 16 },
+    );
+}
+
+#[test]
+fn test_consume_feed_randomness() {
+    let request = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        consume_feed_randomness(&request, &user, &[5; 16]),
+        accounts::ConsumeFeedRandomness {
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            request,
+            result_feed: result_feed_address(&user).0,
+        },
+        instruction::ConsumeFeedRandomness {
+            result: vec![5; 16],
+        },
+    );
+}
+
+#[test]
+fn test_initialize_cached_randomness() {
+    let payer = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        initialize_cached_randomness(&payer, 4),
+        accounts::InitializeCachedRandomness {
+            cache: cached_randomness_address(&payer).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeCachedRandomness { num_bytes: 4 },
+    );
+}
+
+#[test]
+fn test_get_or_request() {
+    let payer = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        get_or_request(&payer, &authority, &request, 300),
+        accounts::GetOrRequest {
+            cache: cached_randomness_address(&authority).0,
+            randomness_service: solana_randomness_service_lite::ID,
+            randomness_request: request,
+            randomness_escrow: randomness_escrow_address(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            payer,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+        },
+        instruction::GetOrRequest { max_age_slots: 300 },
+    );
+}
+
+#[test]
+fn test_consume_cached_randomness() {
+    let request = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        consume_cached_randomness(&request, &authority, &[9; 4]),
+        accounts::ConsumeCachedRandomness {
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            request,
+            cache: cached_randomness_address(&authority).0,
+        },
+        instruction::ConsumeCachedRandomness { result: vec![9; 4] },
+    );
+}
+
+#[test]
+fn test_self_test_accounts_match_program() {
+    assert_eq!(
+        SELF_TEST_SEED,
+        solana_randomness_consumer::SelfTestResult::SEED
+    );
+    assert_eq!(
+        self_test_result_address(),
+        Pubkey::find_program_address(&[solana_randomness_consumer::SelfTestResult::SEED], &ID)
+    );
+
+    let program = solana_randomness_consumer::SelfTestResult {
+        bump: 254,
+        admin: Pubkey::new_unique(),
+        request: Pubkey::new_unique(),
+        request_slot: 10,
+        pending: false,
+        settled_slot: 12,
+        result: 42,
+        runs: 3,
+        passes: 2,
+        reserved: [0; 32],
+    };
+    let mut data = Vec::new();
+    program.try_serialize(&mut data).unwrap();
+    let result = SelfTestResult::try_deserialize(&mut &data[..]).unwrap();
+
+    assert_eq!(
+        result,
+        SelfTestResult {
+            bump: 254,
+            admin: program.admin,
+            request: program.request,
+            request_slot: 10,
+            pending: false,
+            settled_slot: 12,
+            result: 42,
+            runs: 3,
+            passes: 2,
+            reserved: [0; 32],
+        }
+    );
+    assert!(result.passed(&program.request));
+}
+
+#[test]
+fn test_initialize_self_test() {
+    let admin = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        initialize_self_test(&admin),
+        accounts::InitializeSelfTest {
+            self_test_result: self_test_result_address().0,
+            program_data: program_data_address(),
+            admin,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeSelfTest {},
+    );
+}
+
+#[test]
+fn test_self_test() {
+    let admin = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        self_test(&admin, &request),
+        accounts::SelfTest {
+            self_test_result: self_test_result_address().0,
+            program_data: program_data_address(),
+            randomness_service: solana_randomness_service_lite::ID,
+            randomness_request: request,
+            randomness_escrow: randomness_escrow_address(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            admin,
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
+        },
+        instruction::SelfTest {},
+    );
+}
+
+#[test]
+fn test_consume_self_test() {
+    let request = Pubkey::new_unique();
+
+    assert_matches_anchor(
+        consume_self_test(&request, &[7]),
+        accounts::ConsumeSelfTest {
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            request,
+            self_test_result: self_test_result_address().0,
+        },
+        instruction::ConsumeSelfTest { result: vec![7] },
+    );
+}
@@ -0,0 +1,33 @@
+//! Runs the self-test of the example program deployed on devnet. Enabled with the `devnet`
+//! feature; needs the program's upgrade authority in `ADMIN_KEYPAIR` and an initialized
+//! self-test result.
+//!
+//! ```text
+//! ADMIN_KEYPAIR=<FILE> [RPC_URL=<URL>] cargo test -p solana-randomness-consumer-client --features devnet --test devnet
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_consumer_client::*;
+use solana_sdk::signature::read_keypair_file;
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+
+#[tokio::test]
+async fn test_self_test_passes_on_devnet() {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let path = std::env::var("ADMIN_KEYPAIR").expect("ADMIN_KEYPAIR is not set");
+    let admin = read_keypair_file(&path).expect("ADMIN_KEYPAIR is not a keypair file");
+
+    let report = run_self_test(&rpc, &admin, Duration::from_secs(120))
+        .await
+        .unwrap();
+
+    assert!(
+        matches!(report.outcome, SelfTestOutcome::Passed { .. }),
+        "self-test {} did not pass: {:?}",
+        report.request,
+        report.outcome
+    );
+}
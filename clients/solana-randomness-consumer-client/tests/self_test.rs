@@ -0,0 +1,161 @@
+//! `run_self_test` against the lite crate's mock RPC. The mock does not run the program, so
+//! each test plays the oracle by writing the accounts the self-test would leave behind.
+
+use borsh::BorshSerialize;
+use solana_randomness_consumer_client::*;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::LiteClientError;
+use solana_randomness_service_lite::SimpleRandomnessV1Account;
+use solana_sdk::account::Account;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn self_test_result_account(result: &SelfTestResult) -> Account {
+    let mut data = SelfTestResult::DISCRIMINATOR.to_vec();
+    data.extend(result.try_to_vec().unwrap());
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Waits for the `self_test` transaction and returns its request, the second signer.
+async fn sent_request(rpc: &MockRpc) -> Pubkey {
+    loop {
+        if let Some(sent) = rpc.sent_transactions().first() {
+            return sent.message.account_keys[1];
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// A result whose latest self-test is `request`, still waiting for its callback.
+fn pending(admin: Pubkey, request: Pubkey) -> SelfTestResult {
+    SelfTestResult {
+        admin,
+        request,
+        request_slot: 10,
+        pending: true,
+        runs: 1,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_reports_pass() {
+    let admin = Keypair::new();
+    let rpc = Arc::new(MockRpc::default());
+    let oracle = {
+        let rpc = rpc.clone();
+        let admin = admin.pubkey();
+        tokio::spawn(async move {
+            let request = sent_request(&rpc).await;
+            let passed = SelfTestResult {
+                pending: false,
+                settled_slot: 12,
+                result: 42,
+                passes: 1,
+                ..pending(admin, request)
+            };
+            rpc.set_account(
+                self_test_result_address().0,
+                self_test_result_account(&passed),
+            );
+        })
+    };
+
+    let report = run_self_test(rpc.as_ref(), &admin, Duration::from_secs(5))
+        .await
+        .unwrap();
+    oracle.await.unwrap();
+
+    assert_eq!(
+        report.outcome,
+        SelfTestOutcome::Passed {
+            settled_slot: 12,
+            result: 42
+        }
+    );
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].signatures[0], report.signature);
+    assert_eq!(
+        sent[0].message.instructions[0].data,
+        self_test(&admin.pubkey(), &report.request).data
+    );
+}
+
+#[tokio::test]
+async fn test_reports_oracle_error() {
+    let admin = Keypair::new();
+    let rpc = Arc::new(MockRpc::default());
+    let oracle = {
+        let rpc = rpc.clone();
+        let admin = admin.pubkey();
+        tokio::spawn(async move {
+            let request = sent_request(&rpc).await;
+            rpc.set_account(
+                self_test_result_address().0,
+                self_test_result_account(&pending(admin, request)),
+            );
+            let failed = SimpleRandomnessV1Account {
+                num_bytes: 1,
+                user: admin,
+                is_completed: 1,
+                error_message: "callback failed".to_string(),
+                ..Default::default()
+            };
+            rpc.set_account(request, request_account(&failed));
+        })
+    };
+
+    let report = run_self_test(rpc.as_ref(), &admin, Duration::from_secs(5))
+        .await
+        .unwrap();
+    oracle.await.unwrap();
+
+    assert_eq!(
+        report.outcome,
+        SelfTestOutcome::Failed {
+            error_message: "callback failed".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_reports_timeout_while_pending() {
+    let admin = Keypair::new();
+    let rpc = MockRpc::default();
+    // An earlier self-test passed, but not this one
+    let earlier = SelfTestResult {
+        pending: false,
+        passes: 1,
+        ..pending(admin.pubkey(), Pubkey::new_unique())
+    };
+    rpc.set_account(
+        self_test_result_address().0,
+        self_test_result_account(&earlier),
+    );
+
+    let report = run_self_test(&rpc, &admin, Duration::from_millis(50))
+        .await
+        .unwrap();
+
+    assert_eq!(report.outcome, SelfTestOutcome::TimedOut);
+}
+
+#[tokio::test]
+async fn test_fails_when_not_sent() {
+    let admin = Keypair::new();
+    let rpc = MockRpc::default();
+    rpc.set_fail_sends(true);
+
+    assert!(matches!(
+        run_self_test(&rpc, &admin, Duration::from_secs(1)).await,
+        Err(LiteClientError::Rpc(_))
+    ));
+}
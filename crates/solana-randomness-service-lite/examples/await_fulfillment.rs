@@ -0,0 +1,32 @@
+//! Polls a request until the oracle settles it, then prints the randomness.
+//!
+//! ```text
+//! REQUEST=<PUBKEY> [RPC_URL=<URL>] [TIMEOUT_SECS=<SECS>] cargo run --example await_fulfillment --features client
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::await_fulfillment_polling;
+use solana_randomness_service_lite::Pubkey;
+use std::str::FromStr;
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let request = Pubkey::from_str(&std::env::var("REQUEST")?)?;
+    let timeout = match std::env::var("TIMEOUT_SECS") {
+        Ok(secs) => Duration::from_secs(secs.parse()?),
+        Err(_) => Duration::from_secs(60),
+    };
+
+    let outcome = await_fulfillment_polling(&rpc, &request, timeout, POLL_INTERVAL).await?;
+    match outcome.randomness() {
+        Some(randomness) => println!("Fulfilled: {:?}", randomness),
+        None => println!("Not fulfilled: {:?}", outcome),
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! Requests more bytes than one request delivers, as a batch, and prints them once every
+//! request is settled. The requests have no callback.
+//!
+//! ```text
+//! PAYER_KEYPAIR=<FILE> [TOTAL_BYTES=<BYTES>] [RPC_URL=<URL>] [WS_URL=<URL>] cargo run --example batch_request --features client
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::{
+    await_batch_with_pubsub, RandomnessPubsub, RequestBuilder, WebsocketPubsub,
+};
+use solana_randomness_service_lite::Callback;
+use solana_sdk::signature::{read_keypair_file, Signer};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+const DEFAULT_WS_URL: &str = "wss://api.devnet.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let ws_url = std::env::var("WS_URL").unwrap_or_else(|_| DEFAULT_WS_URL.to_string());
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![Arc::new(WebsocketPubsub::new(ws_url))];
+    let payer = read_keypair_file(std::env::var("PAYER_KEYPAIR")?)?;
+    let total_bytes = match std::env::var("TOTAL_BYTES") {
+        Ok(bytes) => bytes.parse()?,
+        Err(_) => 64,
+    };
+
+    let batch =
+        RequestBuilder::new(payer.pubkey(), 32, Callback::default()).num_bytes_total(total_bytes);
+    println!(
+        "Sending {} requests for {} lamports",
+        batch.requests().len(),
+        batch.total_lamports()?
+    );
+    let sent = batch.send(&rpc, &payer).await?;
+    for request in sent.requests.iter() {
+        println!("Sent {} in {}", request.request, request.signature);
+    }
+
+    let randomness =
+        await_batch_with_pubsub(&rpc, &pubsub, &sent, Duration::from_secs(120)).await?;
+    println!("Randomness: {:?}", randomness);
+    Ok(())
+}
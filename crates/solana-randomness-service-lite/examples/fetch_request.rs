@@ -0,0 +1,35 @@
+//! Fetches a request account and prints its status.
+//!
+//! ```text
+//! REQUEST=<PUBKEY> [RPC_URL=<URL>] cargo run --example fetch_request --features client
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::{RandomnessRpc, RequestStatus};
+use solana_randomness_service_lite::Pubkey;
+use std::str::FromStr;
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let request = Pubkey::from_str(&std::env::var("REQUEST")?)?;
+
+    let account = RandomnessRpc::get_account(&rpc, &request).await?;
+    match RequestStatus::from_account(account.as_ref())? {
+        RequestStatus::Pending(request) => println!(
+            "Pending since slot {}: {} bytes for {}",
+            request.request_slot, request.num_bytes, request.callback.program_id
+        ),
+        RequestStatus::Failed(request) => {
+            println!(
+                "Failed, close it to reclaim the rent: {}",
+                request.error_message
+            )
+        }
+        _ => println!("Closed"),
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Prints the randomness service program's fingerprint and the estimated wait for a new
+//! request. Exits with an error if the program data hash differs from `PIN`.
+//!
+//! ```text
+//! [PIN=<HASH>] [RPC_URL=<URL>] cargo run --example health_check --features client
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::{estimate_queue_depth, service_program_fingerprint};
+use solana_sdk::hash::Hash;
+use std::str::FromStr;
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let pin = match std::env::var("PIN") {
+        Ok(pin) => Some(Hash::from_str(&pin)?),
+        Err(_) => None,
+    };
+
+    let fingerprint = service_program_fingerprint(&rpc).await?;
+    println!("{}", serde_json::to_string_pretty(&fingerprint)?);
+    let estimate = estimate_queue_depth(&rpc).await?;
+    println!("{}", serde_json::to_string_pretty(&estimate)?);
+
+    if let Some(pin) = pin {
+        if fingerprint.program_data_hash != pin {
+            return Err(format!(
+                "Randomness service program hash {} does not match pinned hash {}",
+                fingerprint.program_data_hash, pin
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,25 @@
+//! Prints the distributions of the requests made to the randomness service in the last
+//! `SAMPLE_SLOTS` slots as JSON.
+//!
+//! ```text
+//! [SAMPLE_SLOTS=<SLOTS>] [RPC_URL=<URL>] cargo run --example stats --features client
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::service_stats;
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let sample_slots = match std::env::var("SAMPLE_SLOTS") {
+        Ok(slots) => slots.parse()?,
+        Err(_) => 750,
+    };
+
+    let stats = service_stats(&rpc, sample_slots).await?;
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+    Ok(())
+}
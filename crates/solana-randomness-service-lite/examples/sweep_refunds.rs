@@ -0,0 +1,34 @@
+//! Closes every request of the payer that the oracle completed with an error, returning the
+//! rent of each request and its escrow.
+//!
+//! ```text
+//! PAYER_KEYPAIR=<FILE> [RPC_URL=<URL>] cargo run --example sweep_refunds --features client
+//! ```
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::{sweep_failed_requests, ReclaimOutcome};
+use solana_randomness_service_lite::ID;
+use solana_sdk::signature::{read_keypair_file, Signer};
+
+const DEFAULT_URL: &str = "https://api.devnet.solana.com";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+    let rpc = RpcClient::new(url);
+    let payer = read_keypair_file(std::env::var("PAYER_KEYPAIR")?)?;
+
+    let swept = sweep_failed_requests(&rpc, &payer, &ID, &payer.pubkey()).await?;
+    if swept.is_empty() {
+        println!("No failed requests to close");
+    }
+    for (request, outcome) in swept {
+        match outcome {
+            ReclaimOutcome::Reclaimed { signature, .. } => {
+                println!("Closed {} in {}", request, signature)
+            }
+            outcome => println!("Did not close {}: {:?}", request, outcome),
+        }
+    }
+    Ok(())
+}
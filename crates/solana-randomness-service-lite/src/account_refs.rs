@@ -0,0 +1,180 @@
+use crate::*;
+use solana_program::instruction::Instruction;
+use solana_program::{program::invoke, program::invoke_signed};
+
+/// Borrowed accounts for the `simple_randomness_v1` instruction, the same accounts as
+/// [`SimpleRandomnessV1Request`] without cloning them at construction.
+///
+/// Anchor consumers build it from `ctx.accounts` with `AsRef`, e.g.
+/// `payer: ctx.accounts.payer.as_ref()`. Programs using `solana-program` directly can borrow
+/// from the instruction's account slice with [`from_slice`](Self::from_slice).
+#[derive(Clone, Copy)]
+pub struct SimpleRandomnessV1AccountRefs<'a, 'info> {
+    pub request: &'a AccountInfo<'info>,
+    pub escrow: &'a AccountInfo<'info>,
+    pub state: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SimpleRandomnessV1AccountRefs<'a, 'info> {
+    /// Borrows the accounts from the first 8 entries of `accounts`, in instruction order.
+    pub fn from_slice(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let [request, escrow, state, mint, payer, system_program, token_program, associated_token_program, ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        Ok(Self {
+            request,
+            escrow,
+            state,
+            mint,
+            payer,
+            system_program,
+            token_program,
+            associated_token_program,
+        })
+    }
+
+    pub fn instruction(
+        &self,
+        program_id: Pubkey,
+        num_bytes: u8,
+        callback: &Callback,
+        options: Option<&TransactionOptions>,
+    ) -> Result<Instruction, ProgramError> {
+        callback.check_limits()?;
+        callback.check_size(num_bytes)?;
+        Ok(Instruction {
+            program_id,
+            accounts: self.to_account_metas(),
+            data: SimpleRandomnessV1Request::data(num_bytes, callback, options)?,
+        })
+    }
+
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1AccountRefs::instruction`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+    ) -> Result<Instruction, ProgramError> {
+        self.instruction(program_id, num_bytes, callback, options.as_ref())
+    }
+
+    pub(crate) fn to_account_metas(self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*self.request.key, true),
+            AccountMeta::new(*self.escrow.key, false),
+            AccountMeta::new_readonly(*self.state.key, false),
+            AccountMeta::new_readonly(*self.mint.key, false),
+            AccountMeta::new(*self.payer.key, true),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+            AccountMeta::new_readonly(*self.associated_token_program.key, false),
+        ]
+    }
+
+    /// The CPI takes the account infos by value, so they are copied into a fixed size array
+    /// here rather than collected into a `Vec`.
+    fn to_account_infos(self) -> [AccountInfo<'info>; 8] {
+        [
+            self.request.clone(),
+            self.escrow.clone(),
+            self.state.clone(),
+            self.mint.clone(),
+            self.payer.clone(),
+            self.system_program.clone(),
+            self.token_program.clone(),
+            self.associated_token_program.clone(),
+        ]
+    }
+}
+
+impl<'a, 'info> From<&'a SimpleRandomnessV1Request<'info>>
+    for SimpleRandomnessV1AccountRefs<'a, 'info>
+{
+    fn from(request: &'a SimpleRandomnessV1Request<'info>) -> Self {
+        Self {
+            request: &request.request,
+            escrow: &request.escrow,
+            state: &request.state,
+            mint: &request.mint,
+            payer: &request.payer,
+            system_program: &request.system_program,
+            token_program: &request.token_program,
+            associated_token_program: &request.associated_token_program,
+        }
+    }
+}
+
+impl<'info> SimpleRandomnessV1Request<'info> {
+    /// Same as [`cpi`](Self::cpi), with borrowed accounts.
+    pub fn cpi_borrowed(
+        accounts: &SimpleRandomnessV1AccountRefs<'_, 'info>,
+        program: &AccountInfo<'info>,
+        num_bytes: u8,
+        callback: &Callback,
+        options: Option<&TransactionOptions>,
+    ) -> Result<(), ProgramError> {
+        let instruction = accounts.instruction(*program.key, num_bytes, callback, options)?;
+        invoke(&instruction, &accounts.to_account_infos())
+    }
+
+    /// Same as [`cpi_signed`](Self::cpi_signed), with borrowed accounts.
+    pub fn cpi_signed_borrowed(
+        accounts: &SimpleRandomnessV1AccountRefs<'_, 'info>,
+        program: &AccountInfo<'info>,
+        num_bytes: u8,
+        callback: &Callback,
+        options: Option<&TransactionOptions>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        let instruction = accounts.instruction(*program.key, num_bytes, callback, options)?;
+        invoke_signed(&instruction, &accounts.to_account_infos(), signer_seeds)
+    }
+
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1Request::cpi_borrowed`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn invoke_borrowed(
+        accounts: &SimpleRandomnessV1AccountRefs<'_, 'info>,
+        program: &AccountInfo<'info>,
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+    ) -> Result<(), ProgramError> {
+        Self::cpi_borrowed(accounts, program, num_bytes, callback, options.as_ref())
+    }
+
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1Request::cpi_signed_borrowed`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn invoke_signed_borrowed(
+        accounts: &SimpleRandomnessV1AccountRefs<'_, 'info>,
+        program: &AccountInfo<'info>,
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        Self::cpi_signed_borrowed(
+            accounts,
+            program,
+            num_bytes,
+            callback,
+            options.as_ref(),
+            signer_seeds,
+        )
+    }
+}
@@ -0,0 +1,180 @@
+use crate::*;
+
+pub(crate) const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+pub(crate) const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+pub(crate) const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// The seed of the service's state PDA.
+pub const STATE_SEED: &[u8] = b"STATE";
+
+/// The state account of the randomness service deployed at `program_id`, and its bump. For
+/// [`ID`] this is [`RANDOMNESS_SERVICE_STATE`].
+pub fn derive_state_pubkey(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATE_SEED], program_id)
+}
+
+/// The escrow token account the service creates for a request: the request's associated token
+/// account for the mint, derived without the SPL crates.
+pub fn derive_escrow_pubkey(request: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[request.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The accounts the randomness service and its Switchboard function are deployed at.
+///
+/// Defaults to the constants in this crate. When Switchboard rotates its accounts, apply
+/// [`AddressOverrides`] with [`with_overrides`](Self::with_overrides) instead of waiting for a
+/// new release.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServiceAddresses {
+    /// The randomness service program.
+    pub program_id: Pubkey,
+    /// The service's state account, a PDA of the program.
+    pub state: Pubkey,
+    /// The wrapped SOL token account the service pays request fees into, owned by the state
+    /// account.
+    pub reward_wallet: Pubkey,
+    /// The mint requests are paid in. The service only accepts wrapped SOL.
+    pub reward_mint: Pubkey,
+    /// The Switchboard function running the oracle.
+    pub switchboard_function: Pubkey,
+    /// The Switchboard service the oracle settles requests with.
+    pub switchboard_service: Pubkey,
+}
+
+impl ServiceAddresses {
+    pub const fn mainnet() -> Self {
+        Self {
+            program_id: ID,
+            state: RANDOMNESS_SERVICE_STATE,
+            reward_wallet: RANDOMNESS_SERVICE_REWARD_WALLET,
+            reward_mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            switchboard_function: MAINNET_SWITCHBOARD_FUNCTION,
+            switchboard_service: MAINNET_SWITCHBOARD_SERVICE,
+        }
+    }
+
+    pub const fn devnet() -> Self {
+        Self {
+            switchboard_function: DEVNET_SWITCHBOARD_FUNCTION,
+            switchboard_service: DEVNET_SWITCHBOARD_SERVICE,
+            ..Self::mainnet()
+        }
+    }
+
+    /// Replaces the addresses set in `overrides` and checks the result with
+    /// [`verify_address_consistency`](Self::verify_address_consistency).
+    pub fn with_overrides(self, overrides: AddressOverrides) -> Result<Self, AddressError> {
+        let addresses = Self {
+            program_id: overrides.program_id.unwrap_or(self.program_id),
+            state: overrides.state.unwrap_or(self.state),
+            reward_wallet: overrides.reward_wallet.unwrap_or(self.reward_wallet),
+            reward_mint: overrides.reward_mint.unwrap_or(self.reward_mint),
+            switchboard_function: overrides
+                .switchboard_function
+                .unwrap_or(self.switchboard_function),
+            switchboard_service: overrides
+                .switchboard_service
+                .unwrap_or(self.switchboard_service),
+        };
+        addresses.verify_address_consistency()?;
+
+        #[cfg(feature = "client")]
+        if !overrides.is_empty() {
+            log::info!("Using service address overrides {:?}", overrides);
+        }
+
+        Ok(addresses)
+    }
+
+    /// Checks the addresses agree with one another as far as can be told without fetching them:
+    /// the state account is the program's `STATE` PDA, the reward mint is wrapped SOL, and the
+    /// Switchboard function and service are distinct accounts. The reward wallet is set by the
+    /// service authority and is only known from the state account.
+    pub fn verify_address_consistency(&self) -> Result<(), AddressError> {
+        let (state, _) = derive_state_pubkey(&self.program_id);
+        if self.state != state {
+            return Err(AddressError::StateMismatch {
+                expected: state,
+                actual: self.state,
+            });
+        }
+
+        if self.reward_mint != NATIVE_MINT {
+            return Err(AddressError::RewardMintNotNative(self.reward_mint));
+        }
+
+        if self.switchboard_function == self.switchboard_service {
+            return Err(AddressError::SwitchboardAccountsEqual(
+                self.switchboard_function,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ServiceAddresses {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+/// Addresses to replace in [`ServiceAddresses`]. Unset fields keep their built-in value.
+///
+/// With the `client` feature, overrides can be loaded from environment variables or JSON, see
+/// [`AddressOverrides::from_env`] and [`AddressOverrides::from_json`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AddressOverrides {
+    pub program_id: Option<Pubkey>,
+    pub state: Option<Pubkey>,
+    pub reward_wallet: Option<Pubkey>,
+    pub reward_mint: Option<Pubkey>,
+    pub switchboard_function: Option<Pubkey>,
+    pub switchboard_service: Option<Pubkey>,
+}
+
+impl AddressOverrides {
+    /// Whether no address is overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The reason a set of [`ServiceAddresses`] is inconsistent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AddressError {
+    /// The state account is not the `STATE` PDA of the program.
+    StateMismatch { expected: Pubkey, actual: Pubkey },
+    /// The reward mint is not wrapped SOL.
+    RewardMintNotNative(Pubkey),
+    /// The Switchboard function and service are set to the same account.
+    SwitchboardAccountsEqual(Pubkey),
+}
+
+impl std::fmt::Display for AddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressError::StateMismatch { expected, actual } => write!(
+                f,
+                "State account {} is not the program's STATE PDA {}",
+                actual, expected
+            ),
+            AddressError::RewardMintNotNative(mint) => {
+                write!(f, "Reward mint {} is not the native mint", mint)
+            }
+            AddressError::SwitchboardAccountsEqual(pubkey) => write!(
+                f,
+                "Switchboard function and service are both set to {}",
+                pubkey
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
@@ -0,0 +1,114 @@
+//! Anchor account traits for the service's accounts, so an Anchor consumer can type them as
+//! `Account<'info, ServiceState>` without depending on the full `solana-randomness-service`
+//! crate, and the [`SimpleRandomnessV1`] and [`ConsumeRandomnessAccounts`] accounts structs
+//! for requesting randomness and receiving it.
+//!
+//! Enabled with the `anchor` feature.
+
+use crate::*;
+use anchor_lang::error::ErrorCode;
+
+mod accounts;
+
+pub use accounts::{
+    ConsumeRandomnessAccounts, ConsumeRandomnessAccountsBumps, SimpleRandomnessV1,
+    SimpleRandomnessV1Bumps,
+};
+
+// A struct composing one of ours names these modules from its own scope, so they are
+// re-exported for the glob import to bring in.
+#[doc(hidden)]
+pub mod __client_accounts_simple_randomness_v1 {
+    pub use super::accounts::__client_accounts_simple_randomness_v1::*;
+}
+#[doc(hidden)]
+pub mod __cpi_client_accounts_simple_randomness_v1 {
+    pub use super::accounts::__cpi_client_accounts_simple_randomness_v1::*;
+}
+#[doc(hidden)]
+pub mod __client_accounts_consume_randomness_accounts {
+    pub use super::accounts::__client_accounts_consume_randomness_accounts::*;
+}
+#[doc(hidden)]
+pub mod __cpi_client_accounts_consume_randomness_accounts {
+    pub use super::accounts::__cpi_client_accounts_consume_randomness_accounts::*;
+}
+
+/// The service's accounts, read through Anchor's account traits.
+///
+/// Implemented for [`ServiceState`] and [`SimpleRandomnessV1Account`]. A consumer that keeps
+/// the account as an `UncheckedAccount` can run the same checks as `Account` by hand with
+/// [`load`](Self::load).
+pub trait LiteAccount: Sized {
+    const DISCRIMINATOR: [u8; 8];
+
+    fn decode(buf: &mut &[u8]) -> Result<Self, ProgramError>;
+
+    /// Checks the owner and discriminator of the account and deserializes it.
+    fn load(info: &AccountInfo) -> anchor_lang::Result<Self> {
+        if *info.owner != ID {
+            return Err(ErrorCode::AccountOwnedByWrongProgram.into());
+        }
+        let data = info.try_borrow_data()?;
+        lite_try_deserialize(&mut &data[..])
+    }
+}
+
+impl LiteAccount for ServiceState {
+    const DISCRIMINATOR: [u8; 8] = ServiceState::DISCRIMINATOR;
+
+    fn decode(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        ServiceState::try_deserialize_unchecked(buf)
+    }
+}
+
+impl LiteAccount for SimpleRandomnessV1Account {
+    const DISCRIMINATOR: [u8; 8] = SimpleRandomnessV1Account::DISCRIMINATOR;
+
+    fn decode(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        SimpleRandomnessV1Account::try_deserialize_unchecked(buf)
+    }
+}
+
+/// Checks the discriminator with Anchor's error codes before decoding.
+fn lite_try_deserialize<T: LiteAccount>(buf: &mut &[u8]) -> anchor_lang::Result<T> {
+    match buf.get(..8) {
+        None => Err(ErrorCode::AccountDiscriminatorNotFound.into()),
+        Some(disc) if disc != T::DISCRIMINATOR => {
+            Err(ErrorCode::AccountDiscriminatorMismatch.into())
+        }
+        Some(_) => T::decode(buf).map_err(|_| ErrorCode::AccountDidNotDeserialize.into()),
+    }
+}
+
+macro_rules! impl_anchor_account {
+    ($ty:ty) => {
+        impl anchor_lang::AccountDeserialize for $ty {
+            fn try_deserialize(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
+                lite_try_deserialize(buf)
+            }
+
+            fn try_deserialize_unchecked(buf: &mut &[u8]) -> anchor_lang::Result<Self> {
+                <$ty as LiteAccount>::decode(buf)
+                    .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+            }
+        }
+
+        /// The service's accounts are only written by the service, so serializing is a no-op,
+        /// like an account owned by another program in Anchor's own CPI clients.
+        impl anchor_lang::AccountSerialize for $ty {}
+
+        impl anchor_lang::Owner for $ty {
+            fn owner() -> Pubkey {
+                ID
+            }
+        }
+
+        impl anchor_lang::Discriminator for $ty {
+            const DISCRIMINATOR: [u8; 8] = <$ty as LiteAccount>::DISCRIMINATOR;
+        }
+    };
+}
+
+impl_anchor_account!(ServiceState);
+impl_anchor_account!(SimpleRandomnessV1Account);
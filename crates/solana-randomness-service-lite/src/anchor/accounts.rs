@@ -0,0 +1,182 @@
+//! The accounts structs, kept in their own module so the modules Anchor's derive generates
+//! next to them, which are crate-private, can be re-exported under the same names.
+
+use crate::addresses::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::*;
+use anchor_lang::prelude::{Account, Accounts, Program, Signer, System, UncheckedAccount};
+use anchor_lang::{Key, ToAccountInfo};
+use solana_program::msg;
+
+/// The accounts of a `simple_randomness_v1` request, with the constraints every consumer needs,
+/// for composing into a consumer's own accounts struct:
+///
+/// ```ignore
+/// use solana_randomness_service_lite::anchor::*;
+///
+/// #[derive(Accounts)]
+/// pub struct RequestRandomness<'info> {
+///     pub randomness: SimpleRandomnessV1<'info>,
+/// }
+///
+/// ctx.accounts.randomness.cpi(8, &callback, Some(&options))?;
+/// ```
+///
+/// Import it with the glob above: Anchor resolves a composed struct through the modules its
+/// derive generates next to it, which the glob brings into scope.
+///
+/// The request and escrow must be new, empty system accounts, with the request signing so the
+/// service can create it. The escrow must be the request's associated token account for the
+/// state's mint, see [`derive_escrow_pubkey`]. The state is checked as the service's `STATE`
+/// PDA, and the programs by address.
+#[derive(Accounts)]
+pub struct SimpleRandomnessV1<'info> {
+    /// The randomness service program.
+    /// CHECK: the address and executable constraints
+    #[account(address = ID, executable)]
+    pub service: UncheckedAccount<'info>,
+
+    /// The account the service creates to hold the request, picked up by the oracle.
+    /// CHECK: a new system account, created by the service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = request.data_is_empty() && request.lamports() == 0,
+    )]
+    pub request: UncheckedAccount<'info>,
+
+    /// The token account the service creates to hold the request's fee.
+    /// CHECK: a new system account at the request's escrow address, created by the service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = escrow.data_is_empty() && escrow.lamports() == 0,
+        address = derive_escrow_pubkey(&request.key(), &mint.key()),
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// The service's state account, holding the fee mint and the cost per byte.
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        seeds::program = ID,
+    )]
+    pub state: Box<Account<'info, ServiceState>>,
+
+    /// The mint the service takes its fee in.
+    /// CHECK: the state's mint
+    #[account(address = state.mint)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// Pays for the request and its accounts.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: the SPL Token program
+    #[account(address = TOKEN_PROGRAM_ID)]
+    pub token_program: UncheckedAccount<'info>,
+
+    /// CHECK: the SPL Associated Token program
+    #[account(address = ASSOCIATED_TOKEN_PROGRAM_ID)]
+    pub associated_token_program: UncheckedAccount<'info>,
+}
+
+impl<'info> SimpleRandomnessV1<'info> {
+    /// The accounts as a [`SimpleRandomnessV1Request`], for its preflight checks and CPIs.
+    pub fn to_request(&self) -> SimpleRandomnessV1Request<'info> {
+        SimpleRandomnessV1Request {
+            request: self.request.to_account_info(),
+            escrow: self.escrow.to_account_info(),
+            state: self.state.to_account_info(),
+            mint: self.mint.to_account_info(),
+            payer: self.payer.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+
+    /// Requests `num_bytes` of randomness, like [`SimpleRandomnessV1Request::cpi`] with the
+    /// service program.
+    pub fn cpi(
+        &self,
+        num_bytes: u8,
+        callback: &Callback,
+        options: Option<&TransactionOptions>,
+    ) -> Result<(), ProgramError> {
+        self.to_request()
+            .cpi(self.service.to_account_info(), num_bytes, callback, options)
+    }
+}
+
+impl<'info> From<&SimpleRandomnessV1<'info>> for SimpleRandomnessV1Request<'info> {
+    fn from(accounts: &SimpleRandomnessV1<'info>) -> Self {
+        accounts.to_request()
+    }
+}
+
+/// The accounts every callback is invoked with, with the checks of [`verify_callback`], for
+/// composing into a consumer's callback accounts struct:
+///
+/// ```ignore
+/// use solana_randomness_service_lite::anchor::*;
+///
+/// #[derive(Accounts)]
+/// pub struct ConsumeRandomness<'info> {
+///     pub randomness: ConsumeRandomnessAccounts<'info>,
+/// }
+///
+/// ctx.accounts.randomness.verify_program(ctx.program_id)?;
+/// ```
+///
+/// Register the callback with the state as its first account, signing, and the request
+/// second, as [`CallbackBuilder::with_state_signer`] and `with_readonly_account(request)` do.
+/// The state must sign, failing with [`LiteError::CallbackStateNotSigner`], which only the
+/// service can do while it settles a request, and be the service's `STATE` PDA. The request
+/// must be owned by the service.
+///
+/// The accounts cannot see which program they were passed to, so the callback must also call
+/// [`verify_program`](Self::verify_program) with `ctx.program_id`, which checks the request's
+/// stored callback invokes it, failing with [`LiteError::CallbackProgramMismatch`] otherwise.
+#[derive(Accounts)]
+pub struct ConsumeRandomnessAccounts<'info> {
+    /// The service's state account, signing the callback.
+    #[account(
+        signer @ ProgramError::from(LiteError::CallbackStateNotSigner),
+        address = RANDOMNESS_SERVICE_STATE,
+    )]
+    pub state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read in place rather than deserialized.
+    /// CHECK: owned by the service, its callback checked by `verify_program`
+    #[account(
+        owner = ID,
+    )]
+    pub request: UncheckedAccount<'info>,
+}
+
+impl ConsumeRandomnessAccounts<'_> {
+    /// Checks the request's stored callback invokes `program_id`, the program the callback is
+    /// running in, failing with [`LiteError::CallbackProgramMismatch`] otherwise.
+    pub fn verify_program(&self, program_id: &Pubkey) -> Result<(), ProgramError> {
+        let stored = stored_callback_program_id(&self.request);
+        if stored != Some(*program_id) {
+            msg!(
+                "Request {} does not call back {}",
+                self.request.key(),
+                program_id
+            );
+            return Err(LiteError::CallbackProgramMismatch.into());
+        }
+        Ok(())
+    }
+}
+
+/// The program a request account's stored callback invokes, or `None` if it is not a request.
+fn stored_callback_program_id(request: &AccountInfo) -> Option<Pubkey> {
+    SimpleRandomnessV1AccountView::load(request)
+        .and_then(|view| view.callback_program_id())
+        .ok()
+}
@@ -0,0 +1,186 @@
+use crate::*;
+use borsh::BorshDeserialize;
+
+/// A generation of the request layouts: the `simple_randomness_v1` instruction data and the
+/// request account data the service wrote at some point in its history.
+///
+/// Variants are never removed. An indexer replaying the service from its first slot has to
+/// decode every layout the service ever shipped, so once a layout is known it stays decodable,
+/// and a newly identified one is added as a new variant and a new entry of
+/// [`KNOWN_REQUEST_LAYOUTS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RequestLayoutVersion {
+    /// The current layout: the request takes `num_bytes`, `callback`, and optional
+    /// [`TransactionOptions`], and the account stores the callback's compute units, its
+    /// priority fee, and the oracle's error message after the callback.
+    V1,
+    /// A layout registered with [`ArchivalDecoder::with_layout`] that the crate does not know,
+    /// numbered by the caller.
+    Registered(u16),
+}
+
+/// The arguments of a request instruction: `num_bytes`, the callback, and the options.
+pub type RequestArgs = (u8, Callback, Option<TransactionOptions>);
+
+/// How to recognize and decode one layout generation. Decoders normalize an older layout into
+/// the current types, filling fields it did not have with their defaults.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestLayout {
+    pub version: RequestLayoutVersion,
+    /// The first slot the service could have written the layout at. Zero if unknown.
+    pub first_slot: u64,
+    /// The last slot the service could have written the layout at. `None` while the service
+    /// still writes it, or if unknown.
+    pub last_slot: Option<u64>,
+    /// Decodes the instruction data, discriminator included, into `num_bytes`, the callback,
+    /// and the options. Returns `None` if the data is not in this layout.
+    pub decode_instruction: fn(&[u8]) -> Option<RequestArgs>,
+    /// Decodes the account data, discriminator included. Returns `None` if the data is not in
+    /// this layout.
+    pub decode_account: fn(&[u8]) -> Option<SimpleRandomnessV1Account>,
+}
+
+impl RequestLayout {
+    /// The current layout, [`RequestLayoutVersion::V1`].
+    pub const V1: RequestLayout = RequestLayout {
+        version: RequestLayoutVersion::V1,
+        first_slot: 0,
+        last_slot: None,
+        decode_instruction: decode_v1_instruction,
+        decode_account: decode_v1_account,
+    };
+
+    /// Whether the service could have written the layout at `slot`.
+    pub fn covers_slot(&self, slot: u64) -> bool {
+        slot >= self.first_slot && !matches!(self.last_slot, Some(last) if slot > last)
+    }
+}
+
+/// Every layout the crate knows, newest first. Entries are only ever added.
+pub const KNOWN_REQUEST_LAYOUTS: &[RequestLayout] = &[RequestLayout::V1];
+
+/// A request instruction decoded by [`decode_any_request_instruction`], tagged with the layout
+/// it was written in.
+#[derive(Clone, Debug)]
+pub struct DecodedRequest {
+    pub layout: RequestLayoutVersion,
+    pub num_bytes: u8,
+    pub callback: Callback,
+    pub options: Option<TransactionOptions>,
+}
+
+/// A request account decoded by [`decode_any_request_account`], tagged with the layout it was
+/// written in.
+#[derive(Clone, Debug)]
+pub struct DecodedAccount {
+    pub layout: RequestLayoutVersion,
+    pub account: SimpleRandomnessV1Account,
+}
+
+/// Decodes request instructions and accounts in any layout the service has shipped, trying
+/// the layouts newest first and returning the first that decodes.
+///
+/// Starts with [`KNOWN_REQUEST_LAYOUTS`]. Layouts identified outside the crate are added with
+/// [`with_layout`](Self::with_layout) and tried after the known ones, in the order they were
+/// added.
+///
+/// A padded account in an older layout can also decode as a newer one, reading the padding
+/// as the fields the older layout lacks. Give older layouts the slots they were live at:
+/// a layout is skipped for an instruction outside its slots, and for an account whose
+/// `request_slot` is outside them.
+#[derive(Clone, Debug)]
+pub struct ArchivalDecoder {
+    layouts: Vec<RequestLayout>,
+}
+
+impl Default for ArchivalDecoder {
+    fn default() -> Self {
+        Self {
+            layouts: KNOWN_REQUEST_LAYOUTS.to_vec(),
+        }
+    }
+}
+
+impl ArchivalDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layout, tried after the ones already added.
+    pub fn with_layout(mut self, layout: RequestLayout) -> Self {
+        self.layouts.push(layout);
+        self
+    }
+
+    /// The layouts, in the order they are tried.
+    pub fn layouts(&self) -> &[RequestLayout] {
+        &self.layouts
+    }
+
+    /// Decodes `simple_randomness_v1` instruction data, discriminator included. `slot_hint`
+    /// is the slot of the transaction, if known, and skips layouts the service was not
+    /// writing then.
+    pub fn decode_request_instruction(
+        &self,
+        data: &[u8],
+        slot_hint: Option<u64>,
+    ) -> Result<DecodedRequest, ProgramError> {
+        self.layouts
+            .iter()
+            .filter(|layout| !matches!(slot_hint, Some(slot) if !layout.covers_slot(slot)))
+            .find_map(|layout| {
+                let (num_bytes, callback, options) = (layout.decode_instruction)(data)?;
+                Some(DecodedRequest {
+                    layout: layout.version,
+                    num_bytes,
+                    callback,
+                    options,
+                })
+            })
+            .ok_or(ProgramError::InvalidInstructionData)
+    }
+
+    /// Decodes request account data, discriminator included.
+    pub fn decode_request_account(&self, data: &[u8]) -> Result<DecodedAccount, ProgramError> {
+        self.layouts
+            .iter()
+            .find_map(|layout| {
+                let account = (layout.decode_account)(data)?;
+                layout
+                    .covers_slot(account.request_slot)
+                    .then_some(DecodedAccount {
+                        layout: layout.version,
+                        account,
+                    })
+            })
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+}
+
+/// Decodes `simple_randomness_v1` instruction data in any layout in
+/// [`KNOWN_REQUEST_LAYOUTS`]. See [`ArchivalDecoder`] to add layouts.
+///
+/// The V1 arguments are read by their Borsh lengths and must end exactly at the end of the
+/// data, so truncated data or trailing bytes fail rather than shifting into the options.
+pub fn decode_any_request_instruction(
+    data: &[u8],
+    slot_hint: Option<u64>,
+) -> Result<DecodedRequest, ProgramError> {
+    ArchivalDecoder::default().decode_request_instruction(data, slot_hint)
+}
+
+/// Decodes request account data in any layout in [`KNOWN_REQUEST_LAYOUTS`]. See
+/// [`ArchivalDecoder`] to add layouts.
+pub fn decode_any_request_account(data: &[u8]) -> Result<DecodedAccount, ProgramError> {
+    ArchivalDecoder::default().decode_request_account(data)
+}
+
+fn decode_v1_instruction(data: &[u8]) -> Option<RequestArgs> {
+    let args = data.strip_prefix(&SimpleRandomnessV1Request::DISCRIMINATOR[..])?;
+    RequestArgs::try_from_slice(args).ok()
+}
+
+fn decode_v1_account(data: &[u8]) -> Option<SimpleRandomnessV1Account> {
+    SimpleRandomnessV1Account::try_deserialize(&mut &data[..]).ok()
+}
@@ -0,0 +1,450 @@
+#![forbid(unsafe_code)]
+
+//! Command line tools for the Solana Randomness Service.
+//!
+//! ```text
+//! randomness-lite snapshot --out <FILE> [--url <RPC_URL>] [--cluster <NAME>] [--addresses-override <FILE>]
+//! randomness-lite health [--url <RPC_URL>] [--pin <HASH>] [--addresses-override <FILE>] [--queue]
+//! randomness-lite identify-disc --bytes <HEX> --names <NAME,...>
+//! randomness-lite stats [--sample-slots <SLOTS>] [--url <RPC_URL>] [--addresses-override <FILE>]
+//! randomness-lite inspect --signature <SIGNATURE> [--url <RPC_URL>] [--schemas <FILE>]
+//! randomness-lite explain <CODE>
+//! ```
+//!
+//! `--addresses-override` reads a JSON file of [`AddressOverrides`] to use in place of the
+//! built-in service addresses, e.g. after Switchboard rotates its accounts.
+//!
+//! `snapshot` falls back to scanning the program's recent signatures on endpoints that disable
+//! `getProgramAccounts`, and says so on stderr.
+//!
+//! `health` prints the service program's fingerprint and, with `--pin`, exits with an error if
+//! the program data hash differs from the pinned one. `--queue` also prints the number of
+//! pending requests and the estimated wait for a new one.
+//!
+//! `identify-disc` prints which of the candidate instruction names hashes to a hardcoded callback
+//! discriminator.
+//!
+//! `stats` prints the distributions of the byte counts, compute unit prices, escrow funding, and
+//! fulfillment latency of the requests made in the last `--sample-slots` slots as JSON.
+//!
+//! `inspect` prints the [`RequestTag`] of a request transaction and the crate version that built
+//! it, if the request was tagged with the `version-tag` feature enabled. `--schemas` reads a
+//! JSON file of callback schemas, see [`SchemaRegistry::from_json`], and also prints the
+//! arguments of each request's callback.
+//!
+//! `explain` describes a custom program error code, along with the version of this tool.
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_randomness_service_lite::client::{
+    discover_requests, estimate_queue_depth_for, export_snapshot, parse_events_from_logs,
+    program_fingerprint, service_stats_for, DiscoveryStrategy, RandomnessRpc, RequestTag,
+    SchemaRegistry,
+};
+use solana_randomness_service_lite::{
+    explain_error_code, identify_discriminator, AddressOverrides, RandomnessServiceEvent,
+    ServiceAddresses,
+};
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use std::fs::File;
+use std::io::BufWriter;
+use std::str::FromStr;
+
+const USAGE: &str = "Usage:
+  randomness-lite snapshot --out <FILE> [--url <RPC_URL>] [--cluster <NAME>] [--addresses-override <FILE>]
+  randomness-lite health [--url <RPC_URL>] [--pin <HASH>] [--addresses-override <FILE>] [--queue]
+  randomness-lite identify-disc --bytes <HEX> --names <NAME,...>
+  randomness-lite stats [--sample-slots <SLOTS>] [--url <RPC_URL>] [--addresses-override <FILE>]
+  randomness-lite inspect --signature <SIGNATURE> [--url <RPC_URL>] [--schemas <FILE>]
+  randomness-lite explain <CODE>";
+
+const DEFAULT_URL: &str = "https://api.mainnet-beta.solana.com";
+const DEFAULT_CLUSTER: &str = "mainnet-beta";
+/// About five minutes of slots.
+const DEFAULT_SAMPLE_SLOTS: u64 = 750;
+
+/// The built-in addresses for the cluster with the overrides file, if any, applied on top.
+fn service_addresses(
+    cluster: &str,
+    overrides: Option<&str>,
+) -> Result<ServiceAddresses, Box<dyn std::error::Error>> {
+    let addresses = match cluster {
+        "devnet" => ServiceAddresses::devnet(),
+        _ => ServiceAddresses::mainnet(),
+    };
+    match overrides {
+        Some(path) => {
+            let addresses = addresses.with_overrides(AddressOverrides::from_file(path)?)?;
+            eprintln!("Using address overrides from {}", path);
+            Ok(addresses)
+        }
+        None => Ok(addresses),
+    }
+}
+
+struct SnapshotArgs {
+    out: String,
+    url: String,
+    cluster: String,
+    addresses_override: Option<String>,
+}
+
+fn parse_snapshot_args(args: &[String]) -> Result<SnapshotArgs, String> {
+    let mut out = None;
+    let mut url = DEFAULT_URL.to_string();
+    let mut cluster = DEFAULT_CLUSTER.to_string();
+    let mut addresses_override = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?
+            .clone();
+        match arg.as_str() {
+            "--out" => out = Some(value),
+            "--url" => url = value,
+            "--cluster" => cluster = value,
+            "--addresses-override" => addresses_override = Some(value),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(SnapshotArgs {
+        out: out.ok_or("Missing --out")?,
+        url,
+        cluster,
+        addresses_override,
+    })
+}
+
+async fn snapshot(args: SnapshotArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let addresses = service_addresses(&args.cluster, args.addresses_override.as_deref())?;
+    let rpc = RpcClient::new(args.url);
+    let discovered = discover_requests(&rpc, &addresses.program_id, None).await?;
+    if let DiscoveryStrategy::SignatureScan { depth } = discovered.strategy {
+        eprintln!(
+            "getProgramAccounts is disabled on this endpoint, only requests created in the last {} program transactions are included",
+            depth
+        );
+    }
+
+    let file = BufWriter::new(File::create(&args.out)?);
+    export_snapshot(&discovered.requests, discovered.slot, &args.cluster, file)?;
+
+    println!(
+        "Wrote {} requests at slot {} to {}",
+        discovered.requests.len(),
+        discovered.slot,
+        args.out
+    );
+    Ok(())
+}
+
+struct HealthArgs {
+    url: String,
+    pin: Option<Hash>,
+    addresses_override: Option<String>,
+    queue: bool,
+}
+
+fn parse_health_args(args: &[String]) -> Result<HealthArgs, String> {
+    let mut url = DEFAULT_URL.to_string();
+    let mut pin = None;
+    let mut addresses_override = None;
+    let mut queue = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--queue" {
+            queue = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?
+            .clone();
+        match arg.as_str() {
+            "--url" => url = value,
+            "--pin" => {
+                pin = Some(Hash::from_str(&value).map_err(|e| format!("Invalid --pin: {}", e))?)
+            }
+            "--addresses-override" => addresses_override = Some(value),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(HealthArgs {
+        url,
+        pin,
+        addresses_override,
+        queue,
+    })
+}
+
+async fn health(args: HealthArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let addresses = service_addresses(DEFAULT_CLUSTER, args.addresses_override.as_deref())?;
+    let rpc = RpcClient::new(args.url);
+    let fingerprint = program_fingerprint(&rpc, &addresses.program_id).await?;
+
+    println!("{}", serde_json::to_string_pretty(&fingerprint)?);
+    if args.queue {
+        let estimate = estimate_queue_depth_for(&rpc, &addresses).await?;
+        println!("{}", serde_json::to_string_pretty(&estimate)?);
+    }
+
+    if let Some(pin) = args.pin {
+        if fingerprint.program_data_hash != pin {
+            return Err(format!(
+                "Randomness service program hash {} does not match pinned hash {}",
+                fingerprint.program_data_hash, pin
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+struct IdentifyDiscArgs {
+    bytes: [u8; 8],
+    names: Vec<String>,
+}
+
+fn parse_hex_discriminator(value: &str) -> Result<[u8; 8], String> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    if hex.len() != 16 || !hex.is_ascii() {
+        return Err(format!(
+            "Invalid --bytes {}: expected 16 hex characters",
+            value
+        ));
+    }
+
+    let mut bytes = [0u8; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|e| format!("Invalid --bytes {}: {}", value, e))?;
+    }
+    Ok(bytes)
+}
+
+fn parse_identify_disc_args(args: &[String]) -> Result<IdentifyDiscArgs, String> {
+    let mut bytes = None;
+    let mut names = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?
+            .clone();
+        match arg.as_str() {
+            "--bytes" => bytes = Some(parse_hex_discriminator(&value)?),
+            "--names" => {
+                names = Some(
+                    value
+                        .split(',')
+                        .map(|name| name.trim().to_string())
+                        .filter(|name| !name.is_empty())
+                        .collect(),
+                )
+            }
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(IdentifyDiscArgs {
+        bytes: bytes.ok_or("Missing --bytes")?,
+        names: names.ok_or("Missing --names")?,
+    })
+}
+
+fn identify_disc(args: IdentifyDiscArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let names: Vec<&str> = args.names.iter().map(String::as_str).collect();
+    match identify_discriminator(&args.bytes, &names) {
+        Some(name) => {
+            println!("{}", name);
+            Ok(())
+        }
+        None => Err(format!("No candidate name matches {:?}", args.bytes).into()),
+    }
+}
+
+struct StatsArgs {
+    sample_slots: u64,
+    url: String,
+    addresses_override: Option<String>,
+}
+
+fn parse_stats_args(args: &[String]) -> Result<StatsArgs, String> {
+    let mut sample_slots = DEFAULT_SAMPLE_SLOTS;
+    let mut url = DEFAULT_URL.to_string();
+    let mut addresses_override = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?
+            .clone();
+        match arg.as_str() {
+            "--sample-slots" => {
+                sample_slots = value
+                    .parse()
+                    .map_err(|e| format!("Invalid --sample-slots: {}", e))?
+            }
+            "--url" => url = value,
+            "--addresses-override" => addresses_override = Some(value),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(StatsArgs {
+        sample_slots,
+        url,
+        addresses_override,
+    })
+}
+
+async fn stats(args: StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let addresses = service_addresses(DEFAULT_CLUSTER, args.addresses_override.as_deref())?;
+    let rpc = RpcClient::new(args.url);
+    let stats = service_stats_for(&rpc, &addresses, args.sample_slots).await?;
+
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+    Ok(())
+}
+
+struct InspectArgs {
+    signature: Signature,
+    url: String,
+    schemas: Option<String>,
+}
+
+fn parse_inspect_args(args: &[String]) -> Result<InspectArgs, String> {
+    let mut signature = None;
+    let mut url = DEFAULT_URL.to_string();
+    let mut schemas = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?
+            .clone();
+        match arg.as_str() {
+            "--signature" => {
+                signature = Some(
+                    Signature::from_str(&value)
+                        .map_err(|e| format!("Invalid --signature {}: {}", value, e))?,
+                )
+            }
+            "--url" => url = value,
+            "--schemas" => schemas = Some(value),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(InspectArgs {
+        signature: signature.ok_or("Missing --signature")?,
+        url,
+        schemas,
+    })
+}
+
+async fn inspect(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schemas = args
+        .schemas
+        .as_deref()
+        .map(SchemaRegistry::from_file)
+        .transpose()?;
+    let rpc = RpcClient::new(args.url);
+    // RpcClient has an inherent get_transaction with a different signature
+    let transaction = RandomnessRpc::get_transaction(&rpc, &args.signature)
+        .await?
+        .ok_or_else(|| format!("Transaction {} not found", args.signature))?;
+
+    match RequestTag::from_transaction(&transaction) {
+        Some(tag) => {
+            println!("tag: {}", tag.label);
+            match tag.version {
+                Some(version) => println!("built with {}", version),
+                None => println!("built with an unrecorded version"),
+            }
+        }
+        None => println!("untagged"),
+    }
+
+    let Some(schemas) = schemas else {
+        return Ok(());
+    };
+    for event in parse_events_from_logs(&transaction.log_messages) {
+        let RandomnessServiceEvent::Requested(event) = event else {
+            continue;
+        };
+        match schemas.decode_callback_args(&event.callback.ix_data) {
+            Some(decoded) => println!(
+                "request {}: {}",
+                event.request,
+                serde_json::to_string(&decoded)?
+            ),
+            None => println!("request {}: no schema for its callback", event.request),
+        }
+    }
+    Ok(())
+}
+
+fn explain(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let [code] = args else {
+        return Err(USAGE.into());
+    };
+    let code: u32 = code
+        .parse()
+        .map_err(|e| format!("Invalid code {}: {}", code, e))?;
+    let explanation = explain_error_code(code).ok_or_else(|| {
+        format!(
+            "Unknown error code {} (solana-randomness-service-lite {})",
+            code,
+            solana_randomness_service_lite::CRATE_VERSION
+        )
+    })?;
+    println!("{}", explanation);
+    Ok(())
+}
+
+fn block_on(
+    command: impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(command)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("snapshot") => parse_snapshot_args(&args[1..])
+            .map_err(|e| e.into())
+            .and_then(|args| block_on(snapshot(args))),
+        Some("health") => parse_health_args(&args[1..])
+            .map_err(|e| e.into())
+            .and_then(|args| block_on(health(args))),
+        Some("identify-disc") => parse_identify_disc_args(&args[1..])
+            .map_err(|e| e.into())
+            .and_then(identify_disc),
+        Some("stats") => parse_stats_args(&args[1..])
+            .map_err(|e| e.into())
+            .and_then(|args| block_on(stats(args))),
+        Some("inspect") => parse_inspect_args(&args[1..])
+            .map_err(|e| e.into())
+            .and_then(|args| block_on(inspect(args))),
+        Some("explain") => explain(&args[1..]),
+        _ => Err(USAGE.into()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
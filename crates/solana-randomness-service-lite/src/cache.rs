@@ -0,0 +1,74 @@
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// A recent randomness result a consumer program reuses for low-stakes features instead of
+/// requesting fresh randomness every time, stored in a PDA seeded with
+/// [`CachedRandomness::SEED`] and the cache's authority.
+///
+/// Written by the example program's `get_or_request` and `consume_cached_randomness`. A
+/// cached result is public and shared by every reader until it is refreshed, so it must never
+/// decide anything of value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct CachedRandomness {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The account the cache belongs to.
+    pub authority: Pubkey,
+    /// The number of bytes requested on every refresh.
+    pub num_bytes: u8,
+    /// The slot the cached result was delivered at. Zero until the first result.
+    pub slot: u64,
+    /// The number of valid bytes in `result`.
+    pub result_len: u8,
+    /// The cached result, zero padded to the service's 32 byte maximum.
+    pub result: [u8; 32],
+    /// The request that will refresh the cache, or the default pubkey if none is pending.
+    pub pending_request: Pubkey,
+    /// The slot the pending request was made at.
+    pub pending_slot: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+impl CachedRandomness {
+    /// The Anchor account discriminator, `sha256("account:CachedRandomness")[..8]`.
+    pub const DISCRIMINATOR: [u8; 8] = [65, 213, 143, 135, 39, 194, 238, 135];
+
+    /// The seed prefix of the cache PDA, `[SEED, authority]`.
+    pub const SEED: &'static [u8] = b"CACHED_RANDOMNESS";
+
+    /// The account space of a cache, including the discriminator.
+    pub const SPACE: usize = 8 + 1 + 32 + 1 + 8 + 1 + 32 + 32 + 8 + RESERVED_LEN;
+
+    /// The cache address and bump for an authority of the consumer program.
+    pub fn address(consumer_program: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, authority.as_ref()], consumer_program)
+    }
+
+    /// The cached result, or `None` if the cache has not received one yet.
+    pub fn latest(&self) -> Option<&[u8]> {
+        if self.slot == 0 {
+            return None;
+        }
+        self.result.get(..usize::from(self.result_len))
+    }
+
+    /// The cached result if it is at most `max_age_slots` old at `slot`, the result
+    /// `get_or_request` would serve.
+    pub fn fresh(&self, slot: u64, max_age_slots: u64) -> Option<&[u8]> {
+        self.latest()
+            .filter(|_| slot.saturating_sub(self.slot) <= max_age_slots)
+    }
+
+    /// The request that will refresh the cache, if one is pending.
+    pub fn pending_request(&self) -> Option<Pubkey> {
+        (self.pending_request != Pubkey::default()).then_some(self.pending_request)
+    }
+
+    pub fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        if buf.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        deserialize_with_reserved(&buf[8..])
+    }
+}
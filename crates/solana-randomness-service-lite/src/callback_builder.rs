@@ -0,0 +1,94 @@
+use crate::*;
+
+/// Builds a [`Callback`] one account at a time, in the order the callback's accounts struct
+/// declares them, and checks it when it is [built](Self::build) instead of when the oracle
+/// invokes it.
+///
+/// ```ignore
+/// let (callback, len) = Callback::builder(ID)
+///     .with_state_signer()
+///     .with_readonly_account(request)
+///     .with_writable_account(result_feed)
+///     .with_ix_data(anchor_sighash("consume_feed_randomness").to_vec())
+///     .build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct CallbackBuilder {
+    program_id: Pubkey,
+    accounts: Vec<AccountMetaBorsh>,
+    ix_data: Vec<u8>,
+    indexed_tag: Option<IndexedTag>,
+}
+
+impl CallbackBuilder {
+    pub fn new(program_id: Pubkey) -> Self {
+        Self {
+            program_id,
+            accounts: Vec::new(),
+            ix_data: Vec::new(),
+            indexed_tag: None,
+        }
+    }
+
+    /// Appends an account the callback only reads.
+    pub fn with_readonly_account(self, pubkey: Pubkey) -> Self {
+        self.with_account(AccountMeta::new_readonly(pubkey, false))
+    }
+
+    /// Appends an account the callback writes to.
+    pub fn with_writable_account(self, pubkey: Pubkey) -> Self {
+        self.with_account(AccountMeta::new(pubkey, false))
+    }
+
+    /// Appends a read-only signer. Only [`RANDOMNESS_SERVICE_STATE`] can sign a callback, so
+    /// any other key fails [`build`](Self::build).
+    pub fn with_signer(self, pubkey: Pubkey) -> Self {
+        self.with_account(AccountMeta::new_readonly(pubkey, true))
+    }
+
+    /// Appends the service's state account as a signer, which callbacks check to know the
+    /// service invoked them.
+    pub fn with_state_signer(self) -> Self {
+        self.with_signer(RANDOMNESS_SERVICE_STATE)
+    }
+
+    /// Appends an account with the meta's flags.
+    pub fn with_account(mut self, meta: AccountMeta) -> Self {
+        self.accounts.push(meta.into());
+        self
+    }
+
+    /// Sets the instruction data, starting with the callback's 8 byte discriminator, e.g. an
+    /// [`anchor_sighash`].
+    pub fn with_ix_data(mut self, ix_data: Vec<u8>) -> Self {
+        self.ix_data = ix_data;
+        self
+    }
+
+    /// Inserts `tag` and `index` after the discriminator when the callback is built, see
+    /// [`Callback::with_indexed_tag`]. Can be set before or after the instruction data.
+    pub fn with_indexed_tag(mut self, tag: u64, index: u16) -> Self {
+        self.indexed_tag = Some(IndexedTag::new(tag, index));
+        self
+    }
+
+    /// The callback and its serialized length, the bytes it adds to the request instruction
+    /// and account.
+    ///
+    /// Fails with [`LiteError::CallbackDiscriminatorMissing`] if the instruction data is
+    /// shorter than a discriminator, and otherwise with the errors of
+    /// [`Callback::validate`].
+    pub fn build(self) -> Result<(Callback, usize), ProgramError> {
+        if ix_data_parts(&self.ix_data).is_none() {
+            return Err(LiteError::CallbackDiscriminatorMissing.into());
+        }
+        let mut callback = Callback::new(self.program_id, self.accounts, self.ix_data);
+        if let Some(tag) = self.indexed_tag {
+            callback = callback.with_indexed_tag(tag.tag, tag.index)?;
+        }
+        callback.validate()?;
+
+        let len = callback.serialized_len();
+        Ok((callback, len))
+    }
+}
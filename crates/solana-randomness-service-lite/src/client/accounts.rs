@@ -0,0 +1,51 @@
+use crate::client::*;
+use crate::*;
+use solana_sdk::account::Account;
+
+/// Decodes every request account in the list, skipping accounts owned by another program or
+/// holding a different account type.
+pub fn decode_request_accounts(
+    accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+) -> Vec<(Pubkey, SimpleRandomnessV1Account)> {
+    decode_request_accounts_for(accounts, &SimpleRandomnessV1Account::owner())
+}
+
+/// Decodes every request account in the list owned by the given service program.
+pub fn decode_request_accounts_for(
+    accounts: impl IntoIterator<Item = (Pubkey, Account)>,
+    program_id: &Pubkey,
+) -> Vec<(Pubkey, SimpleRandomnessV1Account)> {
+    accounts
+        .into_iter()
+        .filter(|(_, account)| account.owner == *program_id)
+        .filter_map(|(pubkey, account)| {
+            SimpleRandomnessV1Account::try_deserialize(&mut &account.data[..])
+                .ok()
+                .map(|request| (pubkey, request))
+        })
+        .collect()
+}
+
+/// Fetches and decodes every open request account owned by the randomness service, along with
+/// the slot they were fetched at.
+///
+/// Uses `getProgramAccounts`, which many paid providers disable. [`discover_requests`] falls
+/// back to scanning the program's signatures on those endpoints.
+pub async fn fetch_request_accounts<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+) -> Result<(u64, Vec<(Pubkey, SimpleRandomnessV1Account)>), LiteClientError> {
+    fetch_request_accounts_for(rpc, &SimpleRandomnessV1Account::owner()).await
+}
+
+/// Fetches and decodes every open request account owned by the given service program, e.g. the
+/// `program_id` of overridden [`ServiceAddresses`].
+pub async fn fetch_request_accounts_for<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    program_id: &Pubkey,
+) -> Result<(u64, Vec<(Pubkey, SimpleRandomnessV1Account)>), LiteClientError> {
+    let (slot, accounts) = rpc
+        .get_program_accounts(program_id, SimpleRandomnessV1Account::DISCRIMINATOR)
+        .await?;
+
+    Ok((slot, decode_request_accounts_for(accounts, program_id)))
+}
@@ -0,0 +1,170 @@
+use crate::client::serde_utils::*;
+use crate::client::FundingBreakdown;
+use crate::*;
+use serde::Serialize;
+use solana_sdk::signature::Signature;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Receives a copy of every randomness request the [`RequestBuilder`](crate::client::RequestBuilder)
+/// builds and sends.
+///
+/// The builder logs and discards any error returned by [`AuditSink::record`], so a failing sink
+/// never blocks a request.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: AuditEvent) -> std::io::Result<()>;
+}
+
+/// The stage of the request lifecycle an [`AuditEvent`] was recorded at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AuditStage {
+    /// The request transaction was built.
+    Built,
+    /// The request transaction was confirmed.
+    Sent,
+}
+
+/// An account passed to the request instruction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AuditAccount {
+    #[serde(serialize_with = "serialize_display")]
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// The exact request instruction emitted by the builder.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AuditEvent {
+    pub stage: AuditStage,
+    /// The request account created by the service.
+    #[serde(serialize_with = "serialize_display")]
+    pub request: Pubkey,
+    /// The account paying for the request.
+    #[serde(serialize_with = "serialize_display")]
+    pub payer: Pubkey,
+    /// The randomness service program ID.
+    #[serde(serialize_with = "serialize_display")]
+    pub program_id: Pubkey,
+    /// The serialized instruction data, base64 encoded in JSON.
+    #[serde(serialize_with = "serialize_base64")]
+    pub instruction_data: Vec<u8>,
+    /// The instruction accounts, in order.
+    pub accounts: Vec<AuditAccount>,
+    /// The lamports the payer spends on the request.
+    pub funding: FundingBreakdown,
+    /// The unix timestamp, in milliseconds, the request was built.
+    pub built_at: u64,
+    /// The unix timestamp, in milliseconds, the request was confirmed.
+    pub sent_at: Option<u64>,
+    /// The signature of the confirmed request transaction.
+    #[serde(serialize_with = "serialize_opt_display")]
+    pub signature: Option<Signature>,
+}
+
+impl AuditEvent {
+    pub fn new(
+        instruction: &Instruction,
+        request: &Pubkey,
+        payer: &Pubkey,
+        funding: FundingBreakdown,
+    ) -> Self {
+        Self {
+            stage: AuditStage::Built,
+            request: *request,
+            payer: *payer,
+            program_id: instruction.program_id,
+            instruction_data: instruction.data.clone(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|a| AuditAccount {
+                    pubkey: a.pubkey,
+                    is_signer: a.is_signer,
+                    is_writable: a.is_writable,
+                })
+                .collect(),
+            funding,
+            built_at: now_millis(),
+            sent_at: None,
+            signature: None,
+        }
+    }
+
+    /// Marks the event as confirmed with the given transaction signature.
+    pub fn mark_sent(&mut self, signature: Signature) {
+        self.stage = AuditStage::Sent;
+        self.sent_at = Some(now_millis());
+        self.signature = Some(signature);
+    }
+}
+
+/// Appends each [`AuditEvent`] as a line of JSON to a file.
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    /// Opens the file in append mode, creating it if needed.
+    pub fn new<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, event: AuditEvent) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(&event)?;
+        line.push(b'\n');
+
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        file.write_all(&line)
+    }
+}
+
+/// Keeps every [`AuditEvent`] in memory. Intended for tests.
+#[derive(Default)]
+pub struct MemoryAuditSink {
+    events: Mutex<Vec<AuditEvent>>,
+}
+
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the recorded events, oldest first.
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events
+            .lock()
+            .map(|events| events.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    fn record(&self, event: AuditEvent) -> std::io::Result<()> {
+        self.events
+            .lock()
+            .map_err(|e| std::io::Error::other(e.to_string()))?
+            .push(event);
+        Ok(())
+    }
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
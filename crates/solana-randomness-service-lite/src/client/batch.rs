@@ -0,0 +1,263 @@
+use crate::client::*;
+use crate::*;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Splits `total_bytes` into request sizes the service accepts: as many [`NumBytes::MAX`]
+/// requests as fit, followed by one smaller request for the remainder, if any. Zero bytes
+/// needs no requests.
+pub fn split_into_requests(total_bytes: usize) -> Vec<NumBytes> {
+    let max = usize::from(MAX_NUM_BYTES);
+    let mut parts = vec![NumBytes::MAX; total_bytes / max];
+    let remainder = total_bytes % max;
+    // Less than MAX_NUM_BYTES, so valid unless zero
+    parts.extend(NumBytes::new(remainder as u8).ok());
+    parts
+}
+
+/// Reassembles the results of a [`split_into_requests`] batch. `parts` must be in the order
+/// the sizes were returned, which is also the order [`RequestBatch::send`] sends them in; the
+/// results are concatenated in that order, so the first request's bytes come first.
+pub fn combine_results(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+/// The requests that together deliver more bytes than one request can, built by
+/// [`RequestBuilder::num_bytes_total`].
+#[derive(Clone)]
+pub struct RequestBatch {
+    requests: Vec<RequestBuilder>,
+}
+
+/// The requests of a [`RequestBatch`] that were sent, in the batch's order.
+#[derive(Clone, Debug)]
+pub struct SentBatch {
+    pub requests: Vec<SentRequest>,
+}
+
+/// The role of an account in a batch, reported by [`LiteClientError::DuplicateAccountUsage`].
+/// Indices are positions in the batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccountRole {
+    /// The payer of the batch's requests.
+    Payer,
+    /// The request account of the request at the index.
+    Request(usize),
+    /// The escrow the service derives for the request at the index.
+    Escrow(usize),
+}
+
+/// An account a batch would use in more than one role, e.g. a request keypair passed twice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountConflict {
+    pub pubkey: Pubkey,
+    /// Every role the account appears in, in batch order, payer first.
+    pub roles: Vec<AccountRole>,
+}
+
+impl std::fmt::Display for AccountConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} used as ", self.pubkey)?;
+        for (i, role) in self.roles.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match role {
+                AccountRole::Payer => f.write_str("payer")?,
+                AccountRole::Request(index) => write!(f, "request {}", index)?,
+                AccountRole::Escrow(index) => write!(f, "escrow {}", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when a batch is only partially sent.
+#[derive(Debug)]
+pub struct BatchSendError {
+    /// The requests sent before the failure. They will still be fulfilled and paid for.
+    pub sent: Vec<SentRequest>,
+    pub error: LiteClientError,
+}
+
+impl std::fmt::Display for BatchSendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to send batch request {}: {}",
+            self.sent.len(),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for BatchSendError {}
+
+/// The error returned when awaiting a batch.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BatchAwaitError {
+    /// Awaiting the request at `index` failed.
+    Await { index: usize, error: AwaitError },
+    /// The request at `index` errored, or its callback failed, so its bytes never reached the
+    /// program.
+    Failed { index: usize, request: Pubkey },
+    /// The request at `index` was closed, but no settle transaction for it was found.
+    MissingResult { index: usize, request: Pubkey },
+}
+
+impl std::fmt::Display for BatchAwaitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchAwaitError::Await { index, error } => {
+                write!(f, "Failed awaiting batch request {}: {}", index, error)
+            }
+            BatchAwaitError::Failed { index, request } => {
+                write!(f, "Batch request {} ({}) failed", index, request)
+            }
+            BatchAwaitError::MissingResult { index, request } => {
+                write!(
+                    f,
+                    "No settle transaction found for batch request {} ({})",
+                    index, request
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchAwaitError {}
+
+impl RequestBatch {
+    pub(crate) fn new(requests: Vec<RequestBuilder>) -> Self {
+        Self { requests }
+    }
+
+    /// The batch's requests, in order.
+    pub fn requests(&self) -> &[RequestBuilder] {
+        &self.requests
+    }
+
+    /// The lamports the payer will spend on every request in the batch.
+    pub fn total_lamports(&self) -> Result<u64, LiteClientError> {
+        self.requests.iter().map(|r| Ok(r.funding()?.total())).sum()
+    }
+
+    /// Checks that the request accounts can be used for the batch's requests, in order: no
+    /// request account or derived escrow is used twice and no payer is used as a request
+    /// account. Fails with [`LiteClientError::DuplicateAccountUsage`] listing every conflict,
+    /// in the order the accounts first appear, so the input can be fixed in one pass.
+    pub fn check_accounts(&self, requests: &[Pubkey]) -> Result<(), LiteClientError> {
+        let mut usage: Vec<AccountConflict> = Vec::new();
+        for builder in &self.requests {
+            use_account(&mut usage, builder.payer(), AccountRole::Payer);
+        }
+        for (index, (builder, request)) in self.requests.iter().zip(requests).enumerate() {
+            use_account(&mut usage, *request, AccountRole::Request(index));
+            use_account(
+                &mut usage,
+                builder.escrow(request),
+                AccountRole::Escrow(index),
+            );
+        }
+
+        usage.retain(|u| u.roles.len() > 1);
+        if usage.is_empty() {
+            Ok(())
+        } else {
+            Err(LiteClientError::DuplicateAccountUsage(usage))
+        }
+    }
+
+    /// Builds the batch's unsigned request transactions with request keypairs chosen by the
+    /// caller, one per request in order, e.g. for deterministic tests. The accounts are
+    /// checked with [`check_accounts`](Self::check_accounts) before anything is built, since
+    /// a keypair passed twice would only fail once the first request is on chain. Fails with
+    /// [`ProgramError::NotEnoughAccountKeys`] unless there is one keypair per request.
+    pub fn build_with_keypairs(
+        &self,
+        requests: Vec<Keypair>,
+        recent_blockhash: Hash,
+    ) -> Result<Vec<BuiltRequest>, LiteClientError> {
+        if requests.len() != self.requests.len() {
+            return Err(LiteClientError::Program(ProgramError::NotEnoughAccountKeys));
+        }
+        let pubkeys: Vec<Pubkey> = requests.iter().map(Signer::pubkey).collect();
+        self.check_accounts(&pubkeys)?;
+
+        self.requests
+            .iter()
+            .zip(requests)
+            .map(|(builder, request)| builder.build_with(request, recent_blockhash))
+            .collect()
+    }
+
+    /// Sends the requests one at a time, in order.
+    pub async fn send<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &dyn Signer,
+    ) -> Result<SentBatch, BatchSendError> {
+        let mut sent = Vec::with_capacity(self.requests.len());
+        for request in self.requests.iter() {
+            match request.send(rpc, payer).await {
+                Ok(request) => sent.push(request),
+                Err(error) => return Err(BatchSendError { sent, error }),
+            }
+        }
+        Ok(SentBatch { requests: sent })
+    }
+}
+
+fn use_account(usage: &mut Vec<AccountConflict>, pubkey: Pubkey, role: AccountRole) {
+    match usage.iter_mut().find(|u| u.pubkey == pubkey) {
+        Some(u) if !u.roles.contains(&role) => u.roles.push(role),
+        Some(_) => {}
+        None => usage.push(AccountConflict {
+            pubkey,
+            roles: vec![role],
+        }),
+    }
+}
+
+/// Waits for every request in the batch with [`await_fulfillment_with_pubsub`], then reads
+/// each result from its settle transaction's logs and returns them combined with
+/// [`combine_results`]. `timeout` covers the whole batch.
+pub async fn await_batch_with_pubsub<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    pubsub: &[Arc<dyn RandomnessPubsub>],
+    batch: &SentBatch,
+    timeout: Duration,
+) -> Result<Vec<u8>, BatchAwaitError> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut results = Vec::with_capacity(batch.requests.len());
+
+    for (index, sent) in batch.requests.iter().enumerate() {
+        let request = sent.request;
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        let status = await_fulfillment_with_pubsub(rpc, pubsub, &request, remaining)
+            .await
+            .map_err(|error| BatchAwaitError::Await { index, error })?;
+        if !matches!(status, RequestStatus::Closed) {
+            return Err(BatchAwaitError::Failed { index, request });
+        }
+
+        let event = settled_event(rpc, &request)
+            .await
+            .map_err(|e| BatchAwaitError::Await {
+                index,
+                error: e.into(),
+            })?;
+        match event {
+            Some((_, event)) if event.is_success => results.push(event.randomness),
+            Some(_) => return Err(BatchAwaitError::Failed { index, request }),
+            None => return Err(BatchAwaitError::MissingResult { index, request }),
+        }
+    }
+
+    let parts: Vec<&[u8]> = results.iter().map(Vec::as_slice).collect();
+    Ok(combine_results(&parts))
+}
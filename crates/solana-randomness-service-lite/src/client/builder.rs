@@ -0,0 +1,546 @@
+use crate::addresses::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::client::*;
+use crate::*;
+use solana_program::rent::Rent;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::sync::Arc;
+
+/// Builds and sends a `simple_randomness_v1` request from off-chain.
+///
+/// The service program creates the request account and the escrow token account itself, so the
+/// transaction only contains the request instruction, signed by the payer and a freshly
+/// generated request keypair.
+#[derive(Clone)]
+pub struct RequestBuilder {
+    program_id: Pubkey,
+    state: Pubkey,
+    mint: Pubkey,
+    payer: Pubkey,
+    num_bytes: u8,
+    callback: Callback,
+    options: Option<TransactionOptions>,
+    cost_per_byte: u64,
+    rent: Rent,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    error_observer: Option<Arc<dyn ErrorObserver>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    tag: Option<RequestTag>,
+    delegate_funding: Option<DelegateFunding>,
+    referral_fee: Option<ReferralFee>,
+    max_cost: Option<u64>,
+}
+
+/// A request transaction that has been built but not yet signed.
+pub struct BuiltRequest {
+    /// The unsigned request transaction.
+    pub transaction: Transaction,
+    /// The ephemeral request account. Must sign the transaction.
+    pub request: Keypair,
+    /// The lamports the payer will spend on the request.
+    pub funding: FundingBreakdown,
+    /// The unix timestamp, in milliseconds, the request was built.
+    pub built_at: u64,
+}
+
+/// A request transaction that has been confirmed.
+#[derive(Clone, Debug)]
+pub struct SentRequest {
+    /// The request account created by the service.
+    pub request: Pubkey,
+    /// The signature of the request transaction.
+    pub signature: Signature,
+}
+
+/// The result of [`RequestBuilder::send_idempotent`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum IdempotentSend {
+    /// No request existed for the job, so a new one was sent and confirmed.
+    Sent(SentRequest),
+    /// The job's request was created by an earlier send.
+    Existing(Pubkey),
+}
+
+impl IdempotentSend {
+    /// The job's request account.
+    pub fn request(&self) -> Pubkey {
+        match self {
+            IdempotentSend::Sent(sent) => sent.request,
+            IdempotentSend::Existing(request) => *request,
+        }
+    }
+}
+
+impl RequestBuilder {
+    pub fn new(payer: Pubkey, num_bytes: u8, callback: Callback) -> Self {
+        Self {
+            program_id: ID,
+            state: RANDOMNESS_SERVICE_STATE,
+            mint: RANDOMNESS_SERVICE_REWARD_MINT,
+            payer,
+            num_bytes,
+            callback,
+            options: None,
+            cost_per_byte: 0,
+            rent: Rent::default(),
+            audit_sink: None,
+            error_observer: None,
+            rate_limiter: None,
+            tag: None,
+            delegate_funding: None,
+            referral_fee: None,
+            max_cost: None,
+        }
+    }
+
+    /// Overrides the randomness service program ID.
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Overrides the randomness service state account.
+    pub fn state(mut self, service_state: Pubkey) -> Self {
+        self.state = service_state;
+        self
+    }
+
+    /// Overrides the mint used to fund the escrow.
+    pub fn mint(mut self, mint: Pubkey) -> Self {
+        self.mint = mint;
+        self
+    }
+
+    /// Sets the compute budget and priority fee the oracle uses to settle the request. When
+    /// unset, the budget is [`TransactionOptions::advised_for_callback`].
+    pub fn options(mut self, options: Option<TransactionOptions>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the service's cost per randomness byte, read from the service state account.
+    /// Only used to compute the [`FundingBreakdown`].
+    pub fn cost_per_byte(mut self, cost_per_byte: u64) -> Self {
+        self.cost_per_byte = cost_per_byte;
+        self
+    }
+
+    /// Overrides the rent used to compute the [`FundingBreakdown`].
+    pub fn rent(mut self, rent: Rent) -> Self {
+        self.rent = rent;
+        self
+    }
+
+    /// Records every built and sent request to the given sink.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// Shows every error the builder returns to the given observer before returning it.
+    pub fn with_error_observer(mut self, observer: Arc<dyn ErrorObserver>) -> Self {
+        self.error_observer = Some(observer);
+        self
+    }
+
+    /// Waits for a token from the limiter before each send. Share one limiter between every
+    /// builder sending through the same RPC provider.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Sends the tag as a memo after the request instruction, where
+    /// [`RequestTag::from_transaction`] finds it.
+    pub fn with_tag(mut self, tag: RequestTag) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Pays the request's fee from `owner_token_account`, a wrapped SOL token account owned by
+    /// the payer, instead of wrapping it from the payer's lamports. The transaction approves the
+    /// request as the token account's delegate for `approve_amount` before the request
+    /// instruction, which passes the token account as an extra account. The payer still pays
+    /// the rent of the request and escrow.
+    ///
+    /// Only services forked to pull the fee through the delegate read the extra account; see
+    /// [`DelegateFunding`]. Set the fork with [`program_id`](Self::program_id), and send
+    /// [`revoke_instruction`](Self::revoke_instruction) once the request is fulfilled.
+    pub fn fund_via_delegate(mut self, owner_token_account: Pubkey, approve_amount: u64) -> Self {
+        self.delegate_funding = Some(DelegateFunding::new(owner_token_account, approve_amount));
+        self
+    }
+
+    /// Pays `lamports` to `recipient` with a transfer after the request instruction, for teams
+    /// reselling randomness. The fee is in the builder's mint: a system transfer for wrapped
+    /// SOL, or a token transfer from the payer's associated token account otherwise. It is
+    /// counted in the [`FundingBreakdown`] and checked against [`max_cost`](Self::max_cost);
+    /// see [`ReferralFee`].
+    pub fn with_referral_fee(mut self, recipient: Pubkey, lamports: u64) -> Self {
+        self.referral_fee = Some(ReferralFee::new(recipient, lamports));
+        self
+    }
+
+    /// Fails building the request with [`LiteClientError::CostCeilingExceeded`] if its
+    /// [`FundingBreakdown::total`], referral fee included, is over `max_cost`.
+    pub fn max_cost(mut self, max_cost: u64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// The instruction revoking the delegate approved by
+    /// [`fund_via_delegate`](Self::fund_via_delegate), signed by the payer. `None` if the
+    /// request is funded by the payer.
+    pub fn revoke_instruction(&self) -> Option<Instruction> {
+        self.delegate_funding
+            .map(|funding| funding.revoke_instruction(&self.payer))
+    }
+
+    /// Splits the request into as many requests as `total_bytes` needs, with
+    /// [`split_into_requests`]. Every request uses this builder's callback and settings, so
+    /// the callback is invoked once per request.
+    pub fn num_bytes_total(self, total_bytes: usize) -> RequestBatch {
+        let requests = split_into_requests(total_bytes)
+            .into_iter()
+            .map(|num_bytes| Self {
+                num_bytes: num_bytes.get(),
+                ..self.clone()
+            })
+            .collect();
+        RequestBatch::new(requests)
+    }
+
+    pub(crate) fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    /// The escrow the service derives for the request account.
+    pub(crate) fn escrow(&self, request: &Pubkey) -> Pubkey {
+        derive_escrow_pubkey(request, &self.mint)
+    }
+
+    /// The lamports the payer will spend on the request.
+    pub fn funding(&self) -> Result<FundingBreakdown, LiteClientError> {
+        Ok(FundingBreakdown {
+            referral_fee: self.referral_fee.map_or(0, |fee| fee.amount),
+            ..FundingBreakdown::new(
+                self.num_bytes,
+                &self.callback,
+                &self.effective_options(),
+                self.cost_per_byte,
+                &self.rent,
+            )?
+        })
+    }
+
+    /// Builds the `simple_randomness_v1` instruction for the given request account.
+    pub fn instruction(&self, request: &Pubkey) -> Result<Instruction, ProgramError> {
+        let escrow = derive_escrow_pubkey(request, &self.mint);
+
+        // Must match SimpleRandomnessV1Request::to_account_metas
+        let mut accounts = vec![
+            AccountMeta::new(*request, true),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(self.state, false),
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        ];
+        if let Some(funding) = &self.delegate_funding {
+            accounts.push(AccountMeta::new(funding.owner_token_account, false));
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: SimpleRandomnessV1Request::data(
+                self.num_bytes,
+                &self.callback,
+                self.effective_options().as_ref(),
+            )?,
+        })
+    }
+
+    /// The instructions of the request transaction for the given request account: the delegate
+    /// approval, if any, the request instruction, the referral fee transfer, if any, and the
+    /// tag memo, if any.
+    pub fn instructions(&self, request: &Pubkey) -> Result<Vec<Instruction>, LiteClientError> {
+        self.observed(self.try_instructions(request))
+    }
+
+    fn try_instructions(&self, request: &Pubkey) -> Result<Vec<Instruction>, LiteClientError> {
+        if let Some(max_cost) = self.max_cost {
+            let cost = self.funding()?.total();
+            if cost > max_cost {
+                return Err(LiteClientError::CostCeilingExceeded { max_cost, cost });
+            }
+        }
+
+        let mut instructions = Vec::with_capacity(4);
+        if let Some(funding) = &self.delegate_funding {
+            let required = self.funding()?.service_cost();
+            if funding.approve_amount < required {
+                return Err(LiteClientError::DelegateAllowanceTooLow {
+                    approved: funding.approve_amount,
+                    required,
+                });
+            }
+            instructions.push(funding.approve_instruction(&self.payer, request));
+        }
+        instructions.push(self.instruction(request)?);
+        instructions.extend(
+            self.referral_fee
+                .map(|fee| fee.instruction(&self.payer, &self.mint)),
+        );
+        instructions.extend(self.tag.as_ref().map(RequestTag::instruction));
+        Ok(instructions)
+    }
+
+    /// Builds the unsigned request transaction with a new request keypair.
+    pub fn build(&self, recent_blockhash: Hash) -> Result<BuiltRequest, LiteClientError> {
+        self.observed(self.try_build(recent_blockhash))
+    }
+
+    fn try_build(&self, recent_blockhash: Hash) -> Result<BuiltRequest, LiteClientError> {
+        self.try_build_with(Keypair::new(), recent_blockhash)
+    }
+
+    /// Builds the request with a request keypair chosen by the caller, see
+    /// [`RequestBatch::build_with_keypairs`].
+    pub(crate) fn build_with(
+        &self,
+        request: Keypair,
+        recent_blockhash: Hash,
+    ) -> Result<BuiltRequest, LiteClientError> {
+        self.observed(self.try_build_with(request, recent_blockhash))
+    }
+
+    fn try_build_with(
+        &self,
+        request: Keypair,
+        recent_blockhash: Hash,
+    ) -> Result<BuiltRequest, LiteClientError> {
+        let instruction = self.instruction(&request.pubkey())?;
+        let funding = self.funding()?;
+
+        let instructions = self.try_instructions(&request.pubkey())?;
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&self.payer));
+        transaction.message.recent_blockhash = recent_blockhash;
+
+        let event = AuditEvent::new(&instruction, &request.pubkey(), &self.payer, funding);
+        let built_at = event.built_at;
+        self.audit(event);
+
+        Ok(BuiltRequest {
+            transaction,
+            request,
+            funding,
+            built_at,
+        })
+    }
+
+    /// Builds the unsigned request transaction with the session's blockhash and records its
+    /// funding in the session.
+    pub async fn build_in_session<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        session: &PayerSession,
+    ) -> Result<(BuiltRequest, FundingPlan), LiteClientError> {
+        self.observed(self.try_build_in_session(rpc, session).await)
+    }
+
+    async fn try_build_in_session<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        session: &PayerSession,
+    ) -> Result<(BuiltRequest, FundingPlan), LiteClientError> {
+        if session.payer() != self.payer {
+            return Err(LiteClientError::PayerMismatch {
+                session: session.payer(),
+                builder: self.payer,
+            });
+        }
+
+        let recent_blockhash = session.blockhash(rpc).await?;
+        let built = self.try_build(recent_blockhash)?;
+        let plan = session.plan(built.request.pubkey(), built.funding);
+        Ok((built, plan))
+    }
+
+    /// Builds the request with [`build_in_session`](Self::build_in_session), then signs and
+    /// sends it. The payer is `Sync`, so the future is `Send` and can be spawned.
+    pub async fn send_in_session<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &(dyn Signer + Sync),
+        session: &PayerSession,
+    ) -> Result<SentRequest, LiteClientError> {
+        self.throttle().await;
+        // Signed before sending, so the future does not hold the signer across an await
+        let signed = self
+            .try_build_in_session(rpc, session)
+            .await
+            .and_then(|(built, _)| sign_built(payer, built));
+        let result = match signed {
+            Ok(built) => self.send_signed(rpc, built).await,
+            Err(e) => Err(e),
+        };
+        self.observed(result)
+    }
+
+    /// Builds, signs, and sends the request transaction.
+    pub async fn send<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &dyn Signer,
+    ) -> Result<SentRequest, LiteClientError> {
+        self.throttle().await;
+        self.observed(self.try_send(rpc, payer).await)
+    }
+
+    async fn try_send<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &dyn Signer,
+    ) -> Result<SentRequest, LiteClientError> {
+        let recent_blockhash = rpc.get_latest_blockhash().await?;
+        let built = self.try_build(recent_blockhash)?;
+        self.send_built(rpc, payer, built).await
+    }
+
+    /// Sends the request for `job_id` unless the journal shows one was already created for it.
+    ///
+    /// A completed journal entry is returned as is. An incomplete entry, left by a client that
+    /// stopped between sending and confirming, is checked against the chain: if the request
+    /// account exists, or a successful transaction touched it before it was settled and
+    /// closed, the entry is completed and returned. Otherwise a new request is journaled and
+    /// sent. A transaction still in flight when the client restarts is not detected, so wait
+    /// for its blockhash to expire, about a minute, before retrying a job.
+    pub async fn send_idempotent<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &dyn Signer,
+        job_id: &str,
+        journal: &dyn Journal,
+    ) -> Result<IdempotentSend, LiteClientError> {
+        self.observed(self.try_send_idempotent(rpc, payer, job_id, journal).await)
+    }
+
+    async fn try_send_idempotent<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &dyn Signer,
+        job_id: &str,
+        journal: &dyn Journal,
+    ) -> Result<IdempotentSend, LiteClientError> {
+        if let Some(entry) = journal.lookup(job_id)? {
+            if entry.completed {
+                return Ok(IdempotentSend::Existing(entry.request));
+            }
+            if request_landed(rpc, &entry.request).await? {
+                journal.mark_complete(job_id)?;
+                return Ok(IdempotentSend::Existing(entry.request));
+            }
+        }
+
+        self.throttle().await;
+        let recent_blockhash = rpc.get_latest_blockhash().await?;
+        let built = self.try_build(recent_blockhash)?;
+        journal.record_intent(job_id, &built.request.pubkey())?;
+        let sent = self.send_built(rpc, payer, built).await?;
+        journal.mark_complete(job_id)?;
+
+        Ok(IdempotentSend::Sent(sent))
+    }
+
+    async fn send_built<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        payer: &dyn Signer,
+        built: BuiltRequest,
+    ) -> Result<SentRequest, LiteClientError> {
+        let built = sign_built(payer, built)?;
+        self.send_signed(rpc, built).await
+    }
+
+    /// Sends a request signed with [`sign_built`].
+    async fn send_signed<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        built: BuiltRequest,
+    ) -> Result<SentRequest, LiteClientError> {
+        let request = built.request.pubkey();
+        let instruction = self.instruction(&request)?;
+
+        let signature = rpc.send_and_confirm_transaction(&built.transaction).await?;
+
+        let mut event = AuditEvent::new(&instruction, &request, &self.payer, built.funding);
+        event.built_at = built.built_at;
+        event.mark_sent(signature);
+        self.audit(event);
+
+        Ok(SentRequest { request, signature })
+    }
+
+    /// The options sent with the request: the caller's, or the advised budget for the callback.
+    fn effective_options(&self) -> Option<TransactionOptions> {
+        self.options
+            .clone()
+            .or_else(|| Some(TransactionOptions::advised_for_callback(&self.callback)))
+    }
+
+    /// Waits for the rate limiter, if any. Done before fetching the blockhash so a long wait
+    /// cannot expire it.
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+    }
+
+    fn observed<T>(&self, result: Result<T, LiteClientError>) -> Result<T, LiteClientError> {
+        if let (Err(e), Some(observer)) = (&result, &self.error_observer) {
+            observer.observe(e);
+        }
+        result
+    }
+
+    fn audit(&self, event: AuditEvent) {
+        if let Some(sink) = &self.audit_sink {
+            if let Err(e) = sink.record(event) {
+                log::warn!("Failed to record randomness request audit event: {}", e);
+            }
+        }
+    }
+}
+
+/// Signs the request transaction with the payer and the request account.
+fn sign_built(
+    payer: &dyn Signer,
+    mut built: BuiltRequest,
+) -> Result<BuiltRequest, LiteClientError> {
+    let recent_blockhash = built.transaction.message.recent_blockhash;
+    built
+        .transaction
+        .try_sign(&[payer, &built.request], recent_blockhash)?;
+    Ok(built)
+}
+
+/// Whether the request transaction landed. Settled requests are closed, so a missing account
+/// falls back to the request's transaction history.
+async fn request_landed<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+) -> Result<bool, LiteClientError> {
+    if rpc.get_account(request).await?.is_some() {
+        return Ok(true);
+    }
+    Ok(rpc
+        .get_signatures_for_address(request, None, 10)
+        .await?
+        .iter()
+        .any(|s| !s.failed))
+}
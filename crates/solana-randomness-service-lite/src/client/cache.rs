@@ -0,0 +1,19 @@
+use crate::client::*;
+use crate::*;
+
+/// Fetches the [`CachedRandomness`] the consumer program keeps for the authority, or `None` if
+/// the authority has no cache yet.
+pub async fn fetch_cached_randomness<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    consumer_program: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Option<CachedRandomness>, LiteClientError> {
+    let (address, _) = CachedRandomness::address(consumer_program, authority);
+    match rpc.get_account(&address).await? {
+        Some(account) if account.owner == *consumer_program => Ok(Some(
+            CachedRandomness::try_deserialize(&mut &account.data[..])?,
+        )),
+        Some(_) => Err(ProgramError::IllegalOwner.into()),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,28 @@
+use crate::client::*;
+
+/// Where the client helpers that depend on time read the current slot.
+///
+/// [`RpcSlotClock`] reads it from the cluster. A `u64` is a fixed slot, so a test can run a
+/// helper at any slot without a cluster, or against a [`MockRpc`](crate::client::mock::MockRpc)
+/// moved with `warp_to_slot`.
+#[async_trait::async_trait]
+pub trait SlotClock: Send + Sync {
+    async fn current_slot(&self) -> Result<u64, LiteClientError>;
+}
+
+#[async_trait::async_trait]
+impl SlotClock for u64 {
+    async fn current_slot(&self) -> Result<u64, LiteClientError> {
+        Ok(*self)
+    }
+}
+
+/// Reads the current slot with [`RandomnessRpc::get_slot`].
+pub struct RpcSlotClock<'a, R: ?Sized>(pub &'a R);
+
+#[async_trait::async_trait]
+impl<R: RandomnessRpc + ?Sized> SlotClock for RpcSlotClock<'_, R> {
+    async fn current_slot(&self) -> Result<u64, LiteClientError> {
+        self.0.get_slot().await
+    }
+}
@@ -0,0 +1,106 @@
+use crate::addresses::{NATIVE_MINT, TOKEN_PROGRAM_ID};
+use crate::client::*;
+use crate::*;
+
+/// The SPL token `Approve` instruction.
+const APPROVE_INSTRUCTION: u8 = 4;
+/// The SPL token `Revoke` instruction.
+const REVOKE_INSTRUCTION: u8 = 5;
+
+/// Funds a request's fee from a wrapped SOL token account through a delegate approval, for
+/// payers that may approve a delegate but not hold wrapped SOL themselves. Set with
+/// [`RequestBuilder::fund_via_delegate`](crate::client::RequestBuilder::fund_via_delegate).
+///
+/// The request transaction approves the request account, which signs it, as the delegate of
+/// `owner_token_account` for `approve_amount`, and passes the token account to the service
+/// after its own accounts. The deployed service does not read it and still wraps the fee from
+/// the payer; this is an extension for forks of the service, which pull the fee into the
+/// escrow with a `transfer` signed by the request. The harness's service stand-in does the
+/// same. Send [`revoke_instruction`](Self::revoke_instruction) once the request is fulfilled
+/// to clear the allowance left over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DelegateFunding {
+    /// The wrapped SOL token account the fee is pulled from, owned by the payer.
+    pub owner_token_account: Pubkey,
+    /// The amount the request may pull. At least the request's
+    /// [`service_cost`](crate::client::FundingBreakdown::service_cost).
+    pub approve_amount: u64,
+}
+
+impl DelegateFunding {
+    pub fn new(owner_token_account: Pubkey, approve_amount: u64) -> Self {
+        Self {
+            owner_token_account,
+            approve_amount,
+        }
+    }
+
+    /// Checks, before the request is sent, that the token account is an SPL token account of
+    /// `owner` holding at least `required` wrapped SOL, e.g. the request's
+    /// [`service_cost`](crate::client::FundingBreakdown::service_cost). Fails with
+    /// [`LiteClientError::MintMismatch`] for another mint and
+    /// [`LiteClientError::InsufficientBalance`] for a short balance.
+    pub async fn preflight<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+        owner: &Pubkey,
+        required: u64,
+    ) -> Result<TokenAccountView, LiteClientError> {
+        let account = rpc
+            .get_account(&self.owner_token_account)
+            .await?
+            .ok_or(ProgramError::UninitializedAccount)?;
+        if account.owner != TOKEN_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId.into());
+        }
+        let view = parse_token_account(&account.data)?;
+        if view.mint != NATIVE_MINT {
+            return Err(LiteClientError::MintMismatch {
+                expected: NATIVE_MINT,
+                got: view.mint,
+            });
+        }
+        if view.owner != *owner {
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        if view.state == TokenAccountStatus::Frozen {
+            return Err(ProgramError::InvalidAccountData.into());
+        }
+        if view.amount < required {
+            return Err(LiteClientError::InsufficientBalance {
+                balance: view.amount,
+                required,
+            });
+        }
+        Ok(view)
+    }
+
+    /// The SPL token `Approve` instruction making `request` the delegate of the token account,
+    /// signed by its `owner`.
+    pub fn approve_instruction(&self, owner: &Pubkey, request: &Pubkey) -> Instruction {
+        let mut data = vec![APPROVE_INSTRUCTION];
+        data.extend(self.approve_amount.to_le_bytes());
+        Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.owner_token_account, false),
+                AccountMeta::new_readonly(*request, false),
+                AccountMeta::new_readonly(*owner, true),
+            ],
+            data,
+        }
+    }
+
+    /// The SPL token `Revoke` instruction clearing the token account's delegate, signed by its
+    /// `owner`.
+    pub fn revoke_instruction(&self, owner: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(self.owner_token_account, false),
+                AccountMeta::new_readonly(*owner, true),
+            ],
+            data: vec![REVOKE_INSTRUCTION],
+        }
+    }
+}
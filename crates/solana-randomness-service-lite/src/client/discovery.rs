@@ -0,0 +1,131 @@
+use crate::client::events::decode_events;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+
+/// The number of the service program's most recent signatures a
+/// [`SignatureScan`](DiscoveryStrategy::SignatureScan) checks when the strategy is negotiated.
+pub const DEFAULT_SCAN_DEPTH: usize = 1_000;
+
+/// How the open request accounts of the service are enumerated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DiscoveryStrategy {
+    /// One `getProgramAccounts` call filtered by the request discriminator. Complete, but
+    /// disabled by many paid RPC providers.
+    ProgramAccounts,
+    /// Reads the requests created in the program's last `depth` transactions from their
+    /// `SimpleRandomnessV1RequestedEvent` and fetches each request account. Works on any
+    /// endpoint, but misses requests older than the scanned transactions and makes one
+    /// `getTransaction` call per signature.
+    SignatureScan { depth: usize },
+}
+
+/// The open request accounts found by [`discover_requests`] and how they were found.
+#[derive(Clone, Debug)]
+pub struct DiscoveredRequests {
+    /// The slot the accounts were fetched at. A signature scan reports the slot of the newest
+    /// program transaction it saw.
+    pub slot: u64,
+    pub strategy: DiscoveryStrategy,
+    pub requests: Vec<(Pubkey, SimpleRandomnessV1Account)>,
+}
+
+/// The leading `callback_pid`, `user`, and `request` fields of
+/// `SimpleRandomnessV1RequestedEvent`.
+#[derive(BorshDeserialize)]
+struct RequestedEvent {
+    _callback_pid: Pubkey,
+    _user: Pubkey,
+    request: Pubkey,
+}
+
+/// Enumerates the open request accounts owned by the service program.
+///
+/// With `strategy` set to `None` the strategy is negotiated: `getProgramAccounts` is tried
+/// first, and an endpoint that fails with [`LiteClientError::ProgramAccountsDisabled`] is
+/// scanned with [`DiscoveryStrategy::SignatureScan`] to [`DEFAULT_SCAN_DEPTH`] instead. Any
+/// other error is returned as is. The strategy used is reported in the result.
+pub async fn discover_requests<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    program_id: &Pubkey,
+    strategy: Option<DiscoveryStrategy>,
+) -> Result<DiscoveredRequests, LiteClientError> {
+    match strategy {
+        Some(DiscoveryStrategy::ProgramAccounts) => program_accounts(rpc, program_id).await,
+        Some(DiscoveryStrategy::SignatureScan { depth }) => {
+            signature_scan(rpc, program_id, depth).await
+        }
+        None => match program_accounts(rpc, program_id).await {
+            Err(LiteClientError::ProgramAccountsDisabled(e)) => {
+                log::warn!(
+                    "getProgramAccounts is disabled ({}), scanning the last {} signatures",
+                    e,
+                    DEFAULT_SCAN_DEPTH
+                );
+                signature_scan(rpc, program_id, DEFAULT_SCAN_DEPTH).await
+            }
+            result => result,
+        },
+    }
+}
+
+async fn program_accounts<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    program_id: &Pubkey,
+) -> Result<DiscoveredRequests, LiteClientError> {
+    let (slot, requests) = fetch_request_accounts_for(rpc, program_id).await?;
+    Ok(DiscoveredRequests {
+        slot,
+        strategy: DiscoveryStrategy::ProgramAccounts,
+        requests,
+    })
+}
+
+async fn signature_scan<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    program_id: &Pubkey,
+    depth: usize,
+) -> Result<DiscoveredRequests, LiteClientError> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+    while signatures.len() < depth {
+        let page = rpc
+            .get_signatures_for_address(program_id, before, depth - signatures.len())
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(last.signature);
+        signatures.extend(page);
+    }
+    let slot = signatures.first().map(|s| s.slot).unwrap_or_default();
+
+    let mut candidates: Vec<Pubkey> = Vec::new();
+    for signature in signatures.iter().filter(|s| !s.failed) {
+        let Some(transaction) = rpc.get_transaction(&signature.signature).await? else {
+            continue;
+        };
+        for event in
+            decode_events::<RequestedEvent>(&transaction, "SimpleRandomnessV1RequestedEvent")
+        {
+            if !candidates.contains(&event.request) {
+                candidates.push(event.request);
+            }
+        }
+    }
+
+    let mut accounts = Vec::new();
+    for request in candidates {
+        if let Some(account) = rpc.get_account(&request).await? {
+            accounts.push((request, account));
+        }
+    }
+
+    Ok(DiscoveredRequests {
+        slot,
+        strategy: DiscoveryStrategy::SignatureScan { depth },
+        requests: decode_request_accounts_for(accounts, program_id),
+    })
+}
@@ -0,0 +1,256 @@
+use crate::client::AccountConflict;
+use crate::{LiteError, ProgramError, Pubkey};
+
+/// The error returned by the client helpers.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LiteClientError {
+    /// The RPC request failed.
+    Rpc(String),
+    /// Building or serializing the request failed.
+    Program(ProgramError),
+    /// Signing the transaction failed.
+    Signing(String),
+    /// The RPC request did not complete in time.
+    Timeout,
+    /// Reading or writing the request [`Journal`](crate::client::Journal) failed.
+    Journal(std::io::Error),
+    /// Reading or writing the [`RequestStore`](crate::client::RequestStore) of an
+    /// [`Indexer`](crate::client::Indexer) failed.
+    Store(std::io::Error),
+    /// The endpoint does not serve `getProgramAccounts`. Many paid providers disable it; see
+    /// [`DiscoveryStrategy`](crate::client::DiscoveryStrategy) for the fallback.
+    ProgramAccountsDisabled(String),
+    /// A [`RequestTag`](crate::client::RequestTag) label cannot be sent as a memo.
+    InvalidTag(String),
+    /// A builder was used with a [`PayerSession`](crate::client::PayerSession) for another
+    /// payer.
+    PayerMismatch { session: Pubkey, builder: Pubkey },
+    /// The amount approved by [`DelegateFunding`](crate::client::DelegateFunding) does not
+    /// cover the request's service cost.
+    DelegateAllowanceTooLow { approved: u64, required: u64 },
+    /// The request's mint is not the mint recorded in the service's state account, see
+    /// [`preflight_request`](crate::client::preflight_request), or a delegate funding account
+    /// does not hold wrapped SOL.
+    MintMismatch { expected: Pubkey, got: Pubkey },
+    /// The payer cannot afford the request and its transaction fee, see
+    /// [`build_request_transaction`](crate::client::build_request_transaction), or a delegate
+    /// funding account cannot cover its service cost.
+    InsufficientBalance { balance: u64, required: u64 },
+    /// The request costs more than the `max_cost` of its
+    /// [`RequestIntent`](crate::client::RequestIntent) or
+    /// [`RequestBuilder`](crate::client::RequestBuilder::max_cost).
+    CostCeilingExceeded { max_cost: u64, cost: u64 },
+    /// A [`RequestBatch`](crate::client::RequestBatch) would use accounts in more than one
+    /// role. Lists every conflict, see
+    /// [`RequestBatch::check_accounts`](crate::client::RequestBatch::check_accounts).
+    DuplicateAccountUsage(Vec<AccountConflict>),
+    /// A task or future inside a client helper panicked or exited without a result. The
+    /// helper returns this instead of propagating the panic.
+    Internal(String),
+    #[cfg(feature = "test-variants")]
+    #[doc(hidden)]
+    __TestVariant,
+}
+
+impl std::fmt::Display for LiteClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiteClientError::Rpc(e) => write!(f, "RPC error: {}", e),
+            LiteClientError::Program(e) => write!(f, "Program error: {}", e),
+            LiteClientError::Signing(e) => write!(f, "Signing error: {}", e),
+            LiteClientError::Timeout => write!(f, "RPC request timed out"),
+            LiteClientError::Journal(e) => write!(f, "Journal error: {}", e),
+            LiteClientError::Store(e) => write!(f, "Request store error: {}", e),
+            LiteClientError::ProgramAccountsDisabled(e) => {
+                write!(f, "getProgramAccounts is disabled on this endpoint: {}", e)
+            }
+            LiteClientError::InvalidTag(e) => write!(f, "Invalid request tag: {}", e),
+            LiteClientError::PayerMismatch { session, builder } => write!(
+                f,
+                "The builder's payer {} is not the session's payer {}",
+                builder, session
+            ),
+            LiteClientError::DelegateAllowanceTooLow { approved, required } => write!(
+                f,
+                "The delegate is approved for {} but the request costs {}",
+                approved, required
+            ),
+            LiteClientError::MintMismatch { expected, got } => write!(
+                f,
+                "The mint {} is not the service state's mint {}",
+                got, expected
+            ),
+            LiteClientError::InsufficientBalance { balance, required } => write!(
+                f,
+                "The payer has {} lamports but the request needs {}",
+                balance, required
+            ),
+            LiteClientError::CostCeilingExceeded { max_cost, cost } => write!(
+                f,
+                "The request costs {} lamports, over its ceiling of {}",
+                cost, max_cost
+            ),
+            LiteClientError::DuplicateAccountUsage(conflicts) => {
+                f.write_str("Accounts used more than once in the batch: ")?;
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{}", conflict)?;
+                }
+                Ok(())
+            }
+            LiteClientError::Internal(e) => write!(f, "Internal error: {}", e),
+            #[cfg(feature = "test-variants")]
+            LiteClientError::__TestVariant => write!(f, "Test variant"),
+        }
+    }
+}
+
+impl std::error::Error for LiteClientError {}
+
+/// The category of a [`LiteClientError`], for counting failures by cause.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Borsh serialization of an instruction or account failed.
+    Serialization,
+    /// The RPC endpoint failed or refused the request.
+    Rpc,
+    /// The request was rejected before it was sent: a callback or input this crate checks, a
+    /// mismatched payer, or a missing signer.
+    Validation,
+    /// The RPC request did not complete in time.
+    Timeout,
+    /// Any other program error, including the service's own.
+    Program,
+    /// Reading or writing the request journal failed.
+    Io,
+    /// A task or future inside the client panicked.
+    Internal,
+}
+
+impl ErrorKind {
+    /// The metric label of the kind, e.g. `error.kind=rpc`. Labels never change once released.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorKind::Serialization => "serialization",
+            ErrorKind::Rpc => "rpc",
+            ErrorKind::Validation => "validation",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Program => "program",
+            ErrorKind::Io => "io",
+            ErrorKind::Internal => "internal",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl LiteClientError {
+    /// The category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            LiteClientError::Rpc(_) | LiteClientError::ProgramAccountsDisabled(_) => ErrorKind::Rpc,
+            LiteClientError::Timeout => ErrorKind::Timeout,
+            LiteClientError::Journal(_) | LiteClientError::Store(_) => ErrorKind::Io,
+            LiteClientError::Internal(_) => ErrorKind::Internal,
+            LiteClientError::Program(ProgramError::BorshIoError(_)) => ErrorKind::Serialization,
+            LiteClientError::Program(ProgramError::Custom(code))
+                if LiteError::from_code(*code).is_some() =>
+            {
+                ErrorKind::Validation
+            }
+            LiteClientError::Program(_) => ErrorKind::Program,
+            LiteClientError::Signing(_)
+            | LiteClientError::InvalidTag(_)
+            | LiteClientError::PayerMismatch { .. }
+            | LiteClientError::DelegateAllowanceTooLow { .. }
+            | LiteClientError::MintMismatch { .. }
+            | LiteClientError::InsufficientBalance { .. }
+            | LiteClientError::CostCeilingExceeded { .. }
+            | LiteClientError::DuplicateAccountUsage(_) => ErrorKind::Validation,
+            #[cfg(feature = "test-variants")]
+            LiteClientError::__TestVariant => ErrorKind::Program,
+        }
+    }
+
+    /// Whether the same call may succeed if retried: RPC failures and timeouts. An endpoint
+    /// that disables `getProgramAccounts` keeps refusing it.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, LiteClientError::Rpc(_) | LiteClientError::Timeout)
+    }
+
+    /// The metric label of the error's [`kind`](Self::kind).
+    pub fn metric_label(&self) -> &'static str {
+        self.kind().label()
+    }
+}
+
+/// Sees every error a [`RequestBuilder`](crate::client::RequestBuilder) returns, once, before it
+/// is returned, e.g. to count failures by [`LiteClientError::metric_label`].
+pub trait ErrorObserver: Send + Sync {
+    fn observe(&self, error: &LiteClientError);
+}
+
+impl From<ProgramError> for LiteClientError {
+    fn from(value: ProgramError) -> Self {
+        LiteClientError::Program(value)
+    }
+}
+
+/// Whether an RPC error means the endpoint does not serve `getProgramAccounts` at all, as
+/// opposed to a failure of one call.
+///
+/// Matches the JSON-RPC "method not found" code, the "excluded from account secondary
+/// indexes" code, and the messages providers use when they block the method.
+pub fn is_program_accounts_disabled(code: Option<i64>, message: &str) -> bool {
+    /// JSON-RPC method not found.
+    const METHOD_NOT_FOUND: i64 = -32601;
+    /// The validator excludes the program from its account secondary indexes.
+    const KEY_EXCLUDED_FROM_SECONDARY_INDEX: i64 = -32010;
+    const BLOCKED_REASONS: [&str; 5] = [
+        "disabled",
+        "not allowed",
+        "not supported",
+        "unavailable",
+        "blocked",
+    ];
+
+    if matches!(
+        code,
+        Some(METHOD_NOT_FOUND | KEY_EXCLUDED_FROM_SECONDARY_INDEX)
+    ) {
+        return true;
+    }
+    let message = message.to_ascii_lowercase();
+    message.contains("method not found")
+        || message.contains("excluded from account secondary indexes")
+        || (message.contains("getprogramaccounts")
+            && BLOCKED_REASONS
+                .iter()
+                .any(|reason| message.contains(reason)))
+}
+
+impl From<solana_client::client_error::ClientError> for LiteClientError {
+    fn from(value: solana_client::client_error::ClientError) -> Self {
+        LiteClientError::Rpc(value.to_string())
+    }
+}
+
+impl From<solana_sdk::signer::SignerError> for LiteClientError {
+    fn from(value: solana_sdk::signer::SignerError) -> Self {
+        LiteClientError::Signing(value.to_string())
+    }
+}
+
+impl From<std::io::Error> for LiteClientError {
+    fn from(value: std::io::Error) -> Self {
+        LiteClientError::Journal(value)
+    }
+}
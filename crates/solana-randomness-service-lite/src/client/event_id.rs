@@ -0,0 +1,181 @@
+//! Stable ids for fulfillment notifications, so downstream consumers can drop redeliveries.
+
+use crate::client::events::decode_events;
+use crate::client::serde_utils::*;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+use solana_program::hash::hashv;
+use solana_sdk::signature::Signature;
+use std::collections::{BTreeMap, HashMap};
+
+/// The domain separator hashed in front of every [`fulfillment_event_id`].
+pub const FULFILLMENT_EVENT_ID_DOMAIN: &[u8] = b"solana-randomness-service-lite:fulfillment:v1";
+
+/// The idempotency key of a fulfillment: `sha256(FULFILLMENT_EVENT_ID_DOMAIN || request ||
+/// fulfillment_signature)`, with the 32 byte request pubkey and the 64 byte signature.
+///
+/// A request is fulfilled by exactly one settle or callback error transaction, so the id is
+/// the same for every redelivery of the notification and differs between requests. It does not
+/// depend on the slot or commitment the notification was observed at.
+pub fn fulfillment_event_id(request: &Pubkey, fulfillment_signature: &Signature) -> [u8; 32] {
+    hashv(&[
+        FULFILLMENT_EVENT_ID_DOMAIN,
+        request.as_ref(),
+        fulfillment_signature.as_ref(),
+    ])
+    .to_bytes()
+}
+
+/// How a request was fulfilled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FulfillmentKind {
+    /// The oracle invoked the callback, `SimpleRandomnessV1SettledEvent`.
+    Settled,
+    /// The oracle wrote an error message instead, `SimpleRandomnessV1CallbackErrorEvent`.
+    CallbackError,
+}
+
+/// A fulfillment notification decoded from a service transaction, with its
+/// [`fulfillment_event_id`]. Serializes to the JSON payload forwarded to downstream consumers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FulfillmentEvent {
+    #[serde(serialize_with = "serialize_hex")]
+    pub event_id: [u8; 32],
+    pub kind: FulfillmentKind,
+    #[serde(serialize_with = "serialize_display")]
+    pub request: Pubkey,
+    #[serde(serialize_with = "serialize_display")]
+    pub user: Pubkey,
+    #[serde(serialize_with = "serialize_display")]
+    pub signature: Signature,
+    pub request_slot: u64,
+    pub settled_slot: u64,
+    /// The callback arguments, set with [`with_callback_args`](Self::with_callback_args).
+    /// Left out of the JSON when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_args: Option<DecodedArgs>,
+}
+
+/// The fields `SimpleRandomnessV1SettledEvent` and `SimpleRandomnessV1CallbackErrorEvent`
+/// start with.
+#[derive(BorshDeserialize)]
+struct FulfilledEvent {
+    _callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+}
+
+impl FulfillmentEvent {
+    /// Decodes the fulfillments in a service transaction. Failed transactions fulfill nothing.
+    pub fn from_transaction(transaction: &TransactionRecord) -> Vec<Self> {
+        if transaction.failed {
+            return Vec::new();
+        }
+
+        [
+            ("SimpleRandomnessV1SettledEvent", FulfillmentKind::Settled),
+            (
+                "SimpleRandomnessV1CallbackErrorEvent",
+                FulfillmentKind::CallbackError,
+            ),
+        ]
+        .into_iter()
+        .flat_map(|(name, kind)| {
+            decode_events::<FulfilledEvent>(transaction, name)
+                .into_iter()
+                .map(move |event| Self {
+                    event_id: fulfillment_event_id(&event.request, &transaction.signature),
+                    kind,
+                    request: event.request,
+                    user: event.user,
+                    signature: transaction.signature,
+                    request_slot: event.request_slot,
+                    settled_slot: event.settled_slot,
+                    callback_args: None,
+                })
+        })
+        .collect()
+    }
+
+    /// Decodes the request's callback arguments into the payload with the registry. The
+    /// service does not log the callback when it settles, so pass the one the request was
+    /// made with, e.g. from the [`IndexedRequest`](crate::client::IndexedRequest).
+    pub fn with_callback_args(mut self, schemas: &SchemaRegistry, callback: &Callback) -> Self {
+        self.callback_args = schemas.decode_callback_args(&callback.ix_data);
+        self
+    }
+}
+
+/// A bounded set of recently seen event ids, evicting the least recently seen id when full.
+///
+/// Keep one cache across reconnects of a notification source. Replaying the transactions
+/// missed while disconnected redelivers some notifications that were already forwarded, and
+/// the cache drops them as long as they are among the last `capacity` ids seen.
+#[derive(Clone, Debug)]
+pub struct SeenCache {
+    capacity: usize,
+    tick: u64,
+    last_seen: HashMap<[u8; 32], u64>,
+    by_tick: BTreeMap<u64, [u8; 32]>,
+}
+
+impl SeenCache {
+    /// A cache holding at most `capacity` ids, at least one.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            last_seen: HashMap::new(),
+            by_tick: BTreeMap::new(),
+        }
+    }
+
+    /// Records the id as seen and returns whether it is new. A repeated id is marked as the
+    /// most recently seen.
+    pub fn insert(&mut self, id: [u8; 32]) -> bool {
+        self.tick += 1;
+        if let Some(tick) = self.last_seen.insert(id, self.tick) {
+            self.by_tick.remove(&tick);
+            self.by_tick.insert(self.tick, id);
+            return false;
+        }
+
+        self.by_tick.insert(self.tick, id);
+        if self.last_seen.len() > self.capacity {
+            if let Some((_, oldest)) = self.by_tick.pop_first() {
+                self.last_seen.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Whether the id is in the cache, without marking it as seen.
+    pub fn contains(&self, id: &[u8; 32]) -> bool {
+        self.last_seen.contains_key(id)
+    }
+
+    /// Keeps the events that were not seen before, recording them as seen.
+    pub fn filter_new(&mut self, events: Vec<FulfillmentEvent>) -> Vec<FulfillmentEvent> {
+        events
+            .into_iter()
+            .filter(|event| self.insert(event.event_id))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.last_seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last_seen.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
@@ -0,0 +1,83 @@
+use crate::client::*;
+use crate::*;
+use base64::Engine;
+use std::str::FromStr;
+
+/// Decodes the service events in the transaction logs with the given name. Borsh reads the
+/// leading fields it needs and ignores the rest, so `T` can be a prefix of the event.
+pub(crate) fn decode_events<T: BorshDeserialize>(
+    transaction: &TransactionRecord,
+    name: &str,
+) -> Vec<T> {
+    decode_program_events(transaction, anchor_event_discriminator(name))
+}
+
+/// Decodes the events with the given discriminator from the `Program data:` lines of the
+/// transaction logs, as written by Anchor's `emit!` or [`emit_event`], e.g.
+/// `decode_program_events::<ConsumerFulfilled>(&tx, ConsumerFulfilled::DISCRIMINATOR)`.
+/// Lines that do not decode as `T` are skipped.
+pub fn decode_program_events<T: BorshDeserialize>(
+    transaction: &TransactionRecord,
+    discriminator: [u8; 8],
+) -> Vec<T> {
+    transaction
+        .log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter(|data| data.len() > 8 && data[..8] == discriminator)
+        .filter_map(|data| T::deserialize(&mut &data[8..]).ok())
+        .collect()
+}
+
+/// The randomness service events in a transaction's log messages, e.g. the `logMessages` of a
+/// `getTransaction` response, in the order they were logged.
+///
+/// Only the `Program data:` lines logged while the service is the innermost invoked program
+/// are decoded, so events the callback or any other program logs are skipped even if they
+/// carry a service discriminator. Lines that do not decode, such as one cut off where the
+/// runtime truncated the logs, are skipped too.
+pub fn parse_events_from_logs(logs: &[String]) -> Vec<RandomnessServiceEvent> {
+    parse_program_events_from_logs(&ID, logs)
+}
+
+/// Like [`parse_events_from_logs`], for the service deployed at `program_id`.
+pub fn parse_program_events_from_logs(
+    program_id: &Pubkey,
+    logs: &[String],
+) -> Vec<RandomnessServiceEvent> {
+    // The programs invoked, innermost last
+    let mut invoked: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            // Logs that lost their invoke lines are attributed to the service
+            if invoked.last().is_none_or(|program| program == program_id) {
+                events.extend(
+                    base64::engine::general_purpose::STANDARD
+                        .decode(data)
+                        .ok()
+                        .and_then(|data| RandomnessServiceEvent::try_from_log_data(&data).ok()),
+                );
+            }
+            continue;
+        }
+
+        let mut words = log
+            .strip_prefix("Program ")
+            .unwrap_or_default()
+            .split_whitespace();
+        let (Some(program), Some(action)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let Ok(program) = Pubkey::from_str(program) else {
+            continue;
+        };
+        if action == "invoke" {
+            invoked.push(program);
+        } else if action == "success" || action.starts_with("failed") {
+            invoked.pop();
+        }
+    }
+    events
+}
@@ -0,0 +1,202 @@
+use crate::client::*;
+use crate::Pubkey;
+use futures::future::BoxFuture;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How [`FailoverRpc`] serves read requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadMode {
+    /// Try each endpoint in turn, healthiest first.
+    #[default]
+    Sequential,
+    /// Query every endpoint at once and return the first successful response.
+    Race,
+}
+
+/// The observed health of a single [`FailoverRpc`] endpoint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub successes: u64,
+    pub failures: u64,
+    /// The number of failures since the last success. Endpoints are tried in ascending order.
+    pub consecutive_failures: u32,
+}
+
+impl EndpointHealth {
+    fn record(&mut self, success: bool) {
+        if success {
+            self.successes += 1;
+            self.consecutive_failures = 0;
+        } else {
+            self.failures += 1;
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        }
+    }
+}
+
+/// A [`RandomnessRpc`] over an ordered list of endpoints.
+///
+/// Every call is bounded by a per-call timeout. A failed or timed out call is retried on the
+/// next endpoint, and endpoints that have recently failed are tried last. Transactions are
+/// always sent to one endpoint at a time; resending a signed transaction after a timeout is
+/// safe because it keeps the same signature.
+pub struct FailoverRpc {
+    endpoints: Vec<Arc<dyn RandomnessRpc>>,
+    health: Mutex<Vec<EndpointHealth>>,
+    timeout: Duration,
+    read_mode: ReadMode,
+}
+
+impl FailoverRpc {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(endpoints: Vec<Arc<dyn RandomnessRpc>>) -> Self {
+        Self {
+            health: Mutex::new(vec![EndpointHealth::default(); endpoints.len()]),
+            endpoints,
+            timeout: Self::DEFAULT_TIMEOUT,
+            read_mode: ReadMode::default(),
+        }
+    }
+
+    /// Sets the timeout applied to each call on each endpoint.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how read requests are served.
+    pub fn read_mode(mut self, read_mode: ReadMode) -> Self {
+        self.read_mode = read_mode;
+        self
+    }
+
+    /// The health of each endpoint, in the order they were provided.
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.health
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The endpoint indices, healthiest first. Ties keep the configured order.
+    fn ranked(&self) -> Vec<usize> {
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let mut indices: Vec<usize> = (0..self.endpoints.len()).collect();
+        indices.sort_by_key(|i| health[*i].consecutive_failures);
+        indices
+    }
+
+    async fn call_endpoint<'a, T, F>(&'a self, index: usize, f: &F) -> Result<T, LiteClientError>
+    where
+        F: Fn(&'a dyn RandomnessRpc) -> BoxFuture<'a, Result<T, LiteClientError>>,
+    {
+        let result =
+            match tokio::time::timeout(self.timeout, f(self.endpoints[index].as_ref())).await {
+                Ok(result) => result,
+                Err(_) => Err(LiteClientError::Timeout),
+            };
+
+        self.health.lock().unwrap_or_else(|e| e.into_inner())[index].record(result.is_ok());
+
+        result
+    }
+
+    async fn sequential<'a, T, F>(&'a self, f: F) -> Result<T, LiteClientError>
+    where
+        F: Fn(&'a dyn RandomnessRpc) -> BoxFuture<'a, Result<T, LiteClientError>>,
+    {
+        let mut last_error = LiteClientError::Rpc("no RPC endpoints configured".to_string());
+        for index in self.ranked() {
+            match self.call_endpoint(index, &f).await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    log::warn!("RPC endpoint {} failed: {}", index, e);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn read<'a, T, F>(&'a self, f: F) -> Result<T, LiteClientError>
+    where
+        T: Send + 'a,
+        F: Fn(&'a dyn RandomnessRpc) -> BoxFuture<'a, Result<T, LiteClientError>> + Sync,
+    {
+        if self.read_mode == ReadMode::Sequential || self.endpoints.len() < 2 {
+            return self.sequential(f).await;
+        }
+
+        let f = &f;
+        let calls = self.ranked().into_iter().map(|index| {
+            let call: BoxFuture<'_, Result<T, LiteClientError>> =
+                Box::pin(self.call_endpoint(index, f));
+            call
+        });
+        futures::future::select_ok(calls)
+            .await
+            .map(|(value, _)| value)
+    }
+}
+
+#[async_trait::async_trait]
+impl RandomnessRpc for FailoverRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, LiteClientError> {
+        self.read(|rpc| rpc.get_latest_blockhash()).await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, LiteClientError> {
+        self.read(|rpc| rpc.get_account(pubkey)).await
+    }
+
+    async fn get_account_with_slot(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<(u64, Option<Account>), LiteClientError> {
+        self.read(|rpc| rpc.get_account_with_slot(pubkey)).await
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, LiteClientError> {
+        self.sequential(|rpc| rpc.send_and_confirm_transaction(transaction))
+            .await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureRecord>, LiteClientError> {
+        self.read(|rpc| rpc.get_signatures_for_address(address, before, limit))
+            .await
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionRecord>, LiteClientError> {
+        self.read(|rpc| rpc.get_transaction(signature)).await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        discriminator: [u8; 8],
+    ) -> Result<(u64, Vec<(Pubkey, Account)>), LiteClientError> {
+        self.read(|rpc| rpc.get_program_accounts(program_id, discriminator))
+            .await
+    }
+
+    async fn get_slot(&self) -> Result<u64, LiteClientError> {
+        self.read(|rpc| rpc.get_slot()).await
+    }
+}
@@ -0,0 +1,19 @@
+use crate::client::*;
+use crate::*;
+
+/// Fetches the [`ResultFeed`] the consumer program keeps for the user, or `None` if the user
+/// has no feed yet.
+pub async fn latest_result_for_user<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    consumer_program: &Pubkey,
+    user: &Pubkey,
+) -> Result<Option<ResultFeed>, LiteClientError> {
+    let (address, _) = ResultFeed::address(consumer_program, user);
+    match rpc.get_account(&address).await? {
+        Some(account) if account.owner == *consumer_program => {
+            Ok(Some(ResultFeed::try_deserialize(&mut &account.data[..])?))
+        }
+        Some(_) => Err(ProgramError::IllegalOwner.into()),
+        None => Ok(None),
+    }
+}
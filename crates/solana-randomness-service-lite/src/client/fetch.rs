@@ -0,0 +1,120 @@
+use crate::client::*;
+use crate::*;
+use solana_sdk::account::Account;
+
+/// The error returned by [`SimpleRandomnessV1Account::fetch`] and [`ServiceState::fetch`], one
+/// variant per way the account can fail to load.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FetchAccountError {
+    /// No account exists at the address. Settled requests are closed by the service.
+    NotFound(Pubkey),
+    /// The account is not owned by the randomness service.
+    WrongOwner { expected: Pubkey, actual: Pubkey },
+    /// The account is too short to hold a discriminator or holds another account type.
+    BadDiscriminator,
+    /// The account has the expected discriminator but its data does not deserialize.
+    Deserialize(ProgramError),
+    /// Fetching the account failed.
+    Client(LiteClientError),
+}
+
+impl std::fmt::Display for FetchAccountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchAccountError::NotFound(pubkey) => write!(f, "Account {} not found", pubkey),
+            FetchAccountError::WrongOwner { expected, actual } => write!(
+                f,
+                "The account is owned by {}, not the randomness service {}",
+                actual, expected
+            ),
+            FetchAccountError::BadDiscriminator => {
+                write!(f, "The account holds another account type")
+            }
+            FetchAccountError::Deserialize(e) => {
+                write!(f, "Failed to deserialize the account: {}", e)
+            }
+            FetchAccountError::Client(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FetchAccountError {}
+
+impl From<LiteClientError> for FetchAccountError {
+    fn from(value: LiteClientError) -> Self {
+        FetchAccountError::Client(value)
+    }
+}
+
+impl SimpleRandomnessV1Account {
+    /// Fetches and deserializes the request account, checking it is owned by the service and
+    /// has the request discriminator.
+    pub async fn fetch<R: RandomnessRpc + ?Sized>(
+        rpc: &R,
+        pubkey: &Pubkey,
+    ) -> Result<Self, FetchAccountError> {
+        Self::from_fetched(pubkey, rpc.get_account(pubkey).await?)
+    }
+
+    /// Like [`fetch`](Self::fetch), with the blocking [`RpcClient`].
+    ///
+    /// [`RpcClient`]: solana_client::rpc_client::RpcClient
+    pub fn fetch_blocking(
+        rpc: &solana_client::rpc_client::RpcClient,
+        pubkey: &Pubkey,
+    ) -> Result<Self, FetchAccountError> {
+        let account = rpc
+            .get_account_with_commitment(pubkey, rpc.commitment())
+            .map_err(LiteClientError::from)?
+            .value;
+        Self::from_fetched(pubkey, account)
+    }
+
+    fn from_fetched(pubkey: &Pubkey, account: Option<Account>) -> Result<Self, FetchAccountError> {
+        let data = checked_data(pubkey, account, &Self::owner(), &Self::DISCRIMINATOR)?;
+        Self::try_deserialize_unchecked(&mut &data[..]).map_err(FetchAccountError::Deserialize)
+    }
+}
+
+impl ServiceState {
+    /// Fetches and deserializes the state account of the service deployed at `program_id`,
+    /// its [`derive_state_pubkey`], checking it is owned by the program and has the state
+    /// discriminator. Returns the slot the account was read at with it; nothing is cached, so
+    /// fetch again once that slot is too old for the caller.
+    pub async fn fetch<R: RandomnessRpc + ?Sized>(
+        rpc: &R,
+        program_id: &Pubkey,
+    ) -> Result<(u64, Self), FetchAccountError> {
+        let (state, _) = derive_state_pubkey(program_id);
+        let (slot, account) = rpc.get_account_with_slot(&state).await?;
+        let data = checked_data(&state, account, program_id, &Self::DISCRIMINATOR)?;
+        let state = Self::try_deserialize_unchecked(&mut &data[..])
+            .map_err(FetchAccountError::Deserialize)?;
+        Ok((slot, state))
+    }
+}
+
+/// The data of a fetched account, checked to exist, be owned by `owner`, and start with
+/// `discriminator`.
+fn checked_data(
+    pubkey: &Pubkey,
+    account: Option<Account>,
+    owner: &Pubkey,
+    discriminator: &[u8; 8],
+) -> Result<Vec<u8>, FetchAccountError> {
+    let account = match account {
+        Some(account) if account.lamports > 0 => account,
+        _ => return Err(FetchAccountError::NotFound(*pubkey)),
+    };
+    if account.owner != *owner {
+        return Err(FetchAccountError::WrongOwner {
+            expected: *owner,
+            actual: account.owner,
+        });
+    }
+    if account.data.get(..8) != Some(&discriminator[..]) {
+        return Err(FetchAccountError::BadDiscriminator);
+    }
+    Ok(account.data)
+}
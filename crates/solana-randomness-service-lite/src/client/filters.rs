@@ -0,0 +1,51 @@
+//! `getProgramAccounts` filters for scanning the randomness service's request accounts.
+//!
+//! Pass a filter set as the `filters` of an `RpcProgramAccountsConfig` for the service program.
+//! The filters compare the fixed-layout fields at the offsets in [`RequestHeader`], so they
+//! follow the account layout this crate reads.
+//!
+//! Settled requests are closed by the service, so a scan only finds requests waiting for the
+//! oracle and failed requests the user has not closed yet.
+
+use crate::*;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+
+/// Matches accounts whose data starts with the discriminator.
+pub fn discriminator(discriminator: [u8; 8]) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, discriminator.to_vec()))
+}
+
+/// Every [`SimpleRandomnessV1Account`].
+pub fn all_requests() -> Vec<RpcFilterType> {
+    vec![discriminator(SimpleRandomnessV1Account::DISCRIMINATOR)]
+}
+
+/// The requests still waiting for the oracle, with `is_completed` unset.
+pub fn pending_requests() -> Vec<RpcFilterType> {
+    with_is_completed(0)
+}
+
+/// The requests the oracle marked completed without settling, i.e. failed requests with an
+/// `error_message`, which stay open until the user closes them.
+pub fn completed_requests() -> Vec<RpcFilterType> {
+    with_is_completed(1)
+}
+
+/// The requests paid for by `user`.
+pub fn requests_by_user(user: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = all_requests();
+    filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        RequestHeader::USER_OFFSET,
+        user.to_bytes().to_vec(),
+    )));
+    filters
+}
+
+fn with_is_completed(is_completed: u8) -> Vec<RpcFilterType> {
+    let mut filters = all_requests();
+    filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        RequestHeader::IS_COMPLETED_OFFSET,
+        vec![is_completed],
+    )));
+    filters
+}
@@ -0,0 +1,154 @@
+use crate::client::serde_utils::*;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::program_utils::limited_deserialize;
+use solana_sdk::hash::{hash, Hash};
+
+/// Identifies a deployment of the randomness service program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ProgramFingerprint {
+    /// The SHA-256 hash of the program bytes stored in the ProgramData account, the same bytes
+    /// `solana program dump` writes.
+    #[serde(serialize_with = "serialize_display")]
+    pub program_data_hash: Hash,
+    /// The upgrade authority, or `None` if the program is immutable.
+    #[serde(serialize_with = "serialize_opt_display")]
+    pub upgrade_authority: Option<Pubkey>,
+    /// The slot the program was last deployed or upgraded at.
+    pub last_deploy_slot: u64,
+}
+
+/// The error returned when the randomness service program does not match a pinned fingerprint.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FingerprintError {
+    /// The program was upgraded or its upgrade authority changed.
+    Changed {
+        expected: ProgramFingerprint,
+        actual: ProgramFingerprint,
+    },
+    Client(LiteClientError),
+}
+
+impl std::fmt::Display for FingerprintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FingerprintError::Changed { expected, actual } => {
+                write!(f, "Randomness service program changed:")?;
+                if expected.program_data_hash != actual.program_data_hash {
+                    write!(
+                        f,
+                        " hash {} -> {}",
+                        expected.program_data_hash, actual.program_data_hash
+                    )?;
+                }
+                if expected.upgrade_authority != actual.upgrade_authority {
+                    write!(
+                        f,
+                        " upgrade authority {:?} -> {:?}",
+                        expected.upgrade_authority, actual.upgrade_authority
+                    )?;
+                }
+                if expected.last_deploy_slot != actual.last_deploy_slot {
+                    write!(
+                        f,
+                        " deploy slot {} -> {}",
+                        expected.last_deploy_slot, actual.last_deploy_slot
+                    )?;
+                }
+                Ok(())
+            }
+            FingerprintError::Client(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FingerprintError {}
+
+impl From<LiteClientError> for FingerprintError {
+    fn from(value: LiteClientError) -> Self {
+        FingerprintError::Client(value)
+    }
+}
+
+/// Reads the randomness service program's ProgramData account from the upgradeable loader.
+///
+/// Fails with [`ProgramError::IncorrectProgramId`] if the program is not owned by the
+/// upgradeable loader.
+pub async fn service_program_fingerprint<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+) -> Result<ProgramFingerprint, LiteClientError> {
+    program_fingerprint(rpc, &ID).await
+}
+
+/// Reads the ProgramData account of the given upgradeable program, e.g. the `program_id` of
+/// overridden [`ServiceAddresses`].
+pub async fn program_fingerprint<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    program_id: &Pubkey,
+) -> Result<ProgramFingerprint, LiteClientError> {
+    let program = rpc
+        .get_account(program_id)
+        .await?
+        .ok_or(ProgramError::UninitializedAccount)?;
+    if program.owner != bpf_loader_upgradeable::ID {
+        return Err(ProgramError::IncorrectProgramId.into());
+    }
+    let programdata_address = match deserialize_loader_state(&program.data)? {
+        UpgradeableLoaderState::Program {
+            programdata_address,
+        } => programdata_address,
+        _ => return Err(ProgramError::InvalidAccountData.into()),
+    };
+
+    let program_data = rpc
+        .get_account(&programdata_address)
+        .await?
+        .ok_or(ProgramError::UninitializedAccount)?;
+    if program_data.owner != bpf_loader_upgradeable::ID {
+        return Err(ProgramError::IncorrectProgramId.into());
+    }
+    match deserialize_loader_state(&program_data.data)? {
+        UpgradeableLoaderState::ProgramData {
+            slot,
+            upgrade_authority_address,
+        } => {
+            let program_bytes = program_data
+                .data
+                .get(UpgradeableLoaderState::size_of_programdata_metadata()..)
+                .ok_or(ProgramError::AccountDataTooSmall)?;
+            Ok(ProgramFingerprint {
+                program_data_hash: hash(program_bytes),
+                upgrade_authority: upgrade_authority_address,
+                last_deploy_slot: slot,
+            })
+        }
+        _ => Err(ProgramError::InvalidAccountData.into()),
+    }
+}
+
+/// Fetches the program's fingerprint and fails if it differs from the pinned one, so
+/// deployments can alert when the service program is upgraded.
+pub async fn verify_fingerprint<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    expected: &ProgramFingerprint,
+) -> Result<ProgramFingerprint, FingerprintError> {
+    let actual = service_program_fingerprint(rpc).await?;
+    if actual != *expected {
+        return Err(FingerprintError::Changed {
+            expected: *expected,
+            actual,
+        });
+    }
+    Ok(actual)
+}
+
+fn deserialize_loader_state(data: &[u8]) -> Result<UpgradeableLoaderState, ProgramError> {
+    limited_deserialize(
+        data,
+        UpgradeableLoaderState::size_of_programdata_metadata() as u64,
+    )
+    .map_err(|_| ProgramError::InvalidAccountData)
+}
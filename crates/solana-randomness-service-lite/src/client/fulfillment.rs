@@ -0,0 +1,393 @@
+use crate::client::*;
+use crate::*;
+use futures::FutureExt;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signature;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The observed state of a randomness request account.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum RequestStatus {
+    /// The request is waiting for the oracle.
+    Pending(SimpleRandomnessV1Account),
+    /// The oracle could not invoke the callback and wrote an `error_message`. The account stays
+    /// open until the user closes it.
+    Failed(SimpleRandomnessV1Account),
+    /// The account no longer exists. The service closes the request once the callback settles.
+    Closed,
+    #[cfg(feature = "test-variants")]
+    #[doc(hidden)]
+    __TestVariant,
+}
+
+impl RequestStatus {
+    /// Classifies a fetched request account.
+    pub fn from_account(account: Option<&Account>) -> Result<Self, LiteClientError> {
+        let account = match account {
+            Some(account) if account.lamports > 0 => account,
+            _ => return Ok(RequestStatus::Closed),
+        };
+
+        let request = SimpleRandomnessV1Account::try_deserialize(&mut &account.data[..])?;
+        if request.is_completed != 0 || !request.error_message.is_empty() {
+            Ok(RequestStatus::Failed(request))
+        } else {
+            Ok(RequestStatus::Pending(request))
+        }
+    }
+
+    /// Whether the request will not change again.
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, RequestStatus::Pending(_))
+    }
+
+    /// The request account, unless it is closed.
+    pub fn request(&self) -> Option<&SimpleRandomnessV1Account> {
+        match self {
+            RequestStatus::Pending(request) | RequestStatus::Failed(request) => Some(request),
+            _ => None,
+        }
+    }
+}
+
+/// How an awaited request ended. Returned by [`await_fulfillment`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum FulfillmentOutcome {
+    /// The oracle settled the request. The data is recovered from the settle transaction, so it
+    /// is available even though the service closed the request account. Build one with
+    /// [`FulfillmentOutcome::fulfilled`].
+    #[non_exhaustive]
+    Fulfilled {
+        /// The settle transaction.
+        signature: Signature,
+        settled_slot: u64,
+        /// Whether the callback succeeded. The service settles and closes the request even
+        /// when the callback fails.
+        is_success: bool,
+        /// The randomness delivered to the callback.
+        randomness: Vec<u8>,
+        /// The [`fulfillment_event_id`] of the settlement.
+        event_id: [u8; 32],
+    },
+    /// The oracle could not invoke the callback and wrote an `error_message`. The account stays
+    /// open until the user closes it.
+    Failed(SimpleRandomnessV1Account),
+    /// The request account was closed, but no settle transaction exists for it, e.g. it was
+    /// closed after an error before it was observed.
+    ClosedBeforeFulfillment,
+    #[cfg(feature = "test-variants")]
+    #[doc(hidden)]
+    __TestVariant,
+}
+
+impl FulfillmentOutcome {
+    /// A request settled by the transaction `signature` at `settled_slot`, with its
+    /// [`fulfillment_event_id`].
+    pub fn fulfilled(
+        request: &Pubkey,
+        randomness: Vec<u8>,
+        settled_slot: u64,
+        signature: Signature,
+        is_success: bool,
+    ) -> Self {
+        FulfillmentOutcome::Fulfilled {
+            signature,
+            settled_slot,
+            is_success,
+            randomness,
+            event_id: fulfillment_event_id(request, &signature),
+        }
+    }
+
+    /// Whether the randomness was delivered and the callback succeeded.
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            FulfillmentOutcome::Fulfilled {
+                is_success: true,
+                ..
+            }
+        )
+    }
+
+    /// The randomness delivered to the callback, if the request was settled.
+    pub fn randomness(&self) -> Option<&[u8]> {
+        match self {
+            FulfillmentOutcome::Fulfilled { randomness, .. } => Some(randomness),
+            _ => None,
+        }
+    }
+
+    /// The settle transaction, if the request was settled.
+    pub fn signature(&self) -> Option<&Signature> {
+        match self {
+            FulfillmentOutcome::Fulfilled { signature, .. } => Some(signature),
+            _ => None,
+        }
+    }
+
+    /// The [`fulfillment_event_id`] of the settlement, if the request was settled.
+    pub fn event_id(&self) -> Option<[u8; 32]> {
+        match self {
+            FulfillmentOutcome::Fulfilled { event_id, .. } => Some(*event_id),
+            _ => None,
+        }
+    }
+}
+
+/// The error returned when awaiting a request.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AwaitError {
+    /// The request was not settled in time.
+    Timeout {
+        /// The last state observed before the timeout, if any. Boxed to keep the error small.
+        last_status: Option<Box<RequestStatus>>,
+    },
+    Client(LiteClientError),
+}
+
+impl std::fmt::Display for AwaitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AwaitError::Timeout { last_status } => {
+                write!(
+                    f,
+                    "Timed out awaiting request, last status: {:?}",
+                    last_status
+                )
+            }
+            AwaitError::Client(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AwaitError {}
+
+impl From<LiteClientError> for AwaitError {
+    fn from(value: LiteClientError) -> Self {
+        AwaitError::Client(value)
+    }
+}
+
+/// The delay before retrying after a pubsub endpoint fails to subscribe.
+pub const RESUBSCRIBE_DELAY: Duration = Duration::from_millis(500);
+
+/// Waits until the request is settled or fails, using account subscriptions.
+///
+/// Subscribes on the first pubsub endpoint and moves on to the next one, wrapping around,
+/// whenever a subscription disconnects or fails. After every subscription the account is
+/// re-fetched through `rpc`, so a transition that happened while disconnected is not missed.
+pub async fn await_fulfillment_with_pubsub<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    pubsub: &[Arc<dyn RandomnessPubsub>],
+    request: &Pubkey,
+    timeout: Duration,
+) -> Result<RequestStatus, AwaitError> {
+    if pubsub.is_empty() {
+        return Err(LiteClientError::Rpc("no pubsub endpoints configured".to_string()).into());
+    }
+
+    let mut last_status = None;
+    let result = tokio::time::timeout(timeout, async {
+        for (index, endpoint) in pubsub.iter().enumerate().cycle() {
+            let mut updates = match endpoint.subscribe_account(request).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    log::warn!("Pubsub endpoint {} failed to subscribe: {}", index, e);
+                    tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+                    continue;
+                }
+            };
+
+            let account = match rpc.get_account(request).await {
+                Ok(account) => account,
+                Err(e) => {
+                    log::warn!("Failed to fetch request {}: {}", request, e);
+                    tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+                    continue;
+                }
+            };
+            let status = RequestStatus::from_account(account.as_ref())?;
+            if status.is_terminal() {
+                return Ok(status);
+            }
+            last_status = Some(status);
+
+            while let Some(account) = updates.recv().await {
+                let status = RequestStatus::from_account(account.as_ref())?;
+                if status.is_terminal() {
+                    return Ok(status);
+                }
+                last_status = Some(status);
+            }
+
+            log::warn!("Pubsub endpoint {} disconnected, resubscribing", index);
+        }
+        // The endpoints are not empty, so the cycle never ends
+        Err(LiteClientError::Internal("pubsub endpoints exhausted".to_string()).into())
+    })
+    .await;
+
+    match result {
+        Ok(result) => result,
+        Err(_) => Err(AwaitError::Timeout {
+            last_status: last_status.map(Box::new),
+        }),
+    }
+}
+
+/// Waits until the request is settled or fails, like [`await_fulfillment_with_pubsub`], and
+/// resolves a closed request account to its settlement.
+///
+/// The service closes the request account in the settle transaction, and the consumer or a
+/// sweeper can close a failed request at any time, so a subscription usually only sees the
+/// account disappear. The request's signatures are then searched for the settle transaction
+/// and the randomness is read from its `SimpleRandomnessV1SettledEvent`. A request closed
+/// without one returns [`FulfillmentOutcome::ClosedBeforeFulfillment`].
+pub async fn await_fulfillment<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    pubsub: &[Arc<dyn RandomnessPubsub>],
+    request: &Pubkey,
+    timeout: Duration,
+) -> Result<FulfillmentOutcome, AwaitError> {
+    let status = await_fulfillment_with_pubsub(rpc, pubsub, request, timeout).await?;
+    Ok(resolve_outcome(rpc, request, status).await?)
+}
+
+/// Awaits every request concurrently with [`await_fulfillment`], returning one result per
+/// request in the order given, each within `timeout`.
+///
+/// A failure of one request does not affect the others. A panic while awaiting a request,
+/// e.g. in a [`RandomnessRpc`] or [`RandomnessPubsub`] implementation, is caught and returned
+/// as [`LiteClientError::Internal`] for that request.
+pub async fn await_many<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    pubsub: &[Arc<dyn RandomnessPubsub>],
+    requests: &[Pubkey],
+    timeout: Duration,
+) -> Vec<Result<FulfillmentOutcome, AwaitError>> {
+    let awaits = requests.iter().map(|request| {
+        AssertUnwindSafe(await_fulfillment(rpc, pubsub, request, timeout))
+            .catch_unwind()
+            .map(move |result| {
+                result.unwrap_or_else(|panic| {
+                    let message = panic_message(panic.as_ref());
+                    log::error!("Awaiting request {} panicked: {}", request, message);
+                    Err(LiteClientError::Internal(message).into())
+                })
+            })
+    });
+    futures::future::join_all(awaits).await
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(message), _) => format!("panicked: {}", message),
+        (_, Some(message)) => format!("panicked: {}", message),
+        _ => "panicked".to_string(),
+    }
+}
+
+/// The shortest interval [`await_fulfillment_polling`] and [`Indexer::run`] poll at. Shorter
+/// intervals, including zero, are raised to it.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Waits until the request is settled or fails by fetching the request account every
+/// `poll_interval`, for endpoints without a websocket, and resolves it like
+/// [`await_fulfillment`].
+///
+/// A failed fetch is logged and retried at the next interval. On timeout,
+/// [`AwaitError::Timeout`] carries the last status fetched, with the pending account as the
+/// oracle left it.
+pub async fn await_fulfillment_polling<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<FulfillmentOutcome, AwaitError> {
+    let mut last_status = None;
+    let result = tokio::time::timeout(timeout, async {
+        let mut interval = tokio::time::interval(poll_interval.max(MIN_POLL_INTERVAL));
+        loop {
+            interval.tick().await;
+            let account = match rpc.get_account(request).await {
+                Ok(account) => account,
+                Err(e) => {
+                    log::warn!("Failed to fetch request {}: {}", request, e);
+                    continue;
+                }
+            };
+            let status = RequestStatus::from_account(account.as_ref())?;
+            if status.is_terminal() {
+                return Ok::<_, LiteClientError>(status);
+            }
+            last_status = Some(status);
+        }
+    })
+    .await;
+
+    match result {
+        Ok(status) => Ok(resolve_outcome(rpc, request, status?).await?),
+        Err(_) => Err(AwaitError::Timeout {
+            last_status: last_status.map(Box::new),
+        }),
+    }
+}
+
+/// The outcome of a request in a terminal status, searching a closed request's signatures for
+/// its settlement.
+pub(crate) async fn resolve_outcome<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+    status: RequestStatus,
+) -> Result<FulfillmentOutcome, LiteClientError> {
+    if let RequestStatus::Failed(failed) = status {
+        return Ok(FulfillmentOutcome::Failed(failed));
+    }
+
+    // Closed, the only other terminal status
+    match settled_event(rpc, request).await? {
+        Some((signature, event)) => Ok(FulfillmentOutcome::fulfilled(
+            request,
+            event.randomness,
+            event.settled_slot,
+            signature,
+            event.is_success,
+        )),
+        None => Ok(FulfillmentOutcome::ClosedBeforeFulfillment),
+    }
+}
+
+/// The number of signatures checked for a request's settle transaction. A request account is
+/// only touched by its request, settle, and close transactions.
+const SETTLE_SIGNATURE_LIMIT: usize = 100;
+
+/// Finds the request's settle transaction and its event, if the request was settled.
+pub(crate) async fn settled_event<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+) -> Result<Option<(Signature, SimpleRandomnessV1SettledEvent)>, LiteClientError> {
+    let signatures = rpc
+        .get_signatures_for_address(request, None, SETTLE_SIGNATURE_LIMIT)
+        .await?;
+    for signature in signatures.iter().filter(|s| !s.failed) {
+        let Some(transaction) = rpc.get_transaction(&signature.signature).await? else {
+            continue;
+        };
+        let event = decode_program_events::<SimpleRandomnessV1SettledEvent>(
+            &transaction,
+            SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+        )
+        .into_iter()
+        .find(|event| event.request == *request);
+        if let Some(event) = event {
+            return Ok(Some((transaction.signature, event)));
+        }
+    }
+    Ok(None)
+}
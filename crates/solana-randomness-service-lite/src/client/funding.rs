@@ -0,0 +1,103 @@
+use crate::client::preflight::fetch_state_data;
+use crate::client::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+use solana_program::rent::Rent;
+
+/// The lamports a payer spends to create a randomness request.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingBreakdown {
+    /// The flat fee the service charges for every request.
+    pub base_fee: u64,
+    /// The fee for the requested randomness bytes, `cost_per_byte * num_bytes`.
+    pub randomness_fee: u64,
+    /// The priority fee the oracle pays to settle the request, rounded down to whole lamports
+    /// as the service wraps it.
+    pub priority_fee: u64,
+    /// The rent-exempt minimum for the request account.
+    pub request_rent: u64,
+    /// The rent-exempt minimum for the escrow token account.
+    pub escrow_rent: u64,
+    /// The fee paid to a third party in the request transaction, see
+    /// [`RequestBuilder::with_referral_fee`]. Not part of the service cost.
+    #[serde(default)]
+    pub referral_fee: u64,
+}
+
+impl FundingBreakdown {
+    /// The flat fee the service charges for every request, in lamports.
+    pub const BASE_FEE: u64 = REQUEST_BASE_FEE;
+    /// The size of the escrow SPL token account.
+    pub const ESCROW_ACCOUNT_SIZE: usize = crate::ESCROW_ACCOUNT_SIZE;
+
+    /// The breakdown of a request, failing with [`LiteError::FeeOverflow`] if its randomness
+    /// or priority fee does not fit in a u64.
+    pub fn new(
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+        cost_per_byte: u64,
+        rent: &Rent,
+    ) -> Result<Self, ProgramError> {
+        let options = options.clone().unwrap_or_default();
+
+        Ok(Self {
+            base_fee: Self::BASE_FEE,
+            randomness_fee: cost_per_byte
+                .checked_mul(u64::from(num_bytes))
+                .ok_or(LiteError::FeeOverflow)?,
+            priority_fee: wrapped_priority_fee(&options).ok_or(LiteError::FeeOverflow)?,
+            request_rent: rent.minimum_balance(SimpleRandomnessV1Account::space(callback)),
+            escrow_rent: rent.minimum_balance(Self::ESCROW_ACCOUNT_SIZE),
+            referral_fee: 0,
+        })
+    }
+
+    /// Like [`new`](Self::new), with the `cost_per_byte` of the service's deserialized
+    /// state account.
+    pub fn for_state(
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+        state: &ServiceState,
+        rent: &Rent,
+    ) -> Result<Self, ProgramError> {
+        Self::new(num_bytes, callback, options, state.cost_per_byte, rent)
+    }
+
+    /// The lamports wrapped into the escrow to reward the oracle.
+    pub fn service_cost(&self) -> u64 {
+        self.base_fee + self.randomness_fee + self.priority_fee
+    }
+
+    /// The total lamports debited from the payer, excluding the transaction fee.
+    pub fn total(&self) -> u64 {
+        self.service_cost() + self.request_rent + self.escrow_rent + self.referral_fee
+    }
+}
+
+/// Fetches what a request will debit its payer, excluding the transaction fee, before it is
+/// signed. Reads the service's current `cost_per_byte` from its `state` account, so the
+/// breakdown follows the service's pricing, and uses the default rent every cluster runs with.
+/// Its [`service_cost`](FundingBreakdown::service_cost) is the
+/// [`estimate_request_cost`](crate::estimate_request_cost) at that price.
+///
+/// The priority fee is rounded down like the amount the service wraps into the escrow, so it
+/// can be a lamport under [`TransactionOptions::get_priority_fee_lamports`], which rounds up.
+pub async fn fetch_request_cost<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    state: &Pubkey,
+    num_bytes: u8,
+    callback: &Callback,
+    options: &Option<TransactionOptions>,
+) -> Result<FundingBreakdown, LiteClientError> {
+    let cost_per_byte =
+        ServiceState::read_cost_per_byte(&fetch_state_data(rpc, state, &ID).await?)?;
+    Ok(FundingBreakdown::new(
+        num_bytes,
+        callback,
+        options,
+        cost_per_byte,
+        &Rent::default(),
+    )?)
+}
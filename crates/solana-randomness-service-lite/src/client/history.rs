@@ -0,0 +1,129 @@
+use crate::client::LiteClientError;
+use crate::Pubkey;
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiLoadedAddresses};
+use std::str::FromStr;
+
+/// A transaction signature returned by `getSignaturesForAddress`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureRecord {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Whether the transaction failed.
+    pub failed: bool,
+}
+
+/// The change to a token account's balance in a transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBalanceRecord {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Option<Pubkey>,
+    /// The balance before the transaction. Zero if the account did not exist.
+    pub pre: u64,
+    /// The balance after the transaction. Zero if the account was closed.
+    pub post: u64,
+}
+
+impl TokenBalanceRecord {
+    /// The signed change in balance.
+    pub fn delta(&self) -> i128 {
+        i128::from(self.post) - i128::from(self.pre)
+    }
+}
+
+/// The parts of a confirmed transaction used by the client helpers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionRecord {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Whether the transaction failed.
+    pub failed: bool,
+    /// The account keys, including any loaded from address lookup tables.
+    pub account_keys: Vec<Pubkey>,
+    pub log_messages: Vec<String>,
+    pub token_balances: Vec<TokenBalanceRecord>,
+}
+
+impl TransactionRecord {
+    /// Returns the token balance change for the given account, if it was touched.
+    pub fn token_balance(&self, account: &Pubkey) -> Option<&TokenBalanceRecord> {
+        self.token_balances.iter().find(|b| b.account == *account)
+    }
+
+    /// Converts a transaction fetched with the base64 encoding.
+    pub fn from_encoded(
+        signature: Signature,
+        encoded: EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<Self, LiteClientError> {
+        let invalid = |what: &str| LiteClientError::Rpc(format!("invalid transaction {}", what));
+
+        let transaction = encoded
+            .transaction
+            .transaction
+            .decode()
+            .ok_or_else(|| invalid("encoding"))?;
+        let meta = encoded
+            .transaction
+            .meta
+            .ok_or_else(|| invalid("status meta"))?;
+
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let Some(UiLoadedAddresses { writable, readonly }) = Option::from(meta.loaded_addresses)
+        {
+            for key in writable.iter().chain(readonly.iter()) {
+                account_keys.push(Pubkey::from_str(key).map_err(|_| invalid("loaded address"))?);
+            }
+        }
+
+        let mut token_balances: Vec<TokenBalanceRecord> = Vec::new();
+        let pre: Option<Vec<_>> = meta.pre_token_balances.into();
+        let post: Option<Vec<_>> = meta.post_token_balances.into();
+        for (is_post, balance) in pre
+            .unwrap_or_default()
+            .into_iter()
+            .map(|b| (false, b))
+            .chain(post.unwrap_or_default().into_iter().map(|b| (true, b)))
+        {
+            let account = *account_keys
+                .get(balance.account_index as usize)
+                .ok_or_else(|| invalid("token balance index"))?;
+            let amount = balance
+                .ui_token_amount
+                .amount
+                .parse::<u64>()
+                .map_err(|_| invalid("token amount"))?;
+
+            let index = match token_balances.iter().position(|b| b.account == account) {
+                Some(index) => index,
+                None => {
+                    let owner: Option<String> = balance.owner.into();
+                    token_balances.push(TokenBalanceRecord {
+                        account,
+                        mint: Pubkey::from_str(&balance.mint).map_err(|_| invalid("mint"))?,
+                        owner: owner.and_then(|owner| Pubkey::from_str(&owner).ok()),
+                        ..Default::default()
+                    });
+                    token_balances.len() - 1
+                }
+            };
+            if is_post {
+                token_balances[index].post = amount;
+            } else {
+                token_balances[index].pre = amount;
+            }
+        }
+
+        let log_messages: Option<Vec<String>> = meta.log_messages.into();
+
+        Ok(Self {
+            signature,
+            slot: encoded.slot,
+            failed: meta.err.is_some(),
+            account_keys,
+            log_messages: log_messages.unwrap_or_default(),
+            token_balances,
+        })
+    }
+}
@@ -0,0 +1,251 @@
+use crate::client::journal::lock;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The status of a request tracked by an [`Indexer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexedStatus {
+    /// The request is waiting for the oracle.
+    Pending,
+    /// The oracle could not invoke the callback and wrote an `error_message`.
+    Failed,
+    /// The request account is gone. From `Pending`, the oracle settled the request; from
+    /// `Failed`, the user closed it.
+    Closed,
+}
+
+impl IndexedStatus {
+    /// The status of an open request account.
+    pub fn of(request: &SimpleRandomnessV1Account) -> Self {
+        if request.is_completed != 0 || !request.error_message.is_empty() {
+            IndexedStatus::Failed
+        } else {
+            IndexedStatus::Pending
+        }
+    }
+}
+
+/// A request as last seen by an [`Indexer`].
+#[derive(Clone, Debug)]
+pub struct IndexedRequest {
+    /// The request account.
+    pub pubkey: Pubkey,
+    pub status: IndexedStatus,
+    /// The request account as last fetched. Kept once the account is closed.
+    pub request: SimpleRandomnessV1Account,
+    /// The slot of the poll that observed the status.
+    pub slot: u64,
+}
+
+/// A status change observed by [`Indexer::poll`]. `from` is `None` for a newly discovered
+/// request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusTransition {
+    pub request: Pubkey,
+    pub from: Option<IndexedStatus>,
+    pub to: IndexedStatus,
+    pub slot: u64,
+    /// The request's callback arguments, if the indexer has a
+    /// [`SchemaRegistry`](crate::client::SchemaRegistry) with a schema for its callback.
+    pub callback_args: Option<DecodedArgs>,
+}
+
+/// Persists the requests an [`Indexer`] tracks, e.g. in the database of the service embedding
+/// it. Errors abort the poll.
+pub trait RequestStore: Send + Sync {
+    /// Inserts the request, replacing any previous entry for its pubkey.
+    fn upsert(&self, request: IndexedRequest) -> std::io::Result<()>;
+
+    /// Returns the request's entry, if one was stored.
+    fn get(&self, pubkey: &Pubkey) -> std::io::Result<Option<IndexedRequest>>;
+
+    /// Returns every stored request, in no particular order.
+    fn list(&self) -> std::io::Result<Vec<IndexedRequest>>;
+}
+
+/// Keeps the indexed requests in memory. Intended for tests and for services that rebuild the
+/// index on restart.
+#[derive(Default)]
+pub struct MemoryRequestStore {
+    requests: Mutex<HashMap<Pubkey, IndexedRequest>>,
+}
+
+impl MemoryRequestStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RequestStore for MemoryRequestStore {
+    fn upsert(&self, request: IndexedRequest) -> std::io::Result<()> {
+        lock(&self.requests)?.insert(request.pubkey, request);
+        Ok(())
+    }
+
+    fn get(&self, pubkey: &Pubkey) -> std::io::Result<Option<IndexedRequest>> {
+        Ok(lock(&self.requests)?.get(pubkey).cloned())
+    }
+
+    fn list(&self) -> std::io::Result<Vec<IndexedRequest>> {
+        Ok(lock(&self.requests)?.values().cloned().collect())
+    }
+}
+
+/// Sees every [`StatusTransition`] an [`Indexer`] observes, once, after it is stored.
+pub trait TransitionHandler: Send + Sync {
+    fn on_transition(&self, transition: &StatusTransition);
+}
+
+/// Indexes the requests whose callback targets one consumer program by polling the RPC, for
+/// services that run neither Geyser nor webhooks.
+///
+/// Each [`poll`](Self::poll) enumerates the open requests with [`discover_requests`], then
+/// fetches every stored request that is still open but was not discovered, since a settled
+/// request is closed and a signature scan misses older ones. A status that differs from the
+/// stored one is stored and reported to the handlers, so a transition is reported once even
+/// if later polls see the request again.
+pub struct Indexer {
+    program_id: Pubkey,
+    consumer: Pubkey,
+    strategy: Option<DiscoveryStrategy>,
+    store: Arc<dyn RequestStore>,
+    handlers: Vec<Arc<dyn TransitionHandler>>,
+    schemas: Option<Arc<SchemaRegistry>>,
+}
+
+impl Indexer {
+    pub fn new(consumer: Pubkey, store: Arc<dyn RequestStore>) -> Self {
+        Self {
+            program_id: ID,
+            consumer,
+            strategy: None,
+            store,
+            handlers: Vec::new(),
+            schemas: None,
+        }
+    }
+
+    /// Overrides the randomness service program ID.
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Sets the [`DiscoveryStrategy`] instead of negotiating it on every poll.
+    pub fn strategy(mut self, strategy: DiscoveryStrategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Reports every transition to the handler.
+    pub fn with_handler(mut self, handler: Arc<dyn TransitionHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// Decodes the callback arguments of every transition with the registry.
+    pub fn with_schemas(mut self, schemas: Arc<SchemaRegistry>) -> Self {
+        self.schemas = Some(schemas);
+        self
+    }
+
+    /// Polls once, returning the transitions observed. Each was stored and reported to the
+    /// handlers as it was observed, so a store error leaves the transitions before it
+    /// reported and the rest for the next poll.
+    pub async fn poll<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+    ) -> Result<Vec<StatusTransition>, LiteClientError> {
+        let discovered = discover_requests(rpc, &self.program_id, self.strategy).await?;
+        let slot = discovered.slot;
+
+        let mut transitions = Vec::new();
+        let mut seen = HashSet::new();
+        for (pubkey, request) in discovered.requests {
+            if request.callback.program_id != self.consumer {
+                continue;
+            }
+            seen.insert(pubkey);
+            let status = IndexedStatus::of(&request);
+            transitions.extend(self.update(pubkey, status, Some(request), slot)?);
+        }
+
+        let stored = self.store.list().map_err(LiteClientError::Store)?;
+        for indexed in stored {
+            if indexed.status == IndexedStatus::Closed || seen.contains(&indexed.pubkey) {
+                continue;
+            }
+            let account = rpc.get_account(&indexed.pubkey).await?;
+            let (status, request) = match RequestStatus::from_account(account.as_ref())? {
+                RequestStatus::Closed => (IndexedStatus::Closed, None),
+                status => match status.request() {
+                    Some(request) => (IndexedStatus::of(request), Some(request.clone())),
+                    None => continue,
+                },
+            };
+            transitions.extend(self.update(indexed.pubkey, status, request, slot)?);
+        }
+
+        Ok(transitions)
+    }
+
+    /// Polls every `period` until the task is dropped. A failed poll is logged and retried at
+    /// the next tick, so run it in a task of its own, e.g. with `tokio::spawn`.
+    pub async fn run<R: RandomnessRpc + ?Sized>(&self, rpc: &R, period: Duration) {
+        let mut interval = tokio::time::interval(period.max(MIN_POLL_INTERVAL));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.poll(rpc).await {
+                log::warn!("Failed to poll randomness requests: {}", e);
+            }
+        }
+    }
+
+    /// Stores the request's status if it changed and reports the transition to the handlers.
+    /// `request` is the fetched account, `None` once it is closed.
+    fn update(
+        &self,
+        pubkey: Pubkey,
+        status: IndexedStatus,
+        request: Option<SimpleRandomnessV1Account>,
+        slot: u64,
+    ) -> Result<Option<StatusTransition>, LiteClientError> {
+        let previous = self.store.get(&pubkey).map_err(LiteClientError::Store)?;
+        let from = previous.as_ref().map(|p| p.status);
+        if from == Some(status) {
+            return Ok(None);
+        }
+        let Some(request) = request.or(previous.map(|p| p.request)) else {
+            return Ok(None);
+        };
+        let callback_args = self
+            .schemas
+            .as_ref()
+            .and_then(|schemas| schemas.decode_callback_args(&request.callback.ix_data));
+
+        self.store
+            .upsert(IndexedRequest {
+                pubkey,
+                status,
+                request,
+                slot,
+            })
+            .map_err(LiteClientError::Store)?;
+        let transition = StatusTransition {
+            request: pubkey,
+            from,
+            to: status,
+            slot,
+            callback_args,
+        };
+        for handler in &self.handlers {
+            handler.on_transition(&transition);
+        }
+        Ok(Some(transition))
+    }
+}
@@ -0,0 +1,227 @@
+use crate::client::*;
+use crate::*;
+use base64::Engine;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use solana_program::rent::Rent;
+use std::str::FromStr;
+
+/// Everything needed to build a randomness request later, for job queues where one service
+/// creates the request and another sends it.
+///
+/// An intent serializes with Borsh and, with base58 addresses and base64 instruction data, with
+/// serde. It holds no payer, blockhash, or request keypair: the worker that materializes it
+/// with [`into_builder`](Self::into_builder) supplies those.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(try_from = "RequestIntentJson", into = "RequestIntentJson")]
+pub struct RequestIntent {
+    pub num_bytes: u8,
+    pub callback: Callback,
+    /// The options sent with the request. `None` sends the advised budget for the callback.
+    pub options: Option<TransactionOptions>,
+    /// The [`RequestTag`] label, sent as a memo with the request.
+    pub tag: Option<String>,
+    /// The most the request may debit its payer, the [`FundingBreakdown::total`], in lamports.
+    pub max_cost: Option<u64>,
+}
+
+impl RequestIntent {
+    pub fn new(num_bytes: u8, callback: Callback) -> Self {
+        Self {
+            num_bytes,
+            callback,
+            options: None,
+            tag: None,
+            max_cost: None,
+        }
+    }
+
+    pub fn options(mut self, options: Option<TransactionOptions>) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn tag(mut self, label: &str) -> Self {
+        self.tag = Some(label.to_string());
+        self
+    }
+
+    pub fn max_cost(mut self, max_cost: u64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Checks the number of bytes, the callback, the options, and the tag label the way the
+    /// request would be checked when it is built. An intent read back from storage should be
+    /// validated again, since nothing checks it on deserialization.
+    pub fn validate(&self) -> Result<(), LiteClientError> {
+        check_num_bytes(self.num_bytes)?;
+        self.callback.validate()?;
+        if let Some(options) = &self.options {
+            options.validate().map_err(ProgramError::from)?;
+        }
+        if let Some(label) = &self.tag {
+            RequestTag::new(label)?;
+        }
+        Ok(())
+    }
+
+    /// What the request will debit its payer at the service's current pricing, with the
+    /// default rent every cluster runs with.
+    pub fn estimated_cost(
+        &self,
+        state: &ServiceState,
+    ) -> Result<FundingBreakdown, LiteClientError> {
+        let options = self
+            .options
+            .clone()
+            .or_else(|| Some(TransactionOptions::advised_for_callback(&self.callback)));
+        Ok(FundingBreakdown::for_state(
+            self.num_bytes,
+            &self.callback,
+            &options,
+            state,
+            &Rent::default(),
+        )?)
+    }
+
+    /// Like [`estimated_cost`](Self::estimated_cost), failing with
+    /// [`LiteClientError::CostCeilingExceeded`] if the total is over the intent's `max_cost`.
+    pub fn check_cost(&self, state: &ServiceState) -> Result<FundingBreakdown, LiteClientError> {
+        let funding = self.estimated_cost(state)?;
+        match self.max_cost {
+            Some(max_cost) if funding.total() > max_cost => {
+                Err(LiteClientError::CostCeilingExceeded {
+                    max_cost,
+                    cost: funding.total(),
+                })
+            }
+            _ => Ok(funding),
+        }
+    }
+
+    /// Validates the intent and returns a [`RequestBuilder`] for it, paid by `payer`. Set the
+    /// builder's cluster addresses and `cost_per_byte` as for any other request. The builder
+    /// keeps the intent's `max_cost`, so it also covers a referral fee added to the builder.
+    pub fn into_builder(self, payer: Pubkey) -> Result<RequestBuilder, LiteClientError> {
+        self.validate()?;
+        let mut builder =
+            RequestBuilder::new(payer, self.num_bytes, self.callback).options(self.options);
+        if let Some(label) = &self.tag {
+            builder = builder.with_tag(RequestTag::new(label)?);
+        }
+        if let Some(max_cost) = self.max_cost {
+            builder = builder.max_cost(max_cost);
+        }
+        Ok(builder)
+    }
+}
+
+/// The serde form of [`RequestIntent`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequestIntentJson {
+    num_bytes: u8,
+    callback: CallbackJson,
+    #[serde(default)]
+    options: Option<TransactionOptionsJson>,
+    #[serde(default)]
+    tag: Option<String>,
+    #[serde(default)]
+    max_cost: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CallbackJson {
+    program_id: String,
+    accounts: Vec<AccountMetaJson>,
+    /// Base64.
+    ix_data: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AccountMetaJson {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransactionOptionsJson {
+    #[serde(default)]
+    compute_units: Option<u32>,
+    #[serde(default)]
+    compute_unit_price: Option<u64>,
+}
+
+impl From<RequestIntent> for RequestIntentJson {
+    fn from(value: RequestIntent) -> Self {
+        Self {
+            num_bytes: value.num_bytes,
+            callback: CallbackJson {
+                program_id: value.callback.program_id.to_string(),
+                accounts: value
+                    .callback
+                    .accounts
+                    .iter()
+                    .map(|meta| AccountMetaJson {
+                        pubkey: meta.pubkey.to_string(),
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                ix_data: base64::engine::general_purpose::STANDARD.encode(&value.callback.ix_data),
+            },
+            options: value.options.map(|options| TransactionOptionsJson {
+                compute_units: options.compute_units,
+                compute_unit_price: options.compute_unit_price,
+            }),
+            tag: value.tag,
+            max_cost: value.max_cost,
+        }
+    }
+}
+
+impl TryFrom<RequestIntentJson> for RequestIntent {
+    type Error = String;
+
+    fn try_from(value: RequestIntentJson) -> Result<Self, Self::Error> {
+        let accounts = value
+            .callback
+            .accounts
+            .into_iter()
+            .map(|meta| {
+                Ok(AccountMetaBorsh {
+                    pubkey: parse_pubkey("callback account", &meta.pubkey)?,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        let ix_data = base64::engine::general_purpose::STANDARD
+            .decode(&value.callback.ix_data)
+            .map_err(|e| format!("invalid callback ix_data: {}", e))?;
+
+        Ok(Self {
+            num_bytes: value.num_bytes,
+            callback: Callback::new(
+                parse_pubkey("callback program_id", &value.callback.program_id)?,
+                accounts,
+                ix_data,
+            ),
+            options: value.options.map(|options| TransactionOptions {
+                compute_units: options.compute_units,
+                compute_unit_price: options.compute_unit_price,
+            }),
+            tag: value.tag,
+            max_cost: value.max_cost,
+        })
+    }
+}
+
+fn parse_pubkey(name: &str, value: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(value).map_err(|_| format!("invalid {}: {}", name, value))
+}
@@ -0,0 +1,194 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Remembers which request account was created for each logical job, so a client that restarts
+/// mid-request does not create a second one. Used by
+/// [`RequestBuilder::send_idempotent`](crate::client::RequestBuilder::send_idempotent).
+///
+/// Unlike an [`AuditSink`](crate::client::AuditSink), journal errors abort the send: a request
+/// that cannot be journaled could be duplicated after a restart.
+pub trait Journal: Send + Sync {
+    /// Records that a request account is about to be created for the job, replacing any
+    /// previous entry. Must be durable before it returns.
+    fn record_intent(&self, job_id: &str, request: &Pubkey) -> std::io::Result<()>;
+
+    /// Returns the job's entry, if one was recorded.
+    fn lookup(&self, job_id: &str) -> std::io::Result<Option<JournalEntry>>;
+
+    /// Records that the job's request transaction was confirmed.
+    fn mark_complete(&self, job_id: &str) -> std::io::Result<()>;
+}
+
+/// The request account recorded for a job.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub request: Pubkey,
+    /// Whether the request transaction was confirmed. An incomplete entry means the client
+    /// stopped between sending and confirming, and the chain has to be checked.
+    pub completed: bool,
+}
+
+/// Keeps the journal in memory. Intended for tests and for clients that only need to guard
+/// against retries within one process.
+#[derive(Default)]
+pub struct MemoryJournal {
+    entries: Mutex<HashMap<String, JournalEntry>>,
+}
+
+impl MemoryJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Journal for MemoryJournal {
+    fn record_intent(&self, job_id: &str, request: &Pubkey) -> std::io::Result<()> {
+        lock(&self.entries)?.insert(
+            job_id.to_string(),
+            JournalEntry {
+                request: *request,
+                completed: false,
+            },
+        );
+        Ok(())
+    }
+
+    fn lookup(&self, job_id: &str) -> std::io::Result<Option<JournalEntry>> {
+        Ok(lock(&self.entries)?.get(job_id).copied())
+    }
+
+    fn mark_complete(&self, job_id: &str) -> std::io::Result<()> {
+        mark_complete(&mut *lock(&self.entries)?, job_id)
+    }
+}
+
+/// A line of the [`FileJournal`].
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+enum JournalLine {
+    Intent { job_id: String, request: String },
+    Complete { job_id: String },
+}
+
+/// Appends each journal update as a line of JSON to a file, synced to disk before returning.
+///
+/// The file is replayed when it is opened, so a restarted client sees the entries written
+/// before it stopped.
+pub struct FileJournal {
+    file: Mutex<File>,
+    entries: Mutex<HashMap<String, JournalEntry>>,
+}
+
+impl FileJournal {
+    /// Opens the file in append mode, creating it if needed, and replays its entries.
+    ///
+    /// A truncated last line, left by a crash mid-write, is ignored.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        let mut entries = HashMap::new();
+        let lines: Vec<String> = BufReader::new(&file).lines().collect::<Result<_, _>>()?;
+        for (index, line) in lines.iter().enumerate() {
+            let line = match serde_json::from_str(line) {
+                Ok(line) => line,
+                Err(_) if index + 1 == lines.len() => break,
+                Err(e) => return Err(invalid_data(format!("line {}: {}", index + 1, e))),
+            };
+            match line {
+                JournalLine::Intent { job_id, request } => {
+                    let request = Pubkey::from_str(&request)
+                        .map_err(|e| invalid_data(format!("line {}: {}", index + 1, e)))?;
+                    entries.insert(
+                        job_id,
+                        JournalEntry {
+                            request,
+                            completed: false,
+                        },
+                    );
+                }
+                JournalLine::Complete { job_id } => mark_complete(&mut entries, &job_id)?,
+            }
+        }
+
+        Ok(Self {
+            file: Mutex::new(file),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    fn append(&self, line: &JournalLine) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(line)?;
+        line.push(b'\n');
+
+        let mut file = lock(&self.file)?;
+        file.write_all(&line)?;
+        file.sync_data()
+    }
+}
+
+impl Journal for FileJournal {
+    fn record_intent(&self, job_id: &str, request: &Pubkey) -> std::io::Result<()> {
+        let mut entries = lock(&self.entries)?;
+        self.append(&JournalLine::Intent {
+            job_id: job_id.to_string(),
+            request: request.to_string(),
+        })?;
+        entries.insert(
+            job_id.to_string(),
+            JournalEntry {
+                request: *request,
+                completed: false,
+            },
+        );
+        Ok(())
+    }
+
+    fn lookup(&self, job_id: &str) -> std::io::Result<Option<JournalEntry>> {
+        Ok(lock(&self.entries)?.get(job_id).copied())
+    }
+
+    fn mark_complete(&self, job_id: &str) -> std::io::Result<()> {
+        let mut entries = lock(&self.entries)?;
+        // Check before appending so the file never completes an unknown job
+        if !entries.contains_key(job_id) {
+            return mark_complete(&mut entries, job_id);
+        }
+        self.append(&JournalLine::Complete {
+            job_id: job_id.to_string(),
+        })?;
+        mark_complete(&mut entries, job_id)
+    }
+}
+
+fn mark_complete(entries: &mut HashMap<String, JournalEntry>, job_id: &str) -> std::io::Result<()> {
+    match entries.get_mut(job_id) {
+        Some(entry) => {
+            entry.completed = true;
+            Ok(())
+        }
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no journal entry for job {}", job_id),
+        )),
+    }
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> std::io::Result<std::sync::MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
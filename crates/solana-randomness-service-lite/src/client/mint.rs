@@ -0,0 +1,185 @@
+use crate::addresses::NATIVE_MINT;
+use crate::client::*;
+use crate::*;
+use solana_sdk::account::Account;
+
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// The size of an SPL token mint account.
+const MINT_ACCOUNT_SIZE: usize = 82;
+/// The offset of `decimals` in an SPL token mint account.
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// How amounts of the escrow and reward mint are shown to people.
+///
+/// Every amount in this crate is a raw integer in the mint's smallest unit, lamports for wSOL,
+/// and JSON output keeps it that way. Use [`format`](Self::format) to render an amount for a
+/// report or a log line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintInfo {
+    pub decimals: u8,
+    pub symbol: String,
+}
+
+impl MintInfo {
+    /// Wrapped SOL, the mint the randomness service is deployed with.
+    pub fn native() -> Self {
+        Self {
+            decimals: 9,
+            symbol: "SOL".to_string(),
+        }
+    }
+
+    /// The built-in info for well known mints, without an RPC call.
+    pub fn from_table(mint: &Pubkey) -> Option<Self> {
+        (*mint == NATIVE_MINT).then(Self::native)
+    }
+
+    /// Reads the decimals from the mint account. There is no symbol on chain, so the mint
+    /// address is used; replace it with [`symbol`](Self::symbol) if one is known.
+    pub fn from_mint_account(mint: &Pubkey, account: &Account) -> Result<Self, ProgramError> {
+        if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if account.data.len() < MINT_ACCOUNT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            decimals: account.data[MINT_DECIMALS_OFFSET],
+            symbol: mint.to_string(),
+        })
+    }
+
+    /// Resolves the info for a mint, from the built-in table or the chain.
+    ///
+    /// Resolve it once and reuse it; the decimals of a mint never change.
+    pub async fn resolve<R: RandomnessRpc + ?Sized>(
+        rpc: &R,
+        mint: &Pubkey,
+    ) -> Result<Self, LiteClientError> {
+        if let Some(info) = Self::from_table(mint) {
+            return Ok(info);
+        }
+
+        let account = rpc
+            .get_account(mint)
+            .await?
+            .ok_or(ProgramError::UninitializedAccount)?;
+        Ok(Self::from_mint_account(mint, &account)?)
+    }
+
+    /// Replaces the symbol.
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = symbol.into();
+        self
+    }
+
+    /// Renders a raw amount in whole tokens, e.g. `2041000` lamports as `0.002041 SOL`.
+    /// Trailing zeros are dropped.
+    pub fn format(&self, amount: u64) -> String {
+        let decimals = usize::from(self.decimals);
+        let digits = format!("{:0>width$}", amount, width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+
+        if fraction.is_empty() {
+            format!("{} {}", whole, self.symbol)
+        } else {
+            format!("{}.{} {}", whole, fraction, self.symbol)
+        }
+    }
+}
+
+impl Default for MintInfo {
+    fn default() -> Self {
+        Self::native()
+    }
+}
+
+/// Renders a [`FundingBreakdown`] with a [`MintInfo`], one line per fee, and a line for the
+/// referral fee if there is one. Returned by
+/// [`FundingBreakdown::display`].
+pub struct FundingDisplay<'a> {
+    funding: &'a FundingBreakdown,
+    mint: &'a MintInfo,
+}
+
+impl FundingBreakdown {
+    /// Renders the breakdown in whole tokens of the mint.
+    pub fn display<'a>(&'a self, mint: &'a MintInfo) -> FundingDisplay<'a> {
+        FundingDisplay {
+            funding: self,
+            mint,
+        }
+    }
+}
+
+impl std::fmt::Display for FundingDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let funding = self.funding;
+        for (label, amount) in [
+            ("Base fee", funding.base_fee),
+            ("Randomness fee", funding.randomness_fee),
+            ("Priority fee", funding.priority_fee),
+            ("Request rent", funding.request_rent),
+            ("Escrow rent", funding.escrow_rent),
+        ] {
+            writeln!(f, "{:<16}{}", label, self.mint.format(amount))?;
+        }
+        if funding.referral_fee > 0 {
+            writeln!(
+                f,
+                "{:<16}{}",
+                "Referral fee",
+                self.mint.format(funding.referral_fee)
+            )?;
+        }
+        write!(f, "{:<16}{}", "Total", self.mint.format(funding.total()))
+    }
+}
+
+/// Renders a [`PaymentReconciliation`] with a [`MintInfo`], one line per payment followed by
+/// the totals. Returned by [`PaymentReconciliation::display`].
+pub struct ReconciliationDisplay<'a> {
+    reconciliation: &'a PaymentReconciliation,
+    mint: &'a MintInfo,
+}
+
+impl PaymentReconciliation {
+    /// Renders the report in whole tokens of the mint.
+    pub fn display<'a>(&'a self, mint: &'a MintInfo) -> ReconciliationDisplay<'a> {
+        ReconciliationDisplay {
+            reconciliation: self,
+            mint,
+        }
+    }
+}
+
+impl std::fmt::Display for ReconciliationDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reconciliation = self.reconciliation;
+        writeln!(
+            f,
+            "Payments by {} in slots {}..={}",
+            reconciliation.payer, reconciliation.range.start, reconciliation.range.end
+        )?;
+        for payment in reconciliation.payments.iter() {
+            writeln!(
+                f,
+                "{} paid {}, reward wallet received {} ({:?})",
+                payment.request,
+                self.mint.format(payment.paid),
+                self.mint.format(payment.reward_received),
+                payment.status
+            )?;
+        }
+        write!(
+            f,
+            "Total paid {}, received {}",
+            self.mint.format(reconciliation.total_paid()),
+            self.mint.format(reconciliation.total_received())
+        )
+    }
+}
@@ -0,0 +1,368 @@
+//! In-memory RPC and pubsub endpoints for testing the client helpers without a cluster.
+
+use crate::client::*;
+use crate::*;
+use borsh::BorshSerialize;
+use solana_program::rent::Rent;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Serializes a request into an account the way the service program allocates it.
+pub fn request_account(request: &SimpleRandomnessV1Account) -> Account {
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    data.extend(request.try_to_vec().unwrap());
+    data.resize(request.expected_request_account_size(), 0);
+
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Serves accounts from memory, records every transaction it is asked to send, and confirms it
+/// immediately.
+pub struct MockRpc {
+    blockhash: Hash,
+    accounts: Mutex<HashMap<Pubkey, Account>>,
+    sent: Mutex<Vec<Transaction>>,
+    history: Mutex<Vec<TransactionRecord>>,
+    fail_sends: AtomicBool,
+    program_accounts_disabled: AtomicBool,
+    offline: AtomicBool,
+    latency: Mutex<Duration>,
+    calls: AtomicUsize,
+    slot: Mutex<Option<u64>>,
+}
+
+impl Default for MockRpc {
+    fn default() -> Self {
+        Self::new(Hash::new_unique())
+    }
+}
+
+impl MockRpc {
+    pub fn new(blockhash: Hash) -> Self {
+        Self {
+            blockhash,
+            accounts: Mutex::new(HashMap::new()),
+            sent: Mutex::new(Vec::new()),
+            history: Mutex::new(Vec::new()),
+            fail_sends: AtomicBool::new(false),
+            program_accounts_disabled: AtomicBool::new(false),
+            offline: AtomicBool::new(false),
+            latency: Mutex::new(Duration::ZERO),
+            calls: AtomicUsize::new(0),
+            slot: Mutex::new(None),
+        }
+    }
+
+    /// The blockhash returned by `get_latest_blockhash`.
+    pub fn blockhash(&self) -> Hash {
+        self.blockhash
+    }
+
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.accounts.lock().unwrap().insert(pubkey, account);
+    }
+
+    pub fn remove_account(&self, pubkey: &Pubkey) {
+        self.accounts.lock().unwrap().remove(pubkey);
+    }
+
+    /// Adds a confirmed transaction to the history served by `get_signatures_for_address` and
+    /// `get_transaction`.
+    pub fn add_transaction(&self, transaction: TransactionRecord) {
+        self.history.lock().unwrap().push(transaction);
+    }
+
+    /// Returns a copy of the confirmed transactions, oldest first.
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// The number of RPC calls received, including failed ones.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// When set, `send_and_confirm_transaction` returns an RPC error.
+    pub fn set_fail_sends(&self, fail: bool) {
+        self.fail_sends.store(fail, Ordering::SeqCst);
+    }
+
+    /// When set, `get_program_accounts` fails the way a provider that disables the method
+    /// does.
+    pub fn set_program_accounts_disabled(&self, disabled: bool) {
+        self.program_accounts_disabled
+            .store(disabled, Ordering::SeqCst);
+    }
+
+    /// When set, every call returns an RPC error, as if the endpoint were unreachable.
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+
+    /// Delays every call by the given duration.
+    pub fn set_latency(&self, latency: Duration) {
+        *self.latency.lock().unwrap() = latency;
+    }
+
+    /// Sets the slot returned by `get_slot` and reported by `get_program_accounts`. Until it
+    /// is set, the slot of the newest transaction in the history stands in for the current
+    /// slot.
+    pub fn warp_to_slot(&self, slot: u64) {
+        *self.slot.lock().unwrap() = Some(slot);
+    }
+
+    fn current_slot(&self) -> u64 {
+        let warped = *self.slot.lock().unwrap();
+        warped.unwrap_or_else(|| {
+            self.history
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|t| t.slot)
+                .max()
+                .unwrap_or_default()
+        })
+    }
+
+    async fn begin_call(&self) -> Result<(), LiteClientError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        let latency = *self.latency.lock().unwrap();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        if self.offline.load(Ordering::SeqCst) {
+            return Err(LiteClientError::Rpc("mock endpoint offline".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl RandomnessRpc for MockRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, LiteClientError> {
+        self.begin_call().await?;
+        Ok(self.blockhash)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, LiteClientError> {
+        self.begin_call().await?;
+        Ok(self.accounts.lock().unwrap().get(pubkey).cloned())
+    }
+
+    async fn get_account_with_slot(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<(u64, Option<Account>), LiteClientError> {
+        self.begin_call().await?;
+        let account = self.accounts.lock().unwrap().get(pubkey).cloned();
+        Ok((self.current_slot(), account))
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, LiteClientError> {
+        self.begin_call().await?;
+        if self.fail_sends.load(Ordering::SeqCst) {
+            return Err(LiteClientError::Rpc("mock send failure".to_string()));
+        }
+
+        transaction
+            .verify()
+            .map_err(|e| LiteClientError::Rpc(e.to_string()))?;
+
+        self.sent.lock().unwrap().push(transaction.clone());
+
+        Ok(transaction.signatures[0])
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureRecord>, LiteClientError> {
+        self.begin_call().await?;
+
+        let mut history: Vec<TransactionRecord> = self
+            .history
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.account_keys.contains(address))
+            .cloned()
+            .collect();
+        // Newest first, keeping insertion order within a slot reversed like the RPC does
+        history.reverse();
+        history.sort_by_key(|t| std::cmp::Reverse(t.slot));
+
+        let start = match before {
+            Some(before) => match history.iter().position(|t| t.signature == before) {
+                Some(index) => index + 1,
+                None => return Ok(vec![]),
+            },
+            None => 0,
+        };
+
+        Ok(history
+            .iter()
+            .skip(start)
+            .take(limit)
+            .map(|t| SignatureRecord {
+                signature: t.signature,
+                slot: t.slot,
+                failed: t.failed,
+            })
+            .collect())
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionRecord>, LiteClientError> {
+        self.begin_call().await?;
+        Ok(self
+            .history
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.signature == *signature)
+            .cloned())
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        discriminator: [u8; 8],
+    ) -> Result<(u64, Vec<(Pubkey, Account)>), LiteClientError> {
+        self.begin_call().await?;
+        if self.program_accounts_disabled.load(Ordering::SeqCst) {
+            return Err(LiteClientError::ProgramAccountsDisabled(
+                "Method not found".to_string(),
+            ));
+        }
+
+        let slot = self.current_slot();
+        let accounts = self
+            .accounts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, account)| {
+                account.owner == *program_id && account.data.get(..8) == Some(&discriminator[..])
+            })
+            .map(|(pubkey, account)| (*pubkey, account.clone()))
+            .collect();
+        Ok((slot, accounts))
+    }
+    async fn get_slot(&self) -> Result<u64, LiteClientError> {
+        self.begin_call().await?;
+        Ok(self.current_slot())
+    }
+}
+
+/// Delivers account updates pushed by the test to its subscribers.
+#[derive(Default)]
+pub struct MockPubsub {
+    subscribers: Mutex<HashMap<Pubkey, Vec<mpsc::UnboundedSender<Option<Account>>>>>,
+    subscriptions: AtomicUsize,
+    offline: AtomicBool,
+}
+
+impl MockPubsub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends an account update to every subscriber of the account.
+    pub fn notify(&self, pubkey: &Pubkey, account: Option<Account>) {
+        if let Some(subscribers) = self.subscribers.lock().unwrap().get_mut(pubkey) {
+            subscribers.retain(|tx| tx.send(account.clone()).is_ok());
+        }
+    }
+
+    /// Drops every open subscription, as if the websocket disconnected.
+    pub fn disconnect(&self) {
+        self.subscribers.lock().unwrap().clear();
+    }
+
+    /// Drops the open subscriptions to one account, as if their streams failed.
+    pub fn disconnect_account(&self, pubkey: &Pubkey) {
+        self.subscribers.lock().unwrap().remove(pubkey);
+    }
+
+    /// The number of subscriptions opened, including disconnected ones.
+    pub fn subscriptions(&self) -> usize {
+        self.subscriptions.load(Ordering::SeqCst)
+    }
+
+    /// When set, new subscriptions fail.
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+}
+
+#[async_trait::async_trait]
+impl RandomnessPubsub for MockPubsub {
+    async fn subscribe_account(&self, pubkey: &Pubkey) -> Result<AccountUpdates, LiteClientError> {
+        if self.offline.load(Ordering::SeqCst) {
+            return Err(LiteClientError::Rpc("mock pubsub offline".to_string()));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .entry(*pubkey)
+            .or_default()
+            .push(tx);
+        self.subscriptions.fetch_add(1, Ordering::SeqCst);
+
+        Ok(rx)
+    }
+}
+
+/// A [`LimiterClock`] that only moves when told to. A sleep returns at once, advancing the
+/// clock to its deadline if it is not already past it, so a test observes when each wait
+/// would have ended.
+#[derive(Default)]
+pub struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[async_trait::async_trait]
+impl LimiterClock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+
+    async fn sleep_until(&self, deadline: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now = (*now).max(deadline);
+    }
+}
@@ -0,0 +1,133 @@
+//! Off-chain helpers for building and sending randomness requests.
+//!
+//! Enabled with the `client` feature.
+//!
+//! The helpers run inside long-lived services, so nothing here may panic on a failed call:
+//! errors are returned as [`LiteClientError`], and `unwrap` and `expect` are denied outside
+//! the [`mock`] module.
+
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+mod error;
+pub use error::*;
+
+mod serde_utils;
+
+mod events;
+pub use events::*;
+
+mod rpc;
+pub use rpc::*;
+
+mod clock;
+pub use clock::*;
+
+mod history;
+pub use history::*;
+
+mod failover;
+pub use failover::*;
+
+mod pubsub;
+pub use pubsub::*;
+
+mod funding;
+pub use funding::*;
+
+mod mint;
+pub use mint::*;
+
+mod rate_limit;
+pub use rate_limit::*;
+
+mod builder;
+pub use builder::*;
+
+mod transaction;
+pub use transaction::*;
+
+mod intent;
+pub use intent::*;
+
+mod delegate;
+pub use delegate::*;
+
+mod referral;
+pub use referral::*;
+
+mod tag;
+pub use tag::*;
+
+mod session;
+pub use session::*;
+
+mod batch;
+pub use batch::*;
+
+mod refresh;
+pub use refresh::*;
+
+mod reclaim;
+pub use reclaim::*;
+
+mod tuning;
+pub use tuning::*;
+
+mod audit;
+pub use audit::*;
+
+mod journal;
+pub use journal::*;
+
+mod fulfillment;
+pub use fulfillment::*;
+
+mod event_id;
+pub use event_id::*;
+
+mod schema;
+pub use schema::*;
+
+mod accounts;
+pub use accounts::*;
+
+mod fetch;
+pub use fetch::*;
+
+mod discovery;
+pub use discovery::*;
+
+pub mod filters;
+
+mod indexer;
+pub use indexer::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod reconcile;
+pub use reconcile::*;
+
+mod stats;
+pub use stats::*;
+
+mod fingerprint;
+pub use fingerprint::*;
+
+mod overrides;
+pub use overrides::*;
+
+mod queue;
+pub use queue::*;
+
+mod feed;
+pub use feed::*;
+
+mod cache;
+pub use cache::*;
+
+mod preflight;
+pub use preflight::*;
+
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+pub mod mock;
@@ -0,0 +1,125 @@
+use crate::*;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// The JSON form of [`AddressOverrides`], with base58 addresses.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddressOverridesJson {
+    program_id: Option<String>,
+    state: Option<String>,
+    reward_wallet: Option<String>,
+    reward_mint: Option<String>,
+    switchboard_function: Option<String>,
+    switchboard_service: Option<String>,
+}
+
+/// The error returned when loading [`AddressOverrides`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum OverridesError {
+    /// An override is not a valid base58 address.
+    InvalidAddress { name: String, value: String },
+    /// The overrides file could not be read.
+    Io(std::io::Error),
+    /// The overrides are not a valid JSON object.
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for OverridesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverridesError::InvalidAddress { name, value } => {
+                write!(f, "Invalid address override {}: {}", name, value)
+            }
+            OverridesError::Io(e) => write!(f, "Failed to read address overrides: {}", e),
+            OverridesError::Json(e) => write!(f, "Invalid address overrides: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for OverridesError {}
+
+impl From<std::io::Error> for OverridesError {
+    fn from(value: std::io::Error) -> Self {
+        OverridesError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for OverridesError {
+    fn from(value: serde_json::Error) -> Self {
+        OverridesError::Json(value)
+    }
+}
+
+fn parse_address(name: &str, value: Option<String>) -> Result<Option<Pubkey>, OverridesError> {
+    value
+        .map(|value| {
+            Pubkey::from_str(value.trim()).map_err(|_| OverridesError::InvalidAddress {
+                name: name.to_string(),
+                value,
+            })
+        })
+        .transpose()
+}
+
+impl AddressOverrides {
+    /// The environment variables read by [`from_env`](Self::from_env), in field order.
+    pub const ENV_VARS: [&'static str; 6] = [
+        "RANDOMNESS_PROGRAM_ID",
+        "RANDOMNESS_STATE",
+        "RANDOMNESS_REWARD_WALLET",
+        "RANDOMNESS_REWARD_MINT",
+        "RANDOMNESS_SWITCHBOARD_FUNCTION",
+        "RANDOMNESS_SWITCHBOARD_SERVICE",
+    ];
+
+    /// Reads overrides from the [`ENV_VARS`](Self::ENV_VARS) environment variables. Unset or
+    /// empty variables are not overridden.
+    pub fn from_env() -> Result<Self, OverridesError> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Reads overrides from the [`ENV_VARS`](Self::ENV_VARS) names using `lookup`.
+    pub fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, OverridesError> {
+        let var =
+            |name: &str| parse_address(name, lookup(name).filter(|value| !value.trim().is_empty()));
+        let [program_id, state, reward_wallet, reward_mint, switchboard_function, switchboard_service] =
+            Self::ENV_VARS;
+
+        Ok(Self {
+            program_id: var(program_id)?,
+            state: var(state)?,
+            reward_wallet: var(reward_wallet)?,
+            reward_mint: var(reward_mint)?,
+            switchboard_function: var(switchboard_function)?,
+            switchboard_service: var(switchboard_service)?,
+        })
+    }
+
+    /// Parses overrides from a JSON object keyed by field name, e.g.
+    /// `{"switchboard_function": "<ADDRESS>"}`. Missing keys are not overridden.
+    pub fn from_json(json: &str) -> Result<Self, OverridesError> {
+        let overrides: AddressOverridesJson = serde_json::from_str(json)?;
+
+        Ok(Self {
+            program_id: parse_address("program_id", overrides.program_id)?,
+            state: parse_address("state", overrides.state)?,
+            reward_wallet: parse_address("reward_wallet", overrides.reward_wallet)?,
+            reward_mint: parse_address("reward_mint", overrides.reward_mint)?,
+            switchboard_function: parse_address(
+                "switchboard_function",
+                overrides.switchboard_function,
+            )?,
+            switchboard_service: parse_address(
+                "switchboard_service",
+                overrides.switchboard_service,
+            )?,
+        })
+    }
+
+    /// Reads a JSON overrides file, see [`from_json`](Self::from_json).
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, OverridesError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
@@ -0,0 +1,40 @@
+use crate::client::*;
+use crate::*;
+
+/// Checks, before a request is sent, that `mint` is the mint recorded in the service's `state`
+/// account, the client side of [`check_escrow_mint`]. Reads only the state's `mint` field.
+///
+/// A cluster's service can be reconfigured to another mint, and requests passing the old one
+/// then fail on-chain; this fails with [`LiteClientError::MintMismatch`] instead.
+pub async fn preflight_request<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    state: &Pubkey,
+    mint: &Pubkey,
+) -> Result<(), LiteClientError> {
+    let expected = ServiceState::read_mint(&fetch_state_data(rpc, state, &ID).await?)?;
+    if expected != *mint {
+        return Err(LiteClientError::MintMismatch {
+            expected,
+            got: *mint,
+        });
+    }
+
+    Ok(())
+}
+
+/// Fetches the data of the service's `state` account, checking it exists and is owned by the
+/// service deployed at `program_id`.
+pub(crate) async fn fetch_state_data<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    state: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<Vec<u8>, LiteClientError> {
+    let account = rpc
+        .get_account(state)
+        .await?
+        .ok_or(ProgramError::UninitializedAccount)?;
+    if account.owner != *program_id {
+        return Err(ProgramError::IllegalOwner.into());
+    }
+    Ok(account.data)
+}
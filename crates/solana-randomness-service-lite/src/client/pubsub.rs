@@ -0,0 +1,105 @@
+use crate::client::LiteClientError;
+use crate::Pubkey;
+use futures::StreamExt;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::sync::{mpsc, oneshot};
+
+/// A stream of account updates. `None` means the account was closed. The channel closes when
+/// the subscription disconnects.
+pub type AccountUpdates = mpsc::UnboundedReceiver<Option<Account>>;
+
+/// The pubsub methods used by the client helpers.
+#[async_trait::async_trait]
+pub trait RandomnessPubsub: Send + Sync {
+    /// Subscribes to changes to an account.
+    async fn subscribe_account(&self, pubkey: &Pubkey) -> Result<AccountUpdates, LiteClientError>;
+}
+
+/// A [`RandomnessPubsub`] backed by a websocket endpoint. Each subscription opens its own
+/// connection.
+#[derive(Clone, Debug)]
+pub struct WebsocketPubsub {
+    url: String,
+    commitment: CommitmentConfig,
+}
+
+impl WebsocketPubsub {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+
+    pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl RandomnessPubsub for WebsocketPubsub {
+    async fn subscribe_account(&self, pubkey: &Pubkey) -> Result<AccountUpdates, LiteClientError> {
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let url = self.url.clone();
+        let pubkey = *pubkey;
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..Default::default()
+        };
+
+        // The subscription stream borrows the client, so both live in the forwarding task.
+        tokio::spawn(async move {
+            let client = match PubsubClient::new(&url).await {
+                Ok(client) => client,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(LiteClientError::Rpc(e.to_string())));
+                    return;
+                }
+            };
+            let (mut stream, unsubscribe) =
+                match client.account_subscribe(&pubkey, Some(config)).await {
+                    Ok(subscription) => subscription,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(LiteClientError::Rpc(e.to_string())));
+                        return;
+                    }
+                };
+            let _ = ready_tx.send(Ok(()));
+
+            loop {
+                tokio::select! {
+                    update = stream.next() => match update {
+                        Some(response) => {
+                            let account = response
+                                .value
+                                .decode::<Account>()
+                                .filter(|account| account.lamports > 0);
+                            if tx.send(account).is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    },
+                    _ = tx.closed() => break,
+                }
+            }
+
+            drop(stream);
+            unsubscribe().await;
+            let _ = client.shutdown().await;
+        });
+
+        ready_rx
+            .await
+            .map_err(|_| LiteClientError::Internal("pubsub task exited".to_string()))??;
+
+        Ok(rx)
+    }
+}
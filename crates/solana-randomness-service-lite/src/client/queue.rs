@@ -0,0 +1,154 @@
+use crate::client::serde_utils::*;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+use solana_program::clock::DEFAULT_MS_PER_SLOT;
+use std::time::Duration;
+
+/// How far back fulfillments are counted, about an hour of slots.
+pub const FULFILLMENT_WINDOW_SLOTS: u64 = 9_000;
+
+/// The most fulfillments sampled, one `getSignaturesForAddress` page.
+pub const FULFILLMENT_SAMPLE_LIMIT: usize = 1_000;
+
+/// Below this many fulfillments in the window the estimate is flagged as
+/// [`sparse`](QueueEstimate::sparse).
+pub const MIN_FULFILLMENTS_FOR_ESTIMATE: usize = 10;
+
+/// An estimate of how long a new request waits for the oracle.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QueueEstimate {
+    /// Requests waiting for the oracle. Requests that failed with an error message are not
+    /// counted.
+    pub pending_requests: usize,
+    pub recent_fulfillment_rate_per_min: f64,
+    /// The time to fulfill the pending requests and a new one at the recent rate, or `None` if
+    /// no fulfillments were observed.
+    #[serde(serialize_with = "serialize_opt_secs")]
+    pub estimated_wait: Option<Duration>,
+    /// The bounds of an approximate 95% interval around
+    /// [`estimated_wait`](Self::estimated_wait). `max_wait` is `None` when the observed rate is
+    /// consistent with no fulfillments at all.
+    #[serde(serialize_with = "serialize_opt_secs")]
+    pub min_wait: Option<Duration>,
+    #[serde(serialize_with = "serialize_opt_secs")]
+    pub max_wait: Option<Duration>,
+    pub fulfillments_observed: usize,
+    /// The slots the fulfillment rate was measured over.
+    pub window_slots: u64,
+    /// Fewer than [`MIN_FULFILLMENTS_FOR_ESTIMATE`] fulfillments were observed, so the interval
+    /// is wide and the estimate should be shown as rough.
+    pub sparse: bool,
+    /// How the pending requests were found, when fetched by [`estimate_queue_depth_for`]. A
+    /// [`SignatureScan`](DiscoveryStrategy::SignatureScan) only counts recent requests.
+    pub discovery: Option<DiscoveryStrategy>,
+}
+
+impl QueueEstimate {
+    /// Estimates the wait from the open request accounts and the slots of recent fulfillments.
+    ///
+    /// Fulfillments are counted over the last [`FULFILLMENT_WINDOW_SLOTS`], or from the oldest
+    /// one if the sample holds [`FULFILLMENT_SAMPLE_LIMIT`]. Slots are converted to time at the
+    /// nominal 400ms per slot. The interval treats fulfillments as a Poisson process and uses
+    /// the square root transform, `(sqrt(n) ± 0.98)²`.
+    pub fn new(
+        requests: &[(Pubkey, SimpleRandomnessV1Account)],
+        fulfillment_slots: &[u64],
+        current_slot: u64,
+    ) -> Self {
+        let pending_requests = requests
+            .iter()
+            .filter(|(_, request)| request.is_completed == 0)
+            .count();
+
+        let window_start = current_slot.saturating_sub(FULFILLMENT_WINDOW_SLOTS);
+        let observed: Vec<u64> = fulfillment_slots
+            .iter()
+            .copied()
+            .filter(|slot| (window_start..=current_slot).contains(slot))
+            .collect();
+        let window_slots = match observed.iter().min() {
+            Some(oldest) if observed.len() >= FULFILLMENT_SAMPLE_LIMIT => current_slot - oldest,
+            _ => current_slot - window_start,
+        }
+        .max(1);
+
+        let window_mins = window_slots as f64 * DEFAULT_MS_PER_SLOT as f64 / 60_000.0;
+        let n = observed.len() as f64;
+        // The new request waits behind every pending one
+        let position = (pending_requests + 1) as f64;
+        let wait = |fulfillments: f64| {
+            (fulfillments > 0.0)
+                .then(|| Duration::from_secs_f64(position * window_mins * 60.0 / fulfillments))
+        };
+
+        Self {
+            pending_requests,
+            recent_fulfillment_rate_per_min: n / window_mins,
+            estimated_wait: wait(n),
+            min_wait: wait((n.sqrt() + 0.98).powi(2)),
+            max_wait: wait((n.sqrt() - 0.98).max(0.0).powi(2)),
+            fulfillments_observed: observed.len(),
+            window_slots,
+            sparse: observed.len() < MIN_FULFILLMENTS_FOR_ESTIMATE,
+            discovery: None,
+        }
+    }
+}
+
+/// The slots of the successful transactions into the reward wallet in the last
+/// [`FULFILLMENT_WINDOW_SLOTS`], newest first.
+///
+/// Every settlement and error callback transfers the request escrow to the reward wallet, so
+/// its signatures stand in for the service's fulfillments without fetching each transaction.
+pub async fn recent_fulfillment_slots<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    reward_wallet: &Pubkey,
+    current_slot: u64,
+) -> Result<Vec<u64>, LiteClientError> {
+    let window_start = current_slot.saturating_sub(FULFILLMENT_WINDOW_SLOTS);
+    Ok(rpc
+        .get_signatures_for_address(reward_wallet, None, FULFILLMENT_SAMPLE_LIMIT)
+        .await?
+        .into_iter()
+        .filter(|s| !s.failed && s.slot >= window_start)
+        .map(|s| s.slot)
+        .collect())
+}
+
+/// Estimates the wait for a new request on the randomness service.
+pub async fn estimate_queue_depth<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+) -> Result<QueueEstimate, LiteClientError> {
+    estimate_queue_depth_for(rpc, &ServiceAddresses::default()).await
+}
+
+/// Estimates the wait for a new request on the service at the given addresses. The pending
+/// requests are found with [`discover_requests`], negotiating the strategy.
+pub async fn estimate_queue_depth_for<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    addresses: &ServiceAddresses,
+) -> Result<QueueEstimate, LiteClientError> {
+    let discovered = discover_requests(rpc, &addresses.program_id, None).await?;
+    let fulfillments =
+        recent_fulfillment_slots(rpc, &addresses.reward_wallet, discovered.slot).await?;
+    let mut estimate = QueueEstimate::new(&discovered.requests, &fulfillments, discovered.slot);
+    estimate.discovery = Some(discovered.strategy);
+    Ok(estimate)
+}
+
+/// Like [`estimate_queue_depth_for`], but counts the fulfillment window back from the slot
+/// `clock` reads instead of the slot the requests were fetched at.
+pub async fn estimate_queue_depth_with_clock<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    addresses: &ServiceAddresses,
+    clock: &dyn SlotClock,
+) -> Result<QueueEstimate, LiteClientError> {
+    let discovered = discover_requests(rpc, &addresses.program_id, None).await?;
+    let current_slot = clock.current_slot().await?;
+    let fulfillments =
+        recent_fulfillment_slots(rpc, &addresses.reward_wallet, current_slot).await?;
+    let mut estimate = QueueEstimate::new(&discovered.requests, &fulfillments, current_slot);
+    estimate.discovery = Some(discovered.strategy);
+    Ok(estimate)
+}
@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The time source of a [`RateLimiter`]. Swap in a
+/// [`ManualClock`](crate::client::mock::ManualClock) to test pacing without waiting.
+#[async_trait::async_trait]
+pub trait LimiterClock: Send + Sync {
+    /// The time elapsed since an arbitrary, fixed starting point.
+    fn now(&self) -> Duration;
+
+    /// Returns once [`now`](Self::now) reaches `deadline`.
+    async fn sleep_until(&self, deadline: Duration);
+}
+
+/// Reads the tokio clock, so a runtime with paused time drives it too.
+pub struct TokioClock {
+    start: tokio::time::Instant,
+}
+
+impl TokioClock {
+    pub fn new() -> Self {
+        Self {
+            start: tokio::time::Instant::now(),
+        }
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl LimiterClock for TokioClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    async fn sleep_until(&self, deadline: Duration) {
+        tokio::time::sleep_until(self.start + deadline).await;
+    }
+}
+
+/// Paces request transactions across every builder and task that shares it, so a burst of
+/// requests does not trip the RPC provider's rate limits. Attach it with
+/// [`RequestBuilder::with_rate_limiter`](crate::client::RequestBuilder::with_rate_limiter).
+///
+/// A token bucket: it holds up to `burst` tokens, refilled at `sustained_per_sec`, and every
+/// send takes one. A send that finds the bucket empty reserves the next token and waits for
+/// it, so waiting sends are released in the order they arrived.
+pub struct RateLimiter {
+    sustained_per_sec: f64,
+    burst: f64,
+    clock: Arc<dyn LimiterClock>,
+    bucket: Mutex<Bucket>,
+    waiting: AtomicUsize,
+}
+
+struct Bucket {
+    /// Negative while sends are waiting for tokens that have been reserved but not refilled.
+    tokens: f64,
+    refilled_at: Duration,
+}
+
+impl RateLimiter {
+    /// A full bucket of `burst` tokens, refilled at `sustained_per_sec` tokens per second.
+    ///
+    /// Panics if `sustained_per_sec` is not positive and finite, or `burst` is zero.
+    pub fn new(sustained_per_sec: f64, burst: u32) -> Self {
+        Self::with_clock(sustained_per_sec, burst, Arc::new(TokioClock::new()))
+    }
+
+    /// Like [`new`](Self::new), reading time from `clock`.
+    pub fn with_clock(sustained_per_sec: f64, burst: u32, clock: Arc<dyn LimiterClock>) -> Self {
+        assert!(
+            sustained_per_sec.is_finite() && sustained_per_sec > 0.0,
+            "sustained rate must be positive"
+        );
+        assert!(burst > 0, "burst must be at least one");
+
+        let refilled_at = clock.now();
+        Self {
+            sustained_per_sec,
+            burst: f64::from(burst),
+            clock,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                refilled_at,
+            }),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Takes a token, waiting until one is refilled if the bucket is empty.
+    pub async fn acquire(&self) {
+        let deadline = {
+            let mut bucket = self.refill();
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            bucket.refilled_at + Duration::from_secs_f64(-bucket.tokens / self.sustained_per_sec)
+        };
+
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let _waiting = Waiting(&self.waiting);
+        self.clock.sleep_until(deadline).await;
+    }
+
+    /// How much of the burst is in use, from `0.0` for a full bucket to `1.0` for an empty
+    /// one. Stays at `1.0` while sends are [`waiting`](Self::waiting).
+    pub fn saturation(&self) -> f64 {
+        let tokens = self.refill().tokens;
+        (1.0 - tokens / self.burst).clamp(0.0, 1.0)
+    }
+
+    /// The number of sends waiting for a token.
+    pub fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    fn refill(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        // The bucket is consistent after every statement, so a poisoned lock is still usable
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = self.clock.now();
+        let elapsed = now.saturating_sub(bucket.refilled_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.sustained_per_sec).min(self.burst);
+        bucket.refilled_at = bucket.refilled_at.max(now);
+        bucket
+    }
+}
+
+/// Decrements the waiting count when an [`acquire`](RateLimiter::acquire) ends, including when
+/// its future is dropped mid-wait.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
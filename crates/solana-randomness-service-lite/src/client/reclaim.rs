@@ -0,0 +1,245 @@
+use crate::client::fulfillment::resolve_outcome;
+use crate::client::*;
+use crate::*;
+use solana_sdk::signature::{Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// How many times each RPC call of the reclaim is attempted before the task gives up.
+pub const RECLAIM_MAX_ATTEMPTS: u32 = 3;
+
+/// The delay before retrying an RPC call of the reclaim that failed with a retryable error.
+pub const RECLAIM_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How a request watched by [`SentRequest::spawn_auto_reclaim`] ended.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReclaimOutcome {
+    /// The request was settled before the deadline, or closed without a settlement.
+    Fulfilled(FulfillmentOutcome),
+    /// The oracle completed the request with an error and the close transaction returned the
+    /// rent of the request and its escrow to the user.
+    Reclaimed {
+        signature: Signature,
+        /// The failed request, with its `error_message`.
+        request: SimpleRandomnessV1Account,
+    },
+    /// The request was settled while it was being reclaimed, so the close was not needed.
+    FulfilledDuringReclaim(FulfillmentOutcome),
+    /// The request was closed without a settlement while it was being reclaimed, by a close
+    /// whose confirmation was lost or by another party.
+    ClosedDuringReclaim,
+    /// The oracle had not completed the request by the deadline. The service only closes
+    /// requests the oracle completed, so it was left open.
+    StillPending(SimpleRandomnessV1Account),
+    /// The RPC kept failing, or the close transaction was rejected.
+    Failed(LiteClientError),
+    /// The task was cancelled with [`ReclaimHandle::cancel`] before it finished.
+    Cancelled,
+}
+
+/// The task spawned by [`SentRequest::spawn_auto_reclaim`]. Dropping the handle detaches the
+/// task; it keeps running and reports its outcome.
+pub struct ReclaimHandle {
+    cancel: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl ReclaimHandle {
+    /// Stops the task, which reports [`ReclaimOutcome::Cancelled`] unless it already finished.
+    /// A close transaction already sent may still land.
+    pub fn cancel(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            let _ = cancel.send(());
+        }
+    }
+
+    /// Whether the task has finished and reported its outcome.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Waits for the task to finish. A panic in the task, e.g. in `on_outcome`, is returned
+    /// as [`LiteClientError::Internal`].
+    pub async fn join(self) -> Result<(), LiteClientError> {
+        self.task
+            .await
+            .map_err(|e| LiteClientError::Internal(format!("reclaim task failed: {}", e)))
+    }
+}
+
+impl SentRequest {
+    /// Spawns a tokio task that waits for the request to be fulfilled until `deadline`,
+    /// polling its account every `poll_interval`, and reclaims it otherwise. The outcome is
+    /// passed to `on_outcome` once the task ends, including when it is cancelled.
+    ///
+    /// A request the oracle completed with an error, before or at the deadline, is closed
+    /// with [`close_request_instruction`] in a transaction paid and signed by `signer`. The
+    /// account is fetched again before the close is sent and after a failed send, so a
+    /// request settled in the meantime is reported as
+    /// [`FulfilledDuringReclaim`](ReclaimOutcome::FulfilledDuringReclaim) rather than as a
+    /// failure. Each RPC call is attempted up to [`RECLAIM_MAX_ATTEMPTS`] times while it fails
+    /// with a [retryable](LiteClientError::is_retryable) error.
+    pub fn spawn_auto_reclaim<R, F>(
+        &self,
+        rpc: Arc<R>,
+        signer: Arc<dyn Signer + Send + Sync>,
+        deadline: Duration,
+        poll_interval: Duration,
+        on_outcome: F,
+    ) -> ReclaimHandle
+    where
+        R: RandomnessRpc + ?Sized + 'static,
+        F: FnOnce(ReclaimOutcome) + Send + 'static,
+    {
+        let (cancel, cancelled) = oneshot::channel();
+        let request = self.request;
+        let task = tokio::spawn(async move {
+            let reclaim = reclaim(
+                rpc.as_ref(),
+                signer.as_ref(),
+                &request,
+                deadline,
+                poll_interval,
+            );
+            let outcome = tokio::select! {
+                outcome = reclaim => outcome,
+                Ok(()) = cancelled => ReclaimOutcome::Cancelled,
+            };
+            on_outcome(outcome);
+        });
+
+        ReclaimHandle {
+            cancel: Some(cancel),
+            task,
+        }
+    }
+}
+
+/// Closes every request of `user` that the oracle completed with an error, returning the
+/// rent of each request and its escrow to the user. The requests are found with
+/// [`discover_requests`] on `program_id` and each is closed like
+/// [`SentRequest::spawn_auto_reclaim`] closes one, in a transaction paid and signed by
+/// `signer`.
+///
+/// Returns each closed request with its [`ReclaimOutcome`], in discovery order. A close that
+/// fails is reported as [`ReclaimOutcome::Failed`] and does not stop the others.
+pub async fn sweep_failed_requests<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    signer: &(dyn Signer + Send + Sync),
+    program_id: &Pubkey,
+    user: &Pubkey,
+) -> Result<Vec<(Pubkey, ReclaimOutcome)>, LiteClientError> {
+    let discovered = discover_requests(rpc, program_id, None).await?;
+    let mut swept = Vec::new();
+    for (request, account) in discovered.requests {
+        if account.user != *user || account.is_completed == 0 {
+            continue;
+        }
+        let outcome = match close(rpc, signer, &request).await {
+            Ok(outcome) => outcome,
+            Err(e) => ReclaimOutcome::Failed(e),
+        };
+        swept.push((request, outcome));
+    }
+    Ok(swept)
+}
+
+async fn reclaim<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    signer: &(dyn Signer + Send + Sync),
+    request: &Pubkey,
+    deadline: Duration,
+    poll_interval: Duration,
+) -> ReclaimOutcome {
+    match await_fulfillment_polling(rpc, request, deadline, poll_interval).await {
+        Ok(FulfillmentOutcome::Failed(_)) | Err(AwaitError::Timeout { .. }) => {}
+        Ok(outcome) => return ReclaimOutcome::Fulfilled(outcome),
+        Err(AwaitError::Client(e)) => return ReclaimOutcome::Failed(e),
+    }
+
+    match close(rpc, signer, request).await {
+        Ok(outcome) => outcome,
+        Err(e) => ReclaimOutcome::Failed(e),
+    }
+}
+
+/// Closes the request if the oracle completed it, telling a request settled in the meantime
+/// apart from a failed close.
+async fn close<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    signer: &(dyn Signer + Send + Sync),
+    request: &Pubkey,
+) -> Result<ReclaimOutcome, LiteClientError> {
+    let status = fetch_status(rpc, request).await?;
+    let failed = match status {
+        RequestStatus::Failed(failed) => failed,
+        RequestStatus::Pending(pending) => return Ok(ReclaimOutcome::StillPending(pending)),
+        _ => return closed_during_reclaim(rpc, request).await,
+    };
+
+    let close = close_request_instruction(request, &failed);
+    let close = &close;
+    let sent = with_retries(move || async move {
+        let mut transaction =
+            Transaction::new_with_payer(std::slice::from_ref(close), Some(&signer.pubkey()));
+        let blockhash = rpc.get_latest_blockhash().await?;
+        transaction.try_sign(&[signer as &dyn Signer], blockhash)?;
+        rpc.send_and_confirm_transaction(&transaction).await
+    })
+    .await;
+
+    match sent {
+        Ok(signature) => Ok(ReclaimOutcome::Reclaimed {
+            signature,
+            request: failed,
+        }),
+        // The settle transaction may have closed the account first
+        Err(e) => match fetch_status(rpc, request).await? {
+            RequestStatus::Closed => closed_during_reclaim(rpc, request).await,
+            _ => Err(e),
+        },
+    }
+}
+
+async fn fetch_status<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+) -> Result<RequestStatus, LiteClientError> {
+    let account = with_retries(move || rpc.get_account(request)).await?;
+    RequestStatus::from_account(account.as_ref())
+}
+
+async fn closed_during_reclaim<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: &Pubkey,
+) -> Result<ReclaimOutcome, LiteClientError> {
+    let outcome =
+        with_retries(move || resolve_outcome(rpc, request, RequestStatus::Closed)).await?;
+    Ok(match outcome {
+        FulfillmentOutcome::ClosedBeforeFulfillment => ReclaimOutcome::ClosedDuringReclaim,
+        outcome => ReclaimOutcome::FulfilledDuringReclaim(outcome),
+    })
+}
+
+async fn with_retries<T, F, Fut>(mut call: F) -> Result<T, LiteClientError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, LiteClientError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(e) if e.is_retryable() && attempt < RECLAIM_MAX_ATTEMPTS => {
+                log::warn!("Reclaim attempt {} failed, retrying: {}", attempt, e);
+                attempt += 1;
+                tokio::time::sleep(RECLAIM_RETRY_DELAY).await;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::client::events::decode_events;
+use crate::client::serde_utils::*;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+use solana_sdk::signature::Signature;
+
+/// The number of signatures fetched per `getSignaturesForAddress` page.
+const SIGNATURE_PAGE_SIZE: usize = 1_000;
+
+/// An inclusive range of slots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct SlotRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl SlotRange {
+    pub fn contains(&self, slot: u64) -> bool {
+        self.start <= slot && slot <= self.end
+    }
+}
+
+/// How a payment into a request escrow was resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PaymentStatus {
+    /// The request was settled or errored and the reward wallet received the escrow.
+    Matched,
+    /// The request was resolved but the reward wallet received a different amount.
+    AmountMismatch,
+    /// The escrow was returned to the payer without a transfer to the reward wallet. The
+    /// current service always transfers the escrow to the reward wallet before closing it, so
+    /// this is not expected.
+    Refunded,
+    /// The request account is still open and waiting for the oracle.
+    Pending,
+    /// The request was paid for but never resolved or refunded.
+    Unmatched,
+}
+
+/// A single payment into a request escrow.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RequestPayment {
+    #[serde(serialize_with = "serialize_display")]
+    pub request: Pubkey,
+    #[serde(serialize_with = "serialize_display")]
+    pub request_signature: Signature,
+    pub request_slot: u64,
+    /// The lamports wrapped into the escrow.
+    pub paid: u64,
+    /// The transaction that settled, errored, or refunded the request.
+    #[serde(serialize_with = "serialize_opt_display")]
+    pub resolution_signature: Option<Signature>,
+    /// The lamports the reward wallet received for the request.
+    pub reward_received: u64,
+    pub status: PaymentStatus,
+}
+
+impl RequestPayment {
+    /// Whether the payment needs follow up.
+    pub fn is_discrepancy(&self) -> bool {
+        matches!(
+            self.status,
+            PaymentStatus::AmountMismatch | PaymentStatus::Unmatched
+        )
+    }
+}
+
+/// The payments a payer made to the randomness service in a slot range.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PaymentReconciliation {
+    #[serde(serialize_with = "serialize_display")]
+    pub payer: Pubkey,
+    pub range: SlotRange,
+    pub payments: Vec<RequestPayment>,
+}
+
+impl PaymentReconciliation {
+    pub fn total_paid(&self) -> u64 {
+        self.payments.iter().map(|p| p.paid).sum()
+    }
+
+    pub fn total_received(&self) -> u64 {
+        self.payments.iter().map(|p| p.reward_received).sum()
+    }
+
+    /// The payments that need follow up.
+    pub fn discrepancies(&self) -> impl Iterator<Item = &RequestPayment> {
+        self.payments.iter().filter(|p| p.is_discrepancy())
+    }
+}
+
+/// Pairs the payer's randomness requests in the slot range with the transfers into
+/// [`RANDOMNESS_SERVICE_REWARD_WALLET`] made when each request was settled or errored.
+pub async fn reconcile_payments<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    payer: Pubkey,
+    range: SlotRange,
+) -> Result<PaymentReconciliation, LiteClientError> {
+    let mut payments = Vec::new();
+
+    for signature in signatures_in_range(rpc, &payer, range).await? {
+        let transaction = match rpc.get_transaction(&signature).await? {
+            Some(transaction) if !transaction.failed => transaction,
+            _ => continue,
+        };
+
+        for request in requested_by(&transaction, &payer) {
+            let paid = transaction
+                .token_balances
+                .iter()
+                .find(|b| b.account == derive_escrow_pubkey(&request, &b.mint))
+                .map(|b| b.post)
+                .unwrap_or_default();
+
+            payments.push(resolve_payment(rpc, request, &transaction, paid).await?);
+        }
+    }
+
+    Ok(PaymentReconciliation {
+        payer,
+        range,
+        payments,
+    })
+}
+
+/// The successful signatures for the address in the slot range, oldest first.
+pub(crate) async fn signatures_in_range<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    address: &Pubkey,
+    range: SlotRange,
+) -> Result<Vec<Signature>, LiteClientError> {
+    let mut signatures = Vec::new();
+    let mut before = None;
+
+    loop {
+        let page = rpc
+            .get_signatures_for_address(address, before, SIGNATURE_PAGE_SIZE)
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(last.signature);
+        let done = page.len() < SIGNATURE_PAGE_SIZE || last.slot < range.start;
+
+        signatures.extend(
+            page.iter()
+                .filter(|s| !s.failed && range.contains(s.slot))
+                .map(|s| s.signature),
+        );
+
+        if done {
+            break;
+        }
+    }
+
+    // Oldest first
+    signatures.reverse();
+    Ok(signatures)
+}
+
+async fn resolve_payment<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    request: Pubkey,
+    request_transaction: &TransactionRecord,
+    paid: u64,
+) -> Result<RequestPayment, LiteClientError> {
+    let mut payment = RequestPayment {
+        request,
+        request_signature: request_transaction.signature,
+        request_slot: request_transaction.slot,
+        paid,
+        resolution_signature: None,
+        reward_received: 0,
+        status: PaymentStatus::Unmatched,
+    };
+
+    let signatures = rpc
+        .get_signatures_for_address(&request, None, SIGNATURE_PAGE_SIZE)
+        .await?;
+    for signature in signatures.iter().rev() {
+        if signature.failed || signature.signature == request_transaction.signature {
+            continue;
+        }
+        let Some(transaction) = rpc.get_transaction(&signature.signature).await? else {
+            continue;
+        };
+
+        let reward_received = transaction
+            .token_balance(&RANDOMNESS_SERVICE_REWARD_WALLET)
+            .map(|b| b.delta().max(0) as u64)
+            .unwrap_or_default();
+
+        if resolves(&transaction, &request) {
+            payment.resolution_signature = Some(transaction.signature);
+            payment.reward_received = reward_received;
+            payment.status = if reward_received == paid {
+                PaymentStatus::Matched
+            } else {
+                PaymentStatus::AmountMismatch
+            };
+            return Ok(payment);
+        }
+
+        let escrow_refunded = transaction.token_balances.iter().any(|b| {
+            b.account == derive_escrow_pubkey(&request, &b.mint) && b.pre > 0 && b.post == 0
+        });
+        if escrow_refunded && reward_received == 0 {
+            payment.resolution_signature = Some(transaction.signature);
+            payment.status = PaymentStatus::Refunded;
+            return Ok(payment);
+        }
+    }
+
+    let account = rpc.get_account(&request).await?;
+    if let Ok(RequestStatus::Pending(_)) = RequestStatus::from_account(account.as_ref()) {
+        payment.status = PaymentStatus::Pending;
+    }
+
+    Ok(payment)
+}
+
+/// The leading `callback_pid`, `user`, and `request` fields shared by every service event.
+#[derive(BorshDeserialize)]
+struct EventPrefix {
+    _callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+}
+
+fn events(transaction: &TransactionRecord, name: &str) -> Vec<EventPrefix> {
+    decode_events(transaction, name)
+}
+
+fn requested_by(transaction: &TransactionRecord, payer: &Pubkey) -> Vec<Pubkey> {
+    events(transaction, "SimpleRandomnessV1RequestedEvent")
+        .into_iter()
+        .filter(|event| event.user == *payer)
+        .map(|event| event.request)
+        .collect()
+}
+
+fn resolves(transaction: &TransactionRecord, request: &Pubkey) -> bool {
+    [
+        "SimpleRandomnessV1SettledEvent",
+        "SimpleRandomnessV1CallbackErrorEvent",
+    ]
+    .iter()
+    .flat_map(|name| events(transaction, name))
+    .any(|event| event.request == *request)
+}
@@ -0,0 +1,61 @@
+use crate::addresses::{NATIVE_MINT, TOKEN_PROGRAM_ID};
+use crate::*;
+
+/// The system program `Transfer` instruction.
+const SYSTEM_TRANSFER_INSTRUCTION: u32 = 2;
+/// The SPL token `Transfer` instruction.
+const TOKEN_TRANSFER_INSTRUCTION: u8 = 3;
+
+/// A fee paid to a third party, such as a team reselling randomness, in the request
+/// transaction. Set with
+/// [`RequestBuilder::with_referral_fee`](crate::client::RequestBuilder::with_referral_fee).
+///
+/// The transfer is sent after the request instruction, so a request the service rejects pays
+/// no fee. It is counted in the request's
+/// [`FundingBreakdown::referral_fee`](crate::client::FundingBreakdown::referral_fee) and
+/// [`total`](crate::client::FundingBreakdown::total), but not its service cost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReferralFee {
+    /// The wallet paid the fee. For a mint other than wrapped SOL, its associated token
+    /// account for the mint is paid and must already exist.
+    pub recipient: Pubkey,
+    /// The fee, in lamports for wrapped SOL or else in the mint's base units.
+    pub amount: u64,
+}
+
+impl ReferralFee {
+    pub fn new(recipient: Pubkey, amount: u64) -> Self {
+        Self { recipient, amount }
+    }
+
+    /// The transfer paying the fee from `payer`: a system transfer for wrapped SOL, or an SPL
+    /// token `Transfer` between the associated token accounts of the payer and the recipient
+    /// for any other mint.
+    pub fn instruction(&self, payer: &Pubkey, mint: &Pubkey) -> Instruction {
+        if *mint == NATIVE_MINT {
+            let mut data = SYSTEM_TRANSFER_INSTRUCTION.to_le_bytes().to_vec();
+            data.extend(self.amount.to_le_bytes());
+            return Instruction {
+                program_id: solana_program::system_program::ID,
+                accounts: vec![
+                    AccountMeta::new(*payer, true),
+                    AccountMeta::new(self.recipient, false),
+                ],
+                data,
+            };
+        }
+
+        let mut data = vec![TOKEN_TRANSFER_INSTRUCTION];
+        data.extend(self.amount.to_le_bytes());
+        // The associated token accounts, derived like a request's escrow
+        Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(derive_escrow_pubkey(payer, mint), false),
+                AccountMeta::new(derive_escrow_pubkey(&self.recipient, mint), false),
+                AccountMeta::new_readonly(*payer, true),
+            ],
+            data,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::addresses::TOKEN_PROGRAM_ID;
+use crate::client::*;
+use crate::*;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+/// How much to raise the compute unit price of a refreshed request over the price stored in
+/// the request it replaces. The result is capped at
+/// [`TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeBump {
+    /// Percent of the original price added to it, e.g. 50 to pay 1.5 times as much.
+    pub percent: u64,
+    /// Micro-lamports per compute unit added after the percentage.
+    pub micro_lamports: u64,
+}
+
+impl FeeBump {
+    pub fn percent(percent: u64) -> Self {
+        Self {
+            percent,
+            micro_lamports: 0,
+        }
+    }
+
+    pub fn micro_lamports(micro_lamports: u64) -> Self {
+        Self {
+            percent: 0,
+            micro_lamports,
+        }
+    }
+
+    /// The bumped compute unit price.
+    pub fn apply(&self, compute_unit_price: u64) -> u64 {
+        let bump = u128::from(compute_unit_price) * u128::from(self.percent) / 100;
+        u128::from(compute_unit_price)
+            .saturating_add(bump)
+            .saturating_add(u128::from(self.micro_lamports))
+            .min(u128::from(TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE)) as u64
+    }
+}
+
+/// What [`refresh_expired`] did with one request.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RefreshOutcome {
+    /// The request was closed and replaced by `new`, requesting the same bytes with the same
+    /// callback. `signatures` holds one transaction if the close and the new request fit in
+    /// one, or the close followed by the request otherwise.
+    Refreshed {
+        old: Pubkey,
+        new: Pubkey,
+        signatures: Vec<Signature>,
+    },
+    /// The request no longer exists: it was settled, or closed, since it was listed.
+    Settled { request: Pubkey },
+    /// The oracle has not completed the request yet. The service only closes requests it
+    /// completed with an error, so it was left alone.
+    StillPending { request: Pubkey },
+    /// The stored callback fails [`Callback::validate`], so it was not requested again. The
+    /// request was left open.
+    InvalidCallback {
+        request: Pubkey,
+        error: ProgramError,
+    },
+    /// Reading the request, or sending its refresh, failed.
+    Failed {
+        request: Pubkey,
+        error: LiteClientError,
+    },
+}
+
+impl RefreshOutcome {
+    /// The request the outcome is for.
+    pub fn request(&self) -> Pubkey {
+        match self {
+            RefreshOutcome::Refreshed { old, .. } => *old,
+            RefreshOutcome::Settled { request }
+            | RefreshOutcome::StillPending { request }
+            | RefreshOutcome::InvalidCallback { request, .. }
+            | RefreshOutcome::Failed { request, .. } => *request,
+        }
+    }
+
+    /// The request that replaced this one, if it was refreshed.
+    pub fn replacement(&self) -> Option<Pubkey> {
+        match self {
+            RefreshOutcome::Refreshed { new, .. } => Some(*new),
+            _ => None,
+        }
+    }
+}
+
+/// The `simple_randomness_v1_callback_close` instruction, closing a request the oracle
+/// completed with an error and returning the rent of the request and its escrow to its user.
+/// Anyone can send it.
+pub fn close_request_instruction(
+    request: &Pubkey,
+    account: &SimpleRandomnessV1Account,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(account.user, false),
+            AccountMeta::new(*request, false),
+            AccountMeta::new(account.escrow, false),
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, false),
+            AccountMeta::new(RANDOMNESS_SERVICE_REWARD_WALLET, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: anchor_sighash("simple_randomness_v1_callback_close").to_vec(),
+    }
+}
+
+/// Closes each request the oracle gave up on and requests the same bytes again, with the
+/// callback stored in the request account and its compute unit price raised by `fee_bump`.
+/// The signer pays for the new requests; the rent of the closed ones returns to their users.
+///
+/// The close and the new request are sent in one transaction, so a request is never closed
+/// without being replaced. When the callback makes that transaction too large, the close is
+/// sent first and the request after it; if the request then fails, the outcome is
+/// [`Failed`](RefreshOutcome::Failed) and the old request is already closed.
+///
+/// Requests that were settled in the meantime, that are still pending, or whose callback no
+/// longer passes [`Callback::validate`] are reported and left alone. Returns one outcome per
+/// request, in order.
+pub async fn refresh_expired<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    signer: &dyn Signer,
+    requests: &[Pubkey],
+    fee_bump: FeeBump,
+) -> Vec<RefreshOutcome> {
+    let mut outcomes = Vec::with_capacity(requests.len());
+    for request in requests.iter() {
+        let outcome = match refresh_one(rpc, signer, request, fee_bump).await {
+            Ok(outcome) => outcome,
+            Err(error) => RefreshOutcome::Failed {
+                request: *request,
+                error,
+            },
+        };
+        outcomes.push(outcome);
+    }
+    outcomes
+}
+
+async fn refresh_one<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    signer: &dyn Signer,
+    request: &Pubkey,
+    fee_bump: FeeBump,
+) -> Result<RefreshOutcome, LiteClientError> {
+    let Some(account) = rpc.get_account(request).await? else {
+        return Ok(RefreshOutcome::Settled { request: *request });
+    };
+    let pending = SimpleRandomnessV1Account::try_deserialize(&mut &account.data[..])?;
+    if pending.is_completed == 0 {
+        return Ok(RefreshOutcome::StillPending { request: *request });
+    }
+    if let Err(error) = pending.callback.validate() {
+        return Ok(RefreshOutcome::InvalidCallback {
+            request: *request,
+            error,
+        });
+    }
+
+    let options = TransactionOptions {
+        compute_units: Some(pending.compute_units),
+        compute_unit_price: Some(fee_bump.apply(pending.priority_fee_micro_lamports)),
+    };
+    let builder = RequestBuilder::new(signer.pubkey(), pending.num_bytes, pending.callback.clone())
+        .options(Some(options));
+    let new_request = Keypair::new();
+    let close = close_request_instruction(request, &pending);
+    let instructions = builder.instructions(&new_request.pubkey())?;
+
+    let recent_blockhash = rpc.get_latest_blockhash().await?;
+    let combined = [vec![close.clone()], instructions.clone()].concat();
+    let mut transaction = Transaction::new_with_payer(&combined, Some(&signer.pubkey()));
+    let signatures = if transaction_size(&transaction) <= PACKET_DATA_SIZE {
+        transaction.try_sign(&[signer, &new_request], recent_blockhash)?;
+        vec![rpc.send_and_confirm_transaction(&transaction).await?]
+    } else {
+        let mut close = Transaction::new_with_payer(&[close], Some(&signer.pubkey()));
+        close.try_sign(&[signer], recent_blockhash)?;
+        let close = rpc.send_and_confirm_transaction(&close).await?;
+
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&signer.pubkey()));
+        transaction.try_sign(&[signer, &new_request], recent_blockhash)?;
+        vec![close, rpc.send_and_confirm_transaction(&transaction).await?]
+    };
+
+    Ok(RefreshOutcome::Refreshed {
+        old: *request,
+        new: new_request.pubkey(),
+        signatures,
+    })
+}
+
+/// The size of the transaction once signed: the signature count, the signatures, and the
+/// message.
+fn transaction_size(transaction: &Transaction) -> usize {
+    let signatures = usize::from(transaction.message.header.num_required_signatures);
+    1 + 64 * signatures + transaction.message_data().len()
+}
@@ -0,0 +1,223 @@
+use crate::client::{
+    filters, is_program_accounts_disabled, LiteClientError, SignatureRecord, TransactionRecord,
+};
+use crate::Pubkey;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig,
+};
+use solana_client::rpc_request::{RpcError, RpcRequest};
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::str::FromStr;
+
+/// The RPC methods used by the client helpers.
+///
+/// Implemented for the nonblocking [`RpcClient`], for [`FailoverRpc`](crate::client::FailoverRpc),
+/// and for [`MockRpc`](crate::client::mock::MockRpc) so the helpers can be tested without a
+/// cluster.
+#[async_trait::async_trait]
+pub trait RandomnessRpc: Send + Sync {
+    /// Fetches a recent blockhash to sign transactions with.
+    async fn get_latest_blockhash(&self) -> Result<Hash, LiteClientError>;
+
+    /// Fetches an account, returning `None` if it does not exist.
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, LiteClientError>;
+
+    /// Fetches an account along with the slot it was read at. Implementations that cannot
+    /// report the slot of the read fetch the current slot after it, which is no earlier.
+    async fn get_account_with_slot(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<(u64, Option<Account>), LiteClientError> {
+        let account = self.get_account(pubkey).await?;
+        Ok((self.get_slot().await?, account))
+    }
+
+    /// Sends a signed transaction and waits for it to be confirmed.
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, LiteClientError>;
+
+    /// Fetches up to `limit` signatures for transactions involving the address, newest first,
+    /// starting before the given signature.
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureRecord>, LiteClientError>;
+
+    /// Fetches a confirmed transaction, returning `None` if it is not found.
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionRecord>, LiteClientError>;
+
+    /// Fetches the accounts owned by the program whose data starts with the discriminator,
+    /// along with the slot they were fetched at.
+    ///
+    /// Fails with [`LiteClientError::ProgramAccountsDisabled`] if the endpoint does not serve
+    /// `getProgramAccounts`, which is also the default for implementations that do not
+    /// provide it.
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        discriminator: [u8; 8],
+    ) -> Result<(u64, Vec<(Pubkey, Account)>), LiteClientError> {
+        let _ = (program_id, discriminator);
+        Err(LiteClientError::ProgramAccountsDisabled(
+            "not implemented by this RandomnessRpc".to_string(),
+        ))
+    }
+
+    /// Fetches the current slot, the default [`SlotClock`](crate::client::SlotClock) of the
+    /// helpers that depend on time. Fails with [`LiteClientError::Rpc`] for implementations
+    /// that do not provide it.
+    async fn get_slot(&self) -> Result<u64, LiteClientError> {
+        Err(LiteClientError::Rpc(
+            "getSlot is not implemented by this RandomnessRpc".to_string(),
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl RandomnessRpc for RpcClient {
+    async fn get_latest_blockhash(&self) -> Result<Hash, LiteClientError> {
+        Ok(RpcClient::get_latest_blockhash(self).await?)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, LiteClientError> {
+        Ok(self
+            .get_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    async fn get_account_with_slot(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<(u64, Option<Account>), LiteClientError> {
+        let response = self
+            .get_account_with_commitment(pubkey, self.commitment())
+            .await?;
+        Ok((response.context.slot, response.value))
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, LiteClientError> {
+        Ok(RpcClient::send_and_confirm_transaction(self, transaction).await?)
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureRecord>, LiteClientError> {
+        let signatures = self
+            .get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(limit),
+                    commitment: Some(self.commitment()),
+                },
+            )
+            .await?;
+
+        signatures
+            .into_iter()
+            .map(|s| {
+                Ok(SignatureRecord {
+                    signature: Signature::from_str(&s.signature)
+                        .map_err(|e| LiteClientError::Rpc(e.to_string()))?,
+                    slot: s.slot,
+                    failed: s.err.is_some(),
+                })
+            })
+            .collect()
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionRecord>, LiteClientError> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        // Sent directly so a missing transaction deserializes to None rather than an error.
+        let encoded: Option<EncodedConfirmedTransactionWithStatusMeta> = self
+            .send(
+                RpcRequest::GetTransaction,
+                serde_json::json!([signature.to_string(), config]),
+            )
+            .await?;
+
+        encoded
+            .map(|encoded| TransactionRecord::from_encoded(*signature, encoded))
+            .transpose()
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        discriminator: [u8; 8],
+    ) -> Result<(u64, Vec<(Pubkey, Account)>), LiteClientError> {
+        let slot = self.get_slot().await?;
+        let accounts = self
+            .get_program_accounts_with_config(
+                program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![filters::discriminator(discriminator)]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(program_accounts_error)?;
+
+        Ok((slot, accounts))
+    }
+
+    async fn get_slot(&self) -> Result<u64, LiteClientError> {
+        Ok(RpcClient::get_slot(self).await?)
+    }
+}
+
+/// Converts a `getProgramAccounts` error, telling a disabled method apart from a failed call.
+fn program_accounts_error(error: ClientError) -> LiteClientError {
+    let disabled = match error.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+            is_program_accounts_disabled(Some(*code), message)
+        }
+        ClientErrorKind::Reqwest(e) => {
+            matches!(
+                e.status().map(|status| status.as_u16()),
+                Some(403 | 405 | 410)
+            )
+        }
+        kind => is_program_accounts_disabled(None, &kind.to_string()),
+    };
+
+    if disabled {
+        LiteClientError::ProgramAccountsDisabled(error.to_string())
+    } else {
+        error.into()
+    }
+}
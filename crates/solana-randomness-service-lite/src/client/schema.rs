@@ -0,0 +1,254 @@
+//! Decodes the arguments of callbacks registered by name, for indexers that serve several
+//! consumers.
+
+use crate::client::serde_utils::*;
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The Borsh type of one callback argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgType {
+    U8,
+    U16,
+    U32,
+    U64,
+    Pubkey,
+    /// A Borsh `Vec<u8>`, a little endian `u32` length and the bytes.
+    Bytes,
+}
+
+/// The layout of one callback's instruction data: its discriminator followed by `arg_layout`
+/// in order, as Anchor encodes a method's arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallbackSchema {
+    pub discriminator: [u8; 8],
+    /// The label reported with the decoded arguments, e.g. the Anchor method name.
+    pub name: String,
+    pub arg_layout: Vec<ArgType>,
+}
+
+impl CallbackSchema {
+    /// The schema of an Anchor method, with the [`anchor_sighash`] of `name` as its
+    /// discriminator.
+    pub fn anchor(name: &str, arg_layout: Vec<ArgType>) -> Self {
+        Self {
+            discriminator: anchor_sighash(name),
+            name: name.to_string(),
+            arg_layout,
+        }
+    }
+
+    /// Decodes the arguments after the discriminator. Bytes after the last argument, such as
+    /// the randomness the service appends when it invokes the callback, are ignored. Returns
+    /// `None` if the data has another discriminator or ends before the last argument.
+    pub fn decode(&self, ix_data: &[u8]) -> Option<DecodedArgs> {
+        let mut reader = ix_data.strip_prefix(&self.discriminator[..])?;
+        let values = self
+            .arg_layout
+            .iter()
+            .map(|arg_type| ArgValue::read(*arg_type, &mut reader))
+            .collect::<Option<_>>()?;
+
+        Some(DecodedArgs {
+            name: self.name.clone(),
+            discriminator: self.discriminator,
+            values,
+        })
+    }
+}
+
+/// One decoded callback argument. Serializes as `{"type": "u64", "value": 42}`, with a pubkey
+/// in base58 and bytes in base64.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ArgValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    Pubkey(#[serde(serialize_with = "serialize_display")] Pubkey),
+    Bytes(#[serde(serialize_with = "serialize_base64")] Vec<u8>),
+}
+
+impl ArgValue {
+    fn read(arg_type: ArgType, reader: &mut &[u8]) -> Option<Self> {
+        let value = match arg_type {
+            ArgType::U8 => ArgValue::U8(BorshDeserialize::deserialize(reader).ok()?),
+            ArgType::U16 => ArgValue::U16(BorshDeserialize::deserialize(reader).ok()?),
+            ArgType::U32 => ArgValue::U32(BorshDeserialize::deserialize(reader).ok()?),
+            ArgType::U64 => ArgValue::U64(BorshDeserialize::deserialize(reader).ok()?),
+            ArgType::Pubkey => ArgValue::Pubkey(BorshDeserialize::deserialize(reader).ok()?),
+            ArgType::Bytes => {
+                let len: u32 = BorshDeserialize::deserialize(reader).ok()?;
+                let len = len as usize;
+                // Checked before allocating, the length prefix is untrusted
+                let bytes = reader.get(..len)?.to_vec();
+                *reader = &reader[len..];
+                ArgValue::Bytes(bytes)
+            }
+        };
+        Some(value)
+    }
+}
+
+/// A callback's arguments decoded by [`SchemaRegistry::decode_callback_args`], in the order of
+/// its schema's `arg_layout`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DecodedArgs {
+    pub name: String,
+    #[serde(serialize_with = "serialize_hex")]
+    pub discriminator: [u8; 8],
+    pub values: Vec<ArgValue>,
+}
+
+/// The error returned when registering a [`CallbackSchema`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SchemaError {
+    /// A schema's discriminator is not 16 hex characters.
+    InvalidDiscriminator { name: String, value: String },
+    /// Two schemas have the same discriminator.
+    DuplicateDiscriminator { name: String, existing: String },
+    /// The schema file could not be read.
+    Io(std::io::Error),
+    /// The schemas are not a valid JSON array.
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::InvalidDiscriminator { name, value } => {
+                write!(f, "Invalid discriminator for callback {}: {}", name, value)
+            }
+            SchemaError::DuplicateDiscriminator { name, existing } => write!(
+                f,
+                "Callback {} has the same discriminator as {}",
+                name, existing
+            ),
+            SchemaError::Io(e) => write!(f, "Failed to read callback schemas: {}", e),
+            SchemaError::Json(e) => write!(f, "Invalid callback schemas: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<std::io::Error> for SchemaError {
+    fn from(value: std::io::Error) -> Self {
+        SchemaError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SchemaError {
+    fn from(value: serde_json::Error) -> Self {
+        SchemaError::Json(value)
+    }
+}
+
+/// The JSON form of a [`CallbackSchema`]. Without a discriminator, the name is an Anchor
+/// method name.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CallbackSchemaJson {
+    name: String,
+    discriminator: Option<String>,
+    arg_layout: Vec<ArgType>,
+}
+
+fn parse_discriminator(name: &str, value: &str) -> Result<[u8; 8], SchemaError> {
+    let invalid = || SchemaError::InvalidDiscriminator {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    if hex.len() != 16 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut bytes = [0u8; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+/// The [`CallbackSchema`]s of the callbacks an indexer serves, keyed by discriminator and
+/// registered at startup.
+///
+/// Pass it to [`Indexer::with_schemas`](crate::client::Indexer::with_schemas) to decode the
+/// arguments of every reported transition, or to
+/// [`FulfillmentEvent::with_callback_args`](crate::client::FulfillmentEvent::with_callback_args)
+/// for forwarded payloads.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<[u8; 8], CallbackSchema>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the schema. Fails with [`SchemaError::DuplicateDiscriminator`] if another
+    /// schema has its discriminator, since the indexer could not tell the two callbacks apart.
+    pub fn register(&mut self, schema: CallbackSchema) -> Result<(), SchemaError> {
+        if let Some(existing) = self.schemas.get(&schema.discriminator) {
+            return Err(SchemaError::DuplicateDiscriminator {
+                name: schema.name,
+                existing: existing.name.clone(),
+            });
+        }
+        self.schemas.insert(schema.discriminator, schema);
+        Ok(())
+    }
+
+    /// Parses a JSON array of schemas, each with a `name`, an `arg_layout` of `u8`, `u16`,
+    /// `u32`, `u64`, `pubkey`, or `bytes`, and an optional hex `discriminator` that defaults to
+    /// the [`anchor_sighash`] of the name, e.g.
+    /// `[{"name": "consume_randomness", "arg_layout": ["u64", "pubkey"]}]`.
+    pub fn from_json(json: &str) -> Result<Self, SchemaError> {
+        let schemas: Vec<CallbackSchemaJson> = serde_json::from_str(json)?;
+
+        let mut registry = Self::new();
+        for schema in schemas {
+            let discriminator = match &schema.discriminator {
+                Some(value) => parse_discriminator(&schema.name, value)?,
+                None => anchor_sighash(&schema.name),
+            };
+            registry.register(CallbackSchema {
+                discriminator,
+                name: schema.name,
+                arg_layout: schema.arg_layout,
+            })?;
+        }
+        Ok(registry)
+    }
+
+    /// Reads the schemas from a JSON file, see [`from_json`](Self::from_json).
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, SchemaError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn get(&self, discriminator: &[u8; 8]) -> Option<&CallbackSchema> {
+        self.schemas.get(discriminator)
+    }
+
+    /// Decodes callback instruction data with the schema registered for its discriminator,
+    /// either the stored `ix_data` of a request or the data the service invoked the callback
+    /// with. Returns `None` if no schema matches or the data is too short for it.
+    pub fn decode_callback_args(&self, ix_data: &[u8]) -> Option<DecodedArgs> {
+        let discriminator: [u8; 8] = ix_data.get(..8)?.try_into().ok()?;
+        self.get(&discriminator)?.decode(ix_data)
+    }
+
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+}
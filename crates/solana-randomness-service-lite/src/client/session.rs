@@ -0,0 +1,108 @@
+use crate::client::*;
+use crate::*;
+use solana_sdk::hash::Hash;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long a [`PayerSession`] reuses a blockhash before fetching a new one. Blockhashes are
+/// valid for about a minute, so a reused one still leaves time to confirm.
+pub const DEFAULT_BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// The funding of one request built through a [`PayerSession`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FundingPlan {
+    /// The order the request was planned in within the session, starting at zero.
+    pub index: usize,
+    /// The request account the funding is for.
+    pub request: Pubkey,
+    pub funding: FundingBreakdown,
+}
+
+/// Shares one payer between request builders running in parallel.
+///
+/// The service program creates each request's escrow and wraps its funding itself, so the
+/// payer never holds a wSOL account and requests built through a session contain no token
+/// account create or close instructions to collide on. What the session coordinates is the
+/// rest of the payer's state: it fetches one blockhash at a time and reuses it for up to
+/// [`DEFAULT_BLOCKHASH_MAX_AGE`], so parallel builds do not each race for a fresh one, and it
+/// hands every request a [`FundingPlan`] so the total debited from the payer is known.
+///
+/// Share it with an `Arc` and build with [`RequestBuilder::build_in_session`] or
+/// [`RequestBuilder::send_in_session`].
+pub struct PayerSession {
+    payer: Pubkey,
+    max_blockhash_age: Duration,
+    blockhash: tokio::sync::Mutex<Option<(Hash, tokio::time::Instant)>>,
+    plans: Mutex<Vec<FundingPlan>>,
+}
+
+impl PayerSession {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            max_blockhash_age: DEFAULT_BLOCKHASH_MAX_AGE,
+            blockhash: tokio::sync::Mutex::new(None),
+            plans: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Overrides how long a fetched blockhash is reused.
+    pub fn max_blockhash_age(mut self, max_age: Duration) -> Self {
+        self.max_blockhash_age = max_age;
+        self
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    /// The session's blockhash, fetched if there is none or it is older than the maximum age.
+    /// Concurrent callers wait for a single fetch.
+    pub async fn blockhash<R: RandomnessRpc + ?Sized>(
+        &self,
+        rpc: &R,
+    ) -> Result<Hash, LiteClientError> {
+        let mut cached = self.blockhash.lock().await;
+        if let Some((blockhash, fetched_at)) = *cached {
+            if fetched_at.elapsed() < self.max_blockhash_age {
+                return Ok(blockhash);
+            }
+        }
+
+        let blockhash = rpc.get_latest_blockhash().await?;
+        *cached = Some((blockhash, tokio::time::Instant::now()));
+        Ok(blockhash)
+    }
+
+    /// Drops the cached blockhash, e.g. after a send fails with an expired blockhash.
+    pub async fn invalidate_blockhash(&self) {
+        *self.blockhash.lock().await = None;
+    }
+
+    /// Records the funding of a request built in the session.
+    pub(crate) fn plan(&self, request: Pubkey, funding: FundingBreakdown) -> FundingPlan {
+        let mut plans = self.plans.lock().unwrap_or_else(|e| e.into_inner());
+        let plan = FundingPlan {
+            index: plans.len(),
+            request,
+            funding,
+        };
+        plans.push(plan);
+        plan
+    }
+
+    /// Every plan handed out so far, in the order they were planned.
+    pub fn plans(&self) -> Vec<FundingPlan> {
+        self.plans.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// The lamports the planned requests debit from the payer, excluding transaction fees.
+    pub fn total_funding(&self) -> u64 {
+        self.plans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|plan| plan.funding.total())
+            .sum()
+    }
+}
@@ -0,0 +1,173 @@
+//! A compact binary format for dumping request accounts for offline analysis.
+//!
+//! A snapshot is the [`SNAPSHOT_MAGIC`] bytes and a little-endian `u16` version, followed by the
+//! Borsh encoded [`SnapshotHeader`] and `count` entries. Each entry is a little-endian `u32`
+//! length followed by the Borsh encoded request pubkey and [`SimpleRandomnessV1Account`].
+
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::io::{Read, Write};
+
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"RSNP";
+pub const SNAPSHOT_VERSION: u16 = 1;
+/// The largest entry a reader accepts. Request accounts are at most a few kilobytes.
+pub const MAX_SNAPSHOT_ENTRY_LEN: u32 = 64 * 1024;
+
+/// Describes where and when a snapshot was taken.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotHeader {
+    /// The slot the accounts were fetched at.
+    pub slot: u64,
+    /// The cluster the accounts were fetched from, e.g. `mainnet-beta`.
+    pub cluster: String,
+    /// The number of entries in the snapshot.
+    pub count: u64,
+}
+
+/// The error returned when reading or writing a snapshot.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    /// The stream does not start with [`SNAPSHOT_MAGIC`].
+    InvalidMagic,
+    /// The stream was written by a newer version of this crate.
+    UnsupportedVersion(u16),
+    /// The entry at the given index could not be read.
+    Corrupt {
+        index: u64,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "Snapshot IO error: {}", e),
+            SnapshotError::InvalidMagic => write!(f, "Not a request snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported snapshot version {}", v)
+            }
+            SnapshotError::Corrupt { index, reason } => {
+                write!(f, "Corrupt snapshot entry {}: {}", index, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(value: std::io::Error) -> Self {
+        SnapshotError::Io(value)
+    }
+}
+
+/// Writes the request accounts as a snapshot.
+pub fn export_snapshot<W: Write>(
+    accounts: &[(Pubkey, SimpleRandomnessV1Account)],
+    slot: u64,
+    cluster: &str,
+    mut w: W,
+) -> Result<(), SnapshotError> {
+    let header = SnapshotHeader {
+        slot,
+        cluster: cluster.to_string(),
+        count: accounts.len() as u64,
+    };
+
+    w.write_all(&SNAPSHOT_MAGIC)?;
+    w.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+    header.serialize(&mut w)?;
+
+    let mut entry = Vec::new();
+    for (pubkey, request) in accounts.iter() {
+        entry.clear();
+        pubkey.serialize(&mut entry)?;
+        request.serialize(&mut entry)?;
+        w.write_all(&(entry.len() as u32).to_le_bytes())?;
+        w.write_all(&entry)?;
+    }
+
+    w.flush()?;
+    Ok(())
+}
+
+/// Reads a snapshot header and returns an iterator over its entries.
+///
+/// The iterator stops after the first error, so every entry yielded before it is intact.
+pub fn import_snapshot<R: Read>(mut r: R) -> Result<SnapshotReader<R>, SnapshotError> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+
+    let mut version = [0u8; 2];
+    r.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let header = SnapshotHeader::deserialize_reader(&mut r)?;
+
+    Ok(SnapshotReader {
+        reader: r,
+        header,
+        index: 0,
+        failed: false,
+    })
+}
+
+/// An iterator over the entries of a snapshot. See [`import_snapshot`].
+pub struct SnapshotReader<R: Read> {
+    reader: R,
+    header: SnapshotHeader,
+    index: u64,
+    failed: bool,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    pub fn header(&self) -> &SnapshotHeader {
+        &self.header
+    }
+
+    fn read_entry(&mut self) -> Result<(Pubkey, SimpleRandomnessV1Account), String> {
+        let mut len = [0u8; 4];
+        self.reader
+            .read_exact(&mut len)
+            .map_err(|e| e.to_string())?;
+        let len = u32::from_le_bytes(len);
+        if len > MAX_SNAPSHOT_ENTRY_LEN {
+            return Err(format!("entry length {} exceeds the maximum", len));
+        }
+
+        let mut entry = vec![0u8; len as usize];
+        self.reader
+            .read_exact(&mut entry)
+            .map_err(|e| e.to_string())?;
+
+        <(Pubkey, SimpleRandomnessV1Account)>::try_from_slice(&entry).map_err(|e| e.to_string())
+    }
+}
+
+impl<R: Read> Iterator for SnapshotReader<R> {
+    type Item = Result<(Pubkey, SimpleRandomnessV1Account), SnapshotError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.index >= self.header.count {
+            return None;
+        }
+
+        let index = self.index;
+        self.index += 1;
+        match self.read_entry() {
+            Ok(entry) => Some(Ok(entry)),
+            Err(reason) => {
+                self.failed = true;
+                Some(Err(SnapshotError::Corrupt { index, reason }))
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::client::events::decode_events;
+use crate::client::*;
+use crate::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A percentile summary of sampled values.
+///
+/// Percentiles use the nearest rank method: the `p`th percentile of `n` sorted values is the
+/// value at rank `ceil(p / 100 * n)`, so every percentile is a value that was observed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+impl Distribution {
+    /// Summarizes the values, or returns `None` if there are none.
+    pub fn from_values(mut values: Vec<u64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+
+        let sum: u128 = values.iter().map(|v| u128::from(*v)).sum();
+        Some(Self {
+            count: values.len(),
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: sum as f64 / values.len() as f64,
+            p50: percentile(&values, 50),
+            p90: percentile(&values, 90),
+            p99: percentile(&values, 99),
+        })
+    }
+}
+
+/// The nearest rank percentile of sorted, non-empty values.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// What recent requests to the service asked for and paid, and how long they waited. Built by
+/// [`service_stats`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ServiceStats {
+    /// The slots the requests were sampled from.
+    pub range: SlotRange,
+    pub requests: usize,
+    /// The sampled requests that were settled or errored within the range.
+    pub fulfilled: usize,
+    pub num_bytes: Option<Distribution>,
+    /// The compute unit price, in micro-lamports, the oracle was asked to settle with.
+    pub compute_unit_price: Option<Distribution>,
+    /// The lamports wrapped into each request's escrow.
+    pub escrow_funding: Option<Distribution>,
+    /// The slots from each fulfilled request to its settle or callback error transaction.
+    pub fulfillment_latency_slots: Option<Distribution>,
+}
+
+/// The fields `SimpleRandomnessV1SettledEvent` and `SimpleRandomnessV1CallbackErrorEvent`
+/// start with.
+#[derive(BorshDeserialize)]
+struct FulfilledEvent {
+    _callback_pid: Pubkey,
+    _user: Pubkey,
+    request: Pubkey,
+    _request_slot: u64,
+    settled_slot: u64,
+}
+
+impl ServiceStats {
+    /// Computes the statistics from the service program's transactions, in any order. Only
+    /// requests made within `range` are counted, and only fulfillments of those requests.
+    pub fn from_transactions(transactions: &[TransactionRecord], range: SlotRange) -> Self {
+        let mut num_bytes = Vec::new();
+        let mut compute_unit_price = Vec::new();
+        let mut escrow_funding = Vec::new();
+        let mut request_slots = HashMap::new();
+
+        for transaction in transactions.iter().filter(|t| !t.failed) {
+            for event in decode_program_events::<SimpleRandomnessV1RequestedEvent>(
+                transaction,
+                SimpleRandomnessV1RequestedEvent::DISCRIMINATOR,
+            ) {
+                if !range.contains(event.request_slot) {
+                    continue;
+                }
+                num_bytes.push(u64::from(event.num_bytes));
+                compute_unit_price.push(event.priority_fee_micro_lamports);
+                if let Some(balance) = transaction
+                    .token_balances
+                    .iter()
+                    .find(|b| b.account == derive_escrow_pubkey(&event.request, &b.mint))
+                {
+                    escrow_funding.push(balance.post);
+                }
+                request_slots.insert(event.request, event.request_slot);
+            }
+        }
+
+        let mut latencies = HashMap::new();
+        for transaction in transactions.iter().filter(|t| !t.failed) {
+            let fulfilled = [
+                "SimpleRandomnessV1SettledEvent",
+                "SimpleRandomnessV1CallbackErrorEvent",
+            ]
+            .iter()
+            .flat_map(|name| decode_events::<FulfilledEvent>(transaction, name));
+            for event in fulfilled {
+                if !range.contains(event.settled_slot) {
+                    continue;
+                }
+                if let Some(request_slot) = request_slots.get(&event.request) {
+                    latencies.insert(
+                        event.request,
+                        event.settled_slot.saturating_sub(*request_slot),
+                    );
+                }
+            }
+        }
+
+        Self {
+            range,
+            requests: request_slots.len(),
+            fulfilled: latencies.len(),
+            num_bytes: Distribution::from_values(num_bytes),
+            compute_unit_price: Distribution::from_values(compute_unit_price),
+            escrow_funding: Distribution::from_values(escrow_funding),
+            fulfillment_latency_slots: Distribution::from_values(latencies.into_values().collect()),
+        }
+    }
+}
+
+/// Samples the requests made to the randomness service in the last `sample_slots` slots.
+pub async fn service_stats<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    sample_slots: u64,
+) -> Result<ServiceStats, LiteClientError> {
+    service_stats_for(rpc, &ServiceAddresses::default(), sample_slots).await
+}
+
+/// Samples the requests made to the service at the given addresses in the last
+/// `sample_slots` slots, counted back from the program's newest transaction.
+///
+/// Every successful program transaction in the range is fetched, one `getTransaction` call
+/// each, so a long range on a busy cluster takes a while and counts against RPC rate limits.
+pub async fn service_stats_for<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    addresses: &ServiceAddresses,
+    sample_slots: u64,
+) -> Result<ServiceStats, LiteClientError> {
+    let newest = rpc
+        .get_signatures_for_address(&addresses.program_id, None, 1)
+        .await?
+        .first()
+        .map(|s| s.slot)
+        .unwrap_or_default();
+    sample_range(rpc, addresses, newest, sample_slots).await
+}
+
+/// Like [`service_stats_for`], but counts the `sample_slots` back from the slot `clock` reads
+/// instead of the program's newest transaction, so a quiet service reports an empty range.
+pub async fn service_stats_with_clock<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    addresses: &ServiceAddresses,
+    sample_slots: u64,
+    clock: &dyn SlotClock,
+) -> Result<ServiceStats, LiteClientError> {
+    let current_slot = clock.current_slot().await?;
+    sample_range(rpc, addresses, current_slot, sample_slots).await
+}
+
+async fn sample_range<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    addresses: &ServiceAddresses,
+    end: u64,
+    sample_slots: u64,
+) -> Result<ServiceStats, LiteClientError> {
+    let range = SlotRange {
+        start: end.saturating_sub(sample_slots),
+        end,
+    };
+
+    let mut transactions = Vec::new();
+    for signature in signatures_in_range(rpc, &addresses.program_id, range).await? {
+        if let Some(transaction) = rpc.get_transaction(&signature).await? {
+            transactions.push(transaction);
+        }
+    }
+
+    Ok(ServiceStats::from_transactions(&transactions, range))
+}
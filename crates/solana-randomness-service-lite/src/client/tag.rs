@@ -0,0 +1,104 @@
+use crate::client::*;
+use crate::*;
+
+/// The SPL Memo program, which carries request tags in the request transaction.
+pub const MEMO_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// The prefix of every request tag memo.
+const TAG_PREFIX: &str = "rsl1:";
+/// The sub-field carrying the [`VersionCode`] of the crate that built the request.
+const VERSION_FIELD: &str = "v=";
+
+/// A label attached to a request by [`RequestBuilder::with_tag`], sent as a memo in the request
+/// transaction so indexers can group requests without an account of their own.
+///
+/// The memo is `rsl1:<label>` followed by `;`-separated sub-fields. With the `version-tag`
+/// feature the tag carries a `v=` sub-field with the 2-byte [`VersionCode`] of this crate in
+/// hex, so a request can be traced back to the release that built it. Other sub-fields are
+/// reserved and skipped when decoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestTag {
+    pub label: String,
+    /// The version of the crate that built the request, if it was recorded.
+    pub version: Option<VersionCode>,
+}
+
+impl RequestTag {
+    /// The longest label accepted, keeping the memo well under the transaction size limit.
+    pub const MAX_LABEL_LEN: usize = 64;
+
+    /// A tag with the given label, carrying [`VersionCode::CURRENT`] when the `version-tag`
+    /// feature is enabled.
+    ///
+    /// Labels are printable ASCII without `;`, `"`, or `\`, at most
+    /// [`MAX_LABEL_LEN`](Self::MAX_LABEL_LEN) bytes.
+    pub fn new(label: &str) -> Result<Self, LiteClientError> {
+        if label.len() > Self::MAX_LABEL_LEN {
+            return Err(LiteClientError::InvalidTag(format!(
+                "label is {} bytes, over the {} byte limit",
+                label.len(),
+                Self::MAX_LABEL_LEN
+            )));
+        }
+        if let Some(c) = label.chars().find(|c| !is_label_char(*c)) {
+            return Err(LiteClientError::InvalidTag(format!(
+                "label contains {:?}",
+                c
+            )));
+        }
+
+        Ok(Self {
+            label: label.to_string(),
+            version: cfg!(feature = "version-tag").then_some(VersionCode::CURRENT),
+        })
+    }
+
+    /// The memo text of the tag.
+    pub fn encode(&self) -> String {
+        match self.version {
+            Some(version) => format!(
+                "{}{};{}{:04x}",
+                TAG_PREFIX, self.label, VERSION_FIELD, version.0
+            ),
+            None => format!("{}{}", TAG_PREFIX, self.label),
+        }
+    }
+
+    /// Decodes the memo text of a tag. Returns `None` for memos that are not request tags.
+    pub fn decode(memo: &str) -> Option<Self> {
+        let mut fields = memo.strip_prefix(TAG_PREFIX)?.split(';');
+        let label = fields.next()?.to_string();
+        let version = fields
+            .filter_map(|field| field.strip_prefix(VERSION_FIELD))
+            .find_map(|code| u16::from_str_radix(code, 16).ok())
+            .map(VersionCode);
+        Some(Self { label, version })
+    }
+
+    /// The memo instruction that attaches the tag to a transaction.
+    pub fn instruction(&self) -> Instruction {
+        Instruction {
+            program_id: MEMO_PROGRAM_ID,
+            accounts: vec![],
+            data: self.encode().into_bytes(),
+        }
+    }
+
+    /// Reads the tag from the memo program's logs in a request transaction.
+    pub fn from_transaction(transaction: &TransactionRecord) -> Option<Self> {
+        transaction.log_messages.iter().find_map(|log| {
+            // The memo program logs `Memo (len <n>): "<memo>"`
+            let memo = log
+                .strip_prefix("Program log: Memo (len ")?
+                .split_once("): \"")?
+                .1
+                .strip_suffix('"')?;
+            Self::decode(memo)
+        })
+    }
+}
+
+fn is_label_char(c: char) -> bool {
+    c == ' ' || (c.is_ascii_graphic() && !matches!(c, ';' | '"' | '\\'))
+}
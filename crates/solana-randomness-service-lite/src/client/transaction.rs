@@ -0,0 +1,45 @@
+use crate::client::preflight::fetch_state_data;
+use crate::client::*;
+use crate::*;
+use solana_sdk::signature::Keypair;
+use solana_sdk::transaction::Transaction;
+
+/// The fee of each signature on a transaction, in lamports.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Builds a complete, unsigned `simple_randomness_v1` request transaction for the service on
+/// the cluster `addresses` points to, e.g. [`ServiceAddresses::devnet`], and returns it with
+/// the request keypair, which must sign it along with the payer.
+///
+/// The service creates the request account and its escrow and wraps the fee itself, so the
+/// transaction holds the request instruction alone. The price is read from the service's
+/// state account and the blockhash is fetched from `rpc`. Fails with
+/// [`LiteClientError::InsufficientBalance`] if the payer cannot afford the request's
+/// [`FundingBreakdown::total`] plus the fee of the transaction's two signatures.
+///
+/// Use [`RequestBuilder`] directly to tag the request, audit it, or fund it via a delegate.
+pub async fn build_request_transaction<R: RandomnessRpc + ?Sized>(
+    rpc: &R,
+    addresses: &ServiceAddresses,
+    payer: &Pubkey,
+    num_bytes: u8,
+    callback: Callback,
+    options: Option<TransactionOptions>,
+) -> Result<(Transaction, Keypair), LiteClientError> {
+    let state = fetch_state_data(rpc, &addresses.state, &addresses.program_id).await?;
+    let builder = RequestBuilder::new(*payer, num_bytes, callback)
+        .program_id(addresses.program_id)
+        .state(addresses.state)
+        .mint(addresses.reward_mint)
+        .options(options)
+        .cost_per_byte(ServiceState::read_cost_per_byte(&state)?);
+
+    let required = builder.funding()?.total() + 2 * LAMPORTS_PER_SIGNATURE;
+    let balance = rpc.get_account(payer).await?.map_or(0, |a| a.lamports);
+    if balance < required {
+        return Err(LiteClientError::InsufficientBalance { balance, required });
+    }
+
+    let built = builder.build(rpc.get_latest_blockhash().await?)?;
+    Ok((built.transaction, built.request))
+}
@@ -0,0 +1,130 @@
+use crate::client::*;
+use crate::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// The compute units the callback of a settle transaction consumed, from the
+/// [`CallbackComputeUsage`] event the callback logged with a `ComputeMeter`, or else from
+/// the runtime's `Program <callback> consumed N of M compute units` line, which counts the
+/// whole callback invocation.
+pub fn callback_compute_units(transaction: &TransactionRecord, callback: &Callback) -> Option<u64> {
+    let logged = decode_program_events::<CallbackComputeUsage>(
+        transaction,
+        CallbackComputeUsage::DISCRIMINATOR,
+    )
+    .into_iter()
+    .find(|usage| Some(usage.discriminator) == callback.discriminator());
+    if let Some(usage) = logged {
+        return Some(usage.units_consumed);
+    }
+
+    let prefix = format!("Program {} consumed ", callback.program_id);
+    transaction.log_messages.iter().find_map(|log| {
+        log.strip_prefix(&prefix)?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    })
+}
+
+/// The compute units observed for one callback instruction by a [`TuningStore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallbackTuningStats {
+    /// Every sample recorded.
+    pub samples: u64,
+    /// The mean of every sample recorded.
+    pub mean: u64,
+    /// The most units among the last [`TuningStore::WINDOW`] samples.
+    pub recent_max: u64,
+}
+
+#[derive(Default)]
+struct Samples {
+    count: u64,
+    total: u128,
+    recent: VecDeque<u64>,
+}
+
+/// Accumulates the compute units callbacks consumed, per callback instruction discriminator,
+/// and advises the compute units of later requests from them.
+///
+/// The advice is the most units among the last [`WINDOW`](Self::WINDOW) samples plus the
+/// service's [`ADVISED_BASE_COMPUTE_UNITS`], with [`ADVISED_HEADROOM_PERCENT`] headroom, so it
+/// follows a callback whose cost changes within a window. Until
+/// [`MIN_SAMPLES`](Self::MIN_SAMPLES) are recorded, the static estimate is used. Pass the
+/// store to [`TransactionOptions::advised_for_callback_with`]. The store is kept in memory.
+///
+/// [`ADVISED_BASE_COMPUTE_UNITS`]: TransactionOptions::ADVISED_BASE_COMPUTE_UNITS
+/// [`ADVISED_HEADROOM_PERCENT`]: TransactionOptions::ADVISED_HEADROOM_PERCENT
+#[derive(Default)]
+pub struct TuningStore {
+    samples: Mutex<HashMap<[u8; 8], Samples>>,
+}
+
+impl TuningStore {
+    /// The number of recent samples the advice is taken from.
+    pub const WINDOW: usize = 16;
+    /// The number of samples needed before the store advises a callback.
+    pub const MIN_SAMPLES: u64 = 3;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the units consumed by a callback with the given instruction discriminator.
+    pub fn record(&self, discriminator: [u8; 8], units_consumed: u64) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let samples = samples.entry(discriminator).or_default();
+        samples.count += 1;
+        samples.total += u128::from(units_consumed);
+        if samples.recent.len() == Self::WINDOW {
+            samples.recent.pop_front();
+        }
+        samples.recent.push_back(units_consumed);
+    }
+
+    /// Records the units consumed by the callback in its settle transaction, read with
+    /// [`callback_compute_units`], and returns them. Does nothing if the transaction does not
+    /// report them or the callback has no discriminator.
+    pub fn observe(&self, transaction: &TransactionRecord, callback: &Callback) -> Option<u64> {
+        let discriminator = callback.discriminator()?;
+        let units = callback_compute_units(transaction, callback)?;
+        self.record(discriminator, units);
+        Some(units)
+    }
+
+    /// The statistics recorded for the discriminator.
+    pub fn stats(&self, discriminator: &[u8; 8]) -> Option<CallbackTuningStats> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let samples = samples.get(discriminator)?;
+        Some(CallbackTuningStats {
+            samples: samples.count,
+            mean: (samples.total / u128::from(samples.count.max(1))) as u64,
+            recent_max: samples.recent.iter().copied().max().unwrap_or_default(),
+        })
+    }
+
+    /// The compute units advised for the callback instruction, before clamping, or `None`
+    /// until [`MIN_SAMPLES`](Self::MIN_SAMPLES) are recorded.
+    pub fn advised_units(&self, discriminator: &[u8; 8]) -> Option<u64> {
+        let stats = self.stats(discriminator)?;
+        if stats.samples < Self::MIN_SAMPLES {
+            return None;
+        }
+        let estimate = u64::from(TransactionOptions::ADVISED_BASE_COMPUTE_UNITS)
+            .saturating_add(stats.recent_max);
+        Some(
+            estimate.saturating_mul(u64::from(
+                100 + TransactionOptions::ADVISED_HEADROOM_PERCENT,
+            )) / 100,
+        )
+    }
+}
+
+impl ComputeUnitsSource for TuningStore {
+    fn compute_units_for(&self, callback: &Callback) -> Option<u32> {
+        let units = self.advised_units(&callback.discriminator()?)?;
+        Some(u32::try_from(units).unwrap_or(u32::MAX))
+    }
+}
@@ -0,0 +1,24 @@
+use solana_program::clock::Clock;
+
+/// The current slot, as the on-chain helpers that depend on time read it.
+///
+/// Helpers take the time from their caller instead of calling `Clock::get()` themselves, so a
+/// unit test can pass a slot without a runtime. A program passes the `Clock` it read once per
+/// instruction, and tests can pass a bare slot, a `u64`, or the
+/// [`OracleHarness`](crate::harness::OracleHarness), whose slot programs see through
+/// `Clock::get()` during its invocations.
+pub trait SlotSource {
+    fn slot(&self) -> u64;
+}
+
+impl SlotSource for Clock {
+    fn slot(&self) -> u64 {
+        self.slot
+    }
+}
+
+impl SlotSource for u64 {
+    fn slot(&self) -> u64 {
+        *self
+    }
+}
@@ -0,0 +1,122 @@
+use crate::*;
+use borsh::BorshDeserialize;
+
+/// Caps on the variable length fields of a request account, for decoding requests made by
+/// other users on chain with [`SimpleRandomnessV1Account::try_deserialize_bounded`].
+///
+/// A program's heap is 32 KiB and never freed, so a program that decodes many requests, or
+/// one request with a large callback, can run out of it. Every length is checked against its
+/// limit before anything is allocated for it, so a decode within the limits allocates at most
+/// [`max_heap_bytes`](Self::max_heap_bytes).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// The most callback accounts decoded.
+    pub max_accounts: usize,
+    /// The longest callback instruction data decoded.
+    pub max_ix_data_len: usize,
+    /// The longest error message decoded.
+    pub max_error_message_len: usize,
+}
+
+impl DecodeLimits {
+    /// The largest fields a request can hold and still settle, [`MAX_CALLBACK_ACCOUNTS`] and
+    /// [`MAX_CALLBACK_IX_DATA_LEN`], so no valid request is rejected. A decode can allocate
+    /// about 10.7 KiB.
+    pub const SERVICE: DecodeLimits = DecodeLimits {
+        max_accounts: MAX_CALLBACK_ACCOUNTS,
+        max_ix_data_len: MAX_CALLBACK_IX_DATA_LEN,
+        max_error_message_len: SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN,
+    };
+
+    /// The most heap a decode within these limits allocates: the buffers of the callback
+    /// accounts, the callback instruction data, and the error message.
+    pub const fn max_heap_bytes(&self) -> usize {
+        self.max_accounts * std::mem::size_of::<AccountMetaBorsh>()
+            + self.max_ix_data_len
+            + self.max_error_message_len
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::SERVICE
+    }
+}
+
+impl SimpleRandomnessV1Account {
+    /// Like [`try_deserialize`](Self::try_deserialize), but fails with
+    /// [`LiteError::DecodeLimitExceeded`] instead of allocating for a callback or error
+    /// message larger than `limits`.
+    pub fn try_deserialize_bounded(
+        buf: &mut &[u8],
+        limits: DecodeLimits,
+    ) -> Result<Self, ProgramError> {
+        if buf.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data: &[u8] = &buf[8..];
+        let data = &mut data;
+
+        let is_completed = read(data)?;
+        let num_bytes = read(data)?;
+        let user = read(data)?;
+        let escrow = read(data)?;
+        let request_slot = read(data)?;
+
+        let program_id = read(data)?;
+        let len = read_len(data, limits.max_accounts)?;
+        let mut accounts = Vec::with_capacity(len);
+        for _ in 0..len {
+            accounts.push(read::<AccountMetaBorsh>(data)?);
+        }
+        let len = read_len(data, limits.max_ix_data_len)?;
+        let ix_data = take(data, len)?.to_vec();
+
+        let compute_units = read(data)?;
+        let priority_fee_micro_lamports = read(data)?;
+        let len = read_len(data, limits.max_error_message_len)?;
+        let error_message = std::str::from_utf8(take(data, len)?)
+            .map_err(|_| ProgramError::InvalidAccountData)?
+            .to_string();
+
+        Ok(Self {
+            is_completed,
+            num_bytes,
+            user,
+            escrow,
+            request_slot,
+            callback: Callback {
+                program_id,
+                accounts,
+                ix_data,
+            },
+            compute_units,
+            priority_fee_micro_lamports,
+            error_message,
+        })
+    }
+}
+
+fn read<T: BorshDeserialize>(data: &mut &[u8]) -> Result<T, ProgramError> {
+    T::deserialize(data).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Reads a Borsh length prefix and checks it against `limit`. Does not log, as formatting the
+/// message would allocate.
+fn read_len(data: &mut &[u8], limit: usize) -> Result<usize, ProgramError> {
+    let len = read::<u32>(data)? as usize;
+    if len > limit {
+        return Err(LiteError::DecodeLimitExceeded.into());
+    }
+    Ok(len)
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], ProgramError> {
+    let bytes: &'a [u8] = data;
+    if bytes.len() < len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *data = rest;
+    Ok(taken)
+}
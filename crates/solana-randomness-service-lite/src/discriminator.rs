@@ -0,0 +1,26 @@
+use solana_program::hash::hash;
+
+/// The 8 byte discriminator Anchor prefixes to the instruction data of a `#[program]` method,
+/// the first 8 bytes of `sha256("global:<name>")`.
+pub fn anchor_sighash(name: &str) -> [u8; 8] {
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    sighash
+}
+
+/// The 8 byte discriminator Anchor prefixes to an `#[event]` in its `Program data:` log line,
+/// the first 8 bytes of `sha256("event:<name>")`.
+pub fn anchor_event_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("event:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Returns the candidate instruction name whose [`anchor_sighash`] matches the discriminator,
+/// for labelling callbacks configured with hardcoded bytes.
+pub fn identify_discriminator(bytes: &[u8; 8], candidate_names: &[&str]) -> Option<String> {
+    candidate_names
+        .iter()
+        .find(|name| anchor_sighash(name) == *bytes)
+        .map(|name| name.to_string())
+}
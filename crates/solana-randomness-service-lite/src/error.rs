@@ -0,0 +1,295 @@
+use crate::{ProgramError, CRATE_VERSION};
+
+/// The errors returned by the checks in this crate. Converted to `ProgramError::Custom` with
+/// codes starting at [`LiteError::CODE_OFFSET`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LiteError {
+    /// The request account already holds data or lamports. It must be a new account.
+    RequestAccountNotEmpty,
+    /// The request account is owned by the consumer program or the randomness service, so it is
+    /// an existing PDA or request rather than a new account.
+    RequestAccountOwnedByProgram,
+    /// The callback instruction data is shorter than an 8 byte discriminator.
+    CallbackDiscriminatorMissing,
+    /// The state account is listed in the callback without the signer flag, so the service
+    /// rejects the callback, or did not sign a callback checked with
+    /// [`verify_callback`](crate::verify_callback).
+    CallbackStateNotSigner,
+    /// A callback account other than the state account is marked as a signer. The oracle can
+    /// never provide that signature.
+    CallbackSignerNotAllowed,
+    /// A key passed to `Callback::allow_pda_signer` is on the ed25519 curve, so it is a wallet
+    /// rather than a PDA.
+    CallbackSignerOnCurve,
+    /// The callback lists more than [`Callback::MAX_ACCOUNTS`](crate::Callback::MAX_ACCOUNTS)
+    /// accounts.
+    CallbackTooManyAccounts,
+    /// The callback instruction data is longer than
+    /// [`Callback::MAX_IX_DATA_LEN`](crate::Callback::MAX_IX_DATA_LEN).
+    CallbackDataTooLarge,
+    /// A [`Page`](crate::Page) already holds its maximum number of items.
+    PageFull,
+    /// Too few levels of CPI are left below the current instruction, see
+    /// [`assert_cpi_depth_available`](crate::assert_cpi_depth_available).
+    CpiDepthExceeded,
+    /// The number of randomness bytes is outside
+    /// [`MIN_RANDOMNESS_BYTES`](crate::MIN_RANDOMNESS_BYTES) to
+    /// [`MAX_RANDOMNESS_BYTES`](crate::MAX_RANDOMNESS_BYTES).
+    InvalidNumBytes,
+    /// The compute units or compute unit price of the
+    /// [`TransactionOptions`](crate::TransactionOptions) are out of range, see
+    /// [`TransactionOptions::validate`](crate::TransactionOptions::validate).
+    InvalidTransactionOptions,
+    /// The mint passed with the request is not the mint recorded in the service's state
+    /// account, see [`check_escrow_mint`](crate::check_escrow_mint).
+    MintMismatch,
+    /// The oracle's settle transaction for the callback would exceed the transaction size
+    /// limit, see [`Callback::check_size`](crate::Callback::check_size).
+    CallbackTooLargeForSettlement,
+    /// The randomness a callback received is not the number of bytes it expected, see
+    /// [`extract_randomness`](crate::extract_randomness).
+    RandomnessLengthMismatch,
+    /// The lower bound of a range is above its upper bound, see
+    /// [`random_in_range`](crate::random_in_range).
+    InvalidRange,
+    /// A request account has a longer field than the [`DecodeLimits`](crate::DecodeLimits)
+    /// it was decoded with.
+    DecodeLimitExceeded,
+    /// A weight table is empty, all zero, or sums past `u64::MAX`, see
+    /// [`weighted_choice`](crate::weighted_choice).
+    InvalidWeights,
+    /// A request's callback invokes another program than the one checking it, see
+    /// [`verify_callback`](crate::verify_callback).
+    CallbackProgramMismatch,
+    /// A callback's indexed tag is not the one expected, see
+    /// [`verify_tag_matches`](crate::verify_tag_matches).
+    TagMismatch,
+    /// A request's fee does not fit in a u64, see
+    /// [`estimate_request_cost`](crate::estimate_request_cost).
+    FeeOverflow,
+}
+
+impl LiteError {
+    pub const CODE_OFFSET: u32 = 7000;
+
+    const ALL: [LiteError; 21] = [
+        LiteError::RequestAccountNotEmpty,
+        LiteError::RequestAccountOwnedByProgram,
+        LiteError::CallbackDiscriminatorMissing,
+        LiteError::CallbackStateNotSigner,
+        LiteError::CallbackSignerNotAllowed,
+        LiteError::CallbackSignerOnCurve,
+        LiteError::CallbackTooManyAccounts,
+        LiteError::CallbackDataTooLarge,
+        LiteError::PageFull,
+        LiteError::CpiDepthExceeded,
+        LiteError::InvalidNumBytes,
+        LiteError::InvalidTransactionOptions,
+        LiteError::MintMismatch,
+        LiteError::CallbackTooLargeForSettlement,
+        LiteError::RandomnessLengthMismatch,
+        LiteError::InvalidRange,
+        LiteError::DecodeLimitExceeded,
+        LiteError::InvalidWeights,
+        LiteError::CallbackProgramMismatch,
+        LiteError::TagMismatch,
+        LiteError::FeeOverflow,
+    ];
+
+    /// The error with the given custom program error code.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    /// The custom program error code.
+    pub fn code(&self) -> u32 {
+        Self::CODE_OFFSET
+            + match self {
+                LiteError::RequestAccountNotEmpty => 0,
+                LiteError::RequestAccountOwnedByProgram => 1,
+                LiteError::CallbackDiscriminatorMissing => 2,
+                LiteError::CallbackStateNotSigner => 3,
+                LiteError::CallbackSignerNotAllowed => 4,
+                LiteError::CallbackSignerOnCurve => 5,
+                LiteError::CallbackTooManyAccounts => 6,
+                LiteError::CallbackDataTooLarge => 7,
+                LiteError::PageFull => 8,
+                LiteError::CpiDepthExceeded => 9,
+                LiteError::InvalidNumBytes => 10,
+                LiteError::InvalidTransactionOptions => 11,
+                LiteError::MintMismatch => 12,
+                LiteError::CallbackTooLargeForSettlement => 13,
+                LiteError::RandomnessLengthMismatch => 14,
+                LiteError::InvalidRange => 15,
+                LiteError::DecodeLimitExceeded => 16,
+                LiteError::InvalidWeights => 17,
+                LiteError::CallbackProgramMismatch => 18,
+                LiteError::TagMismatch => 19,
+                LiteError::FeeOverflow => 20,
+            }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            LiteError::RequestAccountNotEmpty => "The request account must be empty",
+            LiteError::RequestAccountOwnedByProgram => {
+                "The request account must not be owned by the consumer or randomness service program"
+            }
+            LiteError::CallbackDiscriminatorMissing => {
+                "The callback instruction data must start with an 8 byte discriminator"
+            }
+            LiteError::CallbackStateNotSigner => {
+                "The randomness service state must be a signer of the callback"
+            }
+            LiteError::CallbackSignerNotAllowed => {
+                "Only the randomness service state can sign the callback"
+            }
+            LiteError::CallbackSignerOnCurve => "Only a PDA can be allowed as a callback signer",
+            LiteError::CallbackTooManyAccounts => "The callback lists too many accounts",
+            LiteError::CallbackDataTooLarge => "The callback instruction data is too large",
+            LiteError::PageFull => "The page is full",
+            LiteError::CpiDepthExceeded => {
+                "Not enough cross-program invocation depth is left for the call"
+            }
+            LiteError::InvalidNumBytes => "The number of randomness bytes must be from 1 to 32",
+            LiteError::InvalidTransactionOptions => {
+                "The transaction options are outside the accepted range"
+            }
+            LiteError::MintMismatch => "The mint is not the mint of the randomness service state",
+            LiteError::CallbackTooLargeForSettlement => {
+                "The callback makes the settle transaction too large to send"
+            }
+            LiteError::RandomnessLengthMismatch => {
+                "The callback did not receive the expected number of randomness bytes"
+            }
+            LiteError::InvalidRange => "The lower bound of the range is above its upper bound",
+            LiteError::DecodeLimitExceeded => {
+                "The request account has a field longer than the decode limits"
+            }
+            LiteError::InvalidWeights => "The weights are empty, all zero, or overflow a u64",
+            LiteError::CallbackProgramMismatch => {
+                "The request's callback does not invoke this program"
+            }
+            LiteError::TagMismatch => "The callback's tag is not the expected tag",
+            LiteError::FeeOverflow => "The request's fee does not fit in a u64",
+        }
+    }
+}
+
+impl std::fmt::Display for LiteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+impl std::error::Error for LiteError {}
+
+impl From<LiteError> for ProgramError {
+    fn from(value: LiteError) -> Self {
+        ProgramError::Custom(value.code())
+    }
+}
+
+/// The errors returned by the randomness service program, with the same custom error codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServiceError {
+    InvalidNumberOfBytes,
+    InvalidCallback,
+    MissingCallbackAccount,
+    InvalidEscrow,
+    InsufficientFunds,
+    CpiUnauthorized,
+    RequestStillActive,
+    RequestAlreadyCompleted,
+    ErrorMessageOverflow,
+    IncorrectCallbackProgramId,
+    ProgramWalletInsufficientFunds,
+}
+
+impl ServiceError {
+    /// The first custom error code used by Anchor programs.
+    pub const CODE_OFFSET: u32 = 6000;
+
+    const ALL: [ServiceError; 11] = [
+        ServiceError::InvalidNumberOfBytes,
+        ServiceError::InvalidCallback,
+        ServiceError::MissingCallbackAccount,
+        ServiceError::InvalidEscrow,
+        ServiceError::InsufficientFunds,
+        ServiceError::CpiUnauthorized,
+        ServiceError::RequestStillActive,
+        ServiceError::RequestAlreadyCompleted,
+        ServiceError::ErrorMessageOverflow,
+        ServiceError::IncorrectCallbackProgramId,
+        ServiceError::ProgramWalletInsufficientFunds,
+    ];
+
+    pub fn code(&self) -> u32 {
+        Self::CODE_OFFSET + *self as u32
+    }
+
+    /// The error with the given custom program error code.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| error.code() == code)
+    }
+
+    /// The message the service logs for this error.
+    pub fn description(&self) -> &'static str {
+        match self {
+            ServiceError::InvalidNumberOfBytes => {
+                "num_bytes must be greater than 0 and less than or equal to 32"
+            }
+            ServiceError::InvalidCallback => "User's callback cannot be executed",
+            ServiceError::MissingCallbackAccount => "Account not found in remaining_accounts",
+            ServiceError::InvalidEscrow => "Invalid token account",
+            ServiceError::InsufficientFunds => "User escrow has insufficient funds",
+            ServiceError::CpiUnauthorized => "This instruction cannot be invoked with a CPI call",
+            ServiceError::RequestStillActive => "The provided request has not been completed yet",
+            ServiceError::RequestAlreadyCompleted => {
+                "The randomness request has already been completed"
+            }
+            ServiceError::ErrorMessageOverflow => "The provided error message exceeded 256 bytes",
+            ServiceError::IncorrectCallbackProgramId => {
+                "The provided callback program_id is incorrect"
+            }
+            ServiceError::ProgramWalletInsufficientFunds => {
+                "The program token wallet had insufficient funds for the request"
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<ServiceError> for ProgramError {
+    fn from(value: ServiceError) -> Self {
+        ProgramError::Custom(value.code())
+    }
+}
+
+/// Describes a custom program error code returned by this crate's checks or by the randomness
+/// service.
+pub fn describe_error_code(code: u32) -> Option<&'static str> {
+    LiteError::from_code(code)
+        .map(|error| error.description())
+        .or_else(|| ServiceError::from_code(code).map(|error| error.description()))
+}
+
+/// Describes a custom program error code like [`describe_error_code`], naming the crate version
+/// that explained it, since descriptions can change between releases.
+pub fn explain_error_code(code: u32) -> Option<String> {
+    describe_error_code(code).map(|description| {
+        format!(
+            "Error {}: {} (solana-randomness-service-lite {})",
+            code, description, CRATE_VERSION
+        )
+    })
+}
@@ -0,0 +1,205 @@
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Logs an event the way Anchor's `emit!` does: a `Program data:` line holding the
+/// discriminator followed by the Borsh encoded event, base64 encoded by the runtime.
+///
+/// For programs written without Anchor, so indexers that decode Anchor events can read them.
+/// Use [`anchor_event_discriminator`] for the discriminator of an event named like an Anchor
+/// `#[event]` struct.
+pub fn emit_event<T: BorshSerialize>(discriminator: [u8; 8], event: &T) {
+    let mut data = discriminator.to_vec();
+    // Writing to a Vec does not fail
+    if event.serialize(&mut data).is_ok() {
+        solana_program::log::sol_log_data(&[&data]);
+    }
+}
+
+/// The event a consumer logs when it requests randomness.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct ConsumerRequested {
+    pub request: Pubkey,
+    /// The user who paid for the request.
+    pub user: Pubkey,
+    pub num_bytes: u8,
+    pub request_slot: u64,
+}
+
+impl ConsumerRequested {
+    /// `anchor_event_discriminator("ConsumerRequested")`
+    pub const DISCRIMINATOR: [u8; 8] = [148, 129, 63, 67, 151, 108, 234, 129];
+
+    pub fn emit(&self) {
+        emit_event(Self::DISCRIMINATOR, self);
+    }
+}
+
+/// The event a consumer logs when its callback receives the randomness.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct ConsumerFulfilled {
+    pub request: Pubkey,
+    pub randomness: Vec<u8>,
+    /// The slot the callback ran at.
+    pub slot: u64,
+}
+
+impl ConsumerFulfilled {
+    /// `anchor_event_discriminator("ConsumerFulfilled")`
+    pub const DISCRIMINATOR: [u8; 8] = [170, 49, 123, 20, 108, 90, 202, 84];
+
+    pub fn emit(&self) {
+        emit_event(Self::DISCRIMINATOR, self);
+    }
+}
+
+/// The event a callback logs with the compute units it consumed, for tuning the compute
+/// budget of later requests. Logged by `ComputeMeter::finish` and read by the client's
+/// `callback_compute_units`.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct CallbackComputeUsage {
+    pub request: Pubkey,
+    /// The discriminator of the callback instruction that was measured.
+    pub discriminator: [u8; 8],
+    pub units_consumed: u64,
+}
+
+impl CallbackComputeUsage {
+    /// `anchor_event_discriminator("CallbackComputeUsage")`
+    pub const DISCRIMINATOR: [u8; 8] = [240, 221, 220, 171, 180, 238, 192, 179];
+
+    pub fn emit(&self) {
+        emit_event(Self::DISCRIMINATOR, self);
+    }
+}
+
+/// Measures the compute units a callback consumes with `sol_remaining_compute_units`, which
+/// needs `solana-program` 1.17 or later. Start it first thing in the callback and
+/// [`finish`](Self::finish) it last; what runs before it, such as Anchor's account
+/// deserialization, is not counted.
+#[cfg(feature = "compute-log")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "compute-log")))]
+pub struct ComputeMeter {
+    remaining: u64,
+}
+
+#[cfg(feature = "compute-log")]
+impl ComputeMeter {
+    pub fn start() -> Self {
+        Self {
+            remaining: solana_program::compute_units::sol_remaining_compute_units(),
+        }
+    }
+
+    /// The compute units consumed since [`start`](Self::start).
+    pub fn consumed(&self) -> u64 {
+        self.remaining
+            .saturating_sub(solana_program::compute_units::sol_remaining_compute_units())
+    }
+
+    /// Logs a [`CallbackComputeUsage`] event with the units consumed so far.
+    pub fn finish(self, request: Pubkey, discriminator: [u8; 8]) {
+        CallbackComputeUsage {
+            request,
+            discriminator,
+            units_consumed: self.consumed(),
+        }
+        .emit();
+    }
+}
+
+/// The event the randomness service logs when a request is created.
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct SimpleRandomnessV1RequestedEvent {
+    /// The program the callback invokes.
+    pub callback_pid: Pubkey,
+    /// The user who paid for the request.
+    pub user: Pubkey,
+    pub request: Pubkey,
+    pub request_slot: u64,
+    pub callback: Callback,
+    pub compute_units: u32,
+    pub priority_fee_micro_lamports: u64,
+    pub num_bytes: u8,
+}
+
+impl SimpleRandomnessV1RequestedEvent {
+    /// `anchor_event_discriminator("SimpleRandomnessV1RequestedEvent")`
+    pub const DISCRIMINATOR: [u8; 8] = [21, 26, 233, 167, 116, 76, 187, 183];
+
+    /// Decodes the event from the data of a `Program data:` log line, base64 decoded,
+    /// discriminator included.
+    pub fn try_from_log_data(data: &[u8]) -> Result<Self, ProgramError> {
+        decode_log_data(Self::DISCRIMINATOR, data)
+    }
+}
+
+/// The event the randomness service logs when the oracle settles a request and invokes its
+/// callback.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct SimpleRandomnessV1SettledEvent {
+    /// The program the callback invoked.
+    pub callback_pid: Pubkey,
+    /// The user who paid for the request.
+    pub user: Pubkey,
+    pub request: Pubkey,
+    pub request_slot: u64,
+    pub settled_slot: u64,
+    /// Whether the callback succeeded. The service settles the request either way.
+    pub is_success: bool,
+    /// The randomness delivered to the callback.
+    pub randomness: Vec<u8>,
+}
+
+impl SimpleRandomnessV1SettledEvent {
+    /// `anchor_event_discriminator("SimpleRandomnessV1SettledEvent")`
+    pub const DISCRIMINATOR: [u8; 8] = [185, 253, 253, 64, 75, 227, 202, 173];
+
+    /// Decodes the event from the data of a `Program data:` log line, base64 decoded,
+    /// discriminator included.
+    pub fn try_from_log_data(data: &[u8]) -> Result<Self, ProgramError> {
+        decode_log_data(Self::DISCRIMINATOR, data)
+    }
+}
+
+/// An event logged by the randomness service.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum RandomnessServiceEvent {
+    Requested(SimpleRandomnessV1RequestedEvent),
+    Settled(SimpleRandomnessV1SettledEvent),
+}
+
+impl RandomnessServiceEvent {
+    /// Decodes whichever service event the data of a `Program data:` log line holds, base64
+    /// decoded, discriminator included.
+    pub fn try_from_log_data(data: &[u8]) -> Result<Self, ProgramError> {
+        match data.get(..8) {
+            Some(d) if d == SimpleRandomnessV1RequestedEvent::DISCRIMINATOR => Ok(Self::Requested(
+                SimpleRandomnessV1RequestedEvent::try_from_log_data(data)?,
+            )),
+            Some(d) if d == SimpleRandomnessV1SettledEvent::DISCRIMINATOR => Ok(Self::Settled(
+                SimpleRandomnessV1SettledEvent::try_from_log_data(data)?,
+            )),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+
+    /// The request account the event is about.
+    pub fn request(&self) -> &Pubkey {
+        match self {
+            Self::Requested(event) => &event.request,
+            Self::Settled(event) => &event.request,
+        }
+    }
+}
+
+/// Decodes an event logged with `discriminator`, ignoring any fields a newer service appends.
+fn decode_log_data<T: BorshDeserialize>(
+    discriminator: [u8; 8],
+    data: &[u8],
+) -> Result<T, ProgramError> {
+    if data.get(..8) != Some(&discriminator[..]) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    T::deserialize(&mut &data[8..]).map_err(|_| ProgramError::InvalidArgument)
+}
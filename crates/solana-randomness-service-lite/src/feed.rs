@@ -0,0 +1,61 @@
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The latest randomness result delivered to a user, stored by a consumer program in a PDA
+/// seeded with [`ResultFeed::SEED`] and the user's key.
+///
+/// Frontends poll the one feed address per user instead of tracking each request account,
+/// which the service closes once it is settled. Any consumer writing this layout, such as the
+/// example program's `consume_feed_randomness`, can be read with the same code.
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct ResultFeed {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The user the feed belongs to, the payer of its requests.
+    pub user: Pubkey,
+    /// The request that delivered the latest result. Default until the first result.
+    pub request: Pubkey,
+    /// The slot the latest result was delivered at. Zero until the first result.
+    pub slot: u64,
+    /// The number of valid bytes in `result`.
+    pub result_len: u8,
+    /// The latest result, zero padded to the service's 32 byte maximum.
+    pub result: [u8; 32],
+    /// The slot the request that delivered the latest result was made at. A consumer skips
+    /// results of requests made before it, which a retried settlement can deliver late. Zero
+    /// until the first result.
+    pub request_slot: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; RESERVED_LEN - 8],
+}
+
+impl ResultFeed {
+    /// The Anchor account discriminator, `sha256("account:ResultFeed")[..8]`.
+    pub const DISCRIMINATOR: [u8; 8] = [163, 155, 87, 175, 44, 83, 138, 110];
+
+    /// The seed prefix of the feed PDA, `[SEED, user]`.
+    pub const SEED: &'static [u8] = b"RESULT_FEED";
+
+    /// The account space of a feed, including the discriminator.
+    pub const SPACE: usize = 8 + 1 + 32 + 32 + 8 + 1 + 32 + 8 + (RESERVED_LEN - 8);
+
+    /// The feed address and bump for a user of the consumer program.
+    pub fn address(consumer_program: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, user.as_ref()], consumer_program)
+    }
+
+    /// The latest result, or `None` if the feed has not received one yet.
+    pub fn latest(&self) -> Option<&[u8]> {
+        if self.slot == 0 {
+            return None;
+        }
+        self.result.get(..usize::from(self.result_len))
+    }
+
+    pub fn try_deserialize(buf: &mut &[u8]) -> Result<Self, ProgramError> {
+        if buf.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        deserialize_with_reserved(&buf[8..])
+    }
+}
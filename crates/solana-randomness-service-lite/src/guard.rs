@@ -0,0 +1,133 @@
+use crate::*;
+use solana_program::msg;
+use solana_program::rent::Rent;
+
+/// The flat fee the service charges for every request, in lamports: the `10000u64` term of
+/// `State::request_cost` in `src/impls/state.rs` of solana-randomness-service 1.0.2.
+pub const REQUEST_BASE_FEE: u64 = 10_000;
+
+/// The size of a request's escrow, an SPL token account.
+pub const ESCROW_ACCOUNT_SIZE: usize = 165;
+
+/// The fee the service charges for a request and wraps into its escrow, as its
+/// `State::request_cost` computes it: the [`REQUEST_BASE_FEE`], `cost_per_byte` for each byte,
+/// and the priority fee rounded down to whole lamports, where
+/// [`TransactionOptions::get_priority_fee_lamports`] rounds up. Fails with
+/// [`LiteError::FeeOverflow`] if the fee does not fit in a u64.
+///
+/// Needs no RPC: pass the `cost_per_byte` of the service's state account. With the `client`
+/// feature, `client::fetch_request_cost` reads it and adds the rent the payer funds.
+pub fn estimate_request_cost(
+    num_bytes: u8,
+    cost_per_byte: u64,
+    options: &Option<TransactionOptions>,
+) -> Result<u64, ProgramError> {
+    let options = options.clone().unwrap_or_default();
+    cost_per_byte
+        .checked_mul(u64::from(num_bytes))
+        .zip(wrapped_priority_fee(&options))
+        .and_then(|(randomness_fee, priority_fee)| {
+            REQUEST_BASE_FEE
+                .checked_add(randomness_fee)?
+                .checked_add(priority_fee)
+        })
+        .ok_or_else(|| LiteError::FeeOverflow.into())
+}
+
+/// The priority fee the service wraps into a request's escrow, the exact fee rounded down to
+/// whole lamports.
+pub(crate) fn wrapped_priority_fee(options: &TransactionOptions) -> Option<u64> {
+    options
+        .get_priority_fee_micro_lamports()
+        .map(|fee| fee / MICRO_LAMPORTS_PER_LAMPORT)
+}
+
+/// The lamports the payer of a request spends, excluding the transaction fee: the
+/// [`estimate_request_cost`], wrapped into the escrow, and the rent of the request and escrow
+/// accounts. Lets a consumer check the payer can afford the request before writing anything.
+/// Fails with [`LiteError::FeeOverflow`] if the total does not fit in a u64.
+pub fn required_request_lamports(
+    num_bytes: u8,
+    callback: &Callback,
+    options: Option<&TransactionOptions>,
+    cost_per_byte: u64,
+    rent: &Rent,
+) -> Result<u64, ProgramError> {
+    estimate_request_cost(num_bytes, cost_per_byte, &options.cloned())?
+        .checked_add(rent.minimum_balance(SimpleRandomnessV1Account::space(callback)))
+        .and_then(|lamports| lamports.checked_add(rent.minimum_balance(ESCROW_ACCOUNT_SIZE)))
+        .ok_or_else(|| LiteError::FeeOverflow.into())
+}
+
+/// A consumer account that remembers the request it is waiting on, written by a
+/// [`RequestGuard`] and cleared by the callback.
+pub trait PendingRecord {
+    /// Records `request`, made at `slot`, as pending.
+    fn mark_pending(&mut self, request: Pubkey, slot: u64);
+
+    /// Forgets the pending request.
+    fn clear_pending(&mut self);
+}
+
+/// Writes a consumer's pending record around the service CPI, in the order a request
+/// instruction should follow: validate the request and its funding, write the record with
+/// [`begin`](Self::begin), invoke the service, then [`commit`](Self::commit).
+///
+/// A failed CPI fails the whole transaction, so the runtime discards the record on its own and
+/// the guard never has to undo it there. The guard catches the mistakes the runtime cannot:
+/// returning early between the write and the CPI leaves no pending record behind, since a
+/// guard dropped without `commit` clears it, and [`abort`](Self::abort) clears it explicitly
+/// when the program decides not to request after all.
+///
+/// ```ignore
+/// let guard = RequestGuard::begin(&mut *ctx.accounts.record, request.key(), slot);
+/// request.cpi(program, num_bytes, &callback, Some(&options))?;
+/// guard.commit();
+/// ```
+#[must_use = "commit the guard once the service CPI succeeds, or the pending record is cleared"]
+pub struct RequestGuard<'a, T: PendingRecord + ?Sized> {
+    record: &'a mut T,
+    request: Pubkey,
+    done: bool,
+}
+
+impl<'a, T: PendingRecord + ?Sized> RequestGuard<'a, T> {
+    /// Marks `request`, made at `slot`, pending in the record.
+    pub fn begin(record: &'a mut T, request: Pubkey, slot: u64) -> Self {
+        record.mark_pending(request, slot);
+        Self {
+            record,
+            request,
+            done: false,
+        }
+    }
+
+    /// The request the record is waiting on.
+    pub fn request(&self) -> Pubkey {
+        self.request
+    }
+
+    /// Keeps the pending record. Call once the service CPI returned successfully.
+    pub fn commit(mut self) -> Pubkey {
+        self.done = true;
+        self.request
+    }
+
+    /// Clears the pending record, for a request the program decided not to send.
+    pub fn abort(mut self) {
+        self.record.clear_pending();
+        self.done = true;
+    }
+}
+
+impl<T: PendingRecord + ?Sized> Drop for RequestGuard<'_, T> {
+    fn drop(&mut self) {
+        if !self.done {
+            msg!(
+                "Request {} was not committed, clearing its pending record",
+                self.request
+            );
+            self.record.clear_pending();
+        }
+    }
+}
@@ -0,0 +1,1277 @@
+//! An in-process stand-in for the randomness service and its oracle, for testing consumer
+//! callbacks without a validator.
+//!
+//! Enabled with the `harness` feature. The harness keeps its own account store, invokes the
+//! consumer's entrypoint the way `simple_randomness_v1_settle` does, and routes cross-program
+//! invocations made by the callback to the programs registered with
+//! [`OracleHarness::add_program`]. Besides the happy path, it can inject the failures a
+//! consumer has to guard against:
+//!
+//! - [`OracleHarness::fulfill_with_error`] writes an `error_message` to the request instead of
+//!   invoking the callback, like `simple_randomness_v1_callback_error`.
+//! - [`OracleHarness::fulfill_twice`] invokes the callback a second time before the request is
+//!   closed, to exercise replay protection.
+//! - [`OracleHarness::fulfill_short`] delivers fewer bytes than the request asked for. The
+//!   service does not check the length of the randomness against `num_bytes`.
+//! - [`OracleHarness::fulfill_with_transient_failure_then_success`] runs the callback in
+//!   settle transactions that fail and are retried later, to check the callback holds up when
+//!   it runs late.
+//!
+//! Requests created with [`OracleHarness::submit_request`] are funded the way the service
+//! funds them, so [`OracleHarness::balances`] shows where the fee and rent go:
+//!
+//! - Requesting moves the request and escrow rent plus the fee from the payer into the
+//!   request account and the escrow.
+//! - Settling pays the fee from the escrow to the reward wallet and returns both rents to the
+//!   payer.
+//! - An error also pays the fee to the reward wallet. It is not refunded. Both rents stay in
+//!   the open accounts until [`OracleHarness::close`] returns them to the payer, like
+//!   `simple_randomness_v1_close`.
+//!
+//! [`OracleHarness::run_round`] runs a whole round in one call: the consumer's own request
+//! instruction, whose `simple_randomness_v1` CPI is handled by a stand-in for the service, then
+//! the callback with the given randomness. It returns the accounts the round touched as they
+//! were before and after, and everything logged along the way.
+//!
+//! The service stand-in also accepts a token account after the request's own accounts and
+//! pulls the fee from it through a delegate approval, the way a fork of the service supporting
+//! the client's `RequestBuilder::fund_via_delegate` would.
+//! [`OracleHarness::process_transaction`] runs the approval and the request a client builds,
+//! and [`OracleHarness::token_account`] reads the delegate back.
+//!
+//! A stand-in for the system program handles `Transfer`, so a referral fee paid with the
+//! request, by the client's `RequestBuilder::with_referral_fee` or by a consumer before its
+//! CPI, moves lamports like on a cluster.
+//!
+//! Account data cannot be reallocated during an invocation.
+
+use crate::addresses::{ASSOCIATED_TOKEN_PROGRAM_ID, NATIVE_MINT, TOKEN_PROGRAM_ID};
+use crate::*;
+use base64::Engine;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::{ProcessInstruction, ProgramResult, SUCCESS};
+use solana_program::hash::hashv;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_program::rent::Rent;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Once;
+
+pub use crate::ServiceError;
+
+/// The entrypoint of the consumer program. Anchor's generated `entry` function has this
+/// signature.
+pub type CallbackProcessor =
+    for<'info> fn(&Pubkey, &'info [AccountInfo<'info>], &[u8]) -> ProgramResult;
+
+/// An account held by the harness.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HarnessAccount {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl HarnessAccount {
+    /// A rent exempt account holding the data.
+    pub fn new(data: Vec<u8>, owner: Pubkey) -> Self {
+        Self {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+            executable: false,
+        }
+    }
+}
+
+/// The lamports held by the accounts a request moves funds between, returned by
+/// [`OracleHarness::balances`]. Wrapped SOL token accounts hold their balance as lamports, so
+/// each balance includes the account's rent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BalanceSnapshot {
+    /// The request's escrow token account, zero once it is closed.
+    pub escrow: u64,
+    /// The harness payer, who is also the request's user.
+    pub payer: u64,
+    pub reward_wallet: u64,
+}
+
+/// A round for [`OracleHarness::run_round`].
+#[derive(Clone, Debug)]
+pub struct RoundSpec {
+    /// The consumer's instruction that requests randomness, such as `request_randomness`. Its
+    /// signers are treated as having signed the transaction; the payer is usually
+    /// [`OracleHarness::payer`].
+    pub consumer_request_ix: Instruction,
+    /// The randomness delivered to the callback.
+    pub randomness: Vec<u8>,
+    /// The discriminator the request's callback instruction data must start with.
+    pub expected_callback_disc: [u8; 8],
+}
+
+/// The outcome of [`OracleHarness::run_round`].
+#[derive(Clone, Debug)]
+pub struct RoundResult {
+    /// The request account created by the consumer's instruction. It is closed by the end of
+    /// the round.
+    pub request: Pubkey,
+    /// The request as the consumer's instruction created it.
+    pub pending: SimpleRandomnessV1Account,
+    /// Every account the round touched, as it was before the round. Accounts that did not
+    /// exist yet are missing.
+    pub before: BTreeMap<Pubkey, HarnessAccount>,
+    /// Every account the round touched, as it is after the round. Closed accounts are missing.
+    pub after: BTreeMap<Pubkey, HarnessAccount>,
+    /// The messages logged by the request instruction, followed by those of the callback.
+    pub logs: Vec<String>,
+}
+
+impl RoundResult {
+    /// The touched accounts that were created, closed, or modified by the round.
+    pub fn changed(&self) -> Vec<Pubkey> {
+        let keys: BTreeSet<&Pubkey> = self.before.keys().chain(self.after.keys()).collect();
+        keys.into_iter()
+            .filter(|key| self.before.get(key) != self.after.get(key))
+            .copied()
+            .collect()
+    }
+
+    /// The change in an account's lamports over the round.
+    pub fn lamports_delta(&self, pubkey: &Pubkey) -> i128 {
+        let lamports = |snapshot: &BTreeMap<Pubkey, HarnessAccount>| {
+            snapshot.get(pubkey).map_or(0, |a| a.lamports)
+        };
+        i128::from(lamports(&self.after)) - i128::from(lamports(&self.before))
+    }
+}
+
+/// The fields of an SPL token account that the harness's token program stand-in reads and
+/// writes. Stored in the packed SPL token layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenAccountState {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+    pub delegated_amount: u64,
+}
+
+impl TokenAccountState {
+    /// A wrapped SOL account holding `amount`, without a delegate.
+    pub fn wrapped_sol(owner: Pubkey, amount: u64) -> Self {
+        Self {
+            mint: NATIVE_MINT,
+            owner,
+            amount,
+            ..Default::default()
+        }
+    }
+
+    fn pack(&self) -> Vec<u8> {
+        let mut data = vec![0; ESCROW_ACCOUNT_SIZE];
+        data[..32].copy_from_slice(self.mint.as_ref());
+        data[32..64].copy_from_slice(self.owner.as_ref());
+        data[64..72].copy_from_slice(&self.amount.to_le_bytes());
+        if let Some(delegate) = self.delegate {
+            data[72] = 1;
+            data[76..108].copy_from_slice(delegate.as_ref());
+        }
+        // Initialized
+        data[108] = 1;
+        if self.mint == NATIVE_MINT {
+            data[109] = 1;
+            data[113..121].copy_from_slice(&token_account_rent().to_le_bytes());
+        }
+        data[121..129].copy_from_slice(&self.delegated_amount.to_le_bytes());
+        data
+    }
+
+    fn unpack(data: &[u8]) -> Option<Self> {
+        let view = parse_token_account(data).ok()?;
+        Some(Self {
+            mint: view.mint,
+            owner: view.owner,
+            amount: view.amount,
+            delegate: view.delegate,
+            delegated_amount: view.delegated_amount,
+        })
+    }
+}
+
+/// A deterministic oracle that fulfills requests by invoking the consumer's callback in
+/// process.
+pub struct OracleHarness {
+    program_id: Pubkey,
+    processor: CallbackProcessor,
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    accounts: HashMap<Pubkey, HarnessAccount>,
+    clock: Clock,
+    logs: Vec<String>,
+    payer: Pubkey,
+    cost_per_byte: u64,
+}
+
+impl OracleHarness {
+    /// Creates a harness for the consumer program, e.g.
+    /// `OracleHarness::new(my_program::ID, my_program::entry)`.
+    pub fn new(program_id: Pubkey, processor: CallbackProcessor) -> Self {
+        let mut harness = Self {
+            program_id,
+            processor,
+            programs: HashMap::new(),
+            accounts: HashMap::new(),
+            clock: Clock::default(),
+            logs: Vec::new(),
+            payer: Pubkey::new_unique(),
+            cost_per_byte: 0,
+        };
+        harness.set_account(program_id, executable_account());
+        harness
+    }
+
+    /// Registers a program the callback invokes, such as the SPL token program.
+    pub fn add_program(&mut self, program_id: Pubkey, processor: ProcessInstruction) {
+        self.programs.insert(program_id, processor);
+        self.set_account(program_id, executable_account());
+    }
+
+    pub fn set_account(&mut self, pubkey: Pubkey, account: HarnessAccount) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    pub fn account(&self, pubkey: &Pubkey) -> Option<&HarnessAccount> {
+        self.accounts.get(pubkey)
+    }
+
+    pub fn remove_account(&mut self, pubkey: &Pubkey) -> Option<HarnessAccount> {
+        self.accounts.remove(pubkey)
+    }
+
+    /// Credits lamports to an account, creating it if needed.
+    pub fn airdrop(&mut self, pubkey: &Pubkey, lamports: u64) {
+        self.accounts.entry(*pubkey).or_default().lamports += lamports;
+    }
+
+    /// The account that pays for requests made with [`submit_request`](Self::submit_request).
+    /// Starts without lamports; fund it with [`airdrop`](Self::airdrop).
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    /// Sets the service's cost per randomness byte charged by
+    /// [`submit_request`](Self::submit_request). Defaults to zero.
+    pub fn set_cost_per_byte(&mut self, cost_per_byte: u64) {
+        self.cost_per_byte = cost_per_byte;
+    }
+
+    /// The fee the service charges for a request, wrapped into its escrow, see
+    /// [`estimate_request_cost`].
+    pub fn request_cost(
+        &self,
+        num_bytes: u8,
+        options: &Option<TransactionOptions>,
+    ) -> Result<u64, ProgramError> {
+        estimate_request_cost(num_bytes, self.cost_per_byte, options)
+    }
+
+    /// Creates and funds a request from the [`payer`](Self::payer), like
+    /// `simple_randomness_v1`: the payer pays the rent of the request account and its escrow,
+    /// and the [`request_cost`](Self::request_cost) is wrapped into the escrow.
+    pub fn submit_request(
+        &mut self,
+        request: Pubkey,
+        num_bytes: u8,
+        callback: Callback,
+        options: Option<TransactionOptions>,
+    ) -> ProgramResult {
+        self.create_request(request, self.payer, num_bytes, callback, options, None)
+    }
+
+    /// Creates and funds a request paid for by `user`. With a `funding_source`, the fee is
+    /// pulled from that token account with the request as its delegate instead.
+    fn create_request(
+        &mut self,
+        request: Pubkey,
+        user: Pubkey,
+        num_bytes: u8,
+        callback: Callback,
+        options: Option<TransactionOptions>,
+        funding_source: Option<Pubkey>,
+    ) -> ProgramResult {
+        if self.accounts.contains_key(&request) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let options_or_default = options.clone().unwrap_or_default();
+        let pending = SimpleRandomnessV1Account {
+            num_bytes,
+            user,
+            escrow: derive_escrow_pubkey(&request, &NATIVE_MINT),
+            request_slot: self.clock.slot,
+            compute_units: options_or_default.get_compute_units(),
+            priority_fee_micro_lamports: options_or_default.get_compute_unit_price(),
+            callback,
+            ..Default::default()
+        };
+        let rent = Rent::default();
+        let request_rent = rent.minimum_balance(pending.expected_request_account_size());
+        let escrow_rent = rent.minimum_balance(ESCROW_ACCOUNT_SIZE);
+        let cost = self.request_cost(num_bytes, &options)?;
+        let user_cost = match funding_source {
+            Some(_) => request_rent + escrow_rent,
+            None => request_rent + escrow_rent + cost,
+        };
+
+        let user_lamports = self.accounts.get(&user).map_or(0, |a| a.lamports);
+        if user_lamports < user_cost {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        if let Some(source) = funding_source {
+            self.pull_delegated(&source, &request, cost)?;
+        }
+        self.accounts.entry(user).or_default().lamports -= user_cost;
+
+        self.add_request(request, &pending);
+        self.set_account(
+            pending.escrow,
+            HarnessAccount {
+                lamports: escrow_rent + cost,
+                data: vec![0; ESCROW_ACCOUNT_SIZE],
+                owner: TOKEN_PROGRAM_ID,
+                executable: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// The escrow token account the service creates for a request, to pass to the consumer's
+    /// request instruction in a [round](Self::run_round).
+    pub fn escrow(&self, request: &Pubkey) -> Pubkey {
+        derive_escrow_pubkey(request, &NATIVE_MINT)
+    }
+
+    /// The balances of the request's escrow, the [`payer`](Self::payer), and the reward
+    /// wallet. Take one before and after each step to assert where the funds went.
+    pub fn balances(&self, request: &Pubkey) -> BalanceSnapshot {
+        let lamports = |pubkey: &Pubkey| self.accounts.get(pubkey).map_or(0, |a| a.lamports);
+        BalanceSnapshot {
+            escrow: lamports(&derive_escrow_pubkey(request, &NATIVE_MINT)),
+            payer: lamports(&self.payer),
+            reward_wallet: lamports(&RANDOMNESS_SERVICE_REWARD_WALLET),
+        }
+    }
+
+    /// Stores a pending request the way the service allocates it, without funding it. Use
+    /// [`submit_request`](Self::submit_request) to also create the escrow.
+    pub fn add_request(&mut self, pubkey: Pubkey, request: &SimpleRandomnessV1Account) {
+        let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+        data.extend(request.try_to_vec().expect("request serializes"));
+        data.resize(request.expected_request_account_size(), 0);
+        self.set_account(pubkey, HarnessAccount::new(data, ID));
+    }
+
+    /// Stores an SPL token account in the packed layout. A wrapped SOL account holds its rent
+    /// plus `amount` lamports.
+    pub fn set_token_account(&mut self, pubkey: Pubkey, state: &TokenAccountState) {
+        let mut account = HarnessAccount::new(state.pack(), TOKEN_PROGRAM_ID);
+        if state.mint == NATIVE_MINT {
+            account.lamports += state.amount;
+        }
+        self.set_account(pubkey, account);
+    }
+
+    /// Decodes an SPL token account, returning `None` if it does not exist or is not one.
+    pub fn token_account(&self, pubkey: &Pubkey) -> Option<TokenAccountState> {
+        let account = self.accounts.get(pubkey)?;
+        if account.owner != TOKEN_PROGRAM_ID {
+            return None;
+        }
+        TokenAccountState::unpack(&account.data)
+    }
+
+    /// Runs the instructions of a transaction in order, such as the ones the client's
+    /// `RequestBuilder::instructions` returns, and returns the requests created.
+    /// Each instruction is run by the consumer or a program registered with
+    /// [`add_program`](Self::add_program); call [`add_service_accounts`](Self::add_service_accounts)
+    /// first to run the service and token program stand-ins. Signers are taken from the
+    /// instructions' account metas. If an instruction fails, every account is left as it was.
+    pub fn process_transaction(
+        &mut self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<Pubkey>, ProgramError> {
+        self.logs.clear();
+        let start = self.accounts.clone();
+        let mut created = Vec::new();
+        for instruction in instructions {
+            let processor: CallbackProcessor = if instruction.program_id == self.program_id {
+                self.processor
+            } else {
+                match self.programs.get(&instruction.program_id) {
+                    Some(processor) => *processor,
+                    None => {
+                        self.accounts = start;
+                        return Err(ProgramError::IncorrectProgramId);
+                    }
+                }
+            };
+            let result = self.execute(
+                instruction.program_id,
+                processor,
+                &instruction.accounts,
+                &instruction.data,
+                0,
+            );
+            match result {
+                Ok(requests) => created.extend(requests),
+                Err(e) => {
+                    self.accounts = start;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(created)
+    }
+
+    /// Decodes a request account, returning `None` once it is closed.
+    pub fn request(&self, pubkey: &Pubkey) -> Option<SimpleRandomnessV1Account> {
+        let account = self.accounts.get(pubkey)?;
+        SimpleRandomnessV1Account::try_deserialize(&mut &account.data[..]).ok()
+    }
+
+    pub fn slot(&self) -> u64 {
+        self.clock.slot
+    }
+
+    /// The clock programs read with `Clock::get()` during invocations.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Moves the clock to `slot`, forwards or back. Programs read it with `Clock::get()`
+    /// during invocations, requests made after it record it as their `request_slot`, and
+    /// helpers taking a [`SlotSource`] see it when passed the harness or its
+    /// [`clock`](Self::clock), so expiry and staleness checks agree on and off chain.
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.clock.slot = slot;
+    }
+
+    #[deprecated(since = "1.1.0", note = "use `OracleHarness::warp_to_slot`")]
+    pub fn set_slot(&mut self, slot: u64) {
+        self.warp_to_slot(slot);
+    }
+
+    /// The messages logged during the last fulfillment.
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// The randomness the harness delivers for a request, derived from the request address so
+    /// test runs are reproducible.
+    pub fn randomness(&self, request: &Pubkey, len: usize) -> Vec<u8> {
+        let mut randomness = Vec::with_capacity(len);
+        let mut counter = 0u32;
+        while randomness.len() < len {
+            let block = hashv(&[b"harness", request.as_ref(), &counter.to_le_bytes()]);
+            randomness.extend(block.to_bytes());
+            counter += 1;
+        }
+        randomness.truncate(len);
+        randomness
+    }
+
+    /// Invokes the callback with the randomness and closes the request, like
+    /// `simple_randomness_v1_settle`. If the callback fails, every account is left as it was.
+    pub fn fulfill(&mut self, request: &Pubkey, randomness: &[u8]) -> ProgramResult {
+        self.logs.clear();
+        let pending = self.pending_request(request)?;
+        self.check_escrow(&pending)?;
+        self.invoke_callback(request, &pending, randomness)?;
+        self.pay_reward(&pending);
+        self.close_accounts(request, &pending);
+        Ok(())
+    }
+
+    /// Marks the request as completed with an error message without invoking the callback, like
+    /// `simple_randomness_v1_callback_error`. The fee in the escrow is paid to the reward
+    /// wallet; the request and escrow accounts stay open until they are [closed](Self::close).
+    pub fn fulfill_with_error(&mut self, request: &Pubkey, message: &str) -> ProgramResult {
+        self.logs.clear();
+        let mut pending = self.pending_request(request)?;
+        if message.len() > SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN {
+            return Err(ServiceError::ErrorMessageOverflow.into());
+        }
+        self.check_escrow(&pending)?;
+
+        pending.is_completed = 1;
+        pending.error_message = message.to_string();
+
+        let account = self
+            .accounts
+            .get_mut(request)
+            .ok_or(ProgramError::UninitializedAccount)?;
+        let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+        data.extend(
+            pending
+                .try_to_vec()
+                .map_err(|e| ProgramError::BorshIoError(e.to_string()))?,
+        );
+        if data.len() > account.data.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        account.data[..data.len()].copy_from_slice(&data);
+        self.pay_reward(&pending);
+
+        Ok(())
+    }
+
+    /// Closes a request that completed with an error, returning the rent of the request and
+    /// its escrow to the user, like `simple_randomness_v1_close`.
+    pub fn close(&mut self, request: &Pubkey) -> ProgramResult {
+        let pending = self
+            .request(request)
+            .ok_or(ProgramError::UninitializedAccount)?;
+        if pending.is_completed == 0 {
+            return Err(ServiceError::RequestStillActive.into());
+        }
+        self.close_accounts(request, &pending);
+        Ok(())
+    }
+
+    /// Invokes the callback twice with the same randomness before closing the request,
+    /// returning the result of each invocation. The service closes the request after the first
+    /// callback, so a second delivery can only come from a replayed instruction; the consumer
+    /// should reject it.
+    pub fn fulfill_twice(
+        &mut self,
+        request: &Pubkey,
+        randomness: &[u8],
+    ) -> (ProgramResult, ProgramResult) {
+        self.logs.clear();
+        let pending = match self.pending_request(request) {
+            Ok(pending) => pending,
+            Err(e) => return (Err(e.clone()), Err(e)),
+        };
+
+        if let Err(e) = self.check_escrow(&pending) {
+            return (Err(e.clone()), Err(e));
+        }
+
+        let first = self.invoke_callback(request, &pending, randomness);
+        let second = self.invoke_callback(request, &pending, randomness);
+        if first.is_ok() {
+            self.pay_reward(&pending);
+            self.close_accounts(request, &pending);
+        }
+
+        (first, second)
+    }
+
+    /// Fulfills the request with `len` bytes of [`randomness`](Self::randomness) instead of the
+    /// `num_bytes` it asked for.
+    pub fn fulfill_short(&mut self, request: &Pubkey, len: usize) -> ProgramResult {
+        let randomness = self.randomness(request, len);
+        self.fulfill(request, &randomness)
+    }
+
+    /// Settles the request after `failures` lost settle transactions, the way the oracle
+    /// retries a transaction that passed simulation but failed on-chain.
+    ///
+    /// Each lost attempt invokes the callback and then discards every account change, as if a
+    /// later instruction of the transaction failed, whatever the callback returned. The slot
+    /// then advances by [`CALLBACK_RETRY_BACKOFF_SLOTS`] before the next attempt, and the last
+    /// one [fulfills](Self::fulfill) the request. [`logs`](Self::logs) holds the messages of
+    /// every attempt.
+    pub fn fulfill_with_transient_failure_then_success(
+        &mut self,
+        request: &Pubkey,
+        randomness: &[u8],
+        failures: u32,
+    ) -> ProgramResult {
+        let mut logs = Vec::new();
+        for _ in 0..failures {
+            self.logs.clear();
+            let pending = self.pending_request(request)?;
+            self.check_escrow(&pending)?;
+
+            let accounts = self.accounts.clone();
+            let _ = self.invoke_callback(request, &pending, randomness);
+            self.accounts = accounts;
+
+            logs.append(&mut self.logs);
+            self.clock.slot = self.clock.slot.saturating_add(CALLBACK_RETRY_BACKOFF_SLOTS);
+        }
+
+        let result = self.fulfill(request, randomness);
+        logs.append(&mut self.logs);
+        self.logs = logs;
+        result
+    }
+
+    /// Runs the consumer's request instruction, then [fulfills](Self::fulfill) the request it
+    /// created with the round's randomness.
+    ///
+    /// The instruction's `simple_randomness_v1` CPI is handled by a stand-in for the service
+    /// that creates and funds the request like [`submit_request`](Self::submit_request), from
+    /// the payer passed to the CPI. The service's state account, the native mint, and the
+    /// programs the request needs are added to the harness if missing; the state account's
+    /// `wallet` is [`RANDOMNESS_SERVICE_REWARD_WALLET`] and its cost per byte is the harness's.
+    ///
+    /// Fails if the instruction or the callback fails, if the instruction does not create
+    /// exactly one request, or with `InvalidInstructionData` if the request's callback does
+    /// not start with `expected_callback_disc`. A failed round leaves every account as it was.
+    pub fn run_round(&mut self, spec: RoundSpec) -> Result<RoundResult, ProgramError> {
+        let ix = &spec.consumer_request_ix;
+        if ix.program_id != self.program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        self.add_service_accounts();
+        let start = self.accounts.clone();
+
+        let round = self.run_round_unchecked(&spec);
+        let (request, pending, logs) = match round {
+            Ok(round) => round,
+            Err(e) => {
+                self.accounts = start;
+                return Err(e);
+            }
+        };
+
+        let callback_metas: Vec<AccountMeta> = pending
+            .callback
+            .accounts
+            .iter()
+            .map(AccountMeta::from)
+            .collect();
+        let touched: BTreeSet<Pubkey> = ix
+            .accounts
+            .iter()
+            .chain(callback_metas.iter())
+            .map(|meta| meta.pubkey)
+            .chain([request, pending.escrow, RANDOMNESS_SERVICE_REWARD_WALLET])
+            .collect();
+        let snapshot = |accounts: &HashMap<Pubkey, HarnessAccount>| {
+            touched
+                .iter()
+                .filter_map(|key| Some((*key, accounts.get(key)?.clone())))
+                .collect()
+        };
+
+        Ok(RoundResult {
+            request,
+            before: snapshot(&start),
+            after: snapshot(&self.accounts),
+            pending,
+            logs,
+        })
+    }
+
+    fn run_round_unchecked(
+        &mut self,
+        spec: &RoundSpec,
+    ) -> Result<(Pubkey, SimpleRandomnessV1Account, Vec<String>), ProgramError> {
+        self.logs.clear();
+        let ix = &spec.consumer_request_ix;
+        let requests = self.execute(self.program_id, self.processor, &ix.accounts, &ix.data, 0)?;
+        let [request] = requests[..] else {
+            return Err(ProgramError::InvalidArgument);
+        };
+        let mut logs = std::mem::take(&mut self.logs);
+
+        let pending = self.pending_request(&request)?;
+        if !pending
+            .callback
+            .ix_data
+            .starts_with(&spec.expected_callback_disc)
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        self.fulfill(&request, &spec.randomness)?;
+        logs.extend(self.logs.iter().cloned());
+        Ok((request, pending, logs))
+    }
+
+    /// Adds the accounts a consumer's request instruction passes to the service, and the
+    /// stand-ins that handle its `simple_randomness_v1` CPI and system transfers, unless they
+    /// are already set.
+    /// [`run_round`](Self::run_round) calls this; call it yourself when a test also settles
+    /// requests stored with [`add_request`](Self::add_request), whose callbacks read the state
+    /// account.
+    pub fn add_service_accounts(&mut self) {
+        self.programs.entry(ID).or_insert(process_service_request);
+        self.programs
+            .entry(TOKEN_PROGRAM_ID)
+            .or_insert(process_token_instruction);
+        self.programs
+            .entry(solana_program::system_program::ID)
+            .or_insert(process_system_instruction);
+        for program_id in [
+            ID,
+            solana_program::system_program::ID,
+            TOKEN_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+        ] {
+            self.accounts
+                .entry(program_id)
+                .or_insert_with(executable_account);
+        }
+
+        let (state, bump) = derive_state_pubkey(&ID);
+        let state_data = ServiceState {
+            bump,
+            mint: NATIVE_MINT,
+            wallet: RANDOMNESS_SERVICE_REWARD_WALLET,
+            cost_per_byte: self.cost_per_byte,
+            ..Default::default()
+        };
+        self.accounts.entry(state).or_insert_with(|| {
+            let mut data = ServiceState::DISCRIMINATOR.to_vec();
+            data.extend(state_data.try_to_vec().expect("state serializes"));
+            HarnessAccount::new(data, ID)
+        });
+
+        self.accounts
+            .entry(NATIVE_MINT)
+            .or_insert_with(|| HarnessAccount::new(native_mint_data(), TOKEN_PROGRAM_ID));
+    }
+
+    fn pending_request(&self, request: &Pubkey) -> Result<SimpleRandomnessV1Account, ProgramError> {
+        let pending = self
+            .request(request)
+            .ok_or(ProgramError::UninitializedAccount)?;
+        if pending.is_completed != 0 {
+            return Err(ServiceError::RequestAlreadyCompleted.into());
+        }
+        Ok(pending)
+    }
+
+    /// Fails like the service if the request's escrow holds less than the fee. Requests stored
+    /// with [`add_request`](Self::add_request) have no escrow and are not checked.
+    fn check_escrow(&self, pending: &SimpleRandomnessV1Account) -> ProgramResult {
+        if let Some(escrow) = self.accounts.get(&pending.escrow) {
+            let options = Some(TransactionOptions {
+                compute_units: Some(pending.compute_units),
+                compute_unit_price: Some(pending.priority_fee_micro_lamports),
+            });
+            if escrow_amount(escrow) < self.request_cost(pending.num_bytes, &options)? {
+                return Err(ServiceError::InsufficientFunds.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Transfers `amount` from a wrapped SOL token account with `delegate` as the authority,
+    /// like an SPL token `transfer` signed by the delegate, into the request's escrow when it is
+    /// created.
+    fn pull_delegated(&mut self, source: &Pubkey, delegate: &Pubkey, amount: u64) -> ProgramResult {
+        let mut state = self
+            .token_account(source)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if state.mint != NATIVE_MINT {
+            return Err(ServiceError::InvalidEscrow.into());
+        }
+        if state.delegate != Some(*delegate) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if state.delegated_amount < amount || state.amount < amount {
+            return Err(ServiceError::InsufficientFunds.into());
+        }
+
+        state.amount -= amount;
+        state.delegated_amount -= amount;
+        // SPL token clears the delegate once its allowance is used up
+        if state.delegated_amount == 0 {
+            state.delegate = None;
+        }
+        self.set_token_account(*source, &state);
+        Ok(())
+    }
+
+    /// Moves the wrapped balance of the escrow to the reward wallet, leaving its rent.
+    fn pay_reward(&mut self, pending: &SimpleRandomnessV1Account) {
+        let Some(escrow) = self.accounts.get_mut(&pending.escrow) else {
+            return;
+        };
+        let amount = escrow_amount(escrow);
+        escrow.lamports -= amount;
+        self.airdrop(&RANDOMNESS_SERVICE_REWARD_WALLET, amount);
+    }
+
+    /// Closes the request and its escrow to the user, like the settle instruction's
+    /// `close = user`.
+    fn close_accounts(&mut self, request: &Pubkey, pending: &SimpleRandomnessV1Account) {
+        for pubkey in [request, &pending.escrow] {
+            if let Some(account) = self.accounts.remove(pubkey) {
+                self.airdrop(&pending.user, account.lamports);
+            }
+        }
+    }
+
+    fn invoke_callback(
+        &mut self,
+        request: &Pubkey,
+        pending: &SimpleRandomnessV1Account,
+        randomness: &[u8],
+    ) -> ProgramResult {
+        if randomness.is_empty() || randomness.len() > usize::from(MAX_NUM_BYTES) {
+            return Err(ServiceError::InvalidNumberOfBytes.into());
+        }
+
+        let callback = &pending.callback;
+        if callback.program_id == Pubkey::default() {
+            self.logs
+                .push("The user's callback is undefined, skipping callback".to_string());
+            return Ok(());
+        }
+        if callback.program_id != self.program_id {
+            return Err(ServiceError::IncorrectCallbackProgramId.into());
+        }
+
+        let metas: Vec<AccountMeta> = callback.accounts.iter().map(AccountMeta::from).collect();
+        for meta in metas.iter() {
+            // The service only signs for its state account.
+            if meta.pubkey == RANDOMNESS_SERVICE_STATE && !meta.is_signer {
+                return Err(ServiceError::InvalidCallback.into());
+            }
+            if meta.is_signer && meta.pubkey != RANDOMNESS_SERVICE_STATE {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.pubkey != *request && !self.accounts.contains_key(&meta.pubkey) {
+                return Err(ServiceError::MissingCallbackAccount.into());
+            }
+        }
+
+        let data = [
+            callback.ix_data.clone(),
+            (randomness.len() as u32).to_le_bytes().to_vec(),
+            randomness.to_vec(),
+        ]
+        .concat();
+
+        self.execute(self.program_id, self.processor, &metas, &data, 1)?;
+        Ok(())
+    }
+
+    /// Invokes the program's entrypoint as an instruction at `base_height` below the top of the
+    /// transaction, then stores the accounts and creates the requests made through the service
+    /// stand-in, returning their addresses. If anything fails, every account is left as it was.
+    fn execute(
+        &mut self,
+        program_id: Pubkey,
+        processor: CallbackProcessor,
+        metas: &[AccountMeta],
+        data: &[u8],
+        base_height: usize,
+    ) -> Result<Vec<Pubkey>, ProgramError> {
+        // One copy of each account, flagged with the union of its metas' privileges.
+        let mut keys: Vec<Pubkey> = Vec::new();
+        let mut privileges: Vec<(bool, bool)> = Vec::new();
+        for meta in metas.iter() {
+            match keys.iter().position(|key| *key == meta.pubkey) {
+                Some(index) => {
+                    privileges[index].0 |= meta.is_signer;
+                    privileges[index].1 |= meta.is_writable;
+                }
+                None => {
+                    keys.push(meta.pubkey);
+                    privileges.push((meta.is_signer, meta.is_writable));
+                }
+            }
+        }
+        let mut storage: Vec<HarnessAccount> = keys
+            .iter()
+            .map(|key| self.accounts.get(key).cloned().unwrap_or_default())
+            .collect();
+
+        install_stubs();
+        INVOCATION.with(|invocation| {
+            *invocation.borrow_mut() = Some(Invocation {
+                programs: self.programs.clone(),
+                callers: vec![program_id],
+                base_height,
+                clock: self.clock.clone(),
+                logs: Vec::new(),
+                requests: Vec::new(),
+            })
+        });
+
+        let result = {
+            let infos: Vec<AccountInfo> = keys
+                .iter()
+                .zip(storage.iter_mut())
+                .zip(privileges.iter())
+                .map(|((key, account), (is_signer, is_writable))| {
+                    AccountInfo::new(
+                        key,
+                        *is_signer,
+                        *is_writable,
+                        &mut account.lamports,
+                        &mut account.data,
+                        &account.owner,
+                        account.executable,
+                        0,
+                    )
+                })
+                .collect();
+            let accounts: Vec<AccountInfo> = metas
+                .iter()
+                .map(|meta| {
+                    let index = keys.iter().position(|key| *key == meta.pubkey).unwrap();
+                    infos[index].clone()
+                })
+                .collect();
+
+            processor(&program_id, &accounts, data)
+        };
+
+        let mut requests = Vec::new();
+        let invocation = INVOCATION.with(|invocation| invocation.borrow_mut().take());
+        if let Some(invocation) = invocation {
+            self.logs.extend(invocation.logs);
+            requests = invocation.requests;
+        }
+
+        result?;
+        let start = self.accounts.clone();
+        for (key, account) in keys.into_iter().zip(storage) {
+            if account.lamports == 0 {
+                self.accounts.remove(&key);
+            } else {
+                self.accounts.insert(key, account);
+            }
+        }
+
+        let mut created = Vec::with_capacity(requests.len());
+        for request in requests {
+            let result = self.create_request(
+                request.request,
+                request.payer,
+                request.num_bytes,
+                request.callback,
+                request.options,
+                request.funding_source,
+            );
+            if let Err(e) = result {
+                self.accounts = start;
+                return Err(e);
+            }
+            created.push(request.request);
+        }
+        Ok(created)
+    }
+}
+
+impl SlotSource for OracleHarness {
+    fn slot(&self) -> u64 {
+        self.clock.slot
+    }
+}
+
+/// A `simple_randomness_v1` CPI received by the service stand-in, applied once the instruction
+/// that made it succeeds.
+struct ServiceRequest {
+    request: Pubkey,
+    payer: Pubkey,
+    num_bytes: u8,
+    callback: Callback,
+    options: Option<TransactionOptions>,
+    funding_source: Option<Pubkey>,
+}
+
+/// The stand-in for the service program. It checks a `simple_randomness_v1` instruction the
+/// way the service would and records it for the harness, which creates the request after the
+/// invocation, since account data cannot be reallocated during one. A token account passed
+/// after the request's accounts funds the fee through its delegate, like a fork of the service
+/// supporting the client's `DelegateFunding`.
+fn process_service_request(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.get(..8) != Some(&SimpleRandomnessV1Request::DISCRIMINATOR[..]) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (num_bytes, callback, options) =
+        <(u8, Callback, Option<TransactionOptions>)>::deserialize(&mut &data[8..])
+            .map_err(|e| ProgramError::BorshIoError(e.to_string()))?;
+    if num_bytes == 0 || num_bytes > MAX_NUM_BYTES {
+        return Err(ServiceError::InvalidNumberOfBytes.into());
+    }
+
+    let [request, escrow, state, mint, payer, _, _, _, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !request.is_signer || !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *escrow.key != derive_escrow_pubkey(request.key, &NATIVE_MINT) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *state.key != RANDOMNESS_SERVICE_STATE || *mint.key != NATIVE_MINT {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    with_invocation(|invocation| {
+        invocation.requests.push(ServiceRequest {
+            request: *request.key,
+            payer: *payer.key,
+            num_bytes,
+            callback,
+            options,
+            funding_source: remaining.first().map(|source| *source.key),
+        })
+    });
+    Ok(())
+}
+
+/// The stand-in for the SPL token program, handling the `Approve` and `Revoke` instructions
+/// sent around a request funded through a delegate.
+fn process_token_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (source, delegate, owner) = match (data.first().copied(), accounts) {
+        (Some(TOKEN_APPROVE), [source, delegate, owner, ..]) => (source, Some(delegate), owner),
+        (Some(TOKEN_REVOKE), [source, owner, ..]) => (source, None, owner),
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    if *source.owner != TOKEN_PROGRAM_ID || !source.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let mut state = TokenAccountState::unpack(&source.try_borrow_data()?)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if state.owner != *owner.key || !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    state.delegate = delegate.map(|delegate| *delegate.key);
+    state.delegated_amount = match delegate {
+        Some(_) => data
+            .get(1..9)
+            .and_then(|amount| amount.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidInstructionData)?,
+        None => 0,
+    };
+    source.try_borrow_mut_data()?.copy_from_slice(&state.pack());
+    Ok(())
+}
+
+/// The stand-in for the system program, handling the `Transfer` instruction, such as a
+/// referral fee paid around a request.
+fn process_system_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [from, to, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if data.get(..4) != Some(&SYSTEM_TRANSFER.to_le_bytes()[..]) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let lamports = data
+        .get(4..12)
+        .and_then(|amount| amount.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from.owner != solana_program::system_program::ID || !from.data_is_empty() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !from.is_writable || !to.is_writable {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if from.lamports() < lamports {
+        // The system program's `ResultWithNegativeLamports`
+        return Err(ProgramError::Custom(1));
+    }
+
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+/// A packed SPL token mint for wrapped SOL: no authorities, no supply, and nine decimals.
+fn native_mint_data() -> Vec<u8> {
+    let mut data = vec![0; 82];
+    data[44] = 9;
+    data[45] = 1;
+    data
+}
+
+/// The system program `Transfer` instruction.
+const SYSTEM_TRANSFER: u32 = 2;
+/// The SPL token `Approve` instruction.
+const TOKEN_APPROVE: u8 = 4;
+/// The SPL token `Revoke` instruction.
+const TOKEN_REVOKE: u8 = 5;
+
+/// The rent-exempt minimum of an SPL token account.
+fn token_account_rent() -> u64 {
+    Rent::default().minimum_balance(ESCROW_ACCOUNT_SIZE)
+}
+
+/// The wrapped SOL held by an escrow token account, above its rent.
+fn escrow_amount(escrow: &HarnessAccount) -> u64 {
+    escrow
+        .lamports
+        .saturating_sub(Rent::default().minimum_balance(escrow.data.len()))
+}
+
+fn executable_account() -> HarnessAccount {
+    HarnessAccount {
+        lamports: 1,
+        data: vec![],
+        owner: solana_program::bpf_loader_upgradeable::ID,
+        executable: true,
+    }
+}
+
+/// The state of the invocation in progress on this thread, read by the syscall stubs.
+struct Invocation {
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    /// The program ids of the invocation stack, innermost last.
+    callers: Vec<Pubkey>,
+    /// The number of instructions above the outermost caller: one for a callback, which runs
+    /// under the service's settle instruction, and none for a transaction's own instruction.
+    base_height: usize,
+    clock: Clock,
+    logs: Vec<String>,
+    requests: Vec<ServiceRequest>,
+}
+
+thread_local! {
+    static INVOCATION: RefCell<Option<Invocation>> = const { RefCell::new(None) };
+}
+
+/// Routes the syscalls made by programs running in the harness to the invocation in progress
+/// on the calling thread, so tests can run in parallel.
+struct HarnessStubs;
+
+impl SyscallStubs for HarnessStubs {
+    fn sol_log(&self, message: &str) {
+        let captured = INVOCATION.with(|invocation| match invocation.borrow_mut().as_mut() {
+            Some(invocation) => {
+                invocation.logs.push(message.to_string());
+                true
+            }
+            None => false,
+        });
+        if !captured {
+            println!("{}", message);
+        }
+    }
+
+    fn sol_log_data(&self, data: &[&[u8]]) {
+        // Logged by the runtime as one base64 encoded field after another
+        let fields: Vec<String> = data
+            .iter()
+            .map(|field| base64::engine::general_purpose::STANDARD.encode(field))
+            .collect();
+        self.sol_log(&format!("Program data: {}", fields.join(" ")));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let (processor, caller) = INVOCATION
+            .with(|invocation| {
+                let invocation = invocation.borrow();
+                let invocation = invocation.as_ref()?;
+                Some((
+                    invocation.programs.get(&instruction.program_id).copied(),
+                    *invocation.callers.last()?,
+                ))
+            })
+            .ok_or(ProgramError::InvalidArgument)?;
+        let processor = processor.ok_or(ProgramError::IncorrectProgramId)?;
+
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for meta in instruction.accounts.iter() {
+            let mut account = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?
+                .clone();
+            if meta.is_signer && !account.is_signer && !signers.contains(account.key) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            account.is_signer = meta.is_signer;
+            account.is_writable = meta.is_writable;
+            accounts.push(account);
+        }
+
+        with_invocation(|invocation| invocation.callers.push(instruction.program_id));
+        let result = processor(&instruction.program_id, &accounts, &instruction.data);
+        with_invocation(|invocation| {
+            invocation.callers.pop();
+        });
+
+        result
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        INVOCATION.with(|invocation| {
+            invocation.borrow().as_ref().map_or(0, |invocation| {
+                (invocation.callers.len() + invocation.base_height) as u64
+            })
+        })
+    }
+
+    #[allow(unsafe_code)]
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = INVOCATION.with(|invocation| {
+            invocation
+                .borrow()
+                .as_ref()
+                .map(|invocation| invocation.clock.clone())
+        });
+        match clock {
+            Some(clock) => {
+                // Safety: `Clock::get` passes a pointer to an aligned, initialized `Clock`.
+                unsafe { var_addr.cast::<Clock>().write(clock) };
+                SUCCESS
+            }
+            None => solana_program::program_error::UNSUPPORTED_SYSVAR,
+        }
+    }
+
+    /// The harness charges rent at [`Rent::default`] everywhere, so programs see the same.
+    #[allow(unsafe_code)]
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // Safety: `Rent::get` passes a pointer to an aligned, initialized `Rent`.
+        unsafe { var_addr.cast::<Rent>().write(Rent::default()) };
+        SUCCESS
+    }
+}
+
+fn with_invocation(f: impl FnOnce(&mut Invocation)) {
+    INVOCATION.with(|invocation| {
+        if let Some(invocation) = invocation.borrow_mut().as_mut() {
+            f(invocation);
+        }
+    });
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(HarnessStubs));
+    });
+}
@@ -0,0 +1,54 @@
+use crate::*;
+
+/// The fixed-size fields at the start of a [`SimpleRandomnessV1Account`], read in place at fixed
+/// offsets without deserializing the callback or error message.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequestHeader {
+    pub is_completed: u8,
+    pub num_bytes: u8,
+    pub user: Pubkey,
+    pub escrow: Pubkey,
+    pub request_slot: u64,
+}
+
+impl RequestHeader {
+    /// The byte offsets of the header fields in the account data, after the discriminator.
+    pub const IS_COMPLETED_OFFSET: usize = 8;
+    pub const NUM_BYTES_OFFSET: usize = Self::IS_COMPLETED_OFFSET + 1;
+    pub const USER_OFFSET: usize = Self::NUM_BYTES_OFFSET + 1;
+    pub const ESCROW_OFFSET: usize = Self::USER_OFFSET + 32;
+    pub const REQUEST_SLOT_OFFSET: usize = Self::ESCROW_OFFSET + 32;
+
+    /// The number of bytes of account data the header spans, including the discriminator.
+    pub const LEN: usize = Self::REQUEST_SLOT_OFFSET + 8;
+
+    /// Reads the header from request account data, checking the discriminator.
+    pub fn read(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.get(..8) != Some(&SimpleRandomnessV1Account::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        Ok(Self {
+            is_completed: data[Self::IS_COMPLETED_OFFSET],
+            num_bytes: data[Self::NUM_BYTES_OFFSET],
+            user: Pubkey::new_from_array(read_array(data, Self::USER_OFFSET)?),
+            escrow: Pubkey::new_from_array(read_array(data, Self::ESCROW_OFFSET)?),
+            request_slot: u64::from_le_bytes(read_array(data, Self::REQUEST_SLOT_OFFSET)?),
+        })
+    }
+
+    /// Whether the oracle has marked the request as failed.
+    pub fn is_completed(&self) -> bool {
+        self.is_completed != 0
+    }
+}
+
+/// Copies the `N` bytes at `offset` out of the account data.
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], ProgramError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ProgramError::AccountDataTooSmall)
+}
@@ -0,0 +1,554 @@
+//! Compares the instruction wrappers, account layouts, and error codes hardcoded in this crate
+//! against the randomness service's Anchor IDL.
+//!
+//! Every wrapper this crate exposes is listed in [`instruction_layouts`] and every Borsh type in
+//! [`type_layouts`], so [`check_all`] covers a new wrapper as soon as it is registered there.
+//! The instruction account flags are taken from the metas the wrapper actually builds rather
+//! than restated by hand.
+
+use crate::*;
+use serde::Deserialize;
+use solana_program::hash::hash;
+
+/// The subset of an Anchor IDL (the pre 0.30 format the service is published with) needed to
+/// check the wrappers.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Idl {
+    pub version: String,
+    pub name: String,
+    #[serde(default)]
+    pub instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    pub accounts: Vec<IdlTypeDef>,
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
+    #[serde(default)]
+    pub errors: Vec<IdlErrorCode>,
+}
+
+impl Idl {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn instruction(&self, name: &str) -> Option<&IdlInstruction> {
+        self.instructions.iter().find(|ix| ix.name == name)
+    }
+
+    pub fn account(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.accounts.iter().find(|account| account.name == name)
+    }
+
+    pub fn type_def(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.types.iter().find(|ty| ty.name == name)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdlInstruction {
+    pub name: String,
+    pub accounts: Vec<IdlAccount>,
+    pub args: Vec<IdlField>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlAccount {
+    pub name: String,
+    pub is_mut: bool,
+    pub is_signer: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlType,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdlTypeDefTy {
+    pub kind: String,
+    #[serde(default)]
+    pub fields: Vec<IdlField>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct IdlErrorCode {
+    pub code: u32,
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+/// A field or argument type as written in the IDL.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdlType {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    U128,
+    I128,
+    Bytes,
+    String,
+    PublicKey,
+    Defined(String),
+    Option(Box<IdlType>),
+    Vec(Box<IdlType>),
+    Array(Box<IdlType>, usize),
+}
+
+impl IdlType {
+    pub fn defined(name: &str) -> Self {
+        IdlType::Defined(name.to_string())
+    }
+
+    pub fn option(ty: IdlType) -> Self {
+        IdlType::Option(Box::new(ty))
+    }
+
+    pub fn vec(ty: IdlType) -> Self {
+        IdlType::Vec(Box::new(ty))
+    }
+
+    pub fn array(ty: IdlType, len: usize) -> Self {
+        IdlType::Array(Box::new(ty), len)
+    }
+}
+
+impl std::fmt::Display for IdlType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdlType::Bool => write!(f, "bool"),
+            IdlType::U8 => write!(f, "u8"),
+            IdlType::I8 => write!(f, "i8"),
+            IdlType::U16 => write!(f, "u16"),
+            IdlType::I16 => write!(f, "i16"),
+            IdlType::U32 => write!(f, "u32"),
+            IdlType::I32 => write!(f, "i32"),
+            IdlType::U64 => write!(f, "u64"),
+            IdlType::I64 => write!(f, "i64"),
+            IdlType::U128 => write!(f, "u128"),
+            IdlType::I128 => write!(f, "i128"),
+            IdlType::Bytes => write!(f, "Vec<u8>"),
+            IdlType::String => write!(f, "String"),
+            IdlType::PublicKey => write!(f, "Pubkey"),
+            IdlType::Defined(name) => write!(f, "{}", name),
+            IdlType::Option(ty) => write!(f, "Option<{}>", ty),
+            IdlType::Vec(ty) => write!(f, "Vec<{}>", ty),
+            IdlType::Array(ty, len) => write!(f, "[{}; {}]", ty, len),
+        }
+    }
+}
+
+/// An instruction wrapper as this crate builds it.
+#[derive(Clone, Debug)]
+pub struct InstructionLayout {
+    /// The snake case instruction name, as hashed into the discriminator.
+    pub name: &'static str,
+    pub discriminator: [u8; 8],
+    pub accounts: Vec<AccountLayout>,
+    pub args: Vec<FieldLayout>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountLayout {
+    pub name: &'static str,
+    pub is_mut: bool,
+    pub is_signer: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub ty: IdlType,
+}
+
+impl FieldLayout {
+    pub fn new(name: &'static str, ty: IdlType) -> Self {
+        Self { name, ty }
+    }
+}
+
+/// A Borsh type as this crate declares it. Accounts carry their 8 byte discriminator.
+#[derive(Clone, Debug)]
+pub struct TypeLayout {
+    pub name: &'static str,
+    pub discriminator: Option<[u8; 8]>,
+    pub fields: Vec<FieldLayout>,
+}
+
+/// A difference between the IDL and this crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The instruction, type, or error the difference was found in.
+    pub item: String,
+    pub detail: String,
+}
+
+impl Mismatch {
+    fn new(item: &str, detail: String) -> Self {
+        Self {
+            item: item.to_string(),
+            detail,
+        }
+    }
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.item, self.detail)
+    }
+}
+
+/// The instruction wrappers exposed by this crate. Register new wrappers here so
+/// [`check_all`] covers them.
+pub fn instruction_layouts() -> Vec<InstructionLayout> {
+    vec![simple_randomness_v1_layout()]
+}
+
+/// The Borsh types and accounts declared by this crate.
+pub fn type_layouts() -> Vec<TypeLayout> {
+    vec![
+        TypeLayout {
+            name: "SimpleRandomnessV1Account",
+            discriminator: Some(SimpleRandomnessV1Account::DISCRIMINATOR),
+            fields: vec![
+                FieldLayout::new("is_completed", IdlType::U8),
+                FieldLayout::new("num_bytes", IdlType::U8),
+                FieldLayout::new("user", IdlType::PublicKey),
+                FieldLayout::new("escrow", IdlType::PublicKey),
+                FieldLayout::new("request_slot", IdlType::U64),
+                FieldLayout::new("callback", IdlType::defined("Callback")),
+                FieldLayout::new("compute_units", IdlType::U32),
+                FieldLayout::new("priority_fee_micro_lamports", IdlType::U64),
+                FieldLayout::new("error_message", IdlType::String),
+            ],
+        },
+        TypeLayout {
+            name: "State",
+            discriminator: Some(ServiceState::DISCRIMINATOR),
+            fields: vec![
+                FieldLayout::new("bump", IdlType::U8),
+                FieldLayout::new("authority", IdlType::PublicKey),
+                FieldLayout::new("mint", IdlType::PublicKey),
+                FieldLayout::new("switchboard_service", IdlType::PublicKey),
+                FieldLayout::new("wallet", IdlType::PublicKey),
+                FieldLayout::new("cost_per_byte", IdlType::U64),
+                FieldLayout::new("last_updated", IdlType::I64),
+                FieldLayout::new("ebuf", IdlType::array(IdlType::U8, 512)),
+            ],
+        },
+        TypeLayout {
+            name: "Callback",
+            discriminator: None,
+            fields: vec![
+                FieldLayout::new("program_id", IdlType::PublicKey),
+                FieldLayout::new(
+                    "accounts",
+                    IdlType::vec(IdlType::defined("AccountMetaBorsh")),
+                ),
+                FieldLayout::new("ix_data", IdlType::Bytes),
+            ],
+        },
+        TypeLayout {
+            name: "AccountMetaBorsh",
+            discriminator: None,
+            fields: vec![
+                FieldLayout::new("pubkey", IdlType::PublicKey),
+                FieldLayout::new("is_signer", IdlType::Bool),
+                FieldLayout::new("is_writable", IdlType::Bool),
+            ],
+        },
+        TypeLayout {
+            name: "TransactionOptions",
+            discriminator: None,
+            fields: vec![
+                FieldLayout::new("compute_units", IdlType::option(IdlType::U32)),
+                FieldLayout::new("compute_unit_price", IdlType::option(IdlType::U64)),
+            ],
+        },
+    ]
+}
+
+fn simple_randomness_v1_layout() -> InstructionLayout {
+    const NAMES: [&str; 8] = [
+        "request",
+        "escrow",
+        "state",
+        "mint",
+        "payer",
+        "system_program",
+        "token_program",
+        "associated_token_program",
+    ];
+
+    let key = Pubkey::default();
+    let mut lamports = [0u64; NAMES.len()];
+    let mut data = [[0u8; 0]; NAMES.len()];
+    let mut infos = lamports
+        .iter_mut()
+        .zip(data.iter_mut())
+        .map(|(lamports, data)| {
+            AccountInfo::new(&key, false, false, lamports, data, &key, false, 0)
+        });
+    let mut next = || infos.next().expect("one account info per name");
+    let request = SimpleRandomnessV1Request {
+        request: next(),
+        escrow: next(),
+        state: next(),
+        mint: next(),
+        payer: next(),
+        system_program: next(),
+        token_program: next(),
+        associated_token_program: next(),
+    };
+    let ix = request
+        .instruction(ID, 1, &Callback::default(), None)
+        .expect("default callback serializes");
+
+    InstructionLayout {
+        name: "simple_randomness_v1",
+        discriminator: SimpleRandomnessV1Request::DISCRIMINATOR,
+        accounts: NAMES
+            .iter()
+            .zip(ix.accounts.iter())
+            .map(|(&name, meta)| AccountLayout {
+                name,
+                is_mut: meta.is_writable,
+                is_signer: meta.is_signer,
+            })
+            .collect(),
+        args: vec![
+            FieldLayout::new("num_bytes", IdlType::U8),
+            FieldLayout::new("callback", IdlType::defined("Callback")),
+            FieldLayout::new(
+                "options",
+                IdlType::option(IdlType::defined("TransactionOptions")),
+            ),
+        ],
+    }
+}
+
+/// The camel case name Anchor writes to the IDL for a snake case Rust name.
+fn idl_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.trim_start_matches('_').chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// The 8 byte discriminator Anchor prefixes to account data, the first 8 bytes of
+/// `sha256("account:<Name>")`.
+fn account_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("account:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+fn check_fields(
+    item: &str,
+    kind: &str,
+    expected: &[FieldLayout],
+    actual: &[IdlField],
+) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    if expected.len() != actual.len() {
+        mismatches.push(Mismatch::new(
+            item,
+            format!(
+                "{} {}s in this crate, {} in the IDL",
+                expected.len(),
+                kind,
+                actual.len()
+            ),
+        ));
+    }
+    for (i, (expected, actual)) in expected.iter().zip(actual.iter()).enumerate() {
+        if idl_name(expected.name) != actual.name {
+            mismatches.push(Mismatch::new(
+                item,
+                format!(
+                    "{} {} is `{}` in this crate, `{}` in the IDL",
+                    kind, i, expected.name, actual.name
+                ),
+            ));
+        }
+        if expected.ty != actual.ty {
+            mismatches.push(Mismatch::new(
+                item,
+                format!(
+                    "{} `{}` is `{}` in this crate, `{}` in the IDL",
+                    kind, expected.name, expected.ty, actual.ty
+                ),
+            ));
+        }
+    }
+    mismatches
+}
+
+/// Checks the discriminator, account order and flags, and argument types of an instruction
+/// wrapper.
+pub fn check_instruction(idl: &Idl, layout: &InstructionLayout) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    if layout.discriminator != anchor_sighash(layout.name) {
+        mismatches.push(Mismatch::new(
+            layout.name,
+            "discriminator does not match the instruction name".to_string(),
+        ));
+    }
+    let Some(ix) = idl.instruction(&idl_name(layout.name)) else {
+        mismatches.push(Mismatch::new(
+            layout.name,
+            "missing from the IDL".to_string(),
+        ));
+        return mismatches;
+    };
+
+    if layout.accounts.len() != ix.accounts.len() {
+        mismatches.push(Mismatch::new(
+            layout.name,
+            format!(
+                "{} accounts in this crate, {} in the IDL",
+                layout.accounts.len(),
+                ix.accounts.len()
+            ),
+        ));
+    }
+    for (i, (expected, actual)) in layout.accounts.iter().zip(ix.accounts.iter()).enumerate() {
+        if idl_name(expected.name) != actual.name {
+            mismatches.push(Mismatch::new(
+                layout.name,
+                format!(
+                    "account {} is `{}` in this crate, `{}` in the IDL",
+                    i, expected.name, actual.name
+                ),
+            ));
+        }
+        if expected.is_mut != actual.is_mut || expected.is_signer != actual.is_signer {
+            mismatches.push(Mismatch::new(
+                layout.name,
+                format!(
+                    "account `{}` is mut: {}, signer: {} in this crate, mut: {}, signer: {} in the IDL",
+                    expected.name, expected.is_mut, expected.is_signer, actual.is_mut, actual.is_signer
+                ),
+            ));
+        }
+    }
+
+    mismatches.extend(check_fields(layout.name, "arg", &layout.args, &ix.args));
+    mismatches
+}
+
+/// Checks the field layout of a type, and the discriminator of an account.
+pub fn check_type(idl: &Idl, layout: &TypeLayout) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    let def = match layout.discriminator {
+        Some(discriminator) => {
+            if discriminator != account_discriminator(layout.name) {
+                mismatches.push(Mismatch::new(
+                    layout.name,
+                    "discriminator does not match the account name".to_string(),
+                ));
+            }
+            idl.account(layout.name)
+        }
+        None => idl.type_def(layout.name),
+    };
+    let Some(def) = def else {
+        mismatches.push(Mismatch::new(
+            layout.name,
+            "missing from the IDL".to_string(),
+        ));
+        return mismatches;
+    };
+    if def.ty.kind != "struct" {
+        mismatches.push(Mismatch::new(
+            layout.name,
+            format!("is a {} in the IDL", def.ty.kind),
+        ));
+    }
+    mismatches.extend(check_fields(
+        layout.name,
+        "field",
+        &layout.fields,
+        &def.ty.fields,
+    ));
+    mismatches
+}
+
+/// Checks that every IDL error code maps to the [`ServiceError`] with the same name and
+/// message, and that no [`ServiceError`] is missing from the IDL.
+pub fn check_errors(idl: &Idl) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    for error in idl.errors.iter() {
+        let Some(mirrored) = ServiceError::from_code(error.code) else {
+            mismatches.push(Mismatch::new(
+                &error.name,
+                format!("code {} is not mirrored by ServiceError", error.code),
+            ));
+            continue;
+        };
+        if format!("{:?}", mirrored) != error.name {
+            mismatches.push(Mismatch::new(
+                &error.name,
+                format!(
+                    "code {} is ServiceError::{:?} in this crate",
+                    error.code, mirrored
+                ),
+            ));
+        }
+        if error.msg.as_deref() != Some(mirrored.description()) {
+            mismatches.push(Mismatch::new(
+                &error.name,
+                format!("message is `{}` in this crate", mirrored.description()),
+            ));
+        }
+    }
+    let mut code = ServiceError::CODE_OFFSET;
+    while let Some(mirrored) = ServiceError::from_code(code) {
+        if !idl.errors.iter().any(|error| error.code == code) {
+            mismatches.push(Mismatch::new(
+                &format!("{:?}", mirrored),
+                format!("code {} is missing from the IDL", code),
+            ));
+        }
+        code += 1;
+    }
+    mismatches
+}
+
+/// Runs every check against the IDL.
+pub fn check_all(idl: &Idl) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    for layout in instruction_layouts() {
+        mismatches.extend(check_instruction(idl, &layout));
+    }
+    for layout in type_layouts() {
+        mismatches.extend(check_type(idl, &layout));
+    }
+    mismatches.extend(check_errors(idl));
+    mismatches
+}
@@ -0,0 +1,101 @@
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// A tag and an index carried in a callback's instruction data, so one callback instruction
+/// can route each request's result to one of several accounts, e.g. the bracket PDA at
+/// `index` of the tournament `tag`, chosen when the request is made.
+///
+/// Written right after the discriminator as the Borsh `(u64, u16)`, so an Anchor callback
+/// takes them as its first arguments, before the result the service appends:
+///
+/// ```ignore
+/// pub fn consume_bracket_randomness(
+///     ctx: Context<ConsumeBracketRandomness>,
+///     tag: u64,
+///     index: u16,
+///     result: Vec<u8>,
+/// ) -> Result<()>
+/// ```
+///
+/// The index is only as trustworthy as the request that set it. A callback must check it
+/// against its own bounds and derive the target account from it rather than trust the
+/// account it was passed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct IndexedTag {
+    pub tag: u64,
+    pub index: u16,
+}
+
+impl IndexedTag {
+    /// The encoded length, without the discriminator before it.
+    pub const LEN: usize = 8 + 2;
+
+    pub fn new(tag: u64, index: u16) -> Self {
+        Self { tag, index }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut bytes = [0; Self::LEN];
+        bytes[..8].copy_from_slice(&self.tag.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.index.to_le_bytes());
+        bytes
+    }
+
+    /// Reads the tag after the discriminator of instruction data: a callback's stored
+    /// `ix_data`, or the data a native callback is invoked with. Returns `None` if the data is
+    /// too short to hold both. Any data that long decodes, so only call it for callbacks built
+    /// with a tag.
+    pub fn from_ix_data(data: &[u8]) -> Option<Self> {
+        let (_, args) = ix_data_parts(data)?;
+        let bytes = args.get(..Self::LEN)?;
+        Self::try_from_slice(bytes).ok()
+    }
+}
+
+impl Callback {
+    /// Inserts `tag` and `index` right after the discriminator, before any arguments already
+    /// in `ix_data`. Fails with [`LiteError::CallbackDiscriminatorMissing`] if `ix_data` has
+    /// no discriminator yet.
+    pub fn with_indexed_tag(mut self, tag: u64, index: u16) -> Result<Self, ProgramError> {
+        let (discriminator, args) =
+            ix_data_parts(&self.ix_data).ok_or(LiteError::CallbackDiscriminatorMissing)?;
+        self.ix_data = [
+            discriminator.as_slice(),
+            &IndexedTag::new(tag, index).to_bytes(),
+            args,
+        ]
+        .concat();
+        Ok(self)
+    }
+
+    /// The tag written by [`with_indexed_tag`](Self::with_indexed_tag). See
+    /// [`IndexedTag::from_ix_data`].
+    pub fn indexed_tag(&self) -> Option<IndexedTag> {
+        IndexedTag::from_ix_data(&self.ix_data)
+    }
+}
+
+/// Checks the tag a callback was invoked with is the one the callback expects, e.g. the id of
+/// the account it writes, so a request tagged for one account cannot settle another. Fails with
+/// [`LiteError::TagMismatch`], logging both tags, if the tag or the index differ.
+///
+/// ```ignore
+/// pub fn consume(ctx: Context<Consume>, tag: u64, index: u16, result: Vec<u8>) -> Result<()> {
+///     let expected = IndexedTag::new(ctx.accounts.proposal.id, 0);
+///     verify_tag_matches(IndexedTag::new(tag, index), expected)?;
+///     ...
+/// }
+/// ```
+pub fn verify_tag_matches(tag: IndexedTag, expected: IndexedTag) -> Result<(), ProgramError> {
+    if tag != expected {
+        solana_program::msg!(
+            "Expected the tag {} at index {}, received {} at index {}",
+            expected.tag,
+            expected.index,
+            tag.tag,
+            tag.index
+        );
+        return Err(LiteError::TagMismatch.into());
+    }
+    Ok(())
+}
@@ -1,4 +1,8 @@
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
+// The harness stubs the clock syscall, which writes through a raw pointer. That is the only
+// unsafe code in the crate and it is confined to the test-only `harness` feature.
+#![cfg_attr(not(feature = "harness"), forbid(unsafe_code))]
+#![cfg_attr(feature = "harness", deny(unsafe_code))]
 
 //!  The Solana Randomness Service uses a Switchboard SGX enabled oracle to provide randomness to any Solana program using a callback instruction.
 //!
@@ -8,9 +12,14 @@
 //!
 //!  # Example Program
 //!
-//! ```
+//! With the `anchor` feature the service's accounts implement Anchor's account traits, and
+//! [`anchor::SimpleRandomnessV1`] and [`anchor::ConsumeRandomnessAccounts`] bundle the accounts
+//! of a request and of its callback, so the example only depends on this crate.
+//!
+//! ```ignore
 //! use anchor_lang::prelude::*;
-//! use solana_randomness_service_lite::{SimpleRandomnessV1Request, ID as SolanaRandomnessServiceID};
+//! use solana_randomness_service_lite::anchor::*;
+//! use solana_randomness_service_lite::{Callback, TransactionOptions};
 //!
 //! #[program]
 //! pub mod solana_randomness_consumer {
@@ -19,28 +28,18 @@
 //!     pub fn request_randomness(ctx: Context<RequestRandomness>) -> anchor_lang::prelude::Result<()> {
 //!         msg!("Requesting randomness...");
 //!
-//!         let request = SimpleRandomnessV1Request {
-//!             request: ctx.accounts.randomness_request.to_account_info(),
-//!             escrow: ctx.accounts.randomness_escrow.to_account_info(),
-//!             state: ctx.accounts.randomness_state.to_account_info(),
-//!             mint: ctx.accounts.randomness_mint.to_account_info(),
-//!             payer: ctx.accounts.payer.to_account_info(),
-//!             system_program: ctx.accounts.system_program.to_account_info(),
-//!             token_program: ctx.accounts.token_program.to_account_info(),
-//!             associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
-//!         };
-//!         request.invoke(
-//!             ctx.accounts.randomness_service.to_account_info(),
+//!         let randomness = &ctx.accounts.randomness;
+//!         randomness.cpi(
 //!             8, // Request 8 bytes of randomness
-//!             &solana_randomness_service_lite::Callback::new(
+//!             &Callback::new(
 //!                 ID,
 //!                 vec![
-//!                     AccountMeta::new_readonly(ctx.accounts.randomness_state.key(), true).into(),
-//!                     AccountMeta::new_readonly(ctx.accounts.randomness_request.key(), false).into(),
+//!                     AccountMeta::new_readonly(randomness.state.key(), true).into(),
+//!                     AccountMeta::new_readonly(randomness.request.key(), false).into(),
 //!                 ],
 //!                 [190, 217, 49, 162, 99, 26, 73, 234].to_vec(), // Our callback ixn discriminator. The oracle will append the randomness bytes to the end
 //!             ),
-//!             &Some(solana_randomness_service_lite::TransactionOptions {
+//!             Some(&TransactionOptions {
 //!                 compute_units: Some(1_000_000),
 //!                 compute_unit_price: Some(100),
 //!             }),
@@ -50,75 +49,134 @@
 //!
 //!         Ok(())
 //!     }
-//! }
-//!
-//! #[derive(Accounts)]
-//! pub struct RequestRandomness<'info> {
-//!     /// CHECK: manually check programID and executable status
-//!     #[account(
-//!         constraint = randomness_service.key() == SolanaRandomnessServiceID,
-//!         constraint = randomness_service.executable,
-//!     )]
-//!     pub randomness_service: AccountInfo<'info>,
-//!
-//!     /// The account that will be created on-chain to hold the randomness request.
-//!     /// Used by the off-chain oracle to pickup the request and fulfill it.
-//!     /// CHECK: todo
-//!     #[account(
-//!         mut,
-//!         signer,
-//!         owner = system_program.key(),
-//!         constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
-//!     )]
-//!     pub randomness_request: AccountInfo<'info>,
-//!
-//!     /// The TokenAccount that will store the funds for the randomness request.
-//!     /// CHECK: todo
-//!     #[account(
-//!         mut,
-//!         owner = system_program.key(),
-//!         constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
-//!     )]
-//!     pub randomness_escrow: AccountInfo<'info>,
 //!
-//!     /// The randomness service's state account. Responsible for storing the
-//!     /// reward escrow and the cost per random byte.
-//!     #[account(
-//!         seeds = [b"STATE"],
-//!         bump = randomness_state.bump,
-//!         seeds::program = randomness_service.key(),
-//!     )]
-//!     pub randomness_state: Box<Account<'info, solana_randomness_service::State>>,
+//!     pub fn consume_randomness(ctx: Context<ConsumeRandomness>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+//!         ctx.accounts.randomness.verify_program(ctx.program_id)?;
 //!
-//!     /// The token mint to use for paying for randomness requests.
-//!     #[account(address = NativeMint::ID)]
-//!     pub randomness_mint: Account<'info, Mint>,
+//!         // Read the 8 bytes we requested without bias, rather than `result[0] % 2`
+//!         let heads = solana_randomness_service_lite::coin_flip(&result)?;
+//!         msg!("Flipped {}", if heads { "heads" } else { "tails" });
 //!
-//!     /// The account that will pay for the randomness request.
-//!     #[account(mut)]
-//!     pub payer: Signer<'info>,
-//!
-//!     /// The Solana System program. Used to allocate space on-chain for the randomness_request account.
-//!     pub system_program: Program<'info, System>,
+//!         Ok(())
+//!     }
+//! }
 //!
-//!     /// The Solana Token program. Used to transfer funds to the randomness escrow.
-//!     pub token_program: Program<'info, Token>,
+//! #[derive(Accounts)]
+//! pub struct RequestRandomness<'info> {
+//!     /// The service program, the new request and escrow, the service state and mint, the
+//!     /// payer, and the programs the service invokes.
+//!     pub randomness: SimpleRandomnessV1<'info>,
+//! }
 //!
-//!     /// The Solana Associated Token program. Used to create the TokenAccount for the randomness escrow.
-//!     pub associated_token_program: Program<'info, AssociatedToken>,
+//! #[derive(Accounts)]
+//! pub struct ConsumeRandomness<'info> {
+//!     /// The service's state, which must sign, and a request owned by the service, whose
+//!     /// callback `verify_program` checks invokes this program.
+//!     pub randomness: ConsumeRandomnessAccounts<'info>,
 //! }
 //! ```
 use borsh::{BorshDeserialize, BorshSerialize};
-pub use solana_program::account_info::AccountInfo;
-pub use solana_program::instruction::AccountMeta;
-pub use solana_program::program_error::ProgramError;
-pub use solana_program::pubkey::Pubkey;
 use solana_program::{declare_id, instruction::Instruction};
 use solana_program::{program::invoke, program::invoke_signed, pubkey};
 
+pub mod solana;
+pub use solana::*;
+
+pub mod prelude;
+
 pub mod types;
 pub use types::*;
 
+pub mod callback_builder;
+pub use callback_builder::*;
+
+pub mod indexed_tag;
+pub use indexed_tag::*;
+
+pub mod error;
+pub use error::*;
+
+pub mod version;
+pub use version::*;
+
+mod macros;
+pub use macros::*;
+
+pub mod preflight;
+pub use preflight::*;
+
+pub mod discriminator;
+pub use discriminator::*;
+
+pub mod reserved;
+pub use reserved::*;
+
+pub mod addresses;
+pub use addresses::*;
+
+pub mod header;
+pub use header::*;
+
+pub mod view;
+pub use view::*;
+
+pub mod account_refs;
+pub use account_refs::*;
+
+pub mod page;
+pub use page::*;
+
+pub mod feed;
+pub use feed::*;
+
+pub mod cache;
+pub use cache::*;
+
+pub mod event;
+pub use event::*;
+
+pub mod clock;
+pub use clock::*;
+
+pub mod retry;
+pub use retry::*;
+
+pub mod guard;
+pub use guard::*;
+
+pub mod verify;
+pub use verify::*;
+
+pub mod archive;
+pub use archive::*;
+
+pub mod token_account;
+pub use token_account::*;
+
+pub mod randomness;
+pub use randomness::*;
+
+pub mod decode;
+pub use decode::*;
+
+#[cfg(feature = "client")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "client")))]
+pub mod client;
+
+#[cfg(feature = "anchor")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "anchor")))]
+pub mod anchor;
+#[cfg(feature = "anchor")]
+pub use anchor::LiteAccount;
+
+#[cfg(feature = "harness")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "harness")))]
+pub mod harness;
+
+#[cfg(feature = "idl-check")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "idl-check")))]
+pub mod idl_check;
+
 declare_id!("RANDMo5gFnqnXJW5Z52KNmd24sAo95KAd5VbiCtq5Rh");
 
 pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("sbattyXrzedoNATfc4L31wC9Mhxsi1BmFhTiN8gDshx");
@@ -140,7 +198,7 @@ pub const DEVNET_SWITCHBOARD_FUNCTION: Pubkey =
 pub const DEVNET_SWITCHBOARD_SERVICE: Pubkey =
     pubkey!("2fpdEbugwThMjRQ728Ne4zwGsrjFcCtmYDnwGtzScfnL");
 
-///
+/// The accounts required to invoke the `simple_randomness_v1` instruction.
 pub struct SimpleRandomnessV1Request<'info> {
     pub request: AccountInfo<'info>,
     pub escrow: AccountInfo<'info>,
@@ -159,19 +217,23 @@ impl<'info> SimpleRandomnessV1Request<'info> {
         Self::DISCRIMINATOR
     }
 
-    pub fn get_instruction(
+    /// Builds the `simple_randomness_v1` instruction. `options` of `None` lets the oracle use
+    /// the default compute budget and priority fee.
+    ///
+    /// Fails if the callback fails [`Callback::check_limits`] or, with
+    /// [`LiteError::CallbackTooLargeForSettlement`], [`Callback::check_size`], so the request
+    /// fails before its fee is escrowed.
+    pub fn instruction(
         &self,
         program_id: Pubkey,
         num_bytes: u8,
         callback: &Callback,
-        options: &Option<TransactionOptions>,
+        options: Option<&TransactionOptions>,
     ) -> Result<Instruction, ProgramError> {
+        callback.check_limits()?;
+        callback.check_size(num_bytes)?;
         let accounts = self.to_account_metas();
-
-        let mut data: Vec<u8> = Self::discriminator().to_vec();
-        data.push(num_bytes);
-        data.append(&mut callback.to_vec()?);
-        data.append(&mut TransactionOptions::to_opt_vec(options)?);
+        let data = Self::data(num_bytes, callback, options)?;
 
         Ok(Instruction {
             program_id,
@@ -180,33 +242,139 @@ impl<'info> SimpleRandomnessV1Request<'info> {
         })
     }
 
-    pub fn invoke(
+    /// Same as [`instruction`](Self::instruction), but fails with
+    /// [`LiteError::InvalidTransactionOptions`] if the options fail
+    /// [`TransactionOptions::validate`], instead of having them clamped. `None`, and
+    /// options with both fields `None`, always pass.
+    pub fn instruction_strict(
+        &self,
+        program_id: Pubkey,
+        num_bytes: u8,
+        callback: &Callback,
+        options: Option<&TransactionOptions>,
+    ) -> Result<Instruction, ProgramError> {
+        if let Some(Err(error)) = options.map(TransactionOptions::validate) {
+            solana_program::msg!("{}", error);
+            return Err(error.into());
+        }
+        self.instruction(program_id, num_bytes, callback, options)
+    }
+
+    /// Serializes the `simple_randomness_v1` instruction data: the discriminator followed by the
+    /// Borsh encoded `num_bytes`, `callback`, and `options` arguments.
+    ///
+    /// The arguments are framed by their types, not by separators: the callback's accounts and
+    /// `ix_data` are prefixed with their `u32` lengths, so the options start right after the
+    /// last `ix_data` byte whatever that data holds. [`decode_any_request_instruction`] reads
+    /// them back the same way.
+    ///
+    /// Fails with [`LiteError::InvalidNumBytes`] if `num_bytes` is out of the service's range,
+    /// which the service would only reject once the request is sent.
+    pub fn data(
+        num_bytes: u8,
+        callback: &Callback,
+        options: Option<&TransactionOptions>,
+    ) -> Result<Vec<u8>, ProgramError> {
+        check_num_bytes(num_bytes)?;
+        let mut data: Vec<u8> = Self::discriminator().to_vec();
+        data.push(num_bytes);
+        data.append(&mut callback.to_vec()?);
+        // Borsh encodes an Option as a 0 or 1 tag followed by the value
+        match options {
+            None => data.push(0),
+            Some(options) => {
+                data.push(1);
+                data.append(&mut options.to_vec()?);
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Requests randomness with a CPI into the service program.
+    pub fn cpi(
         &self,
         program: AccountInfo<'info>,
         num_bytes: u8,
         callback: &Callback,
-        options: &Option<TransactionOptions>,
-    ) -> Result<(), solana_program::program_error::ProgramError> {
-        let instruction = self.get_instruction(*program.key, num_bytes, callback, options)?;
+        options: Option<&TransactionOptions>,
+    ) -> Result<(), ProgramError> {
+        let instruction = self.instruction(*program.key, num_bytes, callback, options)?;
         let account_infos = self.to_account_infos();
 
         invoke(&instruction, &account_infos[..])
     }
 
-    pub fn invoke_signed(
+    /// Same as [`cpi`](Self::cpi), signed with the given PDA seeds, e.g. for a PDA payer.
+    pub fn cpi_signed(
         &self,
         program: AccountInfo<'info>,
         num_bytes: u8,
         callback: &Callback,
-        options: &Option<TransactionOptions>,
+        options: Option<&TransactionOptions>,
         signer_seeds: &[&[&[u8]]],
-    ) -> Result<(), solana_program::program_error::ProgramError> {
-        let instruction = self.get_instruction(*program.key, num_bytes, callback, options)?;
+    ) -> Result<(), ProgramError> {
+        let instruction = self.instruction(*program.key, num_bytes, callback, options)?;
         let account_infos = self.to_account_infos();
 
         invoke_signed(&instruction, &account_infos[..], signer_seeds)
     }
 
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1Request::instruction`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+    ) -> Result<Instruction, ProgramError> {
+        self.instruction(program_id, num_bytes, callback, options.as_ref())
+    }
+
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1Request::data`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn instruction_data(
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+    ) -> Result<Vec<u8>, ProgramError> {
+        Self::data(num_bytes, callback, options.as_ref())
+    }
+
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1Request::cpi`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+    ) -> Result<(), ProgramError> {
+        self.cpi(program, num_bytes, callback, options.as_ref())
+    }
+
+    #[deprecated(
+        since = "1.1.0",
+        note = "use `SimpleRandomnessV1Request::cpi_signed`, which takes `Option<&TransactionOptions>`"
+    )]
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        num_bytes: u8,
+        callback: &Callback,
+        options: &Option<TransactionOptions>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<(), ProgramError> {
+        self.cpi_signed(program, num_bytes, callback, options.as_ref(), signer_seeds)
+    }
+
     fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
         vec![
             self.request.clone(),
@@ -221,20 +389,11 @@ impl<'info> SimpleRandomnessV1Request<'info> {
     }
 
     fn to_account_metas(&self) -> Vec<AccountMeta> {
-        vec![
-            AccountMeta::new(*self.request.key, true),
-            AccountMeta::new(*self.escrow.key, false),
-            AccountMeta::new_readonly(*self.state.key, false),
-            AccountMeta::new_readonly(*self.mint.key, false),
-            AccountMeta::new(*self.payer.key, true),
-            AccountMeta::new_readonly(*self.system_program.key, false),
-            AccountMeta::new_readonly(*self.token_program.key, false),
-            AccountMeta::new_readonly(*self.associated_token_program.key, false),
-        ]
+        SimpleRandomnessV1AccountRefs::from(self).to_account_metas()
     }
 }
 
-#[derive(Clone, Default, BorshDeserialize, BorshSerialize)]
+#[derive(Clone, Debug, Default, BorshDeserialize, BorshSerialize)]
 pub struct SimpleRandomnessV1Account {
     pub is_completed: u8,
     pub num_bytes: u8,
@@ -253,6 +412,30 @@ impl SimpleRandomnessV1Account {
         Self::DISCRIMINATOR
     }
 
+    /// The maximum length of the `error_message` the oracle can write to a failed request. The
+    /// service rejects longer messages with `ErrorMessageOverflow`.
+    pub const MAX_ERROR_MESSAGE_LEN: usize = 256;
+
+    /// The number of bytes the randomness service allocates for a request account with the given
+    /// callback. Mirrors the service program's own `SimpleRandomnessV1Account::space`.
+    ///
+    /// The account is allocated once when the request is created and is never reallocated.
+    /// The size is based on the in-memory size of the struct rather than its Borsh encoding and
+    /// reserves [`MAX_ERROR_MESSAGE_LEN`](Self::MAX_ERROR_MESSAGE_LEN) bytes for the error
+    /// message, so the account data is usually larger than the serialized request and the tail
+    /// is zero padded.
+    pub fn space(callback: &Callback) -> usize {
+        8 + std::mem::size_of::<SimpleRandomnessV1Account>()
+            + callback.ix_data.len()
+            + (std::mem::size_of::<AccountMetaBorsh>() * callback.accounts.len())
+            + Self::MAX_ERROR_MESSAGE_LEN
+    }
+
+    /// The size of the on-chain account this request was loaded from.
+    pub fn expected_request_account_size(&self) -> usize {
+        Self::space(&self.callback)
+    }
+
     pub fn owner() -> Pubkey {
         ID
     }
@@ -268,27 +451,76 @@ impl SimpleRandomnessV1Account {
         Self::try_deserialize_unchecked(buf)
     }
 
+    /// Deserializes the request, ignoring the zero padded tail of the account.
     pub fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
-        let mut data: &[u8] = &buf[8..];
-        Self::deserialize(&mut data).map_err(|_| ProgramError::InvalidAccountData)
+        let mut data: &[u8] = buf.get(8..).ok_or(ProgramError::InvalidAccountData)?;
+        let request = Self::deserialize(&mut data).map_err(|_| ProgramError::InvalidAccountData)?;
+        if request.error_message.len() > Self::MAX_ERROR_MESSAGE_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(request)
     }
 }
 
-#[derive(Clone, Default, BorshDeserialize, BorshSerialize)]
-pub struct State {
-    pub is_completed: u8,
-    pub num_bytes: u8,
-    pub user: Pubkey,
-    pub escrow: Pubkey,
-    pub request_slot: u64,
-    pub callback: Callback,
-    pub compute_units: u32,
-    pub priority_fee_micro_lamports: u64,
-    pub error_message: String,
+/// The randomness service's global state account, the `STATE` PDA of the program. Not to be
+/// confused with a request account, [`SimpleRandomnessV1Account`], or the state accounts of a
+/// consumer program.
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct ServiceState {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The program authority.
+    pub authority: Pubkey,
+    /// The token mint for the program reward.
+    pub mint: Pubkey,
+    /// The Switchboard Service responsible for responding to randomness requests.
+    pub switchboard_service: Pubkey,
+    /// Token wallet used for rewards.
+    pub wallet: Pubkey,
+    /// The cost for each randomness byte.
+    pub cost_per_byte: u64,
+    /// The unix timestamp when the cost per byte was last updated.
+    pub last_updated: i64,
+    /// Reserved for future use.
+    pub ebuf: [u8; 512],
+}
+
+impl Default for ServiceState {
+    fn default() -> Self {
+        Self {
+            bump: 0,
+            authority: Pubkey::default(),
+            mint: Pubkey::default(),
+            switchboard_service: Pubkey::default(),
+            wallet: Pubkey::default(),
+            cost_per_byte: 0,
+            last_updated: 0,
+            ebuf: [0; 512],
+        }
+    }
 }
-impl State {
+
+/// The previous name of [`ServiceState`].
+#[deprecated(since = "1.1.0", note = "renamed to `ServiceState`")]
+pub type State = ServiceState;
+
+impl ServiceState {
     pub const DISCRIMINATOR: [u8; 8] = [216, 146, 107, 94, 104, 75, 182, 177];
 
+    /// The byte offset of `mint` in the account data, after the discriminator and `bump` and
+    /// `authority`.
+    pub const MINT_OFFSET: usize = 8 + 1 + 32;
+
+    /// The byte offset of `wallet` in the account data, after `mint` and
+    /// `switchboard_service`.
+    pub const WALLET_OFFSET: usize = Self::MINT_OFFSET + 32 + 32;
+
+    /// The byte offset of `cost_per_byte` in the account data, after `wallet`.
+    pub const COST_PER_BYTE_OFFSET: usize = Self::WALLET_OFFSET + 32;
+
+    /// The size of the account data, discriminator included.
+    pub const LEN: usize = Self::COST_PER_BYTE_OFFSET + 8 + 8 + 512;
+
     pub fn discriminator() -> [u8; 8] {
         Self::DISCRIMINATOR
     }
@@ -305,11 +537,48 @@ impl State {
         if Self::DISCRIMINATOR != given_disc {
             return Err(ProgramError::InvalidAccountData);
         }
+        // The service allocates the whole state, like `read_field` requires
+        if buf.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
         Self::try_deserialize_unchecked(buf)
     }
 
     pub fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self, ProgramError> {
-        let mut data: &[u8] = &buf[8..];
+        let mut data: &[u8] = buf.get(8..).ok_or(ProgramError::InvalidAccountData)?;
         Self::deserialize(&mut data).map_err(|_| ProgramError::InvalidAccountData)
     }
+
+    /// Reads `mint` from the account data at its fixed offset, checking the discriminator,
+    /// without deserializing the rest of the state.
+    pub fn read_mint(data: &[u8]) -> Result<Pubkey, ProgramError> {
+        Self::read_field(data, Self::MINT_OFFSET).map(Pubkey::new_from_array)
+    }
+
+    /// Reads `wallet`, the token account the service's fees are paid to, like
+    /// [`read_mint`](Self::read_mint).
+    pub fn read_wallet(data: &[u8]) -> Result<Pubkey, ProgramError> {
+        Self::read_field(data, Self::WALLET_OFFSET).map(Pubkey::new_from_array)
+    }
+
+    /// Reads `cost_per_byte`, the lamports the service charges per requested byte, like
+    /// [`read_mint`](Self::read_mint).
+    pub fn read_cost_per_byte(data: &[u8]) -> Result<u64, ProgramError> {
+        Self::read_field(data, Self::COST_PER_BYTE_OFFSET).map(u64::from_le_bytes)
+    }
+
+    /// Reads the field at `offset`. The data must be at least [`LEN`](Self::LEN) long, even if
+    /// the field fits in less: the service allocates the whole state, so shorter data is not a
+    /// state account whatever its discriminator.
+    fn read_field<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], ProgramError> {
+        if data.get(..8) != Some(&Self::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&data[offset..offset + N]);
+        Ok(bytes)
+    }
 }
@@ -0,0 +1,156 @@
+use crate::*;
+use solana_program::msg;
+
+/// Logs the error and converts it to a [`ProgramError`]. Custom codes known to
+/// [`describe_error_code`] are logged with their description.
+pub fn log_and_return(error: impl Into<ProgramError>) -> ProgramError {
+    let error = error.into();
+    match error {
+        ProgramError::Custom(code) => match describe_error_code(code) {
+            Some(description) => msg!("Error {}: {}", code, description),
+            None => msg!("Error: {}", error),
+        },
+        _ => msg!("Error: {}", error),
+    }
+    error
+}
+
+/// Logs the error with [`log_and_return`] and returns it from the enclosing function, which must
+/// return a `Result<_, ProgramError>`.
+///
+/// ```ignore
+/// if data.len() < 8 {
+///     randomness_bail!(LiteError::CallbackDiscriminatorMissing);
+/// }
+/// ```
+#[macro_export]
+macro_rules! randomness_bail {
+    ($error:expr $(,)?) => {
+        return Err($crate::log_and_return($error).into())
+    };
+}
+
+/// Bails with the error unless the condition holds, like Anchor's `require!` for programs that
+/// do not use Anchor.
+///
+/// ```ignore
+/// require_randomness!(state.is_signer, LiteError::CallbackStateNotSigner);
+/// ```
+#[macro_export]
+macro_rules! require_randomness {
+    ($condition:expr, $error:expr $(,)?) => {
+        if !($condition) {
+            $crate::randomness_bail!($error);
+        }
+    };
+}
+
+/// Defines a consumer PDA from its seeds, so the request instruction, the callback, and the
+/// client derive it from one definition instead of three hand-written seed lists.
+///
+/// Constant seeds are expressions such as `b"receipt"` or a `&[u8]` constant; `name: Type`
+/// seeds become fields of the generated struct and must implement `AsRef<[u8]>`, like
+/// `Pubkey` or `[u8; 8]` from `to_le_bytes`. The struct also has a `bump` field, and:
+///
+/// - `derive(program_id, fields..) -> (Pubkey, u8)` finds the address and bump.
+/// - `find(program_id, fields..) -> (Pubkey, Self)` does the same, keeping the bump.
+/// - `seeds_with_bump(&self)` returns the seeds and bump for `invoke_signed`.
+/// - `address(&self, program_id)` recreates the address from the stored bump.
+/// - `SEEDS` spells the seeds the way Anchor's `seeds = [...]` constraint does, e.g.
+///   `[b"receipt", request.as_ref()]`, to compare against the program's accounts structs.
+///
+/// ```ignore
+/// define_pda!(ReceiptPda, [b"receipt", request: Pubkey]);
+///
+/// let (receipt, bump) = ReceiptPda::derive(&program_id, request);
+/// invoke_signed(&ix, &accounts, &[&ReceiptPda { request, bump }.seeds_with_bump()])?;
+/// ```
+#[macro_export]
+macro_rules! define_pda {
+    ($name:ident, [$($seeds:tt)*] $(,)?) => {
+        $crate::define_pda!(@munch $name [] [] [$($seeds)*]);
+    };
+
+    // A field seed
+    (@munch $name:ident [$($fields:tt)*] [$($parts:tt)*]
+        [$field:ident : $ty:ty $(, $($rest:tt)*)?]) => {
+        $crate::define_pda!(@munch $name [$($fields)* ($field: $ty)] [$($parts)* (field $field)]
+            [$($($rest)*)?]);
+    };
+    // A constant seed
+    (@munch $name:ident [$($fields:tt)*] [$($parts:tt)*] [$seed:expr $(, $($rest:tt)*)?]) => {
+        $crate::define_pda!(@munch $name [$($fields)*] [$($parts)* (const $seed)]
+            [$($($rest)*)?]);
+    };
+    (@munch $name:ident [$(($field:ident: $ty:ty))*] [$first:tt $($parts:tt)*] []) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $name {
+            $(pub $field: $ty,)*
+            pub bump: u8,
+        }
+
+        impl $name {
+            /// The seeds as an Anchor `seeds = [...]` constraint spells them.
+            pub const SEEDS: &'static str = concat!(
+                "[",
+                $crate::define_pda!(@display $first),
+                $(", ", $crate::define_pda!(@display $parts),)*
+                "]"
+            );
+
+            /// The address and bump of the PDA.
+            pub fn derive(
+                program_id: &$crate::Pubkey,
+                $($field: $ty,)*
+            ) -> ($crate::Pubkey, u8) {
+                $(let $field = &$field;)*
+                $crate::Pubkey::find_program_address(
+                    &[
+                        $crate::define_pda!(@seed $first),
+                        $($crate::define_pda!(@seed $parts),)*
+                    ],
+                    program_id,
+                )
+            }
+
+            /// The address of the PDA and the PDA with its bump.
+            pub fn find(program_id: &$crate::Pubkey, $($field: $ty,)*) -> ($crate::Pubkey, Self) {
+                let (address, bump) = Self::derive(program_id, $($field,)*);
+                (address, Self { $($field,)* bump })
+            }
+
+            /// The seeds followed by the bump, to sign for the PDA with `invoke_signed`.
+            pub fn seeds_with_bump(&self) -> Vec<&[u8]> {
+                $(let $field = &self.$field;)*
+                vec![
+                    $crate::define_pda!(@seed $first),
+                    $($crate::define_pda!(@seed $parts),)*
+                    std::slice::from_ref(&self.bump),
+                ]
+            }
+
+            /// Recreates the address from the stored bump, failing if the seeds and bump are
+            /// not a valid PDA of the program.
+            pub fn address(
+                &self,
+                program_id: &$crate::Pubkey,
+            ) -> Result<$crate::Pubkey, $crate::ProgramError> {
+                $crate::Pubkey::create_program_address(&self.seeds_with_bump(), program_id)
+                    .map_err($crate::ProgramError::from)
+            }
+        }
+    };
+
+    (@seed (field $field:ident)) => {
+        ::core::convert::AsRef::<[u8]>::as_ref($field)
+    };
+    (@seed (const $seed:expr)) => {
+        ::core::convert::AsRef::<[u8]>::as_ref($seed)
+    };
+    (@display (field $field:ident)) => {
+        concat!(stringify!($field), ".as_ref()")
+    };
+    (@display (const $seed:expr)) => {
+        stringify!($seed)
+    };
+}
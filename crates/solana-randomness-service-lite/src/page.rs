@@ -0,0 +1,125 @@
+//! Fixed capacity pages for lists stored in accounts, such as the callbacks or requests a
+//! consumer tracks per user.
+//!
+//! A `Vec` field in an account grows until it hits the 10 KiB realloc limit of a single
+//! instruction, or the 10 MiB account limit. Storing the list as a chain of [`Page`] PDAs,
+//! seeded by the owner of the list and the page index, bounds every account at
+//! [`Page::SPACE`] and lets a full page roll over to the next one.
+use crate::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+/// An item with a fixed serialized size, so the space of a full [`Page`] is known up front.
+pub trait PageItem {
+    /// The serialized size of the item.
+    const SIZE: usize;
+}
+
+impl PageItem for Pubkey {
+    const SIZE: usize = 32;
+}
+
+impl PageItem for AccountMetaBorsh {
+    const SIZE: usize = 32 + 1 + 1;
+}
+
+/// One page of at most `N` items, stored in its own PDA.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Page<T, const N: usize> {
+    /// The position of the page in the list, starting at zero. Part of the PDA seeds.
+    pub index: u32,
+    pub items: Vec<T>,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; RESERVED_LEN],
+}
+
+impl<T: PageItem, const N: usize> Page<T, N> {
+    /// The most items a page holds.
+    pub const CAPACITY: usize = N;
+
+    /// The account space of a full page, excluding the 8 byte discriminator of Anchor
+    /// accounts.
+    pub const SPACE: usize = 4 + 4 + N * T::SIZE + RESERVED_LEN;
+
+    /// Fails the build if a full page could not be allocated or grown within one
+    /// instruction.
+    const SPACE_CHECK: () = assert!(
+        8 + Self::SPACE <= MAX_PERMITTED_DATA_INCREASE,
+        "a full page must fit in MAX_PERMITTED_DATA_INCREASE"
+    );
+
+    pub fn new(index: u32) -> Self {
+        let () = Self::SPACE_CHECK;
+        Self {
+            index,
+            items: Vec::new(),
+            reserved: [0; RESERVED_LEN],
+        }
+    }
+
+    /// The empty page following this one.
+    pub fn next(&self) -> Self {
+        Self::new(self.index + 1)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= N
+    }
+
+    /// Appends the item, or hands it back in [`PageFull`] if the page holds
+    /// [`CAPACITY`](Self::CAPACITY) items. The caller then stores it in the [`next`](Self::next)
+    /// page, at the address derived with the next index.
+    pub fn try_push(&mut self, item: T) -> Result<(), PageFull<T>> {
+        if self.is_full() {
+            return Err(PageFull(item));
+        }
+        self.items.push(item);
+        Ok(())
+    }
+}
+
+/// The PDA seeds of a page: `[seed, owner, index as little endian u32]`. `owner` is the
+/// account the list belongs to, e.g. a user or a fan-out config.
+pub fn page_seeds<'a>(seed: &'a [u8], owner: &'a Pubkey, index: &'a [u8; 4]) -> [&'a [u8]; 3] {
+    [seed, owner.as_ref(), index]
+}
+
+/// The address and bump of page `index` of the list `seed` belonging to `owner`.
+pub fn page_address(seed: &[u8], owner: &Pubkey, index: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+    let index = index.to_le_bytes();
+    Pubkey::find_program_address(&page_seeds(seed, owner, &index), program_id)
+}
+
+/// Splits a list into full pages, numbered from zero, with the remainder in the last page.
+/// An empty list has no pages.
+pub fn iter_pages<T: PageItem, const N: usize>(
+    items: impl IntoIterator<Item = T>,
+) -> impl Iterator<Item = Page<T, N>> {
+    let mut items = items.into_iter().peekable();
+    let mut index = 0;
+    std::iter::from_fn(move || {
+        items.peek()?;
+        let mut page = Page::new(index);
+        page.items.extend(items.by_ref().take(N));
+        index += 1;
+        Some(page)
+    })
+}
+
+/// Returned by [`Page::try_push`] when the page is full, holding the rejected item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageFull<T>(pub T);
+
+impl<T> std::fmt::Display for PageFull<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", LiteError::PageFull)
+    }
+}
+
+impl<T: std::fmt::Debug> std::error::Error for PageFull<T> {}
+
+impl<T> From<PageFull<T>> for ProgramError {
+    fn from(_: PageFull<T>) -> Self {
+        LiteError::PageFull.into()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::*;
+use solana_program::msg;
+
+impl<'info> SimpleRandomnessV1Request<'info> {
+    /// Checks the request accounts before invoking the randomness service. Anchor consumers get
+    /// the same guarantees from the constraints in the crate example; programs calling
+    /// [`cpi`](Self::cpi) or [`cpi_signed`](Self::cpi_signed) directly should call
+    /// this first.
+    ///
+    /// Also checks the mint against the one recorded in the state account, so a program passing
+    /// the native mint fails here if a cluster's service is reconfigured to another mint.
+    pub fn preflight_checks(&self, consumer_program_id: &Pubkey) -> Result<(), ProgramError> {
+        check_request_account(&self.request, consumer_program_id)?;
+        check_escrow_mint(&self.state, &self.mint)
+    }
+}
+
+/// Verifies the request account is a new, empty account and not one of the consumer's own
+/// accounts or an existing randomness request.
+pub fn check_request_account(
+    request: &AccountInfo,
+    consumer_program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    if request.owner == consumer_program_id || *request.owner == ID {
+        msg!(
+            "Request account {} is owned by {}",
+            request.key,
+            request.owner
+        );
+        return Err(LiteError::RequestAccountOwnedByProgram.into());
+    }
+
+    if request.data_len() != 0 || request.lamports() != 0 {
+        msg!("Request account {} is not empty", request.key);
+        return Err(LiteError::RequestAccountNotEmpty.into());
+    }
+
+    Ok(())
+}
+
+/// Verifies the mint passed with a request is the mint recorded in the service's state
+/// account, reading only that field with [`ServiceStateView`]. Fails with
+/// [`LiteError::MintMismatch`], logging both mints.
+pub fn check_escrow_mint(state: &AccountInfo, mint: &AccountInfo) -> Result<(), ProgramError> {
+    let expected = ServiceStateView::load(state)?.mint();
+    if *mint.key != expected {
+        msg!(
+            "Mint {} does not match the service state's mint {}",
+            mint.key,
+            expected
+        );
+        return Err(LiteError::MintMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// The deepest instruction stack the runtime allows: the transaction's own instruction at
+/// height 1 and four nested CPIs below it.
+pub const MAX_STACK_HEIGHT: u8 = 5;
+
+/// The levels of CPI a randomness request adds below the program making it: the service at
+/// the next level, the associated token program it calls to create the escrow, and the token
+/// and system programs that one calls in turn.
+pub const REQUEST_CPI_LEVELS: u8 = 3;
+
+/// The levels of CPI a callback can still make itself. The oracle's settle instruction is the
+/// transaction's instruction and invokes the callback one level below it.
+pub const CALLBACK_CPI_LEVELS: u8 = MAX_STACK_HEIGHT - 2;
+
+/// Fails with [`LiteError::CpiDepthExceeded`] unless `levels_needed` more levels of CPI fit
+/// below the current instruction.
+///
+/// A program composed under others, e.g. a router invoking a consumer that requests
+/// randomness, can call this with [`REQUEST_CPI_LEVELS`] before the request, so a chain that
+/// is too deep fails with a clear error instead of the runtime's call depth error from deep
+/// inside the service. Reads the stack height with `get_stack_height`, so it costs no accounts.
+pub fn assert_cpi_depth_available(levels_needed: u8) -> Result<(), ProgramError> {
+    let height = solana_program::instruction::get_stack_height();
+    if height + usize::from(levels_needed) > usize::from(MAX_STACK_HEIGHT) {
+        msg!(
+            "Stack height {} leaves {} CPI levels, {} needed",
+            height,
+            usize::from(MAX_STACK_HEIGHT).saturating_sub(height),
+            levels_needed
+        );
+        return Err(LiteError::CpiDepthExceeded.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! Everything a program needs to request randomness, for glob imports next to other Switchboard
+//! crates:
+//!
+//! ```ignore
+//! use switchboard_solana::prelude::*;
+//! use solana_randomness_service_lite::prelude::*;
+//! ```
+//!
+//! Unlike the crate root, the prelude leaves out the `ID`, `id`, and `check_id` generated by
+//! `declare_id!`, which every program crate exports under the same names. The service's program
+//! ID is [`RANDOMNESS_SERVICE_ID`] here. The deprecated `State` alias, which would shadow the
+//! `State` of the service's Anchor crate, is left out too.
+
+pub use crate::solana::*;
+pub use crate::ID as RANDOMNESS_SERVICE_ID;
+pub use crate::{
+    define_pda, randomness_bail, require_randomness, ServiceState, SimpleRandomnessV1Account,
+    SimpleRandomnessV1Request, DEVNET_SWITCHBOARD_FUNCTION, DEVNET_SWITCHBOARD_SERVICE,
+    MAINNET_SWITCHBOARD_FUNCTION, MAINNET_SWITCHBOARD_SERVICE, RANDOMNESS_SERVICE_REWARD_MINT,
+    RANDOMNESS_SERVICE_REWARD_WALLET, RANDOMNESS_SERVICE_STATE, SWITCHBOARD_PROGRAM_ID,
+};
+
+pub use crate::account_refs::*;
+pub use crate::addresses::*;
+pub use crate::archive::*;
+pub use crate::cache::*;
+pub use crate::callback_builder::*;
+pub use crate::clock::*;
+pub use crate::discriminator::*;
+pub use crate::error::*;
+pub use crate::event::*;
+pub use crate::feed::*;
+pub use crate::guard::*;
+pub use crate::header::*;
+pub use crate::indexed_tag::*;
+pub use crate::macros::*;
+pub use crate::page::*;
+pub use crate::preflight::*;
+pub use crate::reserved::*;
+pub use crate::retry::*;
+pub use crate::types::*;
+pub use crate::verify::*;
+pub use crate::version::*;
+pub use crate::view::*;
+
+#[cfg(feature = "anchor")]
+pub use crate::anchor::LiteAccount;
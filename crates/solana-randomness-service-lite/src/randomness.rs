@@ -0,0 +1,382 @@
+use crate::*;
+use solana_program::msg;
+
+/// The length of the Borsh `u32` prefix the service writes before the randomness bytes.
+const LEN_PREFIX: usize = 4;
+
+/// The bytes [`random_in_range`] reads for each draw.
+pub const RANGE_DRAW_BYTES: usize = 8;
+
+/// Returns the randomness the service appended to a callback's instruction data, for native
+/// callbacks that read `ix_data` themselves.
+///
+/// The service invokes the callback with its stored `ix_data` followed by the randomness as a
+/// Borsh `Vec<u8>`, a little endian `u32` length and the bytes, so the randomness is always the
+/// last `expected_len` bytes whatever arguments come before it. Pass the `num_bytes` the
+/// request asked for. Fails with [`LiteError::InvalidNumBytes`] unless `expected_len` is from
+/// [`MIN_RANDOMNESS_BYTES`] to [`MAX_RANDOMNESS_BYTES`], and with
+/// [`LiteError::RandomnessLengthMismatch`] if the data is too short to hold the discriminator,
+/// the length, and the bytes, or the appended length is not `expected_len`.
+pub fn extract_randomness(ix_data: &[u8], expected_len: usize) -> Result<&[u8], ProgramError> {
+    check_expected_len(expected_len)?;
+    let Some(prefix_start) = ix_data
+        .len()
+        .checked_sub(expected_len + LEN_PREFIX)
+        .filter(|start| *start >= 8)
+    else {
+        msg!(
+            "Callback data of {} bytes cannot end with {} randomness bytes",
+            ix_data.len(),
+            expected_len
+        );
+        return Err(LiteError::RandomnessLengthMismatch.into());
+    };
+
+    let (prefix, randomness) = ix_data[prefix_start..].split_at(LEN_PREFIX);
+    let mut len = [0; LEN_PREFIX];
+    len.copy_from_slice(prefix);
+    let len = u32::from_le_bytes(len);
+    if len as usize != expected_len {
+        msg!(
+            "Expected {} randomness bytes, the service appended {}",
+            expected_len,
+            len
+        );
+        return Err(LiteError::RandomnessLengthMismatch.into());
+    }
+    Ok(randomness)
+}
+
+/// Checks the randomness an Anchor callback received as its last `Vec<u8>` argument and returns
+/// it, with the same errors as [`extract_randomness`]: the argument must hold exactly
+/// `expected_len` bytes.
+///
+/// ```ignore
+/// pub fn consume_randomness(ctx: Context<ConsumeRandomness>, result: Vec<u8>) -> Result<()> {
+///     let randomness = randomness_from_arg(&result, 8)?;
+///     // ...
+/// }
+/// ```
+pub fn randomness_from_arg(result: &[u8], expected_len: usize) -> Result<&[u8], ProgramError> {
+    check_expected_len(expected_len)?;
+    if result.len() != expected_len {
+        msg!(
+            "Expected {} randomness bytes, received {}",
+            expected_len,
+            result.len()
+        );
+        return Err(LiteError::RandomnessLengthMismatch.into());
+    }
+    Ok(result)
+}
+
+fn check_expected_len(expected_len: usize) -> Result<(), ProgramError> {
+    match u8::try_from(expected_len) {
+        Ok(num_bytes) => check_num_bytes(num_bytes),
+        Err(_) => check_num_bytes(u8::MAX),
+    }
+}
+
+/// Randomness bytes read as integers, e.g. the result a callback received. Borrows the bytes
+/// and never allocates, so it can be used on chain.
+///
+/// Each integer is read little endian from the first bytes it needs; the bytes after them are
+/// ignored. To draw several independent values from one request, split it with
+/// [`chunks`](Self::chunks) rather than reading overlapping bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RandomnessBytes<'a>(pub &'a [u8]);
+
+impl<'a> RandomnessBytes<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The first byte. Fails with [`LiteError::RandomnessLengthMismatch`] if there is none.
+    pub fn to_u8(self) -> Result<u8, ProgramError> {
+        self.take().map(u8::from_le_bytes)
+    }
+
+    /// The first 2 bytes. Fails with [`LiteError::RandomnessLengthMismatch`] if there are
+    /// fewer.
+    pub fn to_u16(self) -> Result<u16, ProgramError> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    /// The first 4 bytes. Fails with [`LiteError::RandomnessLengthMismatch`] if there are
+    /// fewer.
+    pub fn to_u32(self) -> Result<u32, ProgramError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    /// The first 8 bytes. Fails with [`LiteError::RandomnessLengthMismatch`] if there are
+    /// fewer.
+    pub fn to_u64(self) -> Result<u64, ProgramError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    /// The first 16 bytes. Fails with [`LiteError::RandomnessLengthMismatch`] if there are
+    /// fewer.
+    pub fn to_u128(self) -> Result<u128, ProgramError> {
+        self.take().map(u128::from_le_bytes)
+    }
+
+    /// Splits the bytes into consecutive, non-overlapping chunks of `size` bytes, each of which
+    /// can be read as its own value. Bytes left over after the last full chunk are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero, like [`slice::chunks_exact`].
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = RandomnessBytes<'a>> {
+        self.0.chunks_exact(size).map(RandomnessBytes)
+    }
+
+    fn take<const N: usize>(&self) -> Result<[u8; N], ProgramError> {
+        let Some(bytes) = self.0.get(..N) else {
+            msg!("Reading {} bytes of randomness from {}", N, self.0.len());
+            return Err(LiteError::RandomnessLengthMismatch.into());
+        };
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok(array)
+    }
+}
+
+impl<'a> From<&'a [u8]> for RandomnessBytes<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Returns a value from `min` to `max`, both inclusive, drawn from the randomness without the
+/// modulo bias of `value % range`. Every value in the range is equally likely.
+///
+/// The bytes are read in draws of [`RANGE_DRAW_BYTES`], each a little endian `u64`, and a draw
+/// is mapped onto the range with a widening multiply. The few draws that would make some values
+/// more likely than others are rejected and the next draw is read, so the first draw is
+/// accepted with probability above `1 - span / 2^64`, where `span` is the number of values in
+/// the range. 8 bytes are enough for all but astronomically rare inputs; for a range close to
+/// `2^64` values, pass 16 or 32 bytes so a rejection still finds a draw. Bytes after the
+/// accepted draw are not read.
+///
+/// The result depends only on the bytes, so the same callback data always yields the same
+/// value, and it costs one multiply per draw, plus a division on the rare draws near a
+/// rejection. Fails with [`LiteError::InvalidRange`] if `min > max`, and with
+/// [`LiteError::RandomnessLengthMismatch`] if there are fewer than [`RANGE_DRAW_BYTES`] bytes
+/// or every draw was rejected.
+pub fn random_in_range(bytes: &[u8], min: u64, max: u64) -> Result<u64, ProgramError> {
+    if min > max {
+        msg!("Range {}..={} is empty", min, max);
+        return Err(LiteError::InvalidRange.into());
+    }
+    // Zero when the range holds all 2^64 values
+    let span = (max - min).wrapping_add(1);
+    if span == 0 {
+        return RandomnessBytes(bytes).to_u64();
+    }
+
+    match draw_below(&mut bytes.chunks_exact(RANGE_DRAW_BYTES), span) {
+        Some(offset) => Ok(min + offset),
+        None => {
+            msg!(
+                "No unbiased draw in {} bytes of randomness for {}..={}",
+                bytes.len(),
+                min,
+                max
+            );
+            Err(LiteError::RandomnessLengthMismatch.into())
+        }
+    }
+}
+
+/// Flips a fair coin, returning `true` for heads, from one draw of [`RANGE_DRAW_BYTES`] read
+/// like `random_in_range(bytes, 0, 1)`. Two values never cause a rejection, so 8 bytes are
+/// always enough and the bytes after them are ignored. Fails with
+/// [`LiteError::RandomnessLengthMismatch`] if there are fewer.
+pub fn coin_flip(bytes: &[u8]) -> Result<bool, ProgramError> {
+    random_in_range(bytes, 0, 1).map(|side| side == 1)
+}
+
+/// Rolls a die with `sides` faces numbered from 1, like `random_in_range(bytes, 1, sides)`.
+///
+/// Reads one draw of [`RANGE_DRAW_BYTES`], so request 8 bytes; a draw is rejected with
+/// probability below `sides / 2^64`, and only then is the next one read. Fails with
+/// [`LiteError::InvalidRange`] if `sides` is 0, and with
+/// [`LiteError::RandomnessLengthMismatch`] if the draws run out.
+pub fn roll_die(bytes: &[u8], sides: u8) -> Result<u8, ProgramError> {
+    random_in_range(bytes, 1, sides.into()).map(|face| face as u8)
+}
+
+/// The randomness [`roll_dice`] reads for `count` dice: one draw of [`RANGE_DRAW_BYTES`] per
+/// die, so `RANGE_DRAW_BYTES * count` bytes. A single request covers up to 4 dice.
+pub const fn required_randomness_bytes_for_dice(count: usize) -> usize {
+    count.saturating_mul(RANGE_DRAW_BYTES)
+}
+
+/// Rolls `count` dice with `sides` faces numbered from 1, each from the next draw of
+/// [`RANGE_DRAW_BYTES`], so the first die matches [`roll_die`] on the same bytes.
+///
+/// Reads [`required_randomness_bytes_for_dice`] bytes, plus a draw for each rejection, which
+/// has probability below `sides / 2^64` per die. Fails with [`LiteError::InvalidRange`] if
+/// `sides` is 0, and with [`LiteError::RandomnessLengthMismatch`], before allocating, if there
+/// are fewer than [`required_randomness_bytes_for_dice`] bytes, or if a rejection leaves too few
+/// draws for the last dice.
+pub fn roll_dice(bytes: &[u8], sides: u8, count: usize) -> Result<Vec<u8>, ProgramError> {
+    if sides == 0 {
+        msg!("A die needs at least one side");
+        return Err(LiteError::InvalidRange.into());
+    }
+    let required = required_randomness_bytes_for_dice(count);
+    if bytes.len() < required {
+        msg!(
+            "Rolling {} dice needs {} bytes of randomness, received {}",
+            count,
+            required,
+            bytes.len()
+        );
+        return Err(LiteError::RandomnessLengthMismatch.into());
+    }
+
+    let mut draws = bytes.chunks_exact(RANGE_DRAW_BYTES);
+    (0..count)
+        .map(|_| match draw_below(&mut draws, sides.into()) {
+            // Below `sides`, so the face fits a u8
+            Some(face) => Ok(face as u8 + 1),
+            None => {
+                msg!("Ran out of unbiased draws rolling {} dice", count);
+                Err(LiteError::RandomnessLengthMismatch.into())
+            }
+        })
+        .collect()
+}
+
+/// Picks an index with probability proportional to its weight, e.g. `&[70, 25, 5]` for common,
+/// rare, and legendary loot, without the cumulative sums and modulo bias of doing it by hand.
+///
+/// Draws `random_in_range(bytes, 0, total - 1)`, where `total` is the sum of the weights, and
+/// returns the first index whose running sum passes the draw, so index `i` owns the
+/// `weights[i]` values after the weights before it. A zero weight owns none and is never
+/// picked. Reads one draw of [`RANGE_DRAW_BYTES`], so request 8 bytes, plus a draw for each
+/// rejection, which has probability below `total / 2^64`.
+///
+/// Does not allocate. Fails with [`LiteError::InvalidWeights`] if the weights are empty, all
+/// zero, or sum past `u64::MAX`, and with [`LiteError::RandomnessLengthMismatch`] if the draws
+/// run out.
+pub fn weighted_choice(bytes: &[u8], weights: &[u64]) -> Result<usize, ProgramError> {
+    choose_weighted(bytes, weights.iter().copied())
+}
+
+/// Picks an item from `(item, weight)` pairs like [`weighted_choice`] picks an index, so the
+/// same bytes and weights select the same entry, and returns a reference to the item.
+///
+/// ```ignore
+/// let table = [(Tier::Common, 70), (Tier::Rare, 25), (Tier::Legendary, 5)];
+/// let tier = weighted_choice_of(&result, &table)?;
+/// ```
+pub fn weighted_choice_of<'a, T>(
+    bytes: &[u8],
+    choices: &'a [(T, u64)],
+) -> Result<&'a T, ProgramError> {
+    choose_weighted(bytes, choices.iter().map(|(_, weight)| *weight)).map(|i| &choices[i].0)
+}
+
+fn choose_weighted(
+    bytes: &[u8],
+    weights: impl Iterator<Item = u64> + Clone,
+) -> Result<usize, ProgramError> {
+    let mut total: u64 = 0;
+    for weight in weights.clone() {
+        let Some(sum) = total.checked_add(weight) else {
+            msg!("The weights sum past u64::MAX");
+            return Err(LiteError::InvalidWeights.into());
+        };
+        total = sum;
+    }
+    if total == 0 {
+        msg!("The weights are empty or all zero");
+        return Err(LiteError::InvalidWeights.into());
+    }
+
+    let draw = random_in_range(bytes, 0, total - 1)?;
+    let mut end = 0;
+    for (i, weight) in weights.enumerate() {
+        // Cannot overflow, the sum of every weight fits
+        end += weight;
+        if draw < end {
+            return Ok(i);
+        }
+    }
+    unreachable!("the draw is below the sum of the weights")
+}
+
+/// The randomness [`shuffle_in_place`] needs for a slice of `len` items: a draw of
+/// [`RANGE_DRAW_BYTES`] for each of the `len - 1` swaps plus one spare draw, so
+/// `RANGE_DRAW_BYTES * len` bytes, or none for fewer than 2 items.
+///
+/// A single request delivers at most [`MAX_RANDOMNESS_BYTES`], enough for 4 items; request
+/// more with a batch.
+pub const fn required_randomness_bytes_for_shuffle(len: usize) -> usize {
+    if len < 2 {
+        return 0;
+    }
+    len.saturating_mul(RANGE_DRAW_BYTES)
+}
+
+/// Shuffles the items with Fisher-Yates, drawing each swap index from the randomness the way
+/// [`random_in_range`] does, so every ordering is equally likely and the same bytes always give
+/// the same order.
+///
+/// Fails with [`LiteError::RandomnessLengthMismatch`], before moving any item, if there are
+/// fewer than [`required_randomness_bytes_for_shuffle`] bytes. The spare draw stands in for a
+/// rejected one; should the draws still run out, the error is returned with the items partly
+/// shuffled. Bytes beyond the ones read are ignored and never reused.
+pub fn shuffle_in_place<T>(items: &mut [T], randomness: &[u8]) -> Result<(), ProgramError> {
+    let required = required_randomness_bytes_for_shuffle(items.len());
+    if randomness.len() < required {
+        msg!(
+            "Shuffling {} items needs {} bytes of randomness, received {}",
+            items.len(),
+            required,
+            randomness.len()
+        );
+        return Err(LiteError::RandomnessLengthMismatch.into());
+    }
+
+    let mut draws = randomness.chunks_exact(RANGE_DRAW_BYTES);
+    for i in (1..items.len()).rev() {
+        let Some(j) = draw_below(&mut draws, i as u64 + 1) else {
+            msg!("Ran out of unbiased draws shuffling {} items", items.len());
+            return Err(LiteError::RandomnessLengthMismatch.into());
+        };
+        items.swap(i, j as usize);
+    }
+    Ok(())
+}
+
+/// Reads draws until one maps onto `0..span` without bias and returns the value, or `None` if
+/// the draws run out. `span` must not be zero.
+fn draw_below<'a>(draws: &mut impl Iterator<Item = &'a [u8]>, span: u64) -> Option<u64> {
+    for draw in draws {
+        let mut bytes = [0; RANGE_DRAW_BYTES];
+        bytes.copy_from_slice(draw);
+        let product = u128::from(u64::from_le_bytes(bytes)) * u128::from(span);
+        let low = product as u64;
+        // Lemire's method: reject the 2^64 % span draws that would over-represent values
+        if low < span && low < span.wrapping_neg() % span {
+            continue;
+        }
+        return Some((product >> 64) as u64);
+    }
+    None
+}
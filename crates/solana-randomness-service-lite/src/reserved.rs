@@ -0,0 +1,26 @@
+//! Every account layout introduced by this crate and its examples ends in a
+//! `reserved: [u8; RESERVED_LEN]` field, so a later version can add a field by carving it out of
+//! the reserved bytes without changing the size of the account. Reserved bytes are written as
+//! zero and a new field must treat zero as "unset".
+//!
+//! Decode these layouts with [`deserialize_with_reserved`] rather than a plain Borsh
+//! `deserialize`, so accounts written before the reserved bytes existed and accounts written by
+//! a newer layout both decode.
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+
+/// The number of reserved bytes trailing every account layout introduced by this crate.
+pub const RESERVED_LEN: usize = 32;
+
+/// Deserializes a Borsh layout ending in a `reserved: [u8; RESERVED_LEN]` field from the account
+/// data following its discriminator.
+///
+/// Reserved bytes missing from a shorter, older buffer decode as zero. Bytes past the end of the
+/// layout, written by a newer version that appended fields, are ignored.
+pub fn deserialize_with_reserved<T: BorshDeserialize>(data: &[u8]) -> Result<T, ProgramError> {
+    let mut padded = Vec::with_capacity(data.len() + RESERVED_LEN);
+    padded.extend_from_slice(data);
+    padded.resize(data.len() + RESERVED_LEN, 0);
+
+    T::deserialize(&mut &padded[..]).map_err(|_| ProgramError::InvalidAccountData)
+}
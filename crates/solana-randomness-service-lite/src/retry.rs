@@ -0,0 +1,40 @@
+//! What a callback can expect when its settlement is retried.
+//!
+//! The service keeps no retry policy on-chain: [`ServiceState`] has no retry fields and the
+//! oracle decides when to settle. The oracle simulates the settle transaction first, and a
+//! callback that fails simulation is not retried; the oracle relays
+//! `simple_randomness_v1_callback_error` instead. A settle transaction that passed simulation
+//! but failed or was dropped on-chain changed nothing, so the request stays open and the
+//! oracle settles it again once it knows the transaction is lost, when its blockhash expires.
+//!
+//! A retried callback therefore always sees the state its first attempt saw, but possibly
+//! after other callbacks, including ones for later requests, have run in between.
+use crate::*;
+
+/// How many times the oracle retries a callback that fails simulation. None: it reports the
+/// error to the request with `simple_randomness_v1_callback_error` instead.
+pub const CALLBACK_MAX_RETRIES: u32 = 0;
+
+/// The slots the oracle waits before settling a request again after its settle transaction
+/// was lost, about as long as a blockhash is valid.
+pub const CALLBACK_RETRY_BACKOFF_SLOTS: u64 = 150;
+
+/// Whether the callback is most likely running on a retried settlement, because the request
+/// was made at least [`CALLBACK_RETRY_BACKOFF_SLOTS`] before the current slot.
+///
+/// A heuristic: a first attempt delayed by a congested oracle looks the same. Use it to log or
+/// meter retries, not to decide whether to apply a result; a callback should be safe to run
+/// late regardless.
+///
+/// Takes any [`SlotSource`], so the `&Clock` a callback read and a bare slot in a test both
+/// work.
+pub fn is_retry_attempt<S: SlotSource + ?Sized>(
+    request: &SimpleRandomnessV1Account,
+    clock: &S,
+) -> bool {
+    is_retry_slot(request.request_slot, clock.slot())
+}
+
+pub(crate) fn is_retry_slot(request_slot: u64, slot: u64) -> bool {
+    slot.saturating_sub(request_slot) >= CALLBACK_RETRY_BACKOFF_SLOTS
+}
@@ -0,0 +1,11 @@
+//! The Solana types in this crate's signatures, re-exported so programs do not need a direct
+//! `solana-program` dependency to call it.
+//!
+//! These are `solana_program`'s own items, so they resolve to the same item as another crate's
+//! re-export, e.g. `anchor_lang::prelude::Pubkey`, whenever the two share a `solana-program`.
+//! They are also re-exported at the crate root.
+
+pub use solana_program::account_info::AccountInfo;
+pub use solana_program::instruction::AccountMeta;
+pub use solana_program::program_error::ProgramError;
+pub use solana_program::pubkey::Pubkey;
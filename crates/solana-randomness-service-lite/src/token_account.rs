@@ -0,0 +1,93 @@
+use crate::*;
+
+/// The size of the base SPL token account layout, shared by the token program and Token-2022.
+pub const TOKEN_ACCOUNT_BASE_SIZE: usize = 165;
+
+/// The size of an SPL token multisig account, which Token-2022 never gives an account.
+const MULTISIG_SIZE: usize = 355;
+
+/// The Token-2022 account type byte, after the base layout, of a token account.
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// The state of an SPL token account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenAccountStatus {
+    Initialized,
+    Frozen,
+}
+
+/// The fields of an SPL token account the client reads, e.g. from an escrow or a delegate
+/// funding account. Read with [`parse_token_account`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenAccountView {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    /// The balance, in the mint's smallest unit.
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+    pub delegated_amount: u64,
+    pub state: TokenAccountStatus,
+}
+
+/// Reads an SPL token account from its data without depending on `spl-token`.
+///
+/// Accepts the classic 165 byte layout and Token-2022 accounts, whose base layout is the same.
+/// A longer Token-2022 account must carry the token account type byte after the base layout;
+/// the extensions that follow it are not read. Fails with
+/// [`ProgramError::UninitializedAccount`] for an uninitialized account and
+/// [`ProgramError::InvalidAccountData`] for anything else that is not a token account, such as
+/// a mint, a multisig, or truncated data.
+pub fn parse_token_account(data: &[u8]) -> Result<TokenAccountView, ProgramError> {
+    match data.len() {
+        TOKEN_ACCOUNT_BASE_SIZE => {}
+        MULTISIG_SIZE => return Err(ProgramError::InvalidAccountData),
+        len if len > TOKEN_ACCOUNT_BASE_SIZE => {
+            if data[TOKEN_ACCOUNT_BASE_SIZE] != ACCOUNT_TYPE_ACCOUNT {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        _ => return Err(ProgramError::InvalidAccountData),
+    }
+
+    let state = match data[108] {
+        0 => return Err(ProgramError::UninitializedAccount),
+        1 => TokenAccountStatus::Initialized,
+        2 => TokenAccountStatus::Frozen,
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    Ok(TokenAccountView {
+        mint: read_pubkey(data, 0),
+        owner: read_pubkey(data, 32),
+        amount: read_u64(data, 64),
+        delegate: match read_option_tag(data, 72)? {
+            true => Some(read_pubkey(data, 76)),
+            false => None,
+        },
+        delegated_amount: read_u64(data, 121),
+        state,
+    })
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(&data[offset..offset + 32]);
+    Pubkey::new_from_array(bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Reads the 4 byte tag of a packed `COption`.
+fn read_option_tag(data: &[u8], offset: usize) -> Result<bool, ProgramError> {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&data[offset..offset + 4]);
+    match u32::from_le_bytes(bytes) {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
@@ -1,5 +1,112 @@
 use crate::*;
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
+use solana_program::msg;
+
+/// The most randomness bytes one request can ask for. The service's `simple_randomness_v1`
+/// instruction rejects zero bytes or more than this, and so does its settle instruction.
+pub const MAX_NUM_BYTES: u8 = 32;
+
+/// The fewest randomness bytes one request can ask for.
+pub const MIN_RANDOMNESS_BYTES: u8 = 1;
+
+/// The most randomness bytes one request can ask for, the same as [`MAX_NUM_BYTES`].
+pub const MAX_RANDOMNESS_BYTES: u8 = MAX_NUM_BYTES;
+
+/// The most accounts a callback can list. The oracle settles with one legacy transaction: the
+/// state every callback lists only adds its index to it, and each other account its key and
+/// index, so this is the state plus the distinct accounts that fit with the fewest randomness
+/// bytes. A larger callback could never be executed. Fewer fit with more randomness, see
+/// [`Callback::check_size`].
+pub const MAX_CALLBACK_ACCOUNTS: usize = {
+    let state_only = Callback::settle_size(0, 1, MIN_RANDOMNESS_BYTES as usize);
+    1 + (Callback::MAX_TRANSACTION_SIZE - state_only) / (32 + 1)
+};
+
+/// The longest callback instruction data, discriminator included. It reaches the service only
+/// in the `simple_randomness_v1` instruction data, which is at most the runtime's 10 KiB
+/// `MAX_CPI_INSTRUCTION_DATA_LEN` when a program requests by CPI, and less in a 1232 byte
+/// transaction, so this is what is left of it beside the smallest request: the discriminator,
+/// `num_bytes`, a callback listing only the state, and no options. A longer callback could
+/// never be requested.
+pub const MAX_CALLBACK_IX_DATA_LEN: usize = {
+    let state_only = 32 + 4 + std::mem::size_of::<AccountMetaBorsh>() + 4;
+    MAX_CPI_INSTRUCTION_DATA_LEN - (8 + 1 + state_only + 1)
+};
+
+/// The longest instruction data one program can pass another by CPI, published as
+/// `solana_program::syscalls::MAX_CPI_INSTRUCTION_DATA_LEN` in solana-program 1.18.
+const MAX_CPI_INSTRUCTION_DATA_LEN: usize = 10 * 1024;
+
+// The settle instruction invokes the callback with the randomness appended to its data
+const _: () = assert!(
+    MAX_CALLBACK_IX_DATA_LEN + 4 + MAX_RANDOMNESS_BYTES as usize <= MAX_CPI_INSTRUCTION_DATA_LEN
+);
+
+/// Fails with [`LiteError::InvalidNumBytes`] unless `num_bytes` is from
+/// [`MIN_RANDOMNESS_BYTES`] to [`MAX_RANDOMNESS_BYTES`], the range the service accepts.
+pub fn check_num_bytes(num_bytes: u8) -> Result<(), ProgramError> {
+    if !(MIN_RANDOMNESS_BYTES..=MAX_RANDOMNESS_BYTES).contains(&num_bytes) {
+        msg!(
+            "Requested {} randomness bytes, the service accepts {} to {}",
+            num_bytes,
+            MIN_RANDOMNESS_BYTES,
+            MAX_RANDOMNESS_BYTES
+        );
+        return Err(LiteError::InvalidNumBytes.into());
+    }
+    Ok(())
+}
+
+/// A number of randomness bytes the service accepts for one request, from 1 to
+/// [`MAX_NUM_BYTES`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NumBytes(u8);
+
+impl NumBytes {
+    pub const MAX: NumBytes = NumBytes(MAX_NUM_BYTES);
+
+    pub fn new(num_bytes: u8) -> Result<Self, ServiceError> {
+        if num_bytes == 0 || num_bytes > MAX_NUM_BYTES {
+            return Err(ServiceError::InvalidNumberOfBytes);
+        }
+        Ok(Self(num_bytes))
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for NumBytes {
+    type Error = ServiceError;
+
+    fn try_from(num_bytes: u8) -> Result<Self, Self::Error> {
+        Self::new(num_bytes)
+    }
+}
+
+impl From<NumBytes> for u8 {
+    fn from(value: NumBytes) -> Self {
+        value.0
+    }
+}
+
+/// The number of micro-lamports in a lamport, the unit of compute unit prices.
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+// The largest priority fee in micro-lamports fits in a u64
+const _: () = assert!((TransactionOptions::MAXIMUM_COMPUTE_UNITS as u64)
+    .checked_mul(TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE)
+    .is_some());
+
+/// Advises the compute units of a callback from measurements, overriding the estimate of
+/// [`TransactionOptions::advised_for_callback`]. See
+/// [`advised_for_callback_with`](TransactionOptions::advised_for_callback_with).
+pub trait ComputeUnitsSource {
+    /// The compute units to request for the callback's settle transaction, headroom included,
+    /// or `None` to fall back to the estimate.
+    fn compute_units_for(&self, callback: &Callback) -> Option<u32>;
+}
 
 #[derive(Default, Clone, Debug, BorshDeserialize, BorshSerialize)]
 pub struct TransactionOptions {
@@ -15,36 +122,117 @@ impl TransactionOptions {
     pub const MINIMUM_COMPUTE_UNIT_PRICE: u64 = 1;
     pub const MAXIMUM_COMPUTE_UNIT_PRICE: u64 = 1_000_000_000;
 
+    /// The fixed cost of the service's settle instruction and the CPI into the callback.
+    pub const ADVISED_BASE_COMPUTE_UNITS: u32 = 100_000;
+    /// The cost of passing one account to the callback and of a typical callback touching it.
+    pub const ADVISED_COMPUTE_UNITS_PER_ACCOUNT: u32 = 10_000;
+    /// The cost of one byte of callback instruction data.
+    pub const ADVISED_COMPUTE_UNITS_PER_IX_DATA_BYTE: u32 = 100;
+    /// The headroom added on top of the estimate, in percent.
+    pub const ADVISED_HEADROOM_PERCENT: u32 = 25;
+
+    /// A compute budget estimated from the shape of the callback: a base cost, plus a cost per
+    /// account and per byte of instruction data, plus [`ADVISED_HEADROOM_PERCENT`] headroom,
+    /// clamped to the minimum and maximum compute units. The compute unit price is left at
+    /// the default.
+    ///
+    /// This is a starting point for callbacks whose cost is unknown. It cannot see what the
+    /// callback does with its accounts, so measure the callback, e.g. by simulating it, and
+    /// set `compute_units` from the measurement once it is known.
+    ///
+    /// [`ADVISED_HEADROOM_PERCENT`]: Self::ADVISED_HEADROOM_PERCENT
+    pub fn advised_for_callback(callback: &Callback) -> Self {
+        let estimate = u64::from(Self::ADVISED_BASE_COMPUTE_UNITS)
+            + callback.accounts.len() as u64 * u64::from(Self::ADVISED_COMPUTE_UNITS_PER_ACCOUNT)
+            + callback.ix_data.len() as u64
+                * u64::from(Self::ADVISED_COMPUTE_UNITS_PER_IX_DATA_BYTE);
+        let advised = estimate * u64::from(100 + Self::ADVISED_HEADROOM_PERCENT) / 100;
+
+        Self {
+            compute_units: Some(advised.clamp(
+                u64::from(Self::MINIMUM_COMPUTE_UNITS),
+                u64::from(Self::MAXIMUM_COMPUTE_UNITS),
+            ) as u32),
+            compute_unit_price: None,
+        }
+    }
+
+    /// Like [`advised_for_callback`](Self::advised_for_callback), with the compute units
+    /// measured for the callback by `source`, e.g. the client's `TuningStore`, in place of the
+    /// estimate when it has them. They are clamped to the minimum and maximum compute units.
+    pub fn advised_for_callback_with(callback: &Callback, source: &dyn ComputeUnitsSource) -> Self {
+        match source.compute_units_for(callback) {
+            Some(units) => Self {
+                compute_units: Some(
+                    units.clamp(Self::MINIMUM_COMPUTE_UNITS, Self::MAXIMUM_COMPUTE_UNITS),
+                ),
+                compute_unit_price: None,
+            },
+            None => Self::advised_for_callback(callback),
+        }
+    }
+
+    /// Checks that the set fields are within the range the service accepts. Fields left as
+    /// `None` are valid, since their defaults are.
+    ///
+    /// [`get_compute_units`](Self::get_compute_units) and
+    /// [`get_compute_unit_price`](Self::get_compute_unit_price) clamp out of range values
+    /// instead, so a budget of 2,000,000 compute units silently becomes 1,400,000. Call this,
+    /// or build the request with [`SimpleRandomnessV1Request::instruction_strict`], to fail
+    /// when the request is built instead.
+    pub fn validate(&self) -> Result<(), TransactionOptionsError> {
+        if let Some(value) = self.compute_units {
+            if !(Self::MINIMUM_COMPUTE_UNITS..=Self::MAXIMUM_COMPUTE_UNITS).contains(&value) {
+                return Err(TransactionOptionsError::ComputeUnitsOutOfRange {
+                    value,
+                    min: Self::MINIMUM_COMPUTE_UNITS,
+                    max: Self::MAXIMUM_COMPUTE_UNITS,
+                });
+            }
+        }
+        if let Some(value) = self.compute_unit_price {
+            if !(Self::MINIMUM_COMPUTE_UNIT_PRICE..=Self::MAXIMUM_COMPUTE_UNIT_PRICE)
+                .contains(&value)
+            {
+                return Err(TransactionOptionsError::ComputeUnitPriceOutOfRange {
+                    value,
+                    min: Self::MINIMUM_COMPUTE_UNIT_PRICE,
+                    max: Self::MAXIMUM_COMPUTE_UNIT_PRICE,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The compute units, or the default, clamped to the accepted range.
     pub fn get_compute_units(&self) -> u32 {
-        std::cmp::max(
-            Self::MINIMUM_COMPUTE_UNITS,
-            std::cmp::min(
-                Self::MAXIMUM_COMPUTE_UNITS,
-                self.compute_units.unwrap_or(Self::DEFAULT_COMPUTE_UNITS),
-            ),
-        )
+        self.compute_units
+            .unwrap_or(Self::DEFAULT_COMPUTE_UNITS)
+            .clamp(Self::MINIMUM_COMPUTE_UNITS, Self::MAXIMUM_COMPUTE_UNITS)
     }
 
+    /// The compute unit price, or the default, clamped to the accepted range.
     pub fn get_compute_unit_price(&self) -> u64 {
-        std::cmp::max(
-            Self::MINIMUM_COMPUTE_UNIT_PRICE,
-            std::cmp::min(
+        self.compute_unit_price
+            .unwrap_or(Self::DEFAULT_COMPUTE_UNIT_PRICE)
+            .clamp(
+                Self::MINIMUM_COMPUTE_UNIT_PRICE,
                 Self::MAXIMUM_COMPUTE_UNIT_PRICE,
-                self.compute_unit_price
-                    .unwrap_or(Self::DEFAULT_COMPUTE_UNIT_PRICE),
-            ),
-        )
+            )
     }
 
-    pub fn get_priority_fee_lamports(&self) -> u64 {
-        // 1_000_000 compute units * 1 micro_lamports per compute unit
-        // 1 micro_lamports per compute unit * 1_000_000 compute units = 1_000_000 micro_lamports
-        // 1_000_000 micro_lamports / 1_000_000 micro_lamports per lamport = 1 lamport
-
-        // 1_000_000 compute units * 1 micro_lamports per compute unit = 1 lamports
-        // 1_000_000 compute units * 1000 micro_lamports per compute unit = 1_000 lamports
+    /// The priority fee of the callback transaction in micro-lamports, the exact product of
+    /// the clamped compute units and compute unit price, or `None` if it overflows a u64.
+    pub fn get_priority_fee_micro_lamports(&self) -> Option<u64> {
+        u64::from(self.get_compute_units()).checked_mul(self.get_compute_unit_price())
+    }
 
-        (u64::from(self.get_compute_units()) * self.get_compute_unit_price()) / 1_000_000
+    /// The priority fee of the callback transaction in whole lamports, rounded up so a budget
+    /// built from it covers the fee, or `None` if it overflows a u64. A fee of 200,000
+    /// compute units at 3 micro-lamports, 600,000 micro-lamports, is 1 lamport.
+    pub fn get_priority_fee_lamports(&self) -> Option<u64> {
+        self.get_priority_fee_micro_lamports()
+            .map(|fee| fee.div_ceil(MICRO_LAMPORTS_PER_LAMPORT))
     }
 
     pub fn to_vec(&self) -> Result<Vec<u8>, ProgramError> {
@@ -56,6 +244,40 @@ impl TransactionOptions {
     }
 }
 
+/// The field of [`TransactionOptions`] that [`TransactionOptions::validate`] found out of range,
+/// with the range the service accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransactionOptionsError {
+    ComputeUnitsOutOfRange { value: u32, min: u32, max: u32 },
+    ComputeUnitPriceOutOfRange { value: u64, min: u64, max: u64 },
+}
+
+impl std::fmt::Display for TransactionOptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionOptionsError::ComputeUnitsOutOfRange { value, min, max } => write!(
+                f,
+                "Compute units {} are outside the accepted {} to {}",
+                value, min, max
+            ),
+            TransactionOptionsError::ComputeUnitPriceOutOfRange { value, min, max } => write!(
+                f,
+                "Compute unit price {} is outside the accepted {} to {} micro-lamports",
+                value, min, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TransactionOptionsError {}
+
+impl From<TransactionOptionsError> for ProgramError {
+    fn from(_: TransactionOptionsError) -> Self {
+        LiteError::InvalidTransactionOptions.into()
+    }
+}
+
 #[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
 pub struct AccountMetaBorsh {
     pub pubkey: Pubkey,
@@ -88,6 +310,26 @@ pub struct Callback {
     pub ix_data: Vec<u8>,
 }
 impl Callback {
+    /// The most accounts a callback can list, [`MAX_CALLBACK_ACCOUNTS`].
+    pub const MAX_ACCOUNTS: usize = MAX_CALLBACK_ACCOUNTS;
+    /// The longest callback instruction data, [`MAX_CALLBACK_IX_DATA_LEN`].
+    pub const MAX_IX_DATA_LEN: usize = MAX_CALLBACK_IX_DATA_LEN;
+    /// The serialized size of a callback at both limits, the most a callback adds to an
+    /// account storing it.
+    pub const MAX_LEN: usize = 32 + 4 + Self::MAX_ACCOUNTS * 34 + 4 + Self::MAX_IX_DATA_LEN;
+
+    /// The largest transaction the cluster accepts, in bytes.
+    pub const MAX_TRANSACTION_SIZE: usize = 1232;
+    /// The signatures on the oracle's settle transaction: its payer and its enclave signer.
+    pub const SETTLE_SIGNATURES: usize = 2;
+    /// The accounts of the service's settle instruction, before the callback accounts.
+    pub const SETTLE_INSTRUCTION_ACCOUNTS: usize = 13;
+    /// The programs the settle transaction invokes: the service and the compute budget program.
+    pub const SETTLE_PROGRAMS: usize = 2;
+    /// The compiled size of the settle transaction's set compute unit limit and price
+    /// instructions.
+    pub const SETTLE_COMPUTE_BUDGET_SIZE: usize = 20;
+
     pub fn new(program_id: Pubkey, accounts: Vec<AccountMetaBorsh>, ix_data: Vec<u8>) -> Self {
         Self {
             program_id,
@@ -95,9 +337,226 @@ impl Callback {
             ix_data,
         }
     }
+    /// Starts a [`CallbackBuilder`] for a callback into `program_id`.
+    pub fn builder(program_id: Pubkey) -> CallbackBuilder {
+        CallbackBuilder::new(program_id)
+    }
+
     pub fn to_vec(&self) -> Result<Vec<u8>, ProgramError> {
         to_vec(self).map_err(|e| ProgramError::BorshIoError(format!("Serialization failed: {}", e)))
     }
+
+    /// The length of the callback's Borsh encoding, without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        32 + 4 + self.accounts.len() * 34 + 4 + self.ix_data.len()
+    }
+
+    /// The 8 byte discriminator at the start of `ix_data`, or `None` if it is shorter.
+    pub fn discriminator(&self) -> Option<[u8; 8]> {
+        ix_data_parts(&self.ix_data).map(|(discriminator, _)| *discriminator)
+    }
+
+    /// The arguments after the discriminator, or `None` if `ix_data` is shorter than one.
+    pub fn args(&self) -> Option<&[u8]> {
+        ix_data_parts(&self.ix_data).map(|(_, args)| args)
+    }
+
+    /// Replaces the 8 byte discriminator at the start of `ix_data`, leaving any arguments after
+    /// it untouched. Used to move callbacks configured with hardcoded bytes onto
+    /// [`anchor_sighash`].
+    pub fn rewrite_discriminator(&mut self, new: [u8; 8]) -> Result<(), ProgramError> {
+        let (_, args) =
+            ix_data_parts(&self.ix_data).ok_or(LiteError::CallbackDiscriminatorMissing)?;
+        self.ix_data = [new.as_slice(), args].concat();
+        Ok(())
+    }
+
+    /// Checks the callback against [`MAX_ACCOUNTS`](Self::MAX_ACCOUNTS) and
+    /// [`MAX_IX_DATA_LEN`](Self::MAX_IX_DATA_LEN), then checks the signer flags of the callback
+    /// accounts: the randomness service state must be a signer and no other account may be.
+    ///
+    /// The service invokes the callback signed only by its state PDA, so any other signer meta
+    /// fails at settlement with a privilege escalation error. That includes the consumer's own
+    /// PDAs. The oracle cannot sign for them and neither can the service's CPI. A callback that
+    /// needs its PDA to sign an inner CPI lists it as a plain account and signs with
+    /// `invoke_signed` itself. Use [`allow_pda_signer`](Self::allow_pda_signer) to fix up a
+    /// callback that lists it as a signer.
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        self.check_limits()?;
+        self.check_signers(&RANDOMNESS_SERVICE_STATE)?;
+        Ok(())
+    }
+
+    /// Like [`validate`](Self::validate), for the service at `addresses`: its `state` must be
+    /// listed as a signer, failing with [`LiteError::CallbackStateNotSigner`] if it is missing
+    /// or unsigned, and no other account may be. Use it to require the state, and for a
+    /// deployment with overridden addresses, whose state [`validate`](Self::validate) would
+    /// reject as a signer.
+    pub fn validate_for(&self, addresses: &ServiceAddresses) -> Result<(), ProgramError> {
+        self.check_limits()?;
+        if !self.check_signers(&addresses.state)? {
+            msg!(
+                "Callback does not list the randomness service state {}",
+                addresses.state
+            );
+            return Err(LiteError::CallbackStateNotSigner.into());
+        }
+        Ok(())
+    }
+
+    /// Checks `state` is a signer if listed and no other account is, returning whether it is
+    /// listed.
+    fn check_signers(&self, state: &Pubkey) -> Result<bool, ProgramError> {
+        let mut state_listed = false;
+        for account in self.accounts.iter() {
+            if account.pubkey == *state {
+                if !account.is_signer {
+                    return Err(LiteError::CallbackStateNotSigner.into());
+                }
+                state_listed = true;
+            } else if account.is_signer {
+                msg!("Callback account {} cannot be a signer", account.pubkey);
+                return Err(LiteError::CallbackSignerNotAllowed.into());
+            }
+        }
+        Ok(state_listed)
+    }
+
+    /// Checks the callback against [`MAX_ACCOUNTS`](Self::MAX_ACCOUNTS) and
+    /// [`MAX_IX_DATA_LEN`](Self::MAX_IX_DATA_LEN), the part of [`validate`](Self::validate)
+    /// the request instruction builders run.
+    pub fn check_limits(&self) -> Result<(), ProgramError> {
+        if self.accounts.len() > Self::MAX_ACCOUNTS {
+            msg!(
+                "Callback lists {} accounts, the maximum is {}",
+                self.accounts.len(),
+                Self::MAX_ACCOUNTS
+            );
+            return Err(LiteError::CallbackTooManyAccounts.into());
+        }
+        if self.ix_data.len() > Self::MAX_IX_DATA_LEN {
+            msg!(
+                "Callback instruction data is {} bytes, the maximum is {}",
+                self.ix_data.len(),
+                Self::MAX_IX_DATA_LEN
+            );
+            return Err(LiteError::CallbackDataTooLarge.into());
+        }
+        Ok(())
+    }
+
+    /// A conservative estimate of the size of the oracle's legacy settle transaction for this
+    /// callback and `num_bytes` of randomness: [`SETTLE_SIGNATURES`](Self::SETTLE_SIGNATURES),
+    /// the fixed settle and program keys, each distinct callback account, the compute budget
+    /// instructions, and the settle instruction with the callback accounts appended and the
+    /// randomness as its argument. The callback's `ix_data` is read from the request account,
+    /// so it does not count.
+    pub fn settle_transaction_size(&self, num_bytes: u8) -> usize {
+        // The state and the callback program are already among the settle accounts
+        let mut extra_keys: Vec<&Pubkey> = self
+            .accounts
+            .iter()
+            .map(|account| &account.pubkey)
+            .filter(|pubkey| **pubkey != RANDOMNESS_SERVICE_STATE && **pubkey != self.program_id)
+            .collect();
+        extra_keys.sort();
+        extra_keys.dedup();
+        Self::settle_size(
+            extra_keys.len(),
+            self.accounts.len(),
+            usize::from(num_bytes),
+        )
+    }
+
+    /// The [`settle_transaction_size`](Self::settle_transaction_size) with `extra_keys` keys
+    /// besides the settle accounts, `callback_accounts` accounts appended to the settle
+    /// instruction, and `num_bytes` of randomness.
+    const fn settle_size(extra_keys: usize, callback_accounts: usize, num_bytes: usize) -> usize {
+        let keys = Self::SETTLE_INSTRUCTION_ACCOUNTS + Self::SETTLE_PROGRAMS + extra_keys;
+        let settle_accounts = Self::SETTLE_INSTRUCTION_ACCOUNTS + callback_accounts;
+        // The discriminator and the Borsh encoded randomness
+        let settle_data = 8 + 4 + num_bytes;
+
+        compact_len(Self::SETTLE_SIGNATURES)
+            + Self::SETTLE_SIGNATURES * 64
+            // The message header
+            + 3
+            + compact_len(keys)
+            + keys * 32
+            // The recent blockhash
+            + 32
+            + compact_len(3)
+            + Self::SETTLE_COMPUTE_BUDGET_SIZE
+            + 1
+            + compact_len(settle_accounts)
+            + settle_accounts
+            + compact_len(settle_data)
+            + settle_data
+    }
+
+    /// Fails with [`LiteError::CallbackTooLargeForSettlement`] if the
+    /// [`settle_transaction_size`](Self::settle_transaction_size) is over
+    /// [`MAX_TRANSACTION_SIZE`](Self::MAX_TRANSACTION_SIZE). The oracle could never send the
+    /// settlement, so the request would sit unfulfilled with its fee in escrow.
+    pub fn check_size(&self, num_bytes: u8) -> Result<(), ProgramError> {
+        let size = self.settle_transaction_size(num_bytes);
+        if size > Self::MAX_TRANSACTION_SIZE {
+            msg!(
+                "The settle transaction for the callback would be {} bytes, the maximum is {}",
+                size,
+                Self::MAX_TRANSACTION_SIZE
+            );
+            return Err(LiteError::CallbackTooLargeForSettlement.into());
+        }
+        Ok(())
+    }
+
+    /// Accepts a consumer PDA listed as a signer of the callback by clearing its signer flag,
+    /// logging a warning, so the callback passes [`validate`](Self::validate). The PDA still
+    /// reaches the callback and can sign the callback's own CPIs through `invoke_signed`.
+    ///
+    /// Fails with [`LiteError::CallbackSignerOnCurve`] if the key is a wallet, since the
+    /// callback cannot sign for it. The curve check is skipped on-chain, where it has no
+    /// syscall-free implementation. Clearing the flag of a wallet there only drops a
+    /// signature the oracle could never have provided.
+    pub fn allow_pda_signer(&mut self, pubkey: &Pubkey) -> Result<(), ProgramError> {
+        #[cfg(not(target_os = "solana"))]
+        if pubkey.is_on_curve() {
+            return Err(LiteError::CallbackSignerOnCurve.into());
+        }
+
+        for account in self.accounts.iter_mut() {
+            if account.pubkey == *pubkey && account.is_signer {
+                msg!(
+                    "Warning: clearing the signer flag of callback account {}, the oracle cannot sign for a PDA",
+                    pubkey
+                );
+                account.is_signer = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The length of a `compact-u16` encoding of `value`, as used for the lengths in a
+/// transaction.
+const fn compact_len(value: usize) -> usize {
+    match value {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// Splits callback instruction data into its 8 byte discriminator and the arguments after it,
+/// or `None` if it is too short to hold a discriminator. Every helper that reads `ix_data` goes
+/// through this, since the data comes from accounts anyone can create.
+pub(crate) fn ix_data_parts(ix_data: &[u8]) -> Option<(&[u8; 8], &[u8])> {
+    if ix_data.len() < 8 {
+        return None;
+    }
+    let (discriminator, args) = ix_data.split_at(8);
+    Some((discriminator.try_into().ok()?, args))
 }
 
 impl From<AccountMetaBorsh> for AccountMeta {
@@ -0,0 +1,58 @@
+use crate::*;
+use solana_program::msg;
+
+/// Checks a callback was invoked by the randomness service for a request made by
+/// `expected_program_id`, so only the oracle can deliver randomness to it.
+///
+/// Call it first thing in the callback with the state and request accounts the callback was
+/// registered with. It checks that:
+///
+/// - `state` signed the instruction, failing with [`LiteError::CallbackStateNotSigner`]. Only
+///   the service can sign for its state PDA, and only while it settles a request.
+/// - `state` is owned by the service, failing with [`ProgramError::IllegalOwner`], and is the
+///   `[b"STATE"]` PDA [`RANDOMNESS_SERVICE_STATE`], failing with
+///   [`ProgramError::InvalidSeeds`].
+/// - `request` is a request account, loaded like [`SimpleRandomnessV1AccountView::load`] with
+///   its errors, whose stored callback invokes `expected_program_id`, failing with
+///   [`LiteError::CallbackProgramMismatch`].
+///
+/// It reads the request in place and does not allocate. Works the same from Anchor, where the
+/// `ProgramError` converts with `?`:
+///
+/// ```ignore
+/// verify_callback(&ctx.accounts.randomness_state, &ctx.accounts.randomness_request, &ID)?;
+/// ```
+pub fn verify_callback(
+    state: &AccountInfo,
+    request: &AccountInfo,
+    expected_program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    if !state.is_signer {
+        msg!("The randomness service state {} did not sign", state.key);
+        return Err(LiteError::CallbackStateNotSigner.into());
+    }
+    if *state.owner != ID {
+        msg!("The randomness service state is owned by {}", state.owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+    if *state.key != RANDOMNESS_SERVICE_STATE {
+        msg!(
+            "Expected the randomness service state {}, received {}",
+            RANDOMNESS_SERVICE_STATE,
+            state.key
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let program_id = SimpleRandomnessV1AccountView::load(request)?.callback_program_id()?;
+    if program_id != *expected_program_id {
+        msg!(
+            "Request {} calls back {}, not {}",
+            request.key,
+            program_id,
+            expected_program_id
+        );
+        return Err(LiteError::CallbackProgramMismatch.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+/// The version of this crate, for reporting which release built a request or explained an
+/// error.
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A crate version packed into two bytes, the major version in the high byte and the minor
+/// version in the low byte. Patch releases do not change the encoding of requests, so they
+/// share a code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VersionCode(pub u16);
+
+impl VersionCode {
+    /// The code of the running crate version.
+    pub const CURRENT: Self = Self::from_parts(
+        parse_u8(env!("CARGO_PKG_VERSION_MAJOR")),
+        parse_u8(env!("CARGO_PKG_VERSION_MINOR")),
+    );
+
+    pub const fn from_parts(major: u8, minor: u8) -> Self {
+        Self(((major as u16) << 8) | minor as u16)
+    }
+
+    /// Parses the major and minor version of a `major.minor.patch` version string. Returns
+    /// `None` if either does not fit a byte.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some(Self::from_parts(major, minor))
+    }
+
+    pub fn major(&self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub fn minor(&self) -> u8 {
+        self.0 as u8
+    }
+}
+
+impl std::fmt::Display for VersionCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "lite {}.{}.x", self.major(), self.minor())
+    }
+}
+
+/// Parses a version component at compile time. Components over 255 fail the build.
+const fn parse_u8(value: &str) -> u8 {
+    let bytes = value.as_bytes();
+    let mut result: u16 = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        result = result * 10 + (bytes[i] - b'0') as u16;
+        assert!(
+            result <= u8::MAX as u16,
+            "version component does not fit a byte"
+        );
+        i += 1;
+    }
+    result as u8
+}
@@ -0,0 +1,127 @@
+use crate::*;
+use std::cell::Ref;
+
+/// A read-only view of a [`SimpleRandomnessV1Account`] for callbacks, reading the
+/// [`RequestHeader`] fields at their fixed offsets in the borrowed account data.
+///
+/// Unlike deserializing the account, loading a view does not copy the callback or error
+/// message and does not allocate, so a callback that only needs the requester or the request
+/// slot pays for the bytes it reads. The account data stays borrowed until the view is dropped.
+pub struct SimpleRandomnessV1AccountView<'a> {
+    data: Ref<'a, [u8]>,
+}
+
+impl<'a> SimpleRandomnessV1AccountView<'a> {
+    /// Borrows a request account, checking it is owned by the service and is a request.
+    pub fn load(info: &'a AccountInfo<'_>) -> Result<Self, ProgramError> {
+        if *info.owner != ID {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let data = Ref::map(info.try_borrow_data()?, |data| &**data);
+        if data.get(..8) != Some(&SimpleRandomnessV1Account::DISCRIMINATOR[..]) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data.len() < RequestHeader::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(Self { data })
+    }
+
+    /// Whether the oracle has marked the request as failed.
+    pub fn is_completed(&self) -> bool {
+        self.data[RequestHeader::IS_COMPLETED_OFFSET] != 0
+    }
+
+    pub fn num_bytes(&self) -> u8 {
+        self.data[RequestHeader::NUM_BYTES_OFFSET]
+    }
+
+    pub fn user(&self) -> Pubkey {
+        Pubkey::new_from_array(self.array(RequestHeader::USER_OFFSET))
+    }
+
+    pub fn escrow(&self) -> Pubkey {
+        Pubkey::new_from_array(self.array(RequestHeader::ESCROW_OFFSET))
+    }
+
+    pub fn request_slot(&self) -> u64 {
+        u64::from_le_bytes(self.array(RequestHeader::REQUEST_SLOT_OFFSET))
+    }
+
+    /// [`is_retry_attempt`](crate::is_retry_attempt) for the viewed request.
+    pub fn is_retry_attempt<S: SlotSource + ?Sized>(&self, clock: &S) -> bool {
+        crate::retry::is_retry_slot(self.request_slot(), clock.slot())
+    }
+
+    /// The program the request's callback invokes, stored right after the header. Fails with
+    /// [`ProgramError::AccountDataTooSmall`] if the data ends before it.
+    pub fn callback_program_id(&self) -> Result<Pubkey, ProgramError> {
+        self.data
+            .get(RequestHeader::LEN..RequestHeader::LEN + 32)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Pubkey::new_from_array)
+            .ok_or(ProgramError::AccountDataTooSmall)
+    }
+
+    /// Copies every header field out of the view.
+    pub fn header(&self) -> RequestHeader {
+        RequestHeader {
+            is_completed: self.data[RequestHeader::IS_COMPLETED_OFFSET],
+            num_bytes: self.num_bytes(),
+            user: self.user(),
+            escrow: self.escrow(),
+            request_slot: self.request_slot(),
+        }
+    }
+
+    /// `load` checked the data spans the header, so every field offset is in bounds.
+    fn array<const N: usize>(&self, offset: usize) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.data[offset..offset + N]);
+        bytes
+    }
+}
+
+/// A read-only view of the service's [`ServiceState`] for checks before a request, reading
+/// `mint`, `wallet`, and `cost_per_byte` at their fixed offsets without deserializing the rest
+/// of the state.
+pub struct ServiceStateView<'a> {
+    data: Ref<'a, [u8]>,
+}
+
+impl<'a> ServiceStateView<'a> {
+    /// Borrows the state account, checking it is owned by the service and is its state.
+    pub fn load(info: &'a AccountInfo<'_>) -> Result<Self, ProgramError> {
+        if *info.owner != ID {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let data = Ref::map(info.try_borrow_data()?, |data| &**data);
+        ServiceState::read_cost_per_byte(&data)?;
+        Ok(Self { data })
+    }
+
+    /// The mint the service takes its fee in and creates escrows for. `load` checked the data
+    /// spans the whole state.
+    pub fn mint(&self) -> Pubkey {
+        self.pubkey_at(ServiceState::MINT_OFFSET)
+    }
+
+    /// The token account the service's fees are paid to.
+    pub fn wallet(&self) -> Pubkey {
+        self.pubkey_at(ServiceState::WALLET_OFFSET)
+    }
+
+    /// The lamports the service charges per requested byte, on top of the callback's fees.
+    pub fn cost_per_byte(&self) -> u64 {
+        let offset = ServiceState::COST_PER_BYTE_OFFSET;
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.data[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn pubkey_at(&self, offset: usize) -> Pubkey {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(&self.data[offset..offset + 32]);
+        Pubkey::new_from_array(bytes)
+    }
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_randomness_service_lite::*;
+use std::cell::RefCell;
+use std::sync::Once;
+
+/// An invocation captured by the stubs: the instruction, the keys of the account infos, and
+/// the number of signer seed sets.
+type Invocation = (Instruction, Vec<Pubkey>, usize);
+
+thread_local! {
+    static INVOCATIONS: RefCell<Vec<Invocation>> = const { RefCell::new(Vec::new()) };
+}
+
+struct CaptureInvocations;
+
+impl SyscallStubs for CaptureInvocations {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let keys = account_infos.iter().map(|info| *info.key).collect();
+        INVOCATIONS.with(|invocations| {
+            invocations
+                .borrow_mut()
+                .push((instruction.clone(), keys, signers_seeds.len()))
+        });
+        Ok(())
+    }
+}
+
+/// Runs `f` and returns the invocations it made.
+fn capture(f: impl FnOnce() -> ProgramResult) -> Vec<Invocation> {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(CaptureInvocations));
+    });
+
+    INVOCATIONS.with(|invocations| invocations.borrow_mut().clear());
+    f().unwrap();
+    INVOCATIONS.with(|invocations| invocations.take())
+}
+
+struct Accounts {
+    keys: Vec<Pubkey>,
+    lamports: Vec<u64>,
+    data: Vec<Vec<u8>>,
+}
+
+impl Accounts {
+    fn new(len: usize) -> Self {
+        Self {
+            keys: (0..len).map(|_| Pubkey::new_unique()).collect(),
+            lamports: vec![0; len],
+            data: vec![Vec::new(); len],
+        }
+    }
+
+    fn infos(&mut self) -> Vec<AccountInfo<'_>> {
+        self.keys
+            .iter()
+            .zip(self.lamports.iter_mut())
+            .zip(self.data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, false, lamports, data, key, false, 0)
+            })
+            .collect()
+    }
+}
+
+/// A program entrypoint in the `solana-program` style, borrowing the request accounts from the
+/// instruction's account slice.
+fn process_request(accounts: &[AccountInfo], callback: &Callback) -> ProgramResult {
+    let (program, accounts) = accounts.split_last().unwrap();
+    let refs = SimpleRandomnessV1AccountRefs::from_slice(accounts)?;
+    SimpleRandomnessV1Request::cpi_borrowed(&refs, program, 8, callback, None)
+}
+
+#[test]
+fn test_borrowed_invoke_matches_owned() {
+    let mut accounts = Accounts::new(9);
+    let infos = accounts.infos();
+    let callback = callback();
+
+    let borrowed = capture(|| process_request(&infos, &callback));
+    let owned = capture(|| {
+        let request = SimpleRandomnessV1Request {
+            request: infos[0].clone(),
+            escrow: infos[1].clone(),
+            state: infos[2].clone(),
+            mint: infos[3].clone(),
+            payer: infos[4].clone(),
+            system_program: infos[5].clone(),
+            token_program: infos[6].clone(),
+            associated_token_program: infos[7].clone(),
+        };
+        request.cpi(infos[8].clone(), 8, &callback, None)
+    });
+
+    assert_eq!(borrowed, owned);
+    let (instruction, keys, seeds) = &borrowed[0];
+    assert_eq!(instruction.program_id, *infos[8].key);
+    assert_eq!(keys[..], accounts.keys[..8]);
+    assert_eq!(*seeds, 0);
+}
+
+#[test]
+fn test_borrowed_invoke_signed_passes_seeds() {
+    let mut accounts = Accounts::new(9);
+    let infos = accounts.infos();
+    let refs = SimpleRandomnessV1AccountRefs::from_slice(&infos[..8]).unwrap();
+
+    let invocations = capture(|| {
+        SimpleRandomnessV1Request::cpi_signed_borrowed(
+            &refs,
+            &infos[8],
+            8,
+            &callback(),
+            None,
+            &[&[b"PAYER", &[255]]],
+        )
+    });
+
+    assert_eq!(invocations.len(), 1);
+    assert_eq!(invocations[0].2, 1);
+}
+
+#[test]
+fn test_from_slice_requires_eight_accounts() {
+    let mut accounts = Accounts::new(7);
+    let infos = accounts.infos();
+
+    assert_eq!(
+        SimpleRandomnessV1AccountRefs::from_slice(&infos).err(),
+        Some(ProgramError::NotEnoughAccountKeys)
+    );
+}
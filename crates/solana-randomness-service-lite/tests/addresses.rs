@@ -0,0 +1,142 @@
+use solana_randomness_service_lite::*;
+
+#[test]
+fn test_builtin_addresses_are_consistent() {
+    assert_eq!(
+        ServiceAddresses::mainnet().verify_address_consistency(),
+        Ok(())
+    );
+    assert_eq!(
+        ServiceAddresses::devnet().verify_address_consistency(),
+        Ok(())
+    );
+    assert_eq!(ServiceAddresses::default(), ServiceAddresses::mainnet());
+}
+
+#[test]
+fn test_partial_override_replaces_function() {
+    let function = Pubkey::new_unique();
+    let addresses = ServiceAddresses::mainnet()
+        .with_overrides(AddressOverrides {
+            switchboard_function: Some(function),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(addresses.switchboard_function, function);
+    assert_eq!(
+        ServiceAddresses {
+            switchboard_function: MAINNET_SWITCHBOARD_FUNCTION,
+            ..addresses
+        },
+        ServiceAddresses::mainnet()
+    );
+}
+
+#[test]
+fn test_empty_override_keeps_builtin_addresses() {
+    assert!(AddressOverrides::default().is_empty());
+    assert_eq!(
+        ServiceAddresses::devnet().with_overrides(AddressOverrides::default()),
+        Ok(ServiceAddresses::devnet())
+    );
+}
+
+#[test]
+fn test_rejects_program_override_without_state() {
+    let program_id = Pubkey::new_unique();
+    let (state, _) = Pubkey::find_program_address(&[b"STATE"], &program_id);
+
+    assert_eq!(
+        ServiceAddresses::mainnet().with_overrides(AddressOverrides {
+            program_id: Some(program_id),
+            ..Default::default()
+        }),
+        Err(AddressError::StateMismatch {
+            expected: state,
+            actual: RANDOMNESS_SERVICE_STATE,
+        })
+    );
+
+    // Moving the state along with the program is consistent
+    let addresses = ServiceAddresses::mainnet()
+        .with_overrides(AddressOverrides {
+            program_id: Some(program_id),
+            state: Some(state),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(addresses.state, state);
+}
+
+#[test]
+fn test_rejects_non_native_reward_mint() {
+    let mint = Pubkey::new_unique();
+
+    assert_eq!(
+        ServiceAddresses::mainnet().with_overrides(AddressOverrides {
+            reward_mint: Some(mint),
+            ..Default::default()
+        }),
+        Err(AddressError::RewardMintNotNative(mint))
+    );
+}
+
+#[test]
+fn test_rejects_matching_switchboard_accounts() {
+    let result = ServiceAddresses::mainnet().with_overrides(AddressOverrides {
+        switchboard_service: Some(MAINNET_SWITCHBOARD_FUNCTION),
+        ..Default::default()
+    });
+
+    assert_eq!(
+        result,
+        Err(AddressError::SwitchboardAccountsEqual(
+            MAINNET_SWITCHBOARD_FUNCTION
+        ))
+    );
+}
+
+#[test]
+fn test_derive_state_pubkey() {
+    let (state, bump) = derive_state_pubkey(&ID);
+    assert_eq!(state, RANDOMNESS_SERVICE_STATE);
+    assert_eq!(
+        Pubkey::create_program_address(&[STATE_SEED, &[bump]], &ID),
+        Ok(state)
+    );
+
+    // A service deployed elsewhere has its own state
+    let program_id = Pubkey::new_unique();
+    let (other, _) = derive_state_pubkey(&program_id);
+    assert_ne!(other, RANDOMNESS_SERVICE_STATE);
+    assert_eq!(
+        ServiceAddresses {
+            program_id,
+            state: other,
+            ..ServiceAddresses::mainnet()
+        }
+        .verify_address_consistency(),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_derive_escrow_pubkey() {
+    let request = Pubkey::new_unique();
+    let escrow = derive_escrow_pubkey(&request, &RANDOMNESS_SERVICE_REWARD_MINT);
+
+    assert!(!escrow.is_on_curve());
+    assert_eq!(
+        escrow,
+        derive_escrow_pubkey(&request, &RANDOMNESS_SERVICE_REWARD_MINT)
+    );
+    assert_ne!(
+        escrow,
+        derive_escrow_pubkey(&request, &Pubkey::new_unique())
+    );
+    assert_ne!(
+        escrow,
+        derive_escrow_pubkey(&Pubkey::new_unique(), &RANDOMNESS_SERVICE_REWARD_MINT)
+    );
+}
@@ -0,0 +1,369 @@
+//! A consumer's accounts struct typed with only this crate in the dependency tree. The
+//! `solana-randomness-service` crate is not a dependency of this package, so this file failing
+//! to compile means the lite types no longer cover the doc example.
+
+use anchor_lang::prelude::*;
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::anchor::*;
+use solana_randomness_service_lite::{
+    derive_escrow_pubkey, derive_state_pubkey, Callback, LiteError, ServiceState,
+    SimpleRandomnessV1Account, SimpleRandomnessV1Request, ID as SolanaRandomnessServiceID,
+    RANDOMNESS_SERVICE_REWARD_MINT, RANDOMNESS_SERVICE_STATE,
+};
+use std::collections::BTreeSet;
+
+const TOKEN_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    /// CHECK: manually check programID and executable status
+    #[account(
+        constraint = randomness_service.key() == SolanaRandomnessServiceID,
+        constraint = randomness_service.executable,
+    )]
+    pub randomness_service: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// CHECK: checked by hand with `LiteAccount::load`
+    pub randomness_request: UncheckedAccount<'info>,
+}
+
+fn state_data(state: &ServiceState) -> Vec<u8> {
+    let mut data = ServiceState::DISCRIMINATOR.to_vec();
+    data.extend(state.try_to_vec().unwrap());
+    data
+}
+
+#[test]
+fn test_account_loads_service_state() {
+    let state = ServiceState {
+        bump: 254,
+        cost_per_byte: 100,
+        ..Default::default()
+    };
+    let mut lamports = 1_000_000;
+    let mut data = state_data(&state);
+    let owner = SolanaRandomnessServiceID;
+    let info = AccountInfo::new(
+        &RANDOMNESS_SERVICE_STATE,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+
+    let account: Box<Account<ServiceState>> = Box::new(Account::try_from(&info).unwrap());
+    assert_eq!(account.bump, 254);
+    assert_eq!(account.cost_per_byte, 100);
+}
+
+#[test]
+fn test_account_rejects_wrong_owner_and_discriminator() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 1_000_000;
+    let mut data = state_data(&ServiceState::default());
+    let owner = Pubkey::new_unique();
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    assert!(Account::<ServiceState>::try_from(&info).is_err());
+    assert!(ServiceState::load(&info).is_err());
+
+    let mut lamports = 1_000_000;
+    let mut data = state_data(&ServiceState::default());
+    let owner = SolanaRandomnessServiceID;
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    assert!(SimpleRandomnessV1Account::load(&info).is_err());
+    assert!(ServiceState::load(&info).is_ok());
+}
+
+/// A consumer's request instruction composing the exported accounts.
+#[derive(Accounts)]
+pub struct RequestWithComposedAccounts<'info> {
+    pub randomness: SimpleRandomnessV1<'info>,
+}
+
+/// One account of a `SimpleRandomnessV1`, in field order.
+struct TestAccount {
+    key: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+    executable: bool,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, owner: Pubkey) -> Self {
+        Self {
+            key,
+            lamports: 0,
+            data: vec![],
+            owner,
+            is_signer: false,
+            is_writable: false,
+            executable: false,
+        }
+    }
+
+    fn program(key: Pubkey) -> Self {
+        Self {
+            executable: true,
+            ..Self::new(key, anchor_lang::solana_program::bpf_loader::ID)
+        }
+    }
+}
+
+/// The accounts of a valid request for a new request keypair.
+fn request_accounts() -> Vec<TestAccount> {
+    let request = Pubkey::new_unique();
+    let mint = RANDOMNESS_SERVICE_REWARD_MINT;
+    let state = ServiceState {
+        bump: derive_state_pubkey(&SolanaRandomnessServiceID).1,
+        mint,
+        ..Default::default()
+    };
+    let system = anchor_lang::system_program::ID;
+
+    vec![
+        TestAccount::program(SolanaRandomnessServiceID),
+        TestAccount {
+            is_signer: true,
+            is_writable: true,
+            ..TestAccount::new(request, system)
+        },
+        TestAccount {
+            is_writable: true,
+            ..TestAccount::new(derive_escrow_pubkey(&request, &mint), system)
+        },
+        TestAccount {
+            lamports: 1_000_000,
+            data: state_data(&state),
+            ..TestAccount::new(RANDOMNESS_SERVICE_STATE, SolanaRandomnessServiceID)
+        },
+        TestAccount::new(mint, Pubkey::new_unique()),
+        TestAccount {
+            lamports: 1_000_000_000,
+            is_signer: true,
+            is_writable: true,
+            ..TestAccount::new(Pubkey::new_unique(), system)
+        },
+        TestAccount::program(system),
+        TestAccount::program(TOKEN_PROGRAM_ID),
+        TestAccount::program(ASSOCIATED_TOKEN_PROGRAM_ID),
+    ]
+}
+
+fn account_infos(accounts: &mut [TestAccount]) -> Vec<AccountInfo<'_>> {
+    accounts
+        .iter_mut()
+        .map(|account| {
+            AccountInfo::new(
+                &account.key,
+                account.is_signer,
+                account.is_writable,
+                &mut account.lamports,
+                &mut account.data,
+                &account.owner,
+                account.executable,
+                0,
+            )
+        })
+        .collect()
+}
+
+fn try_request_accounts<'info>(
+    infos: &'info [AccountInfo<'info>],
+) -> anchor_lang::Result<RequestWithComposedAccounts<'info>> {
+    let consumer = Pubkey::new_unique();
+    RequestWithComposedAccounts::try_accounts(
+        &consumer,
+        &mut &infos[..],
+        &[],
+        &mut Default::default(),
+        &mut BTreeSet::new(),
+    )
+}
+
+#[test]
+fn test_composed_accounts_convert_to_request() {
+    let mut accounts = request_accounts();
+    let infos = account_infos(&mut accounts);
+
+    let ctx = try_request_accounts(&infos).unwrap();
+    let request = ctx.randomness.to_request();
+
+    assert_eq!(request.request.key, infos[1].key);
+    assert_eq!(request.escrow.key, infos[2].key);
+    assert_eq!(request.state.key, &RANDOMNESS_SERVICE_STATE);
+    assert_eq!(request.mint.key, &RANDOMNESS_SERVICE_REWARD_MINT);
+    assert_eq!(request.payer.key, infos[5].key);
+    assert_eq!(
+        SimpleRandomnessV1Request::from(&ctx.randomness)
+            .associated_token_program
+            .key,
+        infos[8].key
+    );
+    assert_eq!(ctx.randomness.to_account_metas(None).len(), infos.len());
+}
+
+#[test]
+fn test_composed_accounts_reject_bad_request_accounts() {
+    // The request must sign, be empty, and hold no lamports
+    let mut accounts = request_accounts();
+    accounts[1].is_signer = false;
+    assert!(try_request_accounts(&account_infos(&mut accounts)).is_err());
+
+    let mut accounts = request_accounts();
+    accounts[1].lamports = 1;
+    assert!(try_request_accounts(&account_infos(&mut accounts)).is_err());
+
+    // The escrow must be the request's associated token account for the state's mint
+    let mut accounts = request_accounts();
+    accounts[2].key = Pubkey::new_unique();
+    assert!(try_request_accounts(&account_infos(&mut accounts)).is_err());
+
+    let mut accounts = request_accounts();
+    accounts[4].key = Pubkey::new_unique();
+    assert!(try_request_accounts(&account_infos(&mut accounts)).is_err());
+
+    // The service must be the randomness service program
+    let mut accounts = request_accounts();
+    accounts[0].key = Pubkey::new_unique();
+    assert!(try_request_accounts(&account_infos(&mut accounts)).is_err());
+}
+
+/// A consumer's callback composing the exported accounts.
+#[derive(Accounts)]
+pub struct CallbackWithComposedAccounts<'info> {
+    pub randomness: ConsumeRandomnessAccounts<'info>,
+}
+
+/// The state, signing, and a request calling back `consumer`.
+fn callback_accounts(consumer: Pubkey) -> Vec<TestAccount> {
+    let state = ServiceState {
+        bump: derive_state_pubkey(&SolanaRandomnessServiceID).1,
+        ..Default::default()
+    };
+    let request = SimpleRandomnessV1Account {
+        num_bytes: 8,
+        callback: Callback::new(consumer, vec![], vec![1; 8]),
+        ..Default::default()
+    };
+    let mut request_data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    request_data.extend(request.try_to_vec().unwrap());
+
+    vec![
+        TestAccount {
+            lamports: 1_000_000,
+            data: state_data(&state),
+            is_signer: true,
+            ..TestAccount::new(RANDOMNESS_SERVICE_STATE, SolanaRandomnessServiceID)
+        },
+        TestAccount {
+            lamports: 1_000_000,
+            data: request_data,
+            ..TestAccount::new(Pubkey::new_unique(), SolanaRandomnessServiceID)
+        },
+    ]
+}
+
+/// Loads the accounts and checks the request calls back `consumer`, as the callback would.
+fn try_callback_accounts<'info>(
+    consumer: &Pubkey,
+    infos: &'info [AccountInfo<'info>],
+) -> std::result::Result<CallbackWithComposedAccounts<'info>, ProgramError> {
+    let accounts = CallbackWithComposedAccounts::try_accounts(
+        consumer,
+        &mut &infos[..],
+        &[],
+        &mut Default::default(),
+        &mut BTreeSet::new(),
+    )?;
+    accounts.randomness.verify_program(consumer)?;
+    Ok(accounts)
+}
+
+#[test]
+fn test_composed_callback_accounts_accept_own_request() {
+    let consumer = Pubkey::new_unique();
+    let mut accounts = callback_accounts(consumer);
+    let infos = account_infos(&mut accounts);
+
+    let ctx = try_callback_accounts(&consumer, &infos).unwrap();
+
+    assert_eq!(ctx.randomness.state.key(), RANDOMNESS_SERVICE_STATE);
+    assert_eq!(ctx.randomness.request.key(), *infos[1].key);
+    assert_eq!(
+        ctx.randomness.state.bump,
+        derive_state_pubkey(&SolanaRandomnessServiceID).1
+    );
+}
+
+#[test]
+fn test_composed_callback_accounts_reject_unsigned_or_foreign_calls() {
+    let consumer = Pubkey::new_unique();
+    let error = |error: LiteError| Err(ProgramError::from(error));
+
+    // Anyone can pass the state, only the service can sign for it
+    let mut accounts = callback_accounts(consumer);
+    accounts[0].is_signer = false;
+    assert_eq!(
+        try_callback_accounts(&consumer, &account_infos(&mut accounts)).map(|_| ()),
+        error(LiteError::CallbackStateNotSigner)
+    );
+
+    // A request made by another program
+    let mut accounts = callback_accounts(Pubkey::new_unique());
+    assert_eq!(
+        try_callback_accounts(&consumer, &account_infos(&mut accounts)).map(|_| ()),
+        error(LiteError::CallbackProgramMismatch)
+    );
+
+    // Not a request account
+    let mut accounts = callback_accounts(consumer);
+    accounts[1].data = state_data(&ServiceState::default());
+    assert_eq!(
+        try_callback_accounts(&consumer, &account_infos(&mut accounts)).map(|_| ()),
+        error(LiteError::CallbackProgramMismatch)
+    );
+
+    // A copy of a request, owned by someone else
+    let mut accounts = callback_accounts(consumer);
+    accounts[1].owner = Pubkey::new_unique();
+    assert!(try_callback_accounts(&consumer, &account_infos(&mut accounts)).is_err());
+
+    // Another account than the state, even if it signs
+    let mut accounts = callback_accounts(consumer);
+    accounts[0].key = Pubkey::new_unique();
+    assert!(try_callback_accounts(&consumer, &account_infos(&mut accounts)).is_err());
+}
@@ -0,0 +1,191 @@
+//! Decodes request instructions and accounts in the current layout and in a synthetic older
+//! one registered through `ArchivalDecoder::with_layout`.
+
+use borsh::BorshDeserialize;
+use solana_randomness_service_lite::*;
+
+/// A request for 8 bytes in the current layout, with 200k compute units at a price of 100.
+const REQUEST_IX_V1: &[u8] = include_bytes!("fixtures/request_ix_v1.bin");
+/// The same request in the synthetic older layout, which had no options argument.
+const REQUEST_IX_V0: &[u8] = include_bytes!("fixtures/request_ix_v0.bin");
+/// A failed request account in the current layout.
+const REQUEST_ACCOUNT_V1: &[u8] = include_bytes!("fixtures/failed_request.bin");
+/// A pending request account in the synthetic older layout, made at slot 500, which ended
+/// after the callback and was not padded.
+const REQUEST_ACCOUNT_V0: &[u8] = include_bytes!("fixtures/request_account_v0.bin");
+
+/// The synthetic older layout, written up to slot 1,000.
+const V0: RequestLayout = RequestLayout {
+    version: RequestLayoutVersion::Registered(0),
+    first_slot: 0,
+    last_slot: Some(1_000),
+    decode_instruction: decode_v0_instruction,
+    decode_account: decode_v0_account,
+};
+
+#[derive(BorshDeserialize)]
+struct V0Account {
+    is_completed: u8,
+    num_bytes: u8,
+    user: Pubkey,
+    escrow: Pubkey,
+    request_slot: u64,
+    callback: Callback,
+}
+
+fn decode_v0_instruction(data: &[u8]) -> Option<RequestArgs> {
+    let args = data.strip_prefix(&SimpleRandomnessV1Request::DISCRIMINATOR[..])?;
+    let (num_bytes, callback) = <(u8, Callback)>::try_from_slice(args).ok()?;
+    Some((num_bytes, callback, None))
+}
+
+fn decode_v0_account(data: &[u8]) -> Option<SimpleRandomnessV1Account> {
+    let fields = data.strip_prefix(&SimpleRandomnessV1Account::DISCRIMINATOR[..])?;
+    let old = V0Account::try_from_slice(fields).ok()?;
+    Some(SimpleRandomnessV1Account {
+        is_completed: old.is_completed,
+        num_bytes: old.num_bytes,
+        user: old.user,
+        escrow: old.escrow,
+        request_slot: old.request_slot,
+        callback: old.callback,
+        ..Default::default()
+    })
+}
+
+fn decoder() -> ArchivalDecoder {
+    ArchivalDecoder::new().with_layout(V0)
+}
+
+#[test]
+fn test_known_layouts_keep_v1() {
+    assert!(KNOWN_REQUEST_LAYOUTS
+        .iter()
+        .any(|layout| layout.version == RequestLayoutVersion::V1));
+    assert_eq!(
+        decoder()
+            .layouts()
+            .iter()
+            .map(|layout| layout.version)
+            .collect::<Vec<_>>(),
+        vec![
+            RequestLayoutVersion::V1,
+            RequestLayoutVersion::Registered(0)
+        ]
+    );
+}
+
+#[test]
+fn test_decodes_current_instruction() {
+    let decoded = decode_any_request_instruction(REQUEST_IX_V1, None).unwrap();
+
+    assert_eq!(decoded.layout, RequestLayoutVersion::V1);
+    assert_eq!(decoded.num_bytes, 8);
+    let options = decoded.options.clone().unwrap();
+    assert_eq!(options.compute_units, Some(200_000));
+    assert_eq!(options.compute_unit_price, Some(100));
+    // The fixture is what the crate writes today
+    assert_eq!(
+        SimpleRandomnessV1Request::data(
+            decoded.num_bytes,
+            &decoded.callback,
+            decoded.options.as_ref()
+        )
+        .unwrap(),
+        REQUEST_IX_V1
+    );
+}
+
+#[test]
+fn test_decodes_older_instruction() {
+    assert_eq!(
+        decode_any_request_instruction(REQUEST_IX_V0, None).unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+
+    let decoded = decoder()
+        .decode_request_instruction(REQUEST_IX_V0, Some(500))
+        .unwrap();
+    assert_eq!(decoded.layout, RequestLayoutVersion::Registered(0));
+    assert_eq!(decoded.num_bytes, 8);
+    assert!(decoded.options.is_none());
+
+    let current = decode_any_request_instruction(REQUEST_IX_V1, None).unwrap();
+    assert_eq!(
+        decoded.callback.to_vec().unwrap(),
+        current.callback.to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_slot_hint_skips_layouts_not_live() {
+    assert_eq!(
+        decoder()
+            .decode_request_instruction(REQUEST_IX_V0, Some(1_001))
+            .unwrap_err(),
+        ProgramError::InvalidInstructionData
+    );
+    assert_eq!(
+        decoder()
+            .decode_request_instruction(REQUEST_IX_V1, Some(1_001))
+            .unwrap()
+            .layout,
+        RequestLayoutVersion::V1
+    );
+}
+
+#[test]
+fn test_decodes_current_account() {
+    let decoded = decode_any_request_account(REQUEST_ACCOUNT_V1).unwrap();
+    let request = SimpleRandomnessV1Account::try_deserialize(&mut &REQUEST_ACCOUNT_V1[..]).unwrap();
+
+    assert_eq!(decoded.layout, RequestLayoutVersion::V1);
+    assert_eq!(decoded.account.request_slot, request.request_slot);
+    assert_eq!(decoded.account.error_message, request.error_message);
+    assert_eq!(
+        decoder()
+            .decode_request_account(REQUEST_ACCOUNT_V1)
+            .unwrap()
+            .layout,
+        RequestLayoutVersion::V1
+    );
+}
+
+#[test]
+fn test_decodes_older_account() {
+    assert_eq!(
+        decode_any_request_account(REQUEST_ACCOUNT_V0).unwrap_err(),
+        ProgramError::InvalidAccountData
+    );
+
+    let decoded = decoder()
+        .decode_request_account(REQUEST_ACCOUNT_V0)
+        .unwrap();
+    assert_eq!(decoded.layout, RequestLayoutVersion::Registered(0));
+    assert_eq!(decoded.account.is_completed, 0);
+    assert_eq!(decoded.account.num_bytes, 8);
+    assert_eq!(decoded.account.request_slot, 500);
+    assert_eq!(decoded.account.callback.accounts.len(), 2);
+    // Fields the layout did not have are left at their defaults
+    assert_eq!(decoded.account.compute_units, 0);
+    assert!(decoded.account.error_message.is_empty());
+}
+
+#[test]
+fn test_account_outside_layout_slots_is_rejected() {
+    let mut data = REQUEST_ACCOUNT_V0.to_vec();
+    data[RequestHeader::REQUEST_SLOT_OFFSET..RequestHeader::LEN]
+        .copy_from_slice(&2_000u64.to_le_bytes());
+
+    assert_eq!(
+        decoder().decode_request_account(&data).unwrap_err(),
+        ProgramError::InvalidAccountData
+    );
+}
+
+#[test]
+fn test_rejects_other_data() {
+    assert!(decode_any_request_instruction(&[], None).is_err());
+    assert!(decode_any_request_instruction(&REQUEST_IX_V1[..20], None).is_err());
+    assert!(decoder().decode_request_account(&[0; 64]).is_err());
+}
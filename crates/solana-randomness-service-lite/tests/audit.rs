@@ -0,0 +1,142 @@
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::Arc;
+
+fn callback() -> Callback {
+    Callback::new(
+        Pubkey::new_unique(),
+        vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+            AccountMeta::new(Pubkey::new_unique(), false).into(),
+        ],
+        vec![1, 2, 3, 4, 5, 6, 7, 8],
+    )
+}
+
+fn builder(payer: &Keypair) -> RequestBuilder {
+    RequestBuilder::new(payer.pubkey(), 8, callback())
+        .options(Some(TransactionOptions {
+            compute_units: Some(300_000),
+            compute_unit_price: Some(10_000),
+        }))
+        .cost_per_byte(100)
+}
+
+struct FailingSink;
+
+impl AuditSink for FailingSink {
+    fn record(&self, _event: AuditEvent) -> std::io::Result<()> {
+        Err(std::io::Error::other("disk full"))
+    }
+}
+
+#[tokio::test]
+async fn test_audit_events_match_sent_transaction() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let sink = Arc::new(MemoryAuditSink::new());
+    let builder = builder(&payer).with_audit_sink(sink.clone());
+
+    let sent = builder.send(&rpc, &payer).await.unwrap();
+
+    let transactions = rpc.sent_transactions();
+    assert_eq!(transactions.len(), 1);
+    let message = &transactions[0].message;
+    assert_eq!(message.instructions.len(), 1);
+    let compiled = &message.instructions[0];
+
+    let events = sink.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].stage, AuditStage::Built);
+    assert_eq!(events[1].stage, AuditStage::Sent);
+
+    for event in events.iter() {
+        assert_eq!(event.request, sent.request);
+        assert_eq!(event.payer, payer.pubkey());
+        assert_eq!(
+            event.program_id,
+            message.account_keys[compiled.program_id_index as usize]
+        );
+        assert_eq!(event.instruction_data, compiled.data);
+        assert_eq!(event.funding, builder.funding().unwrap());
+        assert_eq!(event.accounts.len(), compiled.accounts.len());
+        for (account, index) in event.accounts.iter().zip(compiled.accounts.iter()) {
+            let index = *index as usize;
+            assert_eq!(account.pubkey, message.account_keys[index]);
+            assert_eq!(account.is_signer, message.is_signer(index));
+            assert_eq!(account.is_writable, message.is_writable(index));
+        }
+    }
+
+    assert_eq!(events[0].signature, None);
+    assert_eq!(events[0].sent_at, None);
+    assert_eq!(events[1].signature, Some(sent.signature));
+    assert_eq!(events[1].built_at, events[0].built_at);
+    assert!(events[1].sent_at.unwrap() >= events[1].built_at);
+}
+
+#[tokio::test]
+async fn test_failing_sink_does_not_fail_send() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let builder = builder(&payer).with_audit_sink(Arc::new(FailingSink));
+
+    builder.send(&rpc, &payer).await.unwrap();
+
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_send_records_only_built_event() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    rpc.set_fail_sends(true);
+    let sink = Arc::new(MemoryAuditSink::new());
+    let builder = builder(&payer).with_audit_sink(sink.clone());
+
+    assert!(builder.send(&rpc, &payer).await.is_err());
+
+    let events = sink.events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].stage, AuditStage::Built);
+}
+
+#[tokio::test]
+async fn test_file_sink_appends_json_lines() {
+    let path =
+        std::env::temp_dir().join(format!("randomness-audit-{}.jsonl", Pubkey::new_unique()));
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let sink = Arc::new(FileAuditSink::new(&path).unwrap());
+    let builder = builder(&payer).with_audit_sink(sink);
+
+    let sent = builder.send(&rpc, &payer).await.unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let lines: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["stage"], "built");
+    assert_eq!(lines[1]["stage"], "sent");
+    assert_eq!(lines[1]["request"], sent.request.to_string());
+    assert_eq!(lines[1]["signature"], sent.signature.to_string());
+    assert_eq!(lines[1]["accounts"].as_array().unwrap().len(), 8);
+    assert_eq!(
+        lines[1]["funding"]["randomness_fee"],
+        builder.funding().unwrap().randomness_fee
+    );
+
+    let transaction = &rpc.sent_transactions()[0];
+    let data = &transaction.message.instructions[0].data;
+    use base64::Engine;
+    assert_eq!(
+        lines[1]["instruction_data"],
+        base64::engine::general_purpose::STANDARD.encode(data)
+    );
+}
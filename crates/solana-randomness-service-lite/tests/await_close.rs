@@ -0,0 +1,184 @@
+//! Requests whose account is closed while they are awaited. The settle transactions are
+//! synthesized to match the logs of the service program's settle instruction.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::mock::{request_account, MockPubsub, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+fn program_data(name: &str, event: impl BorshSerialize) -> String {
+    let mut data = hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+fn pending_request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        ..Default::default()
+    }
+}
+
+fn transaction(request: Pubkey, slot: u64, log_messages: Vec<String>) -> TransactionRecord {
+    TransactionRecord {
+        signature: Signature::new_unique(),
+        slot,
+        failed: false,
+        account_keys: vec![request, ID],
+        log_messages,
+        token_balances: vec![],
+    }
+}
+
+fn settle(rpc: &MockRpc, request: Pubkey, randomness: Vec<u8>) -> Signature {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 1,
+        settled_slot: 3,
+        is_success: true,
+        randomness,
+    };
+    let transaction = transaction(
+        request,
+        3,
+        vec![program_data("SimpleRandomnessV1SettledEvent", event)],
+    );
+    let signature = transaction.signature;
+    rpc.add_transaction(transaction);
+    signature
+}
+
+async fn wait_for_subscriptions(pubsub: &MockPubsub, count: usize) {
+    while pubsub.subscriptions() < count {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_close_after_fulfill_recovers_randomness() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+    let live = Arc::new(MockPubsub::new());
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![live.clone()];
+
+    let (outcome, signature) = tokio::join!(
+        await_fulfillment(&rpc, &pubsub, &request, Duration::from_secs(5)),
+        async {
+            wait_for_subscriptions(&live, 1).await;
+            // The settle transaction closes the account, so the only update is the close
+            let signature = settle(&rpc, request, vec![9; 8]);
+            rpc.remove_account(&request);
+            live.notify(&request, None);
+            signature
+        }
+    );
+
+    match outcome.unwrap() {
+        FulfillmentOutcome::Fulfilled {
+            signature: settled,
+            settled_slot,
+            is_success,
+            randomness,
+            event_id,
+            ..
+        } => {
+            assert_eq!(settled, signature);
+            assert_eq!(settled_slot, 3);
+            assert!(is_success);
+            assert_eq!(randomness, vec![9; 8]);
+            assert_eq!(event_id, fulfillment_event_id(&request, &signature));
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn test_closed_before_subscribing_recovers_randomness() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    settle(&rpc, request, vec![4; 8]);
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![Arc::new(MockPubsub::new())];
+
+    let outcome = await_fulfillment(&rpc, &pubsub, &request, Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    match outcome {
+        FulfillmentOutcome::Fulfilled { randomness, .. } => assert_eq!(randomness, vec![4; 8]),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn test_close_without_fulfill() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+    let live = Arc::new(MockPubsub::new());
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![live.clone()];
+
+    let (outcome, _) = tokio::join!(
+        await_fulfillment(&rpc, &pubsub, &request, Duration::from_secs(5)),
+        async {
+            wait_for_subscriptions(&live, 1).await;
+            // A sweeper closes the request without a settlement
+            rpc.add_transaction(transaction(
+                request,
+                4,
+                vec![format!("Program {} success", ID)],
+            ));
+            rpc.remove_account(&request);
+            live.notify(&request, None);
+        }
+    );
+
+    assert!(matches!(
+        outcome.unwrap(),
+        FulfillmentOutcome::ClosedBeforeFulfillment
+    ));
+}
+
+#[tokio::test]
+async fn test_failed_request_is_not_closed() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    let failed = SimpleRandomnessV1Account {
+        is_completed: 1,
+        error_message: "callback failed".to_string(),
+        ..pending_request()
+    };
+    rpc.set_account(request, request_account(&failed));
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![Arc::new(MockPubsub::new())];
+
+    let outcome = await_fulfillment(&rpc, &pubsub, &request, Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    match outcome {
+        FulfillmentOutcome::Failed(failed) => assert_eq!(failed.error_message, "callback failed"),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
@@ -0,0 +1,173 @@
+//! `await_many` returns a result for every request, however the others fail. The settle
+//! transactions are synthesized to match the logs of the service program's settle instruction.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::mock::{request_account, MockPubsub, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+fn pending_request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        ..Default::default()
+    }
+}
+
+/// Adds the request's settle transaction and closes its account.
+fn settle(rpc: &MockRpc, request: Pubkey, randomness: Vec<u8>) {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 1,
+        settled_slot: 3,
+        is_success: true,
+        randomness,
+    };
+    let mut data = hash(b"event:SimpleRandomnessV1SettledEvent").to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    rpc.add_transaction(TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: 3,
+        failed: false,
+        account_keys: vec![request, ID],
+        log_messages: vec![format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(data)
+        )],
+        token_balances: vec![],
+    });
+    rpc.remove_account(&request);
+}
+
+async fn wait_for_subscriptions(pubsub: &MockPubsub, count: usize) {
+    while pubsub.subscriptions() < count {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+/// Panics when subscribing to the given accounts, as a buggy endpoint implementation might.
+struct PanickingPubsub {
+    inner: Arc<MockPubsub>,
+    panic_for: HashSet<Pubkey>,
+}
+
+#[async_trait::async_trait]
+impl RandomnessPubsub for PanickingPubsub {
+    async fn subscribe_account(&self, pubkey: &Pubkey) -> Result<AccountUpdates, LiteClientError> {
+        if self.panic_for.contains(pubkey) {
+            panic!("subscription bug for {}", pubkey);
+        }
+        self.inner.subscribe_account(pubkey).await
+    }
+}
+
+#[tokio::test]
+async fn test_streams_failing_mid_flight() {
+    let requests: Vec<Pubkey> = (0..100).map(|_| Pubkey::new_unique()).collect();
+    let rpc = MockRpc::default();
+    for request in &requests {
+        rpc.set_account(*request, request_account(&pending_request()));
+    }
+    let live = Arc::new(MockPubsub::new());
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![live.clone()];
+
+    let (results, _) = tokio::join!(
+        await_many(&rpc, &pubsub, &requests, Duration::from_secs(10)),
+        async {
+            wait_for_subscriptions(&live, requests.len()).await;
+            // A third of the streams fail and are resubscribed
+            for request in requests.iter().step_by(3) {
+                live.disconnect_account(request);
+            }
+            for (index, request) in requests.iter().enumerate() {
+                settle(&rpc, *request, vec![index as u8; 8]);
+                live.notify(request, None);
+            }
+        }
+    );
+
+    assert_eq!(results.len(), requests.len());
+    for (index, result) in results.into_iter().enumerate() {
+        assert_eq!(result.unwrap().randomness(), Some(&[index as u8; 8][..]));
+    }
+}
+
+#[tokio::test]
+async fn test_contains_panics() {
+    let requests: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+    let rpc = MockRpc::default();
+    for request in &requests {
+        settle(&rpc, *request, vec![1; 8]);
+    }
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![Arc::new(PanickingPubsub {
+        inner: Arc::new(MockPubsub::new()),
+        panic_for: requests.iter().step_by(3).copied().collect(),
+    })];
+
+    let results = await_many(&rpc, &pubsub, &requests, Duration::from_secs(5)).await;
+
+    assert_eq!(results.len(), requests.len());
+    for (index, result) in results.iter().enumerate() {
+        match result {
+            Err(AwaitError::Client(error)) if index % 3 == 0 => {
+                assert_eq!(error.kind(), ErrorKind::Internal);
+                assert!(error.to_string().contains("subscription bug"));
+            }
+            Ok(outcome) if index % 3 != 0 => assert!(outcome.is_success()),
+            result => panic!("unexpected result for request {}: {:?}", index, result),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_timeouts_return() {
+    let requests: Vec<Pubkey> = (0..10).map(|_| Pubkey::new_unique()).collect();
+    let rpc = MockRpc::default();
+    for request in &requests {
+        rpc.set_account(*request, request_account(&pending_request()));
+    }
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![Arc::new(MockPubsub::new())];
+
+    let results = await_many(&rpc, &pubsub, &requests, Duration::from_millis(50)).await;
+
+    assert_eq!(results.len(), requests.len());
+    for result in results {
+        assert!(matches!(
+            result,
+            Err(AwaitError::Timeout { last_status })
+                if matches!(last_status.as_deref(), Some(RequestStatus::Pending(_)))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_zero_poll_interval() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+
+    let error =
+        await_fulfillment_polling(&rpc, &request, Duration::from_millis(20), Duration::ZERO)
+            .await
+            .unwrap_err();
+    assert!(matches!(error, AwaitError::Timeout { .. }));
+}
@@ -0,0 +1,167 @@
+//! `await_fulfillment_polling` waits on the request account without a websocket. The settle
+//! transaction is synthesized to match the logs of the service program's settle instruction.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+fn pending_request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        request_slot: 1,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        ..Default::default()
+    }
+}
+
+/// Settles the request: adds its settle transaction and closes the account.
+fn settle(rpc: &MockRpc, request: Pubkey, randomness: Vec<u8>) -> Signature {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 1,
+        settled_slot: 3,
+        is_success: true,
+        randomness,
+    };
+    let mut data = hash(b"event:SimpleRandomnessV1SettledEvent").to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    let signature = Signature::new_unique();
+    rpc.add_transaction(TransactionRecord {
+        signature,
+        slot: 3,
+        failed: false,
+        account_keys: vec![request, ID],
+        log_messages: vec![format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(data)
+        )],
+        token_balances: vec![],
+    });
+    rpc.remove_account(&request);
+    signature
+}
+
+#[tokio::test]
+async fn test_returns_settlement() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+
+    let (outcome, signature) = tokio::join!(
+        await_fulfillment_polling(&rpc, &request, Duration::from_secs(5), POLL_INTERVAL),
+        async {
+            tokio::time::sleep(POLL_INTERVAL * 3).await;
+            settle(&rpc, request, vec![9; 8])
+        }
+    );
+
+    let outcome = outcome.unwrap();
+    assert!(outcome.is_success());
+    assert_eq!(outcome.randomness(), Some(&[9; 8][..]));
+    assert_eq!(outcome.signature(), Some(&signature));
+}
+
+#[tokio::test]
+async fn test_returns_oracle_error() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+
+    let (outcome, _) = tokio::join!(
+        await_fulfillment_polling(&rpc, &request, Duration::from_secs(5), POLL_INTERVAL),
+        async {
+            tokio::time::sleep(POLL_INTERVAL * 3).await;
+            let failed = SimpleRandomnessV1Account {
+                is_completed: 1,
+                error_message: "callback failed".to_string(),
+                ..pending_request()
+            };
+            rpc.set_account(request, request_account(&failed));
+        }
+    );
+
+    match outcome.unwrap() {
+        FulfillmentOutcome::Failed(failed) => assert_eq!(failed.error_message, "callback failed"),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn test_returns_close_without_settlement() {
+    // Closed and reclaimed before the first poll
+    let rpc = MockRpc::default();
+    let outcome = await_fulfillment_polling(
+        &rpc,
+        &Pubkey::new_unique(),
+        Duration::from_secs(5),
+        POLL_INTERVAL,
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        outcome,
+        FulfillmentOutcome::ClosedBeforeFulfillment
+    ));
+}
+
+#[tokio::test]
+async fn test_timeout_carries_last_status() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+
+    let error = await_fulfillment_polling(&rpc, &request, POLL_INTERVAL * 5, POLL_INTERVAL)
+        .await
+        .unwrap_err();
+
+    match error {
+        AwaitError::Timeout { last_status } => match last_status.as_deref() {
+            Some(RequestStatus::Pending(pending)) => {
+                assert_eq!(pending.request_slot, 1);
+                assert_eq!(pending.num_bytes, 8);
+            }
+            status => panic!("unexpected status {:?}", status),
+        },
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[tokio::test]
+async fn test_rides_out_rpc_failures() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+    rpc.set_offline(true);
+
+    let (outcome, _) = tokio::join!(
+        await_fulfillment_polling(&rpc, &request, Duration::from_secs(5), POLL_INTERVAL),
+        async {
+            tokio::time::sleep(POLL_INTERVAL * 3).await;
+            settle(&rpc, request, vec![2; 8]);
+            rpc.set_offline(false);
+        }
+    );
+
+    assert_eq!(outcome.unwrap().randomness(), Some(&[2; 8][..]));
+}
@@ -0,0 +1,141 @@
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn batch(payer: &Pubkey, total_bytes: usize) -> RequestBatch {
+    RequestBuilder::new(*payer, 8, Callback::default()).num_bytes_total(total_bytes)
+}
+
+fn copy(keypair: &Keypair) -> Keypair {
+    Keypair::from_bytes(&keypair.to_bytes()).unwrap()
+}
+
+fn conflicts(result: Result<(), LiteClientError>) -> Vec<AccountConflict> {
+    match result {
+        Err(LiteClientError::DuplicateAccountUsage(conflicts)) => conflicts,
+        other => panic!("expected DuplicateAccountUsage, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_distinct_accounts_pass() {
+    let payer = Pubkey::new_unique();
+    let requests = [Pubkey::new_unique(), Pubkey::new_unique()];
+    assert!(batch(&payer, 64).check_accounts(&requests).is_ok());
+}
+
+#[test]
+fn test_duplicate_request_reports_request_and_escrow() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let requests = [request, Pubkey::new_unique(), request];
+
+    let conflicts = conflicts(batch(&payer, 96).check_accounts(&requests));
+    assert_eq!(
+        conflicts,
+        vec![
+            AccountConflict {
+                pubkey: request,
+                roles: vec![AccountRole::Request(0), AccountRole::Request(2)],
+            },
+            AccountConflict {
+                pubkey: derive_escrow_pubkey(&request, &RANDOMNESS_SERVICE_REWARD_MINT),
+                roles: vec![AccountRole::Escrow(0), AccountRole::Escrow(2)],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_payer_as_request() {
+    let payer = Pubkey::new_unique();
+    let requests = [Pubkey::new_unique(), payer];
+
+    let conflicts = conflicts(batch(&payer, 64).check_accounts(&requests));
+    assert_eq!(
+        conflicts,
+        vec![AccountConflict {
+            pubkey: payer,
+            roles: vec![AccountRole::Payer, AccountRole::Request(1)],
+        }]
+    );
+}
+
+#[test]
+fn test_escrow_as_request() {
+    let payer = Pubkey::new_unique();
+    let first = Pubkey::new_unique();
+    let escrow = derive_escrow_pubkey(&first, &RANDOMNESS_SERVICE_REWARD_MINT);
+    let requests = [first, escrow];
+
+    let conflicts = conflicts(batch(&payer, 64).check_accounts(&requests));
+    assert_eq!(
+        conflicts,
+        vec![AccountConflict {
+            pubkey: escrow,
+            roles: vec![AccountRole::Escrow(0), AccountRole::Request(1)],
+        }]
+    );
+}
+
+#[test]
+fn test_every_conflict_is_reported() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let requests = [request, payer, request];
+
+    let error = batch(&payer, 96).check_accounts(&requests).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Validation);
+    assert!(!error.is_retryable());
+    let message = error.to_string();
+    assert!(message.contains(&format!("{} used as payer, request 1", payer)));
+    assert!(message.contains(&format!("{} used as request 0, request 2", request)));
+
+    let conflicts = conflicts(Err(error));
+    let pubkeys: Vec<Pubkey> = conflicts.iter().map(|c| c.pubkey).collect();
+    assert_eq!(
+        pubkeys,
+        vec![
+            payer,
+            request,
+            derive_escrow_pubkey(&request, &RANDOMNESS_SERVICE_REWARD_MINT),
+        ]
+    );
+}
+
+#[test]
+fn test_build_with_keypairs() {
+    let payer = Pubkey::new_unique();
+    let requests = [Keypair::new(), Keypair::new()];
+    let pubkeys: Vec<Pubkey> = requests.iter().map(Signer::pubkey).collect();
+
+    let built = batch(&payer, 64)
+        .build_with_keypairs(requests.iter().map(copy).collect(), Hash::new_unique())
+        .unwrap();
+    let built: Vec<Pubkey> = built.iter().map(|b| b.request.pubkey()).collect();
+    assert_eq!(built, pubkeys);
+}
+
+#[test]
+fn test_build_with_duplicate_keypairs_fails_before_building() {
+    let payer = Pubkey::new_unique();
+    let request = Keypair::new();
+
+    let result =
+        batch(&payer, 64).build_with_keypairs(vec![copy(&request), request], Hash::new_unique());
+    assert!(matches!(
+        result,
+        Err(LiteClientError::DuplicateAccountUsage(ref conflicts)) if conflicts.len() == 2
+    ));
+}
+
+#[test]
+fn test_build_with_wrong_keypair_count() {
+    let payer = Pubkey::new_unique();
+    let result = batch(&payer, 64).build_with_keypairs(vec![Keypair::new()], Hash::new_unique());
+    assert!(matches!(
+        result,
+        Err(LiteClientError::Program(ProgramError::NotEnoughAccountKeys))
+    ));
+}
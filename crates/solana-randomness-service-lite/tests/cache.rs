@@ -0,0 +1,99 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn cache_account(cache: &CachedRandomness, owner: Pubkey) -> Account {
+    let mut data = CachedRandomness::DISCRIMINATOR.to_vec();
+    data.extend(cache.try_to_vec().unwrap());
+
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn cache(authority: Pubkey) -> CachedRandomness {
+    let mut result = [0; 32];
+    result[..4].copy_from_slice(&[1, 2, 3, 4]);
+    CachedRandomness {
+        bump: CachedRandomness::address(&CONSUMER_ID, &authority).1,
+        authority,
+        num_bytes: 4,
+        slot: 1_000,
+        result_len: 4,
+        result,
+        pending_request: Pubkey::default(),
+        pending_slot: 0,
+        reserved: [0; RESERVED_LEN],
+    }
+}
+
+#[test]
+fn test_space_matches_layout() {
+    let account = cache_account(&cache(Pubkey::new_unique()), CONSUMER_ID);
+
+    assert_eq!(account.data.len(), CachedRandomness::SPACE);
+}
+
+#[test]
+fn test_fresh_result() {
+    let cache = cache(Pubkey::new_unique());
+
+    assert_eq!(cache.fresh(1_300, 300), Some(&[1, 2, 3, 4][..]));
+    assert_eq!(cache.fresh(1_301, 300), None);
+    assert_eq!(cache.latest(), Some(&[1, 2, 3, 4][..]));
+
+    let empty = CachedRandomness { slot: 0, ..cache };
+    assert_eq!(empty.fresh(0, u64::MAX), None);
+}
+
+#[tokio::test]
+async fn test_fetch_cached_randomness() {
+    let rpc = MockRpc::default();
+    let authority = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let expected = CachedRandomness {
+        pending_request: request,
+        pending_slot: 1_400,
+        ..cache(authority)
+    };
+    let (address, _) = CachedRandomness::address(&CONSUMER_ID, &authority);
+    rpc.set_account(address, cache_account(&expected, CONSUMER_ID));
+
+    let cache = fetch_cached_randomness(&rpc, &CONSUMER_ID, &authority)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(cache, expected);
+    assert_eq!(cache.pending_request(), Some(request));
+    assert_eq!(
+        fetch_cached_randomness(&rpc, &CONSUMER_ID, &Pubkey::new_unique())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_rejects_cache_of_other_program() {
+    let rpc = MockRpc::default();
+    let authority = Pubkey::new_unique();
+    let (address, _) = CachedRandomness::address(&CONSUMER_ID, &authority);
+    rpc.set_account(
+        address,
+        cache_account(&cache(authority), Pubkey::new_unique()),
+    );
+
+    assert!(matches!(
+        fetch_cached_randomness(&rpc, &CONSUMER_ID, &authority).await,
+        Err(LiteClientError::Program(ProgramError::IllegalOwner))
+    ));
+}
@@ -0,0 +1,91 @@
+use solana_randomness_service_lite::*;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn error(error: LiteError) -> ProgramError {
+    error.into()
+}
+
+#[test]
+fn test_builds_same_callback_as_new() {
+    let request = Pubkey::new_unique();
+    let feed = Pubkey::new_unique();
+    let ix_data = anchor_sighash("consume_randomness").to_vec();
+
+    let (callback, len) = Callback::builder(CONSUMER_ID)
+        .with_state_signer()
+        .with_readonly_account(request)
+        .with_writable_account(feed)
+        .with_ix_data(ix_data.clone())
+        .build()
+        .unwrap();
+
+    let expected = Callback::new(
+        CONSUMER_ID,
+        vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+            AccountMeta::new_readonly(request, false).into(),
+            AccountMeta::new(feed, false).into(),
+        ],
+        ix_data,
+    );
+    assert_eq!(callback.to_vec().unwrap(), expected.to_vec().unwrap());
+    assert_eq!(len, callback.to_vec().unwrap().len());
+}
+
+#[test]
+fn test_with_signer_matches_state_signer() {
+    let built = |builder: CallbackBuilder| {
+        builder
+            .with_ix_data(anchor_sighash("consume_randomness").to_vec())
+            .build()
+            .unwrap()
+    };
+
+    assert_eq!(
+        built(Callback::builder(CONSUMER_ID).with_signer(RANDOMNESS_SERVICE_STATE))
+            .0
+            .to_vec(),
+        built(Callback::builder(CONSUMER_ID).with_state_signer())
+            .0
+            .to_vec()
+    );
+}
+
+#[test]
+fn test_rejects_missing_discriminator() {
+    let builder = Callback::builder(CONSUMER_ID).with_state_signer();
+
+    assert_eq!(
+        builder.clone().build().unwrap_err(),
+        error(LiteError::CallbackDiscriminatorMissing)
+    );
+    assert_eq!(
+        builder.with_ix_data(vec![1; 7]).build().unwrap_err(),
+        error(LiteError::CallbackDiscriminatorMissing)
+    );
+}
+
+#[test]
+fn test_rejects_other_signers() {
+    let result = Callback::builder(CONSUMER_ID)
+        .with_state_signer()
+        .with_signer(Pubkey::new_unique())
+        .with_ix_data(vec![1; 8])
+        .build();
+
+    assert_eq!(
+        result.unwrap_err(),
+        error(LiteError::CallbackSignerNotAllowed)
+    );
+}
+
+#[test]
+fn test_rejects_callback_over_limits() {
+    let result = Callback::builder(CONSUMER_ID)
+        .with_state_signer()
+        .with_ix_data(vec![1; Callback::MAX_IX_DATA_LEN + 1])
+        .build();
+
+    assert_eq!(result.unwrap_err(), error(LiteError::CallbackDataTooLarge));
+}
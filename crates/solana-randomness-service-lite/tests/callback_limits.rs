@@ -0,0 +1,144 @@
+use solana_randomness_service_lite::*;
+
+fn callback(num_accounts: usize, ix_data_len: usize) -> Callback {
+    let mut accounts = vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()];
+    accounts
+        .extend((1..num_accounts).map(|_| AccountMeta::new(Pubkey::new_unique(), false).into()));
+    Callback::new(Pubkey::new_unique(), accounts, vec![0; ix_data_len])
+}
+
+#[test]
+fn test_accepts_callback_at_limits() {
+    let callback = callback(Callback::MAX_ACCOUNTS, Callback::MAX_IX_DATA_LEN);
+
+    assert_eq!(callback.validate(), Ok(()));
+    assert_eq!(callback.to_vec().unwrap().len(), Callback::MAX_LEN);
+    // A callback at the account limit can settle with the fewest randomness bytes
+    assert_eq!(callback.check_size(MIN_RANDOMNESS_BYTES), Ok(()));
+}
+
+#[test]
+fn test_rejects_too_many_accounts() {
+    assert_eq!(
+        callback(Callback::MAX_ACCOUNTS + 1, 8).validate(),
+        Err(LiteError::CallbackTooManyAccounts.into())
+    );
+}
+
+#[test]
+fn test_rejects_oversized_ix_data() {
+    assert_eq!(
+        callback(2, Callback::MAX_IX_DATA_LEN + 1).validate(),
+        Err(LiteError::CallbackDataTooLarge.into())
+    );
+}
+
+#[test]
+fn test_settle_transaction_size() {
+    // Two signatures, the header, 15 keys, the blockhash, the compute budget instructions,
+    // and the settle instruction with the state and 32 bytes of randomness
+    let base = callback(1, 8);
+    assert_eq!(base.settle_transaction_size(32), 727);
+
+    // A distinct account adds its key and its index, a repeated one only its index
+    let mut callback = callback(2, 8);
+    assert_eq!(callback.settle_transaction_size(32), 727 + 33);
+    callback.accounts.push(callback.accounts[1].clone());
+    callback
+        .accounts
+        .push(AccountMeta::new_readonly(callback.program_id, false).into());
+    assert_eq!(callback.settle_transaction_size(32), 727 + 33 + 2);
+
+    // The instruction data is read from the request account
+    let mut callback = base.clone();
+    callback.ix_data = vec![0; Callback::MAX_IX_DATA_LEN];
+    assert_eq!(callback.settle_transaction_size(32), 727);
+}
+
+#[test]
+fn test_check_size_rejects_settlements_over_transaction_limit() {
+    assert_eq!(callback(16, 8).check_size(32), Ok(()));
+    assert_eq!(
+        callback(17, 8).check_size(32),
+        Err(LiteError::CallbackTooLargeForSettlement.into())
+    );
+    // Less randomness leaves room for another account
+    assert_eq!(callback(17, 8).check_size(8), Ok(()));
+    // One more distinct account never fits
+    assert_eq!(
+        callback(Callback::MAX_ACCOUNTS + 1, 8).check_size(MIN_RANDOMNESS_BYTES),
+        Err(LiteError::CallbackTooLargeForSettlement.into())
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_get_instruction_checks_size() {
+    let keys: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+    let mut lamports = [0u64; 8];
+    let mut data: Vec<Vec<u8>> = vec![Vec::new(); 8];
+    let owner = solana_program::system_program::ID;
+    let mut infos = keys
+        .iter()
+        .zip(lamports.iter_mut())
+        .zip(data.iter_mut())
+        .map(|((key, lamports), data)| {
+            AccountInfo::new(
+                key,
+                true,
+                true,
+                lamports,
+                data.as_mut_slice(),
+                &owner,
+                false,
+                0,
+            )
+        });
+    let request = SimpleRandomnessV1Request {
+        request: infos.next().unwrap(),
+        escrow: infos.next().unwrap(),
+        state: infos.next().unwrap(),
+        mint: infos.next().unwrap(),
+        payer: infos.next().unwrap(),
+        system_program: infos.next().unwrap(),
+        token_program: infos.next().unwrap(),
+        associated_token_program: infos.next().unwrap(),
+    };
+
+    assert!(request
+        .get_instruction(ID, 32, &callback(16, 8), &None)
+        .is_ok());
+    assert_eq!(
+        request
+            .get_instruction(ID, 32, &callback(17, 8), &None)
+            .unwrap_err(),
+        LiteError::CallbackTooLargeForSettlement.into()
+    );
+    assert_eq!(
+        request
+            .get_instruction(ID, 32, &callback(2, MAX_CALLBACK_IX_DATA_LEN + 1), &None)
+            .unwrap_err(),
+        LiteError::CallbackDataTooLarge.into()
+    );
+    assert_eq!(
+        request
+            .get_instruction(ID, 1, &callback(MAX_CALLBACK_ACCOUNTS + 1, 8), &None)
+            .unwrap_err(),
+        LiteError::CallbackTooManyAccounts.into()
+    );
+}
+
+#[test]
+fn test_published_limits() {
+    assert_eq!(MAX_CALLBACK_ACCOUNTS, 17);
+    assert_eq!(MAX_CALLBACK_IX_DATA_LEN, 10_156);
+    assert_eq!(MAX_RANDOMNESS_BYTES, 32);
+    assert_eq!(Callback::MAX_ACCOUNTS, MAX_CALLBACK_ACCOUNTS);
+    assert_eq!(Callback::MAX_IX_DATA_LEN, MAX_CALLBACK_IX_DATA_LEN);
+
+    // The signer flags are left to `validate`
+    let mut callback = callback(2, 8);
+    callback.accounts[1].is_signer = true;
+    assert_eq!(callback.check_limits(), Ok(()));
+    assert!(callback.validate().is_err());
+}
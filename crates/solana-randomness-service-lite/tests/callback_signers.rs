@@ -0,0 +1,130 @@
+use solana_randomness_service_lite::*;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn callback(accounts: Vec<AccountMeta>) -> Callback {
+    Callback::new(
+        CONSUMER_ID,
+        accounts.into_iter().map(Into::into).collect(),
+        anchor_sighash("consume_randomness").to_vec(),
+    )
+}
+
+fn error(error: LiteError) -> ProgramError {
+    error.into()
+}
+
+#[test]
+fn test_state_signer_is_valid() {
+    let callback = callback(vec![
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+        AccountMeta::new(Pubkey::new_unique(), false),
+    ]);
+
+    assert_eq!(callback.validate(), Ok(()));
+}
+
+#[test]
+fn test_rejects_unsigned_state() {
+    let callback = callback(vec![AccountMeta::new_readonly(
+        RANDOMNESS_SERVICE_STATE,
+        false,
+    )]);
+
+    assert_eq!(
+        callback.validate(),
+        Err(error(LiteError::CallbackStateNotSigner))
+    );
+}
+
+#[test]
+fn test_validate_for_requires_state() {
+    let addresses = ServiceAddresses::mainnet();
+    for accounts in [vec![], vec![AccountMeta::new(Pubkey::new_unique(), false)]] {
+        let callback = callback(accounts);
+
+        assert_eq!(callback.validate(), Ok(()));
+        assert_eq!(
+            callback.validate_for(&addresses),
+            Err(error(LiteError::CallbackStateNotSigner))
+        );
+    }
+}
+
+#[test]
+fn test_validate_for_overridden_state() {
+    let addresses = ServiceAddresses {
+        state: Pubkey::new_unique(),
+        ..ServiceAddresses::mainnet()
+    };
+    let overridden = callback(vec![AccountMeta::new_readonly(addresses.state, true)]);
+
+    assert_eq!(overridden.validate_for(&addresses), Ok(()));
+    assert_eq!(
+        overridden.validate(),
+        Err(error(LiteError::CallbackSignerNotAllowed))
+    );
+    // The mainnet state is just another account to the overridden service
+    let both = callback(vec![
+        AccountMeta::new_readonly(addresses.state, true),
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+    ]);
+    assert_eq!(
+        both.validate_for(&addresses),
+        Err(error(LiteError::CallbackSignerNotAllowed))
+    );
+}
+
+#[test]
+fn test_rejects_on_curve_signer() {
+    let wallet = Pubkey::new_from_array([1; 32]);
+    assert!(wallet.is_on_curve());
+    let mut callback = callback(vec![
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+        AccountMeta::new(wallet, true),
+    ]);
+
+    assert_eq!(
+        callback.validate(),
+        Err(error(LiteError::CallbackSignerNotAllowed))
+    );
+    assert_eq!(
+        callback.allow_pda_signer(&wallet),
+        Err(error(LiteError::CallbackSignerOnCurve))
+    );
+    assert!(callback.accounts[1].is_signer);
+}
+
+#[test]
+fn test_rejects_pda_signer_until_allowed() {
+    let (pda, _) = Pubkey::find_program_address(&[b"AUTHORITY"], &CONSUMER_ID);
+    let mut callback = callback(vec![
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true),
+        AccountMeta::new(pda, true),
+    ]);
+    assert_eq!(
+        callback.validate(),
+        Err(error(LiteError::CallbackSignerNotAllowed))
+    );
+
+    callback.allow_pda_signer(&pda).unwrap();
+
+    // The flag is stripped, the account itself is kept
+    assert_eq!(callback.validate(), Ok(()));
+    assert_eq!(callback.accounts[1].pubkey, pda);
+    assert!(!callback.accounts[1].is_signer);
+    assert!(callback.accounts[1].is_writable);
+}
+
+#[test]
+fn test_allowing_unlisted_pda_is_a_no_op() {
+    let (pda, _) = Pubkey::find_program_address(&[b"AUTHORITY"], &CONSUMER_ID);
+    let mut callback = callback(vec![AccountMeta::new_readonly(
+        RANDOMNESS_SERVICE_STATE,
+        true,
+    )]);
+
+    callback.allow_pda_signer(&pda).unwrap();
+    assert_eq!(callback.accounts.len(), 1);
+    assert!(callback.accounts[0].is_signer);
+}
@@ -0,0 +1,29 @@
+//! Fixtures shared by the integration tests.
+
+// Each test crate compiles its own copy and calls only some of these
+#![allow(dead_code)]
+
+use solana_randomness_service_lite::*;
+
+/// A valid callback into a new program: the state signing as its only account, and the
+/// `consume_randomness` discriminator as its data.
+pub fn callback() -> Callback {
+    callback_into(Pubkey::new_unique())
+}
+
+/// Like [`callback`], into `program_id`.
+pub fn callback_into(program_id: Pubkey) -> Callback {
+    Callback::new(
+        program_id,
+        vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+        anchor_sighash("consume_randomness").to_vec(),
+    )
+}
+
+/// Like [`callback`], with `ix_data` as its data.
+pub fn callback_with_ix_data(ix_data: Vec<u8>) -> Callback {
+    Callback {
+        ix_data,
+        ..callback()
+    }
+}
@@ -0,0 +1,9 @@
+//! Downstream code written against the crate's `#[non_exhaustive]` enums keeps compiling when a
+//! variant is added. The `test-variants` feature adds a hidden variant to `RequestStatus`,
+//! `FulfillmentOutcome`, and `LiteClientError`, standing in for a future release.
+
+#[test]
+fn test_wildcard_arms_compile_with_added_variants() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/wildcard_arms.rs");
+}
@@ -0,0 +1,79 @@
+//! A router → consumer → randomness service chain, contrived in the harness by nesting the
+//! consumer's callback under a router program that invokes itself.
+
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+use solana_program::program::invoke;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::*;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const ROUTER_ID: Pubkey = Pubkey::new_from_array([9; 32]);
+
+/// Invokes the router with the number of hops left in the first byte of the instruction data,
+/// or checks there is room for a randomness request once no hops are left.
+fn route(hops: u8) -> ProgramResult {
+    if hops == 0 {
+        return assert_cpi_depth_available(REQUEST_CPI_LEVELS);
+    }
+    invoke(
+        &Instruction::new_with_bytes(ROUTER_ID, &[hops - 1], vec![]),
+        &[],
+    )
+}
+
+fn process_router(_program_id: &Pubkey, _accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    route(data[0])
+}
+
+/// The consumer's callback. The first byte of its discriminator is the number of router hops
+/// between it and its randomness request.
+fn process_callback(_program_id: &Pubkey, _accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    route(data[0])
+}
+
+fn fulfill(hops: u8) -> ProgramResult {
+    let mut harness = OracleHarness::new(CONSUMER_ID, process_callback);
+    harness.add_program(ROUTER_ID, process_router);
+    // The callback lists the service's state account
+    harness.add_service_accounts();
+
+    let request = Pubkey::new_unique();
+    harness.add_request(
+        request,
+        &SimpleRandomnessV1Account {
+            num_bytes: 8,
+            // Not held by the harness, so the fee is not checked
+            escrow: Pubkey::new_unique(),
+            callback: Callback::new(
+                CONSUMER_ID,
+                vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+                vec![hops, 0, 0, 0, 0, 0, 0, 0],
+            ),
+            ..Default::default()
+        },
+    );
+    let randomness = harness.randomness(&request, 8);
+    harness.fulfill(&request, &randomness)
+}
+
+#[test]
+fn test_callback_has_room_to_request_again() {
+    // Settle instruction at height 1, callback at 2, and the request needs 3 more
+    assert_eq!(fulfill(0), Ok(()));
+}
+
+#[test]
+fn test_too_deep_chain_fails_with_clear_error() {
+    assert_eq!(fulfill(1), Err(LiteError::CpiDepthExceeded.into()));
+    assert_eq!(fulfill(2), Err(LiteError::CpiDepthExceeded.into()));
+}
+
+#[test]
+fn test_depth_constants() {
+    assert_eq!(CALLBACK_CPI_LEVELS, REQUEST_CPI_LEVELS);
+    assert_eq!(
+        describe_error_code(LiteError::CpiDepthExceeded.code()),
+        Some("Not enough cross-program invocation depth is left for the call")
+    );
+}
@@ -0,0 +1,172 @@
+use borsh::BorshSerialize;
+use solana_program::pubkey;
+use solana_randomness_service_lite::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the bytes each thread allocates, like a program's bump allocator, which never frees.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The bytes allocated by `f` on this thread.
+fn allocated_by<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+const CONSUMER_PROGRAM_ID: Pubkey = pubkey!("39hMZgeiesFXMRFt8svuKVsdCW5geiYueSRx7dxhXN4f");
+
+fn request(num_accounts: usize, ix_data_len: usize, error_message: &str) -> Vec<u8> {
+    let request = SimpleRandomnessV1Account {
+        is_completed: 1,
+        num_bytes: 8,
+        user: Pubkey::new_unique(),
+        escrow: Pubkey::new_unique(),
+        request_slot: 42,
+        callback: Callback::new(
+            CONSUMER_PROGRAM_ID,
+            (0..num_accounts)
+                .map(|_| AccountMeta::new(Pubkey::new_unique(), false).into())
+                .collect(),
+            vec![7; ix_data_len],
+        ),
+        compute_units: 200_000,
+        priority_fee_micro_lamports: 100,
+        error_message: error_message.to_string(),
+    };
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    data.extend(request.try_to_vec().unwrap());
+    data
+}
+
+fn lite_error<T>(result: Result<T, ProgramError>) -> Option<LiteError> {
+    match result {
+        Err(ProgramError::Custom(code)) => LiteError::from_code(code),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_bounded_matches_unbounded() {
+    let data = request(3, 12, "Callback simulation failed");
+
+    let bounded =
+        SimpleRandomnessV1Account::try_deserialize_bounded(&mut &data[..], DecodeLimits::SERVICE)
+            .unwrap();
+    let unbounded = SimpleRandomnessV1Account::try_deserialize(&mut &data[..]).unwrap();
+
+    assert_eq!(
+        bounded.try_to_vec().unwrap(),
+        unbounded.try_to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_bounded_ignores_padding() {
+    let mut data = request(2, 8, "");
+    data.resize(data.len() + 256, 0);
+
+    let decoded =
+        SimpleRandomnessV1Account::try_deserialize_bounded(&mut &data[..], DecodeLimits::SERVICE)
+            .unwrap();
+
+    assert_eq!(decoded.callback.accounts.len(), 2);
+    assert_eq!(decoded.callback.ix_data, vec![7; 8]);
+}
+
+#[test]
+fn test_bounded_rejects_each_limit() {
+    let limits = DecodeLimits {
+        max_accounts: 2,
+        max_ix_data_len: 8,
+        max_error_message_len: 4,
+    };
+
+    for data in [request(3, 8, ""), request(2, 9, ""), request(2, 8, "Error")] {
+        assert_eq!(
+            lite_error(SimpleRandomnessV1Account::try_deserialize_bounded(
+                &mut &data[..],
+                limits
+            )),
+            Some(LiteError::DecodeLimitExceeded)
+        );
+    }
+    let data = request(2, 8, "Fail");
+    assert!(SimpleRandomnessV1Account::try_deserialize_bounded(&mut &data[..], limits).is_ok());
+}
+
+#[test]
+fn test_bounded_rejects_crafted_length_before_allocating() {
+    // A request whose callback claims u32::MAX accounts, which `try_deserialize` would try to
+    // allocate for.
+    let mut data = request(0, 0, "");
+    let accounts_len = 8 + 1 + 1 + 32 + 32 + 8 + 32;
+    data[accounts_len..accounts_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let (result, allocated) = allocated_by(|| {
+        SimpleRandomnessV1Account::try_deserialize_bounded(&mut &data[..], DecodeLimits::SERVICE)
+    });
+
+    assert_eq!(lite_error(result), Some(LiteError::DecodeLimitExceeded));
+    assert_eq!(allocated, 0);
+}
+
+#[test]
+fn test_bounded_rejects_truncated_account() {
+    let data = request(4, 16, "");
+
+    let result =
+        SimpleRandomnessV1Account::try_deserialize_bounded(&mut &data[..60], DecodeLimits::SERVICE);
+
+    assert_eq!(result.unwrap_err(), ProgramError::InvalidAccountData);
+}
+
+#[test]
+fn test_bounded_heap_within_limits() {
+    let limits = DecodeLimits {
+        max_accounts: 4,
+        max_ix_data_len: 64,
+        max_error_message_len: 32,
+    };
+    let data = request(4, 64, &"x".repeat(32));
+
+    let (decoded, allocated) = allocated_by(|| {
+        SimpleRandomnessV1Account::try_deserialize_bounded(&mut &data[..], limits).unwrap()
+    });
+
+    assert_eq!(decoded.callback.accounts.len(), 4);
+    assert!(allocated > 0);
+    assert!(
+        allocated <= limits.max_heap_bytes(),
+        "allocated {} bytes, the bound is {}",
+        allocated,
+        limits.max_heap_bytes()
+    );
+}
+
+#[test]
+fn test_service_limits_heap() {
+    assert_eq!(DecodeLimits::default(), DecodeLimits::SERVICE);
+    assert_eq!(
+        DecodeLimits::SERVICE.max_heap_bytes(),
+        17 * 34 + 10_156 + 256
+    );
+}
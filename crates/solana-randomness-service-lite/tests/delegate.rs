@@ -0,0 +1,315 @@
+//! Requests whose fee is pulled from a token account through a delegate approval, against the
+//! harness's stand-in for a service fork that supports it.
+
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::hash::Hash;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([8; 32]);
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+const SOURCE_BALANCE: u64 = 1_000_000;
+const APPROVE_AMOUNT: u64 = 500_000;
+
+fn process_consumer(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    Ok(())
+}
+
+/// The payer's associated wrapped SOL account, which the payer-funded flow wraps the fee in.
+fn payer_wsol_account(payer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            payer.as_ref(),
+            TOKEN_PROGRAM_ID.as_ref(),
+            NATIVE_MINT.as_ref(),
+        ],
+        &pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+    )
+    .0
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    source: Pubkey,
+    builder: RequestBuilder,
+}
+
+impl Fixture {
+    fn new(approve_amount: u64) -> Self {
+        let mut harness = OracleHarness::new(CONSUMER_ID, process_consumer);
+        harness.add_service_accounts();
+        let payer = harness.payer();
+        harness.airdrop(&payer, 1_000_000_000);
+        let source = Pubkey::new_unique();
+        harness.set_token_account(
+            source,
+            &TokenAccountState::wrapped_sol(payer, SOURCE_BALANCE),
+        );
+
+        let callback = Callback::new(
+            CONSUMER_ID,
+            vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+            vec![2; 8],
+        );
+        let builder =
+            RequestBuilder::new(payer, 8, callback).fund_via_delegate(source, approve_amount);
+
+        Self {
+            harness,
+            source,
+            builder,
+        }
+    }
+}
+
+#[test]
+fn test_builder_approves_request_as_delegate() {
+    let payer = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let builder = RequestBuilder::new(payer, 8, Callback::default())
+        .fund_via_delegate(source, APPROVE_AMOUNT);
+
+    let instructions = builder.instructions(&request).unwrap();
+
+    assert_eq!(instructions.len(), 2);
+    let approve = &instructions[0];
+    assert_eq!(approve.program_id, TOKEN_PROGRAM_ID);
+    assert_eq!(
+        approve.accounts,
+        vec![
+            AccountMeta::new(source, false),
+            AccountMeta::new_readonly(request, false),
+            AccountMeta::new_readonly(payer, true),
+        ]
+    );
+    assert_eq!(
+        approve.data,
+        [&[4][..], &APPROVE_AMOUNT.to_le_bytes()].concat()
+    );
+
+    // The request instruction passes the token account after the service's accounts
+    let request_ix = &instructions[1];
+    assert_eq!(request_ix.program_id, ID);
+    assert_eq!(request_ix.accounts.len(), 9);
+    assert_eq!(request_ix.accounts[8], AccountMeta::new(source, false));
+
+    // Nothing is wrapped into the payer's own token account
+    let payer_wsol = payer_wsol_account(&payer);
+    assert!(instructions
+        .iter()
+        .flat_map(|ix| ix.accounts.iter())
+        .all(|meta| meta.pubkey != payer_wsol));
+
+    let built = builder.build(Hash::new_unique()).unwrap();
+    assert_eq!(built.transaction.message.instructions.len(), 2);
+}
+
+#[test]
+fn test_builder_without_delegate_is_unchanged() {
+    let builder = RequestBuilder::new(Pubkey::new_unique(), 8, Callback::default());
+    let request = Pubkey::new_unique();
+
+    let instructions = builder.instructions(&request).unwrap();
+
+    assert_eq!(instructions, vec![builder.instruction(&request).unwrap()]);
+    assert_eq!(instructions[0].accounts.len(), 8);
+    assert!(builder.revoke_instruction().is_none());
+}
+
+#[test]
+fn test_rejects_allowance_below_service_cost() {
+    let builder = RequestBuilder::new(Pubkey::new_unique(), 8, Callback::default());
+    let required = builder.funding().unwrap().service_cost();
+
+    let result = builder
+        .fund_via_delegate(Pubkey::new_unique(), required - 1)
+        .instructions(&Pubkey::new_unique());
+
+    assert!(matches!(
+        result,
+        Err(LiteClientError::DelegateAllowanceTooLow { approved, required: r })
+            if approved == required - 1 && r == required
+    ));
+}
+
+#[test]
+fn test_approve_request_fulfill_revoke() {
+    let mut fixture = Fixture::new(APPROVE_AMOUNT);
+    let payer = fixture.harness.payer();
+    let request = Pubkey::new_unique();
+    let funding = fixture.builder.funding().unwrap();
+    let cost = funding.service_cost();
+    let payer_lamports = fixture.harness.account(&payer).unwrap().lamports;
+
+    let created = fixture
+        .harness
+        .process_transaction(&fixture.builder.instructions(&request).unwrap())
+        .unwrap();
+    assert_eq!(created, vec![request]);
+
+    // The fee moves from the token account straight into the escrow
+    let source = fixture.harness.token_account(&fixture.source).unwrap();
+    assert_eq!(source.amount, SOURCE_BALANCE - cost);
+    assert_eq!(source.delegate, Some(request));
+    assert_eq!(source.delegated_amount, APPROVE_AMOUNT - cost);
+    assert_eq!(
+        fixture.harness.balances(&request).escrow,
+        funding.escrow_rent + cost
+    );
+    // The payer only pays the rent
+    assert_eq!(
+        payer_lamports - fixture.harness.account(&payer).unwrap().lamports,
+        funding.request_rent + funding.escrow_rent
+    );
+
+    let randomness = fixture.harness.randomness(&request, 8);
+    fixture.harness.fulfill(&request, &randomness).unwrap();
+    assert!(fixture.harness.request(&request).is_none());
+
+    fixture
+        .harness
+        .process_transaction(&[fixture.builder.revoke_instruction().unwrap()])
+        .unwrap();
+    let source = fixture.harness.token_account(&fixture.source).unwrap();
+    assert_eq!(source.delegate, None);
+    assert_eq!(source.delegated_amount, 0);
+    assert_eq!(source.amount, SOURCE_BALANCE - cost);
+
+    // The funds never passed through a wrapped SOL account of the payer
+    assert!(fixture
+        .harness
+        .account(&payer_wsol_account(&payer))
+        .is_none());
+    assert_eq!(
+        fixture.harness.account(&payer).unwrap().lamports,
+        payer_lamports
+    );
+}
+
+#[test]
+fn test_exact_allowance_clears_delegate() {
+    let cost = Fixture::new(APPROVE_AMOUNT)
+        .builder
+        .funding()
+        .unwrap()
+        .service_cost();
+    let mut fixture = Fixture::new(cost);
+    let request = Pubkey::new_unique();
+
+    fixture
+        .harness
+        .process_transaction(&fixture.builder.instructions(&request).unwrap())
+        .unwrap();
+
+    let source = fixture.harness.token_account(&fixture.source).unwrap();
+    assert_eq!(source.delegate, None);
+    assert_eq!(source.delegated_amount, 0);
+}
+
+#[test]
+fn test_request_without_approval_fails() {
+    let mut fixture = Fixture::new(APPROVE_AMOUNT);
+    let request = Pubkey::new_unique();
+    let instructions = fixture.builder.instructions(&request).unwrap();
+
+    assert_eq!(
+        fixture.harness.process_transaction(&instructions[1..]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert!(fixture.harness.request(&request).is_none());
+    assert_eq!(
+        fixture
+            .harness
+            .token_account(&fixture.source)
+            .unwrap()
+            .amount,
+        SOURCE_BALANCE
+    );
+}
+
+#[test]
+fn test_failed_request_rolls_back_approval() {
+    let mut fixture = Fixture::new(APPROVE_AMOUNT);
+    let payer = fixture.harness.payer();
+    fixture.harness.remove_account(&payer);
+    let request = Pubkey::new_unique();
+
+    assert_eq!(
+        fixture
+            .harness
+            .process_transaction(&fixture.builder.instructions(&request).unwrap()),
+        Err(ProgramError::InsufficientFunds)
+    );
+    assert_eq!(
+        fixture.harness.token_account(&fixture.source).unwrap(),
+        TokenAccountState::wrapped_sol(payer, SOURCE_BALANCE)
+    );
+}
+
+/// A packed wrapped SOL token account of `owner`.
+fn wsol_account(owner: &Pubkey, amount: u64) -> solana_sdk::account::Account {
+    let mut data = vec![0; TOKEN_ACCOUNT_BASE_SIZE];
+    data[..32].copy_from_slice(NATIVE_MINT.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1;
+    solana_sdk::account::Account {
+        lamports: 2_039_280 + amount,
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_preflight_checks_token_account() {
+    let owner = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let rpc = mock::MockRpc::default();
+    rpc.set_account(source, wsol_account(&owner, SOURCE_BALANCE));
+    let funding = DelegateFunding::new(source, APPROVE_AMOUNT);
+
+    let view = funding
+        .preflight(&rpc, &owner, APPROVE_AMOUNT)
+        .await
+        .unwrap();
+    assert_eq!(view.amount, SOURCE_BALANCE);
+
+    assert!(matches!(
+        funding.preflight(&rpc, &owner, SOURCE_BALANCE + 1).await,
+        Err(LiteClientError::InsufficientBalance { balance, required })
+            if balance == SOURCE_BALANCE && required == SOURCE_BALANCE + 1
+    ));
+    assert!(funding
+        .preflight(&rpc, &Pubkey::new_unique(), APPROVE_AMOUNT)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_preflight_rejects_other_mints() {
+    let owner = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let mut account = wsol_account(&owner, SOURCE_BALANCE);
+    account.data[..32].copy_from_slice(mint.as_ref());
+    let rpc = mock::MockRpc::default();
+    rpc.set_account(source, account);
+
+    let result = DelegateFunding::new(source, APPROVE_AMOUNT)
+        .preflight(&rpc, &owner, APPROVE_AMOUNT)
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(LiteClientError::MintMismatch { expected, got }) if expected == NATIVE_MINT && got == mint
+    ));
+}
@@ -0,0 +1,64 @@
+//! The default build depends on nothing beyond `borsh` and `solana-program`. Every other
+//! dependency is optional and stays behind the feature that needs it, so supply-chain reviews
+//! of on-chain programs only have these two crates and their own dependencies to audit.
+
+use std::collections::BTreeSet;
+use std::process::Command;
+
+/// The only crates the default build may depend on directly. Adding to this list widens what
+/// every on-chain consumer pulls in; put new dependencies behind a feature instead.
+const ALLOWED: &[&str] = &["borsh", "solana-program"];
+
+/// The direct normal and build dependencies `cargo tree` resolves for every target, with
+/// `extra_args` choosing the features.
+fn direct_dependencies(extra_args: &[&str]) -> BTreeSet<String> {
+    let output = Command::new(env!("CARGO"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args([
+            "tree", "-e", "no-dev", "--depth", "1", "--prefix", "none", "--target", "all",
+        ])
+        .args(extra_args)
+        .output()
+        .expect("cargo tree runs");
+    assert!(
+        output.status.success(),
+        "cargo tree failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Each line is `<name> v<version> [(<source>)]`, the crate itself first
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+fn assert_allowed(dependencies: &BTreeSet<String>) {
+    let unexpected: Vec<&String> = dependencies
+        .iter()
+        .filter(|name| !ALLOWED.contains(&name.as_str()))
+        .collect();
+    assert!(
+        unexpected.is_empty(),
+        "the default build depends on {:?}; only {:?} are allowed, put the rest behind a feature",
+        unexpected,
+        ALLOWED
+    );
+}
+
+#[test]
+fn test_no_default_features_dependencies() {
+    let dependencies = direct_dependencies(&["--no-default-features"]);
+
+    assert_allowed(&dependencies);
+    // Parsing found the dependencies at all
+    assert!(dependencies.contains("solana-program"));
+}
+
+#[test]
+fn test_default_features_dependencies() {
+    assert_allowed(&direct_dependencies(&[]));
+}
@@ -0,0 +1,186 @@
+//! The request transactions below are synthesized to match the logs of the service program's
+//! `simple_randomness_v1` instruction.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+#[derive(BorshSerialize)]
+struct RequestedEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    callback: Callback,
+    compute_units: u32,
+    priority_fee_micro_lamports: u64,
+    num_bytes: u8,
+}
+
+fn program_data(name: &str, event: impl BorshSerialize) -> String {
+    let mut data = hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+/// Adds a request transaction and, if the request is still open, its account.
+fn request(rpc: &MockRpc, slot: u64, open: bool) -> Pubkey {
+    let request = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let event = RequestedEvent {
+        callback_pid: Pubkey::new_unique(),
+        user,
+        request,
+        request_slot: slot,
+        callback: Callback::default(),
+        compute_units: 0,
+        priority_fee_micro_lamports: 0,
+        num_bytes: 8,
+    };
+    rpc.add_transaction(TransactionRecord {
+        signature: Signature::new_unique(),
+        slot,
+        failed: false,
+        account_keys: vec![user, request, ID],
+        log_messages: vec![program_data("SimpleRandomnessV1RequestedEvent", event)],
+        token_balances: vec![],
+    });
+
+    if open {
+        let account = SimpleRandomnessV1Account {
+            num_bytes: 8,
+            user,
+            request_slot: slot,
+            ..Default::default()
+        };
+        rpc.set_account(request, request_account(&account));
+    }
+    request
+}
+
+fn sorted(discovered: &DiscoveredRequests) -> Vec<Pubkey> {
+    let mut requests: Vec<Pubkey> = discovered.requests.iter().map(|(p, _)| *p).collect();
+    requests.sort();
+    requests
+}
+
+fn fixture() -> (MockRpc, Vec<Pubkey>) {
+    let rpc = MockRpc::default();
+    let mut open = vec![
+        request(&rpc, 10, true),
+        request(&rpc, 11, true),
+        request(&rpc, 12, true),
+    ];
+    // Settled, so its account is closed
+    request(&rpc, 13, false);
+    open.sort();
+    (rpc, open)
+}
+
+#[tokio::test]
+async fn test_program_accounts_when_available() {
+    let (rpc, open) = fixture();
+
+    let discovered = discover_requests(&rpc, &ID, None).await.unwrap();
+
+    assert_eq!(discovered.strategy, DiscoveryStrategy::ProgramAccounts);
+    assert_eq!(sorted(&discovered), open);
+}
+
+#[tokio::test]
+async fn test_falls_back_to_signature_scan_when_disabled() {
+    let (rpc, open) = fixture();
+    let expected = discover_requests(&rpc, &ID, None).await.unwrap();
+
+    rpc.set_program_accounts_disabled(true);
+    let discovered = discover_requests(&rpc, &ID, None).await.unwrap();
+
+    assert_eq!(
+        discovered.strategy,
+        DiscoveryStrategy::SignatureScan {
+            depth: DEFAULT_SCAN_DEPTH
+        }
+    );
+    assert_eq!(sorted(&discovered), open);
+    assert_eq!(sorted(&discovered), sorted(&expected));
+    assert_eq!(discovered.slot, 13);
+}
+
+#[tokio::test]
+async fn test_explicit_strategy_is_not_negotiated() {
+    let (rpc, _) = fixture();
+    rpc.set_program_accounts_disabled(true);
+
+    let result = discover_requests(&rpc, &ID, Some(DiscoveryStrategy::ProgramAccounts)).await;
+    assert!(matches!(
+        result,
+        Err(LiteClientError::ProgramAccountsDisabled(_))
+    ));
+
+    // Only the two newest transactions are scanned: the closed request and the one at slot 12
+    let discovered = discover_requests(
+        &rpc,
+        &ID,
+        Some(DiscoveryStrategy::SignatureScan { depth: 2 }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(discovered.requests.len(), 1);
+    assert_eq!(discovered.requests[0].1.request_slot, 12);
+}
+
+#[tokio::test]
+async fn test_other_errors_are_not_treated_as_disabled() {
+    let (rpc, _) = fixture();
+    rpc.set_offline(true);
+
+    let result = discover_requests(&rpc, &ID, None).await;
+    assert!(matches!(result, Err(LiteClientError::Rpc(_))));
+}
+
+#[tokio::test]
+async fn test_queue_estimate_reports_strategy() {
+    let (rpc, _) = fixture();
+    rpc.set_program_accounts_disabled(true);
+
+    let estimate = estimate_queue_depth(&rpc).await.unwrap();
+
+    assert_eq!(estimate.pending_requests, 3);
+    assert!(matches!(
+        estimate.discovery,
+        Some(DiscoveryStrategy::SignatureScan { .. })
+    ));
+}
+
+#[test]
+fn test_detects_disabled_program_accounts() {
+    assert!(is_program_accounts_disabled(
+        Some(-32601),
+        "Method not found"
+    ));
+    assert!(is_program_accounts_disabled(
+        Some(-32010),
+        "TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM excluded from account secondary indexes; this RPC method unavailable for key"
+    ));
+    assert!(is_program_accounts_disabled(
+        None,
+        "getProgramAccounts is disabled for this plan"
+    ));
+    assert!(is_program_accounts_disabled(
+        Some(-32600),
+        "Method getProgramAccounts not allowed"
+    ));
+
+    assert!(!is_program_accounts_disabled(
+        Some(-32005),
+        "Node is unhealthy"
+    ));
+    assert!(!is_program_accounts_disabled(None, "error sending request"));
+}
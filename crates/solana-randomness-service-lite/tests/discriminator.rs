@@ -0,0 +1,50 @@
+use solana_randomness_service_lite::*;
+
+/// The hardcoded discriminator the example consumer used for its callback.
+const CONSUME_RANDOMNESS: [u8; 8] = [190, 217, 49, 162, 99, 26, 73, 234];
+
+#[test]
+fn test_identifies_hardcoded_discriminator() {
+    assert_eq!(anchor_sighash("consume_randomness"), CONSUME_RANDOMNESS);
+    assert_eq!(
+        identify_discriminator(
+            &CONSUME_RANDOMNESS,
+            &["consume_flip_result", "consume_randomness"]
+        ),
+        Some("consume_randomness".to_string())
+    );
+}
+
+#[test]
+fn test_unknown_discriminator() {
+    assert_eq!(
+        identify_discriminator(&[0; 8], &["consume_randomness", "consume_flip_result"]),
+        None
+    );
+    assert_eq!(identify_discriminator(&CONSUME_RANDOMNESS, &[]), None);
+}
+
+#[test]
+fn test_rewrite_preserves_args_suffix() {
+    let mut callback = Callback::new(
+        Pubkey::new_unique(),
+        vec![],
+        [CONSUME_RANDOMNESS.as_slice(), &[1, 2, 3, 4]].concat(),
+    );
+
+    let new = anchor_sighash("consume_flip_result");
+    callback.rewrite_discriminator(new).unwrap();
+    assert_eq!(callback.ix_data[..8], new);
+    assert_eq!(callback.ix_data[8..], [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_rewrite_requires_discriminator() {
+    let mut callback = Callback::new(Pubkey::new_unique(), vec![], vec![1, 2, 3]);
+
+    assert_eq!(
+        callback.rewrite_discriminator(anchor_sighash("consume_randomness")),
+        Err(LiteError::CallbackDiscriminatorMissing.into())
+    );
+    assert_eq!(callback.ix_data, [1, 2, 3]);
+}
@@ -0,0 +1,150 @@
+//! Error kinds, and the observer a request builder reports its errors to.
+
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct LabelRecorder(Mutex<Vec<&'static str>>);
+
+impl ErrorObserver for LabelRecorder {
+    fn observe(&self, error: &LiteClientError) {
+        self.0.lock().unwrap().push(error.metric_label());
+    }
+}
+
+impl LabelRecorder {
+    fn labels(&self) -> Vec<&'static str> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+fn builder(payer: Pubkey, recorder: &Arc<LabelRecorder>) -> RequestBuilder {
+    let callback = Callback::new(
+        Pubkey::new_unique(),
+        vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+        vec![1; 8],
+    );
+    RequestBuilder::new(payer, 8, callback).with_error_observer(recorder.clone())
+}
+
+#[test]
+fn test_kinds_and_labels() {
+    let cases = [
+        (
+            LiteClientError::Program(ProgramError::BorshIoError(
+                "Serialization failed: unexpected end of input".to_string(),
+            )),
+            "serialization",
+            false,
+        ),
+        (
+            LiteClientError::Rpc("connection reset".to_string()),
+            "rpc",
+            true,
+        ),
+        (
+            LiteClientError::ProgramAccountsDisabled("method not found".to_string()),
+            "rpc",
+            false,
+        ),
+        (LiteClientError::Timeout, "timeout", true),
+        (
+            LiteClientError::Program(LiteError::CallbackStateNotSigner.into()),
+            "validation",
+            false,
+        ),
+        (
+            LiteClientError::Program(ServiceError::InsufficientFunds.into()),
+            "program",
+            false,
+        ),
+        (
+            LiteClientError::Program(ProgramError::InvalidArgument),
+            "program",
+            false,
+        ),
+        (
+            LiteClientError::Journal(std::io::Error::other("disk full")),
+            "io",
+            false,
+        ),
+    ];
+
+    for (error, label, retryable) in cases {
+        assert_eq!(error.metric_label(), label, "{}", error);
+        assert_eq!(error.kind().to_string(), label);
+        assert_eq!(error.is_retryable(), retryable, "{}", error);
+    }
+}
+
+#[test]
+fn test_observes_validation_error() {
+    let recorder = Arc::new(LabelRecorder::default());
+    let builder =
+        builder(Pubkey::new_unique(), &recorder).fund_via_delegate(Pubkey::new_unique(), 0);
+
+    assert!(builder.instructions(&Pubkey::new_unique()).is_err());
+    assert_eq!(recorder.labels(), vec!["validation"]);
+}
+
+#[tokio::test]
+async fn test_observes_failed_send_once() {
+    let recorder = Arc::new(LabelRecorder::default());
+    let rpc = MockRpc::default();
+    rpc.set_fail_sends(true);
+    let payer = Keypair::new();
+
+    let error = builder(payer.pubkey(), &recorder)
+        .send(&rpc, &payer)
+        .await
+        .unwrap_err();
+
+    assert!(error.is_retryable());
+    assert_eq!(recorder.labels(), vec!["rpc"]);
+}
+
+#[tokio::test]
+async fn test_observes_unreachable_endpoint() {
+    let recorder = Arc::new(LabelRecorder::default());
+    let rpc = MockRpc::default();
+    rpc.set_offline(true);
+    let payer = Keypair::new();
+
+    assert!(builder(payer.pubkey(), &recorder)
+        .send(&rpc, &payer)
+        .await
+        .is_err());
+    assert_eq!(recorder.labels(), vec!["rpc"]);
+}
+
+#[tokio::test]
+async fn test_observes_session_payer_mismatch_once() {
+    let recorder = Arc::new(LabelRecorder::default());
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let session = PayerSession::new(Pubkey::new_unique());
+
+    let result = builder(payer.pubkey(), &recorder)
+        .send_in_session(&rpc, &payer, &session)
+        .await;
+
+    assert!(matches!(result, Err(LiteClientError::PayerMismatch { .. })));
+    assert_eq!(recorder.labels(), vec!["validation"]);
+}
+
+#[tokio::test]
+async fn test_successful_send_is_not_observed() {
+    let recorder = Arc::new(LabelRecorder::default());
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+
+    builder(payer.pubkey(), &recorder)
+        .send(&rpc, &payer)
+        .await
+        .unwrap();
+
+    assert!(recorder.labels().is_empty());
+}
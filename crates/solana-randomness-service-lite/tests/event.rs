@@ -0,0 +1,131 @@
+//! Events logged with `emit_event` by a consumer written without Anchor, read back with the
+//! client's decoder.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::entrypoint::ProgramResult;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([9; 32]);
+const CALLBACK_DISC: [u8; 8] = [2; 8];
+
+/// Logs a `ConsumerFulfilled` event for the request, then an event of another name.
+fn process_consumer(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [_state, request] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    ConsumerFulfilled {
+        request: *request.key,
+        randomness: data[12..].to_vec(),
+        slot: 5,
+    }
+    .emit();
+    emit_event(
+        anchor_event_discriminator("Unrelated"),
+        &data[12..].to_vec(),
+    );
+    Ok(())
+}
+
+fn transaction(log_messages: Vec<String>) -> TransactionRecord {
+    TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: 5,
+        failed: false,
+        account_keys: vec![CONSUMER_ID],
+        log_messages,
+        token_balances: vec![],
+    }
+}
+
+#[test]
+fn test_discriminators_match_anchor() {
+    assert_eq!(
+        ConsumerRequested::DISCRIMINATOR,
+        anchor_event_discriminator("ConsumerRequested")
+    );
+    assert_eq!(
+        ConsumerFulfilled::DISCRIMINATOR,
+        anchor_event_discriminator("ConsumerFulfilled")
+    );
+}
+
+#[test]
+fn test_decodes_events_emitted_by_callback() {
+    let mut harness = OracleHarness::new(CONSUMER_ID, process_consumer);
+    // The callback lists the service's state account
+    harness.add_service_accounts();
+    let request = Pubkey::new_unique();
+    harness.add_request(
+        request,
+        &SimpleRandomnessV1Account {
+            num_bytes: 8,
+            user: harness.payer(),
+            escrow: Pubkey::new_unique(),
+            callback: Callback::new(
+                CONSUMER_ID,
+                vec![
+                    AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+                    AccountMeta::new_readonly(request, false).into(),
+                ],
+                CALLBACK_DISC.to_vec(),
+            ),
+            ..Default::default()
+        },
+    );
+    let randomness = harness.randomness(&request, 8);
+
+    harness.fulfill(&request, &randomness).unwrap();
+
+    let transaction = transaction(harness.logs().to_vec());
+    assert_eq!(
+        decode_program_events::<ConsumerFulfilled>(&transaction, ConsumerFulfilled::DISCRIMINATOR),
+        vec![ConsumerFulfilled {
+            request,
+            randomness: randomness.clone(),
+            slot: 5,
+        }]
+    );
+    // The other event is only decoded with its own discriminator
+    assert!(decode_program_events::<ConsumerRequested>(
+        &transaction,
+        ConsumerRequested::DISCRIMINATOR
+    )
+    .is_empty());
+    assert_eq!(
+        decode_program_events::<Vec<u8>>(&transaction, anchor_event_discriminator("Unrelated")),
+        vec![randomness]
+    );
+}
+
+#[test]
+fn test_skips_lines_that_are_not_events() {
+    let event = ConsumerRequested {
+        request: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        num_bytes: 8,
+        request_slot: 1,
+    };
+    let mut data = ConsumerRequested::DISCRIMINATOR.to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    let transaction = transaction(vec![
+        "Program log: Requesting randomness".to_string(),
+        "Program data: not base64!".to_string(),
+        format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(ConsumerRequested::DISCRIMINATOR)
+        ),
+        format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(&data)
+        ),
+    ]);
+
+    assert_eq!(
+        decode_program_events::<ConsumerRequested>(&transaction, ConsumerRequested::DISCRIMINATOR),
+        vec![event]
+    );
+}
@@ -0,0 +1,180 @@
+//! The transactions below are synthesized to match the logs of the service program's settle
+//! and callback error instructions.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct CallbackErrorEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    error_message: String,
+}
+
+fn program_data(name: &str, event: impl BorshSerialize) -> String {
+    let mut data = hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+fn transaction(slot: u64, log: String) -> TransactionRecord {
+    TransactionRecord {
+        signature: Signature::new_unique(),
+        slot,
+        failed: false,
+        account_keys: vec![ID],
+        log_messages: vec![
+            format!("Program {} invoke [1]", ID),
+            log,
+            format!("Program {} success", ID),
+        ],
+        token_balances: vec![],
+    }
+}
+
+fn settle(request: Pubkey, slot: u64) -> TransactionRecord {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: slot - 2,
+        settled_slot: slot,
+        is_success: true,
+        randomness: vec![7; 8],
+    };
+    transaction(slot, program_data("SimpleRandomnessV1SettledEvent", event))
+}
+
+fn callback_error(request: Pubkey, slot: u64) -> TransactionRecord {
+    let event = CallbackErrorEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: slot - 2,
+        settled_slot: slot,
+        error_message: "callback failed".to_string(),
+    };
+    transaction(
+        slot,
+        program_data("SimpleRandomnessV1CallbackErrorEvent", event),
+    )
+}
+
+#[test]
+fn test_event_id_is_stable() {
+    let request = Pubkey::new_from_array([1; 32]);
+    let signature = Signature::from([2; 64]);
+
+    let id = fulfillment_event_id(&request, &signature);
+    assert_eq!(id, fulfillment_event_id(&request, &signature));
+    assert_eq!(
+        id,
+        hash(&[FULFILLMENT_EVENT_ID_DOMAIN, &[1; 32], &[2; 64]].concat()).to_bytes()
+    );
+
+    assert_ne!(
+        id,
+        fulfillment_event_id(&Pubkey::new_from_array([3; 32]), &signature)
+    );
+    assert_ne!(
+        id,
+        fulfillment_event_id(&request, &Signature::from([4; 64]))
+    );
+}
+
+#[test]
+fn test_events_carry_their_id() {
+    let request = Pubkey::new_unique();
+    let transaction = settle(request, 100);
+
+    let events = FulfillmentEvent::from_transaction(&transaction);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, FulfillmentKind::Settled);
+    assert_eq!(events[0].request, request);
+    assert_eq!(events[0].settled_slot, 100);
+    assert_eq!(
+        events[0].event_id,
+        fulfillment_event_id(&request, &transaction.signature)
+    );
+
+    let json = serde_json::to_value(&events[0]).unwrap();
+    let hex: String = events[0]
+        .event_id
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    assert_eq!(json["event_id"], hex);
+    assert_eq!(json["kind"], "settled");
+    assert_eq!(json["request"], request.to_string());
+
+    let error = callback_error(request, 101);
+    let events = FulfillmentEvent::from_transaction(&error);
+    assert_eq!(events[0].kind, FulfillmentKind::CallbackError);
+
+    let mut failed = settle(request, 102);
+    failed.failed = true;
+    assert!(FulfillmentEvent::from_transaction(&failed).is_empty());
+}
+
+/// The bridge forwards two fulfillments, disconnects, and replays from an earlier slot on
+/// reconnect. Only the fulfillment it has not forwarded yet gets through.
+#[test]
+fn test_dedupe_across_reconnect_replay() {
+    let transactions = [
+        settle(Pubkey::new_unique(), 100),
+        callback_error(Pubkey::new_unique(), 101),
+        settle(Pubkey::new_unique(), 102),
+    ];
+    let mut seen = SeenCache::new(16);
+
+    let mut forwarded = Vec::new();
+    for transaction in &transactions[..2] {
+        forwarded.extend(seen.filter_new(FulfillmentEvent::from_transaction(transaction)));
+    }
+    assert_eq!(forwarded.len(), 2);
+
+    // Reconnect and replay everything since slot 100
+    for transaction in transactions.iter() {
+        forwarded.extend(seen.filter_new(FulfillmentEvent::from_transaction(transaction)));
+    }
+    assert_eq!(forwarded.len(), 3);
+    assert_eq!(forwarded[2].signature, transactions[2].signature);
+    assert_eq!(seen.len(), 3);
+}
+
+#[test]
+fn test_seen_cache_evicts_least_recently_seen() {
+    let mut seen = SeenCache::new(2);
+    assert!(seen.insert([1; 32]));
+    assert!(seen.insert([2; 32]));
+    // Touching 1 makes 2 the least recently seen
+    assert!(!seen.insert([1; 32]));
+    assert!(seen.insert([3; 32]));
+
+    assert_eq!(seen.len(), 2);
+    assert!(seen.contains(&[1; 32]));
+    assert!(!seen.contains(&[2; 32]));
+    assert!(seen.contains(&[3; 32]));
+    assert!(seen.insert([2; 32]));
+}
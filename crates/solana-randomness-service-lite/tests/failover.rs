@@ -0,0 +1,177 @@
+use solana_randomness_service_lite::client::mock::{request_account, MockPubsub, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn pending_request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        callback: Callback::new(
+            Pubkey::new_unique(),
+            vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+            vec![1; 8],
+        ),
+        ..Default::default()
+    }
+}
+
+fn endpoints(rpcs: &[&Arc<MockRpc>]) -> Vec<Arc<dyn RandomnessRpc>> {
+    rpcs.iter()
+        .map(|rpc| (*rpc).clone() as Arc<dyn RandomnessRpc>)
+        .collect()
+}
+
+async fn wait_for_subscriptions(pubsub: &MockPubsub, count: usize) {
+    while pubsub.subscriptions() < count {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_sequential_fails_over_dead_endpoint() {
+    let dead = Arc::new(MockRpc::default());
+    dead.set_offline(true);
+    let live = Arc::new(MockRpc::default());
+    let rpc = FailoverRpc::new(endpoints(&[&dead, &live]));
+
+    assert_eq!(rpc.get_latest_blockhash().await.unwrap(), live.blockhash());
+    assert_eq!(rpc.health()[0].consecutive_failures, 1);
+    assert_eq!(rpc.health()[1].successes, 1);
+
+    // The dead endpoint is now ranked last and is not retried while the live one succeeds.
+    assert_eq!(rpc.get_latest_blockhash().await.unwrap(), live.blockhash());
+    assert_eq!(dead.calls(), 1);
+    assert_eq!(live.calls(), 2);
+}
+
+#[tokio::test]
+async fn test_sequential_times_out_slow_endpoint() {
+    let slow = Arc::new(MockRpc::default());
+    slow.set_latency(Duration::from_secs(5));
+    let live = Arc::new(MockRpc::default());
+    let rpc = FailoverRpc::new(endpoints(&[&slow, &live])).timeout(Duration::from_millis(50));
+
+    assert_eq!(rpc.get_latest_blockhash().await.unwrap(), live.blockhash());
+    assert_eq!(rpc.health()[0].failures, 1);
+}
+
+#[tokio::test]
+async fn test_all_endpoints_dead() {
+    let a = Arc::new(MockRpc::default());
+    let b = Arc::new(MockRpc::default());
+    a.set_offline(true);
+    b.set_offline(true);
+    let rpc = FailoverRpc::new(endpoints(&[&a, &b]));
+
+    assert!(matches!(
+        rpc.get_account(&Pubkey::new_unique()).await,
+        Err(LiteClientError::Rpc(_))
+    ));
+    assert!(rpc.health().iter().all(|h| h.consecutive_failures == 1));
+}
+
+#[tokio::test]
+async fn test_race_returns_first_success() {
+    let dead = Arc::new(MockRpc::default());
+    dead.set_offline(true);
+    let slow = Arc::new(MockRpc::new(Hash::new_unique()));
+    slow.set_latency(Duration::from_millis(500));
+    let fast = Arc::new(MockRpc::new(Hash::new_unique()));
+    let rpc = FailoverRpc::new(endpoints(&[&dead, &slow, &fast])).read_mode(ReadMode::Race);
+
+    assert_eq!(rpc.get_latest_blockhash().await.unwrap(), fast.blockhash());
+    assert_eq!(dead.calls(), 1);
+    assert_eq!(slow.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_send_fails_over_dead_endpoint() {
+    let dead = Arc::new(MockRpc::default());
+    dead.set_offline(true);
+    let live = Arc::new(MockRpc::default());
+    let rpc = FailoverRpc::new(endpoints(&[&dead, &live]));
+    let payer = Keypair::new();
+
+    RequestBuilder::new(payer.pubkey(), 8, pending_request().callback)
+        .send(&rpc, &payer)
+        .await
+        .unwrap();
+
+    assert!(dead.sent_transactions().is_empty());
+    assert_eq!(live.sent_transactions().len(), 1);
+}
+
+#[tokio::test]
+async fn test_await_resubscribes_after_disconnect() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+    let first = Arc::new(MockPubsub::new());
+    let second = Arc::new(MockPubsub::new());
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![first.clone(), second.clone()];
+
+    let (status, _) = tokio::join!(
+        await_fulfillment_with_pubsub(&rpc, &pubsub, &request, Duration::from_secs(5)),
+        async {
+            wait_for_subscriptions(&first, 1).await;
+            // The request settles while the first endpoint is disconnected, so no update is
+            // delivered for it.
+            rpc.remove_account(&request);
+            first.disconnect();
+        }
+    );
+
+    assert!(matches!(status.unwrap(), RequestStatus::Closed));
+    assert_eq!(second.subscriptions(), 1);
+}
+
+#[tokio::test]
+async fn test_await_skips_dead_pubsub_endpoint() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+    let dead = Arc::new(MockPubsub::new());
+    dead.set_offline(true);
+    let live = Arc::new(MockPubsub::new());
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![dead.clone(), live.clone()];
+
+    let (status, _) = tokio::join!(
+        await_fulfillment_with_pubsub(&rpc, &pubsub, &request, Duration::from_secs(5)),
+        async {
+            wait_for_subscriptions(&live, 1).await;
+            let failed = SimpleRandomnessV1Account {
+                is_completed: 1,
+                error_message: "callback failed".to_string(),
+                ..pending_request()
+            };
+            live.notify(&request, Some(request_account(&failed)));
+        }
+    );
+
+    match status.unwrap() {
+        RequestStatus::Failed(failed) => assert_eq!(failed.error_message, "callback failed"),
+        status => panic!("unexpected status {:?}", status),
+    }
+}
+
+#[tokio::test]
+async fn test_await_timeout_reports_last_status() {
+    let request = Pubkey::new_unique();
+    let rpc = MockRpc::default();
+    rpc.set_account(request, request_account(&pending_request()));
+    let pubsub: Vec<Arc<dyn RandomnessPubsub>> = vec![Arc::new(MockPubsub::new())];
+
+    let result =
+        await_fulfillment_with_pubsub(&rpc, &pubsub, &request, Duration::from_millis(50)).await;
+
+    match result {
+        Err(AwaitError::Timeout { last_status }) => match last_status.as_deref() {
+            Some(RequestStatus::Pending(pending)) => assert_eq!(pending.num_bytes, 8),
+            status => panic!("unexpected status {:?}", status),
+        },
+        result => panic!("unexpected result {:?}", result),
+    }
+}
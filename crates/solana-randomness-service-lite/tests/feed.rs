@@ -0,0 +1,83 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn feed_account(feed: &ResultFeed, owner: Pubkey) -> Account {
+    let mut data = ResultFeed::DISCRIMINATOR.to_vec();
+    data.extend(feed.try_to_vec().unwrap());
+
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn feed(user: Pubkey) -> ResultFeed {
+    let mut result = [0; 32];
+    result[..4].copy_from_slice(&[1, 2, 3, 4]);
+    ResultFeed {
+        bump: ResultFeed::address(&CONSUMER_ID, &user).1,
+        user,
+        request: Pubkey::new_unique(),
+        slot: 42,
+        result_len: 4,
+        result,
+        request_slot: 40,
+        reserved: [0; RESERVED_LEN - 8],
+    }
+}
+
+#[test]
+fn test_space_matches_layout() {
+    let account = feed_account(&feed(Pubkey::new_unique()), CONSUMER_ID);
+
+    assert_eq!(account.data.len(), ResultFeed::SPACE);
+}
+
+#[tokio::test]
+async fn test_latest_result_for_user() {
+    let rpc = MockRpc::default();
+    let user = Pubkey::new_unique();
+    let expected = feed(user);
+    let (address, _) = ResultFeed::address(&CONSUMER_ID, &user);
+    rpc.set_account(address, feed_account(&expected, CONSUMER_ID));
+
+    let feed = latest_result_for_user(&rpc, &CONSUMER_ID, &user)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(feed, expected);
+    assert_eq!(feed.latest(), Some(&[1, 2, 3, 4][..]));
+}
+
+#[tokio::test]
+async fn test_user_without_feed() {
+    let rpc = MockRpc::default();
+
+    let feed = latest_result_for_user(&rpc, &CONSUMER_ID, &Pubkey::new_unique())
+        .await
+        .unwrap();
+
+    assert_eq!(feed, None);
+}
+
+#[tokio::test]
+async fn test_rejects_feed_of_other_program() {
+    let rpc = MockRpc::default();
+    let user = Pubkey::new_unique();
+    let (address, _) = ResultFeed::address(&CONSUMER_ID, &user);
+    rpc.set_account(address, feed_account(&feed(user), Pubkey::new_unique()));
+
+    assert!(matches!(
+        latest_result_for_user(&rpc, &CONSUMER_ID, &user).await,
+        Err(LiteClientError::Program(ProgramError::IllegalOwner))
+    ));
+}
@@ -0,0 +1,219 @@
+//! `SimpleRandomnessV1Account::fetch` tells apart each way a request account fails to load.
+
+use base64::Engine;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+use std::collections::HashMap;
+
+fn request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        user: Pubkey::new_unique(),
+        request_slot: 100,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1, 2, 3]),
+        ..Default::default()
+    }
+}
+
+async fn fetch(account: Option<Account>) -> Result<SimpleRandomnessV1Account, FetchAccountError> {
+    let rpc = MockRpc::default();
+    let pubkey = Pubkey::new_unique();
+    if let Some(account) = account {
+        rpc.set_account(pubkey, account);
+    }
+    SimpleRandomnessV1Account::fetch(&rpc, &pubkey).await
+}
+
+#[tokio::test]
+async fn test_fetches_request() {
+    let expected = request();
+    let fetched = fetch(Some(request_account(&expected))).await.unwrap();
+    assert_eq!(fetched.user, expected.user);
+    assert_eq!(fetched.request_slot, 100);
+    assert_eq!(fetched.callback.program_id, expected.callback.program_id);
+    assert_eq!(fetched.callback.ix_data, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_distinguishes_failures() {
+    assert!(matches!(
+        fetch(None).await,
+        Err(FetchAccountError::NotFound(_))
+    ));
+    // Closed in the same transaction, so the account is empty
+    let closed = Account {
+        lamports: 0,
+        ..request_account(&request())
+    };
+    assert!(matches!(
+        fetch(Some(closed)).await,
+        Err(FetchAccountError::NotFound(_))
+    ));
+
+    let other = Pubkey::new_unique();
+    match fetch(Some(Account {
+        owner: other,
+        ..request_account(&request())
+    }))
+    .await
+    {
+        Err(FetchAccountError::WrongOwner { expected, actual }) => {
+            assert_eq!(expected, ID);
+            assert_eq!(actual, other);
+        }
+        other => panic!("expected WrongOwner, got {:?}", other),
+    }
+
+    let mut state = request_account(&request());
+    state.data[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+    assert!(matches!(
+        fetch(Some(state)).await,
+        Err(FetchAccountError::BadDiscriminator)
+    ));
+    let mut short = request_account(&request());
+    short.data.truncate(4);
+    assert!(matches!(
+        fetch(Some(short)).await,
+        Err(FetchAccountError::BadDiscriminator)
+    ));
+
+    let mut truncated = request_account(&request());
+    truncated.data.truncate(40);
+    assert!(matches!(
+        fetch(Some(truncated)).await,
+        Err(FetchAccountError::Deserialize(
+            ProgramError::InvalidAccountData
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_reports_rpc_failure() {
+    let rpc = MockRpc::default();
+    rpc.set_offline(true);
+    assert!(matches!(
+        SimpleRandomnessV1Account::fetch(&rpc, &Pubkey::new_unique()).await,
+        Err(FetchAccountError::Client(LiteClientError::Rpc(_)))
+    ));
+}
+
+/// A blocking client answering `getAccountInfo` with the account, or `null`.
+fn blocking_rpc(account: Option<&Account>) -> RpcClient {
+    let value = account.map(|account| {
+        serde_json::json!({
+            "lamports": account.lamports,
+            "data": [
+                base64::engine::general_purpose::STANDARD.encode(&account.data),
+                "base64"
+            ],
+            "owner": account.owner.to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+        })
+    });
+    let mut mocks = HashMap::new();
+    mocks.insert(
+        RpcRequest::GetAccountInfo,
+        serde_json::json!({ "context": { "slot": 1 }, "value": value }),
+    );
+    RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+}
+
+#[test]
+fn test_fetch_blocking() {
+    let expected = request();
+    let pubkey = Pubkey::new_unique();
+    let account = request_account(&expected);
+
+    let fetched =
+        SimpleRandomnessV1Account::fetch_blocking(&blocking_rpc(Some(&account)), &pubkey).unwrap();
+    assert_eq!(fetched.user, expected.user);
+
+    assert!(matches!(
+        SimpleRandomnessV1Account::fetch_blocking(&blocking_rpc(None), &pubkey),
+        Err(FetchAccountError::NotFound(p)) if p == pubkey
+    ));
+    let foreign = Account {
+        owner: Pubkey::new_unique(),
+        ..account
+    };
+    assert!(matches!(
+        SimpleRandomnessV1Account::fetch_blocking(&blocking_rpc(Some(&foreign)), &pubkey),
+        Err(FetchAccountError::WrongOwner { .. })
+    ));
+}
+
+const SERVICE_STATE: &[u8] = include_bytes!("fixtures/service_state.bin");
+
+fn state_account(owner: Pubkey) -> Account {
+    Account {
+        lamports: 1,
+        data: SERVICE_STATE.to_vec(),
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_fetches_state_with_slot() {
+    let rpc = MockRpc::default();
+    rpc.set_account(RANDOMNESS_SERVICE_STATE, state_account(ID));
+    rpc.warp_to_slot(250_000_000);
+
+    let (slot, state) = ServiceState::fetch(&rpc, &ID).await.unwrap();
+    assert_eq!(slot, 250_000_000);
+    assert_eq!(state.cost_per_byte, 10_000);
+    assert_eq!(state.wallet, RANDOMNESS_SERVICE_REWARD_WALLET);
+
+    // Nothing is cached: the next fetch sees the new slot
+    rpc.warp_to_slot(250_000_100);
+    assert_eq!(ServiceState::fetch(&rpc, &ID).await.unwrap().0, 250_000_100);
+}
+
+#[tokio::test]
+async fn test_fetches_state_of_program() {
+    let rpc = MockRpc::default();
+    let fork = Pubkey::new_unique();
+    let (state, _) = derive_state_pubkey(&fork);
+
+    assert!(matches!(
+        ServiceState::fetch(&rpc, &fork).await,
+        Err(FetchAccountError::NotFound(p)) if p == state
+    ));
+    // The state of the fork must be owned by the fork
+    rpc.set_account(state, state_account(ID));
+    assert!(matches!(
+        ServiceState::fetch(&rpc, &fork).await,
+        Err(FetchAccountError::WrongOwner { expected, actual }) if expected == fork && actual == ID
+    ));
+    rpc.set_account(state, state_account(fork));
+    assert!(ServiceState::fetch(&rpc, &fork).await.is_ok());
+
+    let mut request = state_account(fork);
+    request.data[..8].copy_from_slice(&SimpleRandomnessV1Account::DISCRIMINATOR);
+    rpc.set_account(state, request);
+    assert!(matches!(
+        ServiceState::fetch(&rpc, &fork).await,
+        Err(FetchAccountError::BadDiscriminator)
+    ));
+}
+
+/// Reads the deployed state from devnet. Run with `cargo test --features client --test fetch --
+/// --ignored`.
+#[tokio::test]
+#[ignore = "reads devnet"]
+async fn test_fetches_devnet_state() {
+    let rpc = solana_client::nonblocking::rpc_client::RpcClient::new(
+        "https://api.devnet.solana.com".to_string(),
+    );
+    let (slot, state) = ServiceState::fetch(&rpc, &ServiceAddresses::devnet().program_id)
+        .await
+        .unwrap();
+    assert!(slot > 0);
+    assert_eq!(state.mint, RANDOMNESS_SERVICE_REWARD_MINT);
+}
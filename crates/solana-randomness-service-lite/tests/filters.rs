@@ -0,0 +1,87 @@
+//! The `getProgramAccounts` filters line up with the serialized request account.
+
+use solana_client::rpc_filter::RpcFilterType;
+use solana_randomness_service_lite::client::filters;
+use solana_randomness_service_lite::client::mock::request_account;
+use solana_randomness_service_lite::*;
+
+fn request(is_completed: u8, user: Pubkey) -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        is_completed,
+        num_bytes: 8,
+        user,
+        escrow: Pubkey::new_unique(),
+        request_slot: 42,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        error_message: if is_completed == 1 {
+            "callback failed".to_string()
+        } else {
+            String::new()
+        },
+        ..Default::default()
+    }
+}
+
+/// Whether the account data passes every filter, as the RPC node evaluates them.
+fn matches(filters: &[RpcFilterType], data: &[u8]) -> bool {
+    filters.iter().all(|filter| match filter {
+        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+        filter => panic!("unexpected filter {:?}", filter),
+    })
+}
+
+#[test]
+fn test_offsets_match_layout() {
+    let user = Pubkey::new_unique();
+    let request = request(1, user);
+    let data = request_account(&request).data;
+
+    assert_eq!(&data[..8], &SimpleRandomnessV1Account::DISCRIMINATOR);
+    assert_eq!(data[RequestHeader::IS_COMPLETED_OFFSET], 1);
+    assert_eq!(data[RequestHeader::NUM_BYTES_OFFSET], 8);
+    assert_eq!(
+        &data[RequestHeader::USER_OFFSET..RequestHeader::USER_OFFSET + 32],
+        user.as_ref()
+    );
+    assert_eq!(
+        &data[RequestHeader::ESCROW_OFFSET..RequestHeader::ESCROW_OFFSET + 32],
+        request.escrow.as_ref()
+    );
+    assert_eq!(
+        &data[RequestHeader::REQUEST_SLOT_OFFSET..RequestHeader::LEN],
+        &42u64.to_le_bytes()
+    );
+
+    // The variable-length fields follow the header
+    let mut rest = &data[RequestHeader::LEN..];
+    let callback: Callback = borsh::BorshDeserialize::deserialize(&mut rest).unwrap();
+    assert_eq!(callback.ix_data, vec![1; 8]);
+}
+
+#[test]
+fn test_status_filters() {
+    let pending = request_account(&request(0, Pubkey::new_unique())).data;
+    let completed = request_account(&request(1, Pubkey::new_unique())).data;
+    let mut state = vec![0; ServiceState::LEN];
+    state[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+
+    assert!(matches(&filters::all_requests(), &pending));
+    assert!(matches(&filters::all_requests(), &completed));
+    assert!(!matches(&filters::all_requests(), &state));
+
+    assert!(matches(&filters::pending_requests(), &pending));
+    assert!(!matches(&filters::pending_requests(), &completed));
+
+    assert!(matches(&filters::completed_requests(), &completed));
+    assert!(!matches(&filters::completed_requests(), &pending));
+}
+
+#[test]
+fn test_user_filter() {
+    let user = Pubkey::new_unique();
+    let own = request_account(&request(0, user)).data;
+    let other = request_account(&request(0, Pubkey::new_unique())).data;
+
+    assert!(matches(&filters::requests_by_user(&user), &own));
+    assert!(!matches(&filters::requests_by_user(&user), &other));
+}
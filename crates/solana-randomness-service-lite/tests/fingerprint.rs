@@ -0,0 +1,132 @@
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+use solana_sdk::hash::hash;
+
+const PROGRAM_BYTES: &[u8] = b"\x7fELF synthesized randomness service program";
+
+fn loader_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner: bpf_loader_upgradeable::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Serves the program and ProgramData accounts the upgradeable loader creates on deploy.
+fn deploy(rpc: &MockRpc, program_bytes: &[u8], slot: u64, authority: Option<Pubkey>) {
+    let (programdata_address, _) =
+        Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID);
+
+    let mut program = 2u32.to_le_bytes().to_vec();
+    program.extend(programdata_address.to_bytes());
+    let mut program = loader_account(program);
+    program.executable = true;
+    rpc.set_account(ID, program);
+
+    let mut program_data = 3u32.to_le_bytes().to_vec();
+    program_data.extend(slot.to_le_bytes());
+    match authority {
+        Some(authority) => {
+            program_data.push(1);
+            program_data.extend(authority.to_bytes());
+        }
+        None => program_data.push(0),
+    }
+    program_data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    program_data.extend(program_bytes);
+    rpc.set_account(programdata_address, loader_account(program_data));
+}
+
+#[tokio::test]
+async fn test_fingerprint_reads_program_data() {
+    let rpc = MockRpc::default();
+    let authority = Pubkey::new_unique();
+    deploy(&rpc, PROGRAM_BYTES, 250_000_000, Some(authority));
+
+    let fingerprint = service_program_fingerprint(&rpc).await.unwrap();
+    assert_eq!(
+        fingerprint,
+        ProgramFingerprint {
+            program_data_hash: hash(PROGRAM_BYTES),
+            upgrade_authority: Some(authority),
+            last_deploy_slot: 250_000_000,
+        }
+    );
+    assert_eq!(
+        verify_fingerprint(&rpc, &fingerprint).await.unwrap(),
+        fingerprint
+    );
+}
+
+#[tokio::test]
+async fn test_immutable_program_has_no_authority() {
+    let rpc = MockRpc::default();
+    deploy(&rpc, PROGRAM_BYTES, 1, None);
+
+    let fingerprint = service_program_fingerprint(&rpc).await.unwrap();
+    assert_eq!(fingerprint.upgrade_authority, None);
+    assert_eq!(fingerprint.program_data_hash, hash(PROGRAM_BYTES));
+}
+
+#[tokio::test]
+async fn test_changed_hash_is_rejected() {
+    let rpc = MockRpc::default();
+    let authority = Pubkey::new_unique();
+    deploy(&rpc, PROGRAM_BYTES, 100, Some(authority));
+    let pinned = service_program_fingerprint(&rpc).await.unwrap();
+
+    deploy(&rpc, b"\x7fELF upgraded program", 200, Some(authority));
+    match verify_fingerprint(&rpc, &pinned).await {
+        Err(FingerprintError::Changed { expected, actual }) => {
+            assert_eq!(expected, pinned);
+            assert_eq!(actual.program_data_hash, hash(b"\x7fELF upgraded program"));
+            assert_eq!(actual.last_deploy_slot, 200);
+            assert_eq!(actual.upgrade_authority, Some(authority));
+        }
+        other => panic!("expected a changed fingerprint, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_changed_authority_is_rejected() {
+    let rpc = MockRpc::default();
+    deploy(&rpc, PROGRAM_BYTES, 100, Some(Pubkey::new_unique()));
+    let pinned = service_program_fingerprint(&rpc).await.unwrap();
+
+    let new_authority = Pubkey::new_unique();
+    deploy(&rpc, PROGRAM_BYTES, 100, Some(new_authority));
+    let err = verify_fingerprint(&rpc, &pinned).await.unwrap_err();
+    match &err {
+        FingerprintError::Changed { actual, .. } => {
+            assert_eq!(actual.program_data_hash, pinned.program_data_hash);
+            assert_eq!(actual.upgrade_authority, Some(new_authority));
+        }
+        other => panic!("expected a changed fingerprint, got {:?}", other),
+    }
+    assert!(err.to_string().contains("upgrade authority"));
+    assert!(!err.to_string().contains("hash"));
+}
+
+#[tokio::test]
+async fn test_rejects_non_upgradeable_program() {
+    let rpc = MockRpc::default();
+    let mut program = loader_account(vec![0; 36]);
+    program.owner = solana_program::bpf_loader::ID;
+    rpc.set_account(ID, program);
+
+    assert!(matches!(
+        service_program_fingerprint(&rpc).await,
+        Err(LiteClientError::Program(ProgramError::IncorrectProgramId))
+    ));
+
+    rpc.remove_account(&ID);
+    assert!(matches!(
+        service_program_fingerprint(&rpc).await,
+        Err(LiteClientError::Program(ProgramError::UninitializedAccount))
+    ));
+}
@@ -0,0 +1,69 @@
+//! A consumer written against 1.0 of this crate. Frozen: do not update it to the new API. It
+//! is compiled by `tests/migration.rs` to prove the deprecated names still work.
+
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+use solana_randomness_service_lite::{
+    Callback, ProgramError, SimpleRandomnessV1AccountRefs, SimpleRandomnessV1Request, State,
+    TransactionOptions,
+};
+
+pub fn options() -> Option<TransactionOptions> {
+    Some(TransactionOptions {
+        compute_units: Some(1_000_000),
+        compute_unit_price: Some(100),
+    })
+}
+
+pub fn decode_state(data: &[u8]) -> Result<State, ProgramError> {
+    State::try_deserialize(&mut &data[..])
+}
+
+pub fn instruction(
+    request: &SimpleRandomnessV1Request,
+    callback: &Callback,
+) -> Result<Instruction, ProgramError> {
+    request.get_instruction(solana_randomness_service_lite::ID, 8, callback, &options())
+}
+
+pub fn instruction_data(callback: &Callback) -> Result<Vec<u8>, ProgramError> {
+    SimpleRandomnessV1Request::instruction_data(8, callback, &None)
+}
+
+pub fn borrowed_instruction(
+    refs: &SimpleRandomnessV1AccountRefs,
+    callback: &Callback,
+) -> Result<Instruction, ProgramError> {
+    refs.get_instruction(solana_randomness_service_lite::ID, 8, callback, &options())
+}
+
+pub fn request_randomness<'info>(
+    request: &SimpleRandomnessV1Request<'info>,
+    program: solana_program::account_info::AccountInfo<'info>,
+    callback: &Callback,
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> ProgramResult {
+    match signer_seeds {
+        Some(seeds) => request.invoke_signed(program, 8, callback, &options(), seeds),
+        None => request.invoke(program, 8, callback, &options()),
+    }
+}
+
+pub fn request_randomness_borrowed<'info>(
+    refs: &SimpleRandomnessV1AccountRefs<'_, 'info>,
+    program: &solana_program::account_info::AccountInfo<'info>,
+    callback: &Callback,
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> ProgramResult {
+    match signer_seeds {
+        Some(seeds) => SimpleRandomnessV1Request::invoke_signed_borrowed(
+            refs,
+            program,
+            8,
+            callback,
+            &options(),
+            seeds,
+        ),
+        None => SimpleRandomnessV1Request::invoke_borrowed(refs, program, 8, callback, &None),
+    }
+}
@@ -0,0 +1,153 @@
+//! The request instruction data is framed by Borsh lengths, not by its contents. Callbacks
+//! whose instruction data looks like the `Option` tags and options that follow it must decode
+//! back to exactly what was encoded.
+
+use borsh::BorshSerialize;
+use proptest::prelude::*;
+use solana_randomness_service_lite::*;
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    prop_oneof![
+        Just(Pubkey::default()),
+        Just(Pubkey::new_from_array([1; 32])),
+        any::<[u8; 32]>().prop_map(Pubkey::new_from_array),
+    ]
+}
+
+fn accounts() -> impl Strategy<Value = Vec<AccountMetaBorsh>> {
+    prop_oneof![
+        Just(vec![]),
+        prop::collection::vec(
+            (pubkey(), any::<bool>(), any::<bool>()).prop_map(
+                |(pubkey, is_signer, is_writable)| AccountMetaBorsh {
+                    pubkey,
+                    is_signer,
+                    is_writable,
+                }
+            ),
+            1..=Callback::MAX_ACCOUNTS,
+        ),
+    ]
+}
+
+fn options() -> impl Strategy<Value = Option<TransactionOptions>> {
+    prop::option::of(
+        (
+            prop::option::of(any::<u32>()),
+            prop::option::of(any::<u64>()),
+        )
+            .prop_map(|(compute_units, compute_unit_price)| TransactionOptions {
+                compute_units,
+                compute_unit_price,
+            }),
+    )
+}
+
+/// Instruction data starting with bytes a decoder scanning for the options could mistake for
+/// them: `Option` tags, an encoded `Option<TransactionOptions>`, or a zero length prefix.
+fn ix_data() -> impl Strategy<Value = Vec<u8>> {
+    let options_prefix = options().prop_map(|options| options.try_to_vec().unwrap());
+    let prefix = prop_oneof![
+        Just(vec![]),
+        Just(vec![0]),
+        Just(vec![1]),
+        Just(vec![1, 0, 0]),
+        Just(vec![0; 4]),
+        options_prefix,
+    ];
+    (prefix, prop::collection::vec(any::<u8>(), 0..=32)).prop_map(|(mut prefix, rest)| {
+        prefix.extend(rest);
+        prefix.truncate(Callback::MAX_IX_DATA_LEN);
+        prefix
+    })
+}
+
+fn callback() -> impl Strategy<Value = Callback> {
+    (pubkey(), accounts(), ix_data())
+        .prop_map(|(program_id, accounts, ix_data)| Callback::new(program_id, accounts, ix_data))
+}
+
+/// Asserts `data` decodes to exactly the encoded arguments.
+fn assert_round_trip(
+    data: &[u8],
+    num_bytes: u8,
+    callback: &Callback,
+    options: &Option<TransactionOptions>,
+) -> Result<(), TestCaseError> {
+    let decoded = decode_any_request_instruction(data, None).unwrap();
+
+    prop_assert_eq!(decoded.layout, RequestLayoutVersion::V1);
+    prop_assert_eq!(decoded.num_bytes, num_bytes);
+    prop_assert_eq!(
+        decoded.callback.try_to_vec().unwrap(),
+        callback.try_to_vec().unwrap()
+    );
+    prop_assert_eq!(
+        decoded.options.try_to_vec().unwrap(),
+        options.try_to_vec().unwrap()
+    );
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn adversarial_callbacks_round_trip(
+        num_bytes in 1..=MAX_NUM_BYTES,
+        callback in callback(),
+        options in options(),
+    ) {
+        let data =
+            SimpleRandomnessV1Request::data(num_bytes, &callback, options.as_ref()).unwrap();
+
+        assert_round_trip(&data, num_bytes, &callback, &options)?;
+    }
+
+    #[test]
+    fn trailing_or_missing_bytes_never_decode(
+        num_bytes in 1..=MAX_NUM_BYTES,
+        callback in callback(),
+        options in options(),
+        trailing in prop::collection::vec(any::<u8>(), 1..=16),
+    ) {
+        let data =
+            SimpleRandomnessV1Request::data(num_bytes, &callback, options.as_ref()).unwrap();
+
+        // Every byte belongs to one argument, so the data cannot be split another way
+        for len in 0..data.len() {
+            prop_assert!(decode_any_request_instruction(&data[..len], None).is_err());
+        }
+        let mut extended = data.clone();
+        extended.extend(trailing);
+        prop_assert!(decode_any_request_instruction(&extended, None).is_err());
+    }
+}
+
+#[test]
+fn test_ix_data_shaped_like_options_stays_in_the_callback() {
+    let options = TransactionOptions {
+        compute_units: Some(1_000_000),
+        compute_unit_price: Some(100),
+    };
+    // The callback's data is the encoding of `Some(options)`, and the request has none
+    let callback = Callback::new(
+        Pubkey::default(),
+        vec![],
+        Some(options.clone()).try_to_vec().unwrap(),
+    );
+
+    for request_options in [None, Some(options)] {
+        let data = SimpleRandomnessV1Request::data(8, &callback, request_options.as_ref()).unwrap();
+
+        assert_round_trip(&data, 8, &callback, &request_options).unwrap();
+    }
+}
+
+#[test]
+fn test_empty_callback_round_trips() {
+    let callback = Callback::new(Pubkey::default(), vec![], vec![]);
+    let data = SimpleRandomnessV1Request::data(1, &callback, None).unwrap();
+
+    // Discriminator, num_bytes, program id, two empty vecs, and the `None` tag
+    assert_eq!(data.len(), 8 + 1 + 32 + 4 + 4 + 1);
+    assert_round_trip(&data, 1, &callback, &None).unwrap();
+}
@@ -0,0 +1,94 @@
+//! The pending record a `RequestGuard` writes around the service CPI.
+
+use solana_program::rent::Rent;
+use solana_randomness_service_lite::*;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Record {
+    request: Pubkey,
+    request_slot: u64,
+    pending: bool,
+}
+
+impl PendingRecord for Record {
+    fn mark_pending(&mut self, request: Pubkey, slot: u64) {
+        self.request = request;
+        self.request_slot = slot;
+        self.pending = true;
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending = false;
+    }
+}
+
+#[test]
+fn test_commit_keeps_pending_record() {
+    let mut record = Record::default();
+    let request = Pubkey::new_unique();
+
+    let guard = RequestGuard::begin(&mut record, request, 1_000);
+    assert_eq!(guard.request(), request);
+    assert_eq!(guard.commit(), request);
+
+    assert_eq!(
+        record,
+        Record {
+            request,
+            request_slot: 1_000,
+            pending: true,
+        }
+    );
+}
+
+#[test]
+fn test_abort_clears_pending_record() {
+    let mut record = Record::default();
+
+    RequestGuard::begin(&mut record, Pubkey::new_unique(), 1_000).abort();
+
+    assert!(!record.pending);
+}
+
+#[test]
+fn test_early_return_clears_pending_record() {
+    fn request(record: &mut Record, cpi: Result<(), ProgramError>) -> Result<Pubkey, ProgramError> {
+        let guard = RequestGuard::begin(record, Pubkey::new_unique(), 1_000);
+        cpi?;
+        Ok(guard.commit())
+    }
+    let mut record = Record::default();
+
+    assert_eq!(
+        request(&mut record, Err(ProgramError::InsufficientFunds)),
+        Err(ProgramError::InsufficientFunds)
+    );
+    assert!(!record.pending);
+
+    let request = request(&mut record, Ok(())).unwrap();
+    assert!(record.pending);
+    assert_eq!(record.request, request);
+}
+
+#[test]
+fn test_required_lamports_cover_fee_and_rent() {
+    let callback = Callback::new(
+        Pubkey::new_unique(),
+        vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+        vec![1; 8],
+    );
+    let options = TransactionOptions {
+        compute_units: Some(1_000_000),
+        compute_unit_price: Some(100),
+    };
+    let rent = Rent::default();
+
+    assert_eq!(
+        required_request_lamports(8, &callback, Some(&options), 10, &rent).unwrap(),
+        REQUEST_BASE_FEE
+            + 80
+            + 100
+            + rent.minimum_balance(SimpleRandomnessV1Account::space(&callback))
+            + rent.minimum_balance(ESCROW_ACCOUNT_SIZE)
+    );
+}
@@ -0,0 +1,468 @@
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::*;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const COUNTER_ID: Pubkey = Pubkey::new_from_array([8; 32]);
+const DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+const NUM_BYTES: u8 = 8;
+
+const ALREADY_SETTLED: u32 = 1;
+const INVALID_LENGTH: u32 = 2;
+
+/// The result account layout: settled flag, settled slot, then the randomness.
+const RESULT_LEN: usize = 1 + 8 + NUM_BYTES as usize;
+
+/// A consumer callback with the guards the harness is meant to exercise. Accounts: the service
+/// state, the result account, the counter PDA, and the counter program.
+fn process_callback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [state, result, counter, _counter_program] = accounts else {
+        randomness_bail!(ProgramError::NotEnoughAccountKeys);
+    };
+    require_randomness!(
+        *state.key == RANDOMNESS_SERVICE_STATE && state.is_signer,
+        LiteError::CallbackStateNotSigner
+    );
+    require_randomness!(
+        data.get(..8) == Some(&DISCRIMINATOR[..]),
+        ProgramError::InvalidInstructionData
+    );
+
+    let len = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+    let randomness = &data[12..];
+    if len != randomness.len() || len != NUM_BYTES as usize {
+        msg!("Expected {} bytes of randomness, got {}", NUM_BYTES, len);
+        randomness_bail!(ProgramError::Custom(INVALID_LENGTH));
+    }
+
+    let mut result = result.try_borrow_mut_data()?;
+    require_randomness!(result[0] == 0, ProgramError::Custom(ALREADY_SETTLED));
+    result[0] = 1;
+    result[1..9].copy_from_slice(&Clock::get()?.slot.to_le_bytes());
+    result[9..].copy_from_slice(randomness);
+
+    // Count the settlement with a CPI signed by the consumer's PDA
+    let (_, bump) = Pubkey::find_program_address(&[b"COUNTER"], program_id);
+    invoke_signed(
+        &Instruction::new_with_bytes(COUNTER_ID, &[], vec![AccountMeta::new(*counter.key, true)]),
+        std::slice::from_ref(counter),
+        &[&[b"COUNTER", &[bump]]],
+    )
+}
+
+fn process_counter(_program_id: &Pubkey, accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    let counter = &accounts[0];
+    if !counter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut data = counter.try_borrow_mut_data()?;
+    data[0] += 1;
+    Ok(())
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    request: Pubkey,
+    result: Pubkey,
+    counter: Pubkey,
+    user: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(CONSUMER_ID, process_callback);
+        harness.add_program(COUNTER_ID, process_counter);
+
+        let result = Pubkey::new_unique();
+        harness.set_account(
+            result,
+            HarnessAccount::new(vec![0; RESULT_LEN], CONSUMER_ID),
+        );
+        let (counter, _) = Pubkey::find_program_address(&[b"COUNTER"], &CONSUMER_ID);
+        harness.set_account(counter, HarnessAccount::new(vec![0], COUNTER_ID));
+        harness.set_account(
+            RANDOMNESS_SERVICE_STATE,
+            HarnessAccount::new(vec![0; 8], ID),
+        );
+
+        let request = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        harness.add_request(
+            request,
+            &SimpleRandomnessV1Account {
+                num_bytes: NUM_BYTES,
+                user,
+                callback: Callback::new(
+                    CONSUMER_ID,
+                    vec![
+                        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+                        AccountMeta::new(result, false).into(),
+                        AccountMeta::new(counter, false).into(),
+                        AccountMeta::new_readonly(COUNTER_ID, false).into(),
+                    ],
+                    DISCRIMINATOR.to_vec(),
+                ),
+                ..Default::default()
+            },
+        );
+
+        Self {
+            harness,
+            request,
+            result,
+            counter,
+            user,
+        }
+    }
+
+    /// Submits a funded request from the harness payer with the fixture's callback.
+    fn submit(&mut self, options: Option<TransactionOptions>) -> Pubkey {
+        let callback = self.harness.request(&self.request).unwrap().callback;
+        let request = Pubkey::new_unique();
+        self.harness
+            .submit_request(request, NUM_BYTES, callback, options)
+            .unwrap();
+        request
+    }
+
+    fn result(&self) -> &[u8] {
+        &self.harness.account(&self.result).unwrap().data
+    }
+
+    fn count(&self) -> u8 {
+        self.harness.account(&self.counter).unwrap().data[0]
+    }
+}
+
+#[test]
+fn test_fulfill_invokes_callback_and_closes_request() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(1_234);
+    let randomness = fixture.harness.randomness(&fixture.request, 8);
+    let request_lamports = fixture.harness.account(&fixture.request).unwrap().lamports;
+
+    fixture
+        .harness
+        .fulfill(&fixture.request, &randomness)
+        .unwrap();
+
+    assert_eq!(fixture.result()[0], 1);
+    assert_eq!(fixture.result()[1..9], 1_234u64.to_le_bytes());
+    assert_eq!(fixture.result()[9..], randomness[..]);
+    assert_eq!(fixture.count(), 1);
+
+    assert!(fixture.harness.account(&fixture.request).is_none());
+    assert_eq!(
+        fixture.harness.account(&fixture.user).unwrap().lamports,
+        request_lamports
+    );
+}
+
+#[test]
+fn test_fulfill_with_error_surfaces_message() {
+    let mut fixture = Fixture::new();
+
+    fixture
+        .harness
+        .fulfill_with_error(&fixture.request, "Callback simulation failed")
+        .unwrap();
+
+    let request = fixture.harness.request(&fixture.request).unwrap();
+    assert_eq!(request.is_completed, 1);
+    assert_eq!(request.error_message, "Callback simulation failed");
+    assert_eq!(fixture.result()[0], 0, "callback should not run");
+
+    // A completed request can no longer be fulfilled
+    let randomness = fixture.harness.randomness(&fixture.request, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&fixture.request, &randomness),
+        Err(ServiceError::RequestAlreadyCompleted.into())
+    );
+    assert_eq!(
+        fixture
+            .harness
+            .fulfill_with_error(&fixture.request, &"x".repeat(300)),
+        Err(ServiceError::RequestAlreadyCompleted.into())
+    );
+}
+
+#[test]
+fn test_fulfill_with_error_rejects_long_message() {
+    let mut fixture = Fixture::new();
+
+    assert_eq!(
+        fixture.harness.fulfill_with_error(
+            &fixture.request,
+            &"x".repeat(SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN + 1)
+        ),
+        Err(ServiceError::ErrorMessageOverflow.into())
+    );
+    assert_eq!(
+        fixture
+            .harness
+            .request(&fixture.request)
+            .unwrap()
+            .is_completed,
+        0
+    );
+}
+
+#[test]
+fn test_fulfill_twice_hits_replay_guard() {
+    let mut fixture = Fixture::new();
+    let randomness = fixture.harness.randomness(&fixture.request, 8);
+
+    let (first, second) = fixture.harness.fulfill_twice(&fixture.request, &randomness);
+
+    assert_eq!(first, Ok(()));
+    assert_eq!(second, Err(ProgramError::Custom(ALREADY_SETTLED)));
+    assert_eq!(fixture.count(), 1);
+    assert!(fixture.harness.account(&fixture.request).is_none());
+}
+
+#[test]
+fn test_fulfill_short_hits_length_guard_and_reverts() {
+    let mut fixture = Fixture::new();
+
+    assert_eq!(
+        fixture.harness.fulfill_short(&fixture.request, 4),
+        Err(ProgramError::Custom(INVALID_LENGTH))
+    );
+    assert!(fixture.harness.logs()[0].contains("got 4"));
+    assert_eq!(
+        fixture.harness.logs()[1],
+        "Error: Custom program error: 0x2"
+    );
+
+    // The failed callback leaves every account untouched
+    assert_eq!(fixture.result(), [0; RESULT_LEN]);
+    assert_eq!(fixture.count(), 0);
+    assert!(fixture.harness.request(&fixture.request).is_some());
+}
+
+#[test]
+fn test_transient_failures_are_discarded_before_retry() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(1_000);
+    let randomness = fixture.harness.randomness(&fixture.request, 8);
+
+    fixture
+        .harness
+        .fulfill_with_transient_failure_then_success(&fixture.request, &randomness, 2)
+        .unwrap();
+
+    // Only the last attempt's writes are kept, so the replay guard does not trip
+    assert_eq!(fixture.result()[0], 1);
+    assert_eq!(
+        fixture.result()[1..9],
+        (1_000 + 2 * CALLBACK_RETRY_BACKOFF_SLOTS).to_le_bytes()
+    );
+    assert_eq!(fixture.result()[9..], randomness[..]);
+    assert_eq!(fixture.count(), 1);
+    assert!(fixture.harness.request(&fixture.request).is_none());
+}
+
+#[test]
+fn test_transient_failures_keep_logs_of_every_attempt() {
+    let mut fixture = Fixture::new();
+    let short = fixture.harness.randomness(&fixture.request, 4);
+
+    assert_eq!(
+        fixture
+            .harness
+            .fulfill_with_transient_failure_then_success(&fixture.request, &short, 1),
+        Err(ProgramError::Custom(INVALID_LENGTH))
+    );
+    assert_eq!(
+        fixture
+            .harness
+            .logs()
+            .iter()
+            .filter(|log| log.contains("got 4"))
+            .count(),
+        2
+    );
+    assert_eq!(fixture.result(), [0; RESULT_LEN]);
+    assert!(fixture.harness.request(&fixture.request).is_some());
+}
+
+#[test]
+fn test_randomness_is_deterministic() {
+    let fixture = Fixture::new();
+
+    assert_eq!(
+        fixture.harness.randomness(&fixture.request, 40),
+        fixture.harness.randomness(&fixture.request, 40)
+    );
+    assert_eq!(
+        fixture.harness.randomness(&fixture.request, 40)[..8],
+        fixture.harness.randomness(&fixture.request, 8)[..]
+    );
+    assert_ne!(
+        fixture.harness.randomness(&fixture.request, 8),
+        fixture.harness.randomness(&Pubkey::new_unique(), 8)
+    );
+}
+
+#[test]
+fn test_rejects_unsigned_state() {
+    let mut fixture = Fixture::new();
+    let mut request = fixture.harness.request(&fixture.request).unwrap();
+    request.callback.accounts[0].is_signer = false;
+    fixture.harness.add_request(fixture.request, &request);
+
+    assert_eq!(
+        fixture.harness.fulfill_short(&fixture.request, 8),
+        Err(ServiceError::InvalidCallback.into())
+    );
+}
+
+const COST_PER_BYTE: u64 = 100;
+
+/// The rent of the request account created by `Fixture::submit` and of its escrow.
+fn rents(fixture: &Fixture, request: &Pubkey) -> (u64, u64) {
+    let request_rent = fixture.harness.account(request).unwrap().lamports;
+    let escrow_rent = Rent::default().minimum_balance(165);
+    (request_rent, escrow_rent)
+}
+
+#[test]
+fn test_balances_through_settlement() {
+    let mut fixture = Fixture::new();
+    fixture.harness.set_cost_per_byte(COST_PER_BYTE);
+    let payer = fixture.harness.payer();
+    fixture.harness.airdrop(&payer, 1_000_000_000);
+    let options = Some(TransactionOptions {
+        compute_units: Some(200_000),
+        compute_unit_price: Some(10_000),
+    });
+
+    let before = fixture.harness.balances(&Pubkey::new_unique());
+    let request = fixture.submit(options.clone());
+    let requested = fixture.harness.balances(&request);
+    let (request_rent, escrow_rent) = rents(&fixture, &request);
+
+    // Base fee, randomness fee, and priority fee
+    let cost = 10_000 + COST_PER_BYTE * u64::from(NUM_BYTES) + 2_000;
+    assert_eq!(fixture.harness.request_cost(NUM_BYTES, &options), Ok(cost));
+
+    // The payer funds both accounts and wraps the fee into the escrow
+    assert_eq!(
+        before.payer - requested.payer,
+        request_rent + escrow_rent + cost
+    );
+    assert_eq!(requested.escrow, escrow_rent + cost);
+    assert_eq!(requested.reward_wallet, before.reward_wallet);
+
+    let randomness = fixture.harness.randomness(&request, NUM_BYTES.into());
+    fixture.harness.fulfill(&request, &randomness).unwrap();
+    let settled = fixture.harness.balances(&request);
+
+    // The reward wallet keeps the fee and both rents return to the payer
+    assert_eq!(settled.reward_wallet - requested.reward_wallet, cost);
+    assert_eq!(settled.payer - requested.payer, request_rent + escrow_rent);
+    assert_eq!(settled.escrow, 0);
+    assert_eq!(before.payer - settled.payer, cost);
+    assert!(fixture.harness.account(&request).is_none());
+}
+
+#[test]
+fn test_balances_through_error_and_close() {
+    let mut fixture = Fixture::new();
+    fixture.harness.set_cost_per_byte(COST_PER_BYTE);
+    let payer = fixture.harness.payer();
+    fixture.harness.airdrop(&payer, 1_000_000_000);
+
+    let before = fixture.harness.balances(&Pubkey::new_unique());
+    let request = fixture.submit(None);
+    let requested = fixture.harness.balances(&request);
+    let (request_rent, escrow_rent) = rents(&fixture, &request);
+    let cost = fixture.harness.request_cost(NUM_BYTES, &None).unwrap();
+
+    fixture
+        .harness
+        .fulfill_with_error(&request, "Callback simulation failed")
+        .unwrap();
+    let errored = fixture.harness.balances(&request);
+
+    // The fee is not refunded: the reward wallet keeps it, and the rent stays locked in the
+    // open request and escrow accounts
+    assert_eq!(errored.reward_wallet - requested.reward_wallet, cost);
+    assert_eq!(errored.payer, requested.payer);
+    assert_eq!(errored.escrow, escrow_rent);
+    assert_eq!(fixture.result()[0], 0, "callback should not run");
+
+    fixture.harness.close(&request).unwrap();
+    let closed = fixture.harness.balances(&request);
+
+    // Closing returns both rents, so the error cost the payer exactly the fee
+    assert_eq!(closed.payer - errored.payer, request_rent + escrow_rent);
+    assert_eq!(closed.escrow, 0);
+    assert_eq!(closed.reward_wallet, errored.reward_wallet);
+    assert_eq!(before.payer - closed.payer, cost);
+    assert!(fixture.harness.account(&request).is_none());
+}
+
+#[test]
+fn test_close_rejects_pending_request() {
+    let mut fixture = Fixture::new();
+    let payer = fixture.harness.payer();
+    fixture.harness.airdrop(&payer, 1_000_000_000);
+    let request = fixture.submit(None);
+
+    assert_eq!(
+        fixture.harness.close(&request),
+        Err(ServiceError::RequestStillActive.into())
+    );
+}
+
+#[test]
+fn test_submit_request_requires_funds() {
+    let mut fixture = Fixture::new();
+    let callback = fixture.harness.request(&fixture.request).unwrap().callback;
+
+    assert_eq!(
+        fixture
+            .harness
+            .submit_request(Pubkey::new_unique(), NUM_BYTES, callback, None),
+        Err(ProgramError::InsufficientFunds)
+    );
+}
+
+#[test]
+fn test_underfunded_escrow_is_rejected() {
+    let mut fixture = Fixture::new();
+    let payer = fixture.harness.payer();
+    fixture.harness.airdrop(&payer, 1_000_000_000);
+    let request = fixture.submit(None);
+
+    // The service's cost went up after the request was funded
+    fixture.harness.set_cost_per_byte(COST_PER_BYTE);
+    let randomness = fixture.harness.randomness(&request, NUM_BYTES.into());
+    assert_eq!(
+        fixture.harness.fulfill(&request, &randomness),
+        Err(ServiceError::InsufficientFunds.into())
+    );
+    assert_eq!(fixture.count(), 0);
+}
+
+#[test]
+fn test_warped_clock_drives_retry_checks() {
+    let mut fixture = Fixture::new();
+    let request = fixture.harness.request(&fixture.request).unwrap();
+    let retry_slot = request.request_slot + CALLBACK_RETRY_BACKOFF_SLOTS;
+
+    fixture.harness.warp_to_slot(retry_slot - 1);
+    assert!(!is_retry_attempt(&request, &fixture.harness));
+    assert!(!is_retry_attempt(&request, fixture.harness.clock()));
+
+    fixture.harness.warp_to_slot(retry_slot);
+    assert!(is_retry_attempt(&request, &fixture.harness));
+    assert_eq!(fixture.harness.clock().slot, retry_slot);
+}
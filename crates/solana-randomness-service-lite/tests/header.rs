@@ -0,0 +1,62 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+fn request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        is_completed: 1,
+        num_bytes: 32,
+        user: Pubkey::new_unique(),
+        escrow: Pubkey::new_unique(),
+        request_slot: 250_000_000,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        error_message: "failed".to_string(),
+        ..Default::default()
+    }
+}
+
+fn account_data(request: &SimpleRandomnessV1Account) -> Vec<u8> {
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    request.serialize(&mut data).unwrap();
+    data.resize(request.expected_request_account_size(), 0);
+    data
+}
+
+#[test]
+fn test_reads_header_fields() {
+    let request = request();
+    let header = RequestHeader::read(&account_data(&request)).unwrap();
+
+    assert_eq!(
+        header,
+        RequestHeader {
+            is_completed: request.is_completed,
+            num_bytes: request.num_bytes,
+            user: request.user,
+            escrow: request.escrow,
+            request_slot: request.request_slot,
+        }
+    );
+    assert!(header.is_completed());
+}
+
+#[test]
+fn test_header_is_prefix_of_account() {
+    let data = account_data(&request());
+
+    // The header only needs its own bytes
+    assert!(RequestHeader::read(&data[..RequestHeader::LEN]).is_ok());
+    for len in 0..RequestHeader::LEN {
+        assert!(RequestHeader::read(&data[..len]).is_err(), "len {}", len);
+    }
+}
+
+#[test]
+fn test_rejects_other_account_types() {
+    let mut data = account_data(&request());
+    data[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+
+    assert_eq!(
+        RequestHeader::read(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
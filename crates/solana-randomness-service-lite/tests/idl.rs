@@ -0,0 +1,78 @@
+//! Checks the hardcoded wrappers against the service IDL checked in at
+//! `tests/fixtures/solana_randomness_service.json`. Set `RANDOMNESS_SERVICE_IDL` to the path of
+//! a freshly fetched IDL (`anchor idl fetch RANDMo5gFnqnXJW5Z52KNmd24sAo95KAd5VbiCtq5Rh`) to
+//! check against the deployed program instead.
+
+use solana_randomness_service_lite::idl_check::*;
+
+fn idl() -> Idl {
+    let json = match std::env::var("RANDOMNESS_SERVICE_IDL") {
+        Ok(path) => std::fs::read_to_string(path).unwrap(),
+        Err(_) => include_str!("fixtures/solana_randomness_service.json").to_string(),
+    };
+    Idl::from_json(&json).unwrap()
+}
+
+fn assert_no_mismatches(mismatches: Vec<Mismatch>) {
+    let report: Vec<String> = mismatches.iter().map(ToString::to_string).collect();
+    assert!(report.is_empty(), "{}", report.join("\n"));
+}
+
+#[test]
+fn test_instructions_match_idl() {
+    let idl = idl();
+    for layout in instruction_layouts() {
+        assert_no_mismatches(check_instruction(&idl, &layout));
+    }
+}
+
+#[test]
+fn test_types_match_idl() {
+    let idl = idl();
+    for layout in type_layouts().iter() {
+        assert_no_mismatches(check_type(&idl, layout));
+    }
+}
+
+#[test]
+fn test_errors_match_idl() {
+    assert_no_mismatches(check_errors(&idl()));
+}
+
+#[test]
+fn test_reports_drift() {
+    let mut idl = idl();
+    let ix = idl
+        .instructions
+        .iter_mut()
+        .find(|ix| ix.name == "simpleRandomnessV1")
+        .unwrap();
+    ix.accounts.swap(0, 1);
+    ix.args[0].ty = IdlType::U32;
+    idl.errors.pop();
+
+    let mismatches = check_all(&idl);
+    let report: Vec<String> = mismatches.iter().map(ToString::to_string).collect();
+    for expected in [
+        "simple_randomness_v1: account 0 is `request` in this crate, `escrow` in the IDL",
+        "simple_randomness_v1: account `request` is mut: true, signer: true in this crate, mut: true, signer: false in the IDL",
+        "simple_randomness_v1: arg `num_bytes` is `u8` in this crate, `u32` in the IDL",
+        "ProgramWalletInsufficientFunds: code 6010 is missing from the IDL",
+    ] {
+        assert!(report.iter().any(|line| line == expected), "{:#?}", report);
+    }
+}
+
+#[test]
+fn test_missing_instruction() {
+    let mut idl = idl();
+    idl.instructions.clear();
+
+    assert_eq!(
+        check_instruction(&idl, &instruction_layouts()[0]),
+        vec![Mismatch {
+            item: "simple_randomness_v1".to_string(),
+            detail: "missing from the IDL".to_string(),
+        }]
+    );
+}
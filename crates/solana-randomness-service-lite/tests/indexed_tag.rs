@@ -0,0 +1,101 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_randomness_service_lite::*;
+
+#[test]
+fn test_tag_goes_after_discriminator() {
+    let discriminator = anchor_sighash("consume_bracket_randomness");
+    let tagged = callback_with_ix_data(discriminator.to_vec())
+        .with_indexed_tag(7, 63)
+        .unwrap();
+
+    assert_eq!(tagged.discriminator(), Some(discriminator));
+    assert_eq!(tagged.args().unwrap(), (7u64, 63u16).try_to_vec().unwrap());
+    assert_eq!(tagged.indexed_tag(), Some(IndexedTag::new(7, 63)));
+}
+
+#[test]
+fn test_tag_goes_before_existing_args() {
+    let mut ix_data = anchor_sighash("consume").to_vec();
+    ix_data.extend_from_slice(&[9, 9]);
+    let tagged = callback_with_ix_data(ix_data)
+        .with_indexed_tag(1, 2)
+        .unwrap();
+
+    assert_eq!(&tagged.args().unwrap()[IndexedTag::LEN..], &[9, 9]);
+    assert_eq!(tagged.indexed_tag(), Some(IndexedTag::new(1, 2)));
+}
+
+#[test]
+fn test_reads_tag_from_invocation_data() {
+    // The service appends the result as a Borsh `Vec<u8>`
+    let tagged = callback_with_ix_data(anchor_sighash("consume").to_vec())
+        .with_indexed_tag(u64::MAX, 5)
+        .unwrap();
+    let data = [tagged.ix_data.clone(), vec![8, 0, 0, 0], vec![1; 8]].concat();
+
+    assert_eq!(
+        IndexedTag::from_ix_data(&data),
+        Some(IndexedTag::new(u64::MAX, 5))
+    );
+}
+
+#[test]
+fn test_requires_discriminator() {
+    assert_eq!(
+        callback_with_ix_data(vec![1, 2, 3])
+            .with_indexed_tag(1, 2)
+            .unwrap_err(),
+        ProgramError::from(LiteError::CallbackDiscriminatorMissing)
+    );
+    assert_eq!(
+        IndexedTag::from_ix_data(&[0; 8 + IndexedTag::LEN - 1]),
+        None
+    );
+}
+
+#[test]
+fn test_builder_adds_tag() {
+    let program_id = Pubkey::new_unique();
+    let discriminator = anchor_sighash("consume_bracket_randomness");
+
+    // The tag can be set before the instruction data
+    let (built, len) = Callback::builder(program_id)
+        .with_state_signer()
+        .with_indexed_tag(3, 4)
+        .with_ix_data(discriminator.to_vec())
+        .build()
+        .unwrap();
+    let expected = callback_with_ix_data(discriminator.to_vec())
+        .with_indexed_tag(3, 4)
+        .unwrap();
+
+    assert_eq!(built.ix_data, expected.ix_data);
+    assert_eq!(len, built.serialized_len());
+}
+
+#[test]
+fn test_verify_tag_matches() {
+    let expected = IndexedTag::new(42, 3);
+    let mismatch = Err(ProgramError::from(LiteError::TagMismatch));
+
+    assert_eq!(verify_tag_matches(IndexedTag::new(42, 3), expected), Ok(()));
+    assert_eq!(
+        verify_tag_matches(IndexedTag::new(43, 3), expected),
+        mismatch.clone()
+    );
+    assert_eq!(
+        verify_tag_matches(IndexedTag::new(42, 4), expected),
+        mismatch
+    );
+    // Read back from a tagged callback, as a native callback would
+    let tagged = callback_with_ix_data(anchor_sighash("consume").to_vec())
+        .with_indexed_tag(42, 3)
+        .unwrap();
+    assert_eq!(
+        verify_tag_matches(tagged.indexed_tag().unwrap(), expected),
+        Ok(())
+    );
+}
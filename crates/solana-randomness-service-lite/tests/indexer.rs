@@ -0,0 +1,139 @@
+//! The indexer reports each status change of a consumer's requests once, across polls of the
+//! mock RPC.
+
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use std::sync::{Arc, Mutex};
+
+const CONSUMER: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// Records every transition it is shown.
+#[derive(Default)]
+struct Recorder(Mutex<Vec<StatusTransition>>);
+
+impl TransitionHandler for Recorder {
+    fn on_transition(&self, transition: &StatusTransition) {
+        self.0.lock().unwrap().push(transition.clone());
+    }
+}
+
+fn add_request(rpc: &MockRpc, consumer: Pubkey) -> Pubkey {
+    let request = Pubkey::new_unique();
+    let account = SimpleRandomnessV1Account {
+        num_bytes: 8,
+        user: Pubkey::new_unique(),
+        callback: Callback::new(consumer, vec![], vec![]),
+        ..Default::default()
+    };
+    rpc.set_account(request, request_account(&account));
+    request
+}
+
+fn indexer(store: Arc<MemoryRequestStore>, recorder: Arc<Recorder>) -> Indexer {
+    Indexer::new(CONSUMER, store).with_handler(recorder)
+}
+
+#[tokio::test]
+async fn test_reports_settlement_once() {
+    let rpc = MockRpc::default();
+    let store = Arc::new(MemoryRequestStore::new());
+    let recorder = Arc::new(Recorder::default());
+    let indexer = indexer(store.clone(), recorder.clone());
+
+    let request = add_request(&rpc, CONSUMER);
+    // Another consumer's request is not indexed
+    add_request(&rpc, Pubkey::new_unique());
+
+    rpc.warp_to_slot(100);
+    let first = indexer.poll(&rpc).await.unwrap();
+    assert_eq!(
+        first,
+        vec![StatusTransition {
+            request,
+            from: None,
+            to: IndexedStatus::Pending,
+            slot: 100,
+            callback_args: None,
+        }]
+    );
+
+    // The oracle settles the request and the service closes it
+    rpc.remove_account(&request);
+    rpc.warp_to_slot(110);
+    let second = indexer.poll(&rpc).await.unwrap();
+    let settled = StatusTransition {
+        request,
+        from: Some(IndexedStatus::Pending),
+        to: IndexedStatus::Closed,
+        slot: 110,
+        callback_args: None,
+    };
+    assert_eq!(second, vec![settled.clone()]);
+
+    // Nothing changes afterwards
+    assert!(indexer.poll(&rpc).await.unwrap().is_empty());
+    let seen = recorder.0.lock().unwrap().clone();
+    assert_eq!(seen.iter().filter(|t| **t == settled).count(), 1);
+    assert_eq!(seen.len(), 2);
+
+    let stored = store.get(&request).unwrap().unwrap();
+    assert_eq!(stored.status, IndexedStatus::Closed);
+    assert_eq!(stored.slot, 110);
+    assert_eq!(stored.request.callback.program_id, CONSUMER);
+    assert_eq!(store.list().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_reports_failure_then_close() {
+    let rpc = MockRpc::default();
+    let store = Arc::new(MemoryRequestStore::new());
+    let recorder = Arc::new(Recorder::default());
+    let indexer = indexer(store, recorder.clone());
+    let request = add_request(&rpc, CONSUMER);
+    indexer.poll(&rpc).await.unwrap();
+
+    let failed = SimpleRandomnessV1Account {
+        num_bytes: 8,
+        callback: Callback::new(CONSUMER, vec![], vec![]),
+        error_message: "callback failed".to_string(),
+        ..Default::default()
+    };
+    rpc.set_account(request, request_account(&failed));
+    let transitions = indexer.poll(&rpc).await.unwrap();
+    assert_eq!(transitions.len(), 1);
+    assert_eq!(transitions[0].from, Some(IndexedStatus::Pending));
+    assert_eq!(transitions[0].to, IndexedStatus::Failed);
+
+    rpc.remove_account(&request);
+    let transitions = indexer.poll(&rpc).await.unwrap();
+    assert_eq!(transitions[0].from, Some(IndexedStatus::Failed));
+    assert_eq!(transitions[0].to, IndexedStatus::Closed);
+    assert_eq!(recorder.0.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_tracks_requests_past_the_signature_scan() {
+    // Requests older than the scanned transactions are not discovered, but the stored ones are
+    // still fetched
+    let rpc = MockRpc::default();
+    rpc.set_program_accounts_disabled(true);
+    let store = Arc::new(MemoryRequestStore::new());
+    let request = add_request(&rpc, CONSUMER);
+    store
+        .upsert(IndexedRequest {
+            pubkey: request,
+            status: IndexedStatus::Pending,
+            request: SimpleRandomnessV1Account::default(),
+            slot: 0,
+        })
+        .unwrap();
+    let indexer = Indexer::new(CONSUMER, store.clone());
+
+    assert!(indexer.poll(&rpc).await.unwrap().is_empty());
+
+    rpc.remove_account(&request);
+    let transitions = indexer.poll(&rpc).await.unwrap();
+    assert_eq!(transitions.len(), 1);
+    assert_eq!(transitions[0].to, IndexedStatus::Closed);
+}
@@ -0,0 +1,164 @@
+//! A `RequestIntent` survives storage as JSON or Borsh and builds the request it describes.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+
+fn callback() -> Callback {
+    Callback::new(
+        Pubkey::new_from_array([7; 32]),
+        vec![
+            AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+            AccountMeta::new(Pubkey::new_from_array([8; 32]), false).into(),
+        ],
+        anchor_sighash("consume_randomness").to_vec(),
+    )
+}
+
+fn intent() -> RequestIntent {
+    RequestIntent::new(16, callback())
+        .options(Some(TransactionOptions {
+            compute_units: Some(250_000),
+            compute_unit_price: Some(100),
+        }))
+        .tag("raffle-7")
+        .max_cost(50_000_000)
+}
+
+fn state(cost_per_byte: u64) -> ServiceState {
+    ServiceState {
+        cost_per_byte,
+        ..Default::default()
+    }
+}
+
+fn assert_same(a: &RequestIntent, b: &RequestIntent) {
+    assert_eq!(a.num_bytes, b.num_bytes);
+    assert_eq!(a.callback.to_vec().unwrap(), b.callback.to_vec().unwrap());
+    assert_eq!(
+        TransactionOptions::to_opt_vec(&a.options).unwrap(),
+        TransactionOptions::to_opt_vec(&b.options).unwrap()
+    );
+    assert_eq!(a.tag, b.tag);
+    assert_eq!(a.max_cost, b.max_cost);
+}
+
+#[test]
+fn test_json_round_trip() {
+    let json = serde_json::to_value(intent()).unwrap();
+    assert_eq!(
+        json["callback"]["program_id"],
+        Pubkey::new_from_array([7; 32]).to_string()
+    );
+    assert_eq!(json["callback"]["accounts"][1]["is_writable"], true);
+    assert_eq!(json["tag"], "raffle-7");
+
+    let decoded: RequestIntent = serde_json::from_value(json).unwrap();
+    assert_same(&decoded, &intent());
+}
+
+#[test]
+fn test_json_defaults_optional_fields() {
+    let json = serde_json::json!({
+        "num_bytes": 8,
+        "callback": {
+            "program_id": Pubkey::new_from_array([7; 32]).to_string(),
+            "accounts": [],
+            "ix_data": "AQIDBAUGBwg=",
+        },
+    });
+    let decoded: RequestIntent = serde_json::from_value(json).unwrap();
+    assert!(decoded.options.is_none());
+    assert!(decoded.tag.is_none());
+    assert!(decoded.max_cost.is_none());
+    assert_eq!(decoded.callback.ix_data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_json_rejects_invalid_address() {
+    let mut json = serde_json::to_value(intent()).unwrap();
+    json["callback"]["program_id"] = "not-an-address".into();
+    assert!(serde_json::from_value::<RequestIntent>(json).is_err());
+}
+
+#[test]
+fn test_borsh_round_trip() {
+    let bytes = intent().try_to_vec().unwrap();
+    let decoded = RequestIntent::try_from_slice(&bytes).unwrap();
+    assert_same(&decoded, &intent());
+}
+
+#[test]
+fn test_validate() {
+    intent().validate().unwrap();
+
+    let mut too_many_bytes = intent();
+    too_many_bytes.num_bytes = MAX_RANDOMNESS_BYTES + 1;
+    assert_eq!(
+        too_many_bytes.validate().unwrap_err().kind(),
+        ErrorKind::Validation
+    );
+
+    let bad_options = intent().options(Some(TransactionOptions {
+        compute_units: Some(1),
+        compute_unit_price: None,
+    }));
+    assert!(bad_options.validate().is_err());
+
+    assert!(matches!(
+        intent().tag("bad;label").validate(),
+        Err(LiteClientError::InvalidTag(_))
+    ));
+}
+
+#[test]
+fn test_cost_ceiling() {
+    let intent = intent();
+    let cost = intent.estimated_cost(&state(10_000)).unwrap();
+    assert_eq!(cost.randomness_fee, 160_000);
+    assert_eq!(intent.check_cost(&state(10_000)).unwrap(), cost);
+
+    let ceiling = intent.clone().max_cost(cost.total() - 1);
+    match ceiling.check_cost(&state(10_000)) {
+        Err(LiteClientError::CostCeilingExceeded {
+            max_cost,
+            cost: got,
+        }) => {
+            assert_eq!(max_cost, cost.total() - 1);
+            assert_eq!(got, cost.total());
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn test_intent_builds_request() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let json = serde_json::to_string(&intent()).unwrap();
+
+    // Another service materializes the stored intent
+    let intent: RequestIntent = serde_json::from_str(&json).unwrap();
+    let instructions = intent
+        .into_builder(payer)
+        .unwrap()
+        .instructions(&request)
+        .unwrap();
+
+    let decoded = decode_any_request_instruction(&instructions[0].data, None).unwrap();
+    assert_eq!(decoded.num_bytes, 16);
+    assert_eq!(
+        decoded.callback.to_vec().unwrap(),
+        callback().to_vec().unwrap()
+    );
+    let options = decoded.options.unwrap();
+    assert_eq!(options.compute_units, Some(250_000));
+    assert_eq!(options.compute_unit_price, Some(100));
+
+    let tag = instructions
+        .iter()
+        .find(|ix| ix.program_id == MEMO_PROGRAM_ID)
+        .unwrap();
+    let tag = RequestTag::decode(std::str::from_utf8(&tag.data).unwrap()).unwrap();
+    assert_eq!(tag.label, "raffle-7");
+}
@@ -0,0 +1,250 @@
+//! Invariants of the funding, sizing, and fee math over generated inputs. A failing case is
+//! shrunk and printed with the full input structs.
+
+use borsh::BorshSerialize;
+use proptest::prelude::*;
+use solana_program::rent::Rent;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn account_meta() -> impl Strategy<Value = AccountMetaBorsh> {
+    (pubkey(), any::<bool>(), any::<bool>()).prop_map(|(pubkey, is_signer, is_writable)| {
+        AccountMetaBorsh {
+            pubkey,
+            is_signer,
+            is_writable,
+        }
+    })
+}
+
+/// A callback within [`Callback::MAX_ACCOUNTS`] and [`Callback::MAX_IX_DATA_LEN`].
+fn callback() -> impl Strategy<Value = Callback> {
+    (
+        pubkey(),
+        prop::collection::vec(account_meta(), 0..=Callback::MAX_ACCOUNTS),
+        prop::collection::vec(any::<u8>(), 0..=Callback::MAX_IX_DATA_LEN),
+    )
+        .prop_map(|(program_id, accounts, ix_data)| Callback::new(program_id, accounts, ix_data))
+}
+
+/// A callback of any shape, including ones over the limits.
+fn oversized_callback() -> impl Strategy<Value = Callback> {
+    (
+        prop::collection::vec(account_meta(), 0..=4 * Callback::MAX_ACCOUNTS),
+        0..=4 * Callback::MAX_IX_DATA_LEN,
+    )
+        .prop_map(|(accounts, ix_data_len)| {
+            Callback::new(Pubkey::default(), accounts, vec![0; ix_data_len])
+        })
+}
+
+fn options() -> impl Strategy<Value = Option<TransactionOptions>> {
+    prop::option::of(
+        (
+            prop::option::of(any::<u32>()),
+            prop::option::of(any::<u64>()),
+        )
+            .prop_map(|(compute_units, compute_unit_price)| TransactionOptions {
+                compute_units,
+                compute_unit_price,
+            }),
+    )
+}
+
+/// A request account the service could have written: a valid callback and an error message
+/// the service accepts.
+fn request_account() -> impl Strategy<Value = SimpleRandomnessV1Account> {
+    (
+        (0..=1u8, 1..=MAX_NUM_BYTES, pubkey(), pubkey(), any::<u64>()),
+        callback(),
+        (
+            any::<u32>(),
+            any::<u64>(),
+            prop::collection::vec(
+                any::<u8>(),
+                0..=SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN,
+            ),
+        ),
+    )
+        .prop_map(
+            |(
+                (is_completed, num_bytes, user, escrow, request_slot),
+                callback,
+                (compute_units, priority_fee_micro_lamports, error_message),
+            )| SimpleRandomnessV1Account {
+                is_completed,
+                num_bytes,
+                user,
+                escrow,
+                request_slot,
+                callback,
+                compute_units,
+                priority_fee_micro_lamports,
+                // ASCII, so the length in bytes is the length generated
+                error_message: error_message.iter().map(|b| char::from(b % 128)).collect(),
+            },
+        )
+}
+
+/// The Borsh length of `options` as the last argument of the request instruction.
+fn options_len(options: &Option<TransactionOptions>) -> usize {
+    match options {
+        None => 1,
+        Some(options) => {
+            1 + 1
+                + options.compute_units.map_or(0, |_| 4)
+                + 1
+                + options.compute_unit_price.map_or(0, |_| 8)
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn funding_total_is_sum_of_components(
+        num_bytes in 1..=MAX_NUM_BYTES,
+        callback in callback(),
+        options in options(),
+        // Up to one SOL per byte
+        cost_per_byte in 0..=1_000_000_000u64,
+    ) {
+        let rent = Rent::default();
+        let funding =
+            FundingBreakdown::new(num_bytes, &callback, &options, cost_per_byte, &rent).unwrap();
+
+        prop_assert_eq!(
+            funding.service_cost(),
+            funding.base_fee + funding.randomness_fee + funding.priority_fee
+        );
+        prop_assert_eq!(
+            funding.total(),
+            funding.service_cost() + funding.request_rent + funding.escrow_rent
+        );
+        prop_assert!(funding.total() >= funding.service_cost());
+        prop_assert_eq!(funding.randomness_fee, cost_per_byte * u64::from(num_bytes));
+        prop_assert_eq!(
+            funding.request_rent,
+            rent.minimum_balance(SimpleRandomnessV1Account::space(&callback))
+        );
+    }
+
+    #[test]
+    fn required_lamports_match_funding_total(
+        num_bytes in 1..=MAX_NUM_BYTES,
+        callback in callback(),
+        options in options(),
+        cost_per_byte in 0..=1_000_000_000u64,
+    ) {
+        let rent = Rent::default();
+        let funding =
+            FundingBreakdown::new(num_bytes, &callback, &options, cost_per_byte, &rent).unwrap();
+
+        prop_assert_eq!(
+            required_request_lamports(num_bytes, &callback, options.as_ref(), cost_per_byte, &rent),
+            Ok(funding.total())
+        );
+    }
+
+    #[test]
+    fn space_holds_serialized_request(account in request_account()) {
+        let serialized = SimpleRandomnessV1Account::DISCRIMINATOR.len()
+            + account.try_to_vec().unwrap().len();
+        let space = SimpleRandomnessV1Account::space(&account.callback);
+
+        // The space is an upper bound, not the serialized length. Its slack depends only on
+        // the error message: the callback adds as many bytes to both.
+        let empty = SimpleRandomnessV1Account::default();
+        let slack = SimpleRandomnessV1Account::space(&empty.callback)
+            - SimpleRandomnessV1Account::DISCRIMINATOR.len()
+            - empty.try_to_vec().unwrap().len();
+        prop_assert!(serialized <= space);
+        prop_assert_eq!(space - serialized, slack - account.error_message.len());
+        prop_assert_eq!(space, account.expected_request_account_size());
+    }
+
+    #[test]
+    fn request_data_len_matches_layout(
+        num_bytes in 1..=MAX_NUM_BYTES,
+        callback in callback(),
+        options in options(),
+    ) {
+        let data =
+            SimpleRandomnessV1Request::data(num_bytes, &callback, options.as_ref()).unwrap();
+        let callback_len = 32 + 4 + 34 * callback.accounts.len() + 4 + callback.ix_data.len();
+
+        prop_assert_eq!(data.len(), 8 + 1 + callback_len + options_len(&options));
+        prop_assert!(callback_len <= Callback::MAX_LEN);
+        prop_assert_eq!(&data[..8], &SimpleRandomnessV1Request::DISCRIMINATOR[..]);
+    }
+
+    #[test]
+    fn builder_instruction_matches_request_data(
+        num_bytes in 1..=MAX_NUM_BYTES,
+        callback in callback(),
+        options in options(),
+    ) {
+        let builder = RequestBuilder::new(Pubkey::new_unique(), num_bytes, callback.clone())
+            .options(options.clone());
+        let expected_options =
+            options.unwrap_or_else(|| TransactionOptions::advised_for_callback(&callback));
+
+        let instruction = builder.instruction(&Pubkey::new_unique()).unwrap();
+
+        prop_assert_eq!(
+            instruction.data,
+            SimpleRandomnessV1Request::data(num_bytes, &callback, Some(&expected_options))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn advised_compute_units_within_bounds(callback in oversized_callback()) {
+        let compute_units = TransactionOptions::advised_for_callback(&callback)
+            .compute_units
+            .unwrap();
+
+        prop_assert!(compute_units >= TransactionOptions::MINIMUM_COMPUTE_UNITS);
+        prop_assert!(compute_units <= TransactionOptions::MAXIMUM_COMPUTE_UNITS);
+    }
+
+    #[test]
+    fn clamped_options_within_bounds(options in options()) {
+        let options = options.unwrap_or_default();
+        let compute_units = options.get_compute_units();
+        let price = options.get_compute_unit_price();
+
+        prop_assert!(compute_units >= TransactionOptions::MINIMUM_COMPUTE_UNITS);
+        prop_assert!(compute_units <= TransactionOptions::MAXIMUM_COMPUTE_UNITS);
+        prop_assert!(price >= TransactionOptions::MINIMUM_COMPUTE_UNIT_PRICE);
+        prop_assert!(price <= TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE);
+        let fee = u64::from(compute_units) * price;
+        prop_assert_eq!(options.get_priority_fee_micro_lamports(), Some(fee));
+        // Rounded up, never short of the exact fee and under a lamport over it
+        let lamports = options.get_priority_fee_lamports().unwrap();
+        prop_assert!(lamports * 1_000_000 >= fee);
+        prop_assert!(lamports * 1_000_000 < fee + 1_000_000);
+        // The service rounds it down
+        prop_assert_eq!(
+            estimate_request_cost(1, 0, &Some(options)),
+            Ok(REQUEST_BASE_FEE + fee / 1_000_000)
+        );
+    }
+
+    #[test]
+    fn split_sums_back_to_total(total_bytes in 0..=10_000usize) {
+        let parts = split_into_requests(total_bytes);
+
+        prop_assert_eq!(
+            parts.iter().map(|part| usize::from(part.get())).sum::<usize>(),
+            total_bytes
+        );
+        prop_assert_eq!(parts.len(), total_bytes.div_ceil(usize::from(MAX_NUM_BYTES)));
+        if let Some((_, full)) = parts.split_last() {
+            prop_assert!(full.iter().all(|part| *part == NumBytes::MAX));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Callback instruction data is read from accounts anyone can create, so every helper that
+//! reads it must handle data too short for a discriminator without panicking.
+
+mod common;
+
+use common::*;
+use solana_randomness_service_lite::*;
+
+fn callback(ix_data_len: usize) -> Callback {
+    callback_with_ix_data((0..ix_data_len as u8).collect())
+}
+
+#[test]
+fn test_short_ix_data_never_panics() {
+    let new = anchor_sighash("consume_randomness");
+
+    for len in 0..=16 {
+        let mut callback = callback(len);
+        let ix_data = callback.ix_data.clone();
+
+        if len < 8 {
+            assert_eq!(callback.discriminator(), None, "len {}", len);
+            assert_eq!(callback.args(), None, "len {}", len);
+            assert_eq!(
+                callback.rewrite_discriminator(new),
+                Err(LiteError::CallbackDiscriminatorMissing.into()),
+                "len {}",
+                len
+            );
+            assert_eq!(callback.ix_data, ix_data, "len {}", len);
+        } else {
+            assert_eq!(
+                callback.discriminator().unwrap(),
+                ix_data[..8],
+                "len {}",
+                len
+            );
+            assert_eq!(callback.args().unwrap(), &ix_data[8..], "len {}", len);
+            callback.rewrite_discriminator(new).unwrap();
+            assert_eq!(callback.discriminator(), Some(new), "len {}", len);
+            assert_eq!(callback.args().unwrap(), &ix_data[8..], "len {}", len);
+        }
+
+        assert_eq!(callback.validate(), Ok(()), "len {}", len);
+        SimpleRandomnessV1Request::data(8, &callback, None).unwrap();
+    }
+}
+
+#[test]
+fn test_discriminator_only_has_empty_args() {
+    let callback = Callback::new(
+        Pubkey::new_unique(),
+        vec![],
+        anchor_sighash("consume_randomness").to_vec(),
+    );
+
+    assert_eq!(
+        callback.discriminator(),
+        Some(anchor_sighash("consume_randomness"))
+    );
+    assert_eq!(callback.args(), Some([].as_slice()));
+}
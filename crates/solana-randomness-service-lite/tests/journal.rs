@@ -0,0 +1,230 @@
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::path::PathBuf;
+
+fn builder(payer: &Keypair) -> RequestBuilder {
+    RequestBuilder::new(
+        payer.pubkey(),
+        8,
+        Callback::new(
+            Pubkey::new_unique(),
+            vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+            vec![1, 2, 3, 4, 5, 6, 7, 8],
+        ),
+    )
+}
+
+fn journal_path() -> PathBuf {
+    std::env::temp_dir().join(format!("randomness-journal-{}.jsonl", Pubkey::new_unique()))
+}
+
+/// Lands every transaction on the inner RPC but loses the confirmation, as if the client
+/// crashed while waiting for it.
+struct CrashBeforeConfirmation<'a>(&'a MockRpc);
+
+#[async_trait::async_trait]
+impl RandomnessRpc for CrashBeforeConfirmation<'_> {
+    async fn get_latest_blockhash(&self) -> Result<Hash, LiteClientError> {
+        self.0.get_latest_blockhash().await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, LiteClientError> {
+        self.0.get_account(pubkey).await
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, LiteClientError> {
+        self.0.send_and_confirm_transaction(transaction).await?;
+        Err(LiteClientError::Rpc("process killed".to_string()))
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureRecord>, LiteClientError> {
+        self.0
+            .get_signatures_for_address(address, before, limit)
+            .await
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionRecord>, LiteClientError> {
+        self.0.get_transaction(signature).await
+    }
+}
+
+/// Sends the job's request and loses the confirmation, returning the request account that
+/// landed.
+async fn crash_mid_send(rpc: &MockRpc, payer: &Keypair, path: &PathBuf) -> Pubkey {
+    let journal = FileJournal::open(path).unwrap();
+    let result = builder(payer)
+        .send_idempotent(&CrashBeforeConfirmation(rpc), payer, "job-1", &journal)
+        .await;
+    assert!(matches!(result, Err(LiteClientError::Rpc(_))));
+
+    let entry = journal.lookup("job-1").unwrap().unwrap();
+    assert!(!entry.completed);
+    entry.request
+}
+
+#[tokio::test]
+async fn test_restart_finds_open_request() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let path = journal_path();
+
+    let request = crash_mid_send(&rpc, &payer, &path).await;
+    // The service created the request account
+    rpc.set_account(
+        request,
+        request_account(&SimpleRandomnessV1Account::default()),
+    );
+
+    let journal = FileJournal::open(&path).unwrap();
+    let sent = builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-1", &journal)
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(sent, IdempotentSend::Existing(r) if r == request));
+    assert_eq!(rpc.sent_transactions().len(), 1);
+    assert!(journal.lookup("job-1").unwrap().unwrap().completed);
+}
+
+#[tokio::test]
+async fn test_restart_finds_settled_request() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let path = journal_path();
+
+    let request = crash_mid_send(&rpc, &payer, &path).await;
+    // The request was settled and its account closed before the restart
+    rpc.add_transaction(TransactionRecord {
+        signature: rpc.sent_transactions()[0].signatures[0],
+        slot: 10,
+        failed: false,
+        account_keys: vec![payer.pubkey(), request, ID],
+        log_messages: vec![],
+        token_balances: vec![],
+    });
+
+    let journal = FileJournal::open(&path).unwrap();
+    let sent = builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-1", &journal)
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(sent.request(), request);
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+#[tokio::test]
+async fn test_retries_request_that_never_landed() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let journal = MemoryJournal::new();
+
+    rpc.set_fail_sends(true);
+    assert!(builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-1", &journal)
+        .await
+        .is_err());
+    let abandoned = journal.lookup("job-1").unwrap().unwrap().request;
+
+    rpc.set_fail_sends(false);
+    let sent = builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-1", &journal)
+        .await
+        .unwrap();
+
+    let IdempotentSend::Sent(sent) = sent else {
+        panic!("expected a new request, got {:?}", sent);
+    };
+    assert_ne!(sent.request, abandoned);
+    assert_eq!(rpc.sent_transactions().len(), 1);
+    assert_eq!(
+        journal.lookup("job-1").unwrap(),
+        Some(JournalEntry {
+            request: sent.request,
+            completed: true,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_completed_job_skips_rpc() {
+    let payer = Keypair::new();
+    let rpc = MockRpc::default();
+    let journal = MemoryJournal::new();
+
+    let first = builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-1", &journal)
+        .await
+        .unwrap();
+    let calls = rpc.calls();
+    let second = builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-1", &journal)
+        .await
+        .unwrap();
+    let other = builder(&payer)
+        .send_idempotent(&rpc, &payer, "job-2", &journal)
+        .await
+        .unwrap();
+
+    assert!(matches!(first, IdempotentSend::Sent(_)));
+    assert!(matches!(second, IdempotentSend::Existing(r) if r == first.request()));
+    assert_eq!(rpc.calls(), calls + 2);
+    assert_ne!(other.request(), first.request());
+    assert_eq!(rpc.sent_transactions().len(), 2);
+}
+
+#[test]
+fn test_file_journal_ignores_truncated_line() {
+    let path = journal_path();
+    let request = Pubkey::new_unique();
+    {
+        let journal = FileJournal::open(&path).unwrap();
+        journal.record_intent("job-1", &request).unwrap();
+        journal.mark_complete("job-1").unwrap();
+    }
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, b"{\"stage\":\"int"))
+        .unwrap();
+
+    let journal = FileJournal::open(&path).unwrap();
+    let entry = journal.lookup("job-1").unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        entry,
+        Some(JournalEntry {
+            request,
+            completed: true,
+        })
+    );
+    assert!(journal.lookup("job-2").unwrap().is_none());
+}
+
+#[test]
+fn test_mark_complete_requires_intent() {
+    let journal = MemoryJournal::new();
+
+    let err = journal.mark_complete("job-1").unwrap_err();
+
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
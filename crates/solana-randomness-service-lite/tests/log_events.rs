@@ -0,0 +1,185 @@
+//! `parse_events_from_logs` reads the service's events from a transaction's log messages. The
+//! fixture holds the `logMessages` of a settle transaction whose callback logs an event of its
+//! own, one line per message.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use std::str::FromStr;
+
+const SETTLE_LOGS: &str = include_str!("fixtures/settle_logs.txt");
+
+const CONSUMER: &str = "39hMZgeiesFXMRFt8svuKVsdCW5geiYueSRx7dxhXN4f";
+
+fn fixture() -> Vec<String> {
+    SETTLE_LOGS.lines().map(str::to_string).collect()
+}
+
+fn data_line(discriminator: [u8; 8], event: &impl BorshSerialize) -> String {
+    let mut data = discriminator.to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+fn requested(request: Pubkey) -> SimpleRandomnessV1RequestedEvent {
+    SimpleRandomnessV1RequestedEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 5,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        compute_units: 200_000,
+        priority_fee_micro_lamports: 10,
+        num_bytes: 32,
+    }
+}
+
+fn settled(request: Pubkey) -> SimpleRandomnessV1SettledEvent {
+    SimpleRandomnessV1SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 5,
+        settled_slot: 7,
+        is_success: true,
+        randomness: vec![3; 32],
+    }
+}
+
+fn invoke(program: &Pubkey, depth: u8) -> String {
+    format!("Program {} invoke [{}]", program, depth)
+}
+
+fn success(program: &Pubkey) -> String {
+    format!("Program {} success", program)
+}
+
+#[test]
+fn test_parses_settle_transaction_fixture() {
+    let events = parse_events_from_logs(&fixture());
+
+    // The callback's ConsumerFulfilled event is not a service event
+    assert_eq!(events.len(), 1);
+    let RandomnessServiceEvent::Settled(event) = &events[0] else {
+        panic!("unexpected event {:?}", events[0]);
+    };
+    assert_eq!(
+        event.request,
+        Pubkey::from_str("37N78UvhAaDH7vdRP7gZRrF3NA5ts3v8XDXNp4cGxPVp").unwrap()
+    );
+    assert_eq!(
+        event.user,
+        Pubkey::from_str("LQVcTQajEfHFgC7dJeWJ6R3uBsqZrSdp9rTzv344p4A").unwrap()
+    );
+    assert_eq!(event.callback_pid, Pubkey::from_str(CONSUMER).unwrap());
+    assert_eq!(event.request_slot, 301_845_990);
+    assert_eq!(event.settled_slot, 301_846_002);
+    assert!(event.is_success);
+    assert_eq!(event.randomness.len(), 32);
+    assert_eq!(event.randomness[..4], [28, 38, 197, 102]);
+}
+
+#[test]
+fn test_fixture_for_another_deployment_has_no_events() {
+    let events = parse_program_events_from_logs(&Pubkey::new_unique(), &fixture());
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_parses_multiple_events_in_order() {
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let consumer = Pubkey::new_unique();
+    let logs = vec![
+        invoke(&consumer, 1),
+        invoke(&ID, 2),
+        data_line(
+            SimpleRandomnessV1RequestedEvent::DISCRIMINATOR,
+            &requested(first),
+        ),
+        success(&ID),
+        invoke(&ID, 2),
+        data_line(
+            SimpleRandomnessV1RequestedEvent::DISCRIMINATOR,
+            &requested(second),
+        ),
+        success(&ID),
+        success(&consumer),
+    ];
+
+    let events = parse_events_from_logs(&logs);
+
+    let requests: Vec<_> = events.iter().map(|event| *event.request()).collect();
+    assert_eq!(requests, vec![first, second]);
+    assert!(matches!(events[0], RandomnessServiceEvent::Requested(_)));
+}
+
+#[test]
+fn test_skips_service_events_logged_by_other_programs() {
+    let request = Pubkey::new_unique();
+    let consumer = Pubkey::new_unique();
+    let logs = vec![
+        invoke(&ID, 1),
+        invoke(&consumer, 2),
+        // A callback spoofing the settled event
+        data_line(
+            SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+            &settled(Pubkey::new_unique()),
+        ),
+        format!("Program {} failed: custom program error: 0x1", consumer),
+        data_line(
+            SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+            &settled(request),
+        ),
+        success(&ID),
+    ];
+
+    let events = parse_events_from_logs(&logs);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].request(), &request);
+}
+
+#[test]
+fn test_skips_truncated_lines() {
+    let request = Pubkey::new_unique();
+    let truncated = data_line(
+        SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+        &settled(Pubkey::new_unique()),
+    );
+    let logs = vec![
+        invoke(&ID, 1),
+        data_line(
+            SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+            &settled(request),
+        ),
+        truncated[..truncated.len() - 12].to_string(),
+        "Log truncated".to_string(),
+    ];
+
+    let events = parse_events_from_logs(&logs);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].request(), &request);
+}
+
+#[test]
+fn test_attributes_logs_without_invoke_lines_to_the_service() {
+    let request = Pubkey::new_unique();
+    let logs = vec![
+        "Program log: unrelated".to_string(),
+        data_line(
+            SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+            &settled(request),
+        ),
+        data_line(ConsumerRequested::DISCRIMINATOR, &request),
+    ];
+
+    let events = parse_events_from_logs(&logs);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].request(), &request);
+}
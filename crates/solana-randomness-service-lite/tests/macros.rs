@@ -0,0 +1,156 @@
+use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use solana_randomness_service_lite::*;
+use std::cell::RefCell;
+use std::sync::Once;
+
+thread_local! {
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+struct CaptureLogs;
+
+impl SyscallStubs for CaptureLogs {
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+    }
+}
+
+/// Runs `f` and returns its result with the messages it logged.
+fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(CaptureLogs));
+    });
+
+    LOGS.with(|logs| logs.borrow_mut().clear());
+    let result = f();
+    (result, LOGS.with(|logs| logs.take()))
+}
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([9; 32]);
+const ROUND_SEED: &[u8] = b"round";
+
+define_pda!(ReceiptPda, [b"receipt", request: Pubkey]);
+define_pda!(RoundPda, [ROUND_SEED, authority: Pubkey, b"number", number: [u8; 8]]);
+
+fn check_signer(state: &Pubkey, is_signer: bool) -> Result<(), ProgramError> {
+    require_randomness!(
+        *state == RANDOMNESS_SERVICE_STATE && is_signer,
+        LiteError::CallbackStateNotSigner
+    );
+    Ok(())
+}
+
+fn check_num_bytes(num_bytes: u8) -> Result<u8, ProgramError> {
+    if num_bytes == 0 || num_bytes > 32 {
+        randomness_bail!(ServiceError::InvalidNumberOfBytes);
+    }
+    Ok(num_bytes)
+}
+
+#[test]
+fn test_require_passes_without_logging() {
+    let (result, logs) = capture(|| check_signer(&RANDOMNESS_SERVICE_STATE, true));
+
+    assert_eq!(result, Ok(()));
+    assert!(logs.is_empty());
+}
+
+#[test]
+fn test_require_logs_lite_error() {
+    let (result, logs) = capture(|| check_signer(&RANDOMNESS_SERVICE_STATE, false));
+
+    assert_eq!(result, Err(ProgramError::Custom(7003)));
+    assert_eq!(
+        logs,
+        ["Error 7003: The randomness service state must be a signer of the callback"]
+    );
+}
+
+#[test]
+fn test_bail_logs_service_error() {
+    let (result, logs) = capture(|| check_num_bytes(33));
+
+    assert_eq!(result, Err(ProgramError::Custom(6000)));
+    assert_eq!(
+        logs,
+        ["Error 6000: num_bytes must be greater than 0 and less than or equal to 32"]
+    );
+}
+
+#[test]
+fn test_bail_logs_builtin_error() {
+    let (error, logs) = capture(|| log_and_return(ProgramError::InvalidAccountData));
+
+    assert_eq!(error, ProgramError::InvalidAccountData);
+    assert_eq!(logs, ["Error: An account's data contents was invalid"]);
+
+    let (error, logs) = capture(|| log_and_return(ProgramError::Custom(42)));
+    assert_eq!(error, ProgramError::Custom(42));
+    assert_eq!(logs, ["Error: Custom program error: 0x2a"]);
+}
+
+#[test]
+fn test_describes_error_codes() {
+    assert_eq!(
+        describe_error_code(LiteError::CallbackSignerOnCurve.code()),
+        Some("Only a PDA can be allowed as a callback signer")
+    );
+    assert_eq!(
+        describe_error_code(ServiceError::ProgramWalletInsufficientFunds.code()),
+        Some("The program token wallet had insufficient funds for the request")
+    );
+    assert_eq!(
+        ServiceError::from_code(6007),
+        Some(ServiceError::RequestAlreadyCompleted)
+    );
+    assert_eq!(describe_error_code(6011), None);
+    assert_eq!(describe_error_code(7021), None);
+    assert_eq!(describe_error_code(0), None);
+}
+
+#[test]
+fn test_pda_matches_hand_written_seeds() {
+    let request = Pubkey::new_unique();
+
+    assert_eq!(
+        ReceiptPda::derive(&PROGRAM_ID, request),
+        Pubkey::find_program_address(&[b"receipt", request.as_ref()], &PROGRAM_ID)
+    );
+
+    let authority = Pubkey::new_unique();
+    let number = 7u64.to_le_bytes();
+    let (address, pda) = RoundPda::find(&PROGRAM_ID, authority, number);
+    assert_eq!(
+        (address, pda.bump),
+        Pubkey::find_program_address(
+            &[ROUND_SEED, authority.as_ref(), b"number", &number],
+            &PROGRAM_ID
+        )
+    );
+    assert_eq!(pda.authority, authority);
+    assert_eq!(pda.number, number);
+}
+
+#[test]
+fn test_pda_seeds_with_bump_round_trip() {
+    let (address, pda) = ReceiptPda::find(&PROGRAM_ID, Pubkey::new_unique());
+
+    assert_eq!(
+        Pubkey::create_program_address(&pda.seeds_with_bump(), &PROGRAM_ID),
+        Ok(address)
+    );
+    assert_eq!(pda.address(&PROGRAM_ID), Ok(address));
+    assert_eq!(pda.seeds_with_bump().last(), Some(&&[pda.bump][..]));
+    // Another program's address is not derived from the same seeds
+    assert_ne!(pda.address(&Pubkey::new_unique()).ok(), Some(address));
+}
+
+#[test]
+fn test_pda_spells_anchor_seeds() {
+    assert_eq!(ReceiptPda::SEEDS, "[b\"receipt\", request.as_ref()]");
+    assert_eq!(
+        RoundPda::SEEDS,
+        "[ROUND_SEED, authority.as_ref(), b\"number\", number.as_ref()]"
+    );
+}
@@ -0,0 +1,107 @@
+//! Compiles a consumer written against 1.0, with the deprecation warnings allowed, and checks
+//! the deprecated shims build the same requests as their replacements.
+
+// Warnings are allowed: the deprecations are the point, and not every function is called
+#[allow(warnings)]
+#[path = "fixtures/old_consumer.rs"]
+mod old_consumer;
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_randomness_service_lite::*;
+
+struct Accounts {
+    keys: Vec<Pubkey>,
+    lamports: Vec<u64>,
+    data: Vec<Vec<u8>>,
+}
+
+impl Accounts {
+    fn new(len: usize) -> Self {
+        Self {
+            keys: (0..len).map(|_| Pubkey::new_unique()).collect(),
+            lamports: vec![0; len],
+            data: vec![Vec::new(); len],
+        }
+    }
+
+    fn infos(&mut self) -> Vec<AccountInfo<'_>> {
+        self.keys
+            .iter()
+            .zip(self.lamports.iter_mut())
+            .zip(self.data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, false, lamports, data, key, false, 0)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_old_instruction_builders_match_new() {
+    let mut accounts = Accounts::new(8);
+    let infos = accounts.infos();
+    let callback = callback();
+    let options = old_consumer::options();
+    let request = SimpleRandomnessV1Request {
+        request: infos[0].clone(),
+        escrow: infos[1].clone(),
+        state: infos[2].clone(),
+        mint: infos[3].clone(),
+        payer: infos[4].clone(),
+        system_program: infos[5].clone(),
+        token_program: infos[6].clone(),
+        associated_token_program: infos[7].clone(),
+    };
+    let expected = request
+        .instruction(ID, 8, &callback, options.as_ref())
+        .unwrap();
+
+    assert_eq!(
+        old_consumer::instruction(&request, &callback).unwrap(),
+        expected
+    );
+    let refs = SimpleRandomnessV1AccountRefs::from_slice(&infos).unwrap();
+    assert_eq!(
+        old_consumer::borrowed_instruction(&refs, &callback).unwrap(),
+        expected
+    );
+    assert_eq!(
+        old_consumer::instruction_data(&callback).unwrap(),
+        SimpleRandomnessV1Request::data(8, &callback, None).unwrap()
+    );
+}
+
+#[test]
+fn test_new_data_encodes_options_like_borsh() {
+    let callback = callback();
+    for options in [None, old_consumer::options()] {
+        let mut expected = SimpleRandomnessV1Request::DISCRIMINATOR.to_vec();
+        8u8.serialize(&mut expected).unwrap();
+        callback.serialize(&mut expected).unwrap();
+        options.serialize(&mut expected).unwrap();
+
+        assert_eq!(
+            SimpleRandomnessV1Request::data(8, &callback, options.as_ref()).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_old_state_decoding() {
+    let mut data = ServiceState::DISCRIMINATOR.to_vec();
+    ServiceState {
+        cost_per_byte: 100,
+        ..Default::default()
+    }
+    .serialize(&mut data)
+    .unwrap();
+
+    assert_eq!(
+        old_consumer::decode_state(&data).unwrap().cost_per_byte,
+        100
+    );
+}
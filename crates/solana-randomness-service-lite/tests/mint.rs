@@ -0,0 +1,132 @@
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+use solana_sdk::rent::Rent;
+
+const TOKEN_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+
+/// An initialized SPL token mint with the given decimals.
+fn mint_account(decimals: u8) -> Account {
+    let mut data = vec![0; 82];
+    data[44] = decimals;
+    data[45] = 1;
+
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[test]
+fn test_formats_wsol() {
+    let sol = MintInfo::native();
+
+    assert_eq!(sol.format(2_041_000), "0.002041 SOL");
+    assert_eq!(sol.format(1_000_000_000), "1 SOL");
+    assert_eq!(sol.format(12_500_000_001), "12.500000001 SOL");
+    assert_eq!(sol.format(0), "0 SOL");
+    assert_eq!(sol.format(u64::MAX), "18446744073.709551615 SOL");
+}
+
+#[test]
+fn test_formats_custom_mint() {
+    let usdc = MintInfo {
+        decimals: 6,
+        symbol: "USDC".to_string(),
+    };
+
+    assert_eq!(usdc.format(1_500_000), "1.5 USDC");
+    assert_eq!(usdc.format(10_000), "0.01 USDC");
+    assert_eq!(
+        MintInfo {
+            decimals: 0,
+            symbol: "TIX".to_string()
+        }
+        .format(42),
+        "42 TIX"
+    );
+}
+
+#[test]
+fn test_funding_display() {
+    let funding = FundingBreakdown {
+        base_fee: 10_000,
+        randomness_fee: 800,
+        priority_fee: 3_000,
+        request_rent: 2_000_000,
+        escrow_rent: 2_039_280,
+        referral_fee: 0,
+    };
+
+    let sol = funding.display(&MintInfo::native()).to_string();
+    let six = funding
+        .display(&MintInfo {
+            decimals: 6,
+            symbol: "USDC".to_string(),
+        })
+        .to_string();
+
+    assert_eq!(
+        sol.lines().collect::<Vec<_>>(),
+        vec![
+            "Base fee        0.00001 SOL",
+            "Randomness fee  0.0000008 SOL",
+            "Priority fee    0.000003 SOL",
+            "Request rent    0.002 SOL",
+            "Escrow rent     0.00203928 SOL",
+            "Total           0.00405308 SOL",
+        ]
+    );
+    assert!(six.ends_with("Total           4.05308 USDC"));
+    // JSON keeps the raw integers
+    assert_eq!(
+        serde_json::to_value(funding).unwrap()["escrow_rent"],
+        2_039_280
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_wsol_from_table() {
+    let rpc = MockRpc::default();
+
+    let info = MintInfo::resolve(&rpc, &RANDOMNESS_SERVICE_REWARD_MINT)
+        .await
+        .unwrap();
+
+    assert_eq!(info, MintInfo::native());
+    assert_eq!(rpc.calls(), 0);
+}
+
+#[tokio::test]
+async fn test_resolve_custom_mint() {
+    let rpc = MockRpc::default();
+    let mint = Pubkey::new_unique();
+    rpc.set_account(mint, mint_account(6));
+
+    let info = MintInfo::resolve(&rpc, &mint).await.unwrap().symbol("USDC");
+
+    assert_eq!(info.decimals, 6);
+    assert_eq!(info.format(2_041_000), "2.041 USDC");
+}
+
+#[tokio::test]
+async fn test_resolve_rejects_non_mint() {
+    let rpc = MockRpc::default();
+    let mint = Pubkey::new_unique();
+    let mut account = mint_account(6);
+    account.owner = Pubkey::new_unique();
+    rpc.set_account(mint, account);
+
+    assert!(matches!(
+        MintInfo::resolve(&rpc, &mint).await,
+        Err(LiteClientError::Program(ProgramError::IncorrectProgramId))
+    ));
+    assert!(matches!(
+        MintInfo::resolve(&rpc, &Pubkey::new_unique()).await,
+        Err(LiteClientError::Program(ProgramError::UninitializedAccount))
+    ));
+}
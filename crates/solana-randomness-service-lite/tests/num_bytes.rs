@@ -0,0 +1,132 @@
+//! The request wrappers reject byte counts the service would refuse before building anything.
+
+mod common;
+
+use common::*;
+use solana_randomness_service_lite::*;
+
+fn invalid() -> ProgramError {
+    LiteError::InvalidNumBytes.into()
+}
+
+/// Runs `f` with new, empty accounts as the request accounts and the service program, all
+/// borrowed for the same lifetime as the wrappers require.
+fn with_request<R>(
+    f: impl for<'info> FnOnce(&SimpleRandomnessV1Request<'info>, AccountInfo<'info>) -> R,
+) -> R {
+    let keys: Vec<Pubkey> = (0..9).map(|_| Pubkey::new_unique()).collect();
+    let mut lamports = [0u64; 9];
+    let mut data: Vec<Vec<u8>> = vec![Vec::new(); 9];
+    let owner = solana_program::system_program::ID;
+    let mut infos = keys
+        .iter()
+        .zip(lamports.iter_mut())
+        .zip(data.iter_mut())
+        .map(|((key, lamports), data)| {
+            AccountInfo::new(
+                key,
+                true,
+                true,
+                lamports,
+                data.as_mut_slice(),
+                &owner,
+                false,
+                0,
+            )
+        });
+    let request = SimpleRandomnessV1Request {
+        request: infos.next().unwrap(),
+        escrow: infos.next().unwrap(),
+        state: infos.next().unwrap(),
+        mint: infos.next().unwrap(),
+        payer: infos.next().unwrap(),
+        system_program: infos.next().unwrap(),
+        token_program: infos.next().unwrap(),
+        associated_token_program: infos.next().unwrap(),
+    };
+    let program = infos.next().unwrap();
+    f(&request, program)
+}
+
+#[test]
+fn test_bounds() {
+    assert_eq!(MIN_RANDOMNESS_BYTES, 1);
+    assert_eq!(MAX_RANDOMNESS_BYTES, 32);
+    assert_eq!(MAX_RANDOMNESS_BYTES, MAX_NUM_BYTES);
+}
+
+#[test]
+fn test_check_num_bytes() {
+    assert_eq!(check_num_bytes(0), Err(invalid()));
+    assert_eq!(check_num_bytes(1), Ok(()));
+    assert_eq!(check_num_bytes(32), Ok(()));
+    assert_eq!(check_num_bytes(33), Err(invalid()));
+}
+
+#[test]
+fn test_data_validates_num_bytes() {
+    let callback = callback();
+
+    assert_eq!(
+        SimpleRandomnessV1Request::data(0, &callback, None),
+        Err(invalid())
+    );
+    assert_eq!(
+        SimpleRandomnessV1Request::data(1, &callback, None).unwrap()[8],
+        1
+    );
+    assert_eq!(
+        SimpleRandomnessV1Request::data(32, &callback, None).unwrap()[8],
+        32
+    );
+    assert_eq!(
+        SimpleRandomnessV1Request::data(33, &callback, None),
+        Err(invalid())
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_get_instruction_validates_num_bytes() {
+    let callback = callback();
+    with_request(|request, _| {
+        for (num_bytes, valid) in [(0, false), (1, true), (32, true), (33, false)] {
+            let instruction = request.get_instruction(ID, num_bytes, &callback, &None);
+            assert_eq!(instruction.is_ok(), valid, "{} bytes", num_bytes);
+            if !valid {
+                assert_eq!(instruction.unwrap_err(), invalid());
+            }
+        }
+    });
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_invoke_validates_num_bytes() {
+    let callback = callback();
+    with_request(|request, program| {
+        for num_bytes in [0, 33] {
+            assert_eq!(
+                request.invoke(program.clone(), num_bytes, &callback, &None),
+                Err(invalid())
+            );
+            assert_eq!(
+                request.invoke_signed(program.clone(), num_bytes, &callback, &None, &[]),
+                Err(invalid())
+            );
+            assert_eq!(
+                request.cpi(program.clone(), num_bytes, &callback, None),
+                Err(invalid())
+            );
+        }
+    });
+}
+
+#[test]
+fn test_describes_invalid_num_bytes() {
+    assert_eq!(
+        describe_error_code(LiteError::InvalidNumBytes.code()),
+        Some("The number of randomness bytes must be from 1 to 32")
+    );
+    assert_eq!(LiteError::InvalidNumBytes.code(), 7010);
+}
@@ -0,0 +1,72 @@
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use std::collections::HashMap;
+
+#[test]
+fn test_json_partial_override() {
+    let function = Pubkey::new_unique();
+    let overrides =
+        AddressOverrides::from_json(&format!(r#"{{"switchboard_function": "{}"}}"#, function))
+            .unwrap();
+
+    assert_eq!(
+        overrides,
+        AddressOverrides {
+            switchboard_function: Some(function),
+            ..Default::default()
+        }
+    );
+    let addresses = ServiceAddresses::mainnet()
+        .with_overrides(overrides)
+        .unwrap();
+    assert_eq!(addresses.switchboard_function, function);
+    assert_eq!(addresses.switchboard_service, MAINNET_SWITCHBOARD_SERVICE);
+}
+
+#[test]
+fn test_json_rejects_invalid_overrides() {
+    assert!(matches!(
+        AddressOverrides::from_json(r#"{"state": "not-an-address"}"#),
+        Err(OverridesError::InvalidAddress { name, .. }) if name == "state"
+    ));
+    assert!(matches!(
+        AddressOverrides::from_json(r#"{"function": "11111111111111111111111111111111"}"#),
+        Err(OverridesError::Json(_))
+    ));
+}
+
+#[test]
+fn test_inconsistent_json_override_is_rejected() {
+    let overrides =
+        AddressOverrides::from_json(&format!(r#"{{"program_id": "{}"}}"#, Pubkey::new_unique()))
+            .unwrap();
+
+    assert!(matches!(
+        ServiceAddresses::mainnet().with_overrides(overrides),
+        Err(AddressError::StateMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_env_overrides() {
+    let service = Pubkey::new_unique();
+    let vars = HashMap::from([
+        ("RANDOMNESS_SWITCHBOARD_SERVICE", service.to_string()),
+        ("RANDOMNESS_STATE", " ".to_string()),
+    ]);
+
+    let overrides = AddressOverrides::from_vars(|name| vars.get(name).cloned()).unwrap();
+    assert_eq!(
+        overrides,
+        AddressOverrides {
+            switchboard_service: Some(service),
+            ..Default::default()
+        }
+    );
+
+    let vars = HashMap::from([("RANDOMNESS_PROGRAM_ID", "bad".to_string())]);
+    assert!(matches!(
+        AddressOverrides::from_vars(|name| vars.get(name).cloned()),
+        Err(OverridesError::InvalidAddress { name, .. }) if name == "RANDOMNESS_PROGRAM_ID"
+    ));
+}
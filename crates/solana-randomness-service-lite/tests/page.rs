@@ -0,0 +1,95 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const SEED: &[u8] = b"CALLBACKS";
+
+type CallbackPage = Page<AccountMetaBorsh, 4>;
+
+fn meta() -> AccountMetaBorsh {
+    AccountMeta::new(Pubkey::new_unique(), false).into()
+}
+
+#[test]
+fn test_full_page_fills_space_exactly() {
+    let mut page = CallbackPage::new(0);
+    for _ in 0..CallbackPage::CAPACITY {
+        page.try_push(meta()).unwrap();
+    }
+
+    assert!(page.is_full());
+    assert_eq!(page.try_to_vec().unwrap().len(), CallbackPage::SPACE);
+}
+
+#[test]
+fn test_push_rolls_to_next_page() {
+    let owner = Pubkey::new_unique();
+    let mut page = CallbackPage::new(0);
+    for _ in 0..CallbackPage::CAPACITY {
+        page.try_push(meta()).unwrap();
+    }
+
+    let item = meta();
+    let PageFull(rejected) = page.try_push(item.clone()).unwrap_err();
+    assert_eq!(rejected.pubkey, item.pubkey);
+    assert_eq!(page.items.len(), CallbackPage::CAPACITY);
+
+    let mut next = page.next();
+    next.try_push(rejected).unwrap();
+    assert_eq!(next.index, 1);
+    assert_eq!(next.items.len(), 1);
+
+    let (first, _) = page_address(SEED, &owner, page.index, &PROGRAM_ID);
+    let (second, bump) = page_address(SEED, &owner, next.index, &PROGRAM_ID);
+    assert_ne!(first, second);
+    assert_eq!(
+        Pubkey::create_program_address(
+            &[SEED, owner.as_ref(), &1u32.to_le_bytes(), &[bump]],
+            &PROGRAM_ID
+        ),
+        Ok(second)
+    );
+}
+
+#[test]
+fn test_page_full_converts_to_program_error() {
+    let mut page = Page::<Pubkey, 1>::new(0);
+    page.try_push(Pubkey::new_unique()).unwrap();
+
+    let error: ProgramError = page.try_push(Pubkey::new_unique()).unwrap_err().into();
+
+    assert_eq!(error, LiteError::PageFull.into());
+}
+
+#[test]
+fn test_iter_pages() {
+    let keys: Vec<Pubkey> = (0..10).map(|_| Pubkey::new_unique()).collect();
+
+    let pages: Vec<Page<Pubkey, 4>> = iter_pages(keys.clone()).collect();
+
+    assert_eq!(
+        pages
+            .iter()
+            .map(|p| (p.index, p.items.len()))
+            .collect::<Vec<_>>(),
+        vec![(0, 4), (1, 4), (2, 2)]
+    );
+    assert_eq!(
+        pages.into_iter().flat_map(|p| p.items).collect::<Vec<_>>(),
+        keys
+    );
+    assert_eq!(iter_pages::<Pubkey, 4>(Vec::new()).count(), 0);
+}
+
+#[test]
+fn test_decodes_partial_page_from_full_allocation() {
+    let mut page = CallbackPage::new(3);
+    page.try_push(meta()).unwrap();
+    let mut data = page.try_to_vec().unwrap();
+    data.resize(CallbackPage::SPACE, 0);
+
+    let decoded: CallbackPage = deserialize_with_reserved(&data).unwrap();
+
+    assert_eq!(decoded.index, 3);
+    assert_eq!(decoded.items[0].pubkey, page.items[0].pubkey);
+}
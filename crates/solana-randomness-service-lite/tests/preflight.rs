@@ -0,0 +1,149 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+const CONSUMER_PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn check(owner: Pubkey, lamports: u64, data_len: usize) -> Result<(), ProgramError> {
+    let key = Pubkey::new_unique();
+    let mut lamports = lamports;
+    let mut data = vec![0u8; data_len];
+    let request = AccountInfo::new(&key, true, true, &mut lamports, &mut data, &owner, false, 0);
+    check_request_account(&request, &CONSUMER_PROGRAM_ID)
+}
+
+#[test]
+fn test_new_account_passes() {
+    assert_eq!(check(solana_program::system_program::ID, 0, 0), Ok(()));
+}
+
+#[test]
+fn test_funded_account_rejected() {
+    assert_eq!(
+        check(solana_program::system_program::ID, 1_000_000, 0),
+        Err(LiteError::RequestAccountNotEmpty.into())
+    );
+}
+
+#[test]
+fn test_consumer_pda_rejected() {
+    assert_eq!(
+        check(CONSUMER_PROGRAM_ID, 1_000_000, 64),
+        Err(LiteError::RequestAccountOwnedByProgram.into())
+    );
+}
+
+#[test]
+fn test_existing_request_rejected() {
+    assert_eq!(
+        check(ID, 1_000_000, 532),
+        Err(LiteError::RequestAccountOwnedByProgram.into())
+    );
+}
+
+fn state_data(mint: Pubkey) -> Vec<u8> {
+    let mut data = ServiceState::DISCRIMINATOR.to_vec();
+    data.extend(
+        ServiceState {
+            mint,
+            cost_per_byte: 100_000,
+            ..Default::default()
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    data
+}
+
+fn check_mint(state_owner: Pubkey, state_data: Vec<u8>, mint: Pubkey) -> Result<(), ProgramError> {
+    let mut state_lamports = 1_000_000;
+    let mut state_data = state_data;
+    let state = AccountInfo::new(
+        &RANDOMNESS_SERVICE_STATE,
+        false,
+        false,
+        &mut state_lamports,
+        &mut state_data,
+        &state_owner,
+        false,
+        0,
+    );
+    let mut mint_lamports = 1_000_000;
+    let mut mint_data = vec![0u8; 82];
+    let token_program = Pubkey::new_unique();
+    let mint = AccountInfo::new(
+        &mint,
+        false,
+        false,
+        &mut mint_lamports,
+        &mut mint_data,
+        &token_program,
+        false,
+        0,
+    );
+    check_escrow_mint(&state, &mint)
+}
+
+#[test]
+fn test_state_mint_passes() {
+    let mint = Pubkey::new_unique();
+    assert_eq!(check_mint(ID, state_data(mint), mint), Ok(()));
+}
+
+#[test]
+fn test_reconfigured_mint_rejected() {
+    assert_eq!(
+        check_mint(ID, state_data(Pubkey::new_unique()), Pubkey::new_unique()),
+        Err(LiteError::MintMismatch.into())
+    );
+}
+
+#[test]
+fn test_state_not_owned_by_service_rejected() {
+    let mint = Pubkey::new_unique();
+    assert_eq!(
+        check_mint(Pubkey::new_unique(), state_data(mint), mint),
+        Err(ProgramError::IllegalOwner)
+    );
+}
+
+#[test]
+fn test_state_data_checked() {
+    let mint = Pubkey::new_unique();
+    let mut data = state_data(mint);
+    data[0] ^= 1;
+    assert_eq!(
+        check_mint(ID, data, mint),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        check_mint(
+            ID,
+            state_data(mint)[..ServiceState::MINT_OFFSET + 31].to_vec(),
+            mint
+        ),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+}
+
+#[test]
+fn test_read_mint_matches_deserialized_state() {
+    let mint = Pubkey::new_unique();
+    let data = state_data(mint);
+    assert_eq!(ServiceState::read_mint(&data), Ok(mint));
+    assert_eq!(
+        ServiceState::try_deserialize(&mut &data[..]).unwrap().mint,
+        mint
+    );
+}
+
+#[test]
+fn test_error_codes() {
+    assert_eq!(
+        ProgramError::from(LiteError::RequestAccountNotEmpty),
+        ProgramError::Custom(7000)
+    );
+    assert_eq!(
+        ProgramError::from(LiteError::RequestAccountOwnedByProgram),
+        ProgramError::Custom(7001)
+    );
+}
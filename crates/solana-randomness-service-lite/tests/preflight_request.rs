@@ -0,0 +1,76 @@
+//! `preflight_request` checks the request's mint against the service state, against the mock
+//! RPC.
+
+use borsh::BorshSerialize;
+use solana_program::pubkey;
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+
+const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+fn state_account(mint: Pubkey, owner: Pubkey) -> Account {
+    let mut data = ServiceState::DISCRIMINATOR.to_vec();
+    data.extend(
+        ServiceState {
+            mint,
+            ..Default::default()
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_state_mint_passes() {
+    let rpc = MockRpc::default();
+    rpc.set_account(RANDOMNESS_SERVICE_STATE, state_account(NATIVE_MINT, ID));
+
+    preflight_request(&rpc, &RANDOMNESS_SERVICE_STATE, &NATIVE_MINT)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_reconfigured_mint_rejected() {
+    let rpc = MockRpc::default();
+    let devnet_mint = Pubkey::new_unique();
+    rpc.set_account(RANDOMNESS_SERVICE_STATE, state_account(devnet_mint, ID));
+
+    let error = preflight_request(&rpc, &RANDOMNESS_SERVICE_STATE, &NATIVE_MINT)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        LiteClientError::MintMismatch { expected, got }
+            if expected == devnet_mint && got == NATIVE_MINT
+    ));
+    assert_eq!(error.kind(), ErrorKind::Validation);
+}
+
+#[tokio::test]
+async fn test_missing_or_foreign_state_rejected() {
+    let rpc = MockRpc::default();
+    assert!(matches!(
+        preflight_request(&rpc, &RANDOMNESS_SERVICE_STATE, &NATIVE_MINT).await,
+        Err(LiteClientError::Program(ProgramError::UninitializedAccount))
+    ));
+
+    rpc.set_account(
+        RANDOMNESS_SERVICE_STATE,
+        state_account(NATIVE_MINT, Pubkey::new_unique()),
+    );
+    assert!(matches!(
+        preflight_request(&rpc, &RANDOMNESS_SERVICE_STATE, &NATIVE_MINT).await,
+        Err(LiteClientError::Program(ProgramError::IllegalOwner))
+    ));
+}
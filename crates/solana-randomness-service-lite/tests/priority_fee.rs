@@ -0,0 +1,99 @@
+//! The priority fee in lamports rounds up, so a budget built from it covers the fee, while the
+//! service's fee rounds it down as the service does when it wraps it into the escrow.
+
+use solana_randomness_service_lite::*;
+
+fn options(compute_units: u32, compute_unit_price: u64) -> TransactionOptions {
+    TransactionOptions {
+        compute_units: Some(compute_units),
+        compute_unit_price: Some(compute_unit_price),
+    }
+}
+
+/// The priority fee in lamports, and the part of the service's fee it makes up.
+fn lamports(compute_units: u32, compute_unit_price: u64) -> (u64, u64) {
+    let options = options(compute_units, compute_unit_price);
+    (
+        options.get_priority_fee_lamports().unwrap(),
+        estimate_request_cost(1, 0, &Some(options)).unwrap() - REQUEST_BASE_FEE,
+    )
+}
+
+#[test]
+fn test_partial_lamport_rounds_up() {
+    // 600,000 micro-lamports: a budget needs 1 lamport, the service wraps 0
+    let options = options(200_000, 3);
+    assert_eq!(options.get_priority_fee_micro_lamports(), Some(600_000));
+    assert_eq!(options.get_priority_fee_lamports(), Some(1));
+    assert_eq!(lamports(200_000, 3), (1, 0));
+
+    assert_eq!(lamports(1_400_000, 1), (2, 1));
+    assert_eq!(lamports(1_000_001, 1), (2, 1));
+}
+
+#[test]
+fn test_whole_lamports_are_exact() {
+    assert_eq!(lamports(1_000_000, 1), (1, 1));
+    assert_eq!(lamports(200_000, 5), (1, 1));
+    assert_eq!(lamports(1_000_000, 1_000), (1_000, 1_000));
+    assert_eq!(lamports(1_000_000, 1_001), (1_001, 1_001));
+}
+
+#[test]
+fn test_boundaries() {
+    // The smallest fee is a fraction of a lamport
+    let minimum = TransactionOptions {
+        compute_units: Some(TransactionOptions::MINIMUM_COMPUTE_UNITS),
+        compute_unit_price: Some(TransactionOptions::MINIMUM_COMPUTE_UNIT_PRICE),
+    };
+    assert_eq!(minimum.get_priority_fee_micro_lamports(), Some(200_000));
+    assert_eq!(minimum.get_priority_fee_lamports(), Some(1));
+
+    // The largest fee does not overflow
+    let maximum = options(
+        TransactionOptions::MAXIMUM_COMPUTE_UNITS,
+        TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE,
+    );
+    assert_eq!(
+        maximum.get_priority_fee_micro_lamports(),
+        Some(1_400_000_000_000_000)
+    );
+    assert_eq!(maximum.get_priority_fee_lamports(), Some(1_400_000_000));
+    assert_eq!(
+        lamports(
+            TransactionOptions::MAXIMUM_COMPUTE_UNITS,
+            TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE
+        ),
+        (1_400_000_000, 1_400_000_000)
+    );
+
+    // Out of range options are clamped before the product
+    assert_eq!(
+        options(u32::MAX, u64::MAX).get_priority_fee_micro_lamports(),
+        maximum.get_priority_fee_micro_lamports()
+    );
+}
+
+#[test]
+fn test_service_fee_overflow() {
+    assert_eq!(
+        estimate_request_cost(2, u64::MAX, &None),
+        Err(LiteError::FeeOverflow.into())
+    );
+    assert_eq!(
+        estimate_request_cost(1, u64::MAX - REQUEST_BASE_FEE, &None),
+        Err(LiteError::FeeOverflow.into())
+    );
+    assert_eq!(
+        estimate_request_cost(1, u64::MAX - REQUEST_BASE_FEE, &Some(options(1, 0))),
+        Ok(u64::MAX)
+    );
+}
+
+#[test]
+fn test_defaults() {
+    let options = TransactionOptions::default();
+    assert_eq!(options.get_priority_fee_micro_lamports(), Some(1_000_000));
+    assert_eq!(options.get_priority_fee_lamports(), Some(1));
+    assert_eq!(estimate_request_cost(1, 0, &None), Ok(REQUEST_BASE_FEE + 1));
+}
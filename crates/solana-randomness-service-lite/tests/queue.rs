@@ -0,0 +1,175 @@
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+use std::time::Duration;
+
+const CURRENT_SLOT: u64 = 100_000;
+
+fn pending_set(pending: usize, errored: usize) -> Vec<(Pubkey, SimpleRandomnessV1Account)> {
+    (0..pending + errored)
+        .map(|i| {
+            let request = SimpleRandomnessV1Account {
+                is_completed: u8::from(i >= pending),
+                ..Default::default()
+            };
+            (Pubkey::new_unique(), request)
+        })
+        .collect()
+}
+
+/// `count` fulfillments evenly spaced over the `span` slots before the current slot, newest
+/// first.
+fn timeline(count: u64, span: u64) -> Vec<u64> {
+    (0..count)
+        .map(|i| CURRENT_SLOT - i * span / count)
+        .collect()
+}
+
+fn secs(duration: Option<Duration>) -> f64 {
+    duration.unwrap().as_secs_f64()
+}
+
+#[test]
+fn test_steady_rate() {
+    // 60 fulfillments over an hour of slots is one a minute
+    let estimate = QueueEstimate::new(
+        &pending_set(4, 2),
+        &timeline(60, FULFILLMENT_WINDOW_SLOTS),
+        CURRENT_SLOT,
+    );
+
+    assert_eq!(estimate.pending_requests, 4);
+    assert_eq!(estimate.fulfillments_observed, 60);
+    assert_eq!(estimate.window_slots, FULFILLMENT_WINDOW_SLOTS);
+    assert!((estimate.recent_fulfillment_rate_per_min - 1.0).abs() < 1e-9);
+    // Behind 4 pending requests at one a minute
+    assert!((secs(estimate.estimated_wait) - 300.0).abs() < 1e-6);
+    assert!(secs(estimate.min_wait) < 300.0 && secs(estimate.max_wait) > 300.0);
+    assert!(!estimate.sparse);
+}
+
+#[test]
+fn test_sparse_history_widens_interval() {
+    let dense = QueueEstimate::new(
+        &pending_set(0, 0),
+        &timeline(60, FULFILLMENT_WINDOW_SLOTS),
+        CURRENT_SLOT,
+    );
+    let sparse = QueueEstimate::new(
+        &pending_set(0, 0),
+        &timeline(3, FULFILLMENT_WINDOW_SLOTS),
+        CURRENT_SLOT,
+    );
+
+    assert!(sparse.sparse);
+    let ratio = |e: &QueueEstimate| secs(e.max_wait) / secs(e.min_wait);
+    assert!(ratio(&sparse) > 4.0 * ratio(&dense));
+}
+
+#[test]
+fn test_no_fulfillments() {
+    let estimate = QueueEstimate::new(&pending_set(2, 0), &[], CURRENT_SLOT);
+
+    assert_eq!(estimate.recent_fulfillment_rate_per_min, 0.0);
+    assert_eq!(estimate.estimated_wait, None);
+    assert_eq!(estimate.max_wait, None);
+    // Even an empty window bounds the rate from above
+    assert!(estimate.min_wait.is_some());
+    assert!(estimate.sparse);
+}
+
+#[test]
+fn test_ignores_fulfillments_outside_window() {
+    let mut slots = timeline(20, FULFILLMENT_WINDOW_SLOTS);
+    slots.push(CURRENT_SLOT - FULFILLMENT_WINDOW_SLOTS - 1);
+    slots.push(CURRENT_SLOT + 1);
+
+    let estimate = QueueEstimate::new(&[], &slots, CURRENT_SLOT);
+
+    assert_eq!(estimate.fulfillments_observed, 20);
+}
+
+#[test]
+fn test_full_sample_narrows_window() {
+    let span = 1_500;
+    let slots = timeline(FULFILLMENT_SAMPLE_LIMIT as u64, span);
+
+    let estimate = QueueEstimate::new(&[], &slots, CURRENT_SLOT);
+
+    let oldest = *slots.last().unwrap();
+    assert_eq!(estimate.window_slots, CURRENT_SLOT - oldest);
+    // 1000 fulfillments in about 10 minutes
+    assert!((estimate.recent_fulfillment_rate_per_min - 100.0).abs() < 1.0);
+}
+
+#[test]
+fn test_early_chain_window() {
+    let estimate = QueueEstimate::new(&[], &[10, 20, 30], 100);
+
+    assert_eq!(estimate.window_slots, 100);
+}
+
+#[tokio::test]
+async fn test_recent_fulfillment_slots() {
+    let rpc = MockRpc::default();
+    let fulfillment = |slot: u64, failed: bool| TransactionRecord {
+        signature: Signature::new_unique(),
+        slot,
+        failed,
+        account_keys: vec![RANDOMNESS_SERVICE_REWARD_WALLET, ID],
+        log_messages: vec![],
+        token_balances: vec![],
+    };
+    rpc.add_transaction(fulfillment(
+        CURRENT_SLOT - FULFILLMENT_WINDOW_SLOTS - 10,
+        false,
+    ));
+    rpc.add_transaction(fulfillment(CURRENT_SLOT - 200, false));
+    rpc.add_transaction(fulfillment(CURRENT_SLOT - 100, true));
+    rpc.add_transaction(fulfillment(CURRENT_SLOT - 50, false));
+    rpc.add_transaction(TransactionRecord {
+        account_keys: vec![ID],
+        ..fulfillment(CURRENT_SLOT, false)
+    });
+
+    let slots = recent_fulfillment_slots(&rpc, &RANDOMNESS_SERVICE_REWARD_WALLET, CURRENT_SLOT)
+        .await
+        .unwrap();
+
+    assert_eq!(slots, vec![CURRENT_SLOT - 50, CURRENT_SLOT - 200]);
+}
+
+#[tokio::test]
+async fn test_estimate_with_warped_clock() {
+    let rpc = MockRpc::default();
+    rpc.add_transaction(TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: CURRENT_SLOT - 50,
+        failed: false,
+        account_keys: vec![RANDOMNESS_SERVICE_REWARD_WALLET, ID],
+        log_messages: vec![],
+        token_balances: vec![],
+    });
+    let addresses = ServiceAddresses::default();
+
+    rpc.warp_to_slot(CURRENT_SLOT);
+    assert_eq!(rpc.get_slot().await.unwrap(), CURRENT_SLOT);
+    let estimate = estimate_queue_depth_with_clock(&rpc, &addresses, &RpcSlotClock(&rpc))
+        .await
+        .unwrap();
+    assert_eq!(estimate.fulfillments_observed, 1);
+
+    // Once the clock passes the window the fulfillment no longer counts
+    rpc.warp_to_slot(CURRENT_SLOT + FULFILLMENT_WINDOW_SLOTS);
+    let estimate = estimate_queue_depth_with_clock(&rpc, &addresses, &RpcSlotClock(&rpc))
+        .await
+        .unwrap();
+    assert_eq!(estimate.fulfillments_observed, 0);
+
+    // A fixed slot ignores the RPC's
+    let estimate = estimate_queue_depth_with_clock(&rpc, &addresses, &CURRENT_SLOT)
+        .await
+        .unwrap();
+    assert_eq!(estimate.fulfillments_observed, 1);
+}
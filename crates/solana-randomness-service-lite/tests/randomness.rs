@@ -0,0 +1,487 @@
+use solana_randomness_service_lite::*;
+
+/// The data the service invokes a callback with: its stored `ix_data` and the Borsh encoded
+/// randomness.
+fn settle_data(ix_data: &[u8], randomness: &[u8]) -> Vec<u8> {
+    [
+        ix_data,
+        &(randomness.len() as u32).to_le_bytes(),
+        randomness,
+    ]
+    .concat()
+}
+
+fn lite_error<T>(result: Result<T, ProgramError>) -> Option<LiteError> {
+    match result {
+        Err(ProgramError::Custom(code)) => LiteError::from_code(code),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_extract_after_discriminator() {
+    let randomness: Vec<u8> = (1..=8).collect();
+    let data = settle_data(&anchor_sighash("consume_randomness"), &randomness);
+
+    assert_eq!(extract_randomness(&data, 8).unwrap(), randomness.as_slice());
+}
+
+#[test]
+fn test_extract_skips_callback_args() {
+    let randomness = [7u8; 32];
+    let ix_data = [
+        anchor_sighash("consume_bracket_randomness").as_slice(),
+        &IndexedTag::new(42, 3).to_bytes(),
+    ]
+    .concat();
+    let data = settle_data(&ix_data, &randomness);
+
+    assert_eq!(extract_randomness(&data, 32).unwrap(), &randomness);
+}
+
+#[test]
+fn test_extract_wrong_length() {
+    let data = settle_data(&anchor_sighash("consume_randomness"), &[1, 2, 3, 4]);
+
+    for expected_len in [3, 5] {
+        assert_eq!(
+            lite_error(extract_randomness(&data, expected_len)),
+            Some(LiteError::RandomnessLengthMismatch)
+        );
+    }
+}
+
+#[test]
+fn test_extract_short_data() {
+    let data = settle_data(&anchor_sighash("consume_randomness"), &[1, 2, 3, 4]);
+
+    // Cut into the length prefix, then into the discriminator
+    for len in [0, 4, 8, 11, data.len() - 1] {
+        assert_eq!(
+            lite_error(extract_randomness(&data[..len], 4)),
+            Some(LiteError::RandomnessLengthMismatch)
+        );
+    }
+    // Without a discriminator the bytes are not the service's
+    let data = settle_data(&[], &[1, 2, 3, 4]);
+    assert_eq!(
+        lite_error(extract_randomness(&data, 4)),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_expected_len_out_of_range() {
+    let data = settle_data(&anchor_sighash("consume_randomness"), &[1; 8]);
+
+    for expected_len in [0, 33, 256] {
+        assert_eq!(
+            lite_error(extract_randomness(&data, expected_len)),
+            Some(LiteError::InvalidNumBytes)
+        );
+        assert_eq!(
+            lite_error(randomness_from_arg(&[1; 8], expected_len)),
+            Some(LiteError::InvalidNumBytes)
+        );
+    }
+}
+
+#[test]
+fn test_randomness_from_arg() {
+    let result = vec![9u8; 16];
+    assert_eq!(randomness_from_arg(&result, 16).unwrap(), result.as_slice());
+
+    for expected_len in [15, 17] {
+        assert_eq!(
+            lite_error(randomness_from_arg(&result, expected_len)),
+            Some(LiteError::RandomnessLengthMismatch)
+        );
+    }
+}
+
+#[test]
+fn test_error_code() {
+    assert_eq!(LiteError::RandomnessLengthMismatch.code(), 7014);
+    assert_eq!(
+        LiteError::from_code(7014),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_bytes_exact_size() {
+    let bytes: Vec<u8> = (1..=16).collect();
+
+    assert_eq!(RandomnessBytes(&bytes[..1]).to_u8(), Ok(1));
+    assert_eq!(RandomnessBytes(&bytes[..2]).to_u16(), Ok(0x0201));
+    assert_eq!(RandomnessBytes(&bytes[..4]).to_u32(), Ok(0x04030201));
+    assert_eq!(
+        RandomnessBytes(&bytes[..8]).to_u64(),
+        Ok(u64::from_le_bytes(bytes[..8].try_into().unwrap()))
+    );
+    assert_eq!(
+        RandomnessBytes(&bytes).to_u128(),
+        Ok(u128::from_le_bytes(bytes[..].try_into().unwrap()))
+    );
+}
+
+#[test]
+fn test_bytes_reads_leading_bytes() {
+    let bytes = [0xff, 0x01, 0x02, 0x03, 0x04];
+    let randomness = RandomnessBytes::new(&bytes);
+
+    assert_eq!(randomness.to_u8(), Ok(0xff));
+    assert_eq!(randomness.to_u16(), Ok(0x01ff));
+    assert_eq!(randomness.to_u32(), Ok(0x030201ff));
+}
+
+#[test]
+fn test_bytes_short_buffers() {
+    let bytes = [7u8; 15];
+    let short = |len: usize| RandomnessBytes(&bytes[..len]);
+
+    assert_eq!(
+        lite_error(short(0).to_u8()),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+    assert_eq!(
+        lite_error(short(1).to_u16()),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+    assert_eq!(
+        lite_error(short(3).to_u32()),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+    assert_eq!(
+        lite_error(short(7).to_u64()),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+    assert_eq!(
+        lite_error(short(15).to_u128()),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_bytes_chunks() {
+    let bytes: Vec<u8> = (0..32).collect();
+    let randomness = RandomnessBytes::from(&bytes[..]);
+
+    let values: Vec<u64> = randomness
+        .chunks(8)
+        .map(|chunk| chunk.to_u64().unwrap())
+        .collect();
+    assert_eq!(values.len(), 4);
+    for (i, value) in values.iter().enumerate() {
+        assert_eq!(value.to_le_bytes(), bytes[i * 8..(i + 1) * 8]);
+    }
+
+    // The 2 bytes after the last full chunk are skipped
+    let chunks: Vec<RandomnessBytes> = randomness.chunks(10).collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[2].as_bytes(), &bytes[20..30]);
+    assert_eq!(RandomnessBytes(&bytes[..4]).chunks(8).count(), 0);
+}
+
+#[test]
+fn test_random_in_range_rejects_empty_range() {
+    assert_eq!(
+        lite_error(random_in_range(&[0; 8], 5, 4)),
+        Some(LiteError::InvalidRange)
+    );
+}
+
+#[test]
+fn test_random_in_range_needs_a_full_draw() {
+    assert_eq!(
+        lite_error(random_in_range(&[0; 7], 1, 6)),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_random_in_range_scales_the_draw() {
+    // The draw is scaled onto the range rather than reduced modulo its size
+    assert_eq!(
+        random_in_range(&(1u64 << 62).to_le_bytes(), 1, 6).unwrap(),
+        2
+    );
+    assert_eq!(random_in_range(&u64::MAX.to_le_bytes(), 1, 6).unwrap(), 6);
+    assert_eq!(
+        random_in_range(&((1u64 << 63) + 1).to_le_bytes(), 0, 9).unwrap(),
+        5
+    );
+    assert_eq!(random_in_range(&[7; 8], 42, 42).unwrap(), 42);
+}
+
+#[test]
+fn test_random_in_range_full_range_returns_the_draw() {
+    let draw = 0x0123_4567_89ab_cdefu64;
+    assert_eq!(
+        random_in_range(&draw.to_le_bytes(), 0, u64::MAX).unwrap(),
+        draw
+    );
+}
+
+#[test]
+fn test_random_in_range_reads_the_next_draw_after_a_rejection() {
+    // A zero draw lands in the 2^64 % 6 draws that would over-represent the low values
+    let rejected = 0u64.to_le_bytes();
+    assert_eq!(
+        lite_error(random_in_range(&rejected, 1, 6)),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+
+    let bytes = [rejected, u64::MAX.to_le_bytes()].concat();
+    assert_eq!(random_in_range(&bytes, 1, 6).unwrap(), 6);
+    // Deterministic for the same bytes
+    assert_eq!(random_in_range(&bytes, 1, 6).unwrap(), 6);
+}
+
+#[test]
+fn test_random_in_range_covers_the_range() {
+    let mut seen = [false; 7];
+    for top in 0..=u16::MAX {
+        let draw = (u64::from(top) << 48) | 0xffff_ffff_ffff;
+        let value = random_in_range(&draw.to_le_bytes(), 10, 16).unwrap();
+        seen[(value - 10) as usize] = true;
+    }
+    assert!(seen.iter().all(|seen| *seen));
+}
+
+#[test]
+fn test_required_randomness_bytes_for_shuffle() {
+    assert_eq!(required_randomness_bytes_for_shuffle(0), 0);
+    assert_eq!(required_randomness_bytes_for_shuffle(1), 0);
+    assert_eq!(required_randomness_bytes_for_shuffle(2), 16);
+    assert_eq!(
+        required_randomness_bytes_for_shuffle(4),
+        usize::from(MAX_RANDOMNESS_BYTES)
+    );
+}
+
+#[test]
+fn test_shuffle_fails_without_enough_randomness() {
+    let mut items = [1, 2, 3, 4];
+    assert_eq!(
+        lite_error(shuffle_in_place(&mut items, &[u8::MAX; 24])),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+    assert_eq!(items, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_shuffle_short_slices_need_no_randomness() {
+    let mut empty: [u8; 0] = [];
+    shuffle_in_place(&mut empty, &[]).unwrap();
+    let mut one = [7];
+    shuffle_in_place(&mut one, &[]).unwrap();
+    assert_eq!(one, [7]);
+}
+
+#[test]
+fn test_shuffle_swaps_from_the_back() {
+    // The maximal draw picks the last remaining index at every step, leaving the order as is
+    let mut items = [1, 2, 3, 4];
+    shuffle_in_place(&mut items, &[u8::MAX; 32]).unwrap();
+    assert_eq!(items, [1, 2, 3, 4]);
+
+    // A draw of 2^62 picks index 1 of 4, then 0 of 3, then 0 of 2
+    let draw = (1u64 << 62).to_le_bytes();
+    let mut items = [1, 2, 3, 4];
+    shuffle_in_place(&mut items, &[draw; 4].concat()).unwrap();
+    assert_eq!(items, [4, 3, 1, 2]);
+}
+
+#[test]
+fn test_shuffle_uses_the_spare_draw_after_a_rejection() {
+    // A zero draw is rejected for 3 values, so the first swap reads the next draw and the
+    // second swap reads the spare
+    let (zero, max) = (0u64.to_le_bytes(), u64::MAX.to_le_bytes());
+    let mut items = ['a', 'b', 'c'];
+    shuffle_in_place(&mut items, &[zero, max, max].concat()).unwrap();
+    assert_eq!(items, ['a', 'b', 'c']);
+
+    assert_eq!(
+        lite_error(shuffle_in_place(&mut items, &[zero, zero, max].concat())),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_shuffle_is_deterministic_permutation() {
+    let randomness: Vec<u8> = (0..64).map(|i| (i * 37 + 11) as u8).collect();
+    let mut first: Vec<u32> = (0..8).collect();
+    let mut second = first.clone();
+    shuffle_in_place(&mut first, &randomness).unwrap();
+    shuffle_in_place(&mut second, &randomness).unwrap();
+
+    assert_eq!(first, second);
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..8).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_coin_flip() {
+    // The top bit of the draw picks the side, and no draw is rejected
+    assert!(!coin_flip(&0u64.to_le_bytes()).unwrap());
+    assert!(coin_flip(&(1u64 << 63).to_le_bytes()).unwrap());
+    assert!(coin_flip(&[u8::MAX; 32]).unwrap());
+    assert_eq!(
+        lite_error(coin_flip(&[u8::MAX; 7])),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_roll_die() {
+    assert_eq!(roll_die(&(1u64 << 62).to_le_bytes(), 6).unwrap(), 2);
+    assert_eq!(roll_die(&u64::MAX.to_le_bytes(), 6).unwrap(), 6);
+    assert_eq!(roll_die(&u64::MAX.to_le_bytes(), u8::MAX).unwrap(), u8::MAX);
+    assert_eq!(roll_die(&0u64.to_le_bytes(), 1).unwrap(), 1);
+
+    // A zero draw is rejected for 6 sides and the next one is read
+    let bytes = [0u64.to_le_bytes(), u64::MAX.to_le_bytes()].concat();
+    assert_eq!(roll_die(&bytes, 6).unwrap(), 6);
+}
+
+#[test]
+fn test_roll_die_errors() {
+    assert_eq!(
+        lite_error(roll_die(&[u8::MAX; 8], 0)),
+        Some(LiteError::InvalidRange)
+    );
+    assert_eq!(
+        lite_error(roll_die(&[u8::MAX; 4], 6)),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+#[test]
+fn test_required_randomness_bytes_for_dice() {
+    assert_eq!(required_randomness_bytes_for_dice(0), 0);
+    assert_eq!(required_randomness_bytes_for_dice(1), 8);
+    assert_eq!(
+        required_randomness_bytes_for_dice(4),
+        usize::from(MAX_RANDOMNESS_BYTES)
+    );
+}
+
+#[test]
+fn test_roll_dice_one_draw_per_die() {
+    let bytes = [
+        (1u64 << 62).to_le_bytes(),
+        u64::MAX.to_le_bytes(),
+        ((1u64 << 63) | (1 << 40)).to_le_bytes(),
+    ]
+    .concat();
+
+    assert_eq!(roll_dice(&bytes, 6, 3).unwrap(), vec![2, 6, 4]);
+    assert_eq!(
+        roll_dice(&bytes, 6, 1).unwrap()[0],
+        roll_die(&bytes, 6).unwrap()
+    );
+    assert_eq!(roll_dice(&[], 6, 0).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn test_roll_dice_errors() {
+    assert_eq!(
+        lite_error(roll_dice(&[u8::MAX; 16], 0, 2)),
+        Some(LiteError::InvalidRange)
+    );
+    assert_eq!(
+        lite_error(roll_dice(&[u8::MAX; 15], 6, 2)),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+
+    // The rejected zero draw leaves no draw for the second die
+    let bytes = [0u64.to_le_bytes(), u64::MAX.to_le_bytes()].concat();
+    assert_eq!(
+        lite_error(roll_dice(&bytes, 6, 2)),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
+
+/// One draw whose top byte is `k`, landing at `k / 256` of the way through the range.
+fn draw_at(k: u8) -> [u8; 8] {
+    (u64::from(k) << 56).to_le_bytes()
+}
+
+#[test]
+fn test_weighted_choice_boundaries() {
+    let weights = [70, 25, 5];
+
+    // Draws 0, 69, 70, 94, 95, and 99 of 0..100
+    assert_eq!(weighted_choice(&draw_at(1), &weights).unwrap(), 0);
+    assert_eq!(weighted_choice(&draw_at(178), &weights).unwrap(), 0);
+    assert_eq!(weighted_choice(&draw_at(180), &weights).unwrap(), 1);
+    assert_eq!(weighted_choice(&draw_at(243), &weights).unwrap(), 1);
+    assert_eq!(weighted_choice(&draw_at(244), &weights).unwrap(), 2);
+    assert_eq!(weighted_choice(&[u8::MAX; 8], &weights).unwrap(), 2);
+}
+
+#[test]
+fn test_weighted_choice_skips_zero_weights() {
+    let weights = [0, 5, 0, 5, 0];
+
+    assert_eq!(weighted_choice(&draw_at(1), &weights).unwrap(), 1);
+    assert_eq!(weighted_choice(&draw_at(127), &weights).unwrap(), 1);
+    assert_eq!(weighted_choice(&draw_at(129), &weights).unwrap(), 3);
+    assert_eq!(weighted_choice(&[u8::MAX; 8], &weights).unwrap(), 3);
+    assert_eq!(weighted_choice(&[u8::MAX; 8], &[0, 0, 1]).unwrap(), 2);
+}
+
+#[test]
+fn test_weighted_choice_matches_random_in_range() {
+    let bytes: Vec<u8> = (1..=8).collect();
+    let weights = [1; 10];
+
+    assert_eq!(
+        weighted_choice(&bytes, &weights).unwrap() as u64,
+        random_in_range(&bytes, 0, 9).unwrap()
+    );
+    // A rejected zero draw moves on to the next one
+    let bytes = [[0; 8], draw_at(200)].concat();
+    assert_eq!(weighted_choice(&bytes, &[70, 25, 5]).unwrap(), 1);
+}
+
+#[test]
+fn test_weighted_choice_of_returns_the_item() {
+    let table = [("common", 70), ("rare", 25), ("legendary", 5)];
+
+    assert_eq!(*weighted_choice_of(&draw_at(1), &table).unwrap(), "common");
+    assert_eq!(*weighted_choice_of(&draw_at(180), &table).unwrap(), "rare");
+    assert_eq!(
+        *weighted_choice_of(&[u8::MAX; 8], &table).unwrap(),
+        "legendary"
+    );
+    assert_eq!(
+        lite_error(weighted_choice_of::<&str>(&[u8::MAX; 8], &[])),
+        Some(LiteError::InvalidWeights)
+    );
+}
+
+#[test]
+fn test_weighted_choice_errors() {
+    for weights in [&[][..], &[0, 0, 0][..]] {
+        assert_eq!(
+            lite_error(weighted_choice(&[u8::MAX; 8], weights)),
+            Some(LiteError::InvalidWeights)
+        );
+    }
+    assert_eq!(
+        lite_error(weighted_choice(&[u8::MAX; 8], &[u64::MAX, 1])),
+        Some(LiteError::InvalidWeights)
+    );
+    // The whole u64 range still sums
+    assert_eq!(
+        weighted_choice(&[u8::MAX; 8], &[u64::MAX - 1, 1]).unwrap(),
+        1
+    );
+    assert_eq!(
+        lite_error(weighted_choice(&[u8::MAX; 7], &[70, 25, 5])),
+        Some(LiteError::RandomnessLengthMismatch)
+    );
+}
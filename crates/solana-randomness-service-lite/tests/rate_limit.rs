@@ -0,0 +1,222 @@
+use solana_randomness_service_lite::client::mock::{ManualClock, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn builder(payer: &Keypair, limiter: &Arc<RateLimiter>) -> RequestBuilder {
+    RequestBuilder::new(
+        payer.pubkey(),
+        8,
+        Callback::new(
+            Pubkey::new_unique(),
+            vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into()],
+            vec![1, 2, 3, 4, 5, 6, 7, 8],
+        ),
+    )
+    .with_rate_limiter(limiter.clone())
+}
+
+/// Records the clock each time a transaction is broadcast.
+struct TimedRpc {
+    inner: MockRpc,
+    clock: Arc<ManualClock>,
+    sent_at: Mutex<Vec<Duration>>,
+}
+
+impl TimedRpc {
+    fn new(clock: Arc<ManualClock>) -> Self {
+        Self {
+            inner: MockRpc::default(),
+            clock,
+            sent_at: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn sent_at(&self) -> Vec<Duration> {
+        let mut sent_at = self.sent_at.lock().unwrap().clone();
+        sent_at.sort();
+        sent_at
+    }
+}
+
+#[async_trait::async_trait]
+impl RandomnessRpc for TimedRpc {
+    async fn get_latest_blockhash(&self) -> Result<Hash, LiteClientError> {
+        self.inner.get_latest_blockhash().await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, LiteClientError> {
+        self.inner.get_account(pubkey).await
+    }
+
+    async fn send_and_confirm_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, LiteClientError> {
+        self.sent_at.lock().unwrap().push(self.clock.now());
+        self.inner.send_and_confirm_transaction(transaction).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureRecord>, LiteClientError> {
+        self.inner
+            .get_signatures_for_address(address, before, limit)
+            .await
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionRecord>, LiteClientError> {
+        self.inner.get_transaction(signature).await
+    }
+}
+
+fn secs(secs: f64) -> Duration {
+    Duration::from_secs_f64(secs)
+}
+
+fn assert_close(actual: Duration, expected: Duration) {
+    let diff = actual.abs_diff(expected);
+    assert!(
+        diff < Duration::from_micros(1),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+/// Sends `count` requests from concurrent tasks sharing one limiter.
+async fn send_concurrently(count: usize, limiter: Arc<RateLimiter>, rpc: Arc<TimedRpc>) {
+    let payer = Arc::new(Keypair::new());
+    let local = tokio::task::LocalSet::new();
+    let tasks: Vec<_> = (0..count)
+        .map(|_| {
+            let builder = builder(&payer, &limiter);
+            let (rpc, payer) = (rpc.clone(), payer.clone());
+            local.spawn_local(async move { builder.send(rpc.as_ref(), payer.as_ref()).await })
+        })
+        .collect();
+
+    local
+        .run_until(async {
+            for task in tasks {
+                task.await.unwrap().unwrap();
+            }
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_burst_then_sustained_pacing() {
+    let clock = Arc::new(ManualClock::new());
+    let limiter = Arc::new(RateLimiter::with_clock(4.0, 2, clock.clone()));
+    let rpc = Arc::new(TimedRpc::new(clock.clone()));
+
+    send_concurrently(10, limiter, rpc.clone()).await;
+
+    // The burst goes out at once, then one send every 250ms
+    let sent_at = rpc.sent_at();
+    assert_eq!(sent_at.len(), 10);
+    assert_eq!(sent_at[0], Duration::ZERO);
+    assert_eq!(sent_at[1], Duration::ZERO);
+    for (i, sent_at) in sent_at.iter().enumerate().skip(2) {
+        assert_close(*sent_at, secs((i - 1) as f64 * 0.25));
+    }
+    assert_close(clock.now(), secs(2.0));
+}
+
+#[test]
+fn test_shared_across_threads() {
+    let clock = Arc::new(ManualClock::new());
+    let limiter = Arc::new(RateLimiter::with_clock(10.0, 5, clock.clone()));
+    let rpc = Arc::new(TimedRpc::new(clock.clone()));
+
+    std::thread::scope(|scope| {
+        for _ in 0..5 {
+            scope.spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap();
+                runtime.block_on(send_concurrently(5, limiter.clone(), rpc.clone()));
+            });
+        }
+    });
+
+    // However the threads interleave, the nth send waits for the nth token
+    let sent_at = rpc.sent_at();
+    assert_eq!(sent_at.len(), 25);
+    for (i, sent_at) in sent_at.iter().enumerate().skip(5) {
+        let earliest = secs((i - 4) as f64 * 0.1);
+        assert!(*sent_at + Duration::from_micros(1) >= earliest);
+    }
+    assert_close(clock.now(), secs(2.0));
+    assert_eq!(limiter.waiting(), 0);
+}
+
+#[tokio::test]
+async fn test_saturation() {
+    let clock = Arc::new(ManualClock::new());
+    let limiter = RateLimiter::with_clock(2.0, 4, clock.clone());
+    assert_eq!(limiter.saturation(), 0.0);
+
+    limiter.acquire().await;
+    limiter.acquire().await;
+    assert_eq!(limiter.saturation(), 0.5);
+
+    limiter.acquire().await;
+    limiter.acquire().await;
+    assert_eq!(limiter.saturation(), 1.0);
+    assert_eq!(clock.now(), Duration::ZERO);
+
+    // Refills at the sustained rate, up to the burst
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(limiter.saturation(), 0.75);
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(limiter.saturation(), 0.0);
+}
+
+#[tokio::test]
+async fn test_idle_bucket_does_not_exceed_burst() {
+    let clock = Arc::new(ManualClock::new());
+    let limiter = RateLimiter::with_clock(1.0, 2, clock.clone());
+    clock.advance(Duration::from_secs(3600));
+
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+    // Only the burst was available, the third waited a full interval
+    assert_close(clock.now(), secs(3601.0));
+}
+
+#[tokio::test]
+async fn test_dropped_wait_is_not_counted() {
+    // The manual clock never parks a wait, so this uses the tokio clock with a slow refill
+    let limiter = Arc::new(RateLimiter::new(0.001, 1));
+    limiter.acquire().await;
+    let waiter = tokio::spawn({
+        let limiter = limiter.clone();
+        async move { limiter.acquire().await }
+    });
+    while limiter.waiting() == 0 {
+        tokio::task::yield_now().await;
+    }
+    waiter.abort();
+    let _ = waiter.await;
+    assert_eq!(limiter.waiting(), 0);
+}
+
+#[test]
+#[should_panic(expected = "sustained rate must be positive")]
+fn test_rejects_zero_rate() {
+    RateLimiter::new(0.0, 1);
+}
@@ -0,0 +1,245 @@
+//! `SentRequest::spawn_auto_reclaim` waits for the request in the background and closes it once
+//! the oracle completed it with an error. The settle transaction is synthesized to match the
+//! logs of the service program's settle instruction.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+fn pending_request(user: Pubkey) -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        user,
+        request_slot: 1,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        ..Default::default()
+    }
+}
+
+fn failed_request(user: Pubkey) -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        is_completed: 1,
+        error_message: "callback failed".to_string(),
+        ..pending_request(user)
+    }
+}
+
+/// Settles the request: adds its settle transaction and closes the account.
+fn settle(rpc: &MockRpc, request: Pubkey, randomness: Vec<u8>) {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 1,
+        settled_slot: 3,
+        is_success: true,
+        randomness,
+    };
+    let mut data = hash(b"event:SimpleRandomnessV1SettledEvent").to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    rpc.add_transaction(TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: 3,
+        failed: false,
+        account_keys: vec![request, ID],
+        log_messages: vec![format!(
+            "Program data: {}",
+            base64::engine::general_purpose::STANDARD.encode(data)
+        )],
+        token_balances: vec![],
+    });
+    rpc.remove_account(&request);
+}
+
+/// Spawns the task and returns its handle with a receiver for its outcome.
+fn spawn(
+    rpc: &Arc<MockRpc>,
+    signer: &Arc<Keypair>,
+    request: Pubkey,
+    deadline: Duration,
+) -> (ReclaimHandle, oneshot::Receiver<ReclaimOutcome>) {
+    let sent = SentRequest {
+        request,
+        signature: Signature::new_unique(),
+    };
+    let (tx, rx) = oneshot::channel();
+    let handle = sent.spawn_auto_reclaim(
+        rpc.clone(),
+        signer.clone(),
+        deadline,
+        POLL_INTERVAL,
+        move |outcome| {
+            let _ = tx.send(outcome);
+        },
+    );
+    (handle, rx)
+}
+
+#[tokio::test]
+async fn test_reports_fulfillment_before_deadline() {
+    let signer = Arc::new(Keypair::new());
+    let request = Pubkey::new_unique();
+    let rpc = Arc::new(MockRpc::default());
+    rpc.set_account(request, request_account(&pending_request(signer.pubkey())));
+
+    let (handle, outcome) = spawn(&rpc, &signer, request, Duration::from_secs(5));
+    tokio::time::sleep(POLL_INTERVAL * 3).await;
+    settle(&rpc, request, vec![9; 8]);
+
+    match outcome.await.unwrap() {
+        ReclaimOutcome::Fulfilled(outcome) => {
+            assert_eq!(outcome.randomness(), Some(&[9; 8][..]))
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+    handle.join().await.unwrap();
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_closes_failed_request() {
+    let signer = Arc::new(Keypair::new());
+    let request = Pubkey::new_unique();
+    let rpc = Arc::new(MockRpc::default());
+    rpc.set_account(request, request_account(&failed_request(signer.pubkey())));
+
+    let (handle, outcome) = spawn(&rpc, &signer, request, Duration::from_secs(5));
+
+    let signature = match outcome.await.unwrap() {
+        ReclaimOutcome::Reclaimed { signature, request } => {
+            assert_eq!(request.error_message, "callback failed");
+            signature
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    };
+    handle.join().await.unwrap();
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].signatures[0], signature);
+    assert_eq!(sent[0].message.account_keys[0], signer.pubkey());
+    let failed = failed_request(signer.pubkey());
+    assert_eq!(
+        sent[0].message.instructions[0].data,
+        close_request_instruction(&request, &failed).data
+    );
+}
+
+#[tokio::test]
+async fn test_settlement_during_reclaim_is_not_a_failure() {
+    let signer = Arc::new(Keypair::new());
+    let request = Pubkey::new_unique();
+    let rpc = Arc::new(MockRpc::default());
+    rpc.set_account(request, request_account(&failed_request(signer.pubkey())));
+    // The close keeps failing while the settle transaction lands
+    rpc.set_fail_sends(true);
+
+    let (_handle, outcome) = spawn(&rpc, &signer, request, Duration::from_secs(5));
+    tokio::time::sleep(RECLAIM_RETRY_DELAY / 2).await;
+    settle(&rpc, request, vec![4; 8]);
+
+    match outcome.await.unwrap() {
+        ReclaimOutcome::FulfilledDuringReclaim(outcome) => {
+            assert_eq!(outcome.randomness(), Some(&[4; 8][..]))
+        }
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[tokio::test]
+async fn test_leaves_pending_request_open() {
+    let signer = Arc::new(Keypair::new());
+    let request = Pubkey::new_unique();
+    let rpc = Arc::new(MockRpc::default());
+    rpc.set_account(request, request_account(&pending_request(signer.pubkey())));
+
+    let (_handle, outcome) = spawn(&rpc, &signer, request, POLL_INTERVAL * 5);
+
+    match outcome.await.unwrap() {
+        ReclaimOutcome::StillPending(pending) => assert_eq!(pending.request_slot, 1),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_cancel_reports_cancelled() {
+    let signer = Arc::new(Keypair::new());
+    let request = Pubkey::new_unique();
+    let rpc = Arc::new(MockRpc::default());
+    rpc.set_account(request, request_account(&pending_request(signer.pubkey())));
+
+    let (mut handle, outcome) = spawn(&rpc, &signer, request, Duration::from_secs(5));
+    tokio::time::sleep(POLL_INTERVAL * 3).await;
+    assert!(!handle.is_finished());
+    handle.cancel();
+
+    assert!(matches!(outcome.await.unwrap(), ReclaimOutcome::Cancelled));
+    handle.join().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sweep_closes_only_the_users_failed_requests() {
+    let signer = Keypair::new();
+    let rpc = MockRpc::default();
+    let failed = Pubkey::new_unique();
+    rpc.set_account(failed, request_account(&failed_request(signer.pubkey())));
+    rpc.set_account(
+        Pubkey::new_unique(),
+        request_account(&pending_request(signer.pubkey())),
+    );
+    rpc.set_account(
+        Pubkey::new_unique(),
+        request_account(&failed_request(Pubkey::new_unique())),
+    );
+
+    let swept = sweep_failed_requests(&rpc, &signer, &ID, &signer.pubkey())
+        .await
+        .unwrap();
+
+    assert_eq!(swept.len(), 1);
+    assert_eq!(swept[0].0, failed);
+    assert!(matches!(swept[0].1, ReclaimOutcome::Reclaimed { .. }));
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(
+        sent[0].message.instructions[0].data,
+        close_request_instruction(&failed, &failed_request(signer.pubkey())).data
+    );
+}
+
+#[tokio::test]
+async fn test_sweep_reports_failed_close() {
+    let signer = Keypair::new();
+    let rpc = MockRpc::default();
+    let failed = Pubkey::new_unique();
+    rpc.set_account(failed, request_account(&failed_request(signer.pubkey())));
+    rpc.set_fail_sends(true);
+
+    let swept = sweep_failed_requests(&rpc, &signer, &ID, &signer.pubkey())
+        .await
+        .unwrap();
+
+    assert_eq!(swept.len(), 1);
+    assert!(matches!(swept[0].1, ReclaimOutcome::Failed(_)));
+}
@@ -0,0 +1,369 @@
+//! The transactions below are synthesized to match the logs and token balances of the service
+//! program's request, settle, and callback error instructions.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_program::pubkey;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+const COST: u64 = 10_000;
+
+#[derive(BorshSerialize)]
+struct RequestedEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    callback: Callback,
+    compute_units: u32,
+    priority_fee_micro_lamports: u64,
+    num_bytes: u8,
+}
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct CallbackErrorEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    error_message: String,
+}
+
+fn program_data(name: &str, event: impl BorshSerialize) -> String {
+    let mut data = hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+fn escrow(request: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            request.as_ref(),
+            TOKEN_PROGRAM_ID.as_ref(),
+            RANDOMNESS_SERVICE_REWARD_MINT.as_ref(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+fn balance(account: Pubkey, owner: Pubkey, pre: u64, post: u64) -> TokenBalanceRecord {
+    TokenBalanceRecord {
+        account,
+        mint: RANDOMNESS_SERVICE_REWARD_MINT,
+        owner: Some(owner),
+        pre,
+        post,
+    }
+}
+
+struct Fixture {
+    rpc: MockRpc,
+    payer: Pubkey,
+    callback_pid: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self {
+            rpc: MockRpc::default(),
+            payer: Pubkey::new_unique(),
+            callback_pid: Pubkey::new_unique(),
+        }
+    }
+
+    fn request(&self, slot: u64, paid: u64) -> (Pubkey, Signature) {
+        let request = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+        let event = RequestedEvent {
+            callback_pid: self.callback_pid,
+            user: self.payer,
+            request,
+            request_slot: slot,
+            callback: Callback::default(),
+            compute_units: 0,
+            priority_fee_micro_lamports: 0,
+            num_bytes: 8,
+        };
+
+        self.rpc.add_transaction(TransactionRecord {
+            signature,
+            slot,
+            failed: false,
+            account_keys: vec![self.payer, request, escrow(&request), ID],
+            log_messages: vec![
+                format!("Program {} invoke [1]", ID),
+                program_data("SimpleRandomnessV1RequestedEvent", event),
+                format!("Program {} success", ID),
+            ],
+            token_balances: vec![balance(escrow(&request), request, 0, paid)],
+        });
+
+        (request, signature)
+    }
+
+    fn settle(&self, request: Pubkey, slot: u64, paid: u64, received: u64) -> Signature {
+        let signature = Signature::new_unique();
+        let event = SettledEvent {
+            callback_pid: self.callback_pid,
+            user: self.payer,
+            request,
+            request_slot: slot - 2,
+            settled_slot: slot,
+            is_success: true,
+            randomness: vec![7; 8],
+        };
+
+        self.rpc.add_transaction(TransactionRecord {
+            signature,
+            slot,
+            failed: false,
+            account_keys: vec![
+                request,
+                escrow(&request),
+                RANDOMNESS_SERVICE_REWARD_WALLET,
+                ID,
+            ],
+            log_messages: vec![program_data("SimpleRandomnessV1SettledEvent", event)],
+            token_balances: vec![
+                balance(escrow(&request), request, paid, 0),
+                balance(
+                    RANDOMNESS_SERVICE_REWARD_WALLET,
+                    RANDOMNESS_SERVICE_STATE,
+                    1_000_000,
+                    1_000_000 + received,
+                ),
+            ],
+        });
+
+        signature
+    }
+}
+
+#[tokio::test]
+async fn test_matched_settled_and_errored_requests() {
+    let fixture = Fixture::new();
+    let (settled, _) = fixture.request(100, COST);
+    let settled_signature = fixture.settle(settled, 102, COST, COST);
+
+    let (errored, _) = fixture.request(101, COST);
+    let errored_signature = Signature::new_unique();
+    fixture.rpc.add_transaction(TransactionRecord {
+        signature: errored_signature,
+        slot: 103,
+        failed: false,
+        account_keys: vec![errored, escrow(&errored), RANDOMNESS_SERVICE_REWARD_WALLET],
+        log_messages: vec![program_data(
+            "SimpleRandomnessV1CallbackErrorEvent",
+            CallbackErrorEvent {
+                callback_pid: fixture.callback_pid,
+                user: fixture.payer,
+                request: errored,
+                request_slot: 101,
+                settled_slot: 103,
+                error_message: "Callback simulation failed".to_string(),
+            },
+        )],
+        token_balances: vec![
+            balance(escrow(&errored), errored, COST, 0),
+            balance(
+                RANDOMNESS_SERVICE_REWARD_WALLET,
+                RANDOMNESS_SERVICE_STATE,
+                0,
+                COST,
+            ),
+        ],
+    });
+
+    let reconciliation = reconcile_payments(
+        &fixture.rpc,
+        fixture.payer,
+        SlotRange { start: 0, end: 200 },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(reconciliation.payments.len(), 2);
+    assert_eq!(reconciliation.payments[0].request, settled);
+    assert_eq!(reconciliation.payments[0].status, PaymentStatus::Matched);
+    assert_eq!(
+        reconciliation.payments[0].resolution_signature,
+        Some(settled_signature)
+    );
+    assert_eq!(reconciliation.payments[1].request, errored);
+    assert_eq!(reconciliation.payments[1].status, PaymentStatus::Matched);
+    assert_eq!(
+        reconciliation.payments[1].resolution_signature,
+        Some(errored_signature)
+    );
+    assert_eq!(reconciliation.total_paid(), 2 * COST);
+    assert_eq!(reconciliation.total_received(), 2 * COST);
+    assert_eq!(reconciliation.discrepancies().count(), 0);
+}
+
+#[tokio::test]
+async fn test_refunded_request() {
+    let fixture = Fixture::new();
+    let (request, _) = fixture.request(100, COST);
+    let refund = Signature::new_unique();
+    fixture.rpc.add_transaction(TransactionRecord {
+        signature: refund,
+        slot: 150,
+        failed: false,
+        account_keys: vec![fixture.payer, request, escrow(&request)],
+        log_messages: vec![],
+        token_balances: vec![balance(escrow(&request), request, COST, 0)],
+    });
+
+    let reconciliation = reconcile_payments(
+        &fixture.rpc,
+        fixture.payer,
+        SlotRange { start: 0, end: 200 },
+    )
+    .await
+    .unwrap();
+
+    let payment = &reconciliation.payments[0];
+    assert_eq!(payment.status, PaymentStatus::Refunded);
+    assert_eq!(payment.resolution_signature, Some(refund));
+    assert_eq!(payment.reward_received, 0);
+    assert!(!payment.is_discrepancy());
+}
+
+#[tokio::test]
+async fn test_orphaned_and_pending_requests() {
+    let fixture = Fixture::new();
+    // Closed without a settle or refund
+    let (orphaned, _) = fixture.request(100, COST);
+    // Still waiting for the oracle
+    let (pending, _) = fixture.request(101, COST);
+    fixture.rpc.set_account(
+        pending,
+        request_account(&SimpleRandomnessV1Account {
+            user: fixture.payer,
+            request_slot: 101,
+            num_bytes: 8,
+            ..Default::default()
+        }),
+    );
+    // Settled for less than was paid
+    let (short, _) = fixture.request(102, COST);
+    fixture.settle(short, 104, COST, COST / 2);
+
+    let reconciliation = reconcile_payments(
+        &fixture.rpc,
+        fixture.payer,
+        SlotRange { start: 0, end: 200 },
+    )
+    .await
+    .unwrap();
+
+    let statuses: Vec<_> = reconciliation
+        .payments
+        .iter()
+        .map(|p| (p.request, p.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (orphaned, PaymentStatus::Unmatched),
+            (pending, PaymentStatus::Pending),
+            (short, PaymentStatus::AmountMismatch),
+        ]
+    );
+
+    let discrepancies: Vec<_> = reconciliation.discrepancies().map(|p| p.request).collect();
+    assert_eq!(discrepancies, vec![orphaned, short]);
+}
+
+#[tokio::test]
+async fn test_slot_range_and_other_payers() {
+    let fixture = Fixture::new();
+    fixture.request(50, COST);
+    let (in_range, _) = fixture.request(100, COST);
+    fixture.request(250, COST);
+
+    // A request paid for by someone else that the payer's key appears in
+    let other = Fixture {
+        rpc: MockRpc::default(),
+        payer: Pubkey::new_unique(),
+        callback_pid: fixture.callback_pid,
+    };
+    let (_, other_signature) = other.request(120, COST);
+    let mut record = other
+        .rpc
+        .get_transaction(&other_signature)
+        .await
+        .unwrap()
+        .unwrap();
+    record.account_keys.push(fixture.payer);
+    fixture.rpc.add_transaction(record);
+
+    let reconciliation = reconcile_payments(
+        &fixture.rpc,
+        fixture.payer,
+        SlotRange {
+            start: 100,
+            end: 200,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(reconciliation.payments.len(), 1);
+    assert_eq!(reconciliation.payments[0].request, in_range);
+}
+
+#[tokio::test]
+async fn test_reconciliation_serializes_to_json() {
+    let fixture = Fixture::new();
+    let (request, request_signature) = fixture.request(100, COST);
+    let settled = fixture.settle(request, 102, COST, COST);
+
+    let reconciliation = reconcile_payments(
+        &fixture.rpc,
+        fixture.payer,
+        SlotRange { start: 0, end: 200 },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&reconciliation).unwrap(),
+        serde_json::json!({
+            "payer": fixture.payer.to_string(),
+            "range": { "start": 0, "end": 200 },
+            "payments": [{
+                "request": request.to_string(),
+                "request_signature": request_signature.to_string(),
+                "request_slot": 100,
+                "paid": COST,
+                "resolution_signature": settled.to_string(),
+                "reward_received": COST,
+                "status": "matched",
+            }],
+        })
+    );
+}
@@ -0,0 +1,219 @@
+//! Requests that pay a referral fee in the same transaction, built by the client and run
+//! against the harness.
+
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::*;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([8; 32]);
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+const REFERRAL_FEE: u64 = 25_000;
+
+fn process_consumer(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    Ok(())
+}
+
+fn associated_token_account(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+    )
+    .0
+}
+
+fn builder(payer: Pubkey) -> RequestBuilder {
+    RequestBuilder::new(payer, 8, callback_into(CONSUMER_ID)).cost_per_byte(100)
+}
+
+#[test]
+fn test_transfer_follows_request_instruction() {
+    let payer = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let builder = builder(payer)
+        .with_tag(RequestTag::new("resold").unwrap())
+        .with_referral_fee(recipient, REFERRAL_FEE);
+
+    let instructions = builder.instructions(&request).unwrap();
+
+    assert_eq!(instructions.len(), 3);
+    assert_eq!(instructions[0], builder.instruction(&request).unwrap());
+    let transfer = &instructions[1];
+    assert_eq!(transfer.program_id, solana_program::system_program::ID);
+    assert_eq!(
+        transfer.accounts,
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(recipient, false),
+        ]
+    );
+    assert_eq!(
+        transfer.data,
+        [&2u32.to_le_bytes()[..], &REFERRAL_FEE.to_le_bytes()].concat()
+    );
+    // The tag memo stays last
+    assert_eq!(
+        instructions[2],
+        RequestTag::new("resold").unwrap().instruction()
+    );
+}
+
+#[test]
+fn test_custom_mint_pays_with_token_transfer() {
+    let payer = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let instructions = builder(payer)
+        .mint(mint)
+        .with_referral_fee(recipient, REFERRAL_FEE)
+        .instructions(&Pubkey::new_unique())
+        .unwrap();
+
+    let transfer = &instructions[1];
+    assert_eq!(transfer.program_id, TOKEN_PROGRAM_ID);
+    assert_eq!(
+        transfer.accounts,
+        vec![
+            AccountMeta::new(associated_token_account(&payer, &mint), false),
+            AccountMeta::new(associated_token_account(&recipient, &mint), false),
+            AccountMeta::new_readonly(payer, true),
+        ]
+    );
+    assert_eq!(
+        transfer.data,
+        [&[3][..], &REFERRAL_FEE.to_le_bytes()].concat()
+    );
+}
+
+#[test]
+fn test_funding_counts_the_fee_outside_the_service_cost() {
+    let payer = Pubkey::new_unique();
+    let without = builder(payer).funding().unwrap();
+    let with = builder(payer)
+        .with_referral_fee(Pubkey::new_unique(), REFERRAL_FEE)
+        .funding()
+        .unwrap();
+
+    assert_eq!(without.referral_fee, 0);
+    assert_eq!(with.referral_fee, REFERRAL_FEE);
+    assert_eq!(with.service_cost(), without.service_cost());
+    assert_eq!(with.total(), without.total() + REFERRAL_FEE);
+
+    let display = with.display(&MintInfo::native()).to_string();
+    assert!(display.contains("Referral fee    0.000025 SOL"));
+    assert!(!without
+        .display(&MintInfo::native())
+        .to_string()
+        .contains("Referral fee"));
+
+    // Breakdowns recorded before the fee existed still read back
+    let mut json = serde_json::to_value(without).unwrap();
+    json.as_object_mut().unwrap().remove("referral_fee");
+    assert_eq!(
+        serde_json::from_value::<FundingBreakdown>(json).unwrap(),
+        without
+    );
+}
+
+#[test]
+fn test_cost_ceiling_includes_the_fee() {
+    let payer = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let cost = builder(payer).funding().unwrap().total();
+
+    // The ceiling covers the request alone, but not with the fee
+    builder(payer)
+        .max_cost(cost)
+        .instructions(&request)
+        .unwrap();
+    let result = builder(payer)
+        .max_cost(cost)
+        .with_referral_fee(Pubkey::new_unique(), REFERRAL_FEE)
+        .instructions(&request);
+
+    assert!(matches!(
+        result,
+        Err(LiteClientError::CostCeilingExceeded { max_cost, cost: c })
+            if max_cost == cost && c == cost + REFERRAL_FEE
+    ));
+}
+
+#[test]
+fn test_intent_ceiling_carries_over_to_the_builder() {
+    let payer = Pubkey::new_unique();
+    let intent = RequestIntent::new(8, Callback::default());
+    let cost = RequestIntent::new(8, Callback::default())
+        .into_builder(payer)
+        .unwrap()
+        .funding()
+        .unwrap()
+        .total();
+
+    let result = intent
+        .max_cost(cost)
+        .into_builder(payer)
+        .unwrap()
+        .with_referral_fee(Pubkey::new_unique(), 1)
+        .build(solana_sdk::hash::Hash::new_unique());
+
+    assert!(matches!(
+        result,
+        Err(LiteClientError::CostCeilingExceeded { .. })
+    ));
+}
+
+#[test]
+fn test_fee_reaches_recipient_in_harness() {
+    let mut harness = OracleHarness::new(CONSUMER_ID, process_consumer);
+    harness.set_cost_per_byte(100);
+    harness.add_service_accounts();
+    let payer = harness.payer();
+    harness.airdrop(&payer, 1_000_000_000);
+    let recipient = Pubkey::new_unique();
+    let request = Pubkey::new_unique();
+    let builder = builder(payer).with_referral_fee(recipient, REFERRAL_FEE);
+
+    let created = harness
+        .process_transaction(&builder.instructions(&request).unwrap())
+        .unwrap();
+
+    assert_eq!(created, vec![request]);
+    assert_eq!(harness.account(&recipient).unwrap().lamports, REFERRAL_FEE);
+    assert_eq!(
+        1_000_000_000 - harness.account(&payer).unwrap().lamports,
+        builder.funding().unwrap().total()
+    );
+}
+
+#[test]
+fn test_unaffordable_fee_fails_the_request() {
+    let mut harness = OracleHarness::new(CONSUMER_ID, process_consumer);
+    harness.add_service_accounts();
+    let payer = harness.payer();
+    let builder = builder(payer).cost_per_byte(0);
+    // Enough for the request, not the fee
+    harness.airdrop(&payer, builder.funding().unwrap().total());
+    let request = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+
+    let result = harness.process_transaction(
+        &builder
+            .with_referral_fee(recipient, REFERRAL_FEE)
+            .instructions(&request)
+            .unwrap(),
+    );
+
+    assert_eq!(result, Err(ProgramError::Custom(1)));
+    assert!(harness.request(&request).is_none());
+    assert!(harness.account(&recipient).is_none());
+}
@@ -0,0 +1,228 @@
+//! Closes and re-requests requests the oracle gave up on, against the mock RPC.
+
+mod common;
+
+use common::*;
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn callback(ix_data_len: usize) -> Callback {
+    callback_with_ix_data(vec![1; ix_data_len])
+}
+
+/// A request the oracle completed with an error.
+fn errored(callback: Callback) -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        is_completed: 1,
+        num_bytes: 16,
+        user: Pubkey::new_unique(),
+        escrow: Pubkey::new_unique(),
+        request_slot: 1_000,
+        callback,
+        compute_units: 200_000,
+        priority_fee_micro_lamports: 100,
+        error_message: "Callback simulation failed".to_string(),
+    }
+}
+
+fn add_request(rpc: &MockRpc, request: &SimpleRandomnessV1Account) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    rpc.set_account(pubkey, request_account(request));
+    pubkey
+}
+
+#[test]
+fn test_fee_bump_raises_price() {
+    assert_eq!(FeeBump::default().apply(100), 100);
+    assert_eq!(FeeBump::percent(50).apply(100), 150);
+    assert_eq!(FeeBump::micro_lamports(25).apply(100), 125);
+    assert_eq!(
+        FeeBump {
+            percent: 100,
+            micro_lamports: 1,
+        }
+        .apply(100),
+        201
+    );
+    assert_eq!(
+        FeeBump::percent(u64::MAX).apply(u64::MAX),
+        TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE
+    );
+}
+
+#[tokio::test]
+async fn test_closes_and_requests_in_one_transaction() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let account = errored(callback(8));
+    let old = add_request(&rpc, &account);
+
+    let outcomes = refresh_expired(&rpc, &payer, &[old], FeeBump::percent(50)).await;
+
+    let [RefreshOutcome::Refreshed {
+        old: refreshed,
+        new,
+        signatures,
+    }] = &outcomes[..]
+    else {
+        panic!("unexpected outcomes {:?}", outcomes);
+    };
+    assert_eq!(*refreshed, old);
+    assert_eq!(outcomes[0].replacement(), Some(*new));
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(signatures, &vec![sent[0].signatures[0]]);
+    let message = &sent[0].message;
+    assert_eq!(message.instructions.len(), 2);
+
+    let close = &message.instructions[0];
+    assert_eq!(
+        close.data,
+        anchor_sighash("simple_randomness_v1_callback_close")
+    );
+    assert_eq!(
+        message.account_keys[close.accounts[0] as usize],
+        account.user
+    );
+    assert_eq!(message.account_keys[close.accounts[1] as usize], old);
+    assert_eq!(
+        message.account_keys[close.accounts[2] as usize],
+        account.escrow
+    );
+
+    // The same bytes and callback, at the bumped price
+    let request = &message.instructions[1];
+    assert_eq!(message.account_keys[request.accounts[0] as usize], *new);
+    assert_eq!(
+        message.account_keys[request.accounts[4] as usize],
+        payer.pubkey()
+    );
+    assert_eq!(
+        request.data,
+        SimpleRandomnessV1Request::data(
+            account.num_bytes,
+            &account.callback,
+            Some(&TransactionOptions {
+                compute_units: Some(200_000),
+                compute_unit_price: Some(150),
+            }),
+        )
+        .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_splits_transaction_too_large_for_both() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let old = add_request(&rpc, &errored(callback(620)));
+
+    let outcomes = refresh_expired(&rpc, &payer, &[old], FeeBump::default()).await;
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 2);
+    assert!(matches!(
+        &outcomes[..],
+        [RefreshOutcome::Refreshed { signatures, .. }] if signatures.len() == 2
+    ));
+    // The close goes first, on its own
+    assert_eq!(sent[0].message.instructions.len(), 1);
+    assert_eq!(
+        sent[0].message.instructions[0].data,
+        anchor_sighash("simple_randomness_v1_callback_close")
+    );
+    assert_eq!(sent[1].message.instructions.len(), 1);
+}
+
+#[tokio::test]
+async fn test_skips_settled_and_pending_requests() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let settled = Pubkey::new_unique();
+    let pending = add_request(
+        &rpc,
+        &SimpleRandomnessV1Account {
+            is_completed: 0,
+            error_message: String::new(),
+            ..errored(callback(8))
+        },
+    );
+
+    let outcomes = refresh_expired(&rpc, &payer, &[settled, pending], FeeBump::default()).await;
+
+    assert!(matches!(
+        outcomes[0],
+        RefreshOutcome::Settled { request } if request == settled
+    ));
+    assert!(matches!(
+        outcomes[1],
+        RefreshOutcome::StillPending { request } if request == pending
+    ));
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_reports_callbacks_failing_validation() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    // Stored before callbacks had to list the state account as a signer
+    let old = add_request(
+        &rpc,
+        &errored(Callback::new(
+            Pubkey::new_unique(),
+            vec![AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, false).into()],
+            vec![1; 8],
+        )),
+    );
+
+    let outcomes = refresh_expired(&rpc, &payer, &[old], FeeBump::default()).await;
+
+    assert!(matches!(
+        &outcomes[..],
+        [RefreshOutcome::InvalidCallback { request, error }]
+            if *request == old
+                && *error == ProgramError::from(LiteError::CallbackStateNotSigner)
+    ));
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_outcomes_follow_request_order() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let first = add_request(&rpc, &errored(callback(8)));
+    let settled = Pubkey::new_unique();
+    let last = add_request(&rpc, &errored(callback(8)));
+
+    let outcomes = refresh_expired(&rpc, &payer, &[first, settled, last], FeeBump::default()).await;
+
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(RefreshOutcome::request)
+            .collect::<Vec<_>>(),
+        vec![first, settled, last]
+    );
+    assert!(outcomes[0].replacement().is_some());
+    assert!(outcomes[1].replacement().is_none());
+    assert!(outcomes[2].replacement().is_some());
+    assert_ne!(outcomes[0].replacement(), outcomes[2].replacement());
+}
+
+#[tokio::test]
+async fn test_reports_failed_sends() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let old = add_request(&rpc, &errored(callback(8)));
+    rpc.set_fail_sends(true);
+
+    let outcomes = refresh_expired(&rpc, &payer, &[old], FeeBump::default()).await;
+
+    assert!(matches!(
+        &outcomes[..],
+        [RefreshOutcome::Failed { request, error: LiteClientError::Rpc(_) }] if *request == old
+    ));
+}
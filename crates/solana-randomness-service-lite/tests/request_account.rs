@@ -0,0 +1,108 @@
+use borsh::BorshSerialize;
+use solana_program::pubkey;
+use solana_randomness_service_lite::*;
+
+/// A failed request, synthesized from the service program's account layout: a request for 8
+/// bytes from the example consumer program whose callback failed off-chain, with the oracle's
+/// error message written by `simple_randomness_v1_callback_error`.
+///
+/// Not a capture of a mainnet account yet. To replace it, dump a request the oracle failed with
+/// `solana account <REQUEST> -um --output-file tests/fixtures/failed_request.bin`
+/// and note the request's address and the slot it was read at here.
+const FAILED_REQUEST: &[u8] = include_bytes!("fixtures/failed_request.bin");
+
+const CONSUMER_PROGRAM_ID: Pubkey = pubkey!("39hMZgeiesFXMRFt8svuKVsdCW5geiYueSRx7dxhXN4f");
+
+fn callback(ix_data_len: usize, num_accounts: usize) -> Callback {
+    Callback::new(
+        CONSUMER_PROGRAM_ID,
+        (0..num_accounts)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false).into())
+            .collect(),
+        vec![7; ix_data_len],
+    )
+}
+
+fn serialize(request: &SimpleRandomnessV1Account) -> Vec<u8> {
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    data.extend(request.try_to_vec().unwrap());
+    data
+}
+
+#[test]
+fn test_failed_request_fixture() {
+    let request = SimpleRandomnessV1Account::try_deserialize(&mut &FAILED_REQUEST[..]).unwrap();
+
+    assert_eq!(request.is_completed, 1);
+    assert_eq!(request.num_bytes, 8);
+    assert_eq!(request.callback.program_id, CONSUMER_PROGRAM_ID);
+    assert_eq!(request.callback.accounts.len(), 2);
+    assert_eq!(
+        request.callback.accounts[0].pubkey,
+        RANDOMNESS_SERVICE_STATE
+    );
+    assert!(request.callback.accounts[0].is_signer);
+    assert_eq!(
+        request.callback.ix_data,
+        vec![190, 217, 49, 162, 99, 26, 73, 234]
+    );
+    assert_eq!(request.compute_units, 1_000_000);
+    assert_eq!(request.priority_fee_micro_lamports, 100);
+    assert!(request
+        .error_message
+        .starts_with("Callback simulation failed"));
+
+    assert_eq!(
+        request.expected_request_account_size(),
+        FAILED_REQUEST.len()
+    );
+    assert!(serialize(&request).len() < FAILED_REQUEST.len());
+    assert!(FAILED_REQUEST[serialize(&request).len()..]
+        .iter()
+        .all(|b| *b == 0));
+}
+
+#[test]
+fn test_short_buffer_rejected() {
+    for len in 0..8 {
+        assert_eq!(
+            SimpleRandomnessV1Account::try_deserialize_unchecked(&mut &FAILED_REQUEST[..len])
+                .unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+}
+
+#[test]
+fn test_max_error_message_fits_in_space() {
+    for (ix_data_len, num_accounts) in [(0, 0), (8, 1), (8, 2), (1024, 32)] {
+        let request = SimpleRandomnessV1Account {
+            is_completed: 1,
+            callback: callback(ix_data_len, num_accounts),
+            error_message: "x".repeat(SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN),
+            ..Default::default()
+        };
+        let space = request.expected_request_account_size();
+        let data = serialize(&request);
+        assert!(data.len() <= space);
+
+        let mut account = data.clone();
+        account.resize(space, 0);
+        let decoded = SimpleRandomnessV1Account::try_deserialize(&mut &account[..]).unwrap();
+        assert_eq!(decoded.error_message, request.error_message);
+    }
+}
+
+#[test]
+fn test_error_message_overflow_rejected() {
+    let request = SimpleRandomnessV1Account {
+        callback: callback(8, 2),
+        error_message: "x".repeat(SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN + 1),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        SimpleRandomnessV1Account::try_deserialize(&mut &serialize(&request)[..]).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+}
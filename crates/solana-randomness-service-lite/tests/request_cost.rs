@@ -0,0 +1,149 @@
+//! `estimate_request_cost` follows the service's fee formula, and `fetch_request_cost` reads the
+//! service's price from its state account.
+
+mod common;
+
+use common::*;
+use solana_program::rent::Rent;
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+
+/// The service's state account, charging 10,000 lamports per byte. See `tests/service_state.rs`.
+const SERVICE_STATE: &[u8] = include_bytes!("fixtures/service_state.bin");
+const COST_PER_BYTE: u64 = 10_000;
+
+/// The rent-exempt minimum of a 165 byte SPL token account, which every cluster charges for a
+/// wrapped SOL account.
+const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+fn options() -> Option<TransactionOptions> {
+    Some(TransactionOptions {
+        compute_units: Some(200_000),
+        compute_unit_price: Some(10_000),
+    })
+}
+
+fn rpc() -> MockRpc {
+    let rpc = MockRpc::default();
+    rpc.set_account(
+        RANDOMNESS_SERVICE_STATE,
+        Account {
+            lamports: 1,
+            data: SERVICE_STATE.to_vec(),
+            owner: ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    rpc
+}
+
+#[test]
+fn test_estimate_follows_service_formula() {
+    // The service's `State::request_cost`: 10,000 lamports, `cost_per_byte` for each byte, and
+    // the priority fee in micro-lamports floored to whole lamports, computed by hand
+    let cases = [
+        // 200,000 compute units at 10,000 micro-lamports, 2,000 lamports
+        (8, COST_PER_BYTE, options(), 92_000),
+        // 600,000 micro-lamports, floored to 0
+        (
+            32,
+            COST_PER_BYTE,
+            Some(TransactionOptions {
+                compute_units: Some(200_000),
+                compute_unit_price: Some(3),
+            }),
+            330_000,
+        ),
+        // The defaults, 1,000,000 compute units at 1 micro-lamport, 1 lamport
+        (255, COST_PER_BYTE, None, 2_560_001),
+        // 1,400,001,400,000 micro-lamports, floored to 1,400,001
+        (
+            1,
+            0,
+            Some(TransactionOptions {
+                compute_units: Some(1_400_000),
+                compute_unit_price: Some(1_000_001),
+            }),
+            1_410_001,
+        ),
+    ];
+
+    for (num_bytes, cost_per_byte, options, expected) in cases {
+        assert_eq!(
+            estimate_request_cost(num_bytes, cost_per_byte, &options),
+            Ok(expected)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_breakdown_uses_state_price() {
+    let callback = callback_into(Pubkey::new_from_array([7; 32]));
+    let estimate = fetch_request_cost(&rpc(), &RANDOMNESS_SERVICE_STATE, 8, &callback, &options())
+        .await
+        .unwrap();
+
+    assert_eq!(estimate.base_fee, 10_000);
+    assert_eq!(estimate.randomness_fee, 80_000);
+    // 200,000 compute units at 10,000 micro-lamports each
+    assert_eq!(estimate.priority_fee, 2_000);
+    assert_eq!(estimate.service_cost(), 92_000);
+    assert_eq!(estimate.escrow_rent, TOKEN_ACCOUNT_RENT);
+    assert_eq!(
+        estimate.request_rent,
+        Rent::default().minimum_balance(SimpleRandomnessV1Account::space(&callback))
+    );
+    assert_eq!(
+        estimate.total(),
+        92_000 + estimate.request_rent + TOKEN_ACCOUNT_RENT
+    );
+}
+
+#[test]
+fn test_randomness_fee_overflow() {
+    assert_eq!(
+        FundingBreakdown::new(2, &Callback::default(), &None, u64::MAX, &Rent::default()),
+        Err(LiteError::FeeOverflow.into())
+    );
+}
+
+#[tokio::test]
+async fn test_rejects_other_state_account() {
+    let rpc = rpc();
+    let other = Pubkey::new_unique();
+    rpc.set_account(
+        other,
+        Account {
+            owner: Pubkey::new_unique(),
+            ..rpc
+                .get_account(&RANDOMNESS_SERVICE_STATE)
+                .await
+                .unwrap()
+                .unwrap()
+        },
+    );
+
+    assert!(matches!(
+        fetch_request_cost(
+            &rpc,
+            &other,
+            8,
+            &callback_into(Pubkey::new_from_array([7; 32])),
+            &options()
+        )
+        .await,
+        Err(LiteClientError::Program(ProgramError::IllegalOwner))
+    ));
+    assert!(fetch_request_cost(
+        &rpc,
+        &Pubkey::new_unique(),
+        8,
+        &callback_into(Pubkey::new_from_array([7; 32])),
+        &options()
+    )
+    .await
+    .is_err());
+}
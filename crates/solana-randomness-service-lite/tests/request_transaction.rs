@@ -0,0 +1,163 @@
+//! `build_request_transaction` prices the request from the state account and refuses payers
+//! who cannot afford it.
+
+mod common;
+
+use common::*;
+use solana_program::rent::Rent;
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signer;
+
+const SERVICE_STATE: &[u8] = include_bytes!("fixtures/service_state.bin");
+const COST_PER_BYTE: u64 = 10_000;
+
+fn account(lamports: u64, data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// The lamports the payer needs for an 8 byte request with the default options.
+fn required() -> u64 {
+    let options =
+        TransactionOptions::advised_for_callback(&callback_into(Pubkey::new_from_array([7; 32])));
+    FundingBreakdown::new(
+        8,
+        &callback_into(Pubkey::new_from_array([7; 32])),
+        &Some(options),
+        COST_PER_BYTE,
+        &Rent::default(),
+    )
+    .unwrap()
+    .total()
+        + 2 * LAMPORTS_PER_SIGNATURE
+}
+
+fn rpc(payer: &Pubkey, balance: u64) -> MockRpc {
+    let rpc = MockRpc::default();
+    rpc.set_account(
+        RANDOMNESS_SERVICE_STATE,
+        account(1, SERVICE_STATE.to_vec(), ID),
+    );
+    rpc.set_account(
+        *payer,
+        account(balance, Vec::new(), solana_program::system_program::ID),
+    );
+    rpc
+}
+
+#[tokio::test]
+async fn test_builds_request_transaction() {
+    let payer = Pubkey::new_unique();
+    let rpc = rpc(&payer, required());
+
+    for addresses in [ServiceAddresses::mainnet(), ServiceAddresses::devnet()] {
+        let (transaction, request) = build_request_transaction(
+            &rpc,
+            &addresses,
+            &payer,
+            8,
+            callback_into(Pubkey::new_from_array([7; 32])),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let message = &transaction.message;
+        assert_eq!(message.recent_blockhash, rpc.blockhash());
+        assert_eq!(message.account_keys[0], payer);
+        assert_eq!(message.header.num_required_signatures, 2);
+        assert_eq!(message.instructions.len(), 1);
+        assert_eq!(message.program_id(0), Some(&ID));
+        assert_eq!(
+            transaction.data(0),
+            &RequestBuilder::new(payer, 8, callback_into(Pubkey::new_from_array([7; 32])))
+                .instruction(&request.pubkey())
+                .unwrap()
+                .data[..]
+        );
+        assert!(message.account_keys.contains(&request.pubkey()));
+        assert!(message.account_keys.contains(&derive_escrow_pubkey(
+            &request.pubkey(),
+            &RANDOMNESS_SERVICE_REWARD_MINT
+        )));
+        // Unsigned
+        assert!(!transaction.is_signed());
+    }
+}
+
+#[tokio::test]
+async fn test_rejects_payer_short_of_funds() {
+    let payer = Pubkey::new_unique();
+    let rpc = rpc(&payer, required() - 1);
+
+    match build_request_transaction(
+        &rpc,
+        &ServiceAddresses::mainnet(),
+        &payer,
+        8,
+        callback_into(Pubkey::new_from_array([7; 32])),
+        None,
+    )
+    .await
+    {
+        Err(LiteClientError::InsufficientBalance {
+            balance,
+            required: r,
+        }) => {
+            assert_eq!(balance, required() - 1);
+            assert_eq!(r, required());
+        }
+        other => panic!("expected InsufficientBalance, got {:?}", other.map(|_| ())),
+    }
+
+    // A payer that does not exist has nothing
+    let missing = build_request_transaction(
+        &rpc,
+        &ServiceAddresses::mainnet(),
+        &Pubkey::new_unique(),
+        8,
+        callback_into(Pubkey::new_from_array([7; 32])),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        missing,
+        LiteClientError::InsufficientBalance { balance: 0, .. }
+    ));
+    assert_eq!(missing.kind(), ErrorKind::Validation);
+}
+
+#[tokio::test]
+async fn test_checks_state_owner_against_cluster_program() {
+    let payer = Pubkey::new_unique();
+    let rpc = rpc(&payer, required());
+    let fork = Pubkey::new_unique();
+    let addresses = ServiceAddresses {
+        program_id: fork,
+        state: derive_state_pubkey(&fork).0,
+        ..ServiceAddresses::mainnet()
+    };
+    rpc.set_account(addresses.state, account(1, SERVICE_STATE.to_vec(), ID));
+
+    assert!(matches!(
+        build_request_transaction(
+            &rpc,
+            &addresses,
+            &payer,
+            8,
+            callback_into(Pubkey::new_from_array([7; 32])),
+            None
+        )
+        .await,
+        Err(LiteClientError::Program(ProgramError::IllegalOwner))
+    ));
+}
@@ -0,0 +1,55 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_randomness_service_lite::*;
+
+#[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+struct Entry {
+    owner: Pubkey,
+    count: u64,
+    reserved: [u8; RESERVED_LEN],
+}
+
+fn entry() -> Entry {
+    Entry {
+        owner: Pubkey::new_unique(),
+        count: 7,
+        reserved: [0; RESERVED_LEN],
+    }
+}
+
+#[test]
+fn test_decodes_current_layout() {
+    let entry = entry();
+    let data = entry.try_to_vec().unwrap();
+
+    assert_eq!(deserialize_with_reserved::<Entry>(&data).unwrap(), entry);
+}
+
+#[test]
+fn test_missing_reserved_bytes_decode_as_zero() {
+    let entry = entry();
+    let data = entry.try_to_vec().unwrap();
+
+    // Written before the reserved bytes were added
+    let old = &data[..data.len() - RESERVED_LEN];
+    assert!(Entry::deserialize(&mut &old[..]).is_err());
+    assert_eq!(deserialize_with_reserved::<Entry>(old).unwrap(), entry);
+}
+
+#[test]
+fn test_ignores_appended_bytes() {
+    let entry = entry();
+    let mut data = entry.try_to_vec().unwrap();
+    data.extend([9; 16]);
+
+    assert_eq!(deserialize_with_reserved::<Entry>(&data).unwrap(), entry);
+}
+
+#[test]
+fn test_rejects_truncated_fields() {
+    let data = entry().try_to_vec().unwrap();
+
+    assert_eq!(
+        deserialize_with_reserved::<Entry>(&data[..16]),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
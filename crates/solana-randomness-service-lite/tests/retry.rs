@@ -0,0 +1,78 @@
+use borsh::BorshSerialize;
+use solana_program::clock::Clock;
+use solana_randomness_service_lite::*;
+
+fn clock(slot: u64) -> Clock {
+    Clock {
+        slot,
+        ..Default::default()
+    }
+}
+
+fn request(request_slot: u64) -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 8,
+        request_slot,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_first_attempt_is_not_a_retry() {
+    let request = request(1_000);
+
+    assert!(!is_retry_attempt(&request, &clock(1_000)));
+    assert!(!is_retry_attempt(&request, &clock(1_002)));
+    assert!(!is_retry_attempt(
+        &request,
+        &clock(1_000 + CALLBACK_RETRY_BACKOFF_SLOTS - 1)
+    ));
+}
+
+#[test]
+fn test_settlement_after_backoff_is_a_retry() {
+    let request = request(1_000);
+
+    assert!(is_retry_attempt(
+        &request,
+        &clock(1_000 + CALLBACK_RETRY_BACKOFF_SLOTS)
+    ));
+    assert!(is_retry_attempt(&request, &clock(u64::MAX)));
+}
+
+#[test]
+fn test_clock_behind_request_is_not_a_retry() {
+    assert!(!is_retry_attempt(&request(1_000), &clock(0)));
+}
+
+#[test]
+fn test_view_matches_account() {
+    let request = request(1_000);
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    request.serialize(&mut data).unwrap();
+    data.resize(request.expected_request_account_size(), 0);
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &ID, false, 0);
+
+    let view = SimpleRandomnessV1AccountView::load(&info).unwrap();
+
+    for slot in [1_000, 1_149, 1_150, 5_000] {
+        assert_eq!(
+            view.is_retry_attempt(&clock(slot)),
+            is_retry_attempt(&request, &clock(slot))
+        );
+    }
+}
+
+#[test]
+fn test_bare_slot_is_a_slot_source() {
+    let request = request(1_000);
+
+    for slot in [0, 1_149, 1_150, 5_000] {
+        assert_eq!(
+            is_retry_attempt(&request, &slot),
+            is_retry_attempt(&request, &clock(slot))
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Full rounds through a minimal consumer whose request instruction makes the
+//! `simple_randomness_v1` CPI, settled by the harness.
+
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+use solana_program::msg;
+use solana_program::pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::*;
+
+const CONSUMER_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const REQUEST_DISC: [u8; 8] = [1; 8];
+const CALLBACK_DISC: [u8; 8] = [2; 8];
+const NUM_BYTES: u8 = 8;
+
+/// Requests `NUM_BYTES` for the result account, or stores the randomness in it when called
+/// back, logging a `ConsumerRequested` or `ConsumerFulfilled` event. Request accounts: the
+/// service's request accounts, the service program, then the result account.
+fn process_consumer(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match data.get(..8) {
+        Some(disc) if disc == REQUEST_DISC => {
+            let [request, escrow, state, mint, payer, system, token, ata, service, result] =
+                accounts
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            msg!("Requesting randomness");
+            SimpleRandomnessV1Request {
+                request: request.clone(),
+                escrow: escrow.clone(),
+                state: state.clone(),
+                mint: mint.clone(),
+                payer: payer.clone(),
+                system_program: system.clone(),
+                token_program: token.clone(),
+                associated_token_program: ata.clone(),
+            }
+            .cpi(
+                service.clone(),
+                NUM_BYTES,
+                &Callback::new(
+                    CONSUMER_ID,
+                    vec![
+                        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+                        AccountMeta::new_readonly(*request.key, false).into(),
+                        AccountMeta::new(*result.key, false).into(),
+                    ],
+                    CALLBACK_DISC.to_vec(),
+                ),
+                None,
+            )?;
+
+            ConsumerRequested {
+                request: *request.key,
+                user: *payer.key,
+                num_bytes: NUM_BYTES,
+                request_slot: Clock::get()?.slot,
+            }
+            .emit();
+            Ok(())
+        }
+        Some(disc) if disc == CALLBACK_DISC => {
+            let [_state, request, result] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            msg!("Consuming randomness");
+            result.try_borrow_mut_data()?.copy_from_slice(&data[12..]);
+
+            ConsumerFulfilled {
+                request: *request.key,
+                randomness: data[12..].to_vec(),
+                slot: Clock::get()?.slot,
+            }
+            .emit();
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    request: Pubkey,
+    result: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(CONSUMER_ID, process_consumer);
+        harness.airdrop(&harness.payer(), 1_000_000_000);
+        let result = Pubkey::new_unique();
+        harness.set_account(
+            result,
+            HarnessAccount::new(vec![0; NUM_BYTES as usize], CONSUMER_ID),
+        );
+
+        Self {
+            harness,
+            request: Pubkey::new_unique(),
+            result,
+        }
+    }
+
+    fn request_ix(&self) -> Instruction {
+        Instruction {
+            program_id: CONSUMER_ID,
+            accounts: vec![
+                AccountMeta::new(self.request, true),
+                AccountMeta::new(self.harness.escrow(&self.request), false),
+                AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, false),
+                AccountMeta::new_readonly(
+                    pubkey!("So11111111111111111111111111111111111111112"),
+                    false,
+                ),
+                AccountMeta::new(self.harness.payer(), true),
+                AccountMeta::new_readonly(solana_program::system_program::ID, false),
+                AccountMeta::new_readonly(
+                    pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM"),
+                    false,
+                ),
+                AccountMeta::new_readonly(
+                    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+                    false,
+                ),
+                AccountMeta::new_readonly(ID, false),
+                AccountMeta::new(self.result, false),
+            ],
+            data: REQUEST_DISC.to_vec(),
+        }
+    }
+
+    fn spec(&self) -> RoundSpec {
+        RoundSpec {
+            consumer_request_ix: self.request_ix(),
+            randomness: self.harness.randomness(&self.request, NUM_BYTES as usize),
+            expected_callback_disc: CALLBACK_DISC,
+        }
+    }
+}
+
+#[test]
+fn test_round_requests_and_settles() {
+    let mut fixture = Fixture::new();
+    let spec = fixture.spec();
+    let payer = fixture.harness.payer();
+
+    let round = fixture.harness.run_round(spec.clone()).unwrap();
+
+    assert_eq!(round.request, fixture.request);
+    assert_eq!(round.pending.user, payer);
+    assert_eq!(round.pending.num_bytes, NUM_BYTES);
+    assert_eq!(round.after[&fixture.result].data, spec.randomness);
+    assert_eq!(round.logs.len(), 4);
+    assert_eq!(round.logs[0], "Requesting randomness");
+    assert!(round.logs[1].starts_with("Program data: "));
+    assert_eq!(round.logs[2], "Consuming randomness");
+    assert!(round.logs[3].starts_with("Program data: "));
+
+    // The request and escrow are created and closed within the round
+    for account in [fixture.request, fixture.harness.escrow(&fixture.request)] {
+        assert!(!round.before.contains_key(&account));
+        assert!(!round.after.contains_key(&account));
+    }
+    assert_eq!(round.changed(), {
+        let mut changed = vec![fixture.result, payer, RANDOMNESS_SERVICE_REWARD_WALLET];
+        changed.sort();
+        changed
+    });
+
+    // Only the fee is spent: both rents return to the payer
+    let fee = fixture.harness.request_cost(NUM_BYTES, &None).unwrap();
+    assert_eq!(round.lamports_delta(&payer), -i128::from(fee));
+    assert_eq!(
+        round.lamports_delta(&RANDOMNESS_SERVICE_REWARD_WALLET),
+        i128::from(fee)
+    );
+}
+
+#[test]
+fn test_round_rejects_unexpected_callback() {
+    let mut fixture = Fixture::new();
+    let spec = RoundSpec {
+        expected_callback_disc: [3; 8],
+        ..fixture.spec()
+    };
+    let payer_lamports = fixture
+        .harness
+        .account(&fixture.harness.payer())
+        .unwrap()
+        .lamports;
+
+    assert_eq!(
+        fixture.harness.run_round(spec).err(),
+        Some(ProgramError::InvalidInstructionData)
+    );
+
+    // The request made by the instruction is rolled back
+    assert!(fixture.harness.request(&fixture.request).is_none());
+    assert_eq!(
+        fixture
+            .harness
+            .account(&fixture.harness.payer())
+            .unwrap()
+            .lamports,
+        payer_lamports
+    );
+}
+
+#[test]
+fn test_round_fails_when_payer_cannot_fund_request() {
+    let mut fixture = Fixture::new();
+    let payer = fixture.harness.payer();
+    let rent = Rent::default().minimum_balance(0);
+    fixture.harness.remove_account(&payer);
+    fixture.harness.airdrop(&payer, rent);
+
+    assert_eq!(
+        fixture.harness.run_round(fixture.spec()).err(),
+        Some(ProgramError::InsufficientFunds)
+    );
+    assert!(fixture.harness.request(&fixture.request).is_none());
+    assert_eq!(
+        fixture.harness.account(&fixture.result).unwrap().data,
+        [0; 8]
+    );
+}
+
+#[test]
+fn test_round_requires_a_request() {
+    let mut fixture = Fixture::new();
+    let mut spec = fixture.spec();
+    // Calls the callback directly, which succeeds without requesting randomness
+    spec.consumer_request_ix.accounts = vec![
+        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, false),
+        AccountMeta::new_readonly(fixture.request, false),
+        AccountMeta::new(fixture.result, false),
+    ];
+    spec.consumer_request_ix.data = [
+        &CALLBACK_DISC[..],
+        &u32::from(NUM_BYTES).to_le_bytes(),
+        &spec.randomness,
+    ]
+    .concat();
+
+    assert_eq!(
+        fixture.harness.run_round(spec).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        fixture.harness.account(&fixture.result).unwrap().data,
+        [0; 8]
+    );
+}
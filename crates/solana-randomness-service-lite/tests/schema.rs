@@ -0,0 +1,219 @@
+//! Two teams' callbacks, with different argument layouts, decoded through one schema registry
+//! by the indexer and the forwarded fulfillment payloads.
+
+use solana_randomness_service_lite::client::mock::{request_account, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+use std::sync::Arc;
+
+const CONSUMER: Pubkey = Pubkey::new_from_array([7; 32]);
+/// The raffle team's hardcoded discriminator.
+const DRAW_WINNER: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+fn schemas_json() -> String {
+    r#"[
+        {"name": "consume_randomness", "arg_layout": ["u64", "pubkey"]},
+        {
+            "name": "draw_winner",
+            "discriminator": "0x0102030405060708",
+            "arg_layout": ["u8", "u16", "u32", "bytes"]
+        }
+    ]"#
+    .to_string()
+}
+
+/// The lootbox team's callback data: a round and the player.
+fn lootbox_ix_data(round: u64, player: &Pubkey) -> Vec<u8> {
+    [
+        &anchor_sighash("consume_randomness")[..],
+        &round.to_le_bytes(),
+        player.as_ref(),
+    ]
+    .concat()
+}
+
+/// The raffle team's callback data: a tier, a raffle id, a ticket count, and a memo.
+fn raffle_ix_data() -> Vec<u8> {
+    [
+        &DRAW_WINNER[..],
+        &[3],
+        &500u16.to_le_bytes(),
+        &70_000u32.to_le_bytes(),
+        &2u32.to_le_bytes(),
+        b"hi",
+    ]
+    .concat()
+}
+
+fn raffle_args() -> DecodedArgs {
+    DecodedArgs {
+        name: "draw_winner".to_string(),
+        discriminator: DRAW_WINNER,
+        values: vec![
+            ArgValue::U8(3),
+            ArgValue::U16(500),
+            ArgValue::U32(70_000),
+            ArgValue::Bytes(b"hi".to_vec()),
+        ],
+    }
+}
+
+#[test]
+fn test_decodes_both_layouts() {
+    let registry = SchemaRegistry::from_json(&schemas_json()).unwrap();
+    let player = Pubkey::new_unique();
+
+    assert_eq!(registry.len(), 2);
+    assert_eq!(
+        registry.decode_callback_args(&lootbox_ix_data(42, &player)),
+        Some(DecodedArgs {
+            name: "consume_randomness".to_string(),
+            discriminator: anchor_sighash("consume_randomness"),
+            values: vec![ArgValue::U64(42), ArgValue::Pubkey(player)],
+        })
+    );
+    assert_eq!(
+        registry.decode_callback_args(&raffle_ix_data()),
+        Some(raffle_args())
+    );
+}
+
+#[test]
+fn test_ignores_the_appended_randomness() {
+    let registry = SchemaRegistry::from_json(&schemas_json()).unwrap();
+    // The data the service invokes the callback with
+    let mut data = raffle_ix_data();
+    data.extend(8u32.to_le_bytes());
+    data.extend([9; 8]);
+
+    assert_eq!(registry.decode_callback_args(&data), Some(raffle_args()));
+}
+
+#[test]
+fn test_unknown_or_short_data_decodes_to_none() {
+    let registry = SchemaRegistry::from_json(&schemas_json()).unwrap();
+    let data = raffle_ix_data();
+
+    assert_eq!(
+        registry.decode_callback_args(&anchor_sighash("other")),
+        None
+    );
+    assert_eq!(registry.decode_callback_args(&data[..data.len() - 1]), None);
+    assert_eq!(registry.decode_callback_args(&data[..4]), None);
+    // A bytes length longer than the data
+    let mut data = raffle_ix_data();
+    data[15..19].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(registry.decode_callback_args(&data), None);
+}
+
+#[test]
+fn test_registration_errors() {
+    let mut registry = SchemaRegistry::from_json(&schemas_json()).unwrap();
+
+    let duplicate = CallbackSchema::anchor("consume_randomness", vec![]);
+    assert!(matches!(
+        registry.register(duplicate),
+        Err(SchemaError::DuplicateDiscriminator { name, existing }) if name == existing
+    ));
+    assert!(matches!(
+        SchemaRegistry::from_json(
+            r#"[{"name": "a", "discriminator": "0102", "arg_layout": []}]"#
+        ),
+        Err(SchemaError::InvalidDiscriminator { name, .. }) if name == "a"
+    ));
+    assert!(matches!(
+        SchemaRegistry::from_json(r#"[{"name": "a", "arg_layout": ["i64"]}]"#),
+        Err(SchemaError::Json(_))
+    ));
+}
+
+#[test]
+fn test_reads_a_schema_file() {
+    let name = format!("callback-schemas-{}.json", Pubkey::new_unique());
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, schemas_json()).unwrap();
+
+    let registry = SchemaRegistry::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        registry.unwrap().get(&DRAW_WINNER).unwrap().arg_layout,
+        vec![ArgType::U8, ArgType::U16, ArgType::U32, ArgType::Bytes]
+    );
+}
+
+#[tokio::test]
+async fn test_indexer_decodes_each_callback() {
+    let rpc = MockRpc::default();
+    let registry = Arc::new(SchemaRegistry::from_json(&schemas_json()).unwrap());
+    let indexer =
+        Indexer::new(CONSUMER, Arc::new(MemoryRequestStore::new())).with_schemas(registry);
+    let player = Pubkey::new_unique();
+    let mut requests = Vec::new();
+    for ix_data in [lootbox_ix_data(1, &player), raffle_ix_data(), vec![0; 8]] {
+        let request = Pubkey::new_unique();
+        let account = SimpleRandomnessV1Account {
+            num_bytes: 8,
+            callback: Callback::new(CONSUMER, vec![], ix_data),
+            ..Default::default()
+        };
+        rpc.set_account(request, request_account(&account));
+        requests.push(request);
+    }
+
+    let transitions = indexer.poll(&rpc).await.unwrap();
+
+    let args = |request: &Pubkey| {
+        transitions
+            .iter()
+            .find(|t| t.request == *request)
+            .unwrap()
+            .callback_args
+            .clone()
+    };
+    assert_eq!(
+        args(&requests[0]).unwrap().values,
+        vec![ArgValue::U64(1), ArgValue::Pubkey(player)]
+    );
+    assert_eq!(args(&requests[1]), Some(raffle_args()));
+    assert_eq!(args(&requests[2]), None);
+}
+
+#[test]
+fn test_fulfillment_payload_carries_the_args() {
+    let registry = SchemaRegistry::from_json(&schemas_json()).unwrap();
+    let event = FulfillmentEvent {
+        event_id: [0; 32],
+        kind: FulfillmentKind::Settled,
+        request: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        signature: Signature::default(),
+        request_slot: 1,
+        settled_slot: 2,
+        callback_args: None,
+    };
+    assert!(serde_json::to_value(&event)
+        .unwrap()
+        .get("callback_args")
+        .is_none());
+
+    let event = event.with_callback_args(
+        &registry,
+        &Callback::new(CONSUMER, vec![], raffle_ix_data()),
+    );
+
+    assert_eq!(
+        serde_json::to_value(&event).unwrap()["callback_args"],
+        serde_json::json!({
+            "name": "draw_winner",
+            "discriminator": "0102030405060708",
+            "values": [
+                {"type": "u8", "value": 3},
+                {"type": "u16", "value": 500},
+                {"type": "u32", "value": 70000},
+                {"type": "bytes", "value": "aGk="},
+            ],
+        })
+    );
+}
@@ -0,0 +1,50 @@
+//! Requests and client handles are moved across tokio tasks, so they must stay `Send + Sync`.
+
+use solana_randomness_service_lite::*;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_core_types_are_send_sync() {
+    assert_send_sync::<Callback>();
+    assert_send_sync::<AccountMetaBorsh>();
+    assert_send_sync::<TransactionOptions>();
+    assert_send_sync::<SimpleRandomnessV1Account>();
+    assert_send_sync::<RequestHeader>();
+    assert_send_sync::<ServiceAddresses>();
+    assert_send_sync::<AddressOverrides>();
+    assert_send_sync::<LiteError>();
+    assert_send_sync::<AddressError>();
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn test_client_handles_are_send_sync() {
+    use solana_randomness_service_lite::client::mock::*;
+    use solana_randomness_service_lite::client::*;
+
+    assert_send_sync::<RequestBuilder>();
+    assert_send_sync::<BuiltRequest>();
+    assert_send_sync::<SentRequest>();
+    assert_send_sync::<FailoverRpc>();
+    assert_send_sync::<WebsocketPubsub>();
+    assert_send_sync::<MockRpc>();
+    assert_send_sync::<MockPubsub>();
+    assert_send_sync::<FileAuditSink>();
+    assert_send_sync::<MemoryAuditSink>();
+    assert_send_sync::<FileJournal>();
+    assert_send_sync::<MemoryJournal>();
+    assert_send_sync::<RateLimiter>();
+    assert_send_sync::<RequestBatch>();
+    assert_send_sync::<SentBatch>();
+    assert_send_sync::<BatchSendError>();
+    assert_send_sync::<BatchAwaitError>();
+    assert_send_sync::<ManualClock>();
+    assert_send_sync::<LiteClientError>();
+    assert_send_sync::<AwaitError>();
+    assert_send_sync::<std::sync::Arc<dyn RandomnessRpc>>();
+    assert_send_sync::<std::sync::Arc<dyn RandomnessPubsub>>();
+    assert_send_sync::<std::sync::Arc<dyn AuditSink>>();
+    assert_send_sync::<std::sync::Arc<dyn Journal>>();
+    assert_send_sync::<std::sync::Arc<dyn LimiterClock>>();
+}
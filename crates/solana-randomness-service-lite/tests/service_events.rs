@@ -0,0 +1,91 @@
+//! The randomness service's events decode from the data of their `Program data:` log lines.
+
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+fn log_data(discriminator: [u8; 8], event: &impl BorshSerialize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    data
+}
+
+fn settled() -> SimpleRandomnessV1SettledEvent {
+    SimpleRandomnessV1SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request: Pubkey::new_unique(),
+        request_slot: 10,
+        settled_slot: 12,
+        is_success: true,
+        randomness: vec![7; 32],
+    }
+}
+
+#[test]
+fn test_discriminators_match_anchor() {
+    assert_eq!(
+        SimpleRandomnessV1RequestedEvent::DISCRIMINATOR,
+        anchor_event_discriminator("SimpleRandomnessV1RequestedEvent")
+    );
+    assert_eq!(
+        SimpleRandomnessV1SettledEvent::DISCRIMINATOR,
+        anchor_event_discriminator("SimpleRandomnessV1SettledEvent")
+    );
+}
+
+#[test]
+fn test_decodes_requested_event() {
+    let callback_pid = Pubkey::new_unique();
+    let event = SimpleRandomnessV1RequestedEvent {
+        callback_pid,
+        user: Pubkey::new_unique(),
+        request: Pubkey::new_unique(),
+        request_slot: 10,
+        callback: Callback::new(callback_pid, vec![], vec![1; 8]),
+        compute_units: 200_000,
+        priority_fee_micro_lamports: 100,
+        num_bytes: 16,
+    };
+    let data = log_data(SimpleRandomnessV1RequestedEvent::DISCRIMINATOR, &event);
+
+    let decoded = SimpleRandomnessV1RequestedEvent::try_from_log_data(&data).unwrap();
+    assert_eq!(decoded.request, event.request);
+    assert_eq!(decoded.user, event.user);
+    assert_eq!(decoded.callback_pid, callback_pid);
+    assert_eq!(decoded.callback.ix_data, vec![1; 8]);
+    assert_eq!(decoded.num_bytes, 16);
+}
+
+#[test]
+fn test_decodes_settled_event() {
+    let event = settled();
+    let data = log_data(SimpleRandomnessV1SettledEvent::DISCRIMINATOR, &event);
+    let decoded = SimpleRandomnessV1SettledEvent::try_from_log_data(&data).unwrap();
+    assert_eq!(decoded, event);
+    assert_eq!(decoded.randomness.len(), 32);
+}
+
+#[test]
+fn test_ignores_appended_fields() {
+    let event = settled();
+    let mut data = log_data(SimpleRandomnessV1SettledEvent::DISCRIMINATOR, &event);
+    data.extend([1, 2, 3]);
+    assert_eq!(
+        SimpleRandomnessV1SettledEvent::try_from_log_data(&data).unwrap(),
+        event
+    );
+}
+
+#[test]
+fn test_rejects_other_events() {
+    let data = log_data(SimpleRandomnessV1RequestedEvent::DISCRIMINATOR, &settled());
+    assert_eq!(
+        SimpleRandomnessV1SettledEvent::try_from_log_data(&data).unwrap_err(),
+        ProgramError::InvalidArgument
+    );
+    assert!(SimpleRandomnessV1SettledEvent::try_from_log_data(&[]).is_err());
+
+    // Truncated after the discriminator
+    let data = log_data(SimpleRandomnessV1SettledEvent::DISCRIMINATOR, &settled());
+    assert!(SimpleRandomnessV1SettledEvent::try_from_log_data(&data[..40]).is_err());
+}
@@ -0,0 +1,170 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+/// A state account synthesized from the service program's account layout: bump 254, the native
+/// mint, the reward wallet, 10,000 lamports per byte, last updated at 2024-01-01, and a zeroed
+/// reserved buffer. The authority and Switchboard service keys are placeholders.
+const SERVICE_STATE: &[u8] = include_bytes!("fixtures/service_state.bin");
+
+fn account_data(state: &ServiceState) -> Vec<u8> {
+    let mut data = ServiceState::DISCRIMINATOR.to_vec();
+    data.extend(state.try_to_vec().unwrap());
+    data
+}
+
+#[test]
+fn test_service_state_round_trip() {
+    let state = ServiceState {
+        bump: 255,
+        wallet: RANDOMNESS_SERVICE_REWARD_WALLET,
+        cost_per_byte: 100,
+        ..Default::default()
+    };
+
+    let data = account_data(&state);
+    let decoded = ServiceState::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(decoded.bump, 255);
+    assert_eq!(decoded.wallet, state.wallet);
+    assert_eq!(decoded.cost_per_byte, 100);
+}
+
+#[test]
+fn test_service_state_is_not_a_request() {
+    let mut data = account_data(&ServiceState::default());
+    data[..8].copy_from_slice(&SimpleRandomnessV1Account::DISCRIMINATOR);
+
+    assert!(ServiceState::try_deserialize(&mut data.as_slice()).is_err());
+}
+
+#[test]
+fn test_short_service_state_is_an_error() {
+    let data = account_data(&ServiceState::default());
+
+    for len in [
+        0,
+        7,
+        8,
+        ServiceState::COST_PER_BYTE_OFFSET + 8,
+        ServiceState::LEN - 1,
+    ] {
+        assert!(ServiceState::try_deserialize(&mut &data[..len]).is_err());
+    }
+    assert_eq!(
+        ServiceState::try_deserialize(&mut &data[..ServiceState::LEN - 1]).unwrap_err(),
+        ProgramError::AccountDataTooSmall
+    );
+    assert_eq!(
+        ServiceState::try_deserialize_unchecked(&mut &data[..4]).unwrap_err(),
+        ProgramError::InvalidAccountData
+    );
+}
+
+/// Code written against the old name keeps compiling until the alias is removed.
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_state_alias() {
+    let state: State = ServiceState::default();
+    assert_eq!(State::DISCRIMINATOR, ServiceState::DISCRIMINATOR);
+    assert_eq!(State::owner(), ID);
+
+    let data = account_data(&state);
+    let decoded: ServiceState = State::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(decoded.wallet, state.wallet);
+}
+
+#[test]
+fn test_service_state_fixture() {
+    assert_eq!(SERVICE_STATE.len(), ServiceState::LEN);
+
+    let state = ServiceState::try_deserialize(&mut &SERVICE_STATE[..]).unwrap();
+    assert_eq!(state.bump, 254);
+    assert_eq!(state.mint, RANDOMNESS_SERVICE_REWARD_MINT);
+    assert_eq!(state.wallet, RANDOMNESS_SERVICE_REWARD_WALLET);
+    assert_eq!(state.cost_per_byte, 10_000);
+    assert_eq!(state.last_updated, 1_704_067_200);
+    assert_eq!(state.ebuf, [0; 512]);
+    assert_eq!(account_data(&state), SERVICE_STATE);
+}
+
+#[test]
+fn test_read_fields_match_deserialized_state() {
+    let state = ServiceState::try_deserialize(&mut &SERVICE_STATE[..]).unwrap();
+
+    assert_eq!(ServiceState::read_mint(SERVICE_STATE), Ok(state.mint));
+    assert_eq!(ServiceState::read_wallet(SERVICE_STATE), Ok(state.wallet));
+    assert_eq!(
+        ServiceState::read_cost_per_byte(SERVICE_STATE),
+        Ok(state.cost_per_byte)
+    );
+    assert_eq!(
+        ServiceState::read_cost_per_byte(&SERVICE_STATE[..ServiceState::COST_PER_BYTE_OFFSET + 7]),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+    assert_eq!(
+        ServiceState::read_wallet(&SimpleRandomnessV1Account::DISCRIMINATOR),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+/// The offsets follow the struct definition: every field of a serialized state is found at its
+/// offset, and the state fills `LEN`.
+#[test]
+fn test_offsets_match_serialized_layout() {
+    let state = ServiceState {
+        bump: 1,
+        authority: Pubkey::new_from_array([2; 32]),
+        mint: Pubkey::new_from_array([3; 32]),
+        switchboard_service: Pubkey::new_from_array([4; 32]),
+        wallet: Pubkey::new_from_array([5; 32]),
+        cost_per_byte: 0x0607_0809_0a0b_0c0d,
+        last_updated: 14,
+        ebuf: [15; 512],
+    };
+    let data = account_data(&state);
+
+    assert_eq!(data.len(), ServiceState::LEN);
+    let mint = ServiceState::MINT_OFFSET;
+    assert_eq!(&data[mint..mint + 32], state.mint.as_ref());
+    let wallet = ServiceState::WALLET_OFFSET;
+    assert_eq!(&data[wallet..wallet + 32], state.wallet.as_ref());
+    let cost_per_byte = ServiceState::COST_PER_BYTE_OFFSET;
+    assert_eq!(
+        &data[cost_per_byte..cost_per_byte + 8],
+        &state.cost_per_byte.to_le_bytes()
+    );
+    assert_eq!(
+        ServiceState::read_cost_per_byte(&data),
+        Ok(state.cost_per_byte)
+    );
+}
+
+#[test]
+fn test_read_fields_reject_short_state() {
+    // Every field fits, but the account is shorter than the state
+    let data = &SERVICE_STATE[..ServiceState::LEN - 1];
+    assert_eq!(
+        ServiceState::read_mint(data),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+    assert_eq!(
+        ServiceState::read_cost_per_byte(data),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+    // Longer data is read as is
+    let mut data = SERVICE_STATE.to_vec();
+    data.push(0);
+    assert_eq!(ServiceState::read_cost_per_byte(&data), Ok(10_000));
+}
+
+#[test]
+fn test_view_reads_fixture() {
+    let key = RANDOMNESS_SERVICE_STATE;
+    let mut lamports = 0;
+    let mut data = SERVICE_STATE.to_vec();
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &ID, false, 0);
+
+    let view = ServiceStateView::load(&info).unwrap();
+    assert_eq!(view.mint(), RANDOMNESS_SERVICE_REWARD_MINT);
+    assert_eq!(view.wallet(), RANDOMNESS_SERVICE_REWARD_WALLET);
+    assert_eq!(view.cost_per_byte(), 10_000);
+}
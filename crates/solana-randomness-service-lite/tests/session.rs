@@ -0,0 +1,121 @@
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+const BUILDS: usize = 10;
+
+fn builder(payer: Pubkey) -> RequestBuilder {
+    RequestBuilder::new(
+        payer,
+        8,
+        Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+    )
+    .cost_per_byte(10_000)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_builds_share_one_session() {
+    let payer = Pubkey::new_unique();
+    let rpc = Arc::new(MockRpc::default());
+    let session = Arc::new(PayerSession::new(payer));
+
+    let builds: Vec<_> = (0..BUILDS)
+        .map(|_| {
+            let rpc = rpc.clone();
+            let session = session.clone();
+            tokio::spawn(async move { builder(payer).build_in_session(&*rpc, &session).await })
+        })
+        .collect();
+    let mut built = Vec::new();
+    for build in builds {
+        built.push(build.await.unwrap().unwrap());
+    }
+
+    // One blockhash fetch for every build
+    assert_eq!(rpc.calls(), 1);
+
+    let requests: HashSet<Pubkey> = built.iter().map(|(b, _)| b.request.pubkey()).collect();
+    assert_eq!(requests.len(), BUILDS);
+    for (built, plan) in &built {
+        let message = &built.transaction.message;
+        assert_eq!(message.recent_blockhash, rpc.blockhash());
+        // Only the request instruction: no wSOL account is created or closed
+        assert_eq!(message.instructions.len(), 1);
+        assert_eq!(message.program_id(0), Some(&ID));
+        assert_eq!(plan.request, built.request.pubkey());
+        assert_eq!(plan.funding, built.funding);
+    }
+
+    let mut indices: Vec<usize> = session.plans().iter().map(|p| p.index).collect();
+    indices.sort();
+    assert_eq!(indices, (0..BUILDS).collect::<Vec<_>>());
+    assert_eq!(
+        session.total_funding(),
+        builder(payer).funding().unwrap().total() * BUILDS as u64
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_sends_share_one_session() {
+    let payer = Arc::new(Keypair::new());
+    let rpc = Arc::new(MockRpc::default());
+    let session = Arc::new(PayerSession::new(payer.pubkey()));
+
+    let sends: Vec<_> = (0..BUILDS)
+        .map(|_| {
+            let (rpc, session, payer) = (rpc.clone(), session.clone(), payer.clone());
+            tokio::spawn(async move {
+                builder(payer.pubkey())
+                    .send_in_session(&*rpc, &*payer, &session)
+                    .await
+            })
+        })
+        .collect();
+    for send in sends {
+        send.await.unwrap().unwrap();
+    }
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), BUILDS);
+    assert!(sent.iter().all(|tx| tx.is_signed()));
+    let signatures: HashSet<_> = sent.iter().map(|tx| tx.signatures[0]).collect();
+    assert_eq!(signatures.len(), BUILDS);
+    assert_eq!(session.plans().len(), BUILDS);
+}
+
+#[tokio::test]
+async fn test_blockhash_is_refetched_when_stale() {
+    let rpc = MockRpc::default();
+    let session = PayerSession::new(Pubkey::new_unique());
+
+    session.blockhash(&rpc).await.unwrap();
+    session.blockhash(&rpc).await.unwrap();
+    assert_eq!(rpc.calls(), 1);
+
+    session.invalidate_blockhash().await;
+    session.blockhash(&rpc).await.unwrap();
+    assert_eq!(rpc.calls(), 2);
+
+    let session = PayerSession::new(Pubkey::new_unique()).max_blockhash_age(Duration::ZERO);
+    session.blockhash(&rpc).await.unwrap();
+    session.blockhash(&rpc).await.unwrap();
+    assert_eq!(rpc.calls(), 4);
+}
+
+#[tokio::test]
+async fn test_rejects_builder_for_other_payer() {
+    let rpc = MockRpc::default();
+    let session = PayerSession::new(Pubkey::new_unique());
+
+    let result = builder(Pubkey::new_unique())
+        .build_in_session(&rpc, &session)
+        .await;
+
+    assert!(matches!(result, Err(LiteClientError::PayerMismatch { .. })));
+    assert!(session.plans().is_empty());
+    assert_eq!(rpc.calls(), 0);
+}
@@ -0,0 +1,152 @@
+use solana_randomness_service_lite::client::mock::request_account;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::account::Account;
+
+fn synthetic_accounts(count: usize) -> Vec<(Pubkey, SimpleRandomnessV1Account)> {
+    (0..count)
+        .map(|i| {
+            let request = SimpleRandomnessV1Account {
+                is_completed: (i % 7 == 0) as u8,
+                num_bytes: (i % 32 + 1) as u8,
+                user: Pubkey::new_unique(),
+                escrow: Pubkey::new_unique(),
+                request_slot: 250_000_000 + i as u64,
+                callback: Callback::new(
+                    Pubkey::new_unique(),
+                    (0..i % 4)
+                        .map(|_| AccountMeta::new(Pubkey::new_unique(), false).into())
+                        .collect(),
+                    vec![i as u8; i % 16],
+                ),
+                compute_units: 200_000 + i as u32,
+                priority_fee_micro_lamports: i as u64,
+                error_message: if i % 7 == 0 {
+                    format!("callback {} failed", i)
+                } else {
+                    String::new()
+                },
+            };
+            (Pubkey::new_unique(), request)
+        })
+        .collect()
+}
+
+fn export(accounts: &[(Pubkey, SimpleRandomnessV1Account)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    export_snapshot(accounts, 251_000_000, "mainnet-beta", &mut buf).unwrap();
+    buf
+}
+
+fn assert_entry_eq(
+    actual: &(Pubkey, SimpleRandomnessV1Account),
+    expected: &(Pubkey, SimpleRandomnessV1Account),
+) {
+    assert_eq!(actual.0, expected.0);
+    assert_eq!(actual.1.num_bytes, expected.1.num_bytes);
+    assert_eq!(actual.1.request_slot, expected.1.request_slot);
+    assert_eq!(actual.1.callback.program_id, expected.1.callback.program_id);
+    assert_eq!(actual.1.callback.ix_data, expected.1.callback.ix_data);
+    assert_eq!(
+        actual.1.callback.accounts.len(),
+        expected.1.callback.accounts.len()
+    );
+    assert_eq!(actual.1.error_message, expected.1.error_message);
+}
+
+#[test]
+fn test_round_trip() {
+    let accounts = synthetic_accounts(5_000);
+    let buf = export(&accounts);
+
+    let reader = import_snapshot(&buf[..]).unwrap();
+    assert_eq!(
+        reader.header(),
+        &SnapshotHeader {
+            slot: 251_000_000,
+            cluster: "mainnet-beta".to_string(),
+            count: 5_000,
+        }
+    );
+
+    let imported: Vec<_> = reader.map(|entry| entry.unwrap()).collect();
+    assert_eq!(imported.len(), accounts.len());
+    for (actual, expected) in imported.iter().zip(accounts.iter()) {
+        assert_entry_eq(actual, expected);
+    }
+}
+
+#[test]
+fn test_truncated_stream_preserves_partial_results() {
+    let accounts = synthetic_accounts(1_000);
+    let full = export(&accounts);
+    let partial = export(&accounts[..600]);
+    // Cut the stream in the middle of entry 600.
+    let buf = &full[..partial.len() + 10];
+
+    let entries: Vec<_> = import_snapshot(buf).unwrap().collect();
+    assert_eq!(entries.len(), 601);
+    for (actual, expected) in entries[..600].iter().zip(accounts.iter()) {
+        assert_entry_eq(actual.as_ref().unwrap(), expected);
+    }
+    assert!(matches!(
+        entries[600],
+        Err(SnapshotError::Corrupt { index: 600, .. })
+    ));
+}
+
+#[test]
+fn test_corrupt_length_prefix() {
+    let accounts = synthetic_accounts(10);
+    let mut buf = export(&accounts);
+    let offset = export(&accounts[..3]).len();
+    buf[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let entries: Vec<_> = import_snapshot(&buf[..]).unwrap().collect();
+    assert_eq!(entries.len(), 4);
+    assert!(entries[..3].iter().all(|entry| entry.is_ok()));
+    assert!(matches!(
+        entries[3],
+        Err(SnapshotError::Corrupt { index: 3, .. })
+    ));
+}
+
+#[test]
+fn test_invalid_header() {
+    let mut buf = export(&synthetic_accounts(1));
+
+    buf[4..6].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        import_snapshot(&buf[..]),
+        Err(SnapshotError::UnsupportedVersion(v)) if v == SNAPSHOT_VERSION + 1
+    ));
+
+    buf[0] = b'X';
+    assert!(matches!(
+        import_snapshot(&buf[..]),
+        Err(SnapshotError::InvalidMagic)
+    ));
+}
+
+#[test]
+fn test_decode_request_accounts_skips_other_accounts() {
+    let accounts = synthetic_accounts(3);
+    let mut raw: Vec<(Pubkey, Account)> = accounts
+        .iter()
+        .map(|(pubkey, request)| (*pubkey, request_account(request)))
+        .collect();
+
+    let mut foreign = request_account(&accounts[0].1);
+    foreign.owner = Pubkey::new_unique();
+    raw.push((Pubkey::new_unique(), foreign));
+
+    let mut state = request_account(&accounts[0].1);
+    state.data[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+    raw.push((Pubkey::new_unique(), state));
+
+    let decoded = decode_request_accounts(raw);
+    assert_eq!(decoded.len(), 3);
+    for (actual, expected) in decoded.iter().zip(accounts.iter()) {
+        assert_entry_eq(actual, expected);
+    }
+}
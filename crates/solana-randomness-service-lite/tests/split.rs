@@ -0,0 +1,214 @@
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_randomness_service_lite::client::mock::{MockPubsub, MockRpc};
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+fn program_data(name: &str, event: impl BorshSerialize) -> String {
+    let mut data = hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+fn sizes(total_bytes: usize) -> Vec<u8> {
+    split_into_requests(total_bytes)
+        .into_iter()
+        .map(NumBytes::get)
+        .collect()
+}
+
+fn settle(rpc: &MockRpc, request: Pubkey, is_success: bool, randomness: Vec<u8>) {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: 1,
+        settled_slot: 3,
+        is_success,
+        randomness,
+    };
+    rpc.add_transaction(TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: 3,
+        failed: false,
+        account_keys: vec![request, RANDOMNESS_SERVICE_REWARD_WALLET, ID],
+        log_messages: vec![program_data("SimpleRandomnessV1SettledEvent", event)],
+        token_balances: vec![],
+    });
+}
+
+fn pubsub() -> Vec<Arc<dyn RandomnessPubsub>> {
+    vec![Arc::new(MockPubsub::new())]
+}
+
+async fn send_batch(rpc: &MockRpc, total_bytes: usize) -> SentBatch {
+    let payer = Keypair::new();
+    RequestBuilder::new(payer.pubkey(), 8, Callback::default())
+        .num_bytes_total(total_bytes)
+        .send(rpc, &payer)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn test_max_num_bytes_matches_service() {
+    assert_eq!(MAX_NUM_BYTES, 32);
+    assert_eq!(NumBytes::MAX.get(), MAX_NUM_BYTES);
+    assert_eq!(NumBytes::new(1).unwrap().get(), 1);
+    assert_eq!(NumBytes::new(0), Err(ServiceError::InvalidNumberOfBytes));
+    assert_eq!(
+        NumBytes::try_from(MAX_NUM_BYTES + 1),
+        Err(ServiceError::InvalidNumberOfBytes)
+    );
+}
+
+#[test]
+fn test_split_exact_multiple() {
+    assert_eq!(sizes(64), vec![32, 32]);
+    assert_eq!(sizes(96), vec![32, 32, 32]);
+}
+
+#[test]
+fn test_split_with_remainder() {
+    assert_eq!(sizes(70), vec![32, 32, 6]);
+    assert_eq!(sizes(33), vec![32, 1]);
+}
+
+#[test]
+fn test_split_single_chunk() {
+    assert_eq!(sizes(5), vec![5]);
+    assert_eq!(sizes(32), vec![32]);
+    assert!(sizes(0).is_empty());
+}
+
+#[test]
+fn test_combine_results_keeps_request_order() {
+    let first = [1u8; 32];
+    let second = [2u8; 6];
+    let combined = combine_results(&[&first, &second]);
+
+    assert_eq!(combined.len(), 38);
+    assert_eq!(&combined[..32], &first);
+    assert_eq!(&combined[32..], &second);
+    assert!(combine_results(&[]).is_empty());
+}
+
+#[tokio::test]
+async fn test_num_bytes_total_sends_one_request_per_chunk() {
+    let rpc = MockRpc::default();
+    let payer = Keypair::new();
+    let batch = RequestBuilder::new(payer.pubkey(), 8, Callback::default())
+        .cost_per_byte(10)
+        .num_bytes_total(70);
+    assert_eq!(batch.requests().len(), 3);
+    assert_eq!(
+        batch.total_lamports().unwrap(),
+        batch
+            .requests()
+            .iter()
+            .map(|r| r.funding().unwrap().total())
+            .sum::<u64>()
+    );
+
+    let sent = batch.send(&rpc, &payer).await.unwrap();
+    assert_eq!(sent.requests.len(), 3);
+
+    // The request's num_bytes follows the 8 byte instruction discriminator
+    let num_bytes: Vec<u8> = rpc
+        .sent_transactions()
+        .iter()
+        .map(|t| t.message.instructions[0].data[8])
+        .collect();
+    assert_eq!(num_bytes, vec![32, 32, 6]);
+}
+
+#[tokio::test]
+async fn test_await_batch_reassembles_in_request_order() {
+    let rpc = MockRpc::default();
+    let sent = send_batch(&rpc, 70).await;
+
+    // Settled out of order, the result still follows the request order
+    let [first, second, third] = [0, 1, 2].map(|i| sent.requests[i].request);
+    settle(&rpc, third, true, vec![3; 6]);
+    settle(&rpc, first, true, vec![1; 32]);
+    settle(&rpc, second, true, vec![2; 32]);
+
+    let result = await_batch_with_pubsub(&rpc, &pubsub(), &sent, Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        [vec![1; 32], vec![2; 32], vec![3; 6]].concat(),
+        "parts should be combined in request order"
+    );
+}
+
+#[tokio::test]
+async fn test_await_batch_reports_failed_callback() {
+    let rpc = MockRpc::default();
+    let sent = send_batch(&rpc, 64).await;
+    settle(&rpc, sent.requests[0].request, true, vec![1; 32]);
+    settle(&rpc, sent.requests[1].request, false, vec![2; 32]);
+
+    let err = await_batch_with_pubsub(&rpc, &pubsub(), &sent, Duration::from_secs(1))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err, BatchAwaitError::Failed { index: 1, request } if request == sent.requests[1].request),
+        "unexpected error: {:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_await_batch_reports_missing_result() {
+    let rpc = MockRpc::default();
+    let sent = send_batch(&rpc, 40).await;
+    settle(&rpc, sent.requests[0].request, true, vec![1; 32]);
+
+    let err = await_batch_with_pubsub(&rpc, &pubsub(), &sent, Duration::from_secs(1))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err, BatchAwaitError::MissingResult { index: 1, .. }),
+        "unexpected error: {:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_failed_send_returns_requests_already_sent() {
+    let rpc = MockRpc::default();
+    rpc.set_fail_sends(true);
+    let payer = Keypair::new();
+
+    let err = RequestBuilder::new(payer.pubkey(), 8, Callback::default())
+        .num_bytes_total(64)
+        .send(&rpc, &payer)
+        .await
+        .unwrap_err();
+
+    assert!(err.sent.is_empty());
+    assert!(matches!(err.error, LiteClientError::Rpc(_)));
+}
@@ -0,0 +1,320 @@
+//! The transactions below are synthesized to match the logs and token balances of the service
+//! program's request, settle, and callback error instructions.
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program::hash::hash;
+use solana_program::pubkey;
+use solana_randomness_service_lite::client::mock::MockRpc;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeYN9wXW5ahPtwC8tsxXw5AoQ6U3ZRJBxM");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+#[derive(BorshSerialize)]
+struct RequestedEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    callback: Callback,
+    compute_units: u32,
+    priority_fee_micro_lamports: u64,
+    num_bytes: u8,
+}
+
+#[derive(BorshSerialize)]
+struct SettledEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    is_success: bool,
+    randomness: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+struct CallbackErrorEvent {
+    callback_pid: Pubkey,
+    user: Pubkey,
+    request: Pubkey,
+    request_slot: u64,
+    settled_slot: u64,
+    error_message: String,
+}
+
+fn program_data(name: &str, event: impl BorshSerialize) -> String {
+    let mut data = hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    data.extend(event.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+fn escrow(request: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            request.as_ref(),
+            TOKEN_PROGRAM_ID.as_ref(),
+            RANDOMNESS_SERVICE_REWARD_MINT.as_ref(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+fn transaction(slot: u64, accounts: Vec<Pubkey>, log: String) -> TransactionRecord {
+    TransactionRecord {
+        signature: Signature::new_unique(),
+        slot,
+        failed: false,
+        account_keys: [accounts, vec![ID]].concat(),
+        log_messages: vec![log],
+        token_balances: vec![],
+    }
+}
+
+/// A request transaction and the request it created.
+fn request(
+    slot: u64,
+    num_bytes: u8,
+    compute_unit_price: u64,
+    paid: u64,
+) -> (Pubkey, TransactionRecord) {
+    let request = Pubkey::new_unique();
+    let event = RequestedEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot: slot,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        compute_units: 200_000,
+        priority_fee_micro_lamports: compute_unit_price,
+        num_bytes,
+    };
+
+    let mut transaction = transaction(
+        slot,
+        vec![request, escrow(&request)],
+        program_data("SimpleRandomnessV1RequestedEvent", event),
+    );
+    transaction.token_balances = vec![TokenBalanceRecord {
+        account: escrow(&request),
+        mint: RANDOMNESS_SERVICE_REWARD_MINT,
+        owner: Some(request),
+        pre: 0,
+        post: paid,
+    }];
+    (request, transaction)
+}
+
+fn settle(request: Pubkey, request_slot: u64, slot: u64) -> TransactionRecord {
+    let event = SettledEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot,
+        settled_slot: slot,
+        is_success: true,
+        randomness: vec![7; 8],
+    };
+    transaction(
+        slot,
+        vec![request],
+        program_data("SimpleRandomnessV1SettledEvent", event),
+    )
+}
+
+fn callback_error(request: Pubkey, request_slot: u64, slot: u64) -> TransactionRecord {
+    let event = CallbackErrorEvent {
+        callback_pid: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request,
+        request_slot,
+        settled_slot: slot,
+        error_message: "callback failed".to_string(),
+    };
+    transaction(
+        slot,
+        vec![request],
+        program_data("SimpleRandomnessV1CallbackErrorEvent", event),
+    )
+}
+
+/// Three requests in slots 100 to 104, one settled and one errored, and an older request settled
+/// inside the range.
+fn sample() -> Vec<TransactionRecord> {
+    let (old, old_request) = request(50, 32, 1_000, 99_000);
+    let (settled, settled_request) = request(100, 8, 100, 10_000);
+    let (errored, errored_request) = request(102, 16, 300, 30_000);
+    let (_, pending_request) = request(104, 32, 200, 20_000);
+
+    vec![
+        old_request,
+        settled_request,
+        errored_request,
+        pending_request,
+        settle(old, 50, 101),
+        settle(settled, 100, 103),
+        callback_error(errored, 102, 110),
+    ]
+}
+
+#[test]
+fn test_percentiles_use_nearest_rank() {
+    let distribution = Distribution::from_values((1..=100).rev().collect()).unwrap();
+    assert_eq!(
+        distribution,
+        Distribution {
+            count: 100,
+            min: 1,
+            max: 100,
+            mean: 50.5,
+            p50: 50,
+            p90: 90,
+            p99: 99,
+        }
+    );
+
+    let distribution = Distribution::from_values(vec![4, 1, 3, 2]).unwrap();
+    assert_eq!(
+        (distribution.p50, distribution.p90, distribution.p99),
+        (2, 4, 4)
+    );
+
+    let distribution = Distribution::from_values(vec![7]).unwrap();
+    assert_eq!(
+        (
+            distribution.min,
+            distribution.p50,
+            distribution.p99,
+            distribution.max
+        ),
+        (7, 7, 7, 7)
+    );
+}
+
+#[test]
+fn test_empty_distribution_is_none() {
+    assert_eq!(Distribution::from_values(vec![]), None);
+
+    let stats = ServiceStats::from_transactions(&[], SlotRange { start: 0, end: 10 });
+    assert_eq!(stats.requests, 0);
+    assert_eq!(stats.num_bytes, None);
+    assert_eq!(stats.fulfillment_latency_slots, None);
+}
+
+#[test]
+fn test_stats_count_requests_in_range() {
+    let stats = ServiceStats::from_transactions(
+        &sample(),
+        SlotRange {
+            start: 100,
+            end: 120,
+        },
+    );
+
+    assert_eq!(stats.requests, 3);
+    assert_eq!(stats.fulfilled, 2);
+
+    let num_bytes = stats.num_bytes.unwrap();
+    assert_eq!((num_bytes.min, num_bytes.p50, num_bytes.max), (8, 16, 32));
+
+    let price = stats.compute_unit_price.unwrap();
+    assert_eq!((price.min, price.p50, price.max), (100, 200, 300));
+    assert_eq!(price.mean, 200.0);
+
+    let funding = stats.escrow_funding.unwrap();
+    assert_eq!(
+        (funding.min, funding.p50, funding.max),
+        (10_000, 20_000, 30_000)
+    );
+
+    // Settled after 3 slots and errored after 8. The older request is not counted.
+    let latency = stats.fulfillment_latency_slots.unwrap();
+    assert_eq!((latency.count, latency.min, latency.max), (2, 3, 8));
+}
+
+#[test]
+fn test_stats_ignore_failed_transactions() {
+    let mut transactions = sample();
+    for transaction in transactions.iter_mut() {
+        transaction.failed = true;
+    }
+
+    let stats = ServiceStats::from_transactions(&transactions, SlotRange { start: 0, end: 200 });
+    assert_eq!(stats.requests, 0);
+}
+
+#[tokio::test]
+async fn test_service_stats_samples_back_from_newest_transaction() {
+    let rpc = MockRpc::default();
+    for transaction in sample() {
+        rpc.add_transaction(transaction);
+    }
+
+    // The newest transaction is at slot 110, so the request at slot 100 is just outside
+    let stats = service_stats(&rpc, 9).await.unwrap();
+    assert_eq!(
+        stats.range,
+        SlotRange {
+            start: 101,
+            end: 110
+        }
+    );
+    assert_eq!(stats.requests, 2);
+    assert_eq!(stats.fulfilled, 1);
+    assert_eq!(stats.fulfillment_latency_slots.unwrap().max, 8);
+}
+
+#[tokio::test]
+async fn test_service_stats_serializes_to_json() {
+    let rpc = MockRpc::default();
+    for transaction in sample() {
+        rpc.add_transaction(transaction);
+    }
+
+    let stats = service_stats(&rpc, 1_000).await.unwrap();
+    let json = serde_json::to_value(&stats).unwrap();
+
+    assert_eq!(json["requests"], 4);
+    assert_eq!(json["num_bytes"]["p50"], 16);
+    assert_eq!(json["range"]["end"], 110);
+    assert!(json["fulfillment_latency_slots"]["mean"].is_number());
+
+    let empty = service_stats(&MockRpc::default(), 1_000).await.unwrap();
+    assert!(serde_json::to_value(&empty).unwrap()["num_bytes"].is_null());
+}
+
+#[tokio::test]
+async fn test_service_stats_with_clock() {
+    let rpc = MockRpc::default();
+    for transaction in sample() {
+        rpc.add_transaction(transaction);
+    }
+    let addresses = ServiceAddresses::default();
+
+    // At the newest transaction's slot it matches `service_stats`
+    let stats = service_stats_with_clock(&rpc, &addresses, 9, &110u64)
+        .await
+        .unwrap();
+    assert_eq!(stats, service_stats(&rpc, 9).await.unwrap());
+
+    // Long after it, the range holds nothing
+    rpc.warp_to_slot(10_000);
+    let stats = service_stats_with_clock(&rpc, &addresses, 9, &RpcSlotClock(&rpc))
+        .await
+        .unwrap();
+    assert_eq!(
+        stats.range,
+        SlotRange {
+            start: 9_991,
+            end: 10_000
+        }
+    );
+    assert_eq!(stats.requests, 0);
+}
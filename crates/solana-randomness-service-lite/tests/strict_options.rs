@@ -0,0 +1,185 @@
+//! `TransactionOptions::validate` and `instruction_strict` reject the options the getters clamp.
+
+mod common;
+
+use common::*;
+use solana_randomness_service_lite::*;
+
+fn options(compute_units: Option<u32>, compute_unit_price: Option<u64>) -> TransactionOptions {
+    TransactionOptions {
+        compute_units,
+        compute_unit_price,
+    }
+}
+
+/// Runs `f` with new, empty accounts as the request accounts.
+fn with_request<R>(f: impl FnOnce(&SimpleRandomnessV1Request) -> R) -> R {
+    let keys: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+    let mut lamports = [0u64; 8];
+    let mut data: Vec<Vec<u8>> = vec![Vec::new(); 8];
+    let owner = solana_program::system_program::ID;
+    let mut infos = keys
+        .iter()
+        .zip(lamports.iter_mut())
+        .zip(data.iter_mut())
+        .map(|((key, lamports), data)| {
+            AccountInfo::new(
+                key,
+                true,
+                true,
+                lamports,
+                data.as_mut_slice(),
+                &owner,
+                false,
+                0,
+            )
+        });
+    let request = SimpleRandomnessV1Request {
+        request: infos.next().unwrap(),
+        escrow: infos.next().unwrap(),
+        state: infos.next().unwrap(),
+        mint: infos.next().unwrap(),
+        payer: infos.next().unwrap(),
+        system_program: infos.next().unwrap(),
+        token_program: infos.next().unwrap(),
+        associated_token_program: infos.next().unwrap(),
+    };
+    f(&request)
+}
+
+#[test]
+fn test_validate_accepts_bounds() {
+    for compute_units in [
+        TransactionOptions::MINIMUM_COMPUTE_UNITS,
+        TransactionOptions::MAXIMUM_COMPUTE_UNITS,
+    ] {
+        for price in [
+            TransactionOptions::MINIMUM_COMPUTE_UNIT_PRICE,
+            TransactionOptions::MAXIMUM_COMPUTE_UNIT_PRICE,
+        ] {
+            assert_eq!(options(Some(compute_units), Some(price)).validate(), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn test_validate_accepts_unset_fields() {
+    // Both defaults are in range
+    assert_eq!(TransactionOptions::default().validate(), Ok(()));
+    assert_eq!(options(Some(300_000), None).validate(), Ok(()));
+    assert_eq!(options(None, Some(100)).validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_names_compute_units() {
+    for compute_units in [0, 199_999, 1_400_001, 2_000_000] {
+        assert_eq!(
+            options(Some(compute_units), None).validate(),
+            Err(TransactionOptionsError::ComputeUnitsOutOfRange {
+                value: compute_units,
+                min: 200_000,
+                max: 1_400_000,
+            })
+        );
+    }
+    // The getter still clamps
+    assert_eq!(
+        options(Some(2_000_000), None).get_compute_units(),
+        TransactionOptions::MAXIMUM_COMPUTE_UNITS
+    );
+}
+
+#[test]
+fn test_validate_names_compute_unit_price() {
+    for price in [0, 1_000_000_001] {
+        assert_eq!(
+            options(None, Some(price)).validate(),
+            Err(TransactionOptionsError::ComputeUnitPriceOutOfRange {
+                value: price,
+                min: 1,
+                max: 1_000_000_000,
+            })
+        );
+    }
+    assert_eq!(
+        options(None, Some(0)).get_compute_unit_price(),
+        TransactionOptions::MINIMUM_COMPUTE_UNIT_PRICE
+    );
+}
+
+#[test]
+fn test_validate_reports_compute_units_first() {
+    assert!(matches!(
+        options(Some(0), Some(0)).validate(),
+        Err(TransactionOptionsError::ComputeUnitsOutOfRange { .. })
+    ));
+}
+
+#[test]
+fn test_error_message() {
+    assert_eq!(
+        options(Some(2_000_000), None)
+            .validate()
+            .unwrap_err()
+            .to_string(),
+        "Compute units 2000000 are outside the accepted 200000 to 1400000"
+    );
+    assert_eq!(
+        ProgramError::from(options(None, Some(0)).validate().unwrap_err()),
+        ProgramError::from(LiteError::InvalidTransactionOptions)
+    );
+}
+
+#[test]
+fn test_instruction_strict() {
+    with_request(|request| {
+        let callback = callback();
+        let invalid: ProgramError = LiteError::InvalidTransactionOptions.into();
+
+        assert_eq!(
+            request
+                .instruction_strict(ID, 8, &callback, Some(&options(Some(2_000_000), None)))
+                .unwrap_err(),
+            invalid
+        );
+        assert_eq!(
+            request
+                .instruction_strict(ID, 8, &callback, Some(&options(None, Some(0))))
+                .unwrap_err(),
+            invalid
+        );
+        // The lenient path still builds, leaving the values to be clamped
+        assert!(request
+            .instruction(ID, 8, &callback, Some(&options(Some(2_000_000), None)))
+            .is_ok());
+
+        for valid in [
+            None,
+            Some(options(None, None)),
+            Some(options(Some(300_000), Some(50))),
+        ] {
+            assert_eq!(
+                request
+                    .instruction_strict(ID, 8, &callback, valid.as_ref())
+                    .unwrap()
+                    .data,
+                request
+                    .instruction(ID, 8, &callback, valid.as_ref())
+                    .unwrap()
+                    .data
+            );
+        }
+    });
+}
+
+#[test]
+fn test_instruction_strict_checks_num_bytes() {
+    with_request(|request| {
+        assert_eq!(
+            request
+                .instruction_strict(ID, 0, &callback(), None)
+                .unwrap_err(),
+            ProgramError::from(LiteError::InvalidNumBytes)
+        );
+    });
+}
@@ -0,0 +1,158 @@
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+
+fn memo_log(memo: &str) -> String {
+    format!("Program log: Memo (len {}): {:?}", memo.len(), memo)
+}
+
+fn transaction(log_messages: Vec<String>) -> TransactionRecord {
+    TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: 1,
+        failed: false,
+        account_keys: vec![ID],
+        log_messages,
+        token_balances: vec![],
+    }
+}
+
+#[test]
+fn test_tag_round_trips_with_version() {
+    let tag = RequestTag {
+        label: "raffle-42".to_string(),
+        version: Some(VersionCode::from_parts(0, 3)),
+    };
+
+    assert_eq!(tag.encode(), "rsl1:raffle-42;v=0003");
+    assert_eq!(RequestTag::decode(&tag.encode()), Some(tag.clone()));
+    assert_eq!(tag.version.unwrap().to_string(), "lite 0.3.x");
+}
+
+#[test]
+fn test_tag_round_trips_without_version() {
+    let tag = RequestTag {
+        label: "raffle-42".to_string(),
+        version: None,
+    };
+
+    assert_eq!(tag.encode(), "rsl1:raffle-42");
+    assert_eq!(RequestTag::decode(&tag.encode()), Some(tag));
+}
+
+#[test]
+fn test_decode_skips_reserved_sub_fields() {
+    assert_eq!(
+        RequestTag::decode("rsl1:raffle;x=1;v=0102"),
+        Some(RequestTag {
+            label: "raffle".to_string(),
+            version: Some(VersionCode::from_parts(1, 2)),
+        })
+    );
+    // A malformed version is treated as unrecorded
+    assert_eq!(
+        RequestTag::decode("rsl1:raffle;v=zz").unwrap().version,
+        None
+    );
+    assert_eq!(RequestTag::decode("hello"), None);
+}
+
+#[test]
+fn test_new_tag_records_version_with_feature() {
+    let tag = RequestTag::new("raffle").unwrap();
+
+    if cfg!(feature = "version-tag") {
+        assert_eq!(tag.version, Some(VersionCode::CURRENT));
+    } else {
+        assert_eq!(tag.version, None);
+    }
+}
+
+#[test]
+fn test_rejects_labels_that_do_not_fit_a_memo() {
+    assert!(RequestTag::new(&"a".repeat(RequestTag::MAX_LABEL_LEN)).is_ok());
+    for label in [
+        "a".repeat(RequestTag::MAX_LABEL_LEN + 1),
+        "raffle;v=0000".to_string(),
+        "raffle\"".to_string(),
+        "raffle\n".to_string(),
+    ] {
+        assert!(
+            matches!(RequestTag::new(&label), Err(LiteClientError::InvalidTag(_))),
+            "{:?}",
+            label
+        );
+    }
+}
+
+#[test]
+fn test_reads_tag_from_memo_log() {
+    let tag = RequestTag {
+        label: "raffle 7".to_string(),
+        version: Some(VersionCode::from_parts(1, 0)),
+    };
+    let transaction = transaction(vec![
+        format!("Program {} invoke [1]", ID),
+        format!("Program {} success", ID),
+        format!("Program {} invoke [1]", MEMO_PROGRAM_ID),
+        memo_log(&tag.encode()),
+        format!("Program {} success", MEMO_PROGRAM_ID),
+    ]);
+
+    assert_eq!(RequestTag::from_transaction(&transaction), Some(tag));
+    assert_eq!(
+        RequestTag::from_transaction(&self::transaction(vec![memo_log("unrelated")])),
+        None
+    );
+}
+
+#[test]
+fn test_builder_sends_tag_as_memo() {
+    let payer = Pubkey::new_unique();
+    let tag = RequestTag::new("raffle").unwrap();
+    let builder = RequestBuilder::new(payer, 8, Callback::default());
+
+    let untagged = builder.clone().build(Hash::new_unique()).unwrap();
+    assert_eq!(untagged.transaction.message.instructions.len(), 1);
+
+    let tagged = builder
+        .with_tag(tag.clone())
+        .build(Hash::new_unique())
+        .unwrap();
+    let message = &tagged.transaction.message;
+    assert_eq!(message.instructions.len(), 2);
+    let memo = &message.instructions[1];
+    assert_eq!(
+        message.account_keys[memo.program_id_index as usize],
+        MEMO_PROGRAM_ID
+    );
+    assert_eq!(memo.data, tag.encode().into_bytes());
+}
+
+#[test]
+fn test_version_code_matches_crate_version() {
+    assert_eq!(
+        VersionCode::parse(CRATE_VERSION),
+        Some(VersionCode::CURRENT)
+    );
+    assert_eq!(
+        VersionCode::parse("1.2.3"),
+        Some(VersionCode::from_parts(1, 2))
+    );
+    assert_eq!(VersionCode::parse("256.0.0"), None);
+    assert_eq!(VersionCode::parse("1"), None);
+}
+
+#[test]
+fn test_explain_includes_running_version() {
+    let code = LiteError::CallbackSignerOnCurve.code();
+    let explanation = explain_error_code(code).unwrap();
+
+    assert!(explanation.contains(describe_error_code(code).unwrap()));
+    assert!(explanation.ends_with(&format!(
+        "(solana-randomness-service-lite {})",
+        CRATE_VERSION
+    )));
+    assert_eq!(explain_error_code(0), None);
+}
@@ -0,0 +1,87 @@
+//! `parse_token_account` reads SPL token accounts of the classic token program and of
+//! Token-2022. The classic fixture is an initialized account with a delegate; the Token-2022
+//! fixture is a frozen account with the immutable owner and memo transfer extensions.
+
+use solana_randomness_service_lite::*;
+
+const CLASSIC: &[u8] = include_bytes!("fixtures/token_account_classic.bin");
+const TOKEN_2022: &[u8] = include_bytes!("fixtures/token_account_2022.bin");
+
+#[test]
+fn test_parses_classic_account() {
+    let view = parse_token_account(CLASSIC).unwrap();
+
+    assert_eq!(CLASSIC.len(), TOKEN_ACCOUNT_BASE_SIZE);
+    assert_eq!(view.mint, Pubkey::new_from_array([1; 32]));
+    assert_eq!(view.owner, Pubkey::new_from_array([2; 32]));
+    assert_eq!(view.amount, 1_500_000);
+    assert_eq!(view.delegate, Some(Pubkey::new_from_array([3; 32])));
+    assert_eq!(view.delegated_amount, 250_000);
+    assert_eq!(view.state, TokenAccountStatus::Initialized);
+}
+
+#[test]
+fn test_parses_token_2022_account_with_extensions() {
+    let view = parse_token_account(TOKEN_2022).unwrap();
+
+    assert!(TOKEN_2022.len() > TOKEN_ACCOUNT_BASE_SIZE);
+    assert_eq!(view.mint, Pubkey::new_from_array([1; 32]));
+    assert_eq!(view.owner, Pubkey::new_from_array([2; 32]));
+    assert_eq!(view.amount, 1_500_000);
+    assert_eq!(view.delegate, None);
+    assert_eq!(view.state, TokenAccountStatus::Frozen);
+}
+
+#[test]
+fn test_rejects_truncated_data() {
+    for len in [0, 72, TOKEN_ACCOUNT_BASE_SIZE - 1] {
+        assert_eq!(
+            parse_token_account(&CLASSIC[..len]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
+
+#[test]
+fn test_rejects_extended_data_of_another_account_type() {
+    // A Token-2022 mint stores account type 1 at the same offset
+    let mut data = TOKEN_2022.to_vec();
+    data[TOKEN_ACCOUNT_BASE_SIZE] = 1;
+    assert_eq!(
+        parse_token_account(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    // A multisig
+    let mut data = CLASSIC.to_vec();
+    data.resize(355, 0);
+    data[TOKEN_ACCOUNT_BASE_SIZE] = 2;
+    assert_eq!(
+        parse_token_account(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn test_rejects_uninitialized_and_corrupt_accounts() {
+    let mut data = CLASSIC.to_vec();
+    data[108] = 0;
+    assert_eq!(
+        parse_token_account(&data),
+        Err(ProgramError::UninitializedAccount)
+    );
+
+    data[108] = 3;
+    assert_eq!(
+        parse_token_account(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    // An option tag other than 0 or 1
+    let mut data = CLASSIC.to_vec();
+    data[72] = 2;
+    assert_eq!(
+        parse_token_account(&data),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
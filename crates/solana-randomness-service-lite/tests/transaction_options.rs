@@ -0,0 +1,95 @@
+use borsh::BorshDeserialize;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+
+fn callback(accounts: usize, ix_data_len: usize) -> Callback {
+    Callback::new(
+        Pubkey::new_unique(),
+        (0..accounts)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false).into())
+            .collect(),
+        vec![0; ix_data_len],
+    )
+}
+
+fn advised_units(accounts: usize, ix_data_len: usize) -> u32 {
+    TransactionOptions::advised_for_callback(&callback(accounts, ix_data_len))
+        .compute_units
+        .unwrap()
+}
+
+/// Pins the heuristic. Update these deliberately when the formula changes.
+#[test]
+fn test_advised_compute_units() {
+    // The example consumer's callback: state and request, plus the 8 byte discriminator
+    assert_eq!(
+        advised_units(2, 8),
+        TransactionOptions::MINIMUM_COMPUTE_UNITS
+    );
+    // A lootbox style callback with token accounts
+    assert_eq!(advised_units(7, 8), 213_500);
+    assert_eq!(advised_units(10, 100), 262_500);
+    assert_eq!(advised_units(Callback::MAX_ACCOUNTS, 1024), 465_500);
+    // The largest callback that can be requested is clamped
+    assert_eq!(
+        advised_units(Callback::MAX_ACCOUNTS, Callback::MAX_IX_DATA_LEN),
+        TransactionOptions::MAXIMUM_COMPUTE_UNITS
+    );
+}
+
+#[test]
+fn test_advised_compute_units_are_clamped() {
+    assert_eq!(
+        advised_units(0, 0),
+        TransactionOptions::MINIMUM_COMPUTE_UNITS
+    );
+    assert_eq!(
+        advised_units(200, 10_000),
+        TransactionOptions::MAXIMUM_COMPUTE_UNITS
+    );
+}
+
+#[test]
+fn test_advised_options_keep_default_price() {
+    let options = TransactionOptions::advised_for_callback(&callback(2, 8));
+    assert_eq!(options.compute_unit_price, None);
+    assert_eq!(
+        options.get_compute_unit_price(),
+        TransactionOptions::DEFAULT_COMPUTE_UNIT_PRICE
+    );
+}
+
+/// The trailing `Option<TransactionOptions>` of the request instruction data.
+fn sent_options(builder: &RequestBuilder) -> Option<TransactionOptions> {
+    let data = builder.instruction(&Pubkey::new_unique()).unwrap().data;
+    let callback = builder_callback();
+    // Discriminator, num_bytes, then the callback
+    let options_offset = 8 + 1 + callback.to_vec().unwrap().len();
+    Option::<TransactionOptions>::try_from_slice(&data[options_offset..]).unwrap()
+}
+
+fn builder_callback() -> Callback {
+    Callback::new(Pubkey::default(), vec![], vec![1; 8])
+}
+
+#[test]
+fn test_builder_defaults_to_advised_options() {
+    let builder = RequestBuilder::new(Pubkey::new_unique(), 8, builder_callback());
+    let advised = TransactionOptions::advised_for_callback(&builder_callback());
+
+    let sent = sent_options(&builder).unwrap();
+    assert_eq!(sent.compute_units, advised.compute_units);
+    assert_eq!(
+        builder.funding().unwrap().priority_fee,
+        advised.get_priority_fee_micro_lamports().unwrap() / 1_000_000
+    );
+
+    // Options set by the caller are sent as is
+    let builder = builder.options(Some(TransactionOptions {
+        compute_units: Some(300_000),
+        compute_unit_price: Some(100),
+    }));
+    let sent = sent_options(&builder).unwrap();
+    assert_eq!(sent.compute_units, Some(300_000));
+    assert_eq!(sent.compute_unit_price, Some(100));
+}
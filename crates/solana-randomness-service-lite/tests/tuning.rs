@@ -0,0 +1,149 @@
+//! `TuningStore` learns the compute units callbacks consume from their settle transactions and
+//! advises the compute budget of later requests from them.
+
+mod common;
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use common::*;
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+fn callback(name: &str) -> Callback {
+    callback_with_ix_data(anchor_sighash(name).to_vec())
+}
+
+/// A settle transaction whose callback the runtime reports as consuming `units`.
+fn fulfillment(callback: &Callback, units: u64) -> TransactionRecord {
+    TransactionRecord {
+        signature: Signature::new_unique(),
+        slot: 1,
+        failed: false,
+        account_keys: vec![ID, callback.program_id],
+        log_messages: vec![
+            format!("Program {} invoke [1]", ID),
+            format!("Program {} invoke [2]", callback.program_id),
+            format!(
+                "Program {} consumed {} of 1380000 compute units",
+                callback.program_id, units
+            ),
+            format!("Program {} success", callback.program_id),
+            format!("Program {} consumed 140000 of 1400000 compute units", ID),
+            format!("Program {} success", ID),
+        ],
+        token_balances: vec![],
+    }
+}
+
+fn usage_log(usage: &CallbackComputeUsage) -> String {
+    let mut data = CallbackComputeUsage::DISCRIMINATOR.to_vec();
+    data.extend(usage.try_to_vec().unwrap());
+    format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    )
+}
+
+/// The advice for a callback observed consuming at most `units`.
+fn advised(units: u64) -> u64 {
+    (u64::from(TransactionOptions::ADVISED_BASE_COMPUTE_UNITS) + units)
+        * u64::from(100 + TransactionOptions::ADVISED_HEADROOM_PERCENT)
+        / 100
+}
+
+#[test]
+fn test_reads_units_from_runtime_log() {
+    let callback = callback("consume_randomness");
+    assert_eq!(
+        callback_compute_units(&fulfillment(&callback, 81_234), &callback),
+        Some(81_234)
+    );
+    assert_eq!(
+        callback_compute_units(&fulfillment(&callback, 81_234), &self::callback("other")),
+        None
+    );
+}
+
+#[test]
+fn test_prefers_logged_usage() {
+    assert_eq!(
+        CallbackComputeUsage::DISCRIMINATOR,
+        anchor_event_discriminator("CallbackComputeUsage")
+    );
+    let callback = callback("consume_randomness");
+    let mut transaction = fulfillment(&callback, 90_000);
+    transaction.log_messages.insert(
+        2,
+        usage_log(&CallbackComputeUsage {
+            request: Pubkey::new_unique(),
+            discriminator: callback.discriminator().unwrap(),
+            units_consumed: 72_000,
+        }),
+    );
+
+    assert_eq!(
+        callback_compute_units(&transaction, &callback),
+        Some(72_000)
+    );
+}
+
+#[test]
+fn test_advice_converges_to_observed_usage() {
+    let store = TuningStore::new();
+    let callback = callback("consume_randomness");
+    let estimate = TransactionOptions::advised_for_callback(&callback);
+
+    // Too few samples to advise
+    for units in [410_000, 395_000] {
+        store.observe(&fulfillment(&callback, units), &callback);
+    }
+    assert_eq!(
+        TransactionOptions::advised_for_callback_with(&callback, &store).compute_units,
+        estimate.compute_units
+    );
+
+    for i in 0..10 {
+        store.observe(&fulfillment(&callback, 400_000 + i * 1_000), &callback);
+    }
+    let stats = store.stats(&callback.discriminator().unwrap()).unwrap();
+    assert_eq!(stats.samples, 12);
+    assert_eq!(stats.recent_max, 410_000);
+    assert_eq!(
+        TransactionOptions::advised_for_callback_with(&callback, &store).compute_units,
+        Some(advised(410_000) as u32)
+    );
+
+    // The callback gets cheaper; the advice follows once the window has passed
+    for _ in 0..TuningStore::WINDOW {
+        store.observe(&fulfillment(&callback, 600_000 - 450_000), &callback);
+    }
+    assert_eq!(
+        store.advised_units(&callback.discriminator().unwrap()),
+        Some(advised(150_000))
+    );
+    assert_eq!(
+        TransactionOptions::advised_for_callback_with(&callback, &store).compute_units,
+        Some(advised(150_000) as u32)
+    );
+}
+
+#[test]
+fn test_advice_is_per_discriminator_and_clamped() {
+    let store = TuningStore::new();
+    let cheap = callback("cheap");
+    let heavy = callback("heavy");
+    for _ in 0..TuningStore::MIN_SAMPLES {
+        store.observe(&fulfillment(&cheap, 5_000), &cheap);
+        store.observe(&fulfillment(&heavy, 2_000_000), &heavy);
+    }
+
+    assert_eq!(
+        TransactionOptions::advised_for_callback_with(&cheap, &store).compute_units,
+        Some(TransactionOptions::MINIMUM_COMPUTE_UNITS)
+    );
+    assert_eq!(
+        TransactionOptions::advised_for_callback_with(&heavy, &store).compute_units,
+        Some(TransactionOptions::MAXIMUM_COMPUTE_UNITS)
+    );
+}
@@ -0,0 +1,181 @@
+//! Downstream code matching every `#[non_exhaustive]` enum of the crate, as the crate asks
+//! for: named arms for the variants it cares about and a wildcard for the rest. Built with the
+//! `test-variants` feature, which adds a variant to some of the enums.
+
+use solana_randomness_service_lite::client::*;
+use solana_randomness_service_lite::*;
+use solana_sdk::signature::Signature;
+
+fn describe_status(status: &RequestStatus) -> &'static str {
+    match status {
+        RequestStatus::Pending(_) => "pending",
+        RequestStatus::Failed(_) => "failed",
+        RequestStatus::Closed => "closed",
+        _ => "unknown",
+    }
+}
+
+fn describe_outcome(outcome: &FulfillmentOutcome) -> &'static str {
+    match outcome {
+        FulfillmentOutcome::Fulfilled {
+            is_success: true, ..
+        } => "fulfilled",
+        FulfillmentOutcome::Fulfilled { .. } => "callback failed",
+        FulfillmentOutcome::Failed(_) => "failed",
+        FulfillmentOutcome::ClosedBeforeFulfillment => "closed",
+        _ => "unknown",
+    }
+}
+
+fn is_retryable(error: &LiteClientError) -> bool {
+    match error {
+        LiteClientError::Rpc(_) | LiteClientError::Timeout => true,
+        _ => false,
+    }
+}
+
+fn is_await_timeout(error: &AwaitError) -> bool {
+    match error {
+        AwaitError::Timeout { .. } => true,
+        _ => false,
+    }
+}
+
+fn failed_batch_index(error: &BatchAwaitError) -> Option<usize> {
+    match error {
+        BatchAwaitError::Failed { index, .. } => Some(*index),
+        _ => None,
+    }
+}
+
+fn is_snapshot_io(error: &SnapshotError) -> bool {
+    match error {
+        SnapshotError::Io(_) => true,
+        _ => false,
+    }
+}
+
+fn is_overrides_io(error: &OverridesError) -> bool {
+    match error {
+        OverridesError::Io(_) => true,
+        _ => false,
+    }
+}
+
+fn is_fingerprint_changed(error: &FingerprintError) -> bool {
+    match error {
+        FingerprintError::Changed { .. } => true,
+        _ => false,
+    }
+}
+
+fn is_sent(stage: AuditStage) -> bool {
+    match stage {
+        AuditStage::Sent => true,
+        _ => false,
+    }
+}
+
+fn scan_depth(strategy: DiscoveryStrategy) -> Option<usize> {
+    match strategy {
+        DiscoveryStrategy::SignatureScan { depth } => Some(depth),
+        _ => None,
+    }
+}
+
+fn is_new_request(send: &IdempotentSend) -> bool {
+    match send {
+        IdempotentSend::Sent(_) => true,
+        _ => false,
+    }
+}
+
+fn is_settled(kind: FulfillmentKind) -> bool {
+    match kind {
+        FulfillmentKind::Settled => true,
+        _ => false,
+    }
+}
+
+fn is_resolved(status: PaymentStatus) -> bool {
+    match status {
+        PaymentStatus::Matched | PaymentStatus::AmountMismatch => true,
+        _ => false,
+    }
+}
+
+fn is_state_mismatch(error: &AddressError) -> bool {
+    match error {
+        AddressError::StateMismatch { .. } => true,
+        _ => false,
+    }
+}
+
+fn is_request_account_error(error: &LiteError) -> bool {
+    match error {
+        LiteError::RequestAccountNotEmpty | LiteError::RequestAccountOwnedByProgram => true,
+        _ => false,
+    }
+}
+
+fn is_funding_error(error: ServiceError) -> bool {
+    match error {
+        ServiceError::InsufficientFunds | ServiceError::InvalidEscrow => true,
+        _ => false,
+    }
+}
+
+fn main() {
+    let request = Pubkey::new_unique();
+    let signature = Signature::new_unique();
+
+    let outcome = FulfillmentOutcome::fulfilled(&request, vec![1; 8], 10, signature, true);
+    assert_eq!(describe_outcome(&outcome), "fulfilled");
+    assert_eq!(outcome.randomness(), Some(&[1; 8][..]));
+    assert_eq!(outcome.signature(), Some(&signature));
+    assert_eq!(
+        outcome.event_id(),
+        Some(fulfillment_event_id(&request, &signature))
+    );
+    let failed_callback = FulfillmentOutcome::fulfilled(&request, vec![1; 8], 10, signature, false);
+    assert_eq!(describe_outcome(&failed_callback), "callback failed");
+    assert!(!failed_callback.is_success());
+
+    assert_eq!(describe_status(&RequestStatus::Closed), "closed");
+    assert!(RequestStatus::Closed.request().is_none());
+    assert!(is_retryable(&LiteClientError::Timeout));
+    assert!(is_await_timeout(&AwaitError::Timeout { last_status: None }));
+    assert_eq!(
+        failed_batch_index(&BatchAwaitError::Failed { index: 2, request }),
+        Some(2)
+    );
+    assert!(!is_snapshot_io(&SnapshotError::InvalidMagic));
+    assert!(!is_overrides_io(&OverridesError::InvalidAddress {
+        name: "program_id".to_string(),
+        value: "?".to_string(),
+    }));
+    assert!(!is_fingerprint_changed(&FingerprintError::Client(
+        LiteClientError::Timeout
+    )));
+    assert!(is_sent(AuditStage::Sent));
+    assert_eq!(
+        scan_depth(DiscoveryStrategy::SignatureScan { depth: 2 }),
+        Some(2)
+    );
+    assert!(!is_new_request(&IdempotentSend::Existing(request)));
+    assert!(is_settled(FulfillmentKind::Settled));
+    assert!(is_resolved(PaymentStatus::Matched));
+    assert!(!is_state_mismatch(&AddressError::RewardMintNotNative(
+        request
+    )));
+    assert!(is_request_account_error(&LiteError::RequestAccountNotEmpty));
+    assert!(is_funding_error(ServiceError::InsufficientFunds));
+
+    // The variants added by `test-variants` land in the wildcard arms
+    assert_eq!(
+        describe_outcome(&FulfillmentOutcome::__TestVariant),
+        "unknown"
+    );
+    assert_eq!(describe_status(&RequestStatus::__TestVariant), "unknown");
+    assert!(!is_retryable(&LiteClientError::__TestVariant));
+}
@@ -0,0 +1,151 @@
+//! `verify_callback` over the accounts a callback receives, as the service passes them and as
+//! an attacker could forge them.
+
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+const CONSUMER: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn request_data(callback_program_id: Pubkey) -> Vec<u8> {
+    let request = SimpleRandomnessV1Account {
+        num_bytes: 8,
+        user: Pubkey::new_unique(),
+        callback: Callback::new(callback_program_id, vec![], vec![1; 8]),
+        ..Default::default()
+    };
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    request.serialize(&mut data).unwrap();
+    data
+}
+
+/// The accounts of one callback invocation, valid unless a test changes them.
+struct Accounts {
+    state_key: Pubkey,
+    state_signer: bool,
+    state_owner: Pubkey,
+    request_key: Pubkey,
+    request_owner: Pubkey,
+    request_data: Vec<u8>,
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self {
+            state_key: RANDOMNESS_SERVICE_STATE,
+            state_signer: true,
+            state_owner: ID,
+            request_key: Pubkey::new_unique(),
+            request_owner: ID,
+            request_data: request_data(CONSUMER),
+        }
+    }
+}
+
+impl Accounts {
+    fn verify(mut self) -> Result<(), ProgramError> {
+        let (mut state_lamports, mut request_lamports) = (0, 0);
+        let mut state_data = vec![0; ServiceState::LEN];
+        let state = AccountInfo::new(
+            &self.state_key,
+            self.state_signer,
+            false,
+            &mut state_lamports,
+            &mut state_data,
+            &self.state_owner,
+            false,
+            0,
+        );
+        let request = AccountInfo::new(
+            &self.request_key,
+            false,
+            false,
+            &mut request_lamports,
+            &mut self.request_data,
+            &self.request_owner,
+            false,
+            0,
+        );
+        verify_callback(&state, &request, &CONSUMER)
+    }
+}
+
+fn lite_error(result: Result<(), ProgramError>) -> Option<LiteError> {
+    match result {
+        Err(ProgramError::Custom(code)) => LiteError::from_code(code),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_accepts_the_service_callback() {
+    assert_eq!(Accounts::default().verify(), Ok(()));
+}
+
+#[test]
+fn test_rejects_an_unsigned_state() {
+    let accounts = Accounts {
+        state_signer: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        lite_error(accounts.verify()),
+        Some(LiteError::CallbackStateNotSigner)
+    );
+}
+
+#[test]
+fn test_rejects_a_forged_state() {
+    // A keypair the caller controls can sign, but is not the service's PDA
+    let accounts = Accounts {
+        state_key: Pubkey::new_unique(),
+        state_owner: Pubkey::new_unique(),
+        ..Default::default()
+    };
+    assert_eq!(accounts.verify(), Err(ProgramError::IllegalOwner));
+
+    let accounts = Accounts {
+        state_key: Pubkey::new_unique(),
+        ..Default::default()
+    };
+    assert_eq!(accounts.verify(), Err(ProgramError::InvalidSeeds));
+}
+
+#[test]
+fn test_rejects_a_forged_request() {
+    let accounts = Accounts {
+        request_owner: CONSUMER,
+        ..Default::default()
+    };
+    assert_eq!(accounts.verify(), Err(ProgramError::IllegalOwner));
+
+    let mut data = request_data(CONSUMER);
+    data[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+    let accounts = Accounts {
+        request_data: data,
+        ..Default::default()
+    };
+    assert_eq!(accounts.verify(), Err(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn test_rejects_another_programs_request() {
+    let accounts = Accounts {
+        request_data: request_data(Pubkey::new_unique()),
+        ..Default::default()
+    };
+    assert_eq!(
+        lite_error(accounts.verify()),
+        Some(LiteError::CallbackProgramMismatch)
+    );
+}
+
+#[test]
+fn test_rejects_a_request_without_a_callback() {
+    let mut data = request_data(CONSUMER);
+    data.truncate(RequestHeader::LEN + 31);
+    let accounts = Accounts {
+        request_data: data,
+        ..Default::default()
+    };
+    assert_eq!(accounts.verify(), Err(ProgramError::AccountDataTooSmall));
+}
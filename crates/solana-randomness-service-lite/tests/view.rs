@@ -0,0 +1,98 @@
+use borsh::BorshSerialize;
+use solana_randomness_service_lite::*;
+
+fn request() -> SimpleRandomnessV1Account {
+    SimpleRandomnessV1Account {
+        num_bytes: 32,
+        user: Pubkey::new_unique(),
+        escrow: Pubkey::new_unique(),
+        request_slot: 250_000_000,
+        callback: Callback::new(Pubkey::new_unique(), vec![], vec![1; 8]),
+        ..Default::default()
+    }
+}
+
+fn account_data(request: &SimpleRandomnessV1Account) -> Vec<u8> {
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    request.serialize(&mut data).unwrap();
+    data.resize(request.expected_request_account_size(), 0);
+    data
+}
+
+#[test]
+fn test_reads_header_fields() {
+    let request = request();
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = account_data(&request);
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &ID, false, 0);
+
+    let view = SimpleRandomnessV1AccountView::load(&info).unwrap();
+
+    assert_eq!(view.user(), request.user);
+    assert_eq!(view.escrow(), request.escrow);
+    assert_eq!(view.request_slot(), request.request_slot);
+    assert_eq!(view.num_bytes(), request.num_bytes);
+    assert!(!view.is_completed());
+    assert_eq!(
+        view.header(),
+        RequestHeader::read(&account_data(&request)).unwrap()
+    );
+}
+
+#[test]
+fn test_view_borrows_account_data() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = account_data(&request());
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &ID, false, 0);
+
+    let view = SimpleRandomnessV1AccountView::load(&info).unwrap();
+    assert!(info.try_borrow_mut_data().is_err());
+    drop(view);
+    assert!(info.try_borrow_mut_data().is_ok());
+}
+
+#[test]
+fn test_rejects_other_owners() {
+    let key = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = account_data(&request());
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+
+    assert_eq!(
+        SimpleRandomnessV1AccountView::load(&info).err(),
+        Some(ProgramError::IllegalOwner)
+    );
+}
+
+#[test]
+fn test_rejects_other_account_types_and_short_data() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mut data = account_data(&request());
+    data[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &ID, false, 0);
+    assert_eq!(
+        SimpleRandomnessV1AccountView::load(&info).err(),
+        Some(ProgramError::InvalidAccountData)
+    );
+
+    let mut lamports = 0;
+    let mut data = account_data(&request())[..RequestHeader::LEN - 1].to_vec();
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &ID, false, 0);
+    assert_eq!(
+        SimpleRandomnessV1AccountView::load(&info).err(),
+        Some(ProgramError::AccountDataTooSmall)
+    );
+}
@@ -0,0 +1,415 @@
+use crate::*;
+use solana_randomness_service_lite::{
+    randomness_from_arg, Callback as LiteCallback, SimpleRandomnessV1Request,
+    TransactionOptions as LiteTransactionOptions,
+};
+
+/// The compute budget requested for the tie break callback, which only writes one account.
+pub const TIE_BREAK_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// The randomness bytes requested for a tie break, read as one little endian `u64`.
+pub const TIE_BREAK_RANDOMNESS_BYTES: usize = 8;
+
+/// The most bidders one tie break can choose between.
+pub const TIE_BREAK_MAX_BIDDERS: usize = 8;
+
+/// How long a tie break can wait on its request or on its authority before anyone can
+/// reclaim it, about as long as the request transaction's blockhash is valid.
+pub const TIE_BREAK_TIMEOUT_SLOTS: u64 = 150;
+
+/// Where a tie break is in its flow. The instructions only change it through the transition
+/// methods, each of which checks the state it starts from and fails with its own error
+/// otherwise, so a replayed or out of order instruction cannot skip a step.
+///
+/// ```text
+/// Open --request--> AwaitingRandomness --callback--> RandomnessReceived --settle--> Settled
+///  ^                        |                                |
+///  +---reclaim (timeout)----+                                +--reclaim (timeout)--> Settled
+/// ```
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum TieBreakState {
+    /// The tied bidders are recorded and no randomness is requested yet.
+    Open,
+    /// Waiting for the callback of `request`, made at `request_slot`.
+    AwaitingRandomness { request: Pubkey, request_slot: u64 },
+    /// The callback delivered the randomness at `slot`. The winner follows from the bytes but
+    /// is only recorded by settling, so the callback does as little as possible.
+    RandomnessReceived { bytes: [u8; 8], slot: u64 },
+    /// The winner is decided. No transition leaves this state.
+    Settled { winner: Pubkey },
+}
+
+impl TieBreakState {
+    /// `Open` to `AwaitingRandomness`.
+    pub fn request(&mut self, request: Pubkey, slot: u64) -> anchor_lang::prelude::Result<()> {
+        require!(*self == Self::Open, ConsumerError::TieBreakNotOpen);
+        *self = Self::AwaitingRandomness {
+            request,
+            request_slot: slot,
+        };
+        Ok(())
+    }
+
+    /// `AwaitingRandomness` to `RandomnessReceived`, for the awaited request only, so the late
+    /// callback of a reclaimed request cannot deliver its bytes.
+    pub fn receive(
+        &mut self,
+        request: &Pubkey,
+        bytes: [u8; TIE_BREAK_RANDOMNESS_BYTES],
+        slot: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        let Self::AwaitingRandomness {
+            request: awaited, ..
+        } = *self
+        else {
+            return err!(ConsumerError::TieBreakNotAwaitingRandomness);
+        };
+        require_keys_eq!(*request, awaited, ConsumerError::UnexpectedTieBreakRequest);
+        *self = Self::RandomnessReceived { bytes, slot };
+        Ok(())
+    }
+
+    /// `RandomnessReceived` to `Settled`, returning the winner picked from `bidders`.
+    pub fn settle(&mut self, bidders: &[Pubkey]) -> anchor_lang::prelude::Result<Pubkey> {
+        let bytes = match *self {
+            Self::RandomnessReceived { bytes, .. } => bytes,
+            Self::Settled { .. } => return err!(ConsumerError::TieBreakAlreadySettled),
+            _ => return err!(ConsumerError::TieBreakRandomnessNotReceived),
+        };
+        let winner = pick_winner(bidders, bytes);
+        *self = Self::Settled { winner };
+        Ok(winner)
+    }
+
+    /// Moves a tie break that waited [`TIE_BREAK_TIMEOUT_SLOTS`] on someone else along:
+    ///
+    /// - `AwaitingRandomness` back to `Open`, when the request errored or was never fulfilled,
+    ///   so the authority can request again.
+    /// - `RandomnessReceived` to `Settled`, when the authority did not settle, so an authority
+    ///   who dislikes the winner cannot hold the auction up. Returns the winner.
+    ///
+    /// An `Open` tie break waits on nobody but its authority, so it has nothing to reclaim.
+    pub fn reclaim(
+        &mut self,
+        bidders: &[Pubkey],
+        slot: u64,
+    ) -> anchor_lang::prelude::Result<Option<Pubkey>> {
+        let since = match *self {
+            Self::AwaitingRandomness { request_slot, .. } => request_slot,
+            Self::RandomnessReceived { slot: received, .. } => received,
+            Self::Open => return err!(ConsumerError::TieBreakNothingToReclaim),
+            Self::Settled { .. } => return err!(ConsumerError::TieBreakAlreadySettled),
+        };
+        require_gte!(
+            slot.saturating_sub(since),
+            TIE_BREAK_TIMEOUT_SLOTS,
+            ConsumerError::TieBreakReclaimTooEarly
+        );
+
+        if let Self::AwaitingRandomness { .. } = self {
+            *self = Self::Open;
+            return Ok(None);
+        }
+        self.settle(bidders).map(Some)
+    }
+}
+
+/// Picks the bidder at the randomness, read as a little endian `u64`, modulo the number of
+/// bidders. With at most [`TIE_BREAK_MAX_BIDDERS`], the modulo bias is below 2^-60.
+pub fn pick_winner(bidders: &[Pubkey], bytes: [u8; TIE_BREAK_RANDOMNESS_BYTES]) -> Pubkey {
+    let index = u64::from_le_bytes(bytes) % bidders.len() as u64;
+    bidders[index as usize]
+}
+
+/// An auction's tie between bidders with equal bids, broken by randomness. One PDA per
+/// authority and auction id.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct TieBreak {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The auctioneer, who pays for the request and settles the tie break.
+    pub authority: Pubkey,
+    /// The auction the tie break belongs to.
+    pub id: u64,
+    /// The number of valid entries in `bidders`.
+    pub bidder_count: u8,
+    /// The tied bidders, padded with the default pubkey. Holds `TIE_BREAK_MAX_BIDDERS`.
+    pub bidders: [Pubkey; 8],
+    pub state: TieBreakState,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl TieBreak {
+    pub const SEED: &'static [u8] = b"TIE_BREAK";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Fails with `InvalidBidderCount` unless there are 2 to [`TIE_BREAK_MAX_BIDDERS`]
+    /// bidders.
+    pub fn check_bidders(bidders: &[Pubkey]) -> anchor_lang::prelude::Result<()> {
+        require!(
+            (2..=TIE_BREAK_MAX_BIDDERS).contains(&bidders.len()),
+            ConsumerError::InvalidBidderCount
+        );
+        Ok(())
+    }
+
+    /// The tied bidders.
+    pub fn bidders(&self) -> &[Pubkey] {
+        &self.bidders[..usize::from(self.bidder_count)]
+    }
+
+    /// Settles the state, see [`TieBreakState::settle`].
+    pub fn settle(&mut self) -> anchor_lang::prelude::Result<Pubkey> {
+        let bidders = self.bidders;
+        let count = usize::from(self.bidder_count);
+        self.state.settle(&bidders[..count])
+    }
+
+    /// Reclaims the state at `slot`, see [`TieBreakState::reclaim`].
+    pub fn reclaim(&mut self, slot: u64) -> anchor_lang::prelude::Result<Option<Pubkey>> {
+        let bidders = self.bidders;
+        let count = usize::from(self.bidder_count);
+        self.state.reclaim(&bidders[..count], slot)
+    }
+}
+
+/// Emitted when a tie break is settled, by its authority or by a reclaim.
+#[event]
+pub struct TieBreakSettled {
+    pub tie_break: Pubkey,
+    pub winner: Pubkey,
+    /// Whether the tie break was settled by a reclaim after its authority did not.
+    pub reclaimed: bool,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct InitializeTieBreak<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = TieBreak::SPACE,
+        seeds = [TieBreak::SEED, authority.key().as_ref(), &id.to_le_bytes()],
+        bump,
+    )]
+    pub tie_break: Box<Account<'info, TieBreak>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeTieBreak<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        id: u64,
+        bidders: Vec<Pubkey>,
+    ) -> anchor_lang::prelude::Result<()> {
+        TieBreak::check_bidders(&bidders)?;
+
+        let tie_break = &mut ctx.accounts.tie_break;
+        tie_break.bump = ctx.bumps.tie_break;
+        tie_break.authority = ctx.accounts.authority.key();
+        tie_break.id = id;
+        tie_break.bidder_count = bidders.len() as u8;
+        tie_break.bidders[..bidders.len()].copy_from_slice(&bidders);
+        tie_break.state = TieBreakState::Open;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RequestTieBreakRandomness<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [TieBreak::SEED, authority.key().as_ref(), &tie_break.id.to_le_bytes()],
+        bump = tie_break.bump,
+    )]
+    pub tie_break: Box<Account<'info, TieBreak>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The tie break's authority. Pays for the request.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RequestTieBreakRandomness<'_> {
+    /// Moves the tie break to `AwaitingRandomness` and invokes the service. A failed CPI
+    /// fails the transaction, so the tie break is never left awaiting a request that does not
+    /// exist.
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        let slot = Clock::get()?.slot;
+        let request_key = ctx.accounts.randomness_request.key();
+        ctx.accounts.tie_break.state.request(request_key, slot)?;
+
+        // Must match the order of the ConsumeTieBreakRandomness accounts struct
+        let (callback, _) = LiteCallback::builder(ID)
+            .with_state_signer()
+            .with_readonly_account(request_key)
+            .with_writable_account(ctx.accounts.tie_break.key())
+            .with_ix_data(get_ixn_discriminator("consume_tie_break_randomness").to_vec())
+            .build()?;
+        let options = LiteTransactionOptions {
+            compute_units: Some(TIE_BREAK_CALLBACK_COMPUTE_UNITS),
+            compute_unit_price: Some(100),
+        };
+
+        let request = SimpleRandomnessV1Request {
+            request: ctx.accounts.randomness_request.to_account_info(),
+            escrow: ctx.accounts.randomness_escrow.to_account_info(),
+            state: ctx.accounts.randomness_state.to_account_info(),
+            mint: ctx.accounts.randomness_mint.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        let program = ctx.accounts.randomness_service.to_account_info();
+        request.cpi(
+            program,
+            TIE_BREAK_RANDOMNESS_BYTES as u8,
+            &callback,
+            Some(&options),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeTieBreakRandomness<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: the view checks the owner and discriminator
+    pub request: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [TieBreak::SEED, tie_break.authority.as_ref(), &tie_break.id.to_le_bytes()],
+        bump = tie_break.bump,
+    )]
+    pub tie_break: Box<Account<'info, TieBreak>>,
+}
+
+impl ConsumeTieBreakRandomness<'_> {
+    /// Only records the randomness. Picking the winner is left to `settle_tie_break`, so the
+    /// callback cannot fail on anything but the delivery itself and leave the request
+    /// unsettled.
+    pub fn actuate(ctx: &mut Context<Self>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+        SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+        let mut bytes = [0; TIE_BREAK_RANDOMNESS_BYTES];
+        bytes.copy_from_slice(randomness_from_arg(&result, TIE_BREAK_RANDOMNESS_BYTES)?);
+
+        let slot = Clock::get()?.slot;
+        ctx.accounts
+            .tie_break
+            .state
+            .receive(&ctx.accounts.request.key(), bytes, slot)
+    }
+}
+
+#[derive(Accounts)]
+pub struct SettleTieBreak<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [TieBreak::SEED, authority.key().as_ref(), &tie_break.id.to_le_bytes()],
+        bump = tie_break.bump,
+    )]
+    pub tie_break: Box<Account<'info, TieBreak>>,
+
+    pub authority: Signer<'info>,
+}
+
+impl SettleTieBreak<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        let winner = ctx.accounts.tie_break.settle()?;
+        emit!(TieBreakSettled {
+            tie_break: ctx.accounts.tie_break.key(),
+            winner,
+            reclaimed: false,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReclaimTieBreak<'info> {
+    #[account(
+        mut,
+        seeds = [TieBreak::SEED, tie_break.authority.as_ref(), &tie_break.id.to_le_bytes()],
+        bump = tie_break.bump,
+    )]
+    pub tie_break: Box<Account<'info, TieBreak>>,
+
+    /// Anyone can reclaim a tie break that timed out, e.g. a bidder.
+    pub caller: Signer<'info>,
+}
+
+impl ReclaimTieBreak<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        let slot = Clock::get()?.slot;
+        if let Some(winner) = ctx.accounts.tie_break.reclaim(slot)? {
+            emit!(TieBreakSettled {
+                tie_break: ctx.accounts.tie_break.key(),
+                winner,
+                reclaimed: true,
+            });
+        }
+
+        Ok(())
+    }
+}
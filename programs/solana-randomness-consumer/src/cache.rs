@@ -0,0 +1,295 @@
+use crate::*;
+
+/// The compute budget requested for the cache callback, which only writes one account.
+pub const CACHE_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// How long a refresh stays pending before `get_or_request` gives up on it and requests
+/// again, about as long as the request transaction's blockhash is valid. Covers requests that
+/// errored or were never fulfilled.
+pub const CACHE_REFRESH_TIMEOUT_SLOTS: u64 = 150;
+
+/// A recent randomness result that low-stakes features reuse instead of paying for a fresh
+/// request every time. One PDA per authority, refreshed by the oracle callback.
+///
+/// Cached randomness is shared by everyone who reads it within `max_age_slots` and was public
+/// on chain since it was delivered. Only use it where a predictable result costs nothing, such
+/// as a cosmetic shuffle.
+///
+/// Matches the lite crate's `CachedRandomness` layout, so clients can read it without this
+/// crate.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct CachedRandomness {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The account the cache belongs to. Part of the PDA seeds.
+    pub authority: Pubkey,
+    /// The number of bytes requested on every refresh.
+    pub num_bytes: u8,
+    /// The slot the cached result was delivered at. Zero until the first result.
+    pub slot: u64,
+    /// The number of valid bytes in `result`.
+    pub result_len: u8,
+    /// The cached result, zero padded to the service's 32 byte maximum.
+    pub result: [u8; 32],
+    /// The request that will refresh the cache, or the default pubkey if none is pending.
+    pub pending_request: Pubkey,
+    /// The slot the pending request was made at.
+    pub pending_slot: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+/// What `get_or_request` does for the cache at a given slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheLookup<'a> {
+    /// The cached result is young enough to reuse.
+    Fresh(&'a [u8]),
+    /// The cache is empty or stale, and no refresh is pending.
+    Refresh,
+    /// The cache is empty or stale, and a refresh requested by an earlier caller has not timed
+    /// out yet.
+    RefreshPending(Pubkey),
+}
+
+impl CachedRandomness {
+    pub const SEED: &'static [u8] = b"CACHED_RANDOMNESS";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Decides whether the cached result can be reused at `slot`.
+    pub fn lookup(&self, slot: u64, max_age_slots: u64) -> CacheLookup<'_> {
+        if self.slot != 0 && slot.saturating_sub(self.slot) <= max_age_slots {
+            return CacheLookup::Fresh(&self.result[..usize::from(self.result_len)]);
+        }
+
+        let pending = self.pending_request != Pubkey::default()
+            && slot.saturating_sub(self.pending_slot) < CACHE_REFRESH_TIMEOUT_SLOTS;
+        if pending {
+            CacheLookup::RefreshPending(self.pending_request)
+        } else {
+            CacheLookup::Refresh
+        }
+    }
+}
+
+/// Emitted when `get_or_request` serves the cached result.
+#[event]
+pub struct CachedRandomnessServed {
+    pub cache: Pubkey,
+    /// The slot the result was delivered at.
+    pub slot: u64,
+    pub result: Vec<u8>,
+}
+
+/// Emitted when `get_or_request` requests a refresh.
+#[event]
+pub struct CachedRandomnessRefreshRequested {
+    pub cache: Pubkey,
+    pub request: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCachedRandomness<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = CachedRandomness::SPACE,
+        seeds = [CachedRandomness::SEED, payer.key().as_ref()],
+        bump,
+    )]
+    pub cache: Box<Account<'info, CachedRandomness>>,
+
+    /// The authority the cache belongs to.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeCachedRandomness<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, num_bytes: u8) -> anchor_lang::prelude::Result<()> {
+        require!(
+            (1..=32).contains(&num_bytes),
+            ConsumerError::InvalidRandomnessLength
+        );
+
+        let cache = &mut ctx.accounts.cache;
+        cache.bump = ctx.bumps.cache;
+        cache.authority = ctx.accounts.payer.key();
+        cache.num_bytes = num_bytes;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetOrRequest<'info> {
+    #[account(
+        mut,
+        seeds = [CachedRandomness::SEED, cache.authority.as_ref()],
+        bump = cache.bump,
+    )]
+    pub cache: Box<Account<'info, CachedRandomness>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request. Unused when
+    /// the cached result is served.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// Pays for the refresh, if one is needed.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl GetOrRequest<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        max_age_slots: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        let slot = Clock::get()?.slot;
+        let cache_key = ctx.accounts.cache.key();
+
+        match ctx.accounts.cache.lookup(slot, max_age_slots) {
+            CacheLookup::Fresh(result) => {
+                anchor_lang::solana_program::program::set_return_data(result);
+                emit!(CachedRandomnessServed {
+                    cache: cache_key,
+                    slot: ctx.accounts.cache.slot,
+                    result: result.to_vec(),
+                });
+                return Ok(());
+            }
+            CacheLookup::RefreshPending(request) => {
+                msg!("Cache refresh {} is still pending", request);
+                return err!(ConsumerError::CacheRefreshPending);
+            }
+            CacheLookup::Refresh => {}
+        }
+
+        solana_randomness_service::cpi::simple_randomness_v1(
+            CpiContext::new(
+                ctx.accounts.randomness_service.to_account_info(),
+                solana_randomness_service::cpi::accounts::SimpleRandomnessV1Request {
+                    request: ctx.accounts.randomness_request.to_account_info(),
+                    escrow: ctx.accounts.randomness_escrow.to_account_info(),
+                    state: ctx.accounts.randomness_state.to_account_info(),
+                    mint: ctx.accounts.randomness_mint.to_account_info(),
+                    payer: ctx.accounts.payer.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    associated_token_program: ctx
+                        .accounts
+                        .associated_token_program
+                        .to_account_info(),
+                },
+            ),
+            ctx.accounts.cache.num_bytes,
+            solana_randomness_service::Callback {
+                program_id: ID,
+                // Must match the order of the ConsumeCachedRandomness accounts struct
+                accounts: vec![
+                    AccountMeta::new_readonly(ctx.accounts.randomness_state.key(), true).into(),
+                    AccountMeta::new_readonly(ctx.accounts.randomness_request.key(), false).into(),
+                    AccountMeta::new(cache_key, false).into(),
+                ],
+                ix_data: get_ixn_discriminator("consume_cached_randomness").to_vec(),
+            },
+            Some(TransactionOptions {
+                compute_units: Some(CACHE_CALLBACK_COMPUTE_UNITS),
+                compute_unit_price: Some(100),
+            }),
+        )?;
+
+        let cache = &mut ctx.accounts.cache;
+        cache.pending_request = ctx.accounts.randomness_request.key();
+        cache.pending_slot = slot;
+        emit!(CachedRandomnessRefreshRequested {
+            cache: cache_key,
+            request: cache.pending_request,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeCachedRandomness<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: the view checks the owner and discriminator
+    pub request: AccountInfo<'info>,
+
+    /// Only the cache's pending refresh may overwrite it, so a late callback from a refresh
+    /// that timed out cannot replace a newer result.
+    #[account(
+        mut,
+        seeds = [CachedRandomness::SEED, cache.authority.as_ref()],
+        bump = cache.bump,
+        constraint = cache.pending_request == request.key() @ ConsumerError::UnexpectedCacheRefresh,
+    )]
+    pub cache: Box<Account<'info, CachedRandomness>>,
+}
+
+impl ConsumeCachedRandomness<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+        SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+        let cache = &mut ctx.accounts.cache;
+        require!(
+            !result.is_empty() && result.len() <= cache.result.len(),
+            ConsumerError::InvalidRandomnessLength
+        );
+
+        cache.slot = Clock::get()?.slot;
+        cache.result_len = result.len() as u8;
+        cache.result = [0; 32];
+        cache.result[..result.len()].copy_from_slice(&result);
+        cache.pending_request = Pubkey::default();
+        cache.pending_slot = 0;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use crate::*;
+
+#[error_code]
+#[derive(Eq, PartialEq)]
+pub enum ConsumerError {
+    #[msg("The randomness result did not contain the expected number of bytes")]
+    InvalidRandomnessLength,
+    #[msg("The lootbox opening has already been settled")]
+    OpeningAlreadySettled,
+    #[msg("The cached randomness is stale and its refresh is still pending")]
+    CacheRefreshPending,
+    #[msg("The callback is not for the cache's pending refresh")]
+    UnexpectedCacheRefresh,
+    #[msg("The request was not made by the owner of the result feed")]
+    RequestUserMismatch,
+    #[msg("The payer cannot afford the randomness request")]
+    InsufficientFunding,
+    #[msg("The record's latest request is still pending")]
+    RequestStillPending,
+    #[msg("The callback is not for the record's pending request")]
+    UnexpectedRecordRequest,
+    #[msg("A tournament has between 1 and 64 brackets")]
+    InvalidBracketCount,
+    #[msg("The bracket index is not below the tournament's bracket count")]
+    BracketIndexOutOfBounds,
+    #[msg("The callback's tag is not the tournament's id")]
+    TournamentTagMismatch,
+    #[msg("The callback is not for the bracket's pending request")]
+    UnexpectedBracketRequest,
+    #[msg("A tie break has between 2 and 8 bidders")]
+    InvalidBidderCount,
+    #[msg("The tie break has already requested randomness")]
+    TieBreakNotOpen,
+    #[msg("The tie break is not awaiting randomness")]
+    TieBreakNotAwaitingRandomness,
+    #[msg("The callback is not for the tie break's pending request")]
+    UnexpectedTieBreakRequest,
+    #[msg("The tie break has not received its randomness")]
+    TieBreakRandomnessNotReceived,
+    #[msg("The tie break has already been settled")]
+    TieBreakAlreadySettled,
+    #[msg("The tie break is not waiting on a request or a settlement")]
+    TieBreakNothingToReclaim,
+    #[msg("The tie break has not timed out yet")]
+    TieBreakReclaimTooEarly,
+    #[msg("The signer is not the program's upgrade authority")]
+    NotProgramAuthority,
+    #[msg("The latest self-test is still pending")]
+    SelfTestPending,
+    #[msg("The callback is not for the pending self-test")]
+    UnexpectedSelfTestRequest,
+    #[msg("The referral recipient is not the one in the referral config")]
+    ReferralRecipientMismatch,
+    #[msg("A proposal tie has between 2 and 8 candidates, each with voting power")]
+    InvalidTiedCandidates,
+    #[msg("The proposal's tiebreak randomness has already been received")]
+    TiebreakAlreadyReceived,
+    #[msg("The callback is not for the proposal's pending tiebreak request")]
+    UnexpectedTiebreakRequest,
+    #[msg("The proposal has not received its tiebreak randomness")]
+    TiebreakNotReceived,
+    #[msg("The proposal's tie has already been resolved")]
+    TieAlreadyResolved,
+}
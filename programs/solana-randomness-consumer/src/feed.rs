@@ -0,0 +1,208 @@
+use crate::*;
+
+/// The compute budget requested for the feed callback, which only writes one account.
+pub const FEED_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// The latest randomness result delivered to a user. One PDA per user, overwritten by every
+/// callback for a request at least as recent as the stored one, so a frontend can poll a
+/// single stable address instead of tracking requests.
+///
+/// Matches the lite crate's `ResultFeed` layout, so clients can read it without this crate.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct ResultFeed {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The user the feed belongs to, the payer of its requests.
+    pub user: Pubkey,
+    /// The request that delivered the latest result.
+    pub request: Pubkey,
+    /// The slot the latest result was delivered at. Zero until the first result.
+    pub slot: u64,
+    /// The number of valid bytes in `result`.
+    pub result_len: u8,
+    /// The latest result, zero padded to the service's 32 byte maximum.
+    pub result: [u8; 32],
+    /// The slot the request that delivered the latest result was made at.
+    pub request_slot: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 24],
+}
+impl ResultFeed {
+    pub const SEED: &'static [u8] = b"RESULT_FEED";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+#[derive(Accounts)]
+pub struct InitializeResultFeed<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = ResultFeed::SPACE,
+        seeds = [ResultFeed::SEED, payer.key().as_ref()],
+        bump,
+    )]
+    pub result_feed: Box<Account<'info, ResultFeed>>,
+
+    /// The user the feed belongs to.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeResultFeed<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        ctx.accounts.result_feed.bump = ctx.bumps.result_feed;
+        ctx.accounts.result_feed.user = ctx.accounts.payer.key();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RequestFeedRandomness<'info> {
+    /// The payer's feed. The callback writes the result into it.
+    #[account(
+        seeds = [ResultFeed::SEED, payer.key().as_ref()],
+        bump = result_feed.bump,
+    )]
+    pub result_feed: Box<Account<'info, ResultFeed>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The user requesting randomness. Pays for the request.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RequestFeedRandomness<'_> {
+    pub fn actuate(ctx: &Context<Self>, num_bytes: u8) -> anchor_lang::prelude::Result<()> {
+        solana_randomness_service::cpi::simple_randomness_v1(
+            CpiContext::new(
+                ctx.accounts.randomness_service.to_account_info(),
+                solana_randomness_service::cpi::accounts::SimpleRandomnessV1Request {
+                    request: ctx.accounts.randomness_request.to_account_info(),
+                    escrow: ctx.accounts.randomness_escrow.to_account_info(),
+                    state: ctx.accounts.randomness_state.to_account_info(),
+                    mint: ctx.accounts.randomness_mint.to_account_info(),
+                    payer: ctx.accounts.payer.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    associated_token_program: ctx
+                        .accounts
+                        .associated_token_program
+                        .to_account_info(),
+                },
+            ),
+            num_bytes,
+            solana_randomness_service::Callback {
+                program_id: ID,
+                // Must match the order of the ConsumeFeedRandomness accounts struct
+                accounts: vec![
+                    AccountMeta::new_readonly(ctx.accounts.randomness_state.key(), true).into(),
+                    AccountMeta::new_readonly(ctx.accounts.randomness_request.key(), false).into(),
+                    AccountMeta::new(ctx.accounts.result_feed.key(), false).into(),
+                ],
+                ix_data: get_ixn_discriminator("consume_feed_randomness").to_vec(),
+            },
+            Some(TransactionOptions {
+                compute_units: Some(FEED_CALLBACK_COMPUTE_UNITS),
+                compute_unit_price: Some(100),
+            }),
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeFeedRandomness<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: the view checks the owner and discriminator
+    pub request: AccountInfo<'info>,
+
+    /// The feed of the user who paid for the request.
+    #[account(
+        mut,
+        seeds = [ResultFeed::SEED, result_feed.user.as_ref()],
+        bump = result_feed.bump,
+    )]
+    pub result_feed: Box<Account<'info, ResultFeed>>,
+}
+
+impl ConsumeFeedRandomness<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+        let (user, request_slot) = {
+            let request = SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+            (request.user(), request.request_slot())
+        };
+        let feed = &mut ctx.accounts.result_feed;
+        require_keys_eq!(user, feed.user, ConsumerError::RequestUserMismatch);
+        require!(
+            !result.is_empty() && result.len() <= feed.result.len(),
+            ConsumerError::InvalidRandomnessLength
+        );
+
+        // A retried settlement can deliver an older request after a newer one. Succeed so the
+        // request is closed, but keep the newer result.
+        if request_slot < feed.request_slot {
+            msg!("Skipping result of request made at slot {}", request_slot);
+            return Ok(());
+        }
+
+        feed.request = ctx.accounts.request.key();
+        feed.slot = Clock::get()?.slot;
+        feed.request_slot = request_slot;
+        feed.result_len = result.len() as u8;
+        feed.result = [0; 32];
+        feed.result[..result.len()].copy_from_slice(&result);
+
+        Ok(())
+    }
+}
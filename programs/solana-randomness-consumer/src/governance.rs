@@ -0,0 +1,388 @@
+use crate::*;
+use solana_randomness_service_lite::{
+    randomness_from_arg, verify_tag_matches, weighted_choice_of, Callback as LiteCallback,
+    IndexedTag, SimpleRandomnessV1Request, TransactionOptions as LiteTransactionOptions,
+};
+
+/// The compute budget requested for the tiebreak callback, which only writes one account.
+pub const TIEBREAK_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// The randomness bytes requested for a tiebreak. `resolve_tie` reads one 8 byte draw, and
+/// the rest cover the draws it rejects to stay unbiased.
+pub const TIEBREAK_RANDOMNESS_BYTES: usize = 32;
+
+/// The most candidates one proposal tie can choose between.
+pub const TIEBREAK_MAX_CANDIDATES: usize = 8;
+
+/// A candidate tied on votes and the voting power behind it.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct TiedCandidate {
+    pub candidate: Pubkey,
+    pub voting_power: u64,
+}
+
+/// The randomness a tiebreak was resolved with, kept on the proposal for auditors.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct TiebreakReceipt {
+    /// The request that delivered the randomness, tagged with the proposal's id.
+    pub request: Pubkey,
+    /// The slot the randomness was delivered at.
+    pub slot: u64,
+    pub randomness: [u8; TIEBREAK_RANDOMNESS_BYTES],
+}
+
+/// The quadratic weight of a candidate's voting power, its integer square root, so a tie is
+/// broken in favor of broader support without letting the largest holder decide it.
+pub fn quadratic_weight(voting_power: u64) -> u64 {
+    // Binary search for the largest root whose square fits, which never overflows
+    let (mut low, mut high) = (0u64, u64::from(u32::MAX));
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if mid * mid <= voting_power {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// Resolves a tie between `candidates` with the stored randomness. The rule, the same for
+/// anyone replaying it from the receipt:
+///
+/// 1. Each candidate is weighted by the [`quadratic_weight`] of its voting power.
+/// 2. `weighted_choice_of` draws from the randomness and picks the candidate whose share of
+///    the running sum of the weights, in the order the candidates were recorded, holds it.
+///
+/// The same candidates and bytes always resolve to the same winner.
+pub fn resolve_tie(
+    candidates: &[TiedCandidate],
+    randomness: &[u8],
+) -> anchor_lang::prelude::Result<Pubkey> {
+    let table: Vec<(Pubkey, u64)> = candidates
+        .iter()
+        .map(|c| (c.candidate, quadratic_weight(c.voting_power)))
+        .collect();
+    Ok(*weighted_choice_of(randomness, &table)?)
+}
+
+/// A governance proposal tied between candidates, broken by randomness with a full audit
+/// trail: the request is tagged with the proposal's id, the callback stores the randomness
+/// as a receipt, and `resolve_tie` applies a fixed rule to it. One PDA per authority and
+/// proposal id.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Proposal {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The DAO's authority, who records the tie and pays for the request.
+    pub authority: Pubkey,
+    /// The proposal id, committed into the tag of the tiebreak request.
+    pub id: u64,
+    /// The number of valid entries in `candidates`.
+    pub candidate_count: u8,
+    /// The tied candidates, padded with defaults. Holds `TIEBREAK_MAX_CANDIDATES`.
+    pub candidates: [TiedCandidate; 8],
+    /// The tiebreak request awaiting its callback, the default pubkey if none.
+    pub pending_request: Pubkey,
+    /// The tiebreak randomness, once received.
+    pub receipt: Option<TiebreakReceipt>,
+    /// The winner, once the tie is resolved.
+    pub winner: Option<Pubkey>,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl Proposal {
+    pub const SEED: &'static [u8] = b"PROPOSAL";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Fails with `InvalidTiedCandidates` unless there are 2 to [`TIEBREAK_MAX_CANDIDATES`]
+    /// candidates, each with voting power.
+    pub fn check_candidates(candidates: &[TiedCandidate]) -> anchor_lang::prelude::Result<()> {
+        require!(
+            (2..=TIEBREAK_MAX_CANDIDATES).contains(&candidates.len())
+                && candidates.iter().all(|c| c.voting_power > 0),
+            ConsumerError::InvalidTiedCandidates
+        );
+        Ok(())
+    }
+
+    /// The tied candidates.
+    pub fn candidates(&self) -> &[TiedCandidate] {
+        &self.candidates[..usize::from(self.candidate_count)]
+    }
+
+    /// The tag of the proposal's tiebreak requests: its id, and its candidate count as the
+    /// index, so a request made before the candidates changed is rejected too.
+    pub fn tiebreak_tag(&self) -> IndexedTag {
+        IndexedTag::new(self.id, u16::from(self.candidate_count))
+    }
+}
+
+/// Emitted when a tiebreak's randomness is stored.
+#[event]
+pub struct TiebreakReceiptStored {
+    pub proposal: Pubkey,
+    pub request: Pubkey,
+    pub slot: u64,
+    pub randomness: [u8; 32],
+}
+
+/// Emitted when a proposal's tie is resolved.
+#[event]
+pub struct TieResolved {
+    pub proposal: Pubkey,
+    pub winner: Pubkey,
+    /// The request whose randomness resolved the tie.
+    pub request: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct InitializeProposal<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Proposal::SPACE,
+        seeds = [Proposal::SEED, authority.key().as_ref(), &id.to_le_bytes()],
+        bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeProposal<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        id: u64,
+        candidates: Vec<TiedCandidate>,
+    ) -> anchor_lang::prelude::Result<()> {
+        Proposal::check_candidates(&candidates)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.authority = ctx.accounts.authority.key();
+        proposal.id = id;
+        proposal.candidate_count = candidates.len() as u8;
+        proposal.candidates[..candidates.len()].copy_from_slice(&candidates);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct RequestTiebreak<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [Proposal::SEED, authority.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The proposal's authority. Pays for the request.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RequestTiebreak<'_> {
+    /// Requests the tiebreak randomness with a callback tagged with the proposal's
+    /// [`tiebreak_tag`](Proposal::tiebreak_tag), and records the request as pending. A new
+    /// request replaces a pending one, whose late callback is then rejected.
+    pub fn actuate(ctx: &mut Context<Self>, proposal_id: u64) -> anchor_lang::prelude::Result<()> {
+        require!(
+            ctx.accounts.proposal.receipt.is_none(),
+            ConsumerError::TiebreakAlreadyReceived
+        );
+        let request_key = ctx.accounts.randomness_request.key();
+        let tag = ctx.accounts.proposal.tiebreak_tag();
+        ctx.accounts.proposal.pending_request = request_key;
+
+        // Must match the order of the ConsumeTiebreak accounts struct. The tag becomes the
+        // callback's `tag` and `index` arguments
+        let (callback, _) = LiteCallback::builder(ID)
+            .with_state_signer()
+            .with_readonly_account(request_key)
+            .with_writable_account(ctx.accounts.proposal.key())
+            .with_ix_data(get_ixn_discriminator("consume_tiebreak").to_vec())
+            .with_indexed_tag(tag.tag, tag.index)
+            .build()?;
+        let options = LiteTransactionOptions {
+            compute_units: Some(TIEBREAK_CALLBACK_COMPUTE_UNITS),
+            compute_unit_price: Some(100),
+        };
+
+        let request = SimpleRandomnessV1Request {
+            request: ctx.accounts.randomness_request.to_account_info(),
+            escrow: ctx.accounts.randomness_escrow.to_account_info(),
+            state: ctx.accounts.randomness_state.to_account_info(),
+            mint: ctx.accounts.randomness_mint.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        let program = ctx.accounts.randomness_service.to_account_info();
+        request.cpi(
+            program,
+            TIEBREAK_RANDOMNESS_BYTES as u8,
+            &callback,
+            Some(&options),
+        )?;
+        msg!("Requested the tiebreak of proposal {}", proposal_id);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeTiebreak<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled.
+    /// CHECK: `verify_callback` checks the owner, discriminator, and callback program
+    pub request: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [Proposal::SEED, proposal.authority.as_ref(), &proposal.id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+}
+
+impl ConsumeTiebreak<'_> {
+    /// Checks the tag is the proposal's and the request is its pending one, then stores the
+    /// randomness as the proposal's receipt. Resolving is left to `resolve_tie`.
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        tag: u64,
+        index: u16,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        verify_callback(
+            &ctx.accounts.randomness_state.to_account_info(),
+            &ctx.accounts.request,
+            &ID,
+        )?;
+        let proposal = &mut ctx.accounts.proposal;
+        verify_tag_matches(IndexedTag::new(tag, index), proposal.tiebreak_tag())?;
+        let request = ctx.accounts.request.key();
+        require_keys_eq!(
+            request,
+            proposal.pending_request,
+            ConsumerError::UnexpectedTiebreakRequest
+        );
+        require!(
+            proposal.receipt.is_none(),
+            ConsumerError::TiebreakAlreadyReceived
+        );
+
+        let mut randomness = [0; TIEBREAK_RANDOMNESS_BYTES];
+        randomness.copy_from_slice(randomness_from_arg(&result, TIEBREAK_RANDOMNESS_BYTES)?);
+        let slot = Clock::get()?.slot;
+        proposal.pending_request = Pubkey::default();
+        proposal.receipt = Some(TiebreakReceipt {
+            request,
+            slot,
+            randomness,
+        });
+
+        emit!(TiebreakReceiptStored {
+            proposal: proposal.key(),
+            request,
+            slot,
+            randomness,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ResolveTie<'info> {
+    #[account(
+        mut,
+        seeds = [Proposal::SEED, proposal.authority.as_ref(), &proposal.id.to_le_bytes()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// Anyone can resolve a tie once its randomness is stored, the rule leaves no choice.
+    pub caller: Signer<'info>,
+}
+
+impl ResolveTie<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.winner.is_none(), ConsumerError::TieAlreadyResolved);
+        let Some(receipt) = proposal.receipt else {
+            return err!(ConsumerError::TiebreakNotReceived);
+        };
+
+        let winner = resolve_tie(proposal.candidates(), &receipt.randomness)?;
+        proposal.winner = Some(winner);
+
+        emit!(TieResolved {
+            proposal: proposal.key(),
+            winner,
+            request: receipt.request,
+        });
+
+        Ok(())
+    }
+}
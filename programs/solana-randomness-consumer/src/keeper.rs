@@ -0,0 +1,72 @@
+use crate::*;
+use solana_randomness_service_lite::{DecodeLimits, LiteError, SimpleRandomnessV1Account};
+
+/// The limits a keeper decodes requests with. A request with a larger callback is skipped
+/// rather than decoded, so each request costs at most
+/// `KEEPER_DECODE_LIMITS.max_heap_bytes()` of the program's 32 KiB heap, which is never
+/// freed. Error messages are decoded up to the service's limit so failed requests are never
+/// skipped.
+pub const KEEPER_DECODE_LIMITS: DecodeLimits = DecodeLimits {
+    max_accounts: 8,
+    max_ix_data_len: 128,
+    max_error_message_len: SimpleRandomnessV1Account::MAX_ERROR_MESSAGE_LEN,
+};
+
+/// The requests a keeper found, in the order they were passed.
+#[event]
+pub struct ExpiredRequestsFound {
+    /// The requests that completed, or were made at least `max_age_slots` ago.
+    pub expired: Vec<Pubkey>,
+    /// The requests whose callback is larger than [`KEEPER_DECODE_LIMITS`].
+    pub skipped: Vec<Pubkey>,
+}
+
+/// Scans request accounts made by any user, passed as the remaining accounts, for ones a
+/// keeper should act on. Requests are untrusted data, so they are decoded with
+/// [`KEEPER_DECODE_LIMITS`].
+#[derive(Accounts)]
+pub struct FindExpiredRequests<'info> {
+    pub keeper: Signer<'info>,
+}
+
+impl FindExpiredRequests<'_> {
+    pub fn actuate(ctx: &Context<Self>, max_age_slots: u64) -> anchor_lang::prelude::Result<()> {
+        let slot = Clock::get()?.slot;
+        let mut expired = Vec::new();
+        let mut skipped = Vec::new();
+        for account in ctx.remaining_accounts {
+            if account.owner != &SolanaRandomnessServiceID {
+                return Err(ProgramError::IllegalOwner.into());
+            }
+            let data = account.try_borrow_data()?;
+            let request = match SimpleRandomnessV1Account::try_deserialize_bounded(
+                &mut &data[..],
+                KEEPER_DECODE_LIMITS,
+            ) {
+                Ok(request) => request,
+                Err(ProgramError::Custom(code))
+                    if LiteError::from_code(code) == Some(LiteError::DecodeLimitExceeded) =>
+                {
+                    msg!("Skipping request {} over the decode limits", account.key);
+                    skipped.push(account.key());
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if request.is_completed != 0
+                || slot.saturating_sub(request.request_slot) >= max_age_slots
+            {
+                expired.push(account.key());
+            }
+        }
+
+        msg!(
+            "Found {} expired requests, skipped {}",
+            expired.len(),
+            skipped.len()
+        );
+        emit!(ExpiredRequestsFound { expired, skipped });
+
+        Ok(())
+    }
+}
@@ -1,21 +1,62 @@
-use solana_randomness_service::SimpleRandomnessV1Account;
+use solana_randomness_service::TransactionOptions;
 use solana_randomness_service::{
-    program::SolanaRandomnessService, ID as SolanaRandomnessServiceID,
+    program::SolanaRandomnessService, State as ServiceState, ID as SolanaRandomnessServiceID,
+};
+use solana_randomness_service_lite::{
+    assert_cpi_depth_available, verify_callback, SimpleRandomnessV1AccountView, REQUEST_CPI_LEVELS,
 };
 use switchboard_solana::prelude::*;
 use switchboard_solana::utils::get_ixn_discriminator;
 
+mod errors;
+pub use errors::*;
+
+pub mod lootbox;
+pub use lootbox::*;
+
+pub mod feed;
+pub use feed::*;
+
+pub mod cache;
+pub use cache::*;
+
+pub mod register;
+pub use register::*;
+
+pub mod tournament;
+pub use tournament::*;
+
+pub mod auction;
+pub use auction::*;
+
+pub mod self_test;
+pub use self_test::*;
+
+pub mod keeper;
+pub use keeper::*;
+
+pub mod referral;
+pub use referral::*;
+
+pub mod governance;
+pub use governance::*;
+
+pub mod state_reads;
+pub use state_reads::*;
+
 declare_id!("39hMZgeiesFXMRFt8svuKVsdCW5geiYueSRx7dxhXN4f");
 
 #[program]
 pub mod solana_randomness_consumer {
-    use solana_randomness_service::TransactionOptions;
-
     use super::*;
 
     pub fn request_randomness(ctx: Context<RequestRandomness>) -> anchor_lang::prelude::Result<()> {
         msg!("Requesting randomness...");
 
+        // When this program is invoked by another, e.g. a router, fail here with a clear error
+        // if the request's CPIs would not fit under it
+        assert_cpi_depth_available(REQUEST_CPI_LEVELS)?;
+
         // Call the randomness service and request a new value
         solana_randomness_service::cpi::simple_randomness_v1(
             CpiContext::new(
@@ -55,12 +96,296 @@ pub mod solana_randomness_consumer {
     }
 
     pub fn consume_randomness(
-        _ctx: Context<ConsumeRandomness>,
+        ctx: Context<ConsumeRandomness>,
         result: Vec<u8>,
     ) -> anchor_lang::prelude::Result<()> {
-        msg!("Randomness received: {:?}", result);
+        // Only the oracle can deliver randomness for our own requests
+        verify_callback(
+            &ctx.accounts.randomness_state.to_account_info(),
+            &ctx.accounts.request,
+            &ID,
+        )?;
+        let request = SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+        msg!(
+            "Randomness received for slot {}: {:?}",
+            request.request_slot(),
+            result
+        );
         Ok(())
     }
+
+    /// Creates a lootbox along with its key and prize mints.
+    pub fn initialize_lootbox(
+        mut ctx: Context<InitializeLootbox>,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeLootbox::actuate(&mut ctx)
+    }
+
+    /// Mints lootbox keys to a token account. Only callable by the lootbox authority.
+    pub fn mint_lootbox_keys(
+        ctx: Context<MintLootboxKeys>,
+        amount: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        MintLootboxKeys::actuate(&ctx, amount)
+    }
+
+    /// Burns one key and requests randomness to decide the prize.
+    pub fn open_box(mut ctx: Context<OpenBox>) -> anchor_lang::prelude::Result<()> {
+        OpenBox::actuate(&mut ctx)
+    }
+
+    /// The lootbox callback. Maps the randomness to a prize tier and mints the prize to the user.
+    pub fn consume_lootbox(
+        mut ctx: Context<ConsumeLootbox>,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeLootbox::actuate(&mut ctx, result)
+    }
+
+    /// Creates the payer's result feed.
+    pub fn initialize_result_feed(
+        mut ctx: Context<InitializeResultFeed>,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeResultFeed::actuate(&mut ctx)
+    }
+
+    /// Requests randomness delivered to the payer's result feed.
+    pub fn request_feed_randomness(
+        ctx: Context<RequestFeedRandomness>,
+        num_bytes: u8,
+    ) -> anchor_lang::prelude::Result<()> {
+        RequestFeedRandomness::actuate(&ctx, num_bytes)
+    }
+
+    /// The feed callback. Overwrites the user's feed with the result.
+    pub fn consume_feed_randomness(
+        mut ctx: Context<ConsumeFeedRandomness>,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeFeedRandomness::actuate(&mut ctx, result)
+    }
+
+    /// Creates the payer's randomness cache, refreshed with `num_bytes` of randomness.
+    pub fn initialize_cached_randomness(
+        mut ctx: Context<InitializeCachedRandomness>,
+        num_bytes: u8,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeCachedRandomness::actuate(&mut ctx, num_bytes)
+    }
+
+    /// Returns the cached randomness if it is at most `max_age_slots` old, as return data and
+    /// a `CachedRandomnessServed` event. Otherwise requests a refresh.
+    pub fn get_or_request(
+        mut ctx: Context<GetOrRequest>,
+        max_age_slots: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        GetOrRequest::actuate(&mut ctx, max_age_slots)
+    }
+
+    /// The cache callback. Stores the result and clears the pending refresh.
+    pub fn consume_cached_randomness(
+        mut ctx: Context<ConsumeCachedRandomness>,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeCachedRandomness::actuate(&mut ctx, result)
+    }
+
+    /// Creates the payer's randomness record.
+    pub fn initialize_randomness_record(
+        mut ctx: Context<InitializeRandomnessRecord>,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeRandomnessRecord::actuate(&mut ctx)
+    }
+
+    /// Requests randomness and marks it pending in the payer's record, in one transaction.
+    pub fn request_and_register(
+        mut ctx: Context<RequestAndRegister>,
+        num_bytes: u8,
+    ) -> anchor_lang::prelude::Result<()> {
+        RequestAndRegister::actuate(&mut ctx, num_bytes)
+    }
+
+    /// The record callback. Stores the result and clears the pending request.
+    pub fn consume_registered_randomness(
+        mut ctx: Context<ConsumeRegisteredRandomness>,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeRegisteredRandomness::actuate(&mut ctx, result)
+    }
+
+    /// Creates the authority's tournament with `bracket_count` brackets, tagging its requests
+    /// with `id`.
+    pub fn initialize_tournament(
+        mut ctx: Context<InitializeTournament>,
+        id: u64,
+        bracket_count: u16,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeTournament::actuate(&mut ctx, id, bracket_count)
+    }
+
+    /// Creates the tournament's bracket at `index`.
+    pub fn initialize_bracket(
+        mut ctx: Context<InitializeBracket>,
+        index: u16,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeBracket::actuate(&mut ctx, index)
+    }
+
+    /// Requests randomness for the bracket at `index`, tagged with the tournament's id and
+    /// the index, and records it as the bracket's pending request.
+    pub fn request_bracket_randomness(
+        mut ctx: Context<RequestBracketRandomness>,
+        index: u16,
+        num_bytes: u8,
+    ) -> anchor_lang::prelude::Result<()> {
+        RequestBracketRandomness::actuate(&mut ctx, index, num_bytes)
+    }
+
+    /// The bracket callback. Writes the result into the bracket at the tagged index, if the
+    /// request is the bracket's pending one.
+    pub fn consume_bracket_randomness(
+        mut ctx: Context<ConsumeBracketRandomness>,
+        tag: u64,
+        index: u16,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeBracketRandomness::actuate(&mut ctx, tag, index, result)
+    }
+
+    /// Creates the authority's tie break between `bidders` for the auction `id`.
+    pub fn initialize_tie_break(
+        mut ctx: Context<InitializeTieBreak>,
+        id: u64,
+        bidders: Vec<Pubkey>,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeTieBreak::actuate(&mut ctx, id, bidders)
+    }
+
+    /// Requests the tie break's randomness.
+    pub fn request_tie_break_randomness(
+        mut ctx: Context<RequestTieBreakRandomness>,
+    ) -> anchor_lang::prelude::Result<()> {
+        RequestTieBreakRandomness::actuate(&mut ctx)
+    }
+
+    /// The tie break callback. Records the randomness for `settle_tie_break`.
+    pub fn consume_tie_break_randomness(
+        mut ctx: Context<ConsumeTieBreakRandomness>,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeTieBreakRandomness::actuate(&mut ctx, result)
+    }
+
+    /// Picks the tie break's winner from the received randomness.
+    pub fn settle_tie_break(mut ctx: Context<SettleTieBreak>) -> anchor_lang::prelude::Result<()> {
+        SettleTieBreak::actuate(&mut ctx)
+    }
+
+    /// Moves a timed out tie break along: a lost request back to open, or received
+    /// randomness to settled.
+    pub fn reclaim_tie_break(
+        mut ctx: Context<ReclaimTieBreak>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ReclaimTieBreak::actuate(&mut ctx)
+    }
+
+    /// Creates the program's self-test result. Only callable by the upgrade authority.
+    pub fn initialize_self_test(
+        mut ctx: Context<InitializeSelfTest>,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeSelfTest::actuate(&mut ctx)
+    }
+
+    /// Requests 1 byte through the configured randomness service to check the full request
+    /// path after a deploy. Only callable by the upgrade authority.
+    pub fn self_test(mut ctx: Context<SelfTest>) -> anchor_lang::prelude::Result<()> {
+        SelfTest::actuate(&mut ctx)
+    }
+
+    /// The self-test callback. Records that the randomness arrived.
+    pub fn consume_self_test(
+        mut ctx: Context<ConsumeSelfTest>,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeSelfTest::actuate(&mut ctx, result)
+    }
+
+    /// Emits the requests among the remaining accounts that completed or are at least
+    /// `max_age_slots` old, for a keeper to close. Requests over the keeper's decode limits
+    /// are skipped.
+    pub fn find_expired_requests(
+        ctx: Context<FindExpiredRequests>,
+        max_age_slots: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        FindExpiredRequests::actuate(&ctx, max_age_slots)
+    }
+
+    /// Creates the referral config, charging `fee_lamports` to `recipient` on each request
+    /// made with `request_randomness_with_referral_fee`. Only callable by the upgrade authority.
+    pub fn initialize_referral_config(
+        mut ctx: Context<InitializeReferralConfig>,
+        recipient: Pubkey,
+        fee_lamports: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeReferralConfig::actuate(&mut ctx, recipient, fee_lamports)
+    }
+
+    /// Changes the referral recipient and fee. Only callable by the upgrade authority.
+    pub fn update_referral_config(
+        mut ctx: Context<UpdateReferralConfig>,
+        recipient: Pubkey,
+        fee_lamports: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        UpdateReferralConfig::actuate(&mut ctx, recipient, fee_lamports)
+    }
+
+    /// Pays the configured referral fee from the payer, then requests randomness like
+    /// `request_randomness`.
+    pub fn request_randomness_with_referral_fee(
+        ctx: Context<RequestRandomnessWithReferralFee>,
+    ) -> anchor_lang::prelude::Result<()> {
+        RequestRandomnessWithReferralFee::actuate(&ctx)
+    }
+
+    /// Creates the authority's proposal `id`, tied between `candidates`.
+    pub fn initialize_proposal(
+        mut ctx: Context<InitializeProposal>,
+        id: u64,
+        candidates: Vec<TiedCandidate>,
+    ) -> anchor_lang::prelude::Result<()> {
+        InitializeProposal::actuate(&mut ctx, id, candidates)
+    }
+
+    /// Requests the randomness breaking the proposal's tie, tagged with the proposal id.
+    pub fn request_tiebreak(
+        mut ctx: Context<RequestTiebreak>,
+        proposal_id: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        RequestTiebreak::actuate(&mut ctx, proposal_id)
+    }
+
+    /// The tiebreak callback. Checks the tag matches the proposal and stores the receipt.
+    pub fn consume_tiebreak(
+        mut ctx: Context<ConsumeTiebreak>,
+        tag: u64,
+        index: u16,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        ConsumeTiebreak::actuate(&mut ctx, tag, index, result)
+    }
+
+    /// Resolves the proposal's tie from its receipt with `resolve_tie`.
+    pub fn resolve_tie(mut ctx: Context<ResolveTie>) -> anchor_lang::prelude::Result<()> {
+        ResolveTie::actuate(&mut ctx)
+    }
+
+    /// Logs the compute units of deserializing the service's state and of reading it at
+    /// fixed offsets, and checks both read the same fields.
+    pub fn measure_state_reads(
+        ctx: Context<MeasureStateReads>,
+    ) -> anchor_lang::prelude::Result<()> {
+        MeasureStateReads::actuate(&ctx)
+    }
 }
 
 // The request_randomness macro breaks IDL generation. So we'll manually implement.
@@ -97,7 +422,7 @@ pub struct RequestRandomness<'info> {
         bump = randomness_state.bump,
         seeds::program = randomness_service.key(),
     )]
-    pub randomness_state: Box<Account<'info, solana_randomness_service::State>>,
+    pub randomness_state: Box<Account<'info, ServiceState>>,
 
     /// The token mint to use for paying for randomness requests.
     #[account(address = NativeMint::ID)]
@@ -126,7 +451,10 @@ pub struct ConsumeRandomness<'info> {
         seeds::program = SolanaRandomnessServiceID,
         bump = randomness_state.bump,
     )]
-    pub randomness_state: Box<Account<'info, solana_randomness_service::State>>,
+    pub randomness_state: Box<Account<'info, ServiceState>>,
 
-    pub request: Box<Account<'info, SimpleRandomnessV1Account>>,
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: `verify_callback` checks the owner, discriminator, and callback program
+    pub request: AccountInfo<'info>,
 }
@@ -0,0 +1,460 @@
+use crate::*;
+
+use anchor_spl::token::{Burn, MintTo};
+
+/// The number of randomness bytes requested when opening a lootbox.
+pub const LOOTBOX_RANDOMNESS_BYTES: u8 = 4;
+
+/// The compute budget requested for the lootbox callback.
+///
+/// The settle transaction runs the service's `simple_randomness_v1_settle` instruction (escrow
+/// transfer, escrow close, settled event) which then invokes `consume_lootbox` (account
+/// validation, one `mint_to` CPI signed by the lootbox PDA). This is the service minimum, not a
+/// measured figure: `tests/lootbox.ts` logs the `computeUnitsConsumed` of every settle
+/// transaction on a local validator and fails if it is over this budget.
+pub const LOOTBOX_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// The prize tier awarded when a lootbox is opened.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, InitSpace,
+)]
+pub enum PrizeTier {
+    #[default]
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+impl PrizeTier {
+    /// The odds of each tier in basis points. Common 70%, Rare 25%, Epic 4%, Legendary 1%.
+    pub const COMMON_BPS: u64 = 7_000;
+    pub const RARE_BPS: u64 = 2_500;
+    pub const EPIC_BPS: u64 = 400;
+    pub const LEGENDARY_BPS: u64 = 100;
+
+    /// Maps the 4 randomness bytes to a prize tier.
+    ///
+    /// The bytes are read as a little-endian u32 and scaled into `[0, 10_000)` basis points with a
+    /// widening multiply, avoiding the modulo of a raw `value % 10_000`.
+    pub fn from_randomness(randomness: &[u8]) -> anchor_lang::prelude::Result<Self> {
+        let bytes: [u8; 4] = randomness
+            .try_into()
+            .map_err(|_| error!(ConsumerError::InvalidRandomnessLength))?;
+        let roll = (u64::from(u32::from_le_bytes(bytes)) * 10_000) >> 32;
+
+        Ok(Self::from_roll(roll))
+    }
+
+    /// Maps a roll in basis points, `[0, 10_000)`, to a prize tier.
+    pub fn from_roll(roll: u64) -> Self {
+        if roll < Self::COMMON_BPS {
+            PrizeTier::Common
+        } else if roll < Self::COMMON_BPS + Self::RARE_BPS {
+            PrizeTier::Rare
+        } else if roll < Self::COMMON_BPS + Self::RARE_BPS + Self::EPIC_BPS {
+            PrizeTier::Epic
+        } else {
+            PrizeTier::Legendary
+        }
+    }
+
+    /// The number of prize tokens minted for this tier.
+    pub fn prize_amount(&self) -> u64 {
+        match self {
+            PrizeTier::Common => 1,
+            PrizeTier::Rare => 5,
+            PrizeTier::Epic => 25,
+            PrizeTier::Legendary => 100,
+        }
+    }
+}
+
+/// A lootbox that exchanges one key token for a random amount of prize tokens.
+/// The lootbox PDA is the mint authority of both the key and prize mints.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Lootbox {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The authority allowed to mint keys.
+    pub authority: Pubkey,
+    /// The mint of the key token burned to open the lootbox.
+    pub key_mint: Pubkey,
+    /// The mint of the prize token awarded in the callback.
+    pub prize_mint: Pubkey,
+    /// The number of lootboxes opened so far.
+    pub num_opened: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+impl Lootbox {
+    pub const SEED: &'static [u8] = b"LOOTBOX";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+solana_randomness_service_lite::define_pda!(LootboxPda, [Lootbox::SEED, key_mint: Pubkey]);
+
+/// Records a single lootbox opening and, once settled, its outcome.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct LootboxOpening {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The lootbox that was opened.
+    pub lootbox: Pubkey,
+    /// The user that burned the key and receives the prize.
+    pub user: Pubkey,
+    /// The randomness request account.
+    pub request: Pubkey,
+    /// The slot the lootbox was opened.
+    pub request_slot: u64,
+    /// The slot the callback settled the opening.
+    pub settled_slot: u64,
+    /// Flag for determining whether the callback has settled the opening.
+    pub is_settled: bool,
+    /// The randomness bytes delivered by the oracle.
+    pub randomness: [u8; 4],
+    /// The prize tier awarded.
+    pub tier: PrizeTier,
+    /// The number of prize tokens minted to the user.
+    pub amount: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+impl LootboxOpening {
+    pub const SEED: &'static [u8] = b"OPENING";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+#[derive(Accounts)]
+pub struct InitializeLootbox<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Lootbox::SPACE,
+        seeds = [Lootbox::SEED, key_mint.key().as_ref()],
+        bump,
+    )]
+    pub lootbox: Box<Account<'info, Lootbox>>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = lootbox,
+    )]
+    pub key_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = lootbox,
+    )]
+    pub prize_mint: Box<Account<'info, Mint>>,
+
+    /// The account that pays for the lootbox and becomes its authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl InitializeLootbox<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        ctx.accounts.lootbox.bump = ctx.bumps.lootbox;
+        ctx.accounts.lootbox.authority = ctx.accounts.payer.key();
+        ctx.accounts.lootbox.key_mint = ctx.accounts.key_mint.key();
+        ctx.accounts.lootbox.prize_mint = ctx.accounts.prize_mint.key();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct MintLootboxKeys<'info> {
+    #[account(
+        seeds = [Lootbox::SEED, key_mint.key().as_ref()],
+        bump = lootbox.bump,
+        has_one = authority,
+        has_one = key_mint,
+    )]
+    pub lootbox: Box<Account<'info, Lootbox>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub key_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = key_mint,
+    )]
+    pub destination: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl MintLootboxKeys<'_> {
+    pub fn actuate(ctx: &Context<Self>, amount: u64) -> anchor_lang::prelude::Result<()> {
+        let key_mint = ctx.accounts.key_mint.key();
+        anchor_spl::token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.key_mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.lootbox.to_account_info(),
+                },
+                &[&[
+                    Lootbox::SEED,
+                    key_mint.as_ref(),
+                    &[ctx.accounts.lootbox.bump],
+                ]],
+            ),
+            amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenBox<'info> {
+    #[account(
+        mut,
+        seeds = [Lootbox::SEED, key_mint.key().as_ref()],
+        bump = lootbox.bump,
+        has_one = key_mint,
+        has_one = prize_mint,
+    )]
+    pub lootbox: Box<Account<'info, Lootbox>>,
+
+    /// The key mint. Mutable because burning reduces its supply.
+    #[account(mut)]
+    pub key_mint: Box<Account<'info, Mint>>,
+
+    /// The payer's key token account. One key is burned from it.
+    #[account(
+        mut,
+        token::mint = key_mint,
+        token::authority = payer,
+    )]
+    pub user_key_account: Box<Account<'info, TokenAccount>>,
+
+    pub prize_mint: Box<Account<'info, Mint>>,
+
+    /// The payer's prize token account. The callback mints the prize into it.
+    #[account(
+        token::mint = prize_mint,
+        token::authority = payer,
+    )]
+    pub user_prize_account: Box<Account<'info, TokenAccount>>,
+
+    /// Records the outcome of this opening. Keyed by the randomness request.
+    #[account(
+        init,
+        payer = payer,
+        space = LootboxOpening::SPACE,
+        seeds = [LootboxOpening::SEED, randomness_request.key().as_ref()],
+        bump,
+    )]
+    pub opening: Box<Account<'info, LootboxOpening>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The user opening the lootbox. Pays for the randomness request.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl OpenBox<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        // 1. Burn the key before anything else. A user without a key fails here, before the
+        // service CPI allocates the request and escrow accounts. Any later failure reverts the
+        // whole transaction, so the key is never lost without a request being made.
+        anchor_spl::token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.key_mint.to_account_info(),
+                    from: ctx.accounts.user_key_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        // 2. Record the pending opening so the callback has somewhere to write the outcome.
+        let opening = &mut ctx.accounts.opening;
+        opening.bump = ctx.bumps.opening;
+        opening.lootbox = ctx.accounts.lootbox.key();
+        opening.user = ctx.accounts.payer.key();
+        opening.request = ctx.accounts.randomness_request.key();
+        opening.request_slot = Clock::get()?.slot;
+
+        ctx.accounts.lootbox.num_opened += 1;
+
+        // 3. Request the randomness. The callback mints the prize.
+        solana_randomness_service::cpi::simple_randomness_v1(
+            CpiContext::new(
+                ctx.accounts.randomness_service.to_account_info(),
+                solana_randomness_service::cpi::accounts::SimpleRandomnessV1Request {
+                    request: ctx.accounts.randomness_request.to_account_info(),
+                    escrow: ctx.accounts.randomness_escrow.to_account_info(),
+                    state: ctx.accounts.randomness_state.to_account_info(),
+                    mint: ctx.accounts.randomness_mint.to_account_info(),
+                    payer: ctx.accounts.payer.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    associated_token_program: ctx
+                        .accounts
+                        .associated_token_program
+                        .to_account_info(),
+                },
+            ),
+            LOOTBOX_RANDOMNESS_BYTES,
+            solana_randomness_service::Callback {
+                program_id: ID,
+                // Must match the order of the ConsumeLootbox accounts struct
+                accounts: vec![
+                    AccountMeta::new_readonly(ctx.accounts.randomness_state.key(), true).into(),
+                    AccountMeta::new_readonly(ctx.accounts.randomness_request.key(), false).into(),
+                    AccountMeta::new_readonly(ctx.accounts.lootbox.key(), false).into(),
+                    AccountMeta::new(ctx.accounts.opening.key(), false).into(),
+                    AccountMeta::new(ctx.accounts.prize_mint.key(), false).into(),
+                    AccountMeta::new(ctx.accounts.user_prize_account.key(), false).into(),
+                    AccountMeta::new_readonly(ctx.accounts.token_program.key(), false).into(),
+                ],
+                ix_data: get_ixn_discriminator("consume_lootbox").to_vec(),
+            },
+            Some(TransactionOptions {
+                compute_units: Some(LOOTBOX_CALLBACK_COMPUTE_UNITS),
+                compute_unit_price: Some(100),
+            }),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeLootbox<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: the view checks the owner and discriminator
+    pub request: AccountInfo<'info>,
+
+    /// The lootbox PDA. Signs the prize `mint_to` CPI as the mint authority.
+    #[account(
+        seeds = [Lootbox::SEED, lootbox.key_mint.as_ref()],
+        bump = lootbox.bump,
+        has_one = prize_mint,
+    )]
+    pub lootbox: Box<Account<'info, Lootbox>>,
+
+    #[account(
+        mut,
+        seeds = [LootboxOpening::SEED, request.key().as_ref()],
+        bump = opening.bump,
+        has_one = lootbox,
+        constraint = !opening.is_settled @ ConsumerError::OpeningAlreadySettled,
+    )]
+    pub opening: Box<Account<'info, LootboxOpening>>,
+
+    #[account(mut)]
+    pub prize_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = prize_mint,
+        token::authority = opening.user,
+    )]
+    pub user_prize_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl ConsumeLootbox<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+        SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+        let tier = PrizeTier::from_randomness(&result)?;
+        let amount = tier.prize_amount();
+
+        // Mint the prize with the lootbox PDA as the signing mint authority (invoke_signed).
+        let lootbox = LootboxPda {
+            key_mint: ctx.accounts.lootbox.key_mint,
+            bump: ctx.accounts.lootbox.bump,
+        };
+        anchor_spl::token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.prize_mint.to_account_info(),
+                    to: ctx.accounts.user_prize_account.to_account_info(),
+                    authority: ctx.accounts.lootbox.to_account_info(),
+                },
+                &[&lootbox.seeds_with_bump()[..]],
+            ),
+            amount,
+        )?;
+
+        let opening = &mut ctx.accounts.opening;
+        opening.is_settled = true;
+        opening.settled_slot = Clock::get()?.slot;
+        opening.randomness.copy_from_slice(&result);
+        opening.tier = tier;
+        opening.amount = amount;
+
+        msg!("Lootbox opened: {:?} x{}", tier, amount);
+
+        Ok(())
+    }
+}
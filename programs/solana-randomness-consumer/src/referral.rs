@@ -0,0 +1,231 @@
+use crate::*;
+use anchor_lang::prelude::ProgramData;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use solana_randomness_service_lite::{
+    Callback as LiteCallback, SimpleRandomnessV1Request,
+    TransactionOptions as LiteTransactionOptions,
+};
+
+/// The referral fee charged on requests made through `request_randomness_with_referral_fee`,
+/// for programs that resell randomness. One PDA per program, set by its upgrade authority.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct ReferralConfig {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The wallet paid the fee.
+    pub recipient: Pubkey,
+    /// The fee paid by the payer of each request, in lamports. Zero charges nothing.
+    pub fee_lamports: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl ReferralConfig {
+    pub const SEED: &'static [u8] = b"REFERRAL";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+/// A referral fee paid with a request.
+#[event]
+pub struct ReferralFeePaid {
+    pub request: Pubkey,
+    pub recipient: Pubkey,
+    pub lamports: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReferralConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ReferralConfig::SPACE,
+        seeds = [ReferralConfig::SEED],
+        bump,
+    )]
+    pub referral_config: Box<Account<'info, ReferralConfig>>,
+
+    /// This program's ProgramData account. Its upgrade authority is the admin.
+    #[account(
+        seeds = [ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ConsumerError::NotProgramAuthority,
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    /// The program's upgrade authority.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeReferralConfig<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        recipient: Pubkey,
+        fee_lamports: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        let referral_config = &mut ctx.accounts.referral_config;
+        referral_config.bump = ctx.bumps.referral_config;
+        referral_config.recipient = recipient;
+        referral_config.fee_lamports = fee_lamports;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateReferralConfig<'info> {
+    #[account(
+        mut,
+        seeds = [ReferralConfig::SEED],
+        bump = referral_config.bump,
+    )]
+    pub referral_config: Box<Account<'info, ReferralConfig>>,
+
+    /// This program's ProgramData account. Its upgrade authority is the admin.
+    #[account(
+        seeds = [ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ConsumerError::NotProgramAuthority,
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    /// The program's upgrade authority.
+    pub admin: Signer<'info>,
+}
+
+impl UpdateReferralConfig<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        recipient: Pubkey,
+        fee_lamports: u64,
+    ) -> anchor_lang::prelude::Result<()> {
+        let referral_config = &mut ctx.accounts.referral_config;
+        referral_config.recipient = recipient;
+        referral_config.fee_lamports = fee_lamports;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RequestRandomnessWithReferralFee<'info> {
+    #[account(
+        seeds = [ReferralConfig::SEED],
+        bump = referral_config.bump,
+    )]
+    pub referral_config: Box<Account<'info, ReferralConfig>>,
+
+    /// The wallet paid the fee.
+    #[account(
+        mut,
+        address = referral_config.recipient @ ConsumerError::ReferralRecipientMismatch,
+    )]
+    pub referral_recipient: SystemAccount<'info>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// Pays for the request and the referral fee.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RequestRandomnessWithReferralFee<'_> {
+    /// Checks the request, takes the fee from the payer, then requests 8 bytes settled by
+    /// `consume_randomness`. All in one instruction, so a request the service rejects pays no
+    /// fee.
+    pub fn actuate(ctx: &Context<Self>) -> anchor_lang::prelude::Result<()> {
+        // Must match the order of the ConsumeRandomness accounts struct
+        let (callback, _) = LiteCallback::builder(ID)
+            .with_state_signer()
+            .with_readonly_account(ctx.accounts.randomness_request.key())
+            .with_ix_data(get_ixn_discriminator("consume_randomness").to_vec())
+            .build()?;
+        let options = LiteTransactionOptions {
+            compute_units: Some(1_000_000),
+            compute_unit_price: Some(100),
+        };
+        let request = SimpleRandomnessV1Request {
+            request: ctx.accounts.randomness_request.to_account_info(),
+            escrow: ctx.accounts.randomness_escrow.to_account_info(),
+            state: ctx.accounts.randomness_state.to_account_info(),
+            mint: ctx.accounts.randomness_mint.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        request.preflight_checks(&ID)?;
+
+        let fee_lamports = ctx.accounts.referral_config.fee_lamports;
+        if fee_lamports > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: ctx.accounts.referral_recipient.to_account_info(),
+                    },
+                ),
+                fee_lamports,
+            )?;
+        }
+        request.cpi(
+            ctx.accounts.randomness_service.to_account_info(),
+            8,
+            &callback,
+            Some(&options),
+        )?;
+
+        emit!(ReferralFeePaid {
+            request: ctx.accounts.randomness_request.key(),
+            recipient: ctx.accounts.referral_recipient.key(),
+            lamports: fee_lamports,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::*;
+use solana_randomness_service_lite::{
+    required_request_lamports, Callback as LiteCallback, PendingRecord, RequestGuard,
+    ServiceStateView, SimpleRandomnessV1Request, TransactionOptions as LiteTransactionOptions,
+    RANDOMNESS_SERVICE_STATE,
+};
+
+/// The compute budget requested for the record callback, which only writes one account.
+pub const REGISTER_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// How long a registered request stays pending before `request_and_register` accepts a new
+/// one, about as long as the request transaction's blockhash is valid. Covers requests that
+/// errored or were never fulfilled.
+pub const REGISTER_REQUEST_TIMEOUT_SLOTS: u64 = 150;
+
+/// The consumer's bookkeeping for a user's latest request: which request it is, whether it is
+/// still pending, and its result once delivered. One PDA per user.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct RandomnessRecord {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The user the record belongs to, the payer of its requests.
+    pub user: Pubkey,
+    /// The latest request.
+    pub request: Pubkey,
+    /// The slot the latest request was made at.
+    pub request_slot: u64,
+    /// Whether the latest request is waiting for its callback.
+    pub pending: bool,
+    /// The slot the result was delivered at. Zero until the first result.
+    pub slot: u64,
+    /// The number of valid bytes in `result`.
+    pub result_len: u8,
+    /// The latest result, zero padded to the service's 32 byte maximum.
+    pub result: [u8; 32],
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl RandomnessRecord {
+    pub const SEED: &'static [u8] = b"RANDOMNESS_RECORD";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Whether a new request can be registered at `slot`: none is pending, or the pending one
+    /// timed out.
+    pub fn accepts_request(&self, slot: u64) -> bool {
+        !self.pending || slot.saturating_sub(self.request_slot) >= REGISTER_REQUEST_TIMEOUT_SLOTS
+    }
+}
+
+impl PendingRecord for RandomnessRecord {
+    fn mark_pending(&mut self, request: Pubkey, slot: u64) {
+        self.request = request;
+        self.request_slot = slot;
+        self.pending = true;
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending = false;
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeRandomnessRecord<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = RandomnessRecord::SPACE,
+        seeds = [RandomnessRecord::SEED, payer.key().as_ref()],
+        bump,
+    )]
+    pub record: Box<Account<'info, RandomnessRecord>>,
+
+    /// The user the record belongs to.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeRandomnessRecord<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        ctx.accounts.record.bump = ctx.bumps.record;
+        ctx.accounts.record.user = ctx.accounts.payer.key();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RequestAndRegister<'info> {
+    /// The payer's record. Marked pending on the new request.
+    #[account(
+        mut,
+        seeds = [RandomnessRecord::SEED, payer.key().as_ref()],
+        bump = record.bump,
+    )]
+    pub record: Box<Account<'info, RandomnessRecord>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account. Only `cost_per_byte` is read, through
+    /// `ServiceStateView` at its fixed offset, so the state is not deserialized.
+    /// CHECK: the service's state address, and the view checks the owner and discriminator
+    #[account(address = RANDOMNESS_SERVICE_STATE)]
+    pub randomness_state: AccountInfo<'info>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The user requesting randomness. Pays for the request.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RequestAndRegister<'_> {
+    /// Requests randomness and registers it in the payer's record, in the one order that keeps
+    /// the two consistent: validate, write the pending record, invoke the service.
+    ///
+    /// - Validating after the write would check the record this instruction just overwrote,
+    ///   so a second request would always look allowed.
+    /// - Invoking the service first only moves the failure: the transaction is atomic either
+    ///   way, but a request the record then rejects has already spent the compute of creating
+    ///   the request and its escrow.
+    /// - Writing the record in another instruction or transaction breaks the atomicity: a
+    ///   request can land without its record, so its callback fails the record's constraint,
+    ///   or a record can name a request that never landed.
+    /// - A failed CPI cannot be caught. It fails the transaction, and the runtime discards the
+    ///   pending record with it, so no code undoes the write.
+    pub fn actuate(ctx: &mut Context<Self>, num_bytes: u8) -> anchor_lang::prelude::Result<()> {
+        let slot = Clock::get()?.slot;
+
+        // 1. Validate, before anything is written
+        require!(
+            (1..=32).contains(&num_bytes),
+            ConsumerError::InvalidRandomnessLength
+        );
+        require!(
+            ctx.accounts.record.accepts_request(slot),
+            ConsumerError::RequestStillPending
+        );
+        // Must match the order of the ConsumeRegisteredRandomness accounts struct
+        let (callback, _) = LiteCallback::builder(ID)
+            .with_state_signer()
+            .with_readonly_account(ctx.accounts.randomness_request.key())
+            .with_writable_account(ctx.accounts.record.key())
+            .with_ix_data(get_ixn_discriminator("consume_registered_randomness").to_vec())
+            .build()?;
+        let options = LiteTransactionOptions {
+            compute_units: Some(REGISTER_CALLBACK_COMPUTE_UNITS),
+            compute_unit_price: Some(100),
+        };
+        let required = required_request_lamports(
+            num_bytes,
+            &callback,
+            Some(&options),
+            ServiceStateView::load(&ctx.accounts.randomness_state)?.cost_per_byte(),
+            &Rent::get()?,
+        )?;
+        require_gte!(
+            ctx.accounts.payer.lamports(),
+            required,
+            ConsumerError::InsufficientFunding
+        );
+
+        let request = SimpleRandomnessV1Request {
+            request: ctx.accounts.randomness_request.to_account_info(),
+            escrow: ctx.accounts.randomness_escrow.to_account_info(),
+            state: ctx.accounts.randomness_state.to_account_info(),
+            mint: ctx.accounts.randomness_mint.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        let program = ctx.accounts.randomness_service.to_account_info();
+        // The mint is pinned to the native mint above, which only the live state can confirm
+        request.preflight_checks(&ID)?;
+
+        // 2. Write the pending record
+        let guard = RequestGuard::begin(&mut **ctx.accounts.record, *request.request.key, slot);
+
+        // 3. Invoke the service. If it fails, so does the transaction, record included
+        request.cpi(program, num_bytes, &callback, Some(&options))?;
+        guard.commit();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeRegisteredRandomness<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: the view checks the owner and discriminator
+    pub request: AccountInfo<'info>,
+
+    /// Only the record's pending request may settle it, so a late callback from a request
+    /// that timed out cannot replace the newer one.
+    #[account(
+        mut,
+        seeds = [RandomnessRecord::SEED, record.user.as_ref()],
+        bump = record.bump,
+        constraint = record.pending && record.request == request.key()
+            @ ConsumerError::UnexpectedRecordRequest,
+    )]
+    pub record: Box<Account<'info, RandomnessRecord>>,
+}
+
+impl ConsumeRegisteredRandomness<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+        SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+        let record = &mut ctx.accounts.record;
+        require!(
+            !result.is_empty() && result.len() <= record.result.len(),
+            ConsumerError::InvalidRandomnessLength
+        );
+
+        record.pending = false;
+        record.slot = Clock::get()?.slot;
+        record.result_len = result.len() as u8;
+        record.result = [0; 32];
+        record.result[..result.len()].copy_from_slice(&result);
+
+        Ok(())
+    }
+}
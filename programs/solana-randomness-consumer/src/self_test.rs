@@ -0,0 +1,261 @@
+use crate::*;
+use anchor_lang::prelude::ProgramData;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use solana_randomness_service_lite::{
+    Callback as LiteCallback, PendingRecord, RequestGuard, SimpleRandomnessV1Request,
+    TransactionOptions as LiteTransactionOptions,
+};
+
+/// The compute budget requested for the self-test callback, which only writes one account.
+pub const SELF_TEST_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// The randomness requested by a self-test, the least the service accepts.
+pub const SELF_TEST_NUM_BYTES: u8 = 1;
+
+/// How long a self-test stays pending before `self_test` accepts a new one. A self-test still
+/// pending after this long was not fulfilled, e.g. because the oracle could not reach the
+/// callback.
+pub const SELF_TEST_TIMEOUT_SLOTS: u64 = 150;
+
+/// The outcome of the latest self-test, which sends a request through the configured
+/// randomness service with a callback into this program. One PDA per program, polled by
+/// operators after a deploy.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct SelfTestResult {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The upgrade authority that started the latest self-test.
+    pub admin: Pubkey,
+    /// The latest self-test's request.
+    pub request: Pubkey,
+    /// The slot the latest self-test was requested at.
+    pub request_slot: u64,
+    /// Whether the latest self-test is waiting for its callback.
+    pub pending: bool,
+    /// The slot the latest self-test's callback ran at. Zero until the first one passes.
+    pub settled_slot: u64,
+    /// The byte the latest passing self-test received.
+    pub result: u8,
+    /// The number of self-tests started.
+    pub runs: u64,
+    /// The number of self-tests whose callback ran.
+    pub passes: u64,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl SelfTestResult {
+    pub const SEED: &'static [u8] = b"SELF_TEST";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Whether a new self-test can start at `slot`: none is pending, or the pending one timed
+    /// out.
+    pub fn accepts_request(&self, slot: u64) -> bool {
+        !self.pending || slot.saturating_sub(self.request_slot) >= SELF_TEST_TIMEOUT_SLOTS
+    }
+}
+
+impl PendingRecord for SelfTestResult {
+    fn mark_pending(&mut self, request: Pubkey, slot: u64) {
+        self.request = request;
+        self.request_slot = slot;
+        self.pending = true;
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending = false;
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeSelfTest<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = SelfTestResult::SPACE,
+        seeds = [SelfTestResult::SEED],
+        bump,
+    )]
+    pub self_test_result: Box<Account<'info, SelfTestResult>>,
+
+    /// This program's ProgramData account. Its upgrade authority is the admin.
+    #[account(
+        seeds = [ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ConsumerError::NotProgramAuthority,
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    /// The program's upgrade authority.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeSelfTest<'_> {
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        ctx.accounts.self_test_result.bump = ctx.bumps.self_test_result;
+        ctx.accounts.self_test_result.admin = ctx.accounts.admin.key();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SelfTest<'info> {
+    /// Marked pending on the new request.
+    #[account(
+        mut,
+        seeds = [SelfTestResult::SEED],
+        bump = self_test_result.bump,
+    )]
+    pub self_test_result: Box<Account<'info, SelfTestResult>>,
+
+    /// This program's ProgramData account. Its upgrade authority is the admin.
+    #[account(
+        seeds = [ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ConsumerError::NotProgramAuthority,
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The program's upgrade authority. Pays for the request.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl SelfTest<'_> {
+    /// Requests [`SELF_TEST_NUM_BYTES`] with a callback that only records its arrival, marking
+    /// the result pending in the same order as `request_and_register`.
+    pub fn actuate(ctx: &mut Context<Self>) -> anchor_lang::prelude::Result<()> {
+        let slot = Clock::get()?.slot;
+        require!(
+            ctx.accounts.self_test_result.accepts_request(slot),
+            ConsumerError::SelfTestPending
+        );
+        // Must match the order of the ConsumeSelfTest accounts struct
+        let (callback, _) = LiteCallback::builder(ID)
+            .with_state_signer()
+            .with_readonly_account(ctx.accounts.randomness_request.key())
+            .with_writable_account(ctx.accounts.self_test_result.key())
+            .with_ix_data(get_ixn_discriminator("consume_self_test").to_vec())
+            .build()?;
+        let options = LiteTransactionOptions {
+            compute_units: Some(SELF_TEST_CALLBACK_COMPUTE_UNITS),
+            compute_unit_price: Some(100),
+        };
+
+        let request = SimpleRandomnessV1Request {
+            request: ctx.accounts.randomness_request.to_account_info(),
+            escrow: ctx.accounts.randomness_escrow.to_account_info(),
+            state: ctx.accounts.randomness_state.to_account_info(),
+            mint: ctx.accounts.randomness_mint.to_account_info(),
+            payer: ctx.accounts.admin.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        let program = ctx.accounts.randomness_service.to_account_info();
+        request.preflight_checks(&ID)?;
+
+        let self_test_result = &mut **ctx.accounts.self_test_result;
+        self_test_result.admin = ctx.accounts.admin.key();
+        self_test_result.runs += 1;
+        let guard = RequestGuard::begin(self_test_result, *request.request.key, slot);
+        request.cpi(program, SELF_TEST_NUM_BYTES, &callback, Some(&options))?;
+        guard.commit();
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConsumeSelfTest<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled, read with `SimpleRandomnessV1AccountView` instead of
+    /// deserializing the whole account.
+    /// CHECK: the view checks the owner and discriminator
+    pub request: AccountInfo<'info>,
+
+    /// Only the pending self-test's request may settle it, so a late callback from one that
+    /// timed out cannot pass the newer one.
+    #[account(
+        mut,
+        seeds = [SelfTestResult::SEED],
+        bump = self_test_result.bump,
+        constraint = self_test_result.pending && self_test_result.request == request.key()
+            @ ConsumerError::UnexpectedSelfTestRequest,
+    )]
+    pub self_test_result: Box<Account<'info, SelfTestResult>>,
+}
+
+impl ConsumeSelfTest<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, result: Vec<u8>) -> anchor_lang::prelude::Result<()> {
+        SimpleRandomnessV1AccountView::load(&ctx.accounts.request)?;
+        require!(
+            result.len() == usize::from(SELF_TEST_NUM_BYTES),
+            ConsumerError::InvalidRandomnessLength
+        );
+
+        let self_test_result = &mut ctx.accounts.self_test_result;
+        self_test_result.pending = false;
+        self_test_result.settled_slot = Clock::get()?.slot;
+        self_test_result.result = result[0];
+        self_test_result.passes += 1;
+
+        Ok(())
+    }
+}
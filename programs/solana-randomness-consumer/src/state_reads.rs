@@ -0,0 +1,39 @@
+use crate::*;
+use anchor_lang::solana_program::log::sol_log_compute_units;
+use solana_randomness_service_lite::{ServiceStateView, RANDOMNESS_SERVICE_STATE};
+
+/// Measures the compute units of reading the service's state both ways: deserializing the
+/// whole `ServiceState`, as `Account<'info, ServiceState>` does, and reading `cost_per_byte`
+/// and `mint` at their fixed offsets with `ServiceStateView`, as `request_and_register` does.
+///
+/// Run it on a validator and read the `Program consumption: N units remaining` lines it logs
+/// before and after each read. The differences are the cost of each.
+#[derive(Accounts)]
+pub struct MeasureStateReads<'info> {
+    /// The randomness service's state account.
+    /// CHECK: the service's state address, checked again by both reads
+    #[account(address = RANDOMNESS_SERVICE_STATE)]
+    pub randomness_state: AccountInfo<'info>,
+}
+
+impl MeasureStateReads<'_> {
+    pub fn actuate(ctx: &Context<Self>) -> anchor_lang::prelude::Result<()> {
+        let info = &ctx.accounts.randomness_state;
+
+        msg!("Deserializing the full state");
+        sol_log_compute_units();
+        let state = Account::<ServiceState>::try_from(info)?;
+        sol_log_compute_units();
+
+        msg!("Reading the state at fixed offsets");
+        sol_log_compute_units();
+        let view = ServiceStateView::load(info)?;
+        let (cost_per_byte, mint) = (view.cost_per_byte(), view.mint());
+        sol_log_compute_units();
+
+        require_eq!(cost_per_byte, state.cost_per_byte);
+        require_keys_eq!(mint, state.mint);
+
+        Ok(())
+    }
+}
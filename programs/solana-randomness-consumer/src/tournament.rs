@@ -0,0 +1,308 @@
+use crate::*;
+use solana_randomness_service_lite::{
+    Callback as LiteCallback, SimpleRandomnessV1Request,
+    TransactionOptions as LiteTransactionOptions,
+};
+
+/// The compute budget requested for the bracket callback, which only writes one account.
+pub const BRACKET_CALLBACK_COMPUTE_UNITS: u32 = 200_000;
+
+/// The most brackets a tournament can have.
+pub const MAX_BRACKETS: u16 = 64;
+
+/// A tournament whose brackets each receive their own randomness. Requests for every bracket
+/// share one callback instruction, which finds the bracket from the indexed tag in its
+/// instruction data. One PDA per authority.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Tournament {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The account that creates the brackets and requests their randomness.
+    pub authority: Pubkey,
+    /// The tag of the tournament's requests, checked by the callback.
+    pub id: u64,
+    /// The number of brackets, at most `MAX_BRACKETS`. Bracket indices are below it.
+    pub bracket_count: u16,
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl Tournament {
+    pub const SEED: &'static [u8] = b"TOURNAMENT";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+/// One bracket of a tournament and the latest randomness delivered to it. The PDA of
+/// `[Bracket::SEED, tournament, index]`, with the index in little endian.
+#[account]
+#[derive(Debug, InitSpace)]
+pub struct Bracket {
+    /// The PDA bump.
+    pub bump: u8,
+    /// The tournament the bracket belongs to.
+    pub tournament: Pubkey,
+    /// The bracket's index in the tournament.
+    pub index: u16,
+    /// The request awaiting its callback, the default pubkey if none. Only it can write the
+    /// result.
+    pub pending_request: Pubkey,
+    /// The request that delivered the latest result.
+    pub request: Pubkey,
+    /// The slot the latest result was delivered at. Zero until the first result.
+    pub slot: u64,
+    /// The number of valid bytes in `result`.
+    pub result_len: u8,
+    /// The latest result, zero padded to the service's 32 byte maximum.
+    pub result: [u8; 32],
+    /// Reserved for future fields, always zero.
+    pub reserved: [u8; 32],
+}
+
+impl Bracket {
+    pub const SEED: &'static [u8] = b"BRACKET";
+    /// The size of the account, discriminator included.
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+#[derive(Accounts)]
+pub struct InitializeTournament<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Tournament::SPACE,
+        seeds = [Tournament::SEED, authority.key().as_ref()],
+        bump,
+    )]
+    pub tournament: Box<Account<'info, Tournament>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeTournament<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        id: u64,
+        bracket_count: u16,
+    ) -> anchor_lang::prelude::Result<()> {
+        require!(
+            (1..=MAX_BRACKETS).contains(&bracket_count),
+            ConsumerError::InvalidBracketCount
+        );
+
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.bump = ctx.bumps.tournament;
+        tournament.authority = ctx.accounts.authority.key();
+        tournament.id = id;
+        tournament.bracket_count = bracket_count;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct InitializeBracket<'info> {
+    #[account(
+        has_one = authority,
+        constraint = index < tournament.bracket_count @ ConsumerError::BracketIndexOutOfBounds,
+    )]
+    pub tournament: Box<Account<'info, Tournament>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Bracket::SPACE,
+        seeds = [Bracket::SEED, tournament.key().as_ref(), &index.to_le_bytes()],
+        bump,
+    )]
+    pub bracket: Box<Account<'info, Bracket>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeBracket<'_> {
+    pub fn actuate(ctx: &mut Context<Self>, index: u16) -> anchor_lang::prelude::Result<()> {
+        let bracket = &mut ctx.accounts.bracket;
+        bracket.bump = ctx.bumps.bracket;
+        bracket.tournament = ctx.accounts.tournament.key();
+        bracket.index = index;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct RequestBracketRandomness<'info> {
+    #[account(
+        has_one = authority,
+        constraint = index < tournament.bracket_count @ ConsumerError::BracketIndexOutOfBounds,
+    )]
+    pub tournament: Box<Account<'info, Tournament>>,
+
+    /// The bracket at `index`. The callback writes the result into it.
+    #[account(
+        mut,
+        seeds = [Bracket::SEED, tournament.key().as_ref(), &index.to_le_bytes()],
+        bump = bracket.bump,
+    )]
+    pub bracket: Box<Account<'info, Bracket>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: validated by the randomness service
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Box<Account<'info, Mint>>,
+
+    /// The tournament authority. Pays for the request.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RequestBracketRandomness<'_> {
+    /// Requests the randomness and records the request as the bracket's pending one. A new
+    /// request replaces a pending one, whose late callback is then rejected.
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        index: u16,
+        num_bytes: u8,
+    ) -> anchor_lang::prelude::Result<()> {
+        // Must match the order of the ConsumeBracketRandomness accounts struct. The tag
+        // becomes the callback's `tag` and `index` arguments
+        let (callback, _) = LiteCallback::builder(ID)
+            .with_state_signer()
+            .with_readonly_account(ctx.accounts.randomness_request.key())
+            .with_readonly_account(ctx.accounts.tournament.key())
+            .with_writable_account(ctx.accounts.bracket.key())
+            .with_ix_data(get_ixn_discriminator("consume_bracket_randomness").to_vec())
+            .with_indexed_tag(ctx.accounts.tournament.id, index)
+            .build()?;
+        ctx.accounts.bracket.pending_request = ctx.accounts.randomness_request.key();
+        let options = LiteTransactionOptions {
+            compute_units: Some(BRACKET_CALLBACK_COMPUTE_UNITS),
+            compute_unit_price: Some(100),
+        };
+
+        let request = SimpleRandomnessV1Request {
+            request: ctx.accounts.randomness_request.to_account_info(),
+            escrow: ctx.accounts.randomness_escrow.to_account_info(),
+            state: ctx.accounts.randomness_state.to_account_info(),
+            mint: ctx.accounts.randomness_mint.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        let program = ctx.accounts.randomness_service.to_account_info();
+        request.cpi(program, num_bytes, &callback, Some(&options))?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(tag: u64, index: u16)]
+pub struct ConsumeBracketRandomness<'info> {
+    /// We need to make sure the randomness service signed this requests so it can only be invoked by a PDA and not a user.
+    #[account(
+        signer,
+        seeds = [b"STATE"],
+        seeds::program = SolanaRandomnessServiceID,
+        bump = randomness_state.bump,
+    )]
+    pub randomness_state: Box<Account<'info, ServiceState>>,
+
+    /// The request being settled.
+    /// CHECK: `verify_callback` checks the owner, discriminator, and callback program
+    pub request: AccountInfo<'info>,
+
+    /// The tagged request's tournament. Checked before the bracket, so an index past the
+    /// brackets fails with its own error instead of a seeds mismatch.
+    #[account(
+        constraint = tag == tournament.id @ ConsumerError::TournamentTagMismatch,
+        constraint = index < tournament.bracket_count @ ConsumerError::BracketIndexOutOfBounds,
+    )]
+    pub tournament: Box<Account<'info, Tournament>>,
+
+    /// The bracket derived from the tag's index, whatever account the request listed. Only
+    /// its pending request may write it, so a request made outside the program, tagged with
+    /// the tournament's id, cannot overwrite or re-roll the result.
+    #[account(
+        mut,
+        seeds = [Bracket::SEED, tournament.key().as_ref(), &index.to_le_bytes()],
+        bump = bracket.bump,
+        constraint = bracket.pending_request == request.key()
+            @ ConsumerError::UnexpectedBracketRequest,
+    )]
+    pub bracket: Box<Account<'info, Bracket>>,
+}
+
+impl ConsumeBracketRandomness<'_> {
+    pub fn actuate(
+        ctx: &mut Context<Self>,
+        tag: u64,
+        index: u16,
+        result: Vec<u8>,
+    ) -> anchor_lang::prelude::Result<()> {
+        verify_callback(
+            &ctx.accounts.randomness_state.to_account_info(),
+            &ctx.accounts.request,
+            &ID,
+        )?;
+        msg!("Randomness for bracket {} of tournament {}", index, tag);
+        let bracket = &mut ctx.accounts.bracket;
+        require!(
+            !result.is_empty() && result.len() <= bracket.result.len(),
+            ConsumerError::InvalidRandomnessLength
+        );
+
+        bracket.pending_request = Pubkey::default();
+        bracket.request = ctx.accounts.request.key();
+        bracket.slot = Clock::get()?.slot;
+        bracket.result_len = result.len() as u8;
+        bracket.result = [0; 32];
+        bracket.result[..result.len()].copy_from_slice(&result);
+
+        Ok(())
+    }
+}
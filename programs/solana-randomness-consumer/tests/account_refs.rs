@@ -0,0 +1,27 @@
+//! Builds the lite crate's borrowed request accounts from an Anchor accounts struct. Compiling
+//! is the test: the refs borrow from `ctx.accounts` without cloning any `AccountInfo`.
+
+use solana_randomness_consumer::RequestRandomness;
+use solana_randomness_service_lite::SimpleRandomnessV1AccountRefs;
+
+fn request_refs<'a, 'info>(
+    accounts: &'a RequestRandomness<'info>,
+) -> SimpleRandomnessV1AccountRefs<'a, 'info> {
+    SimpleRandomnessV1AccountRefs {
+        request: &accounts.randomness_request,
+        escrow: &accounts.randomness_escrow,
+        state: accounts.randomness_state.as_ref().as_ref(),
+        mint: accounts.randomness_mint.as_ref(),
+        payer: accounts.payer.as_ref(),
+        system_program: accounts.system_program.as_ref(),
+        token_program: accounts.token_program.as_ref(),
+        associated_token_program: accounts.associated_token_program.as_ref(),
+    }
+}
+
+#[test]
+fn test_refs_borrow_from_anchor_accounts() {
+    let _: for<'a, 'info> fn(
+        &'a RequestRandomness<'info>,
+    ) -> SimpleRandomnessV1AccountRefs<'a, 'info> = request_refs;
+}
@@ -0,0 +1,31 @@
+//! The lite crate's address helpers agree with `anchor-spl` and the service crate.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::native_mint;
+use solana_randomness_service_lite::{derive_escrow_pubkey, derive_state_pubkey};
+
+#[test]
+fn test_escrow_matches_associated_token_address() {
+    for _ in 0..8 {
+        let request = Pubkey::new_unique();
+        assert_eq!(
+            derive_escrow_pubkey(&request, &native_mint::ID),
+            get_associated_token_address(&request, &native_mint::ID)
+        );
+
+        let mint = Pubkey::new_unique();
+        assert_eq!(
+            derive_escrow_pubkey(&request, &mint),
+            get_associated_token_address(&request, &mint)
+        );
+    }
+}
+
+#[test]
+fn test_state_matches_service_crate() {
+    let (state, _) = derive_state_pubkey(&solana_randomness_service::ID);
+    let (expected, _) = Pubkey::find_program_address(&[b"STATE"], &solana_randomness_service::ID);
+
+    assert_eq!(state, expected);
+}
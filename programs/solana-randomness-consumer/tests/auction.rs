@@ -0,0 +1,415 @@
+//! Walks the tie break state machine through every transition with the lite crate's oracle
+//! harness, and checks every transition from the wrong state fails with its own error.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{
+    pick_winner, ConsumerError, TieBreak, TieBreakState, TIE_BREAK_CALLBACK_COMPUTE_UNITS,
+    TIE_BREAK_MAX_BIDDERS, TIE_BREAK_TIMEOUT_SLOTS,
+};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, Callback, LiteError, RANDOMNESS_SERVICE_STATE,
+};
+
+const AUCTION_ID: u64 = 7;
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    authority: Pubkey,
+    tie_break: Pubkey,
+    bidders: Vec<Pubkey>,
+}
+
+impl Fixture {
+    /// A tie break between three bidders, as `initialize_tie_break` leaves it.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+
+        let authority = harness.payer();
+        harness.airdrop(&authority, 1_000_000_000);
+        let (tie_break, _) = Pubkey::find_program_address(
+            &[
+                TieBreak::SEED,
+                authority.as_ref(),
+                &AUCTION_ID.to_le_bytes(),
+            ],
+            &solana_randomness_consumer::ID,
+        );
+
+        let mut fixture = Self {
+            harness,
+            authority,
+            tie_break,
+            bidders: (0..3).map(|_| Pubkey::new_unique()).collect(),
+        };
+        fixture.set_state(TieBreakState::Open);
+        fixture
+    }
+
+    /// Overwrites the tie break's state, keeping the account at its full size so every
+    /// state fits.
+    fn set_state(&mut self, state: TieBreakState) {
+        let (_, bump) = Pubkey::find_program_address(
+            &[
+                TieBreak::SEED,
+                self.authority.as_ref(),
+                &AUCTION_ID.to_le_bytes(),
+            ],
+            &solana_randomness_consumer::ID,
+        );
+        let mut bidders = [Pubkey::default(); 8];
+        bidders[..self.bidders.len()].copy_from_slice(&self.bidders);
+        let mut data = Vec::new();
+        TieBreak {
+            bump,
+            authority: self.authority,
+            id: AUCTION_ID,
+            bidder_count: self.bidders.len() as u8,
+            bidders,
+            state,
+            reserved: [0; 32],
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        data.resize(TieBreak::SPACE, 0);
+        self.harness.set_account(
+            self.tie_break,
+            HarnessAccount::new(data, solana_randomness_consumer::ID),
+        );
+    }
+
+    fn state(&self) -> TieBreakState {
+        let account = self.harness.account(&self.tie_break).unwrap();
+        TieBreak::try_deserialize(&mut &account.data[..])
+            .unwrap()
+            .state
+    }
+
+    fn request_instruction(&self, request: Pubkey) -> Instruction {
+        let accounts = solana_randomness_consumer::accounts::RequestTieBreakRandomness {
+            tie_break: self.tie_break,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: self.harness.escrow(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            authority: self.authority,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::RequestTieBreakRandomness {}.data(),
+        }
+    }
+
+    /// Sends `request_tie_break_randomness` for a new request without settling it.
+    fn request(&mut self) -> std::result::Result<Pubkey, ProgramError> {
+        let request = Pubkey::new_unique();
+        self.harness
+            .process_transaction(&[self.request_instruction(request)])?;
+        Ok(request)
+    }
+
+    /// Creates a request whose callback targets the tie break without going through the
+    /// program, to deliver a callback in any state.
+    fn submit_request(&mut self) -> Pubkey {
+        let request = Pubkey::new_unique();
+        let (callback, _) = Callback::builder(solana_randomness_consumer::ID)
+            .with_state_signer()
+            .with_readonly_account(request)
+            .with_writable_account(self.tie_break)
+            .with_ix_data(anchor_sighash("consume_tie_break_randomness").to_vec())
+            .build()
+            .unwrap();
+        self.harness
+            .submit_request(request, 8, callback, None)
+            .unwrap();
+        request
+    }
+
+    fn fulfill(&mut self, request: &Pubkey) -> std::result::Result<(), ProgramError> {
+        let randomness = self.harness.randomness(request, 8);
+        self.harness.fulfill(request, &randomness)
+    }
+
+    fn settle(&mut self, signer: Pubkey) -> std::result::Result<(), ProgramError> {
+        let accounts = solana_randomness_consumer::accounts::SettleTieBreak {
+            tie_break: self.tie_break,
+            authority: signer,
+        };
+        self.harness.process_transaction(&[Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::SettleTieBreak {}.data(),
+        }])?;
+        Ok(())
+    }
+
+    fn reclaim(&mut self, caller: Pubkey) -> std::result::Result<(), ProgramError> {
+        let accounts = solana_randomness_consumer::accounts::ReclaimTieBreak {
+            tie_break: self.tie_break,
+            caller,
+        };
+        self.harness.process_transaction(&[Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::ReclaimTieBreak {}.data(),
+        }])?;
+        Ok(())
+    }
+
+    /// The winner the harness's randomness for `request` picks.
+    fn winner(&self, request: &Pubkey) -> Pubkey {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.harness.randomness(request, 8));
+        pick_winner(&self.bidders, bytes)
+    }
+
+    fn received(&self, request: &Pubkey, slot: u64) -> TieBreakState {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.harness.randomness(request, 8));
+        TieBreakState::RandomnessReceived { bytes, slot }
+    }
+}
+
+#[test]
+fn test_request_fulfill_settle() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+
+    let request = fixture.request().unwrap();
+    assert_eq!(
+        fixture.state(),
+        TieBreakState::AwaitingRandomness {
+            request,
+            request_slot: 100
+        }
+    );
+    let pending = fixture.harness.request(&request).unwrap();
+    assert_eq!(pending.compute_units, TIE_BREAK_CALLBACK_COMPUTE_UNITS);
+    assert_eq!(
+        pending.callback.discriminator(),
+        Some(anchor_sighash("consume_tie_break_randomness"))
+    );
+
+    fixture.harness.warp_to_slot(101);
+    fixture.fulfill(&request).unwrap();
+    assert_eq!(fixture.state(), fixture.received(&request, 101));
+
+    let authority = fixture.authority;
+    fixture.settle(authority).unwrap();
+    let winner = fixture.winner(&request);
+    assert!(fixture.bidders.contains(&winner));
+    assert_eq!(fixture.state(), TieBreakState::Settled { winner });
+}
+
+#[test]
+fn test_reclaim_lost_request_reopens() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let lost = fixture.request().unwrap();
+    let bidder = fixture.bidders[0];
+
+    fixture
+        .harness
+        .warp_to_slot(100 + TIE_BREAK_TIMEOUT_SLOTS - 1);
+    assert_eq!(
+        fixture.reclaim(bidder),
+        Err(consumer_error(ConsumerError::TieBreakReclaimTooEarly))
+    );
+
+    fixture.harness.warp_to_slot(100 + TIE_BREAK_TIMEOUT_SLOTS);
+    fixture.reclaim(bidder).unwrap();
+    assert_eq!(fixture.state(), TieBreakState::Open);
+
+    // The authority requests again, and the lost request's late callback is rejected
+    let retry = fixture.request().unwrap();
+    assert_eq!(
+        fixture.fulfill(&lost),
+        Err(consumer_error(ConsumerError::UnexpectedTieBreakRequest))
+    );
+    fixture.fulfill(&retry).unwrap();
+    let slot = fixture.harness.slot();
+    assert_eq!(fixture.state(), fixture.received(&retry, slot));
+}
+
+#[test]
+fn test_reclaim_unsettled_randomness_settles() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let request = fixture.request().unwrap();
+    fixture.fulfill(&request).unwrap();
+    let bidder = fixture.bidders[1];
+
+    fixture
+        .harness
+        .warp_to_slot(100 + TIE_BREAK_TIMEOUT_SLOTS - 1);
+    assert_eq!(
+        fixture.reclaim(bidder),
+        Err(consumer_error(ConsumerError::TieBreakReclaimTooEarly))
+    );
+
+    // The authority never settled, so a bidder does, with the same winner
+    fixture.harness.warp_to_slot(100 + TIE_BREAK_TIMEOUT_SLOTS);
+    fixture.reclaim(bidder).unwrap();
+    let winner = fixture.winner(&request);
+    assert_eq!(fixture.state(), TieBreakState::Settled { winner });
+}
+
+#[test]
+fn test_illegal_transitions() {
+    let request = Pubkey::new_unique();
+    let states = [
+        TieBreakState::Open,
+        TieBreakState::AwaitingRandomness {
+            request,
+            request_slot: 0,
+        },
+        TieBreakState::RandomnessReceived {
+            bytes: [1; 8],
+            slot: 0,
+        },
+        TieBreakState::Settled {
+            winner: Pubkey::new_unique(),
+        },
+    ];
+
+    for state in states {
+        let mut fixture = Fixture::new();
+        let authority = fixture.authority;
+        let expect_unchanged = |fixture: &Fixture| assert_eq!(fixture.state(), state);
+
+        // Requesting is only allowed from Open
+        fixture.set_state(state);
+        if state != TieBreakState::Open {
+            assert_eq!(
+                fixture.request(),
+                Err(consumer_error(ConsumerError::TieBreakNotOpen)),
+                "request from {:?}",
+                state
+            );
+            expect_unchanged(&fixture);
+        }
+
+        // A callback is only accepted while awaiting, and only for the awaited request
+        let other = fixture.submit_request();
+        let expected = match state {
+            TieBreakState::AwaitingRandomness { .. } => ConsumerError::UnexpectedTieBreakRequest,
+            _ => ConsumerError::TieBreakNotAwaitingRandomness,
+        };
+        assert_eq!(
+            fixture.fulfill(&other),
+            Err(consumer_error(expected)),
+            "callback in {:?}",
+            state
+        );
+        expect_unchanged(&fixture);
+
+        // Settling needs the randomness and happens once
+        let expected = match state {
+            TieBreakState::RandomnessReceived { .. } => None,
+            TieBreakState::Settled { .. } => Some(ConsumerError::TieBreakAlreadySettled),
+            _ => Some(ConsumerError::TieBreakRandomnessNotReceived),
+        };
+        if let Some(expected) = expected {
+            assert_eq!(
+                fixture.settle(authority),
+                Err(consumer_error(expected)),
+                "settle from {:?}",
+                state
+            );
+            expect_unchanged(&fixture);
+        }
+
+        // Reclaiming needs something to wait on, and a timeout
+        fixture.harness.warp_to_slot(TIE_BREAK_TIMEOUT_SLOTS - 1);
+        let expected = match state {
+            TieBreakState::Open => ConsumerError::TieBreakNothingToReclaim,
+            TieBreakState::Settled { .. } => ConsumerError::TieBreakAlreadySettled,
+            _ => ConsumerError::TieBreakReclaimTooEarly,
+        };
+        assert_eq!(
+            fixture.reclaim(Pubkey::new_unique()),
+            Err(consumer_error(expected)),
+            "reclaim from {:?}",
+            state
+        );
+        expect_unchanged(&fixture);
+    }
+}
+
+#[test]
+fn test_settle_requires_authority() {
+    let mut fixture = Fixture::new();
+    let request = fixture.request().unwrap();
+    fixture.fulfill(&request).unwrap();
+
+    assert_eq!(
+        fixture.settle(fixture.bidders[0]),
+        Err(ProgramError::Custom(
+            anchor_lang::error::ErrorCode::ConstraintHasOne as u32
+        ))
+    );
+    assert!(matches!(
+        fixture.state(),
+        TieBreakState::RandomnessReceived { .. }
+    ));
+}
+
+#[test]
+fn test_callback_rejects_wrong_randomness_length() {
+    let mut fixture = Fixture::new();
+    let request = fixture.request().unwrap();
+
+    assert_eq!(
+        fixture.harness.fulfill_short(&request, 4),
+        Err(LiteError::RandomnessLengthMismatch.into())
+    );
+    assert!(matches!(
+        fixture.state(),
+        TieBreakState::AwaitingRandomness { .. }
+    ));
+}
+
+#[test]
+fn test_bidder_count() {
+    let bidders: Vec<Pubkey> = (0..=TIE_BREAK_MAX_BIDDERS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+
+    assert!(TieBreak::check_bidders(&bidders[..2]).is_ok());
+    assert!(TieBreak::check_bidders(&bidders[..TIE_BREAK_MAX_BIDDERS]).is_ok());
+    for len in [0, 1, TIE_BREAK_MAX_BIDDERS + 1] {
+        assert_eq!(
+            TieBreak::check_bidders(&bidders[..len]).map_err(ProgramError::from),
+            Err(consumer_error(ConsumerError::InvalidBidderCount))
+        );
+    }
+}
+
+#[test]
+fn test_pick_winner_is_modulo() {
+    let bidders = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+
+    assert_eq!(pick_winner(&bidders, 0u64.to_le_bytes()), bidders[0]);
+    assert_eq!(pick_winner(&bidders, 4u64.to_le_bytes()), bidders[1]);
+    assert_eq!(pick_winner(&bidders, u64::MAX.to_le_bytes()), bidders[0]);
+}
@@ -0,0 +1,251 @@
+//! Checks when `get_or_request` reuses the cache, and refreshes it through the lite crate's
+//! oracle harness.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountSerialize, Discriminator};
+use solana_randomness_consumer::{
+    CacheLookup, CachedRandomness, ConsumerError, CACHE_REFRESH_TIMEOUT_SLOTS,
+};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, Callback, SimpleRandomnessV1Account, CALLBACK_RETRY_BACKOFF_SLOTS,
+    RANDOMNESS_SERVICE_STATE,
+};
+
+fn anchor_account(account: &impl AccountSerialize, owner: Pubkey) -> HarnessAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    HarnessAccount::new(data, owner)
+}
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+/// A cache holding `[1, 2, 3, 4]` delivered at slot 1_000.
+fn cache() -> CachedRandomness {
+    let mut result = [0; 32];
+    result[..4].copy_from_slice(&[1, 2, 3, 4]);
+    CachedRandomness {
+        bump: 255,
+        authority: Pubkey::new_unique(),
+        num_bytes: 4,
+        slot: 1_000,
+        result_len: 4,
+        result,
+        pending_request: Pubkey::default(),
+        pending_slot: 0,
+        reserved: [0; 32],
+    }
+}
+
+#[test]
+fn test_cache_layout_matches_lite() {
+    let authority = Pubkey::new_unique();
+
+    assert_eq!(
+        solana_randomness_service_lite::CachedRandomness::address(
+            &solana_randomness_consumer::ID,
+            &authority
+        ),
+        Pubkey::find_program_address(
+            &[CachedRandomness::SEED, authority.as_ref()],
+            &solana_randomness_consumer::ID
+        )
+    );
+    assert_eq!(
+        solana_randomness_service_lite::CachedRandomness::DISCRIMINATOR,
+        CachedRandomness::DISCRIMINATOR
+    );
+    assert_eq!(
+        solana_randomness_service_lite::CachedRandomness::SPACE,
+        CachedRandomness::SPACE
+    );
+}
+
+#[test]
+fn test_fresh_hit() {
+    let cache = cache();
+
+    assert_eq!(cache.lookup(1_000, 0), CacheLookup::Fresh(&[1, 2, 3, 4]));
+    assert_eq!(cache.lookup(1_300, 300), CacheLookup::Fresh(&[1, 2, 3, 4]));
+}
+
+#[test]
+fn test_stale_miss() {
+    let cache = cache();
+    assert_eq!(cache.lookup(1_301, 300), CacheLookup::Refresh);
+
+    // An empty cache always needs a refresh
+    let empty = CachedRandomness {
+        slot: 0,
+        result_len: 0,
+        ..cache
+    };
+    assert_eq!(empty.lookup(0, u64::MAX), CacheLookup::Refresh);
+}
+
+#[test]
+fn test_refresh_pending() {
+    let request = Pubkey::new_unique();
+    let cache = CachedRandomness {
+        pending_request: request,
+        pending_slot: 1_400,
+        ..cache()
+    };
+
+    // A second caller does not pay for another request while the first is in flight
+    assert_eq!(
+        cache.lookup(1_401, 300),
+        CacheLookup::RefreshPending(request)
+    );
+    // A pending refresh does not hide a result that is still fresh
+    assert_eq!(cache.lookup(1_200, 300), CacheLookup::Fresh(&[1, 2, 3, 4]));
+    // A refresh that never arrived is given up on
+    assert_eq!(
+        cache.lookup(1_400 + CACHE_REFRESH_TIMEOUT_SLOTS, 300),
+        CacheLookup::Refresh
+    );
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    cache: Pubkey,
+}
+
+impl Fixture {
+    /// A cache with a refresh pending for `pending_request`.
+    fn new(pending_request: Pubkey) -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+
+        let (state, state_bump) =
+            Pubkey::find_program_address(&[b"STATE"], &solana_randomness_service::ID);
+        harness.set_account(
+            state,
+            anchor_account(
+                &solana_randomness_service::State {
+                    bump: state_bump,
+                    authority: Pubkey::new_unique(),
+                    mint: anchor_spl::token::spl_token::native_mint::ID,
+                    switchboard_service: Pubkey::new_unique(),
+                    wallet: Pubkey::new_unique(),
+                    cost_per_byte: 10_000,
+                    last_updated: 0,
+                    _ebuf: [0; 512],
+                },
+                solana_randomness_service::ID,
+            ),
+        );
+
+        let authority = Pubkey::new_unique();
+        let (cache, bump) = solana_randomness_service_lite::CachedRandomness::address(
+            &solana_randomness_consumer::ID,
+            &authority,
+        );
+        harness.set_account(
+            cache,
+            anchor_account(
+                &CachedRandomness {
+                    bump,
+                    authority,
+                    pending_request,
+                    pending_slot: 1_400,
+                    ..self::cache()
+                },
+                solana_randomness_consumer::ID,
+            ),
+        );
+
+        Self { harness, cache }
+    }
+
+    /// Adds a request as left behind by `get_or_request`.
+    fn add_request(&mut self, request: Pubkey) {
+        self.harness.add_request(
+            request,
+            &SimpleRandomnessV1Account {
+                num_bytes: 4,
+                user: Pubkey::new_unique(),
+                callback: Callback::new(
+                    solana_randomness_consumer::ID,
+                    vec![
+                        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+                        AccountMeta::new_readonly(request, false).into(),
+                        AccountMeta::new(self.cache, false).into(),
+                    ],
+                    anchor_sighash("consume_cached_randomness").to_vec(),
+                ),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn cache(&self) -> solana_randomness_service_lite::CachedRandomness {
+        let account = self.harness.account(&self.cache).unwrap();
+        solana_randomness_service_lite::CachedRandomness::try_deserialize(&mut &account.data[..])
+            .unwrap()
+    }
+}
+
+#[test]
+fn test_callback_refreshes_cache() {
+    let request = Pubkey::new_unique();
+    let mut fixture = Fixture::new(request);
+    fixture.add_request(request);
+
+    fixture.harness.warp_to_slot(1_410);
+    let result = fixture.harness.randomness(&request, 4);
+    fixture.harness.fulfill(&request, &result).unwrap();
+
+    let cache = fixture.cache();
+    assert_eq!(cache.slot, 1_410);
+    assert_eq!(cache.latest(), Some(&result[..]));
+    assert_eq!(cache.fresh(1_500, 100), Some(&result[..]));
+    assert_eq!(cache.pending_request(), None);
+}
+
+#[test]
+fn test_retried_callback_refreshes_cache() {
+    let request = Pubkey::new_unique();
+    let mut fixture = Fixture::new(request);
+    fixture.add_request(request);
+
+    fixture.harness.warp_to_slot(1_410);
+    let result = fixture.harness.randomness(&request, 4);
+    fixture
+        .harness
+        .fulfill_with_transient_failure_then_success(&request, &result, 1)
+        .unwrap();
+
+    // The lost attempt did not clear the pending refresh, so the retry is still expected
+    let cache = fixture.cache();
+    assert_eq!(cache.slot, 1_410 + CALLBACK_RETRY_BACKOFF_SLOTS);
+    assert_eq!(cache.latest(), Some(&result[..]));
+    assert_eq!(cache.pending_request(), None);
+}
+
+#[test]
+fn test_late_callback_does_not_overwrite_newer_refresh() {
+    // The first refresh timed out and a second caller requested another
+    let timed_out = Pubkey::new_unique();
+    let pending = Pubkey::new_unique();
+    let mut fixture = Fixture::new(pending);
+    fixture.add_request(timed_out);
+    fixture.add_request(pending);
+
+    let late = fixture.harness.randomness(&timed_out, 4);
+    assert_eq!(
+        fixture.harness.fulfill(&timed_out, &late),
+        Err(consumer_error(ConsumerError::UnexpectedCacheRefresh))
+    );
+    assert_eq!(fixture.cache().latest(), Some(&[1, 2, 3, 4][..]));
+    assert_eq!(fixture.cache().pending_request(), Some(pending));
+
+    fixture.harness.warp_to_slot(1_420);
+    let result = fixture.harness.randomness(&pending, 4);
+    fixture.harness.fulfill(&pending, &result).unwrap();
+    assert_eq!(fixture.cache().latest(), Some(&result[..]));
+}
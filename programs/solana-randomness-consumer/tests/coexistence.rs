@@ -0,0 +1,50 @@
+//! The lite crate's prelude glob-imports next to `switchboard_solana`'s without ambiguous
+//! names: the shared Solana types resolve to one item, and the lite prelude leaves out the
+//! `ID` every program crate declares. Compiling this file is most of the test.
+
+use solana_randomness_service_lite::prelude::*;
+use switchboard_solana::prelude::*;
+
+fn request_accounts(state: Pubkey) -> Vec<AccountMeta> {
+    vec![AccountMeta::new_readonly(state, false)]
+}
+
+fn load_state(info: &AccountInfo) -> std::result::Result<u64, ProgramError> {
+    Ok(ServiceStateView::load(info)?.cost_per_byte())
+}
+
+#[test]
+fn test_preludes_coexist() {
+    assert_eq!(RANDOMNESS_SERVICE_ID, solana_randomness_service::ID);
+    assert_eq!(
+        derive_state_pubkey(&RANDOMNESS_SERVICE_ID).0,
+        RANDOMNESS_SERVICE_STATE
+    );
+    assert_eq!(
+        request_accounts(RANDOMNESS_SERVICE_STATE)[0].pubkey,
+        RANDOMNESS_SERVICE_STATE
+    );
+
+    let (callback, _) = CallbackBuilder::new(solana_randomness_consumer::ID)
+        .with_state_signer()
+        .with_ix_data(anchor_sighash("consume_randomness").to_vec())
+        .build()
+        .unwrap();
+    assert_eq!(callback.program_id, solana_randomness_consumer::ID);
+
+    let key = RANDOMNESS_SERVICE_STATE;
+    let mut lamports = 0;
+    let mut data = vec![0; ServiceState::LEN];
+    data[..8].copy_from_slice(&ServiceState::DISCRIMINATOR);
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &RANDOMNESS_SERVICE_ID,
+        false,
+        0,
+    );
+    assert_eq!(load_state(&info), Ok(0));
+}
@@ -0,0 +1,255 @@
+//! Settles feed requests through the lite crate's oracle harness and reads the feed back
+//! with the lite crate's `ResultFeed`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountSerialize, Discriminator, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{ConsumerError, ResultFeed, FEED_CALLBACK_COMPUTE_UNITS};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, Callback, SimpleRandomnessV1Account, CALLBACK_RETRY_BACKOFF_SLOTS,
+    RANDOMNESS_SERVICE_STATE,
+};
+
+struct Fixture {
+    harness: OracleHarness,
+    user: Pubkey,
+    feed: Pubkey,
+}
+
+impl Fixture {
+    /// A funded user whose feed was created by `initialize_result_feed`.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+
+        let user = harness.payer();
+        harness.airdrop(&user, 1_000_000_000);
+        let (feed, bump) = solana_randomness_service_lite::ResultFeed::address(
+            &solana_randomness_consumer::ID,
+            &user,
+        );
+        let mut data = Vec::new();
+        ResultFeed {
+            bump,
+            user,
+            request: Pubkey::default(),
+            slot: 0,
+            result_len: 0,
+            result: [0; 32],
+            request_slot: 0,
+            reserved: [0; 24],
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        harness.set_account(
+            feed,
+            HarnessAccount::new(data, solana_randomness_consumer::ID),
+        );
+
+        Self {
+            harness,
+            user,
+            feed,
+        }
+    }
+
+    /// Requests `num_bytes` with `request_feed_randomness` and settles the request.
+    fn round(&mut self, num_bytes: u8) -> RoundResult {
+        let request = Pubkey::new_unique();
+        let accounts = solana_randomness_consumer::accounts::RequestFeedRandomness {
+            result_feed: self.feed,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: self.harness.escrow(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            payer: self.user,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        let spec = RoundSpec {
+            consumer_request_ix: Instruction {
+                program_id: solana_randomness_consumer::ID,
+                accounts: accounts.to_account_metas(None),
+                data: solana_randomness_consumer::instruction::RequestFeedRandomness { num_bytes }
+                    .data(),
+            },
+            randomness: self.harness.randomness(&request, num_bytes as usize),
+            expected_callback_disc: anchor_sighash("consume_feed_randomness"),
+        };
+        self.harness.run_round(spec).unwrap()
+    }
+
+    /// Adds a request made by `user` at the current slot whose callback writes to this
+    /// fixture's feed.
+    fn add_request_by(&mut self, user: Pubkey, num_bytes: u8) -> Pubkey {
+        let request = Pubkey::new_unique();
+        self.harness.add_request(
+            request,
+            &SimpleRandomnessV1Account {
+                num_bytes,
+                user,
+                request_slot: self.harness.slot(),
+                callback: Callback::new(
+                    solana_randomness_consumer::ID,
+                    vec![
+                        AccountMeta::new_readonly(RANDOMNESS_SERVICE_STATE, true).into(),
+                        AccountMeta::new_readonly(request, false).into(),
+                        AccountMeta::new(self.feed, false).into(),
+                    ],
+                    anchor_sighash("consume_feed_randomness").to_vec(),
+                ),
+                ..Default::default()
+            },
+        );
+        request
+    }
+
+    fn feed(&self) -> solana_randomness_service_lite::ResultFeed {
+        decode_feed(self.harness.account(&self.feed).unwrap())
+    }
+}
+
+fn decode_feed(account: &HarnessAccount) -> solana_randomness_service_lite::ResultFeed {
+    solana_randomness_service_lite::ResultFeed::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+#[test]
+fn test_feed_address_matches_program_seeds() {
+    let user = Pubkey::new_unique();
+
+    assert_eq!(
+        solana_randomness_service_lite::ResultFeed::address(&solana_randomness_consumer::ID, &user),
+        Pubkey::find_program_address(
+            &[ResultFeed::SEED, user.as_ref()],
+            &solana_randomness_consumer::ID
+        )
+    );
+    assert_eq!(
+        solana_randomness_service_lite::ResultFeed::SEED,
+        ResultFeed::SEED
+    );
+    assert_eq!(
+        solana_randomness_service_lite::ResultFeed::DISCRIMINATOR,
+        ResultFeed::DISCRIMINATOR
+    );
+    assert_eq!(
+        solana_randomness_service_lite::ResultFeed::SPACE,
+        ResultFeed::SPACE
+    );
+}
+
+#[test]
+fn test_new_feed_has_no_result() {
+    let fixture = Fixture::new();
+
+    let feed = fixture.feed();
+
+    assert_eq!(feed.user, fixture.user);
+    assert_eq!(feed.latest(), None);
+}
+
+#[test]
+fn test_sequential_rounds_overwrite_feed() {
+    let mut fixture = Fixture::new();
+
+    fixture.harness.warp_to_slot(100);
+    let first = fixture.round(32);
+
+    let before = decode_feed(&first.before[&fixture.feed]);
+    let after = decode_feed(&first.after[&fixture.feed]);
+    let first_result = fixture.harness.randomness(&first.request, 32);
+    assert_eq!(before.latest(), None);
+    assert_eq!(after.request, first.request);
+    assert_eq!(after.slot, 100);
+    assert_eq!(after.latest(), Some(&first_result[..]));
+    assert_eq!(first.pending.user, fixture.user);
+    assert_eq!(first.pending.compute_units, FEED_CALLBACK_COMPUTE_UNITS);
+    // The request and its escrow are closed and their rent returned
+    assert!(!first.after.contains_key(&first.request));
+    assert!(!first.after.contains_key(&first.pending.escrow));
+    assert!(first.lamports_delta(&fixture.user) < 0);
+
+    fixture.harness.warp_to_slot(105);
+    let second = fixture.round(8);
+
+    let feed = fixture.feed();
+    let second_result = fixture.harness.randomness(&second.request, 8);
+    assert_eq!(feed.request, second.request);
+    assert_eq!(feed.slot, 105);
+    assert_eq!(feed.latest(), Some(&second_result[..]));
+    // The shorter result does not leave bytes of the first one behind
+    assert_eq!(feed.result[8..], [0; 24]);
+}
+
+#[test]
+fn test_rejects_request_by_other_user() {
+    let mut fixture = Fixture::new();
+    let request = fixture.add_request_by(Pubkey::new_unique(), 8);
+
+    let result = fixture.harness.randomness(&request, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&request, &result),
+        Err(ProgramError::Custom(
+            anchor_lang::error::ERROR_CODE_OFFSET + ConsumerError::RequestUserMismatch as u32
+        ))
+    );
+    assert_eq!(fixture.feed().latest(), None);
+}
+
+#[test]
+fn test_retried_settlement_updates_feed() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let request = fixture.add_request_by(fixture.user, 8);
+    let result = fixture.harness.randomness(&request, 8);
+
+    fixture
+        .harness
+        .fulfill_with_transient_failure_then_success(&request, &result, 2)
+        .unwrap();
+
+    let feed = fixture.feed();
+    assert_eq!(feed.request, request);
+    assert_eq!(feed.slot, 100 + 2 * CALLBACK_RETRY_BACKOFF_SLOTS);
+    assert_eq!(feed.request_slot, 100);
+    assert_eq!(feed.latest(), Some(&result[..]));
+}
+
+#[test]
+fn test_late_retry_does_not_overwrite_newer_result() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let older = fixture.add_request_by(fixture.user, 8);
+    fixture.harness.warp_to_slot(110);
+    let newer = fixture.add_request_by(fixture.user, 8);
+
+    // The newer request settles while the older one's settle transaction is lost
+    fixture.harness.warp_to_slot(120);
+    let newer_result = fixture.harness.randomness(&newer, 8);
+    fixture.harness.fulfill(&newer, &newer_result).unwrap();
+    let older_result = fixture.harness.randomness(&older, 8);
+    fixture
+        .harness
+        .fulfill_with_transient_failure_then_success(&older, &older_result, 1)
+        .unwrap();
+
+    let feed = fixture.feed();
+    assert_eq!(feed.request, newer);
+    assert_eq!(feed.slot, 120);
+    assert_eq!(feed.request_slot, 110);
+    assert_eq!(feed.latest(), Some(&newer_result[..]));
+    // The older request is still settled and closed
+    assert!(fixture.harness.request(&older).is_none());
+    assert!(fixture
+        .harness
+        .logs()
+        .iter()
+        .any(|log| log.contains("Skipping result of request made at slot 100")));
+}
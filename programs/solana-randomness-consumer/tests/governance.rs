@@ -0,0 +1,282 @@
+//! Breaks a proposal tie with the lite crate's oracle harness: the request is tagged with the
+//! proposal, the callback stores a receipt, and anyone resolves the tie from it.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{
+    quadratic_weight, resolve_tie, ConsumerError, Proposal, TiedCandidate, TIEBREAK_MAX_CANDIDATES,
+    TIEBREAK_RANDOMNESS_BYTES,
+};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, Callback, IndexedTag, LiteError, RANDOMNESS_SERVICE_STATE,
+};
+
+const PROPOSAL_ID: u64 = 17;
+const RANDOMNESS: [u8; TIEBREAK_RANDOMNESS_BYTES] = [0x5a; TIEBREAK_RANDOMNESS_BYTES];
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+fn candidates() -> Vec<TiedCandidate> {
+    [1, 100, 10_000]
+        .into_iter()
+        .enumerate()
+        .map(|(i, voting_power)| TiedCandidate {
+            candidate: Pubkey::new_from_array([i as u8 + 1; 32]),
+            voting_power,
+        })
+        .collect()
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    authority: Pubkey,
+    proposal: Pubkey,
+}
+
+impl Fixture {
+    /// A proposal tied between `candidates()`, laid out as `initialize_proposal` leaves it.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+        let authority = harness.payer();
+        harness.airdrop(&authority, 1_000_000_000);
+
+        let (proposal, bump) = Pubkey::find_program_address(
+            &[
+                Proposal::SEED,
+                authority.as_ref(),
+                &PROPOSAL_ID.to_le_bytes(),
+            ],
+            &solana_randomness_consumer::ID,
+        );
+        let tied = candidates();
+        let mut padded = [TiedCandidate::default(); TIEBREAK_MAX_CANDIDATES];
+        padded[..tied.len()].copy_from_slice(&tied);
+        let mut data = Vec::new();
+        Proposal {
+            bump,
+            authority,
+            id: PROPOSAL_ID,
+            candidate_count: tied.len() as u8,
+            candidates: padded,
+            pending_request: Pubkey::default(),
+            receipt: None,
+            winner: None,
+            reserved: [0; 32],
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        harness.set_account(
+            proposal,
+            HarnessAccount::new(data, solana_randomness_consumer::ID),
+        );
+
+        Self {
+            harness,
+            authority,
+            proposal,
+        }
+    }
+
+    fn request_instruction(&self, request: Pubkey) -> Instruction {
+        let accounts = solana_randomness_consumer::accounts::RequestTiebreak {
+            proposal: self.proposal,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: self.harness.escrow(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            authority: self.authority,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::RequestTiebreak {
+                proposal_id: PROPOSAL_ID,
+            }
+            .data(),
+        }
+    }
+
+    /// Requests the tiebreak through the program and returns the request.
+    fn request_tiebreak(&mut self) -> Pubkey {
+        let request = Pubkey::new_unique();
+        self.harness
+            .process_transaction(&[self.request_instruction(request)])
+            .unwrap();
+        request
+    }
+
+    /// Submits a request whose callback lists the proposal but is tagged with `tag` and
+    /// `index`, as a request built outside the program could.
+    fn submit_tagged(&mut self, tag: u64, index: u16) -> Pubkey {
+        let request = Pubkey::new_unique();
+        let (callback, _) = Callback::builder(solana_randomness_consumer::ID)
+            .with_state_signer()
+            .with_readonly_account(request)
+            .with_writable_account(self.proposal)
+            .with_ix_data(anchor_sighash("consume_tiebreak").to_vec())
+            .with_indexed_tag(tag, index)
+            .build()
+            .unwrap();
+        self.harness
+            .submit_request(request, TIEBREAK_RANDOMNESS_BYTES as u8, callback, None)
+            .unwrap();
+        request
+    }
+
+    fn resolve(&mut self) -> std::result::Result<Vec<Pubkey>, ProgramError> {
+        let accounts = solana_randomness_consumer::accounts::ResolveTie {
+            proposal: self.proposal,
+            caller: Pubkey::new_unique(),
+        };
+        self.harness.process_transaction(&[Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::ResolveTie {}.data(),
+        }])
+    }
+
+    fn proposal(&self) -> Proposal {
+        let account = self.harness.account(&self.proposal).unwrap();
+        Proposal::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+}
+
+#[test]
+fn test_tiebreak_stores_receipt_and_resolves() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(300);
+    let request = Pubkey::new_unique();
+    let randomness = fixture
+        .harness
+        .randomness(&request, TIEBREAK_RANDOMNESS_BYTES);
+
+    let round = fixture
+        .harness
+        .run_round(RoundSpec {
+            consumer_request_ix: fixture.request_instruction(request),
+            randomness: randomness.clone(),
+            expected_callback_disc: anchor_sighash("consume_tiebreak"),
+        })
+        .unwrap();
+
+    assert_eq!(
+        round.pending.callback.indexed_tag(),
+        Some(IndexedTag::new(PROPOSAL_ID, 3))
+    );
+    let proposal = fixture.proposal();
+    let receipt = proposal.receipt.unwrap();
+    assert_eq!(receipt.request, request);
+    assert_eq!(receipt.slot, 300);
+    assert_eq!(&receipt.randomness[..], &randomness[..]);
+    assert_eq!(proposal.pending_request, Pubkey::default());
+    assert_eq!(proposal.winner, None);
+
+    fixture.resolve().unwrap();
+
+    assert_eq!(
+        fixture.proposal().winner,
+        Some(resolve_tie(&candidates(), &randomness).unwrap())
+    );
+    assert_eq!(
+        fixture.resolve(),
+        Err(consumer_error(ConsumerError::TieAlreadyResolved))
+    );
+}
+
+#[test]
+fn test_callback_rejects_mismatched_tag() {
+    let mut fixture = Fixture::new();
+    let tag_mismatch = ProgramError::Custom(LiteError::TagMismatch.code());
+
+    // Another proposal's id, then this proposal's id with a stale candidate count
+    for (tag, index) in [(PROPOSAL_ID + 1, 3), (PROPOSAL_ID, 2)] {
+        let request = fixture.submit_tagged(tag, index);
+
+        assert_eq!(
+            fixture.harness.fulfill(&request, &RANDOMNESS),
+            Err(tag_mismatch.clone())
+        );
+    }
+    assert_eq!(fixture.proposal().receipt, None);
+}
+
+#[test]
+fn test_callback_rejects_replaced_request() {
+    let mut fixture = Fixture::new();
+    let first = fixture.request_tiebreak();
+    let second = fixture.request_tiebreak();
+
+    assert_eq!(
+        fixture.harness.fulfill(&first, &RANDOMNESS),
+        Err(consumer_error(ConsumerError::UnexpectedTiebreakRequest))
+    );
+    fixture.harness.fulfill(&second, &RANDOMNESS).unwrap();
+
+    assert_eq!(fixture.proposal().receipt.unwrap().request, second);
+    let third = Pubkey::new_unique();
+    assert_eq!(
+        fixture
+            .harness
+            .process_transaction(&[fixture.request_instruction(third)]),
+        Err(consumer_error(ConsumerError::TiebreakAlreadyReceived))
+    );
+}
+
+#[test]
+fn test_resolve_requires_receipt() {
+    let mut fixture = Fixture::new();
+    fixture.request_tiebreak();
+
+    assert_eq!(
+        fixture.resolve(),
+        Err(consumer_error(ConsumerError::TiebreakNotReceived))
+    );
+}
+
+#[test]
+fn test_resolution_is_deterministic() {
+    let expected = resolve_tie(&candidates(), &RANDOMNESS).unwrap();
+    for _ in 0..10 {
+        assert_eq!(resolve_tie(&candidates(), &RANDOMNESS).unwrap(), expected);
+    }
+
+    // Separate runs, with other requests delivering the same bytes, pick the same winner
+    for _ in 0..3 {
+        let mut fixture = Fixture::new();
+        let request = fixture.request_tiebreak();
+        fixture.harness.fulfill(&request, &RANDOMNESS).unwrap();
+        fixture.resolve().unwrap();
+
+        assert_eq!(fixture.proposal().winner, Some(expected));
+    }
+}
+
+#[test]
+fn test_quadratic_weights() {
+    assert_eq!(quadratic_weight(0), 0);
+    assert_eq!(quadratic_weight(1), 1);
+    assert_eq!(quadratic_weight(99), 9);
+    assert_eq!(quadratic_weight(100), 10);
+    assert_eq!(quadratic_weight(u64::MAX), u64::from(u32::MAX));
+
+    // A candidate without voting power is never picked
+    let mut tied = candidates();
+    tied[1].voting_power = 0;
+    for byte in 0..=u8::MAX {
+        let winner = resolve_tie(&tied, &[byte; TIEBREAK_RANDOMNESS_BYTES]).unwrap();
+        assert_ne!(winner, tied[1].candidate);
+    }
+}
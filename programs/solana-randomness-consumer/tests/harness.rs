@@ -0,0 +1,265 @@
+//! Runs the lootbox callback against the lite crate's oracle harness, including the oracle
+//! failures the callback has to guard against.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::AccountSerialize;
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{
+    ConsumerError, Lootbox, LootboxOpening, PrizeTier, LOOTBOX_RANDOMNESS_BYTES,
+};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, Callback, SimpleRandomnessV1Account, RANDOMNESS_SERVICE_STATE,
+};
+
+fn anchor_account(account: &impl AccountSerialize, owner: Pubkey) -> HarnessAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    HarnessAccount::new(data, owner)
+}
+
+fn token_account<T: Pack>(state: T) -> HarnessAccount {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    HarnessAccount::new(data, spl_token::ID)
+}
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    request: Pubkey,
+    opening: Pubkey,
+    user_prize_account: Pubkey,
+}
+
+impl Fixture {
+    /// A lootbox opening waiting for the oracle, as left behind by `open_box`.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_program(spl_token::ID, spl_token::processor::Processor::process);
+
+        let (state, state_bump) =
+            Pubkey::find_program_address(&[b"STATE"], &solana_randomness_service::ID);
+        assert_eq!(state, RANDOMNESS_SERVICE_STATE);
+        harness.set_account(
+            state,
+            anchor_account(
+                &solana_randomness_service::State {
+                    bump: state_bump,
+                    authority: Pubkey::new_unique(),
+                    mint: spl_token::native_mint::ID,
+                    switchboard_service: Pubkey::new_unique(),
+                    wallet: Pubkey::new_unique(),
+                    cost_per_byte: 10_000,
+                    last_updated: 0,
+                    _ebuf: [0; 512],
+                },
+                solana_randomness_service::ID,
+            ),
+        );
+
+        let user = Pubkey::new_unique();
+        let key_mint = Pubkey::new_unique();
+        let prize_mint = Pubkey::new_unique();
+        let (lootbox, lootbox_bump) = Pubkey::find_program_address(
+            &[Lootbox::SEED, key_mint.as_ref()],
+            &solana_randomness_consumer::ID,
+        );
+        harness.set_account(
+            lootbox,
+            anchor_account(
+                &Lootbox {
+                    bump: lootbox_bump,
+                    authority: user,
+                    key_mint,
+                    prize_mint,
+                    num_opened: 1,
+                    reserved: [0; 32],
+                },
+                solana_randomness_consumer::ID,
+            ),
+        );
+
+        harness.set_account(
+            prize_mint,
+            token_account(spl_token::state::Mint {
+                mint_authority: COption::Some(lootbox),
+                supply: 0,
+                decimals: 0,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            }),
+        );
+        let user_prize_account = Pubkey::new_unique();
+        harness.set_account(
+            user_prize_account,
+            token_account(spl_token::state::Account {
+                mint: prize_mint,
+                owner: user,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            }),
+        );
+
+        let request = Pubkey::new_unique();
+        let (opening, opening_bump) = Pubkey::find_program_address(
+            &[LootboxOpening::SEED, request.as_ref()],
+            &solana_randomness_consumer::ID,
+        );
+        harness.set_account(
+            opening,
+            anchor_account(
+                &LootboxOpening {
+                    bump: opening_bump,
+                    lootbox,
+                    user,
+                    request,
+                    request_slot: 100,
+                    settled_slot: 0,
+                    is_settled: false,
+                    randomness: [0; 4],
+                    tier: PrizeTier::Common,
+                    amount: 0,
+                    reserved: [0; 32],
+                },
+                solana_randomness_consumer::ID,
+            ),
+        );
+
+        // Must match the callback built by OpenBox::actuate
+        harness.add_request(
+            request,
+            &SimpleRandomnessV1Account {
+                num_bytes: LOOTBOX_RANDOMNESS_BYTES,
+                user,
+                request_slot: 100,
+                callback: Callback::new(
+                    solana_randomness_consumer::ID,
+                    vec![
+                        AccountMeta::new_readonly(state, true).into(),
+                        AccountMeta::new_readonly(request, false).into(),
+                        AccountMeta::new_readonly(lootbox, false).into(),
+                        AccountMeta::new(opening, false).into(),
+                        AccountMeta::new(prize_mint, false).into(),
+                        AccountMeta::new(user_prize_account, false).into(),
+                        AccountMeta::new_readonly(spl_token::ID, false).into(),
+                    ],
+                    anchor_sighash("consume_lootbox").to_vec(),
+                ),
+                ..Default::default()
+            },
+        );
+        harness.warp_to_slot(102);
+
+        Self {
+            harness,
+            request,
+            opening,
+            user_prize_account,
+        }
+    }
+
+    fn opening(&self) -> LootboxOpening {
+        let account = self.harness.account(&self.opening).unwrap();
+        LootboxOpening::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    fn prize_balance(&self) -> u64 {
+        let account = self.harness.account(&self.user_prize_account).unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+}
+
+#[test]
+fn test_fulfill_mints_prize() {
+    let mut fixture = Fixture::new();
+    let randomness = fixture
+        .harness
+        .randomness(&fixture.request, LOOTBOX_RANDOMNESS_BYTES as usize);
+
+    fixture
+        .harness
+        .fulfill(&fixture.request, &randomness)
+        .unwrap();
+
+    let tier = PrizeTier::from_randomness(&randomness).unwrap();
+    let opening = fixture.opening();
+    assert!(opening.is_settled);
+    assert_eq!(opening.settled_slot, 102);
+    assert_eq!(opening.randomness[..], randomness[..]);
+    assert_eq!(opening.tier, tier);
+    assert_eq!(opening.amount, tier.prize_amount());
+    assert_eq!(fixture.prize_balance(), tier.prize_amount());
+
+    // The service closes the request once the callback succeeds
+    assert!(fixture.harness.request(&fixture.request).is_none());
+}
+
+#[test]
+fn test_replayed_fulfillment_is_rejected() {
+    let mut fixture = Fixture::new();
+    let randomness = fixture
+        .harness
+        .randomness(&fixture.request, LOOTBOX_RANDOMNESS_BYTES as usize);
+
+    let (first, second) = fixture.harness.fulfill_twice(&fixture.request, &randomness);
+
+    assert_eq!(first, Ok(()));
+    assert_eq!(
+        second,
+        Err(consumer_error(ConsumerError::OpeningAlreadySettled))
+    );
+    // The prize is only minted once
+    let tier = PrizeTier::from_randomness(&randomness).unwrap();
+    assert_eq!(fixture.prize_balance(), tier.prize_amount());
+}
+
+#[test]
+fn test_short_randomness_is_rejected() {
+    let mut fixture = Fixture::new();
+
+    assert_eq!(
+        fixture.harness.fulfill_short(&fixture.request, 3),
+        Err(consumer_error(ConsumerError::InvalidRandomnessLength))
+    );
+
+    assert!(!fixture.opening().is_settled);
+    assert_eq!(fixture.prize_balance(), 0);
+    assert!(fixture.harness.request(&fixture.request).is_some());
+}
+
+#[test]
+fn test_oracle_error_leaves_opening_pending() {
+    let mut fixture = Fixture::new();
+
+    fixture
+        .harness
+        .fulfill_with_error(&fixture.request, "Callback simulation failed")
+        .unwrap();
+
+    // The callback never runs, so the opening stays pending and the failure is only visible on
+    // the request account.
+    assert!(!fixture.opening().is_settled);
+    assert_eq!(fixture.prize_balance(), 0);
+    let request = fixture.harness.request(&fixture.request).unwrap();
+    assert_eq!(request.is_completed, 1);
+    assert_eq!(request.error_message, "Callback simulation failed");
+
+    let randomness = fixture
+        .harness
+        .randomness(&fixture.request, LOOTBOX_RANDOMNESS_BYTES as usize);
+    assert_eq!(
+        fixture.harness.fulfill(&fixture.request, &randomness),
+        Err(ServiceError::RequestAlreadyCompleted.into())
+    );
+}
@@ -0,0 +1,156 @@
+//! Runs `find_expired_requests` with the lite crate's oracle harness over request accounts
+//! made by other users, including ones crafted to exhaust the program's heap.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_randomness_consumer::KEEPER_DECODE_LIMITS;
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{Callback, SimpleRandomnessV1Account};
+
+fn harness() -> OracleHarness {
+    let mut harness = OracleHarness::new(
+        solana_randomness_consumer::ID,
+        solana_randomness_consumer::entry,
+    );
+    harness.add_service_accounts();
+    harness
+}
+
+/// Writes a request made at `request_slot` with a callback of `num_accounts` accounts.
+fn add_request(
+    harness: &mut OracleHarness,
+    request_slot: u64,
+    is_completed: bool,
+    num_accounts: usize,
+) -> Pubkey {
+    let request = SimpleRandomnessV1Account {
+        is_completed: is_completed.into(),
+        num_bytes: 8,
+        user: Pubkey::new_unique(),
+        escrow: Pubkey::new_unique(),
+        request_slot,
+        callback: Callback::new(
+            Pubkey::new_unique(),
+            (0..num_accounts)
+                .map(|_| AccountMeta::new(Pubkey::new_unique(), false).into())
+                .collect(),
+            vec![1; 8],
+        ),
+        compute_units: 200_000,
+        priority_fee_micro_lamports: 0,
+        error_message: String::new(),
+    };
+    let mut data = SimpleRandomnessV1Account::DISCRIMINATOR.to_vec();
+    data.extend(request.try_to_vec().unwrap());
+    let pubkey = Pubkey::new_unique();
+    harness.set_account(
+        pubkey,
+        HarnessAccount::new(data, solana_randomness_service::ID),
+    );
+    pubkey
+}
+
+fn instruction(keeper: Pubkey, requests: &[Pubkey], max_age_slots: u64) -> Instruction {
+    let mut accounts =
+        solana_randomness_consumer::accounts::FindExpiredRequests { keeper }.to_account_metas(None);
+    accounts.extend(
+        requests
+            .iter()
+            .map(|request| AccountMeta::new_readonly(*request, false)),
+    );
+    Instruction {
+        program_id: solana_randomness_consumer::ID,
+        accounts,
+        data: solana_randomness_consumer::instruction::FindExpiredRequests { max_age_slots }.data(),
+    }
+}
+
+#[test]
+fn test_finds_completed_and_old_requests() {
+    let mut harness = harness();
+    harness.warp_to_slot(1_000);
+    let completed = add_request(&mut harness, 990, true, 2);
+    let old = add_request(&mut harness, 100, false, 2);
+    let recent = add_request(&mut harness, 990, false, 2);
+    let keeper = harness.payer();
+
+    harness
+        .process_transaction(&[instruction(keeper, &[completed, old, recent], 500)])
+        .unwrap();
+
+    assert!(harness
+        .logs()
+        .iter()
+        .any(|log| log.contains("Found 2 expired requests, skipped 0")));
+}
+
+#[test]
+fn test_skips_requests_over_the_decode_limits() {
+    let mut harness = harness();
+    harness.warp_to_slot(1_000);
+    let oversized = add_request(
+        &mut harness,
+        100,
+        true,
+        KEEPER_DECODE_LIMITS.max_accounts + 1,
+    );
+    let old = add_request(&mut harness, 100, false, KEEPER_DECODE_LIMITS.max_accounts);
+    let keeper = harness.payer();
+
+    harness
+        .process_transaction(&[instruction(keeper, &[oversized, old], 500)])
+        .unwrap();
+
+    let logs = harness.logs();
+    assert!(logs.iter().any(|log| log.contains(&format!(
+        "Skipping request {} over the decode limits",
+        oversized
+    ))));
+    assert!(logs
+        .iter()
+        .any(|log| log.contains("Found 1 expired requests, skipped 1")));
+}
+
+#[test]
+fn test_crafted_length_does_not_exhaust_the_heap() {
+    let mut harness = harness();
+    let crafted = add_request(&mut harness, 0, true, 0);
+    let mut account = harness.account(&crafted).unwrap().clone();
+    // The callback account count, claiming u32::MAX accounts of 34 bytes each
+    let offset = 8 + 1 + 1 + 32 + 32 + 8 + 32;
+    account.data[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    harness.set_account(crafted, account);
+    let keeper = harness.payer();
+
+    harness
+        .process_transaction(&[instruction(keeper, &[crafted], 500)])
+        .unwrap();
+
+    assert!(harness
+        .logs()
+        .iter()
+        .any(|log| log.contains("Found 0 expired requests, skipped 1")));
+}
+
+#[test]
+fn test_rejects_accounts_not_owned_by_the_service() {
+    let mut harness = harness();
+    let request = add_request(&mut harness, 0, true, 0);
+    let mut account = harness.account(&request).unwrap().clone();
+    account.owner = solana_randomness_consumer::ID;
+    harness.set_account(request, account);
+    let keeper = harness.payer();
+
+    assert_eq!(
+        harness.process_transaction(&[instruction(keeper, &[request], 500)]),
+        Err(ProgramError::IllegalOwner)
+    );
+}
+
+#[test]
+fn test_keeper_limits_fit_the_heap() {
+    // A legacy transaction passes at most about 35 requests. Decoding all of them at the
+    // limits fits the 32 KiB heap with room to spare.
+    assert!(35 * KEEPER_DECODE_LIMITS.max_heap_bytes() < 24 * 1024);
+}
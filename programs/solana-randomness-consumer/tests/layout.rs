@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, Space};
+use solana_randomness_consumer::{LootboxOpening, PrizeTier};
+use solana_randomness_service_lite::{deserialize_with_reserved, RESERVED_LEN};
+
+fn opening() -> LootboxOpening {
+    LootboxOpening {
+        bump: 254,
+        lootbox: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        request: Pubkey::new_unique(),
+        request_slot: 100,
+        settled_slot: 102,
+        is_settled: true,
+        randomness: [1, 2, 3, 4],
+        tier: PrizeTier::Epic,
+        amount: 25,
+        reserved: [0; RESERVED_LEN],
+    }
+}
+
+fn account_data(opening: &LootboxOpening) -> Vec<u8> {
+    let mut data = Vec::new();
+    opening.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn assert_same(a: &LootboxOpening, b: &LootboxOpening) {
+    assert_eq!(a.try_to_vec().unwrap(), b.try_to_vec().unwrap());
+}
+
+#[test]
+fn test_space_includes_reserved_bytes() {
+    let data = account_data(&opening());
+    assert_eq!(data.len(), 8 + LootboxOpening::INIT_SPACE);
+    assert_eq!(data[data.len() - RESERVED_LEN..], [0; RESERVED_LEN]);
+}
+
+#[test]
+fn test_decodes_old_short_account() {
+    let opening = opening();
+    let data = account_data(&opening);
+    assert_eq!(data[..8], LootboxOpening::DISCRIMINATOR);
+
+    // An opening written before the reserved bytes were added
+    let old = &data[8..data.len() - RESERVED_LEN];
+    let decoded: LootboxOpening = deserialize_with_reserved(old).unwrap();
+    assert_same(&decoded, &opening);
+}
+
+#[test]
+fn test_decodes_new_extended_account() {
+    let opening = opening();
+    let mut data = account_data(&opening);
+
+    // A newer layout appended a field after the reserved bytes
+    data.extend(42u64.to_le_bytes());
+    let decoded: LootboxOpening = deserialize_with_reserved(&data[8..]).unwrap();
+    assert_same(&decoded, &opening);
+
+    // Anchor ignores the trailing bytes of a current account too
+    let decoded = LootboxOpening::try_deserialize(&mut &data[..]).unwrap();
+    assert_same(&decoded, &opening);
+}
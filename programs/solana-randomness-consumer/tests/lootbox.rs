@@ -0,0 +1,77 @@
+use solana_randomness_consumer::PrizeTier;
+
+/// Returns the smallest little-endian u32 whose roll lands on `bps`.
+fn bytes_for_roll(bps: u64) -> [u8; 4] {
+    let value = (bps << 32).div_ceil(10_000);
+    (value as u32).to_le_bytes()
+}
+
+#[test]
+fn test_common_tier() {
+    assert_eq!(
+        PrizeTier::from_randomness(&[0, 0, 0, 0]).unwrap(),
+        PrizeTier::Common
+    );
+    assert_eq!(
+        PrizeTier::from_randomness(&bytes_for_roll(PrizeTier::COMMON_BPS - 1)).unwrap(),
+        PrizeTier::Common
+    );
+    assert_eq!(PrizeTier::Common.prize_amount(), 1);
+}
+
+#[test]
+fn test_rare_tier() {
+    assert_eq!(
+        PrizeTier::from_randomness(&bytes_for_roll(PrizeTier::COMMON_BPS)).unwrap(),
+        PrizeTier::Rare
+    );
+    assert_eq!(
+        PrizeTier::from_randomness(&bytes_for_roll(9_499)).unwrap(),
+        PrizeTier::Rare
+    );
+    assert_eq!(PrizeTier::Rare.prize_amount(), 5);
+}
+
+#[test]
+fn test_epic_tier() {
+    assert_eq!(
+        PrizeTier::from_randomness(&bytes_for_roll(9_500)).unwrap(),
+        PrizeTier::Epic
+    );
+    assert_eq!(
+        PrizeTier::from_randomness(&bytes_for_roll(9_899)).unwrap(),
+        PrizeTier::Epic
+    );
+    assert_eq!(PrizeTier::Epic.prize_amount(), 25);
+}
+
+#[test]
+fn test_legendary_tier() {
+    assert_eq!(
+        PrizeTier::from_randomness(&bytes_for_roll(9_900)).unwrap(),
+        PrizeTier::Legendary
+    );
+    assert_eq!(
+        PrizeTier::from_randomness(&[0xff, 0xff, 0xff, 0xff]).unwrap(),
+        PrizeTier::Legendary
+    );
+    assert_eq!(PrizeTier::Legendary.prize_amount(), 100);
+}
+
+#[test]
+fn test_tier_odds_cover_all_rolls() {
+    assert_eq!(
+        PrizeTier::COMMON_BPS
+            + PrizeTier::RARE_BPS
+            + PrizeTier::EPIC_BPS
+            + PrizeTier::LEGENDARY_BPS,
+        10_000
+    );
+}
+
+#[test]
+fn test_rejects_wrong_randomness_length() {
+    assert!(PrizeTier::from_randomness(&[]).is_err());
+    assert!(PrizeTier::from_randomness(&[0, 0, 0]).is_err());
+    assert!(PrizeTier::from_randomness(&[0, 0, 0, 0, 0]).is_err());
+}
@@ -0,0 +1,217 @@
+//! Runs `request_randomness_with_referral_fee` with the lite crate's oracle harness, which
+//! handles the fee transfer with its system program stand-in.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{ConsumerError, ReferralConfig};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{anchor_sighash, RANDOMNESS_SERVICE_STATE};
+
+const FEE: u64 = 50_000;
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    admin: Pubkey,
+    program_data: Pubkey,
+    referral_config: Pubkey,
+    recipient: Pubkey,
+}
+
+impl Fixture {
+    /// A deployed program whose upgrade authority is the funded harness payer, with a referral
+    /// config charging `fee_lamports`.
+    fn new(fee_lamports: u64) -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+        let admin = harness.payer();
+        harness.airdrop(&admin, 1_000_000_000);
+
+        let (program_data, _) = Pubkey::find_program_address(
+            &[solana_randomness_consumer::ID.as_ref()],
+            &bpf_loader_upgradeable::ID,
+        );
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend(1u64.to_le_bytes());
+        data.push(1);
+        data.extend(admin.to_bytes());
+        data.resize(
+            bpf_loader_upgradeable::UpgradeableLoaderState::size_of_programdata_metadata(),
+            0,
+        );
+        harness.set_account(
+            program_data,
+            HarnessAccount::new(data, bpf_loader_upgradeable::ID),
+        );
+
+        let recipient = Pubkey::new_unique();
+        let (referral_config, bump) =
+            Pubkey::find_program_address(&[ReferralConfig::SEED], &solana_randomness_consumer::ID);
+        let mut data = Vec::new();
+        ReferralConfig {
+            bump,
+            recipient,
+            fee_lamports,
+            reserved: [0; 32],
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        harness.set_account(
+            referral_config,
+            HarnessAccount::new(data, solana_randomness_consumer::ID),
+        );
+
+        Self {
+            harness,
+            admin,
+            program_data,
+            referral_config,
+            recipient,
+        }
+    }
+
+    fn instruction(&self, request: Pubkey, recipient: Pubkey) -> Instruction {
+        let accounts = solana_randomness_consumer::accounts::RequestRandomnessWithReferralFee {
+            referral_config: self.referral_config,
+            referral_recipient: recipient,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: self.harness.escrow(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            payer: self.admin,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::RequestRandomnessWithReferralFee {}
+                .data(),
+        }
+    }
+
+    /// Sends a request paying the fee to `recipient` and returns what the payer spent.
+    fn request(&mut self, recipient: Pubkey) -> std::result::Result<(Pubkey, u64), ProgramError> {
+        let request = Pubkey::new_unique();
+        let before = self.lamports(&self.admin);
+        self.harness
+            .process_transaction(&[self.instruction(request, recipient)])?;
+        Ok((request, before - self.lamports(&self.admin)))
+    }
+
+    fn update(&mut self, signer: Pubkey, recipient: Pubkey, fee_lamports: u64) -> ProgramResult {
+        let accounts = solana_randomness_consumer::accounts::UpdateReferralConfig {
+            referral_config: self.referral_config,
+            program_data: self.program_data,
+            admin: signer,
+        };
+        self.harness.process_transaction(&[Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::UpdateReferralConfig {
+                recipient,
+                fee_lamports,
+            }
+            .data(),
+        }])?;
+        Ok(())
+    }
+
+    fn lamports(&self, pubkey: &Pubkey) -> u64 {
+        self.harness.account(pubkey).map_or(0, |a| a.lamports)
+    }
+
+    fn config(&self) -> ReferralConfig {
+        let account = self.harness.account(&self.referral_config).unwrap();
+        ReferralConfig::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+}
+
+#[test]
+fn test_fee_paid_before_request() {
+    let mut fixture = Fixture::new(FEE);
+    let recipient = fixture.recipient;
+
+    let (request, _) = fixture.request(recipient).unwrap();
+
+    assert_eq!(fixture.lamports(&recipient), FEE);
+    let pending = fixture.harness.request(&request).unwrap();
+    assert_eq!(pending.user, fixture.admin);
+    assert_eq!(
+        pending.callback.discriminator(),
+        Some(anchor_sighash("consume_randomness"))
+    );
+}
+
+#[test]
+fn test_zero_fee_transfers_nothing() {
+    let mut fixture = Fixture::new(0);
+    let recipient = fixture.recipient;
+
+    let (request, spent) = fixture.request(recipient).unwrap();
+
+    assert!(fixture.harness.request(&request).is_some());
+    assert_eq!(fixture.lamports(&recipient), 0);
+    // The same request with a fee costs the payer exactly the fee more
+    let mut with_fee = Fixture::new(FEE);
+    let recipient = with_fee.recipient;
+    assert_eq!(with_fee.request(recipient).unwrap().1, spent + FEE);
+}
+
+#[test]
+fn test_rejects_other_recipient() {
+    let mut fixture = Fixture::new(FEE);
+    let other = Pubkey::new_unique();
+
+    assert_eq!(
+        fixture.request(other).map(|_| ()),
+        Err(consumer_error(ConsumerError::ReferralRecipientMismatch))
+    );
+    assert_eq!(fixture.lamports(&other), 0);
+}
+
+#[test]
+fn test_unaffordable_request_pays_no_fee() {
+    let mut fixture = Fixture::new(FEE);
+    let admin = fixture.admin;
+    let recipient = fixture.recipient;
+    // Enough for the fee, not the request after it
+    let mut account = fixture.harness.account(&admin).unwrap().clone();
+    account.lamports = FEE;
+    fixture.harness.set_account(admin, account);
+
+    assert!(fixture.request(recipient).is_err());
+    assert_eq!(fixture.lamports(&recipient), 0);
+    assert_eq!(fixture.lamports(&admin), FEE);
+}
+
+#[test]
+fn test_upgrade_authority_updates_config() {
+    let mut fixture = Fixture::new(FEE);
+    let new_recipient = Pubkey::new_unique();
+
+    assert_eq!(
+        fixture.update(Pubkey::new_unique(), new_recipient, 1),
+        Err(consumer_error(ConsumerError::NotProgramAuthority))
+    );
+    fixture
+        .update(fixture.admin, new_recipient, 2 * FEE)
+        .unwrap();
+
+    let config = fixture.config();
+    assert_eq!(config.recipient, new_recipient);
+    assert_eq!(config.fee_lamports, 2 * FEE);
+    fixture.request(new_recipient).unwrap();
+    assert_eq!(fixture.lamports(&new_recipient), 2 * FEE);
+}
@@ -0,0 +1,223 @@
+//! Requests through `request_and_register` with the lite crate's oracle harness, checking the
+//! record is only written when the service CPI succeeds.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{
+    ConsumerError, RandomnessRecord, REGISTER_CALLBACK_COMPUTE_UNITS,
+    REGISTER_REQUEST_TIMEOUT_SLOTS,
+};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, LiteError, ServiceState, RANDOMNESS_SERVICE_STATE,
+};
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    user: Pubkey,
+    record: Pubkey,
+}
+
+impl Fixture {
+    /// A funded user whose record was created by `initialize_randomness_record`.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+
+        let user = harness.payer();
+        harness.airdrop(&user, 1_000_000_000);
+        let (record, bump) = Pubkey::find_program_address(
+            &[RandomnessRecord::SEED, user.as_ref()],
+            &solana_randomness_consumer::ID,
+        );
+        let mut data = Vec::new();
+        RandomnessRecord {
+            bump,
+            user,
+            request: Pubkey::default(),
+            request_slot: 0,
+            pending: false,
+            slot: 0,
+            result_len: 0,
+            result: [0; 32],
+            reserved: [0; 32],
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        harness.set_account(
+            record,
+            HarnessAccount::new(data, solana_randomness_consumer::ID),
+        );
+
+        Self {
+            harness,
+            user,
+            record,
+        }
+    }
+
+    fn instruction(&self, request: Pubkey, escrow: Pubkey, num_bytes: u8) -> Instruction {
+        let accounts = solana_randomness_consumer::accounts::RequestAndRegister {
+            record: self.record,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: escrow,
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            payer: self.user,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::RequestAndRegister { num_bytes }.data(),
+        }
+    }
+
+    /// Sends `request_and_register` for a new request without settling it.
+    fn request(&mut self, num_bytes: u8) -> std::result::Result<Pubkey, ProgramError> {
+        let request = Pubkey::new_unique();
+        let escrow = self.harness.escrow(&request);
+        self.harness
+            .process_transaction(&[self.instruction(request, escrow, num_bytes)])?;
+        Ok(request)
+    }
+
+    fn record(&self) -> RandomnessRecord {
+        let account = self.harness.account(&self.record).unwrap();
+        RandomnessRecord::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+}
+
+#[test]
+fn test_request_marks_record_pending() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+
+    let request = fixture.request(8).unwrap();
+
+    let record = fixture.record();
+    assert!(record.pending);
+    assert_eq!(record.request, request);
+    assert_eq!(record.request_slot, 100);
+    let pending = fixture.harness.request(&request).unwrap();
+    assert_eq!(pending.user, fixture.user);
+    assert_eq!(pending.compute_units, REGISTER_CALLBACK_COMPUTE_UNITS);
+    assert_eq!(
+        pending.callback.discriminator(),
+        Some(anchor_sighash("consume_registered_randomness"))
+    );
+}
+
+#[test]
+fn test_callback_settles_record() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let request = Pubkey::new_unique();
+    let spec = RoundSpec {
+        consumer_request_ix: fixture.instruction(request, fixture.harness.escrow(&request), 8),
+        randomness: fixture.harness.randomness(&request, 8),
+        expected_callback_disc: anchor_sighash("consume_registered_randomness"),
+    };
+
+    let round = fixture.harness.run_round(spec).unwrap();
+
+    let record = fixture.record();
+    let result = fixture.harness.randomness(&round.request, 8);
+    assert!(!record.pending);
+    assert_eq!(record.request, request);
+    assert_eq!(record.slot, 100);
+    assert_eq!(
+        &record.result[..usize::from(record.result_len)],
+        &result[..]
+    );
+}
+
+#[test]
+fn test_failed_cpi_does_not_persist_record() {
+    let mut fixture = Fixture::new();
+    let request = Pubkey::new_unique();
+    // Passes the consumer's checks, but not the service's escrow derivation
+    let escrow = Pubkey::new_unique();
+
+    assert_eq!(
+        fixture
+            .harness
+            .process_transaction(&[fixture.instruction(request, escrow, 8)]),
+        Err(ProgramError::InvalidSeeds)
+    );
+
+    let record = fixture.record();
+    assert!(!record.pending);
+    assert_eq!(record.request, Pubkey::default());
+    assert!(fixture.harness.request(&request).is_none());
+}
+
+#[test]
+fn test_rejects_request_while_pending() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let first = fixture.request(8).unwrap();
+
+    assert_eq!(
+        fixture.request(8),
+        Err(consumer_error(ConsumerError::RequestStillPending))
+    );
+    assert_eq!(fixture.record().request, first);
+
+    // A request that never arrived is given up on
+    fixture
+        .harness
+        .warp_to_slot(100 + REGISTER_REQUEST_TIMEOUT_SLOTS);
+    let second = fixture.request(8).unwrap();
+    assert_eq!(fixture.record().request, second);
+
+    // The abandoned request can no longer settle the record
+    let late = fixture.harness.randomness(&first, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&first, &late),
+        Err(consumer_error(ConsumerError::UnexpectedRecordRequest))
+    );
+}
+
+#[test]
+fn test_rejects_underfunded_payer() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    fixture.harness.remove_account(&user);
+    fixture.harness.airdrop(&user, 1_000);
+
+    assert_eq!(
+        fixture.request(8),
+        Err(consumer_error(ConsumerError::InsufficientFunding))
+    );
+    assert!(!fixture.record().pending);
+}
+
+#[test]
+fn test_rejects_mint_other_than_state_mint() {
+    let mut fixture = Fixture::new();
+    // The cluster's service was reconfigured to another mint
+    let mut state = fixture
+        .harness
+        .account(&RANDOMNESS_SERVICE_STATE)
+        .unwrap()
+        .clone();
+    let offset = ServiceState::MINT_OFFSET;
+    state.data[offset..offset + 32].copy_from_slice(Pubkey::new_unique().as_ref());
+    fixture.harness.set_account(RANDOMNESS_SERVICE_STATE, state);
+
+    assert_eq!(fixture.request(8), Err(LiteError::MintMismatch.into()));
+    assert!(!fixture.record().pending);
+}
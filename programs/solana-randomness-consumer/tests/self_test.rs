@@ -0,0 +1,222 @@
+//! Runs `self_test` with the lite crate's oracle harness, as the program's upgrade authority
+//! would after a deploy.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{
+    ConsumerError, SelfTestResult, SELF_TEST_CALLBACK_COMPUTE_UNITS, SELF_TEST_NUM_BYTES,
+    SELF_TEST_TIMEOUT_SLOTS,
+};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{anchor_sighash, RANDOMNESS_SERVICE_STATE};
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    admin: Pubkey,
+    program_data: Pubkey,
+    self_test_result: Pubkey,
+}
+
+impl Fixture {
+    /// A deployed program whose upgrade authority is the funded harness payer, with the
+    /// result created by `initialize_self_test`.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+
+        let admin = harness.payer();
+        harness.airdrop(&admin, 1_000_000_000);
+
+        let (program_data, _) = Pubkey::find_program_address(
+            &[solana_randomness_consumer::ID.as_ref()],
+            &bpf_loader_upgradeable::ID,
+        );
+        let mut fixture = Self {
+            harness,
+            admin,
+            program_data,
+            self_test_result: Pubkey::default(),
+        };
+        fixture.set_upgrade_authority(Some(admin));
+
+        let (self_test_result, bump) =
+            Pubkey::find_program_address(&[SelfTestResult::SEED], &solana_randomness_consumer::ID);
+        let mut data = Vec::new();
+        SelfTestResult {
+            bump,
+            admin,
+            request: Pubkey::default(),
+            request_slot: 0,
+            pending: false,
+            settled_slot: 0,
+            result: 0,
+            runs: 0,
+            passes: 0,
+            reserved: [0; 32],
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        fixture.harness.set_account(
+            self_test_result,
+            HarnessAccount::new(data, solana_randomness_consumer::ID),
+        );
+        fixture.self_test_result = self_test_result;
+        fixture
+    }
+
+    /// Writes the ProgramData account the upgradeable loader keeps for the program.
+    fn set_upgrade_authority(&mut self, authority: Option<Pubkey>) {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend(1u64.to_le_bytes());
+        match authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend(authority.to_bytes());
+            }
+            None => data.push(0),
+        }
+        data.resize(
+            bpf_loader_upgradeable::UpgradeableLoaderState::size_of_programdata_metadata(),
+            0,
+        );
+        self.harness.set_account(
+            self.program_data,
+            HarnessAccount::new(data, bpf_loader_upgradeable::ID),
+        );
+    }
+
+    fn instruction(&self, request: Pubkey) -> Instruction {
+        let accounts = solana_randomness_consumer::accounts::SelfTest {
+            self_test_result: self.self_test_result,
+            program_data: self.program_data,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: self.harness.escrow(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            admin: self.admin,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::SelfTest {}.data(),
+        }
+    }
+
+    /// Sends `self_test` for a new request without settling it.
+    fn request(&mut self) -> std::result::Result<Pubkey, ProgramError> {
+        let request = Pubkey::new_unique();
+        self.harness
+            .process_transaction(&[self.instruction(request)])?;
+        Ok(request)
+    }
+
+    fn result(&self) -> SelfTestResult {
+        let account = self.harness.account(&self.self_test_result).unwrap();
+        SelfTestResult::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+}
+
+#[test]
+fn test_self_test_requests_one_byte_into_the_program() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+
+    let request = fixture.request().unwrap();
+
+    let result = fixture.result();
+    assert!(result.pending);
+    assert_eq!(result.request, request);
+    assert_eq!(result.request_slot, 100);
+    assert_eq!(result.runs, 1);
+    let pending = fixture.harness.request(&request).unwrap();
+    assert_eq!(pending.user, fixture.admin);
+    assert_eq!(pending.num_bytes, SELF_TEST_NUM_BYTES);
+    assert_eq!(pending.compute_units, SELF_TEST_CALLBACK_COMPUTE_UNITS);
+    assert_eq!(pending.callback.program_id, solana_randomness_consumer::ID);
+    assert_eq!(
+        pending.callback.discriminator(),
+        Some(anchor_sighash("consume_self_test"))
+    );
+}
+
+#[test]
+fn test_callback_records_pass() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let request = Pubkey::new_unique();
+    let spec = RoundSpec {
+        consumer_request_ix: fixture.instruction(request),
+        randomness: fixture.harness.randomness(&request, 1),
+        expected_callback_disc: anchor_sighash("consume_self_test"),
+    };
+
+    let round = fixture.harness.run_round(spec).unwrap();
+
+    let result = fixture.result();
+    assert!(!result.pending);
+    assert_eq!(result.request, request);
+    assert_eq!(result.settled_slot, 100);
+    assert_eq!(
+        result.result,
+        fixture.harness.randomness(&round.request, 1)[0]
+    );
+    assert_eq!((result.runs, result.passes), (1, 1));
+}
+
+#[test]
+fn test_rejects_signer_other_than_upgrade_authority() {
+    let mut fixture = Fixture::new();
+    fixture.set_upgrade_authority(Some(Pubkey::new_unique()));
+
+    assert_eq!(
+        fixture.request(),
+        Err(consumer_error(ConsumerError::NotProgramAuthority))
+    );
+
+    // An immutable program has no admin
+    fixture.set_upgrade_authority(None);
+    assert_eq!(
+        fixture.request(),
+        Err(consumer_error(ConsumerError::NotProgramAuthority))
+    );
+    assert_eq!(fixture.result().runs, 0);
+}
+
+#[test]
+fn test_unfulfilled_self_test_times_out() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+    let first = fixture.request().unwrap();
+
+    assert_eq!(
+        fixture.request(),
+        Err(consumer_error(ConsumerError::SelfTestPending))
+    );
+
+    fixture.harness.warp_to_slot(100 + SELF_TEST_TIMEOUT_SLOTS);
+    let second = fixture.request().unwrap();
+    let result = fixture.result();
+    assert_eq!(result.request, second);
+    assert_eq!((result.runs, result.passes), (2, 0));
+
+    // The abandoned request can no longer pass the newer self-test
+    let late = fixture.harness.randomness(&first, 1);
+    assert_eq!(
+        fixture.harness.fulfill(&first, &late),
+        Err(consumer_error(ConsumerError::UnexpectedSelfTestRequest))
+    );
+}
@@ -0,0 +1,207 @@
+//! Every account's `SPACE` holds the account with each field at its largest encoding.
+
+use anchor_lang::prelude::*;
+use anchor_lang::AccountSerialize;
+use solana_randomness_consumer::{
+    Bracket, CachedRandomness, Lootbox, LootboxOpening, PrizeTier, Proposal, RandomnessRecord,
+    ReferralConfig, ResultFeed, SelfTestResult, TieBreak, TieBreakState, TiebreakReceipt,
+    TiedCandidate, Tournament, TIEBREAK_MAX_CANDIDATES, TIE_BREAK_MAX_BIDDERS,
+};
+
+const MAX_PUBKEY: Pubkey = Pubkey::new_from_array([u8::MAX; 32]);
+
+fn assert_fits(account: &impl AccountSerialize, space: usize) {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    assert!(
+        data.len() <= space,
+        "{} bytes do not fit in {}",
+        data.len(),
+        space
+    );
+}
+
+#[test]
+fn test_result_feed_fits() {
+    let feed = ResultFeed {
+        bump: u8::MAX,
+        user: MAX_PUBKEY,
+        request: MAX_PUBKEY,
+        slot: u64::MAX,
+        result_len: u8::MAX,
+        result: [u8::MAX; 32],
+        request_slot: u64::MAX,
+        reserved: [u8::MAX; 24],
+    };
+    assert_fits(&feed, ResultFeed::SPACE);
+}
+
+#[test]
+fn test_cached_randomness_fits() {
+    let cache = CachedRandomness {
+        bump: u8::MAX,
+        authority: MAX_PUBKEY,
+        num_bytes: u8::MAX,
+        slot: u64::MAX,
+        result_len: u8::MAX,
+        result: [u8::MAX; 32],
+        pending_request: MAX_PUBKEY,
+        pending_slot: u64::MAX,
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&cache, CachedRandomness::SPACE);
+}
+
+#[test]
+fn test_randomness_record_fits() {
+    let record = RandomnessRecord {
+        bump: u8::MAX,
+        user: MAX_PUBKEY,
+        request: MAX_PUBKEY,
+        request_slot: u64::MAX,
+        pending: true,
+        slot: u64::MAX,
+        result_len: u8::MAX,
+        result: [u8::MAX; 32],
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&record, RandomnessRecord::SPACE);
+}
+
+#[test]
+fn test_lootbox_fits() {
+    let lootbox = Lootbox {
+        bump: u8::MAX,
+        authority: MAX_PUBKEY,
+        key_mint: MAX_PUBKEY,
+        prize_mint: MAX_PUBKEY,
+        num_opened: u64::MAX,
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&lootbox, Lootbox::SPACE);
+}
+
+#[test]
+fn test_lootbox_opening_fits() {
+    let opening = LootboxOpening {
+        bump: u8::MAX,
+        lootbox: MAX_PUBKEY,
+        user: MAX_PUBKEY,
+        request: MAX_PUBKEY,
+        request_slot: u64::MAX,
+        settled_slot: u64::MAX,
+        is_settled: true,
+        randomness: [u8::MAX; 4],
+        tier: PrizeTier::Legendary,
+        amount: u64::MAX,
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&opening, LootboxOpening::SPACE);
+}
+
+#[test]
+fn test_tournament_fits() {
+    let tournament = Tournament {
+        bump: u8::MAX,
+        authority: MAX_PUBKEY,
+        id: u64::MAX,
+        bracket_count: u16::MAX,
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&tournament, Tournament::SPACE);
+}
+
+#[test]
+fn test_bracket_fits() {
+    let bracket = Bracket {
+        bump: u8::MAX,
+        tournament: MAX_PUBKEY,
+        index: u16::MAX,
+        pending_request: MAX_PUBKEY,
+        request: MAX_PUBKEY,
+        slot: u64::MAX,
+        result_len: u8::MAX,
+        result: [u8::MAX; 32],
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&bracket, Bracket::SPACE);
+}
+
+#[test]
+fn test_tie_break_fits() {
+    let states = [
+        TieBreakState::Open,
+        TieBreakState::AwaitingRandomness {
+            request: MAX_PUBKEY,
+            request_slot: u64::MAX,
+        },
+        TieBreakState::RandomnessReceived {
+            bytes: [u8::MAX; 8],
+            slot: u64::MAX,
+        },
+        TieBreakState::Settled { winner: MAX_PUBKEY },
+    ];
+    for state in states {
+        let tie_break = TieBreak {
+            bump: u8::MAX,
+            authority: MAX_PUBKEY,
+            id: u64::MAX,
+            bidder_count: u8::MAX,
+            bidders: [MAX_PUBKEY; TIE_BREAK_MAX_BIDDERS],
+            state,
+            reserved: [u8::MAX; 32],
+        };
+        assert_fits(&tie_break, TieBreak::SPACE);
+    }
+}
+
+#[test]
+fn test_self_test_result_fits() {
+    let result = SelfTestResult {
+        bump: u8::MAX,
+        admin: MAX_PUBKEY,
+        request: MAX_PUBKEY,
+        request_slot: u64::MAX,
+        pending: true,
+        settled_slot: u64::MAX,
+        result: u8::MAX,
+        runs: u64::MAX,
+        passes: u64::MAX,
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&result, SelfTestResult::SPACE);
+}
+
+#[test]
+fn test_referral_config_fits() {
+    let config = ReferralConfig {
+        bump: u8::MAX,
+        recipient: MAX_PUBKEY,
+        fee_lamports: u64::MAX,
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&config, ReferralConfig::SPACE);
+}
+
+#[test]
+fn test_proposal_fits() {
+    let proposal = Proposal {
+        bump: u8::MAX,
+        authority: MAX_PUBKEY,
+        id: u64::MAX,
+        candidate_count: u8::MAX,
+        candidates: [TiedCandidate {
+            candidate: MAX_PUBKEY,
+            voting_power: u64::MAX,
+        }; TIEBREAK_MAX_CANDIDATES],
+        pending_request: MAX_PUBKEY,
+        receipt: Some(TiebreakReceipt {
+            request: MAX_PUBKEY,
+            slot: u64::MAX,
+            randomness: [u8::MAX; 32],
+        }),
+        winner: Some(MAX_PUBKEY),
+        reserved: [u8::MAX; 32],
+    };
+    assert_fits(&proposal, Proposal::SPACE);
+}
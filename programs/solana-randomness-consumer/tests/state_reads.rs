@@ -0,0 +1,52 @@
+//! Runs `measure_state_reads` with the lite crate's oracle harness. The harness does not meter
+//! compute units, so this checks both reads agree; the units are measured on a validator.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::RANDOMNESS_SERVICE_STATE;
+
+fn instruction(state: Pubkey) -> Instruction {
+    let accounts = solana_randomness_consumer::accounts::MeasureStateReads {
+        randomness_state: state,
+    };
+    Instruction {
+        program_id: solana_randomness_consumer::ID,
+        accounts: accounts.to_account_metas(None),
+        data: solana_randomness_consumer::instruction::MeasureStateReads {}.data(),
+    }
+}
+
+fn harness() -> OracleHarness {
+    let mut harness = OracleHarness::new(
+        solana_randomness_consumer::ID,
+        solana_randomness_consumer::entry,
+    );
+    harness.add_service_accounts();
+    harness
+}
+
+#[test]
+fn test_state_reads_agree() {
+    let mut harness = harness();
+
+    harness
+        .process_transaction(&[instruction(RANDOMNESS_SERVICE_STATE)])
+        .unwrap();
+}
+
+#[test]
+fn test_state_reads_reject_other_account() {
+    let mut harness = harness();
+    let other = Pubkey::new_unique();
+    let state = harness.account(&RANDOMNESS_SERVICE_STATE).unwrap().clone();
+    harness.set_account(other, state);
+
+    assert_eq!(
+        harness.process_transaction(&[instruction(other)]),
+        Err(ProgramError::Custom(
+            anchor_lang::error::ErrorCode::ConstraintAddress as u32
+        ))
+    );
+}
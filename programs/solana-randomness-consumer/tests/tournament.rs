@@ -0,0 +1,295 @@
+//! Routes the randomness of tournament requests to bracket PDAs by the indexed tag in their
+//! callback, with the lite crate's oracle harness.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use solana_randomness_consumer::{Bracket, ConsumerError, Tournament};
+use solana_randomness_service_lite::harness::*;
+use solana_randomness_service_lite::{
+    anchor_sighash, Callback, IndexedTag, RANDOMNESS_SERVICE_STATE,
+};
+
+const TOURNAMENT_ID: u64 = 42;
+const BRACKET_COUNT: u16 = 16;
+
+fn consumer_error(error: ConsumerError) -> ProgramError {
+    ProgramError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+fn anchor_account(account: &impl AccountSerialize) -> HarnessAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    HarnessAccount::new(data, solana_randomness_consumer::ID)
+}
+
+struct Fixture {
+    harness: OracleHarness,
+    authority: Pubkey,
+    tournament: Pubkey,
+}
+
+impl Fixture {
+    /// A tournament of `BRACKET_COUNT` brackets, laid out as `initialize_tournament` and
+    /// `initialize_bracket` leave them.
+    fn new() -> Self {
+        let mut harness = OracleHarness::new(
+            solana_randomness_consumer::ID,
+            solana_randomness_consumer::entry,
+        );
+        harness.add_service_accounts();
+
+        let authority = harness.payer();
+        harness.airdrop(&authority, 1_000_000_000);
+        let (tournament, bump) = Pubkey::find_program_address(
+            &[Tournament::SEED, authority.as_ref()],
+            &solana_randomness_consumer::ID,
+        );
+        harness.set_account(
+            tournament,
+            anchor_account(&Tournament {
+                bump,
+                authority,
+                id: TOURNAMENT_ID,
+                bracket_count: BRACKET_COUNT,
+                reserved: [0; 32],
+            }),
+        );
+
+        let mut fixture = Self {
+            harness,
+            authority,
+            tournament,
+        };
+        for index in 0..BRACKET_COUNT {
+            fixture.add_bracket(index);
+        }
+        fixture
+    }
+
+    fn bracket_address(&self, index: u16) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Bracket::SEED,
+                self.tournament.as_ref(),
+                &index.to_le_bytes(),
+            ],
+            &solana_randomness_consumer::ID,
+        )
+    }
+
+    fn add_bracket(&mut self, index: u16) -> Pubkey {
+        let (bracket, bump) = self.bracket_address(index);
+        self.harness.set_account(
+            bracket,
+            anchor_account(&Bracket {
+                bump,
+                tournament: self.tournament,
+                index,
+                pending_request: Pubkey::default(),
+                request: Pubkey::default(),
+                slot: 0,
+                result_len: 0,
+                result: [0; 32],
+                reserved: [0; 32],
+            }),
+        );
+        bracket
+    }
+
+    fn bracket(&self, index: u16) -> Bracket {
+        let account = self
+            .harness
+            .account(&self.bracket_address(index).0)
+            .unwrap();
+        Bracket::try_deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    fn instruction(&self, request: Pubkey, index: u16) -> Instruction {
+        let accounts = solana_randomness_consumer::accounts::RequestBracketRandomness {
+            tournament: self.tournament,
+            bracket: self.bracket_address(index).0,
+            randomness_service: solana_randomness_service::ID,
+            randomness_request: request,
+            randomness_escrow: self.harness.escrow(&request),
+            randomness_state: RANDOMNESS_SERVICE_STATE,
+            randomness_mint: spl_token::native_mint::ID,
+            authority: self.authority,
+            system_program: anchor_lang::system_program::ID,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+        };
+        Instruction {
+            program_id: solana_randomness_consumer::ID,
+            accounts: accounts.to_account_metas(None),
+            data: solana_randomness_consumer::instruction::RequestBracketRandomness {
+                index,
+                num_bytes: 8,
+            }
+            .data(),
+        }
+    }
+
+    /// Requests randomness for the bracket at `index` through the program and returns the
+    /// request.
+    fn request(&mut self, index: u16) -> Pubkey {
+        let request = Pubkey::new_unique();
+        self.harness
+            .process_transaction(&[self.instruction(request, index)])
+            .unwrap();
+        request
+    }
+
+    /// Submits a request whose callback is tagged with `tag` and `index` but lists the bracket
+    /// at `listed`, as a request built outside the program could.
+    fn submit_tagged(&mut self, tag: u64, index: u16, listed: u16) -> Pubkey {
+        let request = Pubkey::new_unique();
+        let (callback, _) = Callback::builder(solana_randomness_consumer::ID)
+            .with_state_signer()
+            .with_readonly_account(request)
+            .with_readonly_account(self.tournament)
+            .with_writable_account(self.bracket_address(listed).0)
+            .with_ix_data(anchor_sighash("consume_bracket_randomness").to_vec())
+            .with_indexed_tag(tag, index)
+            .build()
+            .unwrap();
+        self.harness
+            .submit_request(request, 8, callback, None)
+            .unwrap();
+        request
+    }
+}
+
+#[test]
+fn test_results_land_in_tagged_brackets() {
+    let mut fixture = Fixture::new();
+    fixture.harness.warp_to_slot(100);
+
+    let mut rounds = Vec::new();
+    for index in [3, 10] {
+        let request = Pubkey::new_unique();
+        let spec = RoundSpec {
+            consumer_request_ix: fixture.instruction(request, index),
+            randomness: fixture.harness.randomness(&request, 8),
+            expected_callback_disc: anchor_sighash("consume_bracket_randomness"),
+        };
+        let round = fixture.harness.run_round(spec).unwrap();
+        assert_eq!(
+            round.pending.callback.indexed_tag(),
+            Some(IndexedTag::new(TOURNAMENT_ID, index))
+        );
+        rounds.push((index, round.request));
+    }
+
+    for (index, request) in rounds {
+        let bracket = fixture.bracket(index);
+        assert_eq!(bracket.request, request);
+        assert_eq!(bracket.slot, 100);
+        assert_eq!(
+            &bracket.result[..usize::from(bracket.result_len)],
+            &fixture.harness.randomness(&request, 8)[..]
+        );
+    }
+    // The other brackets are untouched
+    assert_eq!(fixture.bracket(0).request, Pubkey::default());
+    assert_eq!(fixture.bracket(0).result_len, 0);
+}
+
+#[test]
+fn test_request_rejects_out_of_bounds_index() {
+    let mut fixture = Fixture::new();
+    // A bracket left over from a larger configuration
+    fixture.add_bracket(BRACKET_COUNT);
+
+    assert_eq!(
+        fixture
+            .harness
+            .process_transaction(&[fixture.instruction(Pubkey::new_unique(), BRACKET_COUNT)]),
+        Err(consumer_error(ConsumerError::BracketIndexOutOfBounds))
+    );
+}
+
+#[test]
+fn test_callback_rejects_out_of_bounds_index() {
+    let mut fixture = Fixture::new();
+    fixture.add_bracket(BRACKET_COUNT);
+    let request = fixture.submit_tagged(TOURNAMENT_ID, BRACKET_COUNT, BRACKET_COUNT);
+
+    let randomness = fixture.harness.randomness(&request, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&request, &randomness),
+        Err(consumer_error(ConsumerError::BracketIndexOutOfBounds))
+    );
+    assert_eq!(fixture.bracket(BRACKET_COUNT).result_len, 0);
+}
+
+#[test]
+fn test_callback_derives_bracket_from_index() {
+    let mut fixture = Fixture::new();
+    // Tagged for bracket 5, but listing bracket 3
+    let request = fixture.submit_tagged(TOURNAMENT_ID, 5, 3);
+
+    let randomness = fixture.harness.randomness(&request, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&request, &randomness),
+        Err(ProgramError::Custom(
+            anchor_lang::error::ErrorCode::ConstraintSeeds as u32
+        ))
+    );
+    assert_eq!(fixture.bracket(3).result_len, 0);
+}
+
+#[test]
+fn test_callback_rejects_other_tournament_tag() {
+    let mut fixture = Fixture::new();
+    let request = fixture.submit_tagged(TOURNAMENT_ID + 1, 3, 3);
+
+    let randomness = fixture.harness.randomness(&request, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&request, &randomness),
+        Err(consumer_error(ConsumerError::TournamentTagMismatch))
+    );
+}
+
+#[test]
+fn test_callback_rejects_request_made_outside_the_program() {
+    let mut fixture = Fixture::new();
+    fixture.request(3);
+    // Tagged and listed like the program's own request, but not the bracket's pending one
+    let request = fixture.submit_tagged(TOURNAMENT_ID, 3, 3);
+
+    let randomness = fixture.harness.randomness(&request, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&request, &randomness),
+        Err(consumer_error(ConsumerError::UnexpectedBracketRequest))
+    );
+    assert_eq!(fixture.bracket(3).result_len, 0);
+}
+
+#[test]
+fn test_callback_rejects_replaced_or_settled_request() {
+    let mut fixture = Fixture::new();
+    let first = fixture.request(3);
+    let second = fixture.request(3);
+
+    let randomness = fixture.harness.randomness(&first, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&first, &randomness),
+        Err(consumer_error(ConsumerError::UnexpectedBracketRequest))
+    );
+    let randomness = fixture.harness.randomness(&second, 8);
+    fixture.harness.fulfill(&second, &randomness).unwrap();
+
+    let bracket = fixture.bracket(3);
+    assert_eq!(bracket.request, second);
+    assert_eq!(bracket.pending_request, Pubkey::default());
+    // Nothing is pending once settled, so the result cannot be re-rolled
+    let reroll = fixture.submit_tagged(TOURNAMENT_ID, 3, 3);
+    let randomness = fixture.harness.randomness(&reroll, 8);
+    assert_eq!(
+        fixture.harness.fulfill(&reroll, &randomness),
+        Err(consumer_error(ConsumerError::UnexpectedBracketRequest))
+    );
+    assert_eq!(fixture.bracket(3).request, second);
+}
@@ -0,0 +1,204 @@
+import type { SolanaRandomnessConsumer } from "../target/types/solana_randomness_consumer";
+
+import type { Program } from "@coral-xyz/anchor";
+import * as anchor from "@coral-xyz/anchor";
+import { createAssociatedTokenAccount, getAccount } from "@solana/spl-token";
+import { RandomnessService } from "@switchboard-xyz/solana-randomness-service";
+import assert from "assert";
+
+// Mirrors LOOTBOX_CALLBACK_COMPUTE_UNITS in programs/solana-randomness-consumer/src/lootbox.rs
+const LOOTBOX_CALLBACK_COMPUTE_UNITS = 200_000;
+
+// Mirrors PrizeTier::from_randomness and PrizeTier::prize_amount
+function expectedPrize(randomness: Uint8Array): [string, number] {
+  const value = new anchor.BN(Buffer.from(randomness), "le");
+  const roll = value.muln(10_000).shrn(32).toNumber();
+  if (roll < 7_000) return ["common", 1];
+  if (roll < 9_500) return ["rare", 5];
+  if (roll < 9_900) return ["epic", 25];
+  return ["legendary", 100];
+}
+
+describe("Lootbox Example", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace
+    .SolanaRandomnessConsumer as Program<SolanaRandomnessConsumer>;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  let randomnessService: RandomnessService;
+
+  const keyMint = anchor.web3.Keypair.generate();
+  const prizeMint = anchor.web3.Keypair.generate();
+  const [lootbox] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("LOOTBOX"), keyMint.publicKey.toBuffer()],
+    program.programId
+  );
+
+  let userKeyAccount: anchor.web3.PublicKey;
+  let userPrizeAccount: anchor.web3.PublicKey;
+
+  function openBoxAccounts(requestKeypair: anchor.web3.Keypair) {
+    const [opening] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("OPENING"), requestKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+    return {
+      opening,
+      accounts: {
+        lootbox,
+        keyMint: keyMint.publicKey,
+        userKeyAccount,
+        prizeMint: prizeMint.publicKey,
+        userPrizeAccount,
+        opening,
+        randomnessService: randomnessService.programId,
+        randomnessRequest: requestKeypair.publicKey,
+        randomnessEscrow: anchor.utils.token.associatedAddress({
+          mint: randomnessService.accounts.mint,
+          owner: requestKeypair.publicKey,
+        }),
+        randomnessState: randomnessService.accounts.state,
+        randomnessMint: randomnessService.accounts.mint,
+        payer: provider.wallet.publicKey,
+      },
+    };
+  }
+
+  before(async () => {
+    randomnessService = await RandomnessService.fromProvider(provider);
+
+    await program.methods
+      .initializeLootbox()
+      .accounts({
+        lootbox,
+        keyMint: keyMint.publicKey,
+        prizeMint: prizeMint.publicKey,
+        payer: provider.wallet.publicKey,
+      })
+      .signers([keyMint, prizeMint])
+      .rpc();
+
+    userKeyAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      keyMint.publicKey,
+      provider.wallet.publicKey
+    );
+    userPrizeAccount = await createAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      prizeMint.publicKey,
+      provider.wallet.publicKey
+    );
+  });
+
+  it("rejects opening a lootbox without a key", async () => {
+    const requestKeypair = anchor.web3.Keypair.generate();
+    const { opening, accounts } = openBoxAccounts(requestKeypair);
+
+    await assert.rejects(
+      program.methods
+        .openBox()
+        .accounts(accounts)
+        .signers([requestKeypair])
+        .rpc(),
+      "Opening a lootbox without a key should fail"
+    );
+
+    // The burn runs first, so the randomness request was never created
+    assert.equal(
+      await provider.connection.getAccountInfo(requestKeypair.publicKey),
+      null,
+      "Request account should not exist"
+    );
+    assert.equal(
+      await provider.connection.getAccountInfo(opening),
+      null,
+      "Opening account should not exist"
+    );
+  });
+
+  it("opens a lootbox and mints the prize in the callback", async () => {
+    await program.methods
+      .mintLootboxKeys(new anchor.BN(1))
+      .accounts({
+        lootbox,
+        authority: provider.wallet.publicKey,
+        keyMint: keyMint.publicKey,
+        destination: userKeyAccount,
+      })
+      .rpc();
+
+    const requestKeypair = anchor.web3.Keypair.generate();
+    const { opening, accounts } = openBoxAccounts(requestKeypair);
+
+    const settledRandomnessEventPromise = randomnessService.awaitSettledEvent(
+      requestKeypair.publicKey
+    );
+
+    const signature = await program.methods
+      .openBox()
+      .accounts(accounts)
+      .signers([requestKeypair])
+      .rpc();
+    console.log(`[TX] openBox: ${signature}`);
+
+    const keyAccount = await getAccount(provider.connection, userKeyAccount);
+    assert.equal(
+      keyAccount.amount.toString(),
+      "0",
+      "Key should have been burned"
+    );
+
+    const [settledRandomnessEvent] = await settledRandomnessEventPromise;
+    assert.equal(
+      settledRandomnessEvent.isSuccess,
+      true,
+      "Request did not complete successfully"
+    );
+
+    const openingState = await program.account.lootboxOpening.fetch(opening);
+    const [expectedTier, expectedAmount] = expectedPrize(
+      new Uint8Array(settledRandomnessEvent.randomness)
+    );
+    assert.equal(openingState.isSettled, true, "Opening should be settled");
+    assert.equal(
+      Object.keys(openingState.tier)[0],
+      expectedTier,
+      "Recorded tier should match the randomness"
+    );
+    assert.equal(openingState.amount.toNumber(), expectedAmount);
+
+    const prizeAccount = await getAccount(provider.connection, userPrizeAccount);
+    assert.equal(
+      prizeAccount.amount.toString(),
+      expectedAmount.toString(),
+      "Prize should have been minted to the user"
+    );
+
+    // Measure the compute consumed by the settle transaction, which includes the callback
+    const [settleSignature] = await provider.connection.getSignaturesForAddress(
+      opening,
+      { limit: 1 },
+      "confirmed"
+    );
+    const settleTx = await provider.connection.getTransaction(
+      settleSignature.signature,
+      { commitment: "confirmed", maxSupportedTransactionVersion: 0 }
+    );
+    const unitsConsumed = settleTx?.meta?.computeUnitsConsumed ?? 0;
+    console.log(`Lootbox settlement consumed ${unitsConsumed} compute units`);
+    // The callback's own share, which reading the request with
+    // SimpleRandomnessV1AccountView instead of deserializing it keeps down
+    const callbackLog = (settleTx?.meta?.logMessages ?? []).find((log) =>
+      log.startsWith(`Program ${program.programId.toBase58()} consumed`)
+    );
+    console.log(`Lootbox callback: ${callbackLog ?? "no compute log found"}`);
+    assert(
+      unitsConsumed < LOOTBOX_CALLBACK_COMPUTE_UNITS,
+      `Settlement used ${unitsConsumed} CU, over the ${LOOTBOX_CALLBACK_COMPUTE_UNITS} CU budget`
+    );
+  });
+});
@@ -89,4 +89,51 @@ describe("Solana Randomness Service Example", () => {
       )}]\nRequest completed in ${latency} slots!\n`
     );
   });
+
+  it("rejects a request account owned by the consumer program", async () => {
+    // An initialized account owned by the consumer, like one of its own PDAs
+    const requestKeypair = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: provider.wallet.publicKey,
+          newAccountPubkey: requestKeypair.publicKey,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(64),
+          space: 64,
+          programId: program.programId,
+        })
+      ),
+      [requestKeypair]
+    );
+
+    await assert.rejects(
+      program.methods
+        .requestRandomness()
+        .accounts({
+          randomnessService: randomnessService.programId,
+          randomnessRequest: requestKeypair.publicKey,
+          randomnessEscrow: anchor.utils.token.associatedAddress({
+            mint: randomnessService.accounts.mint,
+            owner: requestKeypair.publicKey,
+          }),
+          randomnessState: randomnessService.accounts.state,
+          randomnessMint: randomnessService.accounts.mint,
+          payer: provider.wallet.publicKey,
+        })
+        .signers([requestKeypair])
+        .rpc(),
+      /ConstraintOwner/,
+      "A non-empty request account should be rejected"
+    );
+
+    const account = await provider.connection.getAccountInfo(
+      requestKeypair.publicKey
+    );
+    assert.equal(
+      account.owner.toBase58(),
+      program.programId.toBase58(),
+      "Request account should be untouched"
+    );
+  });
 });
@@ -0,0 +1,112 @@
+//! Templates for a new Anchor consumer of the randomness service.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CARGO_TOML: &str = include_str!("../templates/consumer/Cargo.toml.tmpl");
+const LIB_RS: &str = include_str!("../templates/consumer/lib.rs.tmpl");
+const CALLBACK_TEST: &str = include_str!("../templates/consumer/callback.rs.tmpl");
+
+/// Where the generated crate gets `solana-randomness-service-lite` from.
+pub enum LiteDependency {
+    Published,
+    /// A local checkout, e.g. this repository's `crates/solana-randomness-service-lite`.
+    Path(PathBuf),
+}
+
+pub struct ConsumerOptions {
+    name: String,
+    callback: String,
+    lite: LiteDependency,
+}
+
+impl ConsumerOptions {
+    /// Both names must be snake case Rust identifiers: the program module and the callback
+    /// instruction.
+    pub fn new(name: String, callback: String, lite: LiteDependency) -> Result<Self, String> {
+        for (flag, value) in [("--name", &name), ("--callback", &callback)] {
+            if !is_snake_case_ident(value) {
+                return Err(format!(
+                    "Invalid {} {}: expected a snake case identifier",
+                    flag, value
+                ));
+            }
+        }
+        Ok(Self {
+            name,
+            callback,
+            lite,
+        })
+    }
+
+    pub fn crate_name(&self) -> String {
+        self.name.replace('_', "-")
+    }
+
+    fn lite_dependency(&self, features: &str) -> String {
+        match &self.lite {
+            LiteDependency::Published => format!("{{ version = \"1\"{} }}", features),
+            LiteDependency::Path(path) => format!(
+                "{{ path = \"{}\"{} }}",
+                path.display().to_string().replace('\\', "/"),
+                features
+            ),
+        }
+    }
+
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("{{crate_name}}", &self.crate_name())
+            .replace("{{lib_name}}", &self.name)
+            .replace("{{callback}}", &self.callback)
+            .replace("{{callback_struct}}", &upper_camel_case(&self.callback))
+            .replace("{{lite_dependency}}", &self.lite_dependency(""))
+            .replace(
+                "{{lite_harness_dependency}}",
+                &self.lite_dependency(", features = [\"harness\"]"),
+            )
+    }
+}
+
+/// Writes the consumer crate to `out`, which must not exist yet or be empty.
+pub fn generate(options: &ConsumerOptions, out: &Path) -> io::Result<()> {
+    if out.exists() && fs::read_dir(out)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is not empty", out.display()),
+        ));
+    }
+
+    for (path, template) in [
+        ("Cargo.toml", CARGO_TOML),
+        ("src/lib.rs", LIB_RS),
+        ("tests/callback.rs", CALLBACK_TEST),
+    ] {
+        let path = out.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, options.render(template))?;
+    }
+    Ok(())
+}
+
+fn is_snake_case_ident(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some('a'..='z'))
+        && chars.all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_'))
+}
+
+fn upper_camel_case(value: &str) -> String {
+    value
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
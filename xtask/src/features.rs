@@ -0,0 +1,98 @@
+//! The feature combinations of `solana-randomness-service-lite` that must keep building.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Every feature of the lite crate. Each is checked alone, and a feature missing here fails
+/// the xtask test, so new features join the matrix when they are added.
+pub const LITE_FEATURES: &[&str] = &[
+    "anchor",
+    "client",
+    "harness",
+    "idl-check",
+    "test-variants",
+    "version-tag",
+];
+
+/// Combinations checked on top of each feature alone, for features that share code paths or
+/// are commonly enabled together.
+const COMBINATIONS: &[&[&str]] = &[
+    &["anchor", "client"],
+    &["anchor", "harness"],
+    &["client", "idl-check"],
+    &["client", "harness"],
+];
+
+/// One `cargo check` invocation of the matrix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeatureSet {
+    NoDefault,
+    Default,
+    Features(Vec<&'static str>),
+    All,
+}
+
+impl FeatureSet {
+    fn cargo_args(&self) -> Vec<String> {
+        match self {
+            FeatureSet::NoDefault => vec!["--no-default-features".to_string()],
+            FeatureSet::Default => vec![],
+            FeatureSet::Features(features) => vec![
+                "--no-default-features".to_string(),
+                "--features".to_string(),
+                features.join(","),
+            ],
+            FeatureSet::All => vec!["--all-features".to_string()],
+        }
+    }
+}
+
+impl std::fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureSet::NoDefault => write!(f, "--no-default-features"),
+            FeatureSet::Default => write!(f, "default"),
+            FeatureSet::Features(features) => write!(f, "{}", features.join(",")),
+            FeatureSet::All => write!(f, "--all-features"),
+        }
+    }
+}
+
+/// The matrix in the order it is checked: no features, the defaults, each feature alone, the
+/// curated combinations, and everything.
+pub fn matrix() -> Vec<FeatureSet> {
+    let mut matrix = vec![FeatureSet::NoDefault, FeatureSet::Default];
+    matrix.extend(
+        LITE_FEATURES
+            .iter()
+            .map(|feature| FeatureSet::Features(vec![*feature])),
+    );
+    matrix.extend(
+        COMBINATIONS
+            .iter()
+            .map(|features| FeatureSet::Features(features.to_vec())),
+    );
+    matrix.push(FeatureSet::All);
+    matrix
+}
+
+/// Runs `cargo check --all-targets` on the lite crate for every set in the matrix, stopping at
+/// the first one that fails to build.
+pub fn check(lite: &Path) -> Result<(), String> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let manifest = lite.join("Cargo.toml");
+
+    for set in matrix() {
+        println!("Checking {}", set);
+        let status = Command::new(&cargo)
+            .args(["check", "--all-targets", "--quiet", "--manifest-path"])
+            .arg(&manifest)
+            .args(set.cargo_args())
+            .status()
+            .map_err(|e| format!("Failed to run cargo: {}", e))?;
+        if !status.success() {
+            return Err(format!("Feature combination {} failed to build", set));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Development tasks for the randomness service workspace, run with `cargo xtask <TASK>`.
+//!
+//! ```text
+//! cargo xtask generate-consumer --name <PROGRAM> --callback <INSTRUCTION> --out <DIR> [--lite-path <PATH>]
+//! cargo xtask feature-matrix [--list]
+//! ```
+//!
+//! `generate-consumer` writes a new Anchor consumer crate to `--out`: a request instruction, a
+//! guarded callback, a settings PDA, and a test running the callback against the lite crate's
+//! oracle harness. `--lite-path` depends on a local checkout of
+//! `solana-randomness-service-lite` instead of the published crate.
+//!
+//! `feature-matrix` runs `cargo check` on the lite crate with no features, the defaults, each
+//! feature alone, a few combinations, and all features, and names the first combination that
+//! fails to build. `--list` prints the combinations without checking them.
+
+#![forbid(unsafe_code)]
+
+mod consumer;
+mod features;
+
+use consumer::{ConsumerOptions, LiteDependency};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage:
+  cargo xtask generate-consumer --name <PROGRAM> --callback <INSTRUCTION> --out <DIR> [--lite-path <PATH>]
+  cargo xtask feature-matrix [--list]";
+
+struct GenerateConsumerArgs {
+    options: ConsumerOptions,
+    out: PathBuf,
+}
+
+fn parse_generate_consumer_args(args: &[String]) -> Result<GenerateConsumerArgs, String> {
+    let mut name = None;
+    let mut callback = None;
+    let mut out = None;
+    let mut lite = LiteDependency::Published;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?
+            .clone();
+        match arg.as_str() {
+            "--name" => name = Some(value),
+            "--callback" => callback = Some(value),
+            "--out" => out = Some(PathBuf::from(value)),
+            "--lite-path" => lite = LiteDependency::Path(PathBuf::from(value)),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    Ok(GenerateConsumerArgs {
+        options: ConsumerOptions::new(
+            name.ok_or("Missing --name")?,
+            callback.ok_or("Missing --callback")?,
+            lite,
+        )?,
+        out: out.ok_or("Missing --out")?,
+    })
+}
+
+fn generate_consumer(args: GenerateConsumerArgs) -> Result<(), Box<dyn std::error::Error>> {
+    consumer::generate(&args.options, &args.out)?;
+    println!(
+        "Generated {} in {}",
+        args.options.crate_name(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+fn feature_matrix(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args {
+        [] => {
+            let lite = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../crates/solana-randomness-service-lite");
+            features::check(&lite)?;
+            println!("All feature combinations build");
+        }
+        [flag] if flag == "--list" => {
+            for set in features::matrix() {
+                println!("{}", set);
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("generate-consumer") => parse_generate_consumer_args(&args[1..])
+            .map_err(|e| e.into())
+            .and_then(generate_consumer),
+        Some("feature-matrix") => feature_matrix(&args[1..]),
+        _ => Err(USAGE.into()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,57 @@
+//! Keeps the feature matrix in sync with the lite crate's manifest. Building the matrix itself
+//! is `cargo xtask feature-matrix`, which is too slow to run on every test.
+
+use std::path::Path;
+use std::process::Command;
+
+/// The features declared in the `[features]` table of the lite crate's manifest.
+fn manifest_features() -> Vec<String> {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../crates/solana-randomness-service-lite/Cargo.toml");
+    let manifest = std::fs::read_to_string(manifest).unwrap();
+
+    manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[features]")
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('=').map(|(name, _)| name.trim()))
+        .filter(|name| !name.is_empty() && *name != "default")
+        .map(str::to_string)
+        .collect()
+}
+
+fn list() -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_xtask"))
+        .args(["feature-matrix", "--list"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_matrix_checks_every_feature_alone() {
+    let list = list();
+    let features = manifest_features();
+    assert!(!features.is_empty());
+
+    for feature in features {
+        assert!(
+            list.contains(&feature),
+            "feature {} is missing from the matrix in xtask/src/features.rs",
+            feature
+        );
+    }
+}
+
+#[test]
+fn test_matrix_includes_no_default_and_all_features() {
+    let list = list();
+    assert_eq!(list.first().unwrap(), "--no-default-features");
+    assert_eq!(list.last().unwrap(), "--all-features");
+}
@@ -0,0 +1,76 @@
+//! Generates a consumer against this checkout of the lite crate and runs its tests, so the
+//! templates keep building as the lite crate changes.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn generate(out: &Path, name: &str, callback: &str) -> std::process::Output {
+    let lite = workspace_root().join("crates/solana-randomness-service-lite");
+    Command::new(env!("CARGO_BIN_EXE_xtask"))
+        .args(["generate-consumer", "--name", name, "--callback", callback])
+        .arg("--out")
+        .arg(out)
+        .arg("--lite-path")
+        .arg(lite)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_generated_consumer_builds_and_passes_tests() {
+    let out = std::env::temp_dir().join(format!("xtask-consumer-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&out);
+
+    let output = generate(&out, "coin_flip", "settle_flip");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Pin the dependencies to the versions the workspace already builds with
+    let lockfile = workspace_root().join("Cargo.lock");
+    if lockfile.exists() {
+        std::fs::copy(lockfile, out.join("Cargo.lock")).unwrap();
+    }
+
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .args(["test", "--offline", "--quiet"])
+        .current_dir(&out)
+        // A separate target directory, the workspace one is locked by this test run
+        .env(
+            "CARGO_TARGET_DIR",
+            workspace_root().join("target/xtask-consumer"),
+        )
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&out);
+    assert!(
+        output.status.success(),
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_rejects_invalid_names() {
+    let out = std::env::temp_dir().join(format!("xtask-invalid-{}", std::process::id()));
+
+    let output = generate(&out, "CoinFlip", "settle_flip");
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "Invalid --name CoinFlip: expected a snake case identifier"
+    );
+    assert!(!out.exists());
+}